@@ -0,0 +1,49 @@
+using BenchmarkDotNet.Attributes;
+using HarfRust.Bindings;
+
+namespace HarfRust.Benchmarks;
+
+/// <summary>
+/// Shapes the short runs typical of PDFs (numbers, labels) with the native
+/// small-run fast path on and off.
+/// </summary>
+[MemoryDiagnoser]
+public class SmallRunBenchmarks
+{
+    private static readonly string[] Runs = ["12.50", "Page 3", "Total", "(1/4)", "2024", "-7", "ID:", "x"];
+
+    private HarfRustFont _font = null!;
+
+    [Params(true, false)]
+    public bool FastPath;
+
+    [GlobalSetup]
+    public void Setup()
+    {
+        HarfRustBackend.Current = NativeBackend.Instance;
+        NativeBackend.SmallRunFastPath = FastPath;
+        _font = new HarfRustFont(BenchmarkUtils.GetFontData());
+    }
+
+    [GlobalCleanup]
+    public void Cleanup()
+    {
+        _font.Dispose();
+        NativeBackend.SmallRunFastPath = true;
+    }
+
+    [Benchmark]
+    public int Native_ShapeShortRuns()
+    {
+        var glyphs = 0;
+        foreach (var run in Runs)
+        {
+            using var buffer = new HarfRustBuffer();
+            buffer.AddString(run);
+            buffer.GuessSegmentProperties();
+            using var result = _font.Shape(buffer);
+            glyphs += result.Length;
+        }
+        return glyphs;
+    }
+}
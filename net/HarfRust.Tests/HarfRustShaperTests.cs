@@ -128,4 +128,29 @@ public abstract class ShaperTestsBase<TFixture> : IClassFixture<TFixture> where
         CheckGlyph(8, primary);   // C
         CheckGlyph(9, fallback);  // 😀
     }
+
+    [Fact]
+    public void ShapeWithFallback_HexBoxes_ForCharactersNoFontHas()
+    {
+        HarfRustBackend.Current = Backend;
+
+        using var font = new HarfRustFont(Fixture.GetTestFontData(), Backend);
+
+        // A CJK ideograph and an Egyptian hieroglyph the test fonts lack
+        string text = "a\u4E00\U00013000b";
+        var notdef = HarfRustShaper.ShapeWithFallback(text, font, null);
+        Assert.Contains(notdef, g => g.GlyphId == 0);
+        Assert.DoesNotContain(notdef, g => g.IsHexBox);
+
+        var results = HarfRustShaper.ShapeWithFallback(text, font, null, missingGlyphs: MissingGlyphMode.HexBox);
+        var boxes = results.Where(g => g.IsHexBox).ToArray();
+        Assert.Equal(2, boxes.Length);
+        Assert.Equal(0x4E00, boxes[0].Codepoint);
+        Assert.Equal(1u, boxes[0].Cluster);
+        Assert.Equal(ShapedGlyph.HexBoxWidth(0x4E00, font.UnitsPerEm), boxes[0].XAdvance);
+        Assert.Equal(0x13000, boxes[1].Codepoint);
+        Assert.Equal(2u, boxes[1].Cluster);
+        Assert.True(boxes[1].XAdvance > boxes[0].XAdvance);
+        Assert.Equal(2, results.Count(g => !g.IsHexBox && g.GlyphId != 0));
+    }
 }
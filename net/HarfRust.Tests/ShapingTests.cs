@@ -393,4 +393,76 @@ public abstract class ShapingTestsBase<TFixture> : IClassFixture<TFixture> where
         session.Dispose();
         result.Dispose();
     }
+
+    [Fact]
+    public void Buffer_Context_SelectsJoiningForms()
+    {
+        var fontData = Fixture.GetTestFontData();
+        using var font = new HarfRustFont(fontData, Backend);
+
+        uint ShapeBeh(string preContext, string postContext)
+        {
+            using var buffer = new HarfRustBuffer(Backend);
+            buffer.AddString("\u0628");
+            buffer.SetContext(preContext, postContext);
+            buffer.GuessSegmentProperties();
+            using var result = font.Shape(buffer);
+            return result.GlyphInfos[0].GlyphId;
+        }
+
+        // A beh between two others takes its medial form; joiners stand in for them
+        var isolated = ShapeBeh("", "");
+        var medial = ShapeBeh("\u0628", "\u0628");
+        Assert.NotEqual(isolated, medial);
+        Assert.Equal(medial, ShapeBeh("\u200D", "\u200D"));
+    }
+
+    [Fact]
+    public void Font_HorizontalScale_CondensesAdvances()
+    {
+        var fontData = Fixture.GetTestFontData();
+        using var font = new HarfRustFont(fontData, Backend);
+        using var session = new HarfRustShapeSession(Backend);
+
+        int Width()
+        {
+            using var result = session.Shape(font, "Hello");
+            var width = 0;
+            foreach (var position in result.GlyphPositions)
+            {
+                width += position.XAdvance;
+            }
+            return width;
+        }
+
+        var normal = Width();
+        Assert.Equal(1f, font.HorizontalScale);
+        font.HorizontalScale = 0.5f;
+        Assert.InRange(Width(), normal / 2 - 5, normal / 2 + 5);
+        Assert.Throws<ArgumentOutOfRangeException>(() => font.HorizontalScale = 0);
+    }
+
+    [Fact]
+    public void Font_CharacterSpacing_AddsAdvanceAfterEachCluster()
+    {
+        var fontData = Fixture.GetTestFontData();
+        using var font = new HarfRustFont(fontData, Backend);
+        using var session = new HarfRustShapeSession(Backend);
+
+        int[] Advances()
+        {
+            using var result = session.Shape(font, "a b");
+            var advances = new int[result.Length];
+            for (int i = 0; i < advances.Length; i++)
+            {
+                advances[i] = result.GlyphPositions[i].XAdvance;
+            }
+            return advances;
+        }
+
+        var plain = Advances();
+        font.CharacterSpacing = 100;
+        Assert.Equal(100, font.CharacterSpacing);
+        Assert.Equal(plain.Select(advance => advance + 100), Advances());
+    }
 }
@@ -159,6 +159,70 @@ internal sealed class WasmBuffer : IBackendBuffer
         }
     }
 
+    public void SetContext(ReadOnlySpan<char> preContext, ReadOnlySpan<char> postContext)
+    {
+        ThrowIfDisposedOrConsumed();
+
+        var preBytes = checked(preContext.Length * sizeof(char));
+        var postBytes = checked(postContext.Length * sizeof(char));
+        var ptr = _context.Malloc(Math.Max(preBytes + postBytes, 1));
+        if (ptr == 0)
+        {
+            throw new OutOfMemoryException("Failed to allocate WASM memory.");
+        }
+
+        try
+        {
+            _context.WriteBytes(ptr, MemoryMarshal.AsBytes(preContext));
+            _context.WriteBytes(ptr + preBytes, MemoryMarshal.AsBytes(postContext));
+            var result = _context.BufferSetContext(_handle, ptr, preContext.Length, ptr + preBytes, postContext.Length);
+            if (result != 0)
+            {
+                throw new InvalidOperationException($"Failed to set buffer context (error code: {result})");
+            }
+        }
+        finally
+        {
+            _context.Free(ptr, Math.Max(preBytes + postBytes, 1));
+        }
+    }
+
+    public SoftHyphen SoftHyphen
+    {
+        get
+        {
+            ThrowIfDisposedOrConsumed();
+            return (SoftHyphen)_context.BufferGetSoftHyphen(_handle);
+        }
+        set
+        {
+            ThrowIfDisposedOrConsumed();
+            var result = _context.BufferSetSoftHyphen(_handle, (int)value);
+            if (result != 0)
+            {
+                throw new InvalidOperationException($"Failed to set the soft hyphen policy (error code: {result})");
+            }
+        }
+    }
+
+    public bool NormalizeSpaces
+    {
+        get
+        {
+            ThrowIfDisposedOrConsumed();
+            return _context.BufferGetNormalizeSpaces(_handle) == 1;
+        }
+        set
+        {
+            ThrowIfDisposedOrConsumed();
+            var result = _context.BufferSetNormalizeSpaces(_handle, value ? 1 : 0);
+            if (result != 0)
+            {
+                throw new InvalidOperationException($"Failed to set space normalization (error code: {result})");
+            }
+        }
+    }
+
     public void GuessSegmentProperties()
     {
         ThrowIfDisposedOrConsumed();
@@ -20,16 +20,27 @@ internal sealed class WasmContext : IDisposable
     private readonly Action<int, int> _bufferSetScript;
     private readonly Func<int, int> _bufferGetScript;
     private readonly Func<int, int, int> _bufferSetLanguage;
+    private readonly Func<int, int, int, int, int, int> _bufferSetContext;
+    private readonly Func<int, int, int> _bufferSetSoftHyphen;
+    private readonly Func<int, int> _bufferGetSoftHyphen;
+    private readonly Func<int, int, int> _bufferSetNormalizeSpaces;
+    private readonly Func<int, int> _bufferGetNormalizeSpaces;
     private readonly Action<int> _bufferGuessSegmentProperties;
     private readonly Func<int, int, int> _fontFromData;
     private readonly Func<int, int, int, int> _fontFromDataIndex;
     private readonly Func<int, int> _fontUnitsPerEm;
+    private readonly Func<int, int, int> _fontGetMetrics;
+    private readonly Func<int, float, int> _fontSetHorizontalScale;
+    private readonly Func<int, float> _fontGetHorizontalScale;
+    private readonly Func<int, int, int> _fontSetCharacterSpacing;
+    private readonly Func<int, int> _fontGetCharacterSpacing;
     private readonly Action<int> _fontFree;
     private readonly Func<int, int, int> _shape;
     private readonly Func<int, int, int, int, int, int, int> _shapeFull;
     private readonly Func<int, int> _glyphBufferLen;
     private readonly Func<int, int> _glyphBufferGetInfos;
     private readonly Func<int, int> _glyphBufferGetPositions;
+    private readonly Func<int, int> _glyphBufferGetFlags;
     private readonly Func<int, int> _glyphBufferIntoBuffer;
     private readonly Action<int> _glyphBufferFree;
     private readonly Func<int, int> _malloc;
@@ -67,6 +78,16 @@ internal sealed class WasmContext : IDisposable
             ?? throw new InvalidOperationException("Missing export: harfrust_buffer_get_script");
         _bufferSetLanguage = _instance.GetFunction<int, int, int>("harfrust_buffer_set_language")
             ?? throw new InvalidOperationException("Missing export: harfrust_buffer_set_language");
+        _bufferSetContext = _instance.GetFunction<int, int, int, int, int, int>("harfrust_buffer_set_context")
+            ?? throw new InvalidOperationException("Missing export: harfrust_buffer_set_context");
+        _bufferSetSoftHyphen = _instance.GetFunction<int, int, int>("harfrust_buffer_set_soft_hyphen")
+            ?? throw new InvalidOperationException("Missing export: harfrust_buffer_set_soft_hyphen");
+        _bufferGetSoftHyphen = _instance.GetFunction<int, int>("harfrust_buffer_get_soft_hyphen")
+            ?? throw new InvalidOperationException("Missing export: harfrust_buffer_get_soft_hyphen");
+        _bufferSetNormalizeSpaces = _instance.GetFunction<int, int, int>("harfrust_buffer_set_normalize_spaces")
+            ?? throw new InvalidOperationException("Missing export: harfrust_buffer_set_normalize_spaces");
+        _bufferGetNormalizeSpaces = _instance.GetFunction<int, int>("harfrust_buffer_get_normalize_spaces")
+            ?? throw new InvalidOperationException("Missing export: harfrust_buffer_get_normalize_spaces");
         _bufferGuessSegmentProperties = _instance.GetAction<int>("harfrust_buffer_guess_segment_properties")
             ?? throw new InvalidOperationException("Missing export: harfrust_buffer_guess_segment_properties");
         _fontFromData = _instance.GetFunction<int, int, int>("harfrust_font_from_data")
@@ -75,6 +96,16 @@ internal sealed class WasmContext : IDisposable
             ?? throw new InvalidOperationException("Missing export: harfrust_font_from_data_index");
         _fontUnitsPerEm = _instance.GetFunction<int, int>("harfrust_font_units_per_em")
             ?? throw new InvalidOperationException("Missing export: harfrust_font_units_per_em");
+        _fontGetMetrics = _instance.GetFunction<int, int, int>("harfrust_font_get_metrics")
+            ?? throw new InvalidOperationException("Missing export: harfrust_font_get_metrics");
+        _fontSetHorizontalScale = _instance.GetFunction<int, float, int>("harfrust_font_set_horizontal_scale")
+            ?? throw new InvalidOperationException("Missing export: harfrust_font_set_horizontal_scale");
+        _fontGetHorizontalScale = _instance.GetFunction<int, float>("harfrust_font_get_horizontal_scale")
+            ?? throw new InvalidOperationException("Missing export: harfrust_font_get_horizontal_scale");
+        _fontSetCharacterSpacing = _instance.GetFunction<int, int, int>("harfrust_font_set_character_spacing")
+            ?? throw new InvalidOperationException("Missing export: harfrust_font_set_character_spacing");
+        _fontGetCharacterSpacing = _instance.GetFunction<int, int>("harfrust_font_get_character_spacing")
+            ?? throw new InvalidOperationException("Missing export: harfrust_font_get_character_spacing");
         _fontFree = _instance.GetAction<int>("harfrust_font_free")
             ?? throw new InvalidOperationException("Missing export: harfrust_font_free");
         _shape = _instance.GetFunction<int, int, int>("harfrust_shape")
@@ -87,6 +118,8 @@ internal sealed class WasmContext : IDisposable
             ?? throw new InvalidOperationException("Missing export: harfrust_glyph_buffer_get_infos");
         _glyphBufferGetPositions = _instance.GetFunction<int, int>("harfrust_glyph_buffer_get_positions")
             ?? throw new InvalidOperationException("Missing export: harfrust_glyph_buffer_get_positions");
+        _glyphBufferGetFlags = _instance.GetFunction<int, int>("harfrust_glyph_buffer_get_flags")
+            ?? throw new InvalidOperationException("Missing export: harfrust_glyph_buffer_get_flags");
         _glyphBufferIntoBuffer = _instance.GetFunction<int, int>("harfrust_glyph_buffer_into_buffer")
             ?? throw new InvalidOperationException("Missing export: harfrust_glyph_buffer_into_buffer");
         _glyphBufferFree = _instance.GetAction<int>("harfrust_glyph_buffer_free")
@@ -112,12 +145,23 @@ internal sealed class WasmContext : IDisposable
     public void BufferSetScript(int buffer, int script) => _bufferSetScript(buffer, script);
     public int BufferGetScript(int buffer) => _bufferGetScript(buffer);
     public int BufferSetLanguage(int buffer, int langPtr) => _bufferSetLanguage(buffer, langPtr);
+    public int BufferSetContext(int buffer, int prePtr, int preLen, int postPtr, int postLen)
+        => _bufferSetContext(buffer, prePtr, preLen, postPtr, postLen);
+    public int BufferSetSoftHyphen(int buffer, int mode) => _bufferSetSoftHyphen(buffer, mode);
+    public int BufferGetSoftHyphen(int buffer) => _bufferGetSoftHyphen(buffer);
+    public int BufferSetNormalizeSpaces(int buffer, int enabled) => _bufferSetNormalizeSpaces(buffer, enabled);
+    public int BufferGetNormalizeSpaces(int buffer) => _bufferGetNormalizeSpaces(buffer);
     public void BufferGuessSegmentProperties(int buffer) => _bufferGuessSegmentProperties(buffer);
 
     // Font operations
     public int FontFromData(int dataPtr, int len) => _fontFromData(dataPtr, len);
     public int FontFromDataIndex(int dataPtr, int len, int index) => _fontFromDataIndex(dataPtr, len, index);
     public int FontUnitsPerEm(int font) => _fontUnitsPerEm(font);
+    public int FontGetMetrics(int font, int outPtr) => _fontGetMetrics(font, outPtr);
+    public int FontSetHorizontalScale(int font, float scale) => _fontSetHorizontalScale(font, scale);
+    public float FontGetHorizontalScale(int font) => _fontGetHorizontalScale(font);
+    public int FontSetCharacterSpacing(int font, int spacing) => _fontSetCharacterSpacing(font, spacing);
+    public int FontGetCharacterSpacing(int font) => _fontGetCharacterSpacing(font);
     public void FontFree(int font) => _fontFree(font);
 
     // Shape operations
@@ -129,6 +173,7 @@ internal sealed class WasmContext : IDisposable
     public int GlyphBufferLen(int buffer) => _glyphBufferLen(buffer);
     public int GlyphBufferGetInfos(int buffer) => _glyphBufferGetInfos(buffer);
     public int GlyphBufferGetPositions(int buffer) => _glyphBufferGetPositions(buffer);
+    public int GlyphBufferGetFlags(int buffer) => _glyphBufferGetFlags(buffer);
     public int GlyphBufferIntoBuffer(int buffer) => _glyphBufferIntoBuffer(buffer);
     public void GlyphBufferFree(int buffer) => _glyphBufferFree(buffer);
 
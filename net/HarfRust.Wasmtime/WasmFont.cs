@@ -52,6 +52,77 @@ internal sealed class WasmFont : IBackendFont
         }
     }
 
+    public FontMetrics Metrics
+    {
+        get
+        {
+            ThrowIfDisposed();
+            const int size = 6 * sizeof(int);
+            var ptr = _context.Malloc(size);
+            if (ptr == 0)
+            {
+                throw new OutOfMemoryException("Failed to allocate WASM memory.");
+            }
+
+            try
+            {
+                var result = _context.FontGetMetrics(_handle, ptr);
+                if (result != 0)
+                {
+                    throw new InvalidOperationException($"Failed to read font metrics (error code: {result})");
+                }
+                var bytes = _context.ReadBytes(ptr, size);
+                return new FontMetrics(
+                    BinaryPrimitives.ReadInt32LittleEndian(bytes),
+                    BinaryPrimitives.ReadInt32LittleEndian(bytes.Slice(4)),
+                    BinaryPrimitives.ReadInt32LittleEndian(bytes.Slice(8)),
+                    BinaryPrimitives.ReadInt32LittleEndian(bytes.Slice(12)),
+                    BinaryPrimitives.ReadInt32LittleEndian(bytes.Slice(16)),
+                    BinaryPrimitives.ReadInt32LittleEndian(bytes.Slice(20)));
+            }
+            finally
+            {
+                _context.Free(ptr, size);
+            }
+        }
+    }
+
+    public float HorizontalScale
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return _context.FontGetHorizontalScale(_handle);
+        }
+        set
+        {
+            ThrowIfDisposed();
+            var result = _context.FontSetHorizontalScale(_handle, value);
+            if (result != 0)
+            {
+                throw new InvalidOperationException($"Failed to set the horizontal scale (error code: {result})");
+            }
+        }
+    }
+
+    public int CharacterSpacing
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return _context.FontGetCharacterSpacing(_handle);
+        }
+        set
+        {
+            ThrowIfDisposed();
+            var result = _context.FontSetCharacterSpacing(_handle, value);
+            if (result != 0)
+            {
+                throw new InvalidOperationException($"Failed to set the character spacing (error code: {result})");
+            }
+        }
+    }
+
     public IBackendGlyphBuffer Shape(IBackendBuffer buffer)
     {
         ThrowIfDisposed();
@@ -50,6 +50,15 @@ internal sealed class WasmGlyphBuffer : IBackendGlyphBuffer
         }
     }
 
+    public ShapeResultFlags Flags
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return (ShapeResultFlags)Math.Max(_context.GlyphBufferGetFlags(_handle), 0);
+        }
+    }
+
     private void EnsureCached()
     {
         if (_glyphInfos != null)
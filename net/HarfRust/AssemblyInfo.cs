@@ -0,0 +1,6 @@
+using System.Runtime.CompilerServices;
+
+// The generated bindings use [LibraryImport] with blittable types only, so no
+// P/Invoke needs runtime marshalling. Disabling it keeps the library usable
+// under NativeAOT and trimming.
+[assembly: DisableRuntimeMarshalling]
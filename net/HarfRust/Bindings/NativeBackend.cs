@@ -1,3 +1,4 @@
+using System.Runtime.InteropServices;
 using HarfRust.Bindings;
 
 namespace HarfRust.Bindings;
@@ -7,11 +8,100 @@ namespace HarfRust.Bindings;
 /// </summary>
 public sealed class NativeBackend : IHarfRustBackend
 {
+    /// <summary>
+    /// Major and minor version of the native library these bindings were generated from. A native
+    /// library with another major or minor version has an incompatible ABI.
+    /// </summary>
+    private static readonly Version BindingsVersion = new(0, 1);
+
     /// <summary>
     /// Singleton instance of the Native backend.
     /// </summary>
+    /// <exception cref="TypeInitializationException">The loaded native library does not match the bindings.</exception>
     public static NativeBackend Instance { get; } = new();
 
+    private NativeBackend()
+    {
+        Version native;
+        try
+        {
+            native = NativeVersion;
+        }
+        catch (EntryPointNotFoundException e)
+        {
+            throw new InvalidOperationException(
+                $"The native harfrust_ffi library predates version reporting; version {BindingsVersion} is required.", e);
+        }
+
+        if (native.Major != BindingsVersion.Major || native.Minor != BindingsVersion.Minor)
+        {
+            throw new InvalidOperationException(
+                $"The native harfrust_ffi library is version {native}, but the bindings require version {BindingsVersion}.x.");
+        }
+    }
+
+    /// <summary>
+    /// Version of the loaded native library.
+    /// </summary>
+    public static unsafe Version NativeVersion
+    {
+        get
+        {
+            uint major, minor, patch;
+            NativeMethods.harfrust_version(&major, &minor, &patch);
+            return new Version((int)major, (int)minor, (int)patch);
+        }
+    }
+
+    /// <summary>
+    /// Version of the harfrust shaping engine the native library was built with, e.g. "0.5.2".
+    /// </summary>
+    public static unsafe string EngineVersion =>
+        Marshal.PtrToStringUTF8((IntPtr)NativeMethods.harfrust_engine_version_string()) ?? string.Empty;
+
+    /// <summary>
+    /// Enables or disables the small-run fast path, which shapes short Latin
+    /// runs that no layout lookup affects without the full pipeline. Enabled
+    /// by default; results are the same either way.
+    /// </summary>
+    public static bool SmallRunFastPath
+    {
+        set => NativeMethods.harfrust_set_small_run_fast_path(value ? 1 : 0);
+    }
+
+    /// <summary>
+    /// Version of the Unicode Character Database the native shaping engine
+    /// was built from.
+    /// </summary>
+    public static unsafe Version UnicodeVersion
+    {
+        get
+        {
+            uint major, minor, micro;
+            NativeMethods.harfrust_unicode_version(&major, &minor, &micro);
+            return new Version((int)major, (int)minor, (int)micro);
+        }
+    }
+
+    /// <summary>
+    /// OpenType script tags of the scripts the native library shapes with a
+    /// dedicated (complex) shaper, such as Arabic, Indic and the Universal
+    /// Shaping Engine scripts.
+    /// </summary>
+    public static unsafe IReadOnlyList<uint> SupportedScripts
+    {
+        get
+        {
+            int count = NativeMethods.harfrust_supported_scripts(null, 0);
+            var tags = new uint[Math.Max(count, 0)];
+            fixed (uint* tagsPtr = tags)
+            {
+                NativeMethods.harfrust_supported_scripts(tagsPtr, (uint)tags.Length);
+            }
+            return tags;
+        }
+    }
+
     /// <inheritdoc />
     public IBackendBuffer CreateBuffer() => new NativeBuffer();
 
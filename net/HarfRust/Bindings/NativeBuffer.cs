@@ -55,9 +55,9 @@ internal sealed unsafe class NativeBuffer : IBackendBuffer
         fixed (char* ptr = text)
         {
             var result = NativeMethods.harfrust_buffer_add_utf16(_handle, (ushort*)ptr, text.Length);
-            if (result != 0)
+            if (result != HarfRustStatus.Ok)
             {
-                throw new InvalidOperationException($"Failed to add string to buffer (error code: {result})");
+                throw NativeError.ToException(result, "Failed to add string to buffer", nameof(text));
             }
         }
     }
@@ -120,9 +120,9 @@ internal sealed unsafe class NativeBuffer : IBackendBuffer
             fixed (byte* ptr = bytes)
             {
                 var result = NativeMethods.harfrust_buffer_set_language(_handle, ptr);
-                if (result != 0)
+                if (result != HarfRustStatus.Ok)
                 {
-                    throw new ArgumentException($"Invalid language tag: {language.ToString()} (error code: {result})", nameof(language));
+                    throw NativeError.ToException(result, $"Invalid language tag: {language.ToString()}", nameof(language));
                 }
             }
         }
@@ -135,6 +135,58 @@ internal sealed unsafe class NativeBuffer : IBackendBuffer
         }
     }
 
+    public void SetContext(ReadOnlySpan<char> preContext, ReadOnlySpan<char> postContext)
+    {
+        ThrowIfDisposedOrConsumed();
+
+        fixed (char* prePtr = preContext)
+        fixed (char* postPtr = postContext)
+        {
+            var result = NativeMethods.harfrust_buffer_set_context(
+                _handle, (ushort*)prePtr, preContext.Length, (ushort*)postPtr, postContext.Length);
+            if (result != HarfRustStatus.Ok)
+            {
+                throw NativeError.ToException(result, "Failed to set buffer context", nameof(preContext));
+            }
+        }
+    }
+
+    public SoftHyphen SoftHyphen
+    {
+        get
+        {
+            ThrowIfDisposedOrConsumed();
+            return (SoftHyphen)NativeMethods.harfrust_buffer_get_soft_hyphen(_handle);
+        }
+        set
+        {
+            ThrowIfDisposedOrConsumed();
+            var result = NativeMethods.harfrust_buffer_set_soft_hyphen(_handle, (Bindings.HarfRustSoftHyphen)value);
+            if (result != HarfRustStatus.Ok)
+            {
+                throw NativeError.ToException(result, "Failed to set the soft hyphen policy", nameof(value));
+            }
+        }
+    }
+
+    public bool NormalizeSpaces
+    {
+        get
+        {
+            ThrowIfDisposedOrConsumed();
+            return NativeMethods.harfrust_buffer_get_normalize_spaces(_handle) == 1;
+        }
+        set
+        {
+            ThrowIfDisposedOrConsumed();
+            var result = NativeMethods.harfrust_buffer_set_normalize_spaces(_handle, value ? 1 : 0);
+            if (result != HarfRustStatus.Ok)
+            {
+                throw NativeError.ToException(result, "Failed to set space normalization", nameof(value));
+            }
+        }
+    }
+
     public void GuessSegmentProperties()
     {
         ThrowIfDisposedOrConsumed();
@@ -0,0 +1,49 @@
+using System.Runtime.InteropServices;
+
+namespace HarfRust.Bindings;
+
+/// <summary>
+/// Reads the native last-error record to build exception messages.
+/// </summary>
+internal static unsafe class NativeError
+{
+    /// <summary>
+    /// Returns <paramref name="summary"/> followed by the message of the last native failure
+    /// on this thread, if one was recorded, and clears the record.
+    /// </summary>
+    public static string Describe(string summary)
+    {
+        var message = NativeMethods.harfrust_last_error_message();
+        if (message == null)
+        {
+            return summary;
+        }
+
+        try
+        {
+            return $"{summary}: {Marshal.PtrToStringUTF8((IntPtr)message)}";
+        }
+        finally
+        {
+            NativeMethods.harfrust_string_free(message);
+            NativeMethods.harfrust_clear_last_error();
+        }
+    }
+
+    /// <summary>
+    /// Creates the exception matching a failure status, described as by <see cref="Describe"/>.
+    /// </summary>
+    public static Exception ToException(HarfRustStatus status, string summary, string? paramName = null)
+    {
+        var message = Describe($"{summary} ({status})");
+        return status switch
+        {
+            HarfRustStatus.NullArgument => new ArgumentNullException(paramName, message),
+            HarfRustStatus.OutOfRange => new ArgumentOutOfRangeException(paramName, message),
+            HarfRustStatus.InvalidArgument or HarfRustStatus.InvalidUtf8 or HarfRustStatus.InvalidLanguage
+                or HarfRustStatus.InvalidFont => new ArgumentException(message, paramName),
+            HarfRustStatus.Io => new IOException(message),
+            _ => new InvalidOperationException(message),
+        };
+    }
+}
@@ -26,7 +26,7 @@ internal sealed unsafe class NativeFont : IBackendFont
 
         if (_handle == null)
         {
-            throw new ArgumentException("Invalid font data.", nameof(data));
+            throw new ArgumentException(NativeError.Describe("Invalid font data"), nameof(data));
         }
     }
 
@@ -45,7 +45,7 @@ internal sealed unsafe class NativeFont : IBackendFont
 
         if (_handle == null)
         {
-            throw new ArgumentException("Invalid font data or index out of range.", nameof(data));
+            throw new ArgumentException(NativeError.Describe("Invalid font data or index out of range"), nameof(data));
         }
     }
 
@@ -58,6 +58,58 @@ internal sealed unsafe class NativeFont : IBackendFont
         }
     }
 
+    public FontMetrics Metrics
+    {
+        get
+        {
+            ThrowIfDisposed();
+            Bindings.HarfRustFontMetrics metrics;
+            var result = NativeMethods.harfrust_font_get_metrics(_handle, &metrics);
+            if (result != HarfRustStatus.Ok)
+            {
+                throw NativeError.ToException(result, "Failed to read font metrics");
+            }
+            return new FontMetrics(
+                metrics.units_per_em, metrics.ascent, metrics.descent, metrics.line_gap, metrics.x_height, metrics.cap_height);
+        }
+    }
+
+    public float HorizontalScale
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return NativeMethods.harfrust_font_get_horizontal_scale(_handle);
+        }
+        set
+        {
+            ThrowIfDisposed();
+            var result = NativeMethods.harfrust_font_set_horizontal_scale(_handle, value);
+            if (result != HarfRustStatus.Ok)
+            {
+                throw NativeError.ToException(result, "Failed to set the horizontal scale", nameof(value));
+            }
+        }
+    }
+
+    public int CharacterSpacing
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return NativeMethods.harfrust_font_get_character_spacing(_handle);
+        }
+        set
+        {
+            ThrowIfDisposed();
+            var result = NativeMethods.harfrust_font_set_character_spacing(_handle, value);
+            if (result != HarfRustStatus.Ok)
+            {
+                throw NativeError.ToException(result, "Failed to set the character spacing", nameof(value));
+            }
+        }
+    }
+
     public IBackendGlyphBuffer Shape(IBackendBuffer buffer)
     {
         ArgumentNullException.ThrowIfNull(buffer);
@@ -67,7 +119,7 @@ internal sealed unsafe class NativeFont : IBackendFont
         var glyphBuffer = NativeMethods.harfrust_shape(_handle, (Bindings.HarfRustBuffer*)bufferHandle);
         if (glyphBuffer == null)
         {
-            throw new InvalidOperationException("Shaping failed.");
+            throw new InvalidOperationException(NativeError.Describe("Shaping failed"));
         }
 
         return new NativeGlyphBuffer(glyphBuffer);
@@ -146,7 +198,7 @@ internal sealed unsafe class NativeFont : IBackendFont
 
             if (glyphBuffer == null)
             {
-                throw new InvalidOperationException("Shaping failed.");
+                throw new InvalidOperationException(NativeError.Describe("Shaping failed"));
             }
 
             return new NativeGlyphBuffer(glyphBuffer);
@@ -55,6 +55,15 @@ internal sealed unsafe class NativeGlyphBuffer : IBackendGlyphBuffer
         }
     }
 
+    public ShapeResultFlags Flags
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return (ShapeResultFlags)Math.Max(NativeMethods.harfrust_glyph_buffer_get_flags(_handle), 0);
+        }
+    }
+
     public IBackendBuffer IntoBuffer()
     {
         ThrowIfDisposed();
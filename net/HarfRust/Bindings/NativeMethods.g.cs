@@ -6,6 +6,7 @@
 #pragma warning disable CS8981
 using System;
 using System.Runtime.InteropServices;
+using System.Runtime.CompilerServices;
 
 
 namespace HarfRust.Bindings
@@ -21,297 +22,3475 @@ namespace HarfRust.Bindings
         /// <summary>
         ///  Creates a new empty buffer for text shaping.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_new", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustBuffer* harfrust_buffer_new();
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_new")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBuffer* harfrust_buffer_new();
 
         /// <summary>
         ///  Adds a UTF-8 string to the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `text` must be null or a
+        ///  NUL-terminated string.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_add_str", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_buffer_add_str(HarfRustBuffer* buffer, byte* text);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_add_str")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_add_str(HarfRustBuffer* buffer, byte* text);
+
+        /// <summary>
+        ///  Adds `byte_len` bytes of UTF-8 text to the buffer. Unlike
+        ///  `harfrust_buffer_add_str` the text needs no NUL terminator, and embedded
+        ///  NULs are added like any other character. Clusters are byte offsets.
+        ///  Returns `InvalidUtf8` if the text is not valid UTF-8, in which case
+        ///  nothing is added.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `text` must be null or point
+        ///  to `byte_len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_add_utf8")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_add_utf8(HarfRustBuffer* buffer, byte* text, int byte_len);
 
         /// <summary>
         ///  Adds a UTF-16 string to the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `text` must be null or point
+        ///  to `len` UTF-16 code units.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_add_utf16")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_add_utf16(HarfRustBuffer* buffer, ushort* text, int len);
+
+        /// <summary>
+        ///  Same as `harfrust_buffer_add_utf16` with a 64-bit length. Returns
+        ///  `OutOfRange` if the text is too long for its offsets to fit the 32-bit
+        ///  cluster values.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `text` must be null or point
+        ///  to `len` UTF-16 code units.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_add_utf16_64")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_add_utf16_64(HarfRustBuffer* buffer, ushort* text, ulong len);
+
+        /// <summary>
+        ///  Adds a UTF-16 string to the buffer after applying a case transform.
+        ///  Characters produced by the transform keep the cluster (UTF-16 offset) of
+        ///  the original character they came from, so shaped glyphs map back to the
+        ///  untransformed text, e.g. both glyphs of "SS" from "ß" share its cluster.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `text` must be null or point
+        ///  to `len` UTF-16 code units.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_add_utf16", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_buffer_add_utf16(HarfRustBuffer* buffer, ushort* text, int len);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_add_utf16_transformed")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_add_utf16_transformed(HarfRustBuffer* buffer, ushort* text, int len, HarfRustCaseTransform transform);
 
         /// <summary>
         ///  Returns the number of characters currently in the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_len", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_buffer_len(HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_len")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_buffer_len(HarfRustBuffer* buffer);
 
         /// <summary>
         ///  Clears all content from the buffer, preparing it for reuse.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_clear")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_buffer_clear(HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Saves the buffer's text, context and segment properties (direction,
+        ///  script, language, flags and cluster level). Since shaping consumes the buffer, a
+        ///  snapshot taken before a trial shape can be restored into a fresh buffer if
+        ///  the trial is rejected. Returns null if the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_save_state")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBufferState* harfrust_buffer_save_state(HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Replaces the buffer's contents, context and segment properties with a
+        ///  saved state.
+        ///  The state is not consumed and can be restored any number of times.
+        ///  Returns `NullArgument` if the buffer or state is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` and `state` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_restore_state")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_restore_state(HarfRustBuffer* buffer, HarfRustBufferState* state);
+
+        /// <summary>
+        ///  Frees a state previously created by `harfrust_buffer_save_state`.
+        ///
+        ///  # Safety
+        ///
+        ///  `state` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_clear", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_buffer_clear(HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_state_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_buffer_state_free(HarfRustBufferState* state);
 
         /// <summary>
         ///  Frees a buffer previously created by `harfrust_buffer_new`.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_buffer_free(HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_buffer_free(HarfRustBuffer* buffer);
 
         /// <summary>
         ///  Sets the text direction of the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_set_direction", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_buffer_set_direction(HarfRustBuffer* buffer, HarfRustDirection direction);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_set_direction")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_buffer_set_direction(HarfRustBuffer* buffer, HarfRustDirection direction);
 
         /// <summary>
         ///  Gets the text direction of the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_get_direction", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustDirection harfrust_buffer_get_direction(HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_get_direction")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustDirection harfrust_buffer_get_direction(HarfRustBuffer* buffer);
 
         /// <summary>
         ///  Sets the script of the buffer using an ISO 15924 tag (4 bytes as u32).
         ///  Example: "Latn" = 0x4C61746E
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_set_script", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_buffer_set_script(HarfRustBuffer* buffer, uint script_tag);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_set_script")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_buffer_set_script(HarfRustBuffer* buffer, uint script_tag);
 
         /// <summary>
         ///  Gets the script of the buffer as an ISO 15924 tag (4 bytes as u32).
         ///  Returns 0 if no script is set.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_get_script", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern uint harfrust_buffer_get_script(HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_get_script")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial uint harfrust_buffer_get_script(HarfRustBuffer* buffer);
 
         /// <summary>
         ///  Sets the language of the buffer from a BCP 47 language tag string.
         ///  Example: "en", "en-US", "zh-Hans"
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `language` must be null or a
+        ///  NUL-terminated string.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_set_language")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_set_language(HarfRustBuffer* buffer, byte* language);
+
+        /// <summary>
+        ///  Sets how the buffer shapes soft hyphens (U+00AD), for text added before or
+        ///  after the call; see `HarfRustSoftHyphen`. Soft hyphens shown as a hyphen
+        ///  keep their own cluster, so the glyph still maps back to the soft hyphen.
+        ///  The setting survives `harfrust_buffer_clear`. Returns `NullArgument` if
+        ///  the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_set_soft_hyphen")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_set_soft_hyphen(HarfRustBuffer* buffer, HarfRustSoftHyphen mode);
+
+        /// <summary>
+        ///  Returns how the buffer shapes soft hyphens, or `Hidden` if the buffer is
+        ///  null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_get_soft_hyphen")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustSoftHyphen harfrust_buffer_get_soft_hyphen(HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Enables (non-zero) or disables (0, the default) shaping no-break, fixed
+        ///  width and narrow spaces (U+00A0, U+2000 to U+200A, U+202F and U+205F) as
+        ///  an ordinary space, with its glyph and advance, when the font has no glyph
+        ///  for them, as is common in subset fonts embedded in PDFs. Otherwise such
+        ///  spaces are shaped with the space glyph at a width derived from the em, or
+        ///  as .notdef without layout tables. Applies to the shape call consuming the
+        ///  buffer, and to the copies `harfrust_shape_copy` and `harfrust_shape_into`
+        ///  shape; results report it in `harfrust_glyph_buffer_get_flags`. The
+        ///  setting survives `harfrust_buffer_clear`. Returns `NullArgument` if the
+        ///  buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_set_normalize_spaces")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_set_normalize_spaces(HarfRustBuffer* buffer, int enabled);
+
+        /// <summary>
+        ///  Returns 1 if the buffer normalizes missing spaces, 0 if not, or a negative
+        ///  status if the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_get_normalize_spaces")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_buffer_get_normalize_spaces(HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Sets the UTF-16 text around the buffer's: `pre_context` comes before it
+        ///  and `post_context` after it. Context is not shaped but is seen by shaping
+        ///  decisions that look past the buffer's ends, such as Arabic joining, so a
+        ///  run split from a word (e.g. at a font change) keeps the forms it has in
+        ///  the word. Only the nearest 5 characters on each side are used. Null with
+        ///  a length of 0 sets no context; clearing the buffer removes it. Returns
+        ///  `NullArgument` if the buffer, or a text with a non-zero length, is null,
+        ///  or `InvalidArgument` if a length is negative.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle; `pre_context` must be null or
+        ///  point to `pre_len` UTF-16 code units; and `post_context` must be null or
+        ///  point to `post_len` UTF-16 code units.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_set_language", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_buffer_set_language(HarfRustBuffer* buffer, byte* language);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_set_context")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_buffer_set_context(HarfRustBuffer* buffer, ushort* pre_context, int pre_len, ushort* post_context, int post_len);
 
         /// <summary>
         ///  Guesses and sets the segment properties (direction, script, language)
         ///  based on the buffer contents.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_buffer_guess_segment_properties")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_buffer_guess_segment_properties(HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Detects the base direction of a paragraph of UTF-16 text from its first
+        ///  strong character, skipping isolated (LRI/RLI/FSI...PDI) content.
+        ///  Returns `Invalid` if the text has no strong characters; passing that to
+        ///  `harfrust_buffer_set_direction` keeps automatic direction guessing at
+        ///  shape time.
+        ///
+        ///  # Safety
+        ///
+        ///  `text` must be null or point to `len` UTF-16 code units.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_buffer_guess_segment_properties", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_buffer_guess_segment_properties(HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_text_detect_base_direction")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustDirection harfrust_text_detect_base_direction(ushort* text, int len);
 
         /// <summary>
-        ///  Creates a font from raw font data (TTF/OTF bytes).
+        ///  Creates a font from raw font data (TTF/OTF bytes, or WOFF with the `woff`
+        ///  feature). The data is copied. Malformed fonts (e.g. embedded in PDFs with
+        ///  a bad sfnt version, truncated tables or a directory that overruns the
+        ///  data) are repaired as `harfrust_font_repair` does when they would
+        ///  otherwise fail to load or lack core tables. Fonts created from identical
+        ///  data share a single copy of it.
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_font_from_data", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustFont* harfrust_font_from_data(byte* data, int len);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_data")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_data(byte* data, int len);
+
+        /// <summary>
+        ///  Same as `harfrust_font_from_data` with a 64-bit length, for data of 2 GB
+        ///  or more. Returns null if the data cannot be addressed or copied on this
+        ///  platform, or is larger than the 4 GB a font file can describe.
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_data64")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_data64(byte* data, ulong len);
 
         /// <summary>
         ///  Creates a font from raw font data at a specific index (for font collections).
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_data_index")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_data_index(byte* data, int len, uint index);
+
+        /// <summary>
+        ///  Creates a font from raw font data at a specific index, choosing between
+        ///  lazy, eager and tolerant parsing. In eager mode the core tables are
+        ///  validated and caches are warmed before returning, and null is returned if
+        ///  validation fails. In tolerant mode recoverable corruption is repaired or
+        ///  skipped and reported as warnings through the log callback (see
+        ///  `harfrust_set_log_callback`).
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_data_with_mode")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_data_with_mode(byte* data, int len, uint index, HarfRustParseMode mode);
+
+        /// <summary>
+        ///  Creates a font that reads `data` in place instead of copying it. The
+        ///  memory must stay valid and unmoved until `release_callback` is invoked
+        ///  with `user_data`, which happens when the font is freed, or before this
+        ///  function returns if creation fails. The callback may be null.
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes that stay valid and unmoved
+        ///  until `release_callback` is invoked, and `release_callback` must be null
+        ///  or safe to call with `user_data` on any thread.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_data_borrowed")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_data_borrowed(byte* data, int len, delegate* unmanaged[Cdecl]<void*, void> release_callback, void* user_data);
+
+        /// <summary>
+        ///  Creates a font from a bare CFF font program, such as a PDF FontFile3
+        ///  stream (Type1C or CIDFontType0C), by wrapping it in a minimal OpenType
+        ///  font. Advances come from the charstrings at 1000 units per em. Glyphs are
+        ///  mapped to Unicode through "uniXXXX"/"uXXXX" and ASCII glyph names only;
+        ///  CID-keyed fonts have no Unicode mapping and must be addressed by glyph ID.
+        ///  The data is copied. Returns null if the data is not a readable CFF font.
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_cff")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_cff(byte* data, int len);
+
+        /// <summary>
+        ///  Repairs common malformations of a single font file, as found in fonts
+        ///  embedded in PDFs: an unrecognized sfnt version, a table count larger than
+        ///  the directory, tables running past the end of the data, unsorted or
+        ///  duplicate table records, a bad `head` magic number and stale checksums.
+        ///  Returns the rebuilt font, or null if the data is null, a collection, or
+        ///  has no readable table directory. Free the result with `harfrust_blob_free`.
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_repair")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBlob* harfrust_font_repair(byte* data, int len);
+
+        /// <summary>
+        ///  Creates a font from face `face_index` of the font file at `path` (UTF-8),
+        ///  memory-mapping the file instead of reading it into memory. The file must
+        ///  not be modified while the font is alive. Returns null if the file cannot
+        ///  be opened or the face cannot be parsed.
+        ///
+        ///  # Safety
+        ///
+        ///  `path` must be null or a NUL-terminated string.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_from_file")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_from_file(byte* path, uint face_index);
+
+        /// <summary>
+        ///  Returns the number of faces in font data: the face count of a TrueType
+        ///  collection (.ttc/.otc), 1 for a single font, or 0 if the data is null or
+        ///  not a font. Valid indices for `harfrust_font_from_data_index` are
+        ///  0 to count - 1.
+        ///
+        ///  # Safety
+        ///
+        ///  `data` must be null or point to `len` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_collection_count")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_collection_count(byte* data, int len);
+
+        /// <summary>
+        ///  Returns the index of the face the font was created from (0 for fonts that
+        ///  are not part of a collection), or -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_index")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_get_index(HarfRustFont* font);
+
+        /// <summary>
+        ///  Returns a 64-bit FNV-1a hash of the font file data (after WOFF decoding;
+        ///  the whole file for a face of a collection), or 0 if the font is null.
+        ///  Fonts created from identical bytes share one copy of the data and report
+        ///  the same hash, which callers can use to deduplicate embedded fonts.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_data_hash")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial ulong harfrust_font_data_hash(HarfRustFont* font);
+
+        /// <summary>
+        ///  Writes a key identifying a glyph as rendered from this font at the given
+        ///  variation settings and pixels-per-em to `out`. The key combines the font
+        ///  data hash, face index, glyph id, normalized axis coordinates and size, so
+        ///  it is stable across processes and identical for settings that resolve to
+        ///  the same instance (omitted axes and explicit defaults, out-of-range values
+        ///  and their clamped limits). Returns `NullArgument` if the font or `out` is
+        ///  null or `variations` is null with a non-zero count, `OutOfRange` if `ppem`
+        ///  is negative or not finite.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `variations` must be null or point
+        ///  to `num_variations` variations; and `out` must be null or point to a
+        ///  writable `HarfRustCacheKey`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_cache_key")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_glyph_cache_key(HarfRustFont* font, uint glyph_id, HarfRustVariation* variations, uint num_variations, float ppem, HarfRustCacheKey* @out);
+
+        /// <summary>
+        ///  Returns 1 if the font's GSUB, GPOS or GDEF table exists but cannot be
+        ///  read, so every shaping function shapes it as `harfrust_shape_fallback`
+        ///  does; 0 if not; -1 if the font is null. Unless the font was created in
+        ///  `HarfRustParseMode::Eager` or `Tolerant` mode, the tables are read on the
+        ///  first call to this or a shaping function.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_font_from_data_index", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustFont* harfrust_font_from_data_index(byte* data, int len, uint index);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_is_degraded")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_is_degraded(HarfRustFont* font);
 
         /// <summary>
         ///  Returns the font's units per em.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_font_units_per_em", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_font_units_per_em(HarfRustFont* font);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_units_per_em")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_units_per_em(HarfRustFont* font);
 
         /// <summary>
-        ///  Frees a font previously created by `harfrust_font_from_data`.
+        ///  Returns the number of glyphs in the font (maxp numGlyphs), so valid glyph
+        ///  IDs are `0..count`. Returns -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_font_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_font_free(HarfRustFont* font);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_glyph_count")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_glyph_count(HarfRustFont* font);
 
         /// <summary>
-        ///  Shapes text in a buffer using the given font.
+        ///  Returns the horizontal advance of a glyph in font units.
+        ///  Results are cached per font, so repeated width-only queries are cheap.
+        ///  Returns -1 (`NullArgument`) if the font is null or -3 (`OutOfRange`) if
+        ///  the glyph ID is out of range.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_shape", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustGlyphBuffer* harfrust_shape(HarfRustFont* font, HarfRustBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_glyph_advance")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_glyph_advance(HarfRustFont* font, uint glyph_id);
 
         /// <summary>
-        ///  Shapes text in a buffer using the given font and OpenType features.
+        ///  Returns the horizontal advance of a glyph in font units at a variation
+        ///  instance from `harfrust_instance_new` (null for the default instance).
+        ///  Each instance's advances are cached separately. Returns the same errors as
+        ///  `harfrust_font_glyph_advance`.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `instance` must be null or live handles.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_shape_with_features", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustGlyphBuffer* harfrust_shape_with_features(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_glyph_advance_with_instance")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_glyph_advance_with_instance(HarfRustFont* font, HarfRustInstance* instance, uint glyph_id);
 
         /// <summary>
-        ///  Shapes text in a buffer using the given font, features, and variable font settings.
+        ///  Copies the horizontal advance of every glyph (indexed by glyph ID) into `out`.
+        ///  At most `capacity` entries are written. Returns the total number of glyphs
+        ///  in the font, so a call with a null `out` can be used to size the array.
+        ///  Returns -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or have room
+        ///  for `capacity` entries.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_shape_full", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustGlyphBuffer* harfrust_shape_full(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features, HarfRustVariation* variations, uint num_variations);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_all_advances")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_all_advances(HarfRustFont* font, ushort* @out, uint capacity);
 
         /// <summary>
-        ///  Returns the number of glyphs in the glyph buffer.
+        ///  Writes the ink extents of the `count` glyphs in `glyphs` to `out`, which
+        ///  must have room for `count` entries, so that e.g. an atlas builder can
+        ///  measure many glyphs in one call. Edges are rounded outwards. Glyphs
+        ///  without an outline (such as spaces) and glyph IDs out of range get zero
+        ///  extents. Returns `NullArgument` if the font, `glyphs` or `out` is null
+        ///  (the arrays may be null if `count` is 0).
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `glyphs` must be null or point to
+        ///  `count` glyph IDs; and `out` must be null or have room for `count`
+        ///  entries.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_glyph_buffer_len", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_glyph_buffer_len(HarfRustGlyphBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_glyph_extents_batch")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_glyph_extents_batch(HarfRustFont* font, uint* glyphs, uint count, HarfRustGlyphExtents* @out);
 
         /// <summary>
-        ///  Returns a pointer to the glyph info array.
+        ///  Looks up the glyph the font's best cmap subtable maps a Unicode codepoint
+        ///  to, without shaping, and writes it to `out_glyph` (0 if the codepoint is
+        ///  not mapped). Returns 1 if the codepoint is mapped, 0 if not, or -1 if the
+        ///  font or `out_glyph` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out_glyph` must be null or
+        ///  point to a writable `u32`.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_infos", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustGlyphInfo* harfrust_glyph_buffer_get_infos(HarfRustGlyphBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_nominal_glyph")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_nominal_glyph(HarfRustFont* font, uint codepoint, uint* out_glyph);
 
         /// <summary>
-        ///  Returns a pointer to the glyph position array.
+        ///  Copies all (codepoint, glyph) pairs of the font's best cmap subtable into
+        ///  `out`, ordered by codepoint. At most `capacity` entries are written.
+        ///  Returns the total number of mappings, so a call with a null `out` can be
+        ///  used to size the array. Returns -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or have room
+        ///  for `capacity` entries.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_positions", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustGlyphPosition* harfrust_glyph_buffer_get_positions(HarfRustGlyphBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_cmap_mappings")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_cmap_mappings(HarfRustFont* font, HarfRustCmapEntry* @out, uint capacity);
 
         /// <summary>
-        ///  Clears the glyph buffer and returns a new unicode buffer for reuse.
+        ///  Checks whether the font renders a character sequence (e.g. an emoji ZWJ
+        ///  sequence) as a single glyph.
+        ///  The sequence is shaped and counts as supported when it forms one cluster
+        ///  with exactly one visible glyph and no .notdef glyphs; zero-advance glyphs
+        ///  such as hidden joiners are ignored.
+        ///  Returns 1 if supported, 0 if not, or a negative value on invalid arguments.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `text` must be null or point to
+        ///  `len` UTF-16 code units.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_glyph_buffer_into_buffer", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern HarfRustBuffer* harfrust_glyph_buffer_into_buffer(HarfRustGlyphBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_supports_sequence")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_supports_sequence(HarfRustFont* font, ushort* text, int len);
 
         /// <summary>
-        ///  Frees a glyph buffer previously created by `harfrust_shape`.
+        ///  Checks whether UTF-16 text fits within `max_width` font units.
+        ///  Sums the nominal (cmap + hmtx) advances without shaping and stops as soon
+        ///  as the limit is exceeded, so kerning and ligatures are not taken into
+        ///  account. The font's advance overrides, character spacing (once per
+        ///  character and the marks following it, as shaping adds it once per
+        ///  cluster) and horizontal scaling apply. Returns 1 if the text fits, 0 if not, or a
+        ///  negative value on invalid arguments.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `text` must be null or point to
+        ///  `len` UTF-16 code units.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_glyph_buffer_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_glyph_buffer_free(HarfRustGlyphBuffer* buffer);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_text_fits")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_text_fits(HarfRustFont* font, ushort* text, int len, int max_width);
 
         /// <summary>
-        ///  Allocates memory in the WASM linear memory.
-        ///  Used by the host to allocate space for passing data to WASM.
+        ///  Eagerly does the parsing and cache filling that would otherwise happen on
+        ///  the first shape or metrics call: walks the cmap, resolves every glyph
+        ///  advance and shapes a short sample so the shaper's lookup data is built.
+        ///  Safe to call from a background thread while the font is otherwise unused.
+        ///  Returns `NullArgument` if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_alloc", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern int harfrust_alloc(int size);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_warmup")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_warmup(HarfRustFont* font);
 
         /// <summary>
-        ///  Frees memory allocated by harfrust_alloc.
+        ///  Freezes a font: from now on no call changes how it shapes or measures, so
+        ///  the handle can be shared by any number of threads without further
+        ///  synchronization. Its lazily filled caches stay internally synchronized
+        ///  and do not change results. Functions that would change the font (such as
+        ///  `harfrust_font_set_feature_overrides`) fail with `Unsupported` on a frozen
+        ///  font, including through handles from `harfrust_font_retain`. Freezing is
+        ///  permanent, and freezing a frozen font does nothing.
+        ///  Returns `NullArgument` if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        [DllImport(__DllName, EntryPoint = "harfrust_dealloc", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        internal static extern void harfrust_dealloc(int ptr, int size);
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_freeze")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_freeze(HarfRustFont* font);
 
+        /// <summary>
+        ///  Returns 1 if the font is frozen (see `harfrust_font_freeze`), 0 if not,
+        ///  or -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_is_frozen")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_is_frozen(HarfRustFont* font);
 
-    }
+        /// <summary>
+        ///  Writes the font's superscript or subscript size and offset (OS/2
+        ///  ySuperscript* and ySubscript* values, in font units) to `out`. Fonts without
+        ///  usable OS/2 values report conventional defaults.
+        ///  Returns `NullArgument` if the font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustScriptMetrics`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_script_metrics")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_script_metrics(HarfRustFont* font, HarfRustScriptPosition position, HarfRustScriptMetrics* @out);
 
-    /// <summary>
-    ///  Glyph information after shaping.
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustGlyphInfo
-    {
         /// <summary>
-        ///  The glyph ID in the font.
+        ///  Writes the font's line layout metrics to `out` in one call. Ascent,
+        ///  descent and line gap follow the font's preferred vertical metrics (OS/2
+        ///  typographic when USE_TYPO_METRICS is set, else hhea); the x-height and cap
+        ///  height are those of `harfrust_font_get_x_height` and
+        ///  `harfrust_font_get_cap_height`. The baseline of a line lies `ascent` below
+        ///  its top, and lines are `ascent - descent + line_gap` apart. Returns
+        ///  `NullArgument` if the font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustFontMetrics`.
         /// </summary>
-        public uint glyph_id;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_metrics")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_metrics(HarfRustFont* font, HarfRustFontMetrics* @out);
+
         /// <summary>
-        ///  The cluster index (position in original text).
+        ///  Writes the font's weight and width classes, fsSelection style bits and
+        ///  fsType embedding permissions to `out`. Fonts without an OS/2 table report
+        ///  regular weight, normal width, the italic and bold bits of the head table's
+        ///  macStyle, and no embedding restrictions. Returns `NullArgument` if the
+        ///  font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustFontStyle`.
         /// </summary>
-        public uint cluster;
-    }
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_style")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_style(HarfRustFont* font, HarfRustFontStyle* @out);
 
-    /// <summary>
-    ///  Glyph positioning information after shaping.
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustGlyphPosition
-    {
         /// <summary>
-        ///  Horizontal advance after drawing this glyph.
+        ///  Writes the font's italic angle, underline position and thickness, and
+        ///  whether it is monospaced, from the `post` table, to `out`. Fonts without a
+        ///  `post` table report an upright proportional font with an underline 10% of
+        ///  the em below the baseline, 5% of the em thick. Returns `NullArgument` if
+        ///  the font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustPostInfo`.
         /// </summary>
-        public int x_advance;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_post_info")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_post_info(HarfRustFont* font, HarfRustPostInfo* @out);
+
         /// <summary>
-        ///  Vertical advance after drawing this glyph.
+        ///  Writes the font's global bounding box, flags and macStyle from the head
+        ///  table to `out`. The bounding box is what a PDF FontDescriptor declares as
+        ///  /FontBBox and bounds every glyph, so it can be used to clip rendered runs
+        ///  conservatively. Fonts without a head table report all zeros.
+        ///  Returns `NullArgument` if the font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustHeadInfo`.
         /// </summary>
-        public int y_advance;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_head_info")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_head_info(HarfRustFont* font, HarfRustHeadInfo* @out);
+
         /// <summary>
-        ///  Horizontal offset for drawing.
+        ///  Writes the font's strikeout position and thickness (OS/2
+        ///  yStrikeoutPosition and yStrikeoutSize, in font units) to `out`, for
+        ///  drawing line-through decorations. Fonts without usable OS/2 values report
+        ///  a line 25% of the em above the baseline, 5% of the em thick. Superscript
+        ///  and subscript metrics are available from
+        ///  `harfrust_font_get_script_metrics`.
+        ///  Returns `NullArgument` if the font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustStrikeout`.
         /// </summary>
-        public int x_offset;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_strikeout")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_strikeout(HarfRustFont* font, HarfRustStrikeout* @out);
+
         /// <summary>
-        ///  Vertical offset for drawing.
+        ///  Writes the font's cap height in font units to `out_value`. Uses OS/2
+        ///  sCapHeight when present (version 2+), otherwise the top of the 'H' glyph,
+        ///  otherwise 70% of the em. `out_source` (optional) receives which was used.
+        ///  Returns `NullArgument` if the font or `out_value` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `out_value` must be null or point to
+        ///  a writable `i32`; and `out_source` must be null or point to a writable
+        ///  `HarfRustMetricSource`.
         /// </summary>
-        public int y_offset;
-    }
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_cap_height")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_cap_height(HarfRustFont* font, int* out_value, HarfRustMetricSource* out_source);
 
-    /// <summary>
-    ///  OpenType feature for shaping.
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustFeature
-    {
         /// <summary>
-        ///  The feature tag (e.g. 'liga', 'kern').
+        ///  Writes the font's x-height in font units to `out_value`. Uses OS/2
+        ///  sxHeight when present (version 2+), otherwise the top of the 'x' glyph,
+        ///  otherwise 50% of the em. `out_source` (optional) receives which was used.
+        ///  Returns `NullArgument` if the font or `out_value` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `out_value` must be null or point to
+        ///  a writable `i32`; and `out_source` must be null or point to a writable
+        ///  `HarfRustMetricSource`.
         /// </summary>
-        public uint tag;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_x_height")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_x_height(HarfRustFont* font, int* out_value, HarfRustMetricSource* out_source);
+
         /// <summary>
-        ///  The value of the feature (0 = disabled, 1 = enabled, or other values).
+        ///  Writes an estimate of the dominant vertical stem width (PDF StemV) in font
+        ///  units to `out_value`, measured across the stem of 'l' or 'I'. Fonts
+        ///  without usable outlines get a heuristic based on the OS/2 weight class.
+        ///  `out_source` (optional) receives which method was used.
+        ///  Returns `NullArgument` if the font or `out_value` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `out_value` must be null or point to
+        ///  a writable `i32`; and `out_source` must be null or point to a writable
+        ///  `HarfRustMetricSource`.
         /// </summary>
-        public uint value;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_estimate_stemv")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_estimate_stemv(HarfRustFont* font, int* out_value, HarfRustMetricSource* out_source);
+
         /// <summary>
-        ///  The start index in the buffer to apply this feature.
+        ///  Writes the metrics PDF text extraction compares against (units per em,
+        ///  ascent, descent, default width and missing width) to `out`, so extraction
+        ///  heuristics and shaping use the same values. Ascent and descent follow the
+        ///  font's preferred vertical metrics (OS/2 typographic when USE_TYPO_METRICS
+        ///  is set, else hhea). Returns `NullArgument` if the font or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustPdfMetrics`.
         /// </summary>
-        public uint start;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_pdf_metrics")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_get_pdf_metrics(HarfRustFont* font, HarfRustPdfMetrics* @out);
+
         /// <summary>
-        ///  The end index in the buffer to apply this feature (u32::MAX for end).
+        ///  Writes the unhinted outline of a glyph as path commands, in a y-up glyph
+        ///  space of `units_per_em` units (1000 for PDF glyph space), or in font units
+        ///  if `units_per_em` is 0. Quadratic curves are converted to cubics, so the
+        ///  commands map directly onto PDF path operators. At most `capacity` commands
+        ///  are written. Returns the number of commands (0 for glyphs without an
+        ///  outline, such as spaces), so a call with a null `out` can be used to size
+        ///  the array. Returns -1 (`NullArgument`) if the font is null, -2
+        ///  (`InvalidArgument`) if `units_per_em` is negative, or -3 (`OutOfRange`) if
+        ///  the glyph ID is out of range.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or have room
+        ///  for `capacity` entries.
         /// </summary>
-        public uint end;
-    }
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_glyph_outline")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_glyph_outline(HarfRustFont* font, uint glyph_id, float units_per_em, HarfRustPathCommand* @out, uint capacity);
 
-    /// <summary>
-    ///  Font variation settings.
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustVariation
-    {
         /// <summary>
-        ///  The variation tag (e.g. 'wght', 'wdth').
+        ///  Writes the vertical metrics of `count` glyphs to `out`, in PDF glyph space
+        ///  and sign conventions, for the W2 array of a vertical (Identity-V) CID
+        ///  font. Advances come from `vmtx` and origins from `VORG` or `vmtx`; fonts
+        ///  without vertical tables get the advance of a full line (ascent minus
+        ///  descent) and an origin at the ascender, horizontally centered on the
+        ///  glyph. Returns the number of glyphs written, -1 if the font is null, or -2
+        ///  if `glyphs` or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `glyphs` must be null or point to
+        ///  `count` glyph IDs; and `out` must be null or have room for `count`
+        ///  entries.
         /// </summary>
-        public uint tag;
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_vertical_metrics")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_vertical_metrics(HarfRustFont* font, uint* glyphs, uint count, HarfRustVerticalMetrics* @out);
+
         /// <summary>
-        ///  The variation value (in design units).
+        ///  Adds a reference to a font, so that independent owners (e.g. a layout
+        ///  engine and a glyph cache) can share the handle and each call
+        ///  `harfrust_font_free` when done; the font and its data are freed by the
+        ///  last call. Returns `font`, or null if it is null or invalid.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        public float value;
-    }
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_retain")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustFont* harfrust_font_retain(HarfRustFont* font);
 
-    /// <summary>
-    ///  Opaque wrapper around harfrust's UnicodeBuffer.
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustBuffer
-    {
-    }
+        /// <summary>
+        ///  Releases a reference to a font created by `harfrust_font_from_data` (or
+        ///  added by `harfrust_font_retain`), freeing the font when it was the last.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_font_free(HarfRustFont* font);
 
-    /// <summary>
-    ///  Opaque wrapper that owns font data and provides shaping capabilities.
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustFont
-    {
-    }
+        /// <summary>
+        ///  Sets `feature.start`/`feature.end` from a range of UTF-16 code units
+        ///  (.NET `string` indices) into `text`, converted to the cluster values of a
+        ///  buffer built with the given `unit`. `char_end` of -1 means the end of the
+        ///  text, which maps to u32::MAX so the feature also covers later additions;
+        ///  an explicit `char_end` of `len` stops the feature at the end of `text`.
+        ///  A range boundary inside a surrogate pair is widened to cover the whole pair.
+        ///  Returns `NullArgument` if the feature or text is null, `InvalidArgument`
+        ///  if `len` is negative, or `OutOfRange` if the range is outside the text.
+        ///
+        ///  # Safety
+        ///
+        ///  `feature` must be null or point to a writable `HarfRustFeature`, and
+        ///  `text` must be null or point to `len` UTF-16 code units.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_feature_set_range")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_feature_set_range(HarfRustFeature* feature, ushort* text, int len, int char_start, int char_end, HarfRustClusterUnit unit);
 
-    /// <summary>
-    ///  Opaque wrapper around harfrust's GlyphBuffer (shaping result).
-    /// </summary>
-    [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct HarfRustGlyphBuffer
-    {
-    }
+        /// <summary>
+        ///  Builds feature ranges that turn digit/slash/digit spans (e.g. "1/2", or
+        ///  with U+2044 FRACTION SLASH) in UTF-16 `text` into fractions. Fonts with
+        ///  `frac` get one `frac` range per span; fonts with only `numr`/`dnom` get
+        ///  those over the numerator and denominator digits. Ranges are in cluster
+        ///  values for `unit`. At most `capacity` features are written. Returns the
+        ///  total number of features, so a call with a null `out` can be used to size
+        ///  the array. Returns -1 (`NullArgument`) if the font or text is null or -2
+        ///  (`InvalidArgument`) if `len` is negative.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `text` must be null or point to
+        ///  `len` UTF-16 code units; and `out` must be null or have room for
+        ///  `capacity` features.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_fraction_features")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_fraction_features(HarfRustFont* font, ushort* text, int len, HarfRustClusterUnit unit, HarfRustFeature* @out, uint capacity);
 
+        /// <summary>
+        ///  Writes the tags (4 bytes as u32) of the features the shaper applies by
+        ///  default to a run with the given ISO 15924 script and direction, limited to
+        ///  those the font actually implements for that script. Tags are in the order
+        ///  the shaper applies them; any of them can be turned off by passing it with
+        ///  value 0 to `harfrust_shape_with_features`. An `Invalid` direction uses the
+        ///  script's natural horizontal direction.
+        ///  At most `capacity` tags are written. Returns the total number of tags, or
+        ///  -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or have room
+        ///  for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_default_features")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_default_features(HarfRustFont* font, uint script_tag, HarfRustDirection direction, uint* @out, uint capacity);
 
-    /// <summary>
-    ///  Text direction for shaping.
-    /// </summary>
-    internal enum HarfRustDirection : uint
-    {
         /// <summary>
-        ///  Initial, unset direction.
+        ///  Sets features applied to every run shaped with this font, before the
+        ///  features passed to the shape call (which win where they overlap). Typical
+        ///  use is turning off a feature that a particular font implements badly, e.g.
+        ///  `calt` with value 0. Replaces any previous overrides; passing no features
+        ///  clears them. Shape calls already running are not affected.
+        ///  Returns `NullArgument` if the font is null, or if `features` is null with
+        ///  a non-zero count, and `Unsupported` if the font is frozen.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `features` must be null or point
+        ///  to `num_features` features.
         /// </summary>
-        Invalid = 0,
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_set_feature_overrides")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_set_feature_overrides(HarfRustFont* font, HarfRustFeature* features, uint num_features);
+
         /// <summary>
-        ///  Left-to-right text.
+        ///  Copies the font's feature overrides (see
+        ///  `harfrust_font_set_feature_overrides`) into `out`. At most `capacity`
+        ///  features are written. Returns the total number of overrides, so a call
+        ///  with a null `out` can be used to size the array, or -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or have room
+        ///  for `capacity` features.
         /// </summary>
-        LeftToRight = 4,
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_feature_overrides")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_get_feature_overrides(HarfRustFont* font, HarfRustFeature* @out, uint capacity);
+
         /// <summary>
-        ///  Right-to-left text.
+        ///  Sets advances that replace the shaped horizontal advance of glyphs, e.g.
+        ///  to force the space to the width a PDF declares in its /Widths array so
+        ///  that layout matches what the PDF consumer will draw. Overrides apply to
+        ///  every result shaped with this font, after shaping and before any scaling
+        ///  the shape call does; offsets and vertical advances are left alone.
+        ///  Replaces any previous overrides (a later entry for the same glyph wins);
+        ///  passing none clears them. Shape calls already running are not affected.
+        ///  Returns `NullArgument` if the font is null, or if `overrides` is null with
+        ///  a non-zero count, and `Unsupported` if the font is frozen.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `overrides` must be null or
+        ///  point to `num_overrides` entries.
         /// </summary>
-        RightToLeft = 5,
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_set_advance_overrides")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_set_advance_overrides(HarfRustFont* font, HarfRustAdvanceOverride* overrides, uint num_overrides);
+
         /// <summary>
-        ///  Top-to-bottom text.
+        ///  Copies the font's advance overrides (see
+        ///  `harfrust_font_set_advance_overrides`) into `out`, in glyph ID order. At
+        ///  most `capacity` overrides are written. Returns the total number of
+        ///  overrides, so a call with a null `out` can be used to size the array, or
+        ///  -1 if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or have room
+        ///  for `capacity` entries.
         /// </summary>
-        TopToBottom = 6,
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_advance_overrides")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_get_advance_overrides(HarfRustFont* font, HarfRustAdvanceOverride* @out, uint capacity);
+
         /// <summary>
-        ///  Bottom-to-top text.
+        ///  Sets a fixed extra advance, in font units, added after every cluster of
+        ///  every result shaped with this font, spaces included, like the PDF Tc
+        ///  text state parameter (a Tc of `t` at font size `s` is `t *
+        ///  units_per_em / s`). Unlike typographic tracking it does not depend on the
+        ///  size or skip any glyphs, so measured text matches what a PDF viewer
+        ///  draws. Applied after advance overrides and before horizontal scaling, as
+        ///  PDF scales Tc by Tz; vertical runs are left alone. Measurement and line
+        ///  fitting use the spaced advances. Negative values tighten text.
+        ///  Returns `NullArgument` if the font is null and `Unsupported` if the font
+        ///  is frozen.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
         /// </summary>
-        BottomToTop = 7,
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_set_character_spacing")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_set_character_spacing(HarfRustFont* font, int spacing);
+
+        /// <summary>
+        ///  Returns the font's character spacing in font units (see
+        ///  `harfrust_font_set_character_spacing`), 0 unless set or if the font is
+        ///  null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_character_spacing")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_get_character_spacing(HarfRustFont* font);
+
+        /// <summary>
+        ///  Sets a horizontal scaling factor, like the PDF Tz text state parameter
+        ///  divided by 100, for condensed or expanded text. Every result shaped with
+        ///  this font has its advances and offsets multiplied by `scale` in x, after
+        ///  advance overrides and before any scaling the shape call does; vertical
+        ///  advances and offsets are left alone. Measurement and line fitting
+        ///  (`harfrust_text_fits`, `harfrust_shape_fit_size`,
+        ///  `harfrust_shape_field_line`) use the scaled advances. Font metrics such
+        ///  as `harfrust_font_glyph_advance` are not affected. Shape calls already
+        ///  running may or may not see the new factor.
+        ///  Returns `NullArgument` if the font is null, `OutOfRange` if `scale` is not
+        ///  a positive finite number, and `Unsupported` if the font is frozen.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_set_horizontal_scale")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_set_horizontal_scale(HarfRustFont* font, float scale);
+
+        /// <summary>
+        ///  Returns the font's horizontal scaling factor (see
+        ///  `harfrust_font_set_horizontal_scale`), 1.0 unless set, or 0.0 if the font
+        ///  is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_get_horizontal_scale")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial float harfrust_font_get_horizontal_scale(HarfRustFont* font);
+
+        /// <summary>
+        ///  Shapes text in a buffer using the given font. Fonts whose layout tables
+        ///  cannot be read are shaped as `harfrust_shape_fallback` does.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape(HarfRustFont* font, HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Shapes text in a buffer without layout tables: each character maps to
+        ///  its nominal glyph through `cmap` (.notdef if unmapped) and advances by its
+        ///  `hmtx` width, in visual order. This never fails for a valid font and
+        ///  buffer, even if GSUB, GPOS or GDEF are damaged. The result reports
+        ///  `harfrust_glyph_buffer_is_degraded`. Consumes the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_fallback")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_fallback(HarfRustFont* font, HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Shapes text in a buffer using the given font and OpenType features.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles, and `features` must be
+        ///  null or point to `num_features` features.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_with_features")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_with_features(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features);
+
+        /// <summary>
+        ///  Shapes text in a buffer using the given font, features, and variable font settings.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles; `features` must be null
+        ///  or point to `num_features` features; and `variations` must be null or
+        ///  point to `num_variations` variations.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_full")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_full(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features, HarfRustVariation* variations, uint num_variations);
+
+        /// <summary>
+        ///  Shapes a UTF-16 run in a single call, without a separate buffer handle.
+        ///  `direction` `Invalid`, `script_tag` 0 and a null `language` are guessed
+        ///  from the text. A nonzero `normalize_spaces` normalizes spaces as
+        ///  `harfrust_buffer_set_normalize_spaces` does. Returns null if the font or
+        ///  text is null or the language is invalid.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `text` must be null or point to
+        ///  `len` UTF-16 code units; `language` must be null or a NUL-terminated
+        ///  string; and `features` must be null or point to `num_features` features.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_run_utf16")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_run_utf16(HarfRustFont* font, ushort* text, int len, HarfRustDirection direction, uint script_tag, byte* language, HarfRustFeature* features, uint num_features, int normalize_spaces);
+
+        /// <summary>
+        ///  Writes a key identifying the result of shaping `run` with the given
+        ///  variation settings to `out`, without shaping it, so callers can look up
+        ///  their own cache of rendered runs first. The key combines the font data
+        ///  hash, face index, the text, its direction, script and language after
+        ///  guessing, the features in order, the font's advance overrides, character
+        ///  spacing and horizontal scale, whether spaces are normalized, the
+        ///  normalized axis coordinates and the library version: it is stable across processes and changes on upgrades,
+        ///  which may shape differently. Returns `NullArgument` if the font, `run` or
+        ///  `out` is null or `variations` is null with a non-zero count, or
+        ///  `InvalidArgument` if the run's text or language is invalid.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `run` must be null or point to a
+        ///  `HarfRustRunDescriptor`, and its pointers must meet the requirements
+        ///  `harfrust_shape_run_utf16` places on its arguments; `variations` must be
+        ///  null or point to `num_variations` variations; and `out` must be null or
+        ///  point to a writable `HarfRustCacheKey`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_run_key")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_run_key(HarfRustFont* font, HarfRustRunDescriptor* run, HarfRustVariation* variations, uint num_variations, HarfRustCacheKey* @out);
+
+        /// <summary>
+        ///  Shapes `num_runs` runs with one font in a single call, writing one glyph
+        ///  buffer handle per run to `out_buffers` (null for runs whose text or
+        ///  language is invalid). Runs of a degraded font are shaped with the
+        ///  fallback shaper, as `harfrust_shape` does. Each handle must be freed with
+        ///  `harfrust_glyph_buffer_free`. Returns the number of runs shaped,
+        ///  -1 if the font is null, or -2 if `runs` or `out_buffers` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `runs` must be null or point to
+        ///  `num_runs` entries, each of which meets the requirements
+        ///  `harfrust_shape_run_utf16` places on its arguments.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_batch")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_shape_batch(HarfRustFont* font, HarfRustRunDescriptor* runs, uint num_runs, HarfRustGlyphBuffer** out_buffers);
+
+        /// <summary>
+        ///  Same as `harfrust_shape_batch`, but shapes the runs on up to
+        ///  `num_threads` threads (0 for the number set by `harfrust_init`, by default
+        ///  the number of available cores; never more than there are cores) and
+        ///  returns once all are complete. Runs are
+        ///  split into contiguous chunks; the calling thread shapes one of them. Where
+        ///  threads are unavailable (e.g. WebAssembly) every run is shaped on the
+        ///  calling thread.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `runs` must be null or point to
+        ///  `num_runs` entries, each of which meets the requirements
+        ///  `harfrust_shape_run_utf16` places on its arguments.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_batch_parallel")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_shape_batch_parallel(HarfRustFont* font, HarfRustRunDescriptor* runs, uint num_runs, HarfRustGlyphBuffer** out_buffers, uint num_threads);
+
+        /// <summary>
+        ///  Shapes a copy of the buffer's text with the given font and optional
+        ///  OpenType features. Unlike `harfrust_shape`, the buffer is not consumed:
+        ///  it stays owned by the caller and can be cleared, reused or shaped again.
+        ///  Segment properties are guessed on the copy if no direction is set, and
+        ///  spaces are normalized in the copy if enabled on the buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles, and `features` must be
+        ///  null or point to `num_features` features.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_copy")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_copy(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features);
+
+        /// <summary>
+        ///  Shapes a copy of the buffer's text like `harfrust_shape_copy`, writing
+        ///  glyph infos and positions straight into caller arrays of `capacity`
+        ///  entries instead of allocating a glyph buffer. `written_out` receives the
+        ///  number of glyphs, and `flags_out`, if not null, the
+        ///  `HarfRustResultFlags` bits `harfrust_glyph_buffer_get_flags` would
+        ///  report. Returns `NullArgument` if the font, buffer or `infos_out`,
+        ///  `positions_out` or `written_out` is null, or `InsufficientCapacity` if the
+        ///  glyphs do not fit; nothing is written then and `written_out` receives the
+        ///  capacity needed. The buffer is not consumed, so it can be shaped again
+        ///  into larger arrays.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles; `features` must be null
+        ///  or point to `num_features` features; `infos_out` must be null or have room
+        ///  for `capacity` entries; `positions_out` must be null or have room for
+        ///  `capacity` entries; and `written_out` and `flags_out` must be null or
+        ///  point to a writable `u32` and `i32`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_into")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_shape_into(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features, HarfRustGlyphInfo* infos_out, HarfRustGlyphPosition* positions_out, uint capacity, uint* written_out, int* flags_out);
+
+        /// <summary>
+        ///  Shapes text as small caps. The font's `smcp` feature is applied to the
+        ///  characters it covers, whose glyphs keep a scale of 1.0. Lowercase letters
+        ///  it does not cover (all of them if the font has no `smcp`) are shaped as
+        ///  uppercase and their glyphs are reported with `scale` through
+        ///  `harfrust_glyph_buffer_get_transforms`; their advances and offsets are already
+        ///  scaled, so the renderer only needs to scale the outlines. Clusters still
+        ///  refer to the original text. Returns null on invalid arguments or if
+        ///  `scale` is not a positive finite number.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_small_caps")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_small_caps(HarfRustFont* font, HarfRustBuffer* buffer, float scale);
+
+        /// <summary>
+        ///  Shapes text as superscript or subscript. The font's `sups` or `subs`
+        ///  feature is applied to the characters it covers. Glyphs of the others (all
+        ///  of them if the font has no such feature) are synthesized from the OS/2
+        ///  script metrics: advances and offsets are scaled by the script size, the
+        ///  script offset is added to each glyph's offset, and the scale is reported
+        ///  through `harfrust_glyph_buffer_get_transforms`.
+        ///  Returns null on invalid arguments.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_script_position")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_script_position(HarfRustFont* font, HarfRustBuffer* buffer, HarfRustScriptPosition position);
+
+        /// <summary>
+        ///  Shapes text for a vertical line with mixed orientation (CSS
+        ///  `text-orientation: mixed`): characters that are upright in vertical text
+        ///  (CJK, kana, Hangul and most symbols, per UAX #50) are shaped top to
+        ///  bottom, and runs of the others (e.g. Latin words) are shaped horizontally
+        ///  and set sideways, turned 90 degrees clockwise with their em box centered
+        ///  on the line. Sideways glyphs report a rotation of -90 through
+        ///  `harfrust_glyph_buffer_get_transforms`; their advances and offsets are
+        ///  already in the vertical line's coordinates. The buffer's script and
+        ///  language are used for both kinds of run, and its direction is ignored.
+        ///  Consumes the buffer. Returns null if the font or buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_vertical_mixed")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_vertical_mixed(HarfRustFont* font, HarfRustBuffer* buffer);
+
+        /// <summary>
+        ///  Finds the largest font size in `[min_size, max_size]` at which the shaped
+        ///  UTF-16 text fits within `max_width` (and, if positive, `max_height`).
+        ///  Widths and heights use the same unit as the size (e.g. points); the height
+        ///  of the text is its ascent minus descent at that size.
+        ///  The shaped advance is binary-searched with the shaper's point size set, so
+        ///  size-dependent tracking is honored. The chosen size is written to
+        ///  `out_size`. Returns 0 on success, 1 if the text does not fit even at
+        ///  `min_size` (in which case `min_size` is written), or a negative value on
+        ///  invalid arguments.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `text` must be null or point to
+        ///  `len` UTF-16 code units; and `out_size` must be null or point to a
+        ///  writable `f32`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_fit_size")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_shape_fit_size(HarfRustFont* font, ushort* text, int len, float max_width, float max_height, float min_size, float max_size, float* out_size);
+
+        /// <summary>
+        ///  Lays out one line of UTF-16 text in a `width` x `height` rectangle, as
+        ///  needed for PDF form field appearance streams. The line is aligned within
+        ///  `padding` of the left and right edges and centered vertically on its
+        ///  ascent and descent. A `font_size` of 0 selects the largest size, up to the
+        ///  padded height, at which the line fits inside the padding (but no smaller
+        ///  than 4); text set at a fixed size may overflow. Returns the shaped glyphs,
+        ///  whose positions are in font units, and writes the chosen size and
+        ///  placement to `out_layout`. Returns null on invalid arguments or if the
+        ///  padding leaves no room.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `text` must be null or point to
+        ///  `len` UTF-16 code units; and `out_layout` must be null or point to a
+        ///  writable `HarfRustLineLayout`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_field_line")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_field_line(HarfRustFont* font, ushort* text, int len, float width, float height, float padding, float font_size, HarfRustTextAlign align, HarfRustLineLayout* out_layout);
+
+        /// <summary>
+        ///  Places the runs of a horizontal line side by side, in the order given
+        ///  (left to right), each on its own baseline shifted by its rise, so that
+        ///  superscript and subscript runs sit correctly among the others. The
+        ///  placement of each run is written to `out_placements` (optional, one entry
+        ///  per run), and the line's width and vertical extent to `out_metrics`: the
+        ///  ascent and descent of each run's font, scaled to its size and moved by
+        ///  its rise, so a raised run makes the line taller. A line without runs is
+        ///  empty. Returns `NullArgument` if `runs` is null with a non-zero count,
+        ///  `out_metrics` is null, or a run's font or glyphs is null, and
+        ///  `InvalidArgument` if a font size or rise is not finite.
+        ///
+        ///  # Safety
+        ///
+        ///  `runs` must be null or point to `num_runs` entries, and in each run `font`
+        ///  and `glyphs` must be null or live handles; `out_placements` must be null
+        ///  or have room for `num_runs` entries; and `out_metrics` must be null or
+        ///  point to a writable `HarfRustLineMetrics`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_layout_line")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_layout_line(HarfRustLayoutRun* runs, uint num_runs, HarfRustRunPlacement* out_placements, HarfRustLineMetrics* out_metrics);
+
+        /// <summary>
+        ///  Creates a reusable shaper for `font` at the given variable font settings
+        ///  (none for the default instance). The variation instance is computed once
+        ///  here rather than on every shape call. The shaper holds a reference to the
+        ///  font (as `harfrust_font_retain`), so the caller may free the font first.
+        ///  Returns null if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `variations` must be null or
+        ///  point to `num_variations` variations.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shaper_new")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustShaper* harfrust_shaper_new(HarfRustFont* font, HarfRustVariation* variations, uint num_variations);
+
+        /// <summary>
+        ///  Shapes text in a buffer with the shaper's font and variation settings and
+        ///  the given OpenType features. Like `harfrust_shape`, the buffer is consumed.
+        ///
+        ///  # Safety
+        ///
+        ///  `shaper` and `buffer` must be null or live handles, and `features` must be
+        ///  null or point to `num_features` features.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shaper_shape")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shaper_shape(HarfRustShaper* shaper, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features);
+
+        /// <summary>
+        ///  Frees a shaper and releases its reference to the font, freeing the font
+        ///  if `harfrust_font_free` was already called on it.
+        ///
+        ///  # Safety
+        ///
+        ///  `shaper` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shaper_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_shaper_free(HarfRustShaper* shaper);
+
+        /// <summary>
+        ///  Creates a variation instance of `font` at the given axis settings, to be
+        ///  passed to `harfrust_shape_with_instance` instead of recomputing it from
+        ///  the settings on every `harfrust_shape_full` call. The instance is only
+        ///  valid with the font it was created for, but does not borrow it. Returns
+        ///  null if the font is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `variations` must be null or
+        ///  point to `num_variations` variations.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_instance_new")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustInstance* harfrust_instance_new(HarfRustFont* font, HarfRustVariation* variations, uint num_variations);
+
+        /// <summary>
+        ///  Shapes text in a buffer using the given font, variation instance (null for
+        ///  the default instance) and OpenType features. The buffer is consumed.
+        ///
+        ///  # Safety
+        ///
+        ///  `font`, `instance` and `buffer` must be null or live handles; and
+        ///  `features` must be null or point to `num_features` features.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shape_with_instance")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_shape_with_instance(HarfRustFont* font, HarfRustInstance* instance, HarfRustBuffer* buffer, HarfRustFeature* features, uint num_features);
+
+        /// <summary>
+        ///  Frees a variation instance.
+        ///
+        ///  # Safety
+        ///
+        ///  `instance` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_instance_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_instance_free(HarfRustInstance* instance);
+
+        /// <summary>
+        ///  Writes the version of this library to the given (optional) pointers, so a
+        ///  host can check that the binary it loaded matches its bindings.
+        ///
+        ///  # Safety
+        ///
+        ///  `out_major` must be null or point to a writable `u32`; `out_minor` must be
+        ///  null or point to a writable `u32`; and `out_patch` must be null or point
+        ///  to a writable `u32`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_version")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_version(uint* out_major, uint* out_minor, uint* out_patch);
+
+        /// <summary>
+        ///  Returns the version of the harfrust shaping engine the library was built
+        ///  with (e.g. "0.5.2") as a static NUL-terminated string, which must not be
+        ///  freed.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_engine_version_string")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial byte* harfrust_engine_version_string();
+
+        /// <summary>
+        ///  Writes the version of the Unicode Character Database the shaping engine
+        ///  was built from (e.g. 17.0.0) to the given (optional) pointers. Characters
+        ///  assigned in later versions shape with default properties.
+        ///
+        ///  # Safety
+        ///
+        ///  `out_major` must be null or point to a writable `u32`; `out_minor` must be
+        ///  null or point to a writable `u32`; and `out_micro` must be null or point
+        ///  to a writable `u32`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_unicode_version")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_unicode_version(uint* out_major, uint* out_minor, uint* out_micro);
+
+        /// <summary>
+        ///  Copies the OpenType script tags (e.g. 'Arab', 'Deva') of the scripts with
+        ///  a dedicated shaper into `out`. Other scripts are shaped by applying the
+        ///  font's lookups without script-specific reordering or joining. At most
+        ///  `capacity` tags are written. Returns the total number of scripts, so a call
+        ///  with a null `out` can be used to size the array.
+        ///
+        ///  # Safety
+        ///
+        ///  `out` must be null or have room for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_supported_scripts")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_supported_scripts(uint* @out, uint capacity);
+
+        /// <summary>
+        ///  Writes to `out` how well the font supports the script with the given ISO
+        ///  15924 tag (e.g. "Deva" = 0x44657661), for ranking fallback fonts: `Full`
+        ///  if it maps characters of the script and has GSUB lookups for it (or the
+        ///  script has no dedicated shaper), `CmapOnly` if it only maps characters,
+        ///  `Unsupported` if it maps none. Reading the `cmap` makes this too slow to
+        ///  call per run; cache the result per font. Returns `NullArgument` if the
+        ///  font or `out` is null, or `InvalidArgument` for an unknown script tag.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `out` must be null or point to a
+        ///  writable `HarfRustScriptSupport`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_script_support")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_font_script_support(HarfRustFont* font, uint script_tag, HarfRustScriptSupport* @out);
+
+        /// <summary>
+        ///  Returns the status of the last failure on the calling thread, or `Ok` if
+        ///  none was recorded. Failing calls record their status and a message;
+        ///  successful calls leave the record unchanged, so check it only after a
+        ///  call has failed.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_last_error_code")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_last_error_code();
+
+        /// <summary>
+        ///  Returns the message of the last failure on the calling thread as a
+        ///  NUL-terminated UTF-8 string, e.g. "font data cannot be parsed: ...", or
+        ///  null if none was recorded. Free it with `harfrust_string_free`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_last_error_message")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial byte* harfrust_last_error_message();
+
+        /// <summary>
+        ///  Forgets the last failure on the calling thread.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_clear_last_error")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_clear_last_error();
+
+        /// <summary>
+        ///  Frees a string returned by this library.
+        ///
+        ///  # Safety
+        ///
+        ///  `string` must be null or a string returned by this library that has not
+        ///  been freed.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_string_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_string_free(byte* @string);
+
+        /// <summary>
+        ///  Applies library-wide settings (see `HarfRustConfig`); a null `config`
+        ///  restores the defaults. May be called again to change them, and need not
+        ///  be called at all. Handles created before the call stay valid. Returns
+        ///  `InvalidArgument` if `config.size` is smaller than the `size` field.
+        ///
+        ///  # Safety
+        ///
+        ///  `config` must be null or point to a `HarfRustConfig`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_init")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_init(HarfRustConfig* config);
+
+        /// <summary>
+        ///  Restores the default settings and releases global state: the result
+        ///  cache is emptied and disabled, the log callback removed and the small-run
+        ///  fast path re-enabled. Live handles stay valid and must still be freed.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_shutdown")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_shutdown();
+
+        /// <summary>
+        ///  Routes all of the library's native allocations through host callbacks,
+        ///  e.g. to attribute leaks in a long-running service. Memory must be freed by
+        ///  the allocator that returned it, so this must be the first call into the
+        ///  library and cannot be undone; `realloc_fn` may be null, in which case
+        ///  resizing allocates, copies and frees. The callbacks may be called on any
+        ///  thread and must not call back into the library.
+        ///
+        ///  Returns `NullArgument` if `alloc_fn` or `free_fn` is null, and
+        ///  `Unsupported` if the library was built without the `host-allocator`
+        ///  feature or has already allocated.
+        ///
+        ///  # Safety
+        ///
+        ///  `alloc_fn`, `free_fn` and `realloc_fn` must be null or behave like
+        ///  `malloc`, `free` and `realloc` when called with `user_data` from any
+        ///  thread.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_set_allocator")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_set_allocator(delegate* unmanaged[Cdecl]<nuint, nuint, void*, void*> alloc_fn, delegate* unmanaged[Cdecl]<void*, nuint, nuint, void*, void> free_fn, delegate* unmanaged[Cdecl]<void*, nuint, nuint, nuint, void*, void*> realloc_fn, void* user_data);
+
+        /// <summary>
+        ///  Installs the callback that receives diagnostic messages, such as the
+        ///  warnings of `HarfRustParseMode::Tolerant`, replacing any previous one.
+        ///  A null callback removes it. `user_data` is passed back on every call.
+        ///
+        ///  # Safety
+        ///
+        ///  `callback` must be null or safe to call with `user_data` and a
+        ///  NUL-terminated message from any thread.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_set_log_callback")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_set_log_callback(delegate* unmanaged[Cdecl]<HarfRustLogLevel, byte*, void*, void> callback, void* user_data);
+
+        /// <summary>
+        ///  Enables (non-zero, the default) or disables the small-run fast path:
+        ///  `harfrust_shape`, `harfrust_shape_with_features` and `harfrust_shape_full`
+        ///  (without variations) answer left-to-right Latin or Common runs of up to
+        ///  8 printable ASCII characters directly from `cmap` and `hmtx` when none of
+        ///  their glyphs can start a GSUB or GPOS lookup and the font has no AAT
+        ///  tables or legacy `kern` kerning. Results are the same either way.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_set_small_run_fast_path")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_set_small_run_fast_path(int enabled);
+
+        /// <summary>
+        ///  Enables the process-wide cache of shaping results, or changes its limits.
+        ///  `harfrust_shape`, `harfrust_shape_with_features` and `harfrust_shape_full`
+        ///  then answer repeated combinations of font, text, segment properties,
+        ///  features and variations from the cache. The least recently used entries
+        ///  are evicted beyond `max_entries` results or about `max_bytes` bytes of
+        ///  memory. A limit of 0 disables the cache and frees its entries (the
+        ///  default). Always returns `Ok`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_cache_configure")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_cache_configure(uint max_entries, ulong max_bytes);
+
+        /// <summary>
+        ///  Drops every cached shaping result, keeping the cache enabled.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_cache_clear")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_cache_clear();
+
+        /// <summary>
+        ///  Writes the number of cached shaping results and the approximate memory
+        ///  they use to the given (optional) pointers.
+        ///
+        ///  # Safety
+        ///
+        ///  `out_entries` must be null or point to a writable `u32`, and `out_bytes`
+        ///  must be null or point to a writable `u64`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_cache_stats")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_cache_stats(uint* out_entries, ulong* out_bytes);
+
+        /// <summary>
+        ///  Writes the native memory and live handle counts of the whole process to
+        ///  `out`, for monitoring memory the managed garbage collector cannot see.
+        ///  Handles retained with `harfrust_font_retain` count once. Returns
+        ///  `NullArgument` if `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `out` must be null or point to a writable `HarfRustMemoryStats`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_memory_stats")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_memory_stats(HarfRustMemoryStats* @out);
+
+        /// <summary>
+        ///  Returns 1 if the glyph buffer was produced by fallback shaping without
+        ///  layout tables (see `harfrust_shape_fallback`), 0 if not, or -1 if the
+        ///  buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_is_degraded")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_is_degraded(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Returns the `HarfRustResultFlags` bits describing how a shaping result
+        ///  was produced, or a negative status if the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_flags")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_flags(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Writes the number of .notdef glyphs and inserted dotted circles in a
+        ///  shaping result to `out`, for logging text quality without reading the
+        ///  glyph arrays. `font` is the font the buffer was shaped with, which
+        ///  identifies its dotted circle glyph. Returns `NullArgument` if the font,
+        ///  buffer or `out` is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles, and `out` must be null
+        ///  or point to a writable `HarfRustShapeSummary`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_summary")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_glyph_buffer_get_summary(HarfRustFont* font, HarfRustGlyphBuffer* buffer, HarfRustShapeSummary* @out);
+
+        /// <summary>
+        ///  Returns the number of glyphs in the glyph buffer.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_len")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_len(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Returns a pointer to the glyph info array.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_infos")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphInfo* harfrust_glyph_buffer_get_infos(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Returns a pointer to the glyph position array.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_positions")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphPosition* harfrust_glyph_buffer_get_positions(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Recovers the reading order of a line for text extraction. `runs` are
+        ///  the horizontal runs of the line as placed on the page, left to right,
+        ///  possibly shaped by separate calls with different fonts and directions. The
+        ///  characters of their text are written to `out` in logical order, with
+        ///  their positions on the line; text that maps to no glyph is omitted.
+        ///  `direction` is the line's base direction, or `Invalid` to take it from
+        ///  the text at the line's ends. At most `capacity` entries are written.
+        ///  Returns the total number of characters, -1 (`NullArgument`) if `runs`,
+        ///  a run's glyphs or its text is null, or -2 (`InvalidArgument`) if a length
+        ///  is negative or `direction` is vertical.
+        ///
+        ///  # Safety
+        ///
+        ///  `runs` must be null or point to `num_runs` entries, and in each run
+        ///  `glyphs` must be null or a live handle and `text` must be null or point to
+        ///  `len` UTF-16 code units; and `out` must be null or have room for
+        ///  `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_line_extract_text")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_line_extract_text(HarfRustLineRun* runs, uint num_runs, HarfRustDirection direction, HarfRustExtractedChar* @out, uint capacity);
+
+        /// <summary>
+        ///  Writes, for each glyph, the range of the UTF-16 source text it represents.
+        ///  Clusters are expected to be UTF-16 offsets (as produced by
+        ///  `harfrust_buffer_add_utf16`). When several glyphs share a cluster, the first
+        ///  one in buffer order carries the cluster's whole text and the others get an
+        ///  empty range, so concatenating the ranges never duplicates text. Ranges are
+        ///  clamped to `len` and never split a surrogate pair.
+        ///  At most `capacity` entries are written. Returns the number of glyphs, -1
+        ///  (`NullArgument`) if the buffer or text is null or -2 (`InvalidArgument`) if
+        ///  `len` is negative.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle; `text` must be null or point to
+        ///  `len` UTF-16 code units; and `out` must be null or have room for
+        ///  `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_text_ranges")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_text_ranges(HarfRustGlyphBuffer* buffer, ushort* text, int len, HarfRustTextRange* @out, uint capacity);
+
+        /// <summary>
+        ///  Writes, for each glyph, which substitution produced it: the kind of GSUB
+        ///  lookup, its index and feature, the alternate chosen and the input text
+        ///  range. harfrust does not record the lookups it applies, so the trace is
+        ///  reconstructed from the font's GSUB table on request; shaping itself costs
+        ///  nothing extra. Glyphs that several lookups can produce are credited to the
+        ///  most likely one, so the trace is a debugging aid rather than exact.
+        ///  `text` is the UTF-16 text the buffer was shaped from, as for
+        ///  `harfrust_glyph_buffer_get_text_ranges`.
+        ///  At most `capacity` entries are written. Returns the number of glyphs, -1
+        ///  (`NullArgument`) if the font, buffer or text is null or -2
+        ///  (`InvalidArgument`) if `len` is negative.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` and `buffer` must be null or live handles; `text` must be null or
+        ///  point to `len` UTF-16 code units; and `out` must be null or have room for
+        ///  `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_substitution_trace")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_substitution_trace(HarfRustFont* font, HarfRustGlyphBuffer* buffer, ushort* text, int len, HarfRustGlyphTrace* @out, uint capacity);
+
+        /// <summary>
+        ///  Writes the summed advance of each cluster, in glyph buffer order. A cluster
+        ///  entry covers a run of consecutive glyphs with the same cluster value.
+        ///  At most `capacity` entries are written. Returns the total number of
+        ///  clusters, so a call with a null `out` can be used to size the array.
+        ///  Returns -1 if the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `out` must be null or have
+        ///  room for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_cluster_advances")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_cluster_advances(HarfRustGlyphBuffer* buffer, HarfRustClusterAdvance* @out, uint capacity);
+
+        /// <summary>
+        ///  Writes the permutation between the visual and logical order of the
+        ///  buffer's clusters, so selection and text extraction can convert between
+        ///  them. Visual indices are those of `harfrust_glyph_buffer_get_cluster_advances`
+        ///  (glyph order, reversed for right-to-left runs); logical indices order the
+        ///  clusters by cluster value, i.e. by text position. `out_visual_to_logical[v]`
+        ///  receives the logical index of visual cluster `v` and
+        ///  `out_logical_to_visual[l]` the visual index of logical cluster `l`; either
+        ///  may be null. At most `capacity` entries are written to each. Returns the
+        ///  total number of clusters, so a call with null arrays can be used to size
+        ///  them. Returns -1 if the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle; `out_visual_to_logical` must be
+        ///  null or have room for `capacity` entries; and `out_logical_to_visual` must
+        ///  be null or have room for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_cluster_order")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_cluster_order(HarfRustGlyphBuffer* buffer, uint* out_visual_to_logical, uint* out_logical_to_visual, uint capacity);
+
+        /// <summary>
+        ///  Copies the per-glyph transforms of a synthesized layout (such as
+        ///  `harfrust_shape_small_caps`) into `out`. This is an opt-in companion to
+        ///  the position array: glyphs without a transform report scale 1.0 and
+        ///  rotation 0. At most `capacity` entries are written. Returns the number of
+        ///  glyphs, or -1 if the buffer is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `out` must be null or have
+        ///  room for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_transforms")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_transforms(HarfRustGlyphBuffer* buffer, HarfRustGlyphTransform* @out, uint capacity);
+
+        /// <summary>
+        ///  Copies only the scale factor of each glyph transform into `out`; see
+        ///  `harfrust_glyph_buffer_get_transforms`.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `out` must be null or have
+        ///  room for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_get_scales")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_get_scales(HarfRustGlyphBuffer* buffer, float* @out, uint capacity);
+
+        /// <summary>
+        ///  Writes the glyph IDs as the 2-byte big-endian character codes of an
+        ///  Identity-H (or Identity-V) encoded PDF string, in glyph buffer order, ready
+        ///  to be placed in a string operand of Tj or TJ. Only whole glyphs are
+        ///  written, at most `capacity / 2` of them. Returns the number of bytes the
+        ///  whole buffer needs, so a call with a null `out` can be used to size the
+        ///  array. Returns -1 if the buffer is null, or -3 if a glyph ID does not fit
+        ///  in two bytes.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle, and `out` must be null or have
+        ///  room for `capacity` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_to_identity_h")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_to_identity_h(HarfRustGlyphBuffer* buffer, byte* @out, uint capacity);
+
+        /// <summary>
+        ///  Compares the shaped advances of a buffer with the widths a PDF font
+        ///  dictionary declares for its glyphs, so a viewer can decide whether to
+        ///  trust the embedded widths or the font. Character codes are glyph IDs, as
+        ///  written by `harfrust_glyph_buffer_to_identity_h`: `widths[i]` is the
+        ///  width of glyph `first_code + i` in PDF glyph space (1000 units per em),
+        ///  and glyphs outside the array have `missing_width` (the font descriptor's
+        ///  MissingWidth). The buffer must hold unscaled advances shaped with `font`.
+        ///  Each glyph's declared width minus its shaped advance, in PDF glyph space,
+        ///  is written to `out` in glyph buffer order: positive where the PDF draws
+        ///  the glyph wider than the font. At most `capacity` deltas are written.
+        ///  Returns the number of glyphs, so a call with a null `out` can be used to
+        ///  size the array. Returns -1 if the buffer or font is null, or if `widths`
+        ///  is null with a non-zero count.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` and `font` must be null or live handles; `widths` must be null or
+        ///  point to `num_widths` entries; and `out` must be null or have room for
+        ///  `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_width_deltas")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_glyph_buffer_width_deltas(HarfRustGlyphBuffer* buffer, HarfRustFont* font, uint first_code, float* widths, uint num_widths, float missing_width, float* @out, uint capacity);
+
+        /// <summary>
+        ///  Creates a new glyph buffer holding only the glyphs whose cluster lies in
+        ///  `[start_cluster, end_cluster)`, in their original order. Since whole
+        ///  clusters are selected, a ligature is never split. The source buffer is not
+        ///  modified. Returns null if the buffer is null or the range is empty.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_slice")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustGlyphBuffer* harfrust_glyph_buffer_slice(HarfRustGlyphBuffer* buffer, uint start_cluster, uint end_cluster);
+
+        /// <summary>
+        ///  Clears the glyph buffer and returns a new unicode buffer for reuse.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_into_buffer")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBuffer* harfrust_glyph_buffer_into_buffer(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Frees a glyph buffer previously created by `harfrust_shape`.
+        ///
+        ///  # Safety
+        ///
+        ///  `buffer` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_glyph_buffer_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_glyph_buffer_free(HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Computes the glyph closure of a seed glyph set: the seeds plus .notdef,
+        ///  every glyph reachable from them through GSUB substitutions, and the
+        ///  components of composite glyphs. GSUB traversal can be limited to the given
+        ///  OpenType script tags and feature tags (4 bytes as u32); a null pointer
+        ///  selects all of them. Writes the closure sorted by glyph ID; at most
+        ///  `capacity` entries are written. Returns the closure size, so a call with a
+        ///  null `out` can be used to size the array. Returns -1 if the font is null,
+        ///  or -2 if `glyphs` is null with a non-zero count.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `glyphs` must be null or point to
+        ///  `count` glyph IDs; `scripts` must be null or point to `num_scripts`
+        ///  entries; `features` must be null or point to `num_features` entries; and
+        ///  `out` must be null or have room for `capacity` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_glyph_closure")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_font_glyph_closure(HarfRustFont* font, uint* glyphs, uint count, uint* scripts, uint num_scripts, uint* features, uint num_features, uint* @out, uint capacity);
+
+        /// <summary>
+        ///  Builds a subset of a TrueType-outline (`glyf`) font that keeps the glyph
+        ///  closure of `glyphs` (see `harfrust_font_glyph_closure`). In `Compact` mode
+        ///  the kept glyphs are renumbered in glyph ID order, so the new ID of a glyph
+        ///  is its index in the closure, and tables indexed by glyph ID (cmap, layout
+        ///  and color tables) are dropped. In `RetainGids` mode glyph IDs and all other
+        ///  tables are unchanged. The family, unique, full and PostScript names are
+        ///  prefixed with a six-letter subset tag ("ABCDEF+"); the new PostScript name
+        ///  is written to `out_name` as a NUL-terminated string, truncated to
+        ///  `name_capacity` bytes (72 always suffice for valid names). `out_name` may
+        ///  be null. Returns null if the font is null, `glyphs` is null with a
+        ///  non-zero count, or the font has no `glyf` outlines.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle; `glyphs` must be null or point to
+        ///  `count` glyph IDs; and `out_name` must be null or have room for
+        ///  `name_capacity` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_subset")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBlob* harfrust_font_subset(HarfRustFont* font, uint* glyphs, uint count, HarfRustSubsetMode mode, byte* out_name, uint name_capacity);
+
+        /// <summary>
+        ///  Creates an empty subsetter.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_subsetter_new")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustSubsetter* harfrust_subsetter_new();
+
+        /// <summary>
+        ///  Adds glyph IDs to the subsetter's glyph set.
+        ///
+        ///  # Safety
+        ///
+        ///  `subsetter` must be null or a live handle, and `glyphs` must be null or
+        ///  point to `count` glyph IDs.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_subsetter_add_glyphs")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_subsetter_add_glyphs(HarfRustSubsetter* subsetter, uint* glyphs, uint count);
+
+        /// <summary>
+        ///  Adds every glyph of a shaping result to the subsetter's glyph set. The
+        ///  glyph buffer is not consumed.
+        ///
+        ///  # Safety
+        ///
+        ///  `subsetter` and `buffer` must be null or live handles.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_subsetter_add_glyph_buffer")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_subsetter_add_glyph_buffer(HarfRustSubsetter* subsetter, HarfRustGlyphBuffer* buffer);
+
+        /// <summary>
+        ///  Returns the number of distinct glyphs added so far (before closure), or -1
+        ///  if the subsetter is null.
+        ///
+        ///  # Safety
+        ///
+        ///  `subsetter` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_subsetter_glyph_count")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_subsetter_glyph_count(HarfRustSubsetter* subsetter);
+
+        /// <summary>
+        ///  Builds the subset of `font` for the accumulated glyph set; see
+        ///  `harfrust_font_subset` for `mode`, `out_name` and null returns. The
+        ///  subsetter is not consumed and can keep accumulating.
+        ///
+        ///  # Safety
+        ///
+        ///  `subsetter` and `font` must be null or live handles, and `out_name` must
+        ///  be null or have room for `name_capacity` bytes.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_subsetter_build")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBlob* harfrust_subsetter_build(HarfRustSubsetter* subsetter, HarfRustFont* font, HarfRustSubsetMode mode, byte* out_name, uint name_capacity);
+
+        /// <summary>
+        ///  Frees a subsetter created by `harfrust_subsetter_new`.
+        ///
+        ///  # Safety
+        ///
+        ///  `subsetter` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_subsetter_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_subsetter_free(HarfRustSubsetter* subsetter);
+
+        /// <summary>
+        ///  Returns a pointer to the bytes of a blob.
+        ///
+        ///  # Safety
+        ///
+        ///  `blob` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_blob_get_data")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial byte* harfrust_blob_get_data(HarfRustBlob* blob);
+
+        /// <summary>
+        ///  Returns the length of a blob in bytes.
+        ///
+        ///  # Safety
+        ///
+        ///  `blob` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_blob_len")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_blob_len(HarfRustBlob* blob);
+
+        /// <summary>
+        ///  Frees a blob returned by the library.
+        ///
+        ///  # Safety
+        ///
+        ///  `blob` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_blob_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_blob_free(HarfRustBlob* blob);
+
+        /// <summary>
+        ///  Builds a static TrueType font from a variable font at the given axis
+        ///  values (e.g. wght=700), for consumers that don't support variable fonts.
+        ///  Outlines and advances are evaluated at that location; composite glyphs
+        ///  are flattened, hinting instructions dropped and variation tables removed.
+        ///  Axes not listed keep their default; a wght setting also becomes the OS/2
+        ///  weight class. Static fonts yield an equivalent static copy. Returns null if
+        ///  the font is null, `variations` is null with a non-zero count, or the font
+        ///  has no `glyf` outlines (CFF2 is not supported).
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `variations` must be null or
+        ///  point to `num_variations` variations.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_instantiate")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustBlob* harfrust_font_instantiate(HarfRustFont* font, HarfRustVariation* variations, uint num_variations);
+
+        /// <summary>
+        ///  Renders a color glyph (COLR/CPAL or embedded color bitmap) at the given
+        ///  pixels-per-em into a premultiplied RGBA bitmap.
+        ///  `palette_index` selects the CPAL palette; out-of-range values use palette 0.
+        ///  Returns null, recording `InvalidArgument`, if the glyph has no color
+        ///  representation, or `Unsupported` if its only one is an SVG document, which
+        ///  is not rendered.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_render_color_glyph")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustColorBitmap* harfrust_font_render_color_glyph(HarfRustFont* font, uint glyph_id, float ppem, uint palette_index);
+
+        /// <summary>
+        ///  Renders a color glyph as `harfrust_font_render_color_glyph` does, with
+        ///  `overrides_len` entries of the palette replaced by `overrides` (which may
+        ///  be null if the length is 0). An override with index 0xFFFF sets the text
+        ///  foreground color that COLR glyphs may paint with; overrides of entries the
+        ///  palette lacks are ignored. Embedded color bitmaps are unaffected.
+        ///
+        ///  # Safety
+        ///
+        ///  `font` must be null or a live handle, and `overrides` must be null or
+        ///  point to `overrides_len` entries.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_font_render_color_glyph_with_overrides")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustColorBitmap* harfrust_font_render_color_glyph_with_overrides(HarfRustFont* font, uint glyph_id, float ppem, uint palette_index, HarfRustColorOverride* overrides, uint overrides_len);
+
+        /// <summary>
+        ///  Writes the dimensions and placement of a color bitmap to `out`.
+        ///
+        ///  # Safety
+        ///
+        ///  `bitmap` must be null or a live handle, and `out` must be null or point to
+        ///  a writable `HarfRustBitmapInfo`.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_color_bitmap_get_info")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial HarfRustStatus harfrust_color_bitmap_get_info(HarfRustColorBitmap* bitmap, HarfRustBitmapInfo* @out);
+
+        /// <summary>
+        ///  Returns a pointer to the premultiplied RGBA pixel data of a color bitmap.
+        ///
+        ///  # Safety
+        ///
+        ///  `bitmap` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_color_bitmap_get_pixels")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial byte* harfrust_color_bitmap_get_pixels(HarfRustColorBitmap* bitmap);
+
+        /// <summary>
+        ///  Frees a bitmap previously created by `harfrust_font_render_color_glyph`.
+        ///
+        ///  # Safety
+        ///
+        ///  `bitmap` must be null or a live handle.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_color_bitmap_free")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_color_bitmap_free(HarfRustColorBitmap* bitmap);
+
+        /// <summary>
+        ///  Allocates memory in the WASM linear memory.
+        ///  Used by the host to allocate space for passing data to WASM.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_alloc")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial int harfrust_alloc(int size);
+
+        /// <summary>
+        ///  Frees memory allocated by harfrust_alloc.
+        ///
+        ///  # Safety
+        ///
+        ///  `ptr` must be 0 or an address returned by `harfrust_alloc` for the same
+        ///  `size` that has not been freed.
+        /// </summary>
+        [LibraryImport(__DllName, EntryPoint = "harfrust_dealloc")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(CallConvCdecl) })]
+        internal static partial void harfrust_dealloc(int ptr, int size);
+
+
+    }
+
+    /// <summary>
+    ///  Glyph information after shaping.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustGlyphInfo
+    {
+        /// <summary>
+        ///  The glyph ID in the font.
+        /// </summary>
+        public uint glyph_id;
+        /// <summary>
+        ///  The cluster index (position in original text).
+        /// </summary>
+        public uint cluster;
+    }
+
+    /// <summary>
+    ///  Glyph positioning information after shaping.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustGlyphPosition
+    {
+        /// <summary>
+        ///  Horizontal advance after drawing this glyph.
+        /// </summary>
+        public int x_advance;
+        /// <summary>
+        ///  Vertical advance after drawing this glyph.
+        /// </summary>
+        public int y_advance;
+        /// <summary>
+        ///  Horizontal offset for drawing.
+        /// </summary>
+        public int x_offset;
+        /// <summary>
+        ///  Vertical offset for drawing.
+        /// </summary>
+        public int y_offset;
+    }
+
+    /// <summary>
+    ///  OpenType feature for shaping.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustFeature
+    {
+        /// <summary>
+        ///  The feature tag (e.g. 'liga', 'kern').
+        /// </summary>
+        public uint tag;
+        /// <summary>
+        ///  The value of the feature (0 = disabled, 1 = enabled, or other values).
+        /// </summary>
+        public uint value;
+        /// <summary>
+        ///  The first cluster value the feature applies to. Cluster values are
+        ///  UTF-16 offsets for `harfrust_buffer_add_utf16` and UTF-8 byte offsets for
+        ///  `harfrust_buffer_add_str`; see `harfrust_feature_set_range`.
+        /// </summary>
+        public uint start;
+        /// <summary>
+        ///  The cluster value one past the last one the feature applies to
+        ///  (u32::MAX for end).
+        /// </summary>
+        public uint end;
+    }
+
+    /// <summary>
+    ///  Font variation settings.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustVariation
+    {
+        /// <summary>
+        ///  The variation tag (e.g. 'wght', 'wdth').
+        /// </summary>
+        public uint tag;
+        /// <summary>
+        ///  The variation value (in design units).
+        /// </summary>
+        public float value;
+    }
+
+    /// <summary>
+    ///  One run of UTF-16 text to shape in a batch.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustRunDescriptor
+    {
+        /// <summary>
+        ///  Pointer to the UTF-16 text of the run.
+        /// </summary>
+        public ushort* text;
+        /// <summary>
+        ///  Length of the text in UTF-16 code units.
+        /// </summary>
+        public int len;
+        /// <summary>
+        ///  Run direction (`Invalid` to guess from the text).
+        /// </summary>
+        public HarfRustDirection direction;
+        /// <summary>
+        ///  ISO 15924 script tag (0 to guess from the text).
+        /// </summary>
+        public uint script;
+        /// <summary>
+        ///  Null-terminated BCP 47 language tag (null to use the default).
+        /// </summary>
+        public byte* language;
+        /// <summary>
+        ///  Features for this run (may be null).
+        /// </summary>
+        public HarfRustFeature* features;
+        /// <summary>
+        ///  Number of entries in `features`.
+        /// </summary>
+        public uint num_features;
+        /// <summary>
+        ///  Nonzero to normalize spaces as `harfrust_buffer_set_normalize_spaces`
+        ///  does.
+        /// </summary>
+        public int normalize_spaces;
+    }
+
+    /// <summary>
+    ///  A shaped run placed on a line, for `harfrust_line_extract_text`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustLineRun
+    {
+        /// <summary>
+        ///  The shaping result of the run.
+        /// </summary>
+        public HarfRustGlyphBuffer* glyphs;
+        /// <summary>
+        ///  The UTF-16 text the run was shaped from; clusters are offsets into it.
+        /// </summary>
+        public ushort* text;
+        /// <summary>
+        ///  Length of the text in UTF-16 code units.
+        /// </summary>
+        public int len;
+        /// <summary>
+        ///  Pen position of the run's first glyph on the line.
+        /// </summary>
+        public float x;
+        /// <summary>
+        ///  Line units per glyph position unit, e.g. font size / units per em.
+        /// </summary>
+        public float scale;
+    }
+
+    /// <summary>
+    ///  A character of a line in reading order, with the box it occupies.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustExtractedChar
+    {
+        /// <summary>
+        ///  The Unicode codepoint.
+        /// </summary>
+        public uint codepoint;
+        /// <summary>
+        ///  Index of the run the character comes from.
+        /// </summary>
+        public uint run;
+        /// <summary>
+        ///  Offset of the character in its run's text, in UTF-16 code units.
+        /// </summary>
+        public uint offset;
+        /// <summary>
+        ///  Left edge of the character on the line.
+        /// </summary>
+        public float x;
+        /// <summary>
+        ///  Width of the character; a ligature's width is split among its
+        ///  characters.
+        /// </summary>
+        public float width;
+    }
+
+    /// <summary>
+    ///  A single (codepoint, glyph) mapping from the font's cmap.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustCmapEntry
+    {
+        /// <summary>
+        ///  The Unicode codepoint.
+        /// </summary>
+        public uint codepoint;
+        /// <summary>
+        ///  The glyph ID the codepoint maps to.
+        /// </summary>
+        public uint glyph_id;
+    }
+
+    /// <summary>
+    ///  Total advance of a run of consecutive glyphs sharing a cluster.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustClusterAdvance
+    {
+        /// <summary>
+        ///  The cluster value shared by the glyphs.
+        /// </summary>
+        public uint cluster;
+        /// <summary>
+        ///  Index of the first glyph of the cluster in the glyph buffer.
+        /// </summary>
+        public uint first_glyph;
+        /// <summary>
+        ///  Number of glyphs in the cluster.
+        /// </summary>
+        public uint glyph_count;
+        /// <summary>
+        ///  Sum of the glyphs' horizontal advances.
+        /// </summary>
+        public int x_advance;
+        /// <summary>
+        ///  Sum of the glyphs' vertical advances.
+        /// </summary>
+        public int y_advance;
+    }
+
+    /// <summary>
+    ///  Extra per-glyph transform produced by synthesized layout effects (small
+    ///  caps, superscripts, rotated glyphs). Applied around the glyph origin after
+    ///  positioning; advances and offsets already account for it.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustGlyphTransform
+    {
+        /// <summary>
+        ///  Uniform scale factor for the glyph outline (1.0 = unscaled).
+        /// </summary>
+        public float scale;
+        /// <summary>
+        ///  Counter-clockwise rotation of the glyph outline in degrees, e.g. -90
+        ///  for glyphs set sideways by `harfrust_shape_vertical_mixed`.
+        /// </summary>
+        public float rotation;
+    }
+
+    /// <summary>
+    ///  Superscript or subscript size and offset from the OS/2 table, in font units.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustScriptMetrics
+    {
+        /// <summary>
+        ///  Horizontal font size of the scripted glyphs.
+        /// </summary>
+        public int x_size;
+        /// <summary>
+        ///  Vertical font size of the scripted glyphs.
+        /// </summary>
+        public int y_size;
+        /// <summary>
+        ///  Horizontal offset of the scripted glyphs.
+        /// </summary>
+        public int x_offset;
+        /// <summary>
+        ///  Vertical offset of the scripted glyphs: upwards from the baseline for
+        ///  superscripts, downwards for subscripts (as in OS/2).
+        /// </summary>
+        public int y_offset;
+    }
+
+    /// <summary>
+    ///  A range of UTF-16 code units in the source text.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustTextRange
+    {
+        /// <summary>
+        ///  Offset of the first code unit.
+        /// </summary>
+        public uint start;
+        /// <summary>
+        ///  Number of code units (0 if the glyph carries no text of its own).
+        /// </summary>
+        public uint length;
+    }
+
+    /// <summary>
+    ///  Where a shaped glyph came from; see
+    ///  `harfrust_glyph_buffer_get_substitution_trace`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustGlyphTrace
+    {
+        public HarfRustSubstitutionKind kind;
+        /// <summary>
+        ///  Tag of the feature the lookup belongs to, directly or through a
+        ///  contextual lookup, or 0.
+        /// </summary>
+        public uint feature;
+        /// <summary>
+        ///  Index of the GSUB lookup, or -1.
+        /// </summary>
+        public int lookup_index;
+        /// <summary>
+        ///  Position of the glyph in its alternate set for `Alternate`, or -1.
+        /// </summary>
+        public int alternate_index;
+        /// <summary>
+        ///  UTF-16 range of the input characters: the glyph's whole cluster, so
+        ///  the components of a ligature.
+        /// </summary>
+        public uint input_start;
+        public uint input_length;
+    }
+
+    /// <summary>
+    ///  Font-wide metrics a PDF text extractor checks font descriptors and width
+    ///  arrays against, in font units.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustPdfMetrics
+    {
+        public int units_per_em;
+        /// <summary>
+        ///  Typographic ascent (positive, above the baseline).
+        /// </summary>
+        public int ascent;
+        /// <summary>
+        ///  Typographic descent (negative, below the baseline).
+        /// </summary>
+        public int descent;
+        /// <summary>
+        ///  Most common non-zero glyph advance, the natural CID font DW.
+        /// </summary>
+        public int default_width;
+        /// <summary>
+        ///  Advance of the .notdef glyph, the natural MissingWidth.
+        /// </summary>
+        public int missing_width;
+    }
+
+    /// <summary>
+    ///  Font-wide metrics for line layout, in font units, as reported by
+    ///  `harfrust_font_get_metrics`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustFontMetrics
+    {
+        public int units_per_em;
+        /// <summary>
+        ///  Typographic ascent (positive, above the baseline).
+        /// </summary>
+        public int ascent;
+        /// <summary>
+        ///  Typographic descent (negative, below the baseline).
+        /// </summary>
+        public int descent;
+        /// <summary>
+        ///  Extra space between lines, added to ascent minus descent.
+        /// </summary>
+        public int line_gap;
+        /// <summary>
+        ///  See `harfrust_font_get_x_height`.
+        /// </summary>
+        public int x_height;
+        /// <summary>
+        ///  See `harfrust_font_get_cap_height`.
+        /// </summary>
+        public int cap_height;
+    }
+
+    /// <summary>
+    ///  Style and embedding permissions from the OS/2 table, as reported by
+    ///  `harfrust_font_get_style`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustFontStyle
+    {
+        /// <summary>
+        ///  usWeightClass: 100 (thin) to 900 (black), 400 is regular.
+        /// </summary>
+        public uint weight_class;
+        /// <summary>
+        ///  usWidthClass: 1 (ultra-condensed) to 9 (ultra-expanded), 5 is normal.
+        /// </summary>
+        public uint width_class;
+        /// <summary>
+        ///  fsSelection bits, e.g. 0x01 italic, 0x20 bold, 0x40 regular and
+        ///  0x200 oblique.
+        /// </summary>
+        public uint fs_selection;
+        /// <summary>
+        ///  fsType embedding permission bits: 0x02 restricted license, 0x04
+        ///  preview and print, 0x08 editable, 0x100 no subsetting and 0x200
+        ///  bitmap embedding only. 0 is installable, the most permissive.
+        /// </summary>
+        public uint fs_type;
+        /// <summary>
+        ///  1 if fsType allows embedding the outlines in a document.
+        /// </summary>
+        public uint embeddable;
+        /// <summary>
+        ///  1 if fsType allows embedding a subset rather than the whole font.
+        /// </summary>
+        public uint subsettable;
+    }
+
+    /// <summary>
+    ///  Values from the `post` table, as reported by `harfrust_font_post_info`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustPostInfo
+    {
+        /// <summary>
+        ///  Degrees counter-clockwise from vertical; negative for fonts leaning
+        ///  right, as PDF ItalicAngle.
+        /// </summary>
+        public float italic_angle;
+        /// <summary>
+        ///  Top of the underline in font units, negative below the baseline.
+        /// </summary>
+        public int underline_position;
+        /// <summary>
+        ///  Underline thickness in font units.
+        /// </summary>
+        public int underline_thickness;
+        /// <summary>
+        ///  1 if the font is monospaced (PDF FixedPitch flag).
+        /// </summary>
+        public uint is_fixed_pitch;
+    }
+
+    /// <summary>
+    ///  Values from the `head` table, as reported by `harfrust_font_head_info`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustHeadInfo
+    {
+        /// <summary>
+        ///  Bounding box of all glyphs in font units (PDF FontBBox once scaled
+        ///  to 1000 units per em).
+        /// </summary>
+        public int x_min;
+        public int y_min;
+        public int x_max;
+        public int y_max;
+        /// <summary>
+        ///  head flags bits, e.g. 0x01 baseline at y=0 and 0x02 left sidebearing
+        ///  at x=0.
+        /// </summary>
+        public uint flags;
+        /// <summary>
+        ///  macStyle bits: 0x01 bold, 0x02 italic, 0x04 underline, 0x08 outline,
+        ///  0x10 shadow, 0x20 condensed and 0x40 extended.
+        /// </summary>
+        public uint mac_style;
+    }
+
+    /// <summary>
+    ///  Strikeout line from the OS/2 table, in font units.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustStrikeout
+    {
+        /// <summary>
+        ///  Top of the strikeout line, above the baseline.
+        /// </summary>
+        public int position;
+        /// <summary>
+        ///  Strikeout thickness.
+        /// </summary>
+        public int thickness;
+    }
+
+    /// <summary>
+    ///  Advance a glyph is given after shaping, see
+    ///  `harfrust_font_set_advance_overrides`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustAdvanceOverride
+    {
+        public uint glyph_id;
+        /// <summary>
+        ///  Horizontal advance in font units.
+        /// </summary>
+        public int advance;
+    }
+
+    /// <summary>
+    ///  One glyph outline path command. Unused control points are zero.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustPathCommand
+    {
+        /// <summary>
+        ///  The operator.
+        /// </summary>
+        public HarfRustPathVerb verb;
+        /// <summary>
+        ///  First control point (`CubicTo` only).
+        /// </summary>
+        public float x1;
+        public float y1;
+        /// <summary>
+        ///  Second control point (`CubicTo` only).
+        /// </summary>
+        public float x2;
+        public float y2;
+        /// <summary>
+        ///  End point.
+        /// </summary>
+        public float x;
+        public float y;
+    }
+
+    /// <summary>
+    ///  Placement of a single line of text in a rectangle, in the rectangle's
+    ///  units (e.g. points).
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustLineLayout
+    {
+        /// <summary>
+        ///  Font size the line was shaped at.
+        /// </summary>
+        public float font_size;
+        /// <summary>
+        ///  Pen position of the first glyph, from the rectangle's lower-left corner.
+        /// </summary>
+        public float x;
+        /// <summary>
+        ///  Baseline, from the rectangle's lower-left corner.
+        /// </summary>
+        public float y;
+        /// <summary>
+        ///  Total advance of the line.
+        /// </summary>
+        public float width;
+    }
+
+    /// <summary>
+    ///  A shaped run of a multi-run line, for `harfrust_layout_line`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustLayoutRun
+    {
+        /// <summary>
+        ///  The font the run was shaped with.
+        /// </summary>
+        public HarfRustFont* font;
+        /// <summary>
+        ///  The shaping result of the run, with positions in font units.
+        /// </summary>
+        public HarfRustGlyphBuffer* glyphs;
+        /// <summary>
+        ///  Font size of the run, in line units (e.g. points).
+        /// </summary>
+        public float font_size;
+        /// <summary>
+        ///  Baseline shift in line units, positive upwards, as the PDF text rise
+        ///  (Ts): raises superscript runs and lowers subscript runs.
+        /// </summary>
+        public float rise;
+    }
+
+    /// <summary>
+    ///  Where a run of a multi-run line goes, relative to the line's origin on
+    ///  its baseline, in line units.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustRunPlacement
+    {
+        /// <summary>
+        ///  Pen position of the run's first glyph.
+        /// </summary>
+        public float x;
+        /// <summary>
+        ///  Baseline of the run, positive upwards (the run's rise).
+        /// </summary>
+        public float y;
+    }
+
+    /// <summary>
+    ///  Extent of a multi-run line in line units, with each run's ascent and
+    ///  descent moved by its rise.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustLineMetrics
+    {
+        /// <summary>
+        ///  Total advance of the line.
+        /// </summary>
+        public float width;
+        /// <summary>
+        ///  Highest ascent above the line's baseline.
+        /// </summary>
+        public float ascent;
+        /// <summary>
+        ///  Lowest descent, negative below the line's baseline.
+        /// </summary>
+        public float descent;
+        /// <summary>
+        ///  Largest line gap of the line's fonts.
+        /// </summary>
+        public float line_gap;
+    }
+
+    /// <summary>
+    ///  Vertical writing-mode metrics of a glyph in PDF glyph space (1000 units
+    ///  per em), as written to the W2 array of a vertical CID font.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustVerticalMetrics
+    {
+        /// <summary>
+        ///  Vertical displacement; negative, since vertical text runs downwards.
+        /// </summary>
+        public float w1y;
+        /// <summary>
+        ///  Position vector from the horizontal origin to the vertical origin.
+        /// </summary>
+        public float vx;
+        public float vy;
+    }
+
+    /// <summary>
+    ///  Ink bounding box of a glyph in font units, laid out as HarfBuzz's
+    ///  `hb_glyph_extents_t`: y grows upwards, so `height` is negative.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustGlyphExtents
+    {
+        /// <summary>
+        ///  Left edge, relative to the glyph origin.
+        /// </summary>
+        public int x_bearing;
+        /// <summary>
+        ///  Top edge, relative to the baseline.
+        /// </summary>
+        public int y_bearing;
+        public int width;
+        /// <summary>
+        ///  Distance from the top edge to the bottom edge.
+        /// </summary>
+        public int height;
+    }
+
+    /// <summary>
+    ///  Layout of a rendered color glyph bitmap.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustBitmapInfo
+    {
+        /// <summary>
+        ///  Width of the bitmap in pixels.
+        /// </summary>
+        public uint width;
+        /// <summary>
+        ///  Height of the bitmap in pixels.
+        /// </summary>
+        public uint height;
+        /// <summary>
+        ///  Distance from the pen position to the left edge of the bitmap, in pixels.
+        /// </summary>
+        public int left;
+        /// <summary>
+        ///  Distance from the baseline up to the top edge of the bitmap, in pixels.
+        /// </summary>
+        public int top;
+        /// <summary>
+        ///  Number of bytes per row (always `width * 4`).
+        /// </summary>
+        public uint stride;
+    }
+
+    /// <summary>
+    ///  Replacement for one palette entry when rendering color glyphs, e.g. to
+    ///  adapt icons to a dark theme.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustColorOverride
+    {
+        /// <summary>
+        ///  CPAL palette entry index, or 0xFFFF for the text foreground color
+        ///  (black unless overridden).
+        /// </summary>
+        public ushort index;
+        /// <summary>
+        ///  Color components, not premultiplied.
+        /// </summary>
+        public byte red;
+        public byte green;
+        public byte blue;
+        public byte alpha;
+    }
+
+    /// <summary>
+    ///  128-bit cache key, split into two halves.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustCacheKey
+    {
+        /// <summary>
+        ///  Upper 64 bits of the key.
+        /// </summary>
+        public ulong high;
+        /// <summary>
+        ///  Lower 64 bits of the key.
+        /// </summary>
+        public ulong low;
+    }
+
+    /// <summary>
+    ///  Opaque wrapper around harfrust's UnicodeBuffer.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustBuffer
+    {
+    }
+
+    /// <summary>
+    ///  Saved buffer contents and segment properties.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustBufferState
+    {
+    }
+
+    /// <summary>
+    ///  Native memory and handles held by the library, as reported by
+    ///  `harfrust_memory_stats`. Byte counts cover the large allocations only.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustMemoryStats
+    {
+        /// <summary>
+        ///  Font data copied into the library; data shared by several fonts is
+        ///  counted once. Caller-owned data (`harfrust_font_from_data_borrowed`)
+        ///  is not included.
+        /// </summary>
+        public ulong font_data_bytes;
+        /// <summary>
+        ///  Font files mapped into memory by `harfrust_font_from_file`.
+        /// </summary>
+        public ulong mapped_font_bytes;
+        /// <summary>
+        ///  Per-font glyph advance tables.
+        /// </summary>
+        public ulong advance_cache_bytes;
+        /// <summary>
+        ///  Shaping result cache (see `harfrust_cache_configure`).
+        /// </summary>
+        public ulong result_cache_bytes;
+        public uint result_cache_entries;
+        /// <summary>
+        ///  Shape plans cached across all fonts.
+        /// </summary>
+        public uint shape_plans;
+        /// <summary>
+        ///  Shaping buffers kept for reuse across all threads (`arena` feature).
+        /// </summary>
+        public uint pooled_buffers;
+        /// <summary>
+        ///  Live handles by type.
+        /// </summary>
+        public uint fonts;
+        public uint buffers;
+        public uint buffer_states;
+        public uint glyph_buffers;
+        public uint color_bitmaps;
+        public uint subsetters;
+        public uint blobs;
+        public uint shapers;
+        public uint instances;
+    }
+
+    /// <summary>
+    ///  Text quality counts of one shaping result, as reported by
+    ///  `harfrust_glyph_buffer_get_summary`.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustShapeSummary
+    {
+        public uint glyph_count;
+        /// <summary>
+        ///  Glyphs the font has no mapping for (glyph 0, .notdef).
+        /// </summary>
+        public uint notdef_count;
+        /// <summary>
+        ///  Dotted circles (U+25CC) shown as the base of a mark that has none.
+        ///  Dotted circles in the source text are included.
+        /// </summary>
+        public uint dotted_circle_count;
+        /// <summary>
+        ///  1 if the result was produced without layout tables; see
+        ///  `harfrust_glyph_buffer_is_degraded`.
+        /// </summary>
+        public uint degraded;
+    }
+
+    /// <summary>
+    ///  Library-wide settings passed to `harfrust_init`. Zeroed fields take their
+    ///  defaults.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustConfig
+    {
+        /// <summary>
+        ///  Size of the struct in bytes as the caller knows it, so fields added in
+        ///  later versions are left at their defaults for older callers.
+        /// </summary>
+        public uint size;
+        /// <summary>
+        ///  Limits of the shaping result cache, as for `harfrust_cache_configure`;
+        ///  0 disables the cache.
+        /// </summary>
+        public uint result_cache_max_entries;
+        public ulong result_cache_max_bytes;
+        /// <summary>
+        ///  Shape plans kept per font; 0 for the default of 32.
+        /// </summary>
+        public uint plan_cache_capacity;
+        /// <summary>
+        ///  Threads `harfrust_shape_batch_parallel` uses when passed 0; 0 for one
+        ///  per available core. Larger values are clamped to the core count.
+        /// </summary>
+        public uint num_threads;
+        /// <summary>
+        ///  Non-zero validates handles in release builds too (see the `checked`
+        ///  feature). Debug builds always do.
+        /// </summary>
+        public int check_handles;
+        /// <summary>
+        ///  Least severe message passed to the log callback.
+        /// </summary>
+        public HarfRustLogLevel log_level;
+    }
+
+    /// <summary>
+    ///  Opaque wrapper that owns font data and provides shaping capabilities.
+    ///  The `FontRef`, `ShaperData` and units per em are built once at creation
+    ///  and reused by every call; only the lightweight `Shaper` view is rebuilt.
+    ///  Shape plans are cached per combination of segment properties, features
+    ///  and variation instance.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustFont
+    {
+    }
+
+    /// <summary>
+    ///  Opaque wrapper around harfrust's GlyphBuffer (shaping result).
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustGlyphBuffer
+    {
+    }
+
+    /// <summary>
+    ///  Opaque wrapper around a rendered color glyph (premultiplied RGBA).
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustColorBitmap
+    {
+    }
+
+    /// <summary>
+    ///  Opaque glyph set accumulated across many shaping results (e.g. every page
+    ///  of a document) and subset once at the end.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustSubsetter
+    {
+    }
+
+    /// <summary>
+    ///  Opaque byte array produced by the library (e.g. a subset font).
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustBlob
+    {
+    }
+
+    /// <summary>
+    ///  Opaque shaping configuration: a font plus a variation instance computed
+    ///  once and reused for every shape call. Holds a reference to the font.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustShaper
+    {
+    }
+
+    /// <summary>
+    ///  Opaque variable font instance (normalized axis coordinates and derived
+    ///  variation data) for shaping many runs at the same settings.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    internal unsafe partial struct HarfRustInstance
+    {
+    }
+
+
+    /// <summary>
+    ///  Text direction for shaping.
+    /// </summary>
+    internal enum HarfRustDirection : uint
+    {
+        /// <summary>
+        ///  Initial, unset direction.
+        /// </summary>
+        Invalid = 0,
+        /// <summary>
+        ///  Left-to-right text.
+        /// </summary>
+        LeftToRight = 4,
+        /// <summary>
+        ///  Right-to-left text.
+        /// </summary>
+        RightToLeft = 5,
+        /// <summary>
+        ///  Top-to-bottom text.
+        /// </summary>
+        TopToBottom = 6,
+        /// <summary>
+        ///  Bottom-to-top text.
+        /// </summary>
+        BottomToTop = 7,
+    }
+
+    /// <summary>
+    ///  Result of a fallible function. Functions that return a count, size or
+    ///  flag instead report failure with these negative values.
+    /// </summary>
+    internal enum HarfRustStatus : int
+    {
+        /// <summary>
+        ///  The call succeeded.
+        /// </summary>
+        Ok = 0,
+        /// <summary>
+        ///  A required handle or pointer is null.
+        /// </summary>
+        NullArgument = -1,
+        /// <summary>
+        ///  An argument is invalid, e.g. a negative length.
+        /// </summary>
+        InvalidArgument = -2,
+        /// <summary>
+        ///  A value is outside the range the function accepts.
+        /// </summary>
+        OutOfRange = -3,
+        /// <summary>
+        ///  A language tag cannot be parsed.
+        /// </summary>
+        InvalidLanguage = -4,
+        /// <summary>
+        ///  Font data cannot be decoded or parsed, or lacks a required table.
+        /// </summary>
+        InvalidFont = -5,
+        /// <summary>
+        ///  A file cannot be opened or read.
+        /// </summary>
+        Io = -6,
+        /// <summary>
+        ///  Text is not valid UTF-8.
+        /// </summary>
+        InvalidUtf8 = -7,
+        /// <summary>
+        ///  An output array is too small; the size needed was reported.
+        /// </summary>
+        InsufficientCapacity = -8,
+        /// <summary>
+        ///  An internal error was caught; the last-error message has details.
+        /// </summary>
+        Panic = -9,
+        /// <summary>
+        ///  A handle points at an object of another type, or at a freed one.
+        ///  Detected only in debug builds or with the `checked` feature.
+        /// </summary>
+        InvalidHandle = -10,
+        /// <summary>
+        ///  A handle refers to an object that has been freed. Detected only with
+        ///  the `handle-table` feature.
+        /// </summary>
+        StaleHandle = -11,
+        /// <summary>
+        ///  The library was built without the feature the function needs, the
+        ///  call came too late to take effect, the object it would change is
+        ///  frozen (see `harfrust_font_freeze`), or the font data uses a format
+        ///  the library cannot handle, such as SVG glyphs.
+        /// </summary>
+        Unsupported = -12,
+    }
+
+    /// <summary>
+    ///  How much work font creation does up front.
+    /// </summary>
+    internal enum HarfRustParseMode : uint
+    {
+        /// <summary>
+        ///  Parse only what is needed to create the font; tables are read on first use.
+        /// </summary>
+        Lazy = 0,
+        /// <summary>
+        ///  Validate the core tables and warm all caches during creation, so
+        ///  broken fonts fail immediately and the first shape has no extra cost.
+        /// </summary>
+        Eager = 1,
+        /// <summary>
+        ///  Load fonts with recoverable corruption (bad checksums, overlapping or
+        ///  truncated tables, unreadable optional tables), reporting each problem
+        ///  through the log callback. Only unreadable required tables fail.
+        /// </summary>
+        Tolerant = 2,
+    }
+
+    /// <summary>
+    ///  Severity of a message passed to the log callback.
+    /// </summary>
+    internal enum HarfRustLogLevel : uint
+    {
+        Info = 0,
+        Warning = 1,
+        Error = 2,
+    }
+
+    /// <summary>
+    ///  Case transform applied to text as it is added to a buffer.
+    /// </summary>
+    internal enum HarfRustCaseTransform : uint
+    {
+        /// <summary>
+        ///  Text is added unchanged.
+        /// </summary>
+        None = 0,
+        /// <summary>
+        ///  Full Unicode uppercase mapping.
+        /// </summary>
+        Uppercase = 1,
+        /// <summary>
+        ///  Full Unicode lowercase mapping, including final sigma.
+        /// </summary>
+        Lowercase = 2,
+        /// <summary>
+        ///  The first letter of each word is titlecased (CSS `capitalize`).
+        /// </summary>
+        Capitalize = 3,
+    }
+
+    /// <summary>
+    ///  Where a derived font metric came from.
+    /// </summary>
+    internal enum HarfRustMetricSource : uint
+    {
+        /// <summary>
+        ///  Read directly from the font tables.
+        /// </summary>
+        Table = 0,
+        /// <summary>
+        ///  Measured from representative glyph outlines.
+        /// </summary>
+        GlyphOutline = 1,
+        /// <summary>
+        ///  Estimated from the em size because nothing better was available.
+        /// </summary>
+        Estimated = 2,
+    }
+
+    /// <summary>
+    ///  Superscript or subscript placement.
+    /// </summary>
+    internal enum HarfRustScriptPosition : uint
+    {
+        /// <summary>
+        ///  Raised, reduced-size text (e.g. footnote markers).
+        /// </summary>
+        Superscript = 0,
+        /// <summary>
+        ///  Lowered, reduced-size text.
+        /// </summary>
+        Subscript = 1,
+    }
+
+    /// <summary>
+    ///  How well a font supports a script, ordered from worst to best.
+    /// </summary>
+    internal enum HarfRustScriptSupport : uint
+    {
+        /// <summary>
+        ///  The font maps no character of the script.
+        /// </summary>
+        Unsupported = 0,
+        /// <summary>
+        ///  The font maps characters of a script that needs a dedicated shaper,
+        ///  but its GSUB has no lookups for it, so text shapes in logical order
+        ///  without conjuncts, joining or reordering.
+        /// </summary>
+        CmapOnly = 1,
+        /// <summary>
+        ///  The font maps characters of the script and has GSUB lookups for it,
+        ///  or the script needs none.
+        /// </summary>
+        Full = 2,
+    }
+
+    /// <summary>
+    ///  How a subset font numbers its glyphs.
+    /// </summary>
+    internal enum HarfRustSubsetMode : uint
+    {
+        /// <summary>
+        ///  Kept glyphs are renumbered 0..n in original glyph ID order.
+        /// </summary>
+        Compact = 0,
+        /// <summary>
+        ///  Glyphs keep their original IDs; unused glyphs are left empty, so
+        ///  content that references glyph IDs stays valid.
+        /// </summary>
+        RetainGids = 1,
+    }
+
+    /// <summary>
+    ///  Horizontal alignment of a line, matching the PDF form field quadding (Q)
+    ///  values.
+    /// </summary>
+    internal enum HarfRustTextAlign : uint
+    {
+        Left = 0,
+        Center = 1,
+        Right = 2,
+    }
+
+    /// <summary>
+    ///  Unit of the cluster values in a buffer, determined by how text was added.
+    /// </summary>
+    internal enum HarfRustClusterUnit : uint
+    {
+        /// <summary>
+        ///  UTF-16 code unit offsets (`harfrust_buffer_add_utf16`).
+        /// </summary>
+        Utf16 = 0,
+        /// <summary>
+        ///  UTF-8 byte offsets (`harfrust_buffer_add_str`).
+        /// </summary>
+        Utf8 = 1,
+    }
+
+    /// <summary>
+    ///  How a buffer shapes soft hyphens (U+00AD), which mark where a word may be
+    ///  hyphenated.
+    /// </summary>
+    internal enum HarfRustSoftHyphen : uint
+    {
+        /// <summary>
+        ///  Invisible and without advance, wherever they are.
+        /// </summary>
+        Hidden = 0,
+        /// <summary>
+        ///  Shown as a hyphen (U+002D) wherever they are.
+        /// </summary>
+        Visible = 1,
+        /// <summary>
+        ///  Shown as a hyphen only at the end of the text, where a line broke at
+        ///  it, and invisible elsewhere: how word processors show them. A layout
+        ///  engine shaping each line separately gets the hyphen on lines that end
+        ///  with one.
+        /// </summary>
+        LineEnd = 2,
+    }
+
+    /// <summary>
+    ///  The kind of GSUB substitution that produced a glyph.
+    /// </summary>
+    internal enum HarfRustSubstitutionKind : uint
+    {
+        /// <summary>
+        ///  The nominal glyph of a character in its cluster.
+        /// </summary>
+        None = 0,
+        Single = 1,
+        Multiple = 2,
+        Alternate = 3,
+        Ligature = 4,
+        ReverseChainSingle = 5,
+        /// <summary>
+        ///  Not the output of any GSUB lookup (e.g. a glyph from a shaper
+        ///  fallback such as the dotted circle).
+        /// </summary>
+        Unknown = 6,
+    }
+
+    /// <summary>
+    ///  Path construction operator, matching the PDF path operators m, l, c and h.
+    /// </summary>
+    internal enum HarfRustPathVerb : uint
+    {
+        /// <summary>
+        ///  Begins a new subpath at (x, y).
+        /// </summary>
+        MoveTo = 0,
+        /// <summary>
+        ///  Straight line to (x, y).
+        /// </summary>
+        LineTo = 1,
+        /// <summary>
+        ///  Cubic Bézier curve to (x, y) with control points (x1, y1) and (x2, y2).
+        /// </summary>
+        CubicTo = 2,
+        /// <summary>
+        ///  Closes the current subpath; (x, y) is the current point.
+        /// </summary>
+        Close = 3,
     }
 
 
@@ -0,0 +1,25 @@
+namespace HarfRust;
+
+/// <summary>
+/// How a run reshaped with a fallback font relates to the text around it.
+/// </summary>
+public enum FallbackContinuity
+{
+    /// <summary>
+    /// The run is shaped on its own, as if it were the whole text, with the features as given.
+    /// </summary>
+    Independent = 0,
+
+    /// <summary>
+    /// The surrounding text is passed as shaping context and feature ranges are moved to the run,
+    /// so joining and contextual forms continue across the font switch.
+    /// </summary>
+    Context = 1,
+
+    /// <summary>
+    /// Like <see cref="Context"/>, but a zero width joiner stands in for the neighbouring text
+    /// wherever the run touches it inside a word, so the run keeps its joining forms whatever
+    /// the neighbouring characters are.
+    /// </summary>
+    Joiners = 2,
+}
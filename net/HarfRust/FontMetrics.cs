@@ -0,0 +1,62 @@
+using System.Runtime.InteropServices;
+
+namespace HarfRust;
+
+/// <summary>
+/// Font-wide metrics for line layout, in font units.
+/// </summary>
+/// <remarks>
+/// The baseline of a line lies <see cref="Ascent"/> below its top, and consecutive baselines are
+/// <see cref="LineHeight"/> apart.
+/// </remarks>
+[StructLayout(LayoutKind.Sequential)]
+public readonly struct FontMetrics
+{
+    /// <summary>
+    /// The font's units per em.
+    /// </summary>
+    public readonly int UnitsPerEm;
+
+    /// <summary>
+    /// Typographic ascent, positive above the baseline.
+    /// </summary>
+    public readonly int Ascent;
+
+    /// <summary>
+    /// Typographic descent, negative below the baseline.
+    /// </summary>
+    public readonly int Descent;
+
+    /// <summary>
+    /// Extra space between lines.
+    /// </summary>
+    public readonly int LineGap;
+
+    /// <summary>
+    /// Height of lowercase letters such as 'x'.
+    /// </summary>
+    public readonly int XHeight;
+
+    /// <summary>
+    /// Height of capital letters such as 'H'.
+    /// </summary>
+    public readonly int CapHeight;
+
+    /// <summary>
+    /// Creates new font metrics.
+    /// </summary>
+    public FontMetrics(int unitsPerEm, int ascent, int descent, int lineGap, int xHeight, int capHeight)
+    {
+        UnitsPerEm = unitsPerEm;
+        Ascent = ascent;
+        Descent = descent;
+        LineGap = lineGap;
+        XHeight = xHeight;
+        CapHeight = capHeight;
+    }
+
+    /// <summary>
+    /// Distance between consecutive baselines: ascent minus descent plus line gap.
+    /// </summary>
+    public int LineHeight => Ascent - Descent + LineGap;
+}
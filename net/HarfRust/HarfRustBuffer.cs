@@ -115,6 +115,43 @@ public sealed class HarfRustBuffer : IDisposable
         }
     }
 
+    /// <summary>
+    /// Gets or sets how soft hyphens (U+00AD) are shaped, for text already added and text added
+    /// later. Defaults to <see cref="HarfRust.SoftHyphen.Hidden"/> and is kept by <see cref="Clear"/>.
+    /// </summary>
+    public SoftHyphen SoftHyphen
+    {
+        get
+        {
+            ThrowIfDisposedOrConsumed();
+            return _backend.SoftHyphen;
+        }
+        set
+        {
+            ThrowIfDisposedOrConsumed();
+            _backend.SoftHyphen = value;
+        }
+    }
+
+    /// <summary>
+    /// Gets or sets whether no-break, fixed width and narrow spaces the font has no glyph for are
+    /// shaped as an ordinary space, as is common with subset fonts embedded in PDFs. Results report
+    /// it in <see cref="HarfRustGlyphBuffer.Flags"/>. Defaults to false and is kept by <see cref="Clear"/>.
+    /// </summary>
+    public bool NormalizeSpaces
+    {
+        get
+        {
+            ThrowIfDisposedOrConsumed();
+            return _backend.NormalizeSpaces;
+        }
+        set
+        {
+            ThrowIfDisposedOrConsumed();
+            _backend.NormalizeSpaces = value;
+        }
+    }
+
     /// <summary>
     /// Gets or sets the script of the buffer as an ISO 15924 tag.
     /// </summary>
@@ -153,6 +190,19 @@ public sealed class HarfRustBuffer : IDisposable
         _backend.SetLanguage(language);
     }
 
+    /// <summary>
+    /// Sets the text surrounding the buffer's. Context is not shaped, but shaping decisions that look
+    /// past the buffer's ends (such as Arabic joining) see it, so a run split from a word keeps the forms
+    /// it has in the word. Call after adding text; clearing the buffer removes the context.
+    /// </summary>
+    /// <param name="preContext">Text before the buffer's; only its last 5 characters are used.</param>
+    /// <param name="postContext">Text after the buffer's; only its first 5 characters are used.</param>
+    public void SetContext(ReadOnlySpan<char> preContext, ReadOnlySpan<char> postContext)
+    {
+        ThrowIfDisposedOrConsumed();
+        _backend.SetContext(preContext, postContext);
+    }
+
     /// <summary>
     /// Guesses and sets the segment properties (direction, script, language) based on the buffer contents.
     /// </summary>
@@ -97,6 +97,61 @@ public sealed class HarfRustFont : IDisposable
         }
     }
 
+    /// <summary>
+    /// Gets the font's ascent, descent, line gap, x-height and cap height, for placing baselines.
+    /// </summary>
+    public FontMetrics Metrics
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return _backend.Metrics;
+        }
+    }
+
+    /// <summary>
+    /// Gets or sets the horizontal scaling factor, like the PDF Tz operand divided by 100.
+    /// Advances and offsets of every result shaped with this font are multiplied by it in x,
+    /// so condensed or expanded text needs no post-processing. Defaults to 1.
+    /// </summary>
+    /// <exception cref="ArgumentOutOfRangeException">Thrown if the value is not a positive finite number.</exception>
+    public float HorizontalScale
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return _backend.HorizontalScale;
+        }
+        set
+        {
+            ThrowIfDisposed();
+            if (!(value > 0 && float.IsFinite(value)))
+            {
+                throw new ArgumentOutOfRangeException(nameof(value), value, "Horizontal scale must be positive and finite.");
+            }
+            _backend.HorizontalScale = value;
+        }
+    }
+
+    /// <summary>
+    /// Gets or sets a fixed extra advance, in font units, added after every cluster (spaces included)
+    /// of every result shaped with this font, like the PDF Tc operator. A Tc of <c>t</c> at font size
+    /// <c>s</c> is <c>t * UnitsPerEm / s</c>. Applied before <see cref="HorizontalScale"/>. Defaults to 0.
+    /// </summary>
+    public int CharacterSpacing
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return _backend.CharacterSpacing;
+        }
+        set
+        {
+            ThrowIfDisposed();
+            _backend.CharacterSpacing = value;
+        }
+    }
+
     /// <summary>
     /// Shapes the text in the buffer and returns the shaping results.
     /// </summary>
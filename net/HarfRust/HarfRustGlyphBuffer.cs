@@ -62,6 +62,18 @@ public sealed class HarfRustGlyphBuffer : IDisposable
         }
     }
 
+    /// <summary>
+    /// Gets how the result was produced, e.g. whether missing spaces were normalized.
+    /// </summary>
+    public ShapeResultFlags Flags
+    {
+        get
+        {
+            ThrowIfDisposed();
+            return _backend.Flags;
+        }
+    }
+
     /// <summary>
     /// Clears the glyph buffer and returns a unicode buffer for reuse.
     /// </summary>
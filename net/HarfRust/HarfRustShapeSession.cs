@@ -8,6 +8,8 @@ public sealed class HarfRustShapeSession : IDisposable
     private IBackendBuffer? _buffer;
     private bool _disposed;
     private bool _shapeInProgress;
+    private SoftHyphen _softHyphen;
+    private bool _normalizeSpaces;
 
     /// <summary>
     /// Creates a new shape session using the current backend.
@@ -45,6 +47,33 @@ public sealed class HarfRustShapeSession : IDisposable
         set => GetBuffer().Direction = value;
     }
 
+    /// <summary>
+    /// Gets or sets how soft hyphens (U+00AD) are shaped. The setting is kept across shapes.
+    /// </summary>
+    public SoftHyphen SoftHyphen
+    {
+        get => _softHyphen;
+        set
+        {
+            GetBuffer().SoftHyphen = value;
+            _softHyphen = value;
+        }
+    }
+
+    /// <summary>
+    /// Gets or sets whether spaces the font has no glyph for are shaped as an ordinary space.
+    /// The setting is kept across shapes.
+    /// </summary>
+    public bool NormalizeSpaces
+    {
+        get => _normalizeSpaces;
+        set
+        {
+            GetBuffer().NormalizeSpaces = value;
+            _normalizeSpaces = value;
+        }
+    }
+
     /// <summary>
     /// Gets or sets the script of the buffer as an ISO 15924 tag.
     /// </summary>
@@ -96,6 +125,15 @@ public sealed class HarfRustShapeSession : IDisposable
         SetLanguage(language.AsSpan());
     }
 
+    /// <summary>
+    /// Sets the text surrounding the buffer's, which shaping consults but does not shape.
+    /// Clearing the buffer removes it.
+    /// </summary>
+    public void SetContext(ReadOnlySpan<char> preContext, ReadOnlySpan<char> postContext)
+    {
+        GetBuffer().SetContext(preContext, postContext);
+    }
+
     /// <summary>
     /// Guesses segment properties from the current buffer contents.
     /// </summary>
@@ -186,6 +224,15 @@ public sealed class HarfRustShapeSession : IDisposable
         }
         else
         {
+            // The buffer handed back after shaping starts with default settings
+            if (_softHyphen != SoftHyphen.Hidden)
+            {
+                buffer.SoftHyphen = _softHyphen;
+            }
+            if (_normalizeSpaces)
+            {
+                buffer.NormalizeSpaces = true;
+            }
             _buffer = buffer;
             _shapeInProgress = false;
         }
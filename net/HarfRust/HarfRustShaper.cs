@@ -19,13 +19,17 @@ public static class HarfRustShaper
     /// <param name="fallbackFonts">Ordered list of fallback fonts.</param>
     /// <param name="features">OpenType features to apply.</param>
     /// <param name="variations">Variable font axis settings.</param>
+    /// <param name="continuity">How runs reshaped with a fallback font see the text around them.</param>
+    /// <param name="missingGlyphs">What to output for characters no font has a glyph for.</param>
     /// <returns>A combined array of shaped glyphs.</returns>
     public static ShapedGlyph[] ShapeWithFallback(
         string text,
         HarfRustFont primaryFont,
         IEnumerable<HarfRustFont>? fallbackFonts,
         Feature[]? features = null,
-        Variation[]? variations = null)
+        Variation[]? variations = null,
+        FallbackContinuity continuity = FallbackContinuity.Context,
+        MissingGlyphMode missingGlyphs = MissingGlyphMode.Notdef)
     {
         ArgumentNullException.ThrowIfNull(text);
         ArgumentNullException.ThrowIfNull(primaryFont);
@@ -37,7 +41,7 @@ public static class HarfRustShaper
         }
 
         using var session = new HarfRustShapeSession(primaryFont.Backend);
-        return ShapeRecursive(text, 0, text.Length, fonts, 0, features, variations, session);
+        return ShapeRecursive(text, 0, text.Length, fonts, 0, features, variations, continuity, missingGlyphs, session);
     }
 
     private static ShapedGlyph[] ShapeRecursive(
@@ -48,6 +52,8 @@ public static class HarfRustShaper
         int fontIndex,
         Feature[]? features,
         Variation[]? variations,
+        FallbackContinuity continuity,
+        MissingGlyphMode missingGlyphs,
         HarfRustShapeSession session)
     {
         if (length == 0) return Array.Empty<ShapedGlyph>();
@@ -57,7 +63,26 @@ public static class HarfRustShaper
         // Shape this segment
         List<ShapedGlyph> shapedGlyphs;
 
-        using (var result = session.Shape(font, fullText.AsSpan(start, length), features, variations, guessSegmentProperties: true))
+        session.Clear();
+        session.Add(fullText.AsSpan(start, length));
+        switch (continuity)
+        {
+            case FallbackContinuity.Context:
+                session.SetContext(fullText.AsSpan(0, start), fullText.AsSpan(start + length));
+                break;
+            case FallbackContinuity.Joiners:
+                session.SetContext(
+                    JoinerContext(fullText, start - 1),
+                    JoinerContext(fullText, start + length));
+                break;
+        }
+        session.GuessSegmentProperties();
+
+        var runFeatures = continuity == FallbackContinuity.Independent
+            ? features
+            : MoveFeatures(features, start, length);
+
+        using (var result = session.Shape(font, runFeatures, variations))
         {
             var infos = result.GlyphInfos;
             var positions = result.GlyphPositions;
@@ -82,7 +107,9 @@ public static class HarfRustShaper
         // If this is the last font, or no missing glyphs, return results
         if (fontIndex >= fonts.Count - 1)
         {
-            return shapedGlyphs.ToArray();
+            return missingGlyphs == MissingGlyphMode.HexBox
+                ? ReplaceWithHexBoxes(shapedGlyphs, fullText, start + length, fonts[0])
+                : shapedGlyphs.ToArray();
         }
 
         // Check for missing glyphs (ID 0)
@@ -146,6 +173,8 @@ public static class HarfRustShaper
                 fontIndex + 1, 
                 features, 
                 variations,
+                continuity,
+                missingGlyphs,
                 session
             );
 
@@ -154,4 +183,94 @@ public static class HarfRustShaper
 
         return finalResult.ToArray();
     }
+
+    /// <summary>
+    /// Replaces the .notdef glyphs of each cluster with a hex box per character of the cluster.
+    /// </summary>
+    private static ShapedGlyph[] ReplaceWithHexBoxes(List<ShapedGlyph> glyphs, string fullText, int end, HarfRustFont font)
+    {
+        var result = new List<ShapedGlyph>(glyphs.Count);
+        for (int i = 0; i < glyphs.Count; i++)
+        {
+            var glyph = glyphs[i];
+            if (glyph.GlyphId != 0)
+            {
+                result.Add(glyph);
+                continue;
+            }
+            if (i > 0 && glyphs[i - 1].Cluster == glyph.Cluster)
+            {
+                continue;
+            }
+
+            // The cluster's text runs up to the next cluster in text order
+            var clusterEnd = end;
+            foreach (var other in glyphs)
+            {
+                if (other.Cluster > glyph.Cluster && other.Cluster < clusterEnd)
+                {
+                    clusterEnd = (int)other.Cluster;
+                }
+            }
+
+            var boxes = new List<ShapedGlyph>();
+            for (int offset = (int)glyph.Cluster; offset < clusterEnd; offset++)
+            {
+                int codepoint = fullText[offset];
+                if (char.IsSurrogatePair(fullText, offset))
+                {
+                    codepoint = char.ConvertToUtf32(fullText[offset], fullText[offset + 1]);
+                    offset++;
+                }
+                boxes.Add(ShapedGlyph.HexBox(codepoint, glyph.Cluster, font));
+            }
+            // Right-to-left runs list clusters in decreasing order
+            var rightToLeft = (i + 1 < glyphs.Count && glyphs[i + 1].Cluster < glyph.Cluster)
+                || (i > 0 && glyphs[i - 1].Cluster > glyph.Cluster);
+            if (rightToLeft)
+            {
+                boxes.Reverse();
+            }
+            result.AddRange(boxes);
+        }
+        return result.ToArray();
+    }
+
+    /// <summary>
+    /// A zero width joiner if the character at <paramref name="index"/> continues the word, otherwise nothing.
+    /// </summary>
+    private static ReadOnlySpan<char> JoinerContext(string text, int index)
+    {
+        if (index < 0 || index >= text.Length || char.IsWhiteSpace(text[index]))
+        {
+            return ReadOnlySpan<char>.Empty;
+        }
+        return "\u200D";
+    }
+
+    /// <summary>
+    /// Moves feature ranges given in full-text offsets to a run starting at <paramref name="start"/>,
+    /// dropping features that do not reach the run.
+    /// </summary>
+    private static Feature[]? MoveFeatures(Feature[]? features, int start, int length)
+    {
+        if (features == null || start == 0)
+        {
+            return features;
+        }
+
+        var runStart = (uint)start;
+        var runEnd = (uint)(start + length);
+        var moved = new List<Feature>(features.Length);
+        foreach (var feature in features)
+        {
+            if (feature.End <= runStart || feature.Start >= runEnd)
+            {
+                continue;
+            }
+            var end = feature.End == uint.MaxValue ? uint.MaxValue : Math.Min(feature.End, runEnd) - runStart;
+            moved.Add(new Feature(feature.Tag, feature.Value, Math.Max(feature.Start, runStart) - runStart, end));
+        }
+        return moved.ToArray();
+    }
 }
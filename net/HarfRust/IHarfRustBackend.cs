@@ -66,6 +66,22 @@ public interface IBackendBuffer : IDisposable
     /// </summary>
     void SetLanguage(ReadOnlySpan<char> language);
 
+    /// <summary>
+    /// Sets the text before and after the buffer's, which shaping consults
+    /// (e.g. for Arabic joining) but does not shape.
+    /// </summary>
+    void SetContext(ReadOnlySpan<char> preContext, ReadOnlySpan<char> postContext);
+
+    /// <summary>
+    /// Gets or sets how soft hyphens are shaped.
+    /// </summary>
+    SoftHyphen SoftHyphen { get; set; }
+
+    /// <summary>
+    /// Gets or sets whether spaces the font has no glyph for are shaped as an ordinary space.
+    /// </summary>
+    bool NormalizeSpaces { get; set; }
+
     /// <summary>
     /// Guesses and sets the segment properties based on buffer contents.
     /// </summary>
@@ -89,6 +105,21 @@ public interface IBackendFont : IDisposable
     /// </summary>
     int UnitsPerEm { get; }
 
+    /// <summary>
+    /// Gets the font's line layout metrics.
+    /// </summary>
+    FontMetrics Metrics { get; }
+
+    /// <summary>
+    /// Gets or sets the horizontal scaling factor applied to shaped advances and offsets.
+    /// </summary>
+    float HorizontalScale { get; set; }
+
+    /// <summary>
+    /// Gets or sets the extra advance added after every cluster, in font units.
+    /// </summary>
+    int CharacterSpacing { get; set; }
+
     /// <summary>
     /// Shapes the text in the buffer.
     /// </summary>
@@ -126,6 +157,11 @@ public interface IBackendGlyphBuffer : IDisposable
     /// </summary>
     ReadOnlySpan<GlyphPosition> GlyphPositions { get; }
 
+    /// <summary>
+    /// Gets how the result was produced.
+    /// </summary>
+    ShapeResultFlags Flags { get; }
+
     /// <summary>
     /// Converts back to a reusable backend buffer.
     /// </summary>
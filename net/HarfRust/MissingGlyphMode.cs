@@ -0,0 +1,19 @@
+namespace HarfRust;
+
+/// <summary>
+/// What font fallback outputs for characters no font has a glyph for.
+/// </summary>
+public enum MissingGlyphMode
+{
+    /// <summary>
+    /// The last fallback font's .notdef glyph (glyph ID 0), with its advance.
+    /// </summary>
+    Notdef = 0,
+
+    /// <summary>
+    /// One hex box record per character, with <see cref="ShapedGlyph.IsHexBox"/> set, the character
+    /// in <see cref="ShapedGlyph.Codepoint"/> and the width of the box in the primary font's units as
+    /// its advance, so renderers can draw the last-resort box showing the code point in hex.
+    /// </summary>
+    HexBox = 1,
+}
@@ -0,0 +1,24 @@
+namespace HarfRust;
+
+/// <summary>
+/// Describes how a shaping result was produced.
+/// </summary>
+[Flags]
+public enum ShapeResultFlags
+{
+    /// <summary>
+    /// The result was shaped normally.
+    /// </summary>
+    None = 0,
+
+    /// <summary>
+    /// The font's layout tables could not be read, so each character was shaped as its nominal glyph.
+    /// </summary>
+    Degraded = 1,
+
+    /// <summary>
+    /// Spaces the font has no glyph for were shaped as an ordinary space; see
+    /// <see cref="HarfRustBuffer.NormalizeSpaces"/>.
+    /// </summary>
+    SpacesNormalized = 2,
+}
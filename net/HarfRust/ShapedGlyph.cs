@@ -40,6 +40,17 @@ public struct ShapedGlyph
     /// </summary>
     public HarfRustFont Font { get; set; }
 
+    /// <summary>
+    /// Whether this is a hex box standing in for a character no font has a glyph for; see
+    /// <see cref="MissingGlyphMode.HexBox"/>. Its glyph ID is 0.
+    /// </summary>
+    public bool IsHexBox { get; set; }
+
+    /// <summary>
+    /// The character a hex box stands for, or 0 for other glyphs.
+    /// </summary>
+    public int Codepoint { get; set; }
+
     public ShapedGlyph(uint glyphId, uint cluster, int xAdvance, int yAdvance, int xOffset, int yOffset, HarfRustFont font)
     {
         GlyphId = glyphId;
@@ -49,5 +60,29 @@ public struct ShapedGlyph
         XOffset = xOffset;
         YOffset = yOffset;
         Font = font;
+        IsHexBox = false;
+        Codepoint = 0;
+    }
+
+    /// <summary>
+    /// Creates a hex box for <paramref name="codepoint"/>, sized in the units of <paramref name="font"/>.
+    /// </summary>
+    public static ShapedGlyph HexBox(int codepoint, uint cluster, HarfRustFont font)
+    {
+        return new ShapedGlyph(0, cluster, HexBoxWidth(codepoint, font.UnitsPerEm), 0, 0, 0, font)
+        {
+            IsHexBox = true,
+            Codepoint = codepoint,
+        };
+    }
+
+    /// <summary>
+    /// The advance of a hex box: two rows of hex digits, two columns for code points up to U+FFFF and
+    /// three above, each column 0.3 em wide, with 0.1 em of margin.
+    /// </summary>
+    public static int HexBoxWidth(int codepoint, int unitsPerEm)
+    {
+        var columns = codepoint > 0xFFFF ? 3 : 2;
+        return (int)Math.Round(unitsPerEm * (0.1 + 0.3 * columns));
     }
 }
@@ -0,0 +1,24 @@
+namespace HarfRust;
+
+/// <summary>
+/// How a buffer shapes soft hyphens (U+00AD), which mark where a word may be hyphenated.
+/// </summary>
+public enum SoftHyphen
+{
+    /// <summary>
+    /// Soft hyphens are invisible and have no advance.
+    /// </summary>
+    Hidden = 0,
+
+    /// <summary>
+    /// Soft hyphens are shown as a hyphen (U+002D) wherever they are.
+    /// </summary>
+    Visible = 1,
+
+    /// <summary>
+    /// A soft hyphen is shown as a hyphen only at the end of the text, where a line broke at it,
+    /// and is invisible elsewhere, as in word processors. Shape each line separately to get the
+    /// hyphen on lines that end with one.
+    /// </summary>
+    LineEnd = 2,
+}
@@ -1,9 +1,122 @@
+const CSHARP_BINDINGS: &str = "../net/HarfRust/Bindings/NativeMethods.g.cs";
+
 fn main() {
+    let prefix = symbol_prefix();
+    println!("cargo:rustc-env=HARFRUST_SYMBOL_PREFIX={prefix}");
+    println!("cargo:rerun-if-env-changed=HARFRUST_SYMBOL_PREFIX");
+    // Declaring any rerun condition replaces the default of rerunning when
+    // anything in the package changes
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=build.rs");
+    let exports = exports_source(&prefix);
+
     csbindgen::Builder::default()
-        .input_extern_file("src/lib.rs")
+        .input_extern_file(&exports)
         .csharp_dll_name("harfrust_ffi")
         .csharp_namespace("HarfRust.Bindings")
         .csharp_class_name("NativeMethods")
-        .generate_csharp_file("../net/HarfRust/Bindings/NativeMethods.g.cs")
+        .generate_csharp_file(CSHARP_BINDINGS)
         .unwrap();
+    use_library_import(CSHARP_BINDINGS);
+
+    // C has one namespace for all enumerators, so they are prefixed with
+    // their enum's name (HarfRustStatus_Ok).
+    let mut header_config = cbindgen::Config::default();
+    header_config.enumeration.prefix_with_name = true;
+    cbindgen::Builder::new()
+        .with_config(header_config)
+        .with_src(&exports)
+        .with_language(cbindgen::Language::C)
+        .with_include_guard("HARFRUST_FFI_H")
+        .with_autogen_warning("/* Generated by cbindgen from src/lib.rs. Do not edit. */")
+        .with_documentation(true)
+        .with_cpp_compat(true)
+        .generate()
+        .unwrap()
+        .write_to_file("include/harfrust_ffi.h");
+
+    println!("cargo:rustc-env=HARFRUST_ENGINE_VERSION={}", engine_version());
+}
+
+/// Prefix for every exported symbol, from `HARFRUST_SYMBOL_PREFIX` (e.g.
+/// `acme_` exports `acme_harfrust_shape`), so several libraries built from
+/// this crate or with clashing names can share a process. Empty by default.
+fn symbol_prefix() -> String {
+    let prefix = std::env::var("HARFRUST_SYMBOL_PREFIX").unwrap_or_default();
+    let valid = prefix.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !prefix.starts_with(|c: char| c.is_ascii_digit());
+    assert!(valid, "HARFRUST_SYMBOL_PREFIX must be a C identifier prefix, got {prefix:?}");
+    prefix
+}
+
+/// Writes a copy of src/lib.rs for the binding generators with each export's
+/// `symbol!` name spelled out, as they only read literal export names, and
+/// returns its path. C# keeps the unprefixed method names and calls the
+/// prefixed entry points; the C header declares the prefixed names.
+fn exports_source(prefix: &str) -> std::path::PathBuf {
+    let mut source = String::new();
+    for line in std::fs::read_to_string("src/lib.rs").unwrap().lines() {
+        let name = line
+            .strip_prefix("#[export_name = symbol!(\"")
+            .and_then(|rest| rest.strip_suffix("\")]"));
+        match name {
+            Some(name) => source.push_str(&format!("#[export_name = \"{prefix}{name}\"]")),
+            None => source.push_str(line),
+        }
+        source.push('\n');
+    }
+    let path = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("lib.rs");
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+/// Rewrites csbindgen's `[DllImport]` declarations as source-generated
+/// `[LibraryImport]` ones, so calls need no runtime marshalling and work under
+/// NativeAOT and trimming. That only holds while every export is blittable:
+/// csbindgen marshals `bool` with `MarshalAs`, so exports take integers
+/// instead.
+fn use_library_import(path: &str) {
+    let source = std::fs::read_to_string(path).unwrap();
+    assert!(
+        !source.contains("MarshalAs"),
+        "exported signatures must be blittable; use an integer instead of bool"
+    );
+
+    let mut out = String::with_capacity(source.len());
+    for line in source.lines() {
+        let code = line.trim_start();
+        let indent = &line[..line.len() - code.len()];
+        if let Some(rest) = code.strip_prefix("[DllImport(__DllName, EntryPoint = \"") {
+            let entry_point = &rest[..rest.find('"').unwrap()];
+            out.push_str(&format!("{indent}[LibraryImport(__DllName, EntryPoint = \"{entry_point}\")]\n"));
+            out.push_str(&format!("{indent}[UnmanagedCallConv(CallConvs = new[] {{ typeof(CallConvCdecl) }})]\n"));
+            continue;
+        }
+        if code.starts_with("internal static extern ") {
+            out.push_str(&line.replacen(" static extern ", " static partial ", 1));
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+        if line == "using System.Runtime.InteropServices;" {
+            out.push_str("using System.Runtime.CompilerServices;\n");
+        }
+    }
+    std::fs::write(path, out).unwrap();
+}
+
+/// Version of the harfrust crate this build resolved to, from the lock file.
+fn engine_version() -> String {
+    let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line == "name = \"harfrust\"" {
+            let version = lines.next().and_then(|line| line.strip_prefix("version = \""));
+            if let Some(version) = version.and_then(|version| version.strip_suffix('"')) {
+                return version.to_string();
+            }
+        }
+    }
+    "unknown".to_string()
 }
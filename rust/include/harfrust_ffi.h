@@ -0,0 +1,3357 @@
+#ifndef HARFRUST_FFI_H
+#define HARFRUST_FFI_H
+
+/* Generated by cbindgen from src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of a fallible function. Functions that return a count, size or
+ * flag instead report failure with these negative values.
+ */
+enum HarfRustStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  /**
+   * The call succeeded.
+   */
+  HarfRustStatus_Ok = 0,
+  /**
+   * A required handle or pointer is null.
+   */
+  HarfRustStatus_NullArgument = -1,
+  /**
+   * An argument is invalid, e.g. a negative length.
+   */
+  HarfRustStatus_InvalidArgument = -2,
+  /**
+   * A value is outside the range the function accepts.
+   */
+  HarfRustStatus_OutOfRange = -3,
+  /**
+   * A language tag cannot be parsed.
+   */
+  HarfRustStatus_InvalidLanguage = -4,
+  /**
+   * Font data cannot be decoded or parsed, or lacks a required table.
+   */
+  HarfRustStatus_InvalidFont = -5,
+  /**
+   * A file cannot be opened or read.
+   */
+  HarfRustStatus_Io = -6,
+  /**
+   * Text is not valid UTF-8.
+   */
+  HarfRustStatus_InvalidUtf8 = -7,
+  /**
+   * An output array is too small; the size needed was reported.
+   */
+  HarfRustStatus_InsufficientCapacity = -8,
+  /**
+   * An internal error was caught; the last-error message has details.
+   */
+  HarfRustStatus_Panic = -9,
+  /**
+   * A handle points at an object of another type, or at a freed one.
+   * Detected only in debug builds or with the `checked` feature.
+   */
+  HarfRustStatus_InvalidHandle = -10,
+  /**
+   * A handle refers to an object that has been freed. Detected only with
+   * the `handle-table` feature.
+   */
+  HarfRustStatus_StaleHandle = -11,
+  /**
+   * The library was built without the feature the function needs, the
+   * call came too late to take effect, the object it would change is
+   * frozen (see `harfrust_font_freeze`), or the font data uses a format
+   * the library cannot handle, such as SVG glyphs.
+   */
+  HarfRustStatus_Unsupported = -12,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum HarfRustStatus HarfRustStatus;
+#else
+typedef int32_t HarfRustStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+/**
+ * Case transform applied to text as it is added to a buffer.
+ */
+typedef enum HarfRustCaseTransform {
+  /**
+   * Text is added unchanged.
+   */
+  HarfRustCaseTransform_None = 0,
+  /**
+   * Full Unicode uppercase mapping.
+   */
+  HarfRustCaseTransform_Uppercase = 1,
+  /**
+   * Full Unicode lowercase mapping, including final sigma.
+   */
+  HarfRustCaseTransform_Lowercase = 2,
+  /**
+   * The first letter of each word is titlecased (CSS `capitalize`).
+   */
+  HarfRustCaseTransform_Capitalize = 3,
+} HarfRustCaseTransform;
+
+/**
+ * Text direction for shaping.
+ */
+typedef enum HarfRustDirection {
+  /**
+   * Initial, unset direction.
+   */
+  HarfRustDirection_Invalid = 0,
+  /**
+   * Left-to-right text.
+   */
+  HarfRustDirection_LeftToRight = 4,
+  /**
+   * Right-to-left text.
+   */
+  HarfRustDirection_RightToLeft = 5,
+  /**
+   * Top-to-bottom text.
+   */
+  HarfRustDirection_TopToBottom = 6,
+  /**
+   * Bottom-to-top text.
+   */
+  HarfRustDirection_BottomToTop = 7,
+} HarfRustDirection;
+
+/**
+ * How a buffer shapes soft hyphens (U+00AD), which mark where a word may be
+ * hyphenated.
+ */
+typedef enum HarfRustSoftHyphen {
+  /**
+   * Invisible and without advance, wherever they are.
+   */
+  HarfRustSoftHyphen_Hidden = 0,
+  /**
+   * Shown as a hyphen (U+002D) wherever they are.
+   */
+  HarfRustSoftHyphen_Visible = 1,
+  /**
+   * Shown as a hyphen only at the end of the text, where a line broke at
+   * it, and invisible elsewhere: how word processors show them. A layout
+   * engine shaping each line separately gets the hyphen on lines that end
+   * with one.
+   */
+  HarfRustSoftHyphen_LineEnd = 2,
+} HarfRustSoftHyphen;
+
+/**
+ * How much work font creation does up front.
+ */
+typedef enum HarfRustParseMode {
+  /**
+   * Parse only what is needed to create the font; tables are read on first use.
+   */
+  HarfRustParseMode_Lazy = 0,
+  /**
+   * Validate the core tables and warm all caches during creation, so
+   * broken fonts fail immediately and the first shape has no extra cost.
+   */
+  HarfRustParseMode_Eager = 1,
+  /**
+   * Load fonts with recoverable corruption (bad checksums, overlapping or
+   * truncated tables, unreadable optional tables), reporting each problem
+   * through the log callback. Only unreadable required tables fail.
+   */
+  HarfRustParseMode_Tolerant = 2,
+} HarfRustParseMode;
+
+/**
+ * Superscript or subscript placement.
+ */
+typedef enum HarfRustScriptPosition {
+  /**
+   * Raised, reduced-size text (e.g. footnote markers).
+   */
+  HarfRustScriptPosition_Superscript = 0,
+  /**
+   * Lowered, reduced-size text.
+   */
+  HarfRustScriptPosition_Subscript = 1,
+} HarfRustScriptPosition;
+
+/**
+ * Where a derived font metric came from.
+ */
+typedef enum HarfRustMetricSource {
+  /**
+   * Read directly from the font tables.
+   */
+  HarfRustMetricSource_Table = 0,
+  /**
+   * Measured from representative glyph outlines.
+   */
+  HarfRustMetricSource_GlyphOutline = 1,
+  /**
+   * Estimated from the em size because nothing better was available.
+   */
+  HarfRustMetricSource_Estimated = 2,
+} HarfRustMetricSource;
+
+/**
+ * Path construction operator, matching the PDF path operators m, l, c and h.
+ */
+typedef enum HarfRustPathVerb {
+  /**
+   * Begins a new subpath at (x, y).
+   */
+  HarfRustPathVerb_MoveTo = 0,
+  /**
+   * Straight line to (x, y).
+   */
+  HarfRustPathVerb_LineTo = 1,
+  /**
+   * Cubic Bézier curve to (x, y) with control points (x1, y1) and (x2, y2).
+   */
+  HarfRustPathVerb_CubicTo = 2,
+  /**
+   * Closes the current subpath; (x, y) is the current point.
+   */
+  HarfRustPathVerb_Close = 3,
+} HarfRustPathVerb;
+
+/**
+ * Unit of the cluster values in a buffer, determined by how text was added.
+ */
+typedef enum HarfRustClusterUnit {
+  /**
+   * UTF-16 code unit offsets (`harfrust_buffer_add_utf16`).
+   */
+  HarfRustClusterUnit_Utf16 = 0,
+  /**
+   * UTF-8 byte offsets (`harfrust_buffer_add_str`).
+   */
+  HarfRustClusterUnit_Utf8 = 1,
+} HarfRustClusterUnit;
+
+/**
+ * Horizontal alignment of a line, matching the PDF form field quadding (Q)
+ * values.
+ */
+typedef enum HarfRustTextAlign {
+  HarfRustTextAlign_Left = 0,
+  HarfRustTextAlign_Center = 1,
+  HarfRustTextAlign_Right = 2,
+} HarfRustTextAlign;
+
+/**
+ * How well a font supports a script, ordered from worst to best.
+ */
+typedef enum HarfRustScriptSupport {
+  /**
+   * The font maps no character of the script.
+   */
+  HarfRustScriptSupport_Unsupported = 0,
+  /**
+   * The font maps characters of a script that needs a dedicated shaper,
+   * but its GSUB has no lookups for it, so text shapes in logical order
+   * without conjuncts, joining or reordering.
+   */
+  HarfRustScriptSupport_CmapOnly = 1,
+  /**
+   * The font maps characters of the script and has GSUB lookups for it,
+   * or the script needs none.
+   */
+  HarfRustScriptSupport_Full = 2,
+} HarfRustScriptSupport;
+
+/**
+ * Severity of a message passed to the log callback.
+ */
+typedef enum HarfRustLogLevel {
+  HarfRustLogLevel_Info = 0,
+  HarfRustLogLevel_Warning = 1,
+  HarfRustLogLevel_Error = 2,
+} HarfRustLogLevel;
+
+/**
+ * The kind of GSUB substitution that produced a glyph.
+ */
+typedef enum HarfRustSubstitutionKind {
+  /**
+   * The nominal glyph of a character in its cluster.
+   */
+  HarfRustSubstitutionKind_None = 0,
+  HarfRustSubstitutionKind_Single = 1,
+  HarfRustSubstitutionKind_Multiple = 2,
+  HarfRustSubstitutionKind_Alternate = 3,
+  HarfRustSubstitutionKind_Ligature = 4,
+  HarfRustSubstitutionKind_ReverseChainSingle = 5,
+  /**
+   * Not the output of any GSUB lookup (e.g. a glyph from a shaper
+   * fallback such as the dotted circle).
+   */
+  HarfRustSubstitutionKind_Unknown = 6,
+} HarfRustSubstitutionKind;
+
+/**
+ * How a subset font numbers its glyphs.
+ */
+typedef enum HarfRustSubsetMode {
+  /**
+   * Kept glyphs are renumbered 0..n in original glyph ID order.
+   */
+  HarfRustSubsetMode_Compact = 0,
+  /**
+   * Glyphs keep their original IDs; unused glyphs are left empty, so
+   * content that references glyph IDs stays valid.
+   */
+  HarfRustSubsetMode_RetainGids = 1,
+} HarfRustSubsetMode;
+
+/**
+ * Opaque byte array produced by the library (e.g. a subset font).
+ */
+typedef struct HarfRustBlob HarfRustBlob;
+
+/**
+ * Opaque wrapper around harfrust's UnicodeBuffer.
+ */
+typedef struct HarfRustBuffer HarfRustBuffer;
+
+/**
+ * Saved buffer contents and segment properties.
+ */
+typedef struct HarfRustBufferState HarfRustBufferState;
+
+/**
+ * Opaque wrapper around a rendered color glyph (premultiplied RGBA).
+ */
+typedef struct HarfRustColorBitmap HarfRustColorBitmap;
+
+/**
+ * Opaque wrapper that owns font data and provides shaping capabilities.
+ * The `FontRef`, `ShaperData` and units per em are built once at creation
+ * and reused by every call; only the lightweight `Shaper` view is rebuilt.
+ * Shape plans are cached per combination of segment properties, features
+ * and variation instance.
+ */
+typedef struct HarfRustFont HarfRustFont;
+
+/**
+ * Opaque wrapper around harfrust's GlyphBuffer (shaping result).
+ */
+typedef struct HarfRustGlyphBuffer HarfRustGlyphBuffer;
+
+/**
+ * Opaque variable font instance (normalized axis coordinates and derived
+ * variation data) for shaping many runs at the same settings.
+ */
+typedef struct HarfRustInstance HarfRustInstance;
+
+/**
+ * Opaque shaping configuration: a font plus a variation instance computed
+ * once and reused for every shape call. Holds a reference to the font.
+ */
+typedef struct HarfRustShaper HarfRustShaper;
+
+/**
+ * Opaque glyph set accumulated across many shaping results (e.g. every page
+ * of a document) and subset once at the end.
+ */
+typedef struct HarfRustSubsetter HarfRustSubsetter;
+
+/**
+ * Callback that releases caller-owned font data once the font is freed.
+ */
+typedef void (*HarfRustReleaseCallback)(void *user_data);
+
+/**
+ * Font variation settings.
+ */
+typedef struct HarfRustVariation {
+  /**
+   * The variation tag (e.g. 'wght', 'wdth').
+   */
+  uint32_t tag;
+  /**
+   * The variation value (in design units).
+   */
+  float value;
+} HarfRustVariation;
+
+/**
+ * 128-bit cache key, split into two halves.
+ */
+typedef struct HarfRustCacheKey {
+  /**
+   * Upper 64 bits of the key.
+   */
+  uint64_t high;
+  /**
+   * Lower 64 bits of the key.
+   */
+  uint64_t low;
+} HarfRustCacheKey;
+
+/**
+ * Ink bounding box of a glyph in font units, laid out as HarfBuzz's
+ * `hb_glyph_extents_t`: y grows upwards, so `height` is negative.
+ */
+typedef struct HarfRustGlyphExtents {
+  /**
+   * Left edge, relative to the glyph origin.
+   */
+  int32_t x_bearing;
+  /**
+   * Top edge, relative to the baseline.
+   */
+  int32_t y_bearing;
+  int32_t width;
+  /**
+   * Distance from the top edge to the bottom edge.
+   */
+  int32_t height;
+} HarfRustGlyphExtents;
+
+/**
+ * A single (codepoint, glyph) mapping from the font's cmap.
+ */
+typedef struct HarfRustCmapEntry {
+  /**
+   * The Unicode codepoint.
+   */
+  uint32_t codepoint;
+  /**
+   * The glyph ID the codepoint maps to.
+   */
+  uint32_t glyph_id;
+} HarfRustCmapEntry;
+
+/**
+ * Superscript or subscript size and offset from the OS/2 table, in font units.
+ */
+typedef struct HarfRustScriptMetrics {
+  /**
+   * Horizontal font size of the scripted glyphs.
+   */
+  int32_t x_size;
+  /**
+   * Vertical font size of the scripted glyphs.
+   */
+  int32_t y_size;
+  /**
+   * Horizontal offset of the scripted glyphs.
+   */
+  int32_t x_offset;
+  /**
+   * Vertical offset of the scripted glyphs: upwards from the baseline for
+   * superscripts, downwards for subscripts (as in OS/2).
+   */
+  int32_t y_offset;
+} HarfRustScriptMetrics;
+
+/**
+ * Font-wide metrics for line layout, in font units, as reported by
+ * `harfrust_font_get_metrics`.
+ */
+typedef struct HarfRustFontMetrics {
+  int32_t units_per_em;
+  /**
+   * Typographic ascent (positive, above the baseline).
+   */
+  int32_t ascent;
+  /**
+   * Typographic descent (negative, below the baseline).
+   */
+  int32_t descent;
+  /**
+   * Extra space between lines, added to ascent minus descent.
+   */
+  int32_t line_gap;
+  /**
+   * See `harfrust_font_get_x_height`.
+   */
+  int32_t x_height;
+  /**
+   * See `harfrust_font_get_cap_height`.
+   */
+  int32_t cap_height;
+} HarfRustFontMetrics;
+
+/**
+ * Style and embedding permissions from the OS/2 table, as reported by
+ * `harfrust_font_get_style`.
+ */
+typedef struct HarfRustFontStyle {
+  /**
+   * usWeightClass: 100 (thin) to 900 (black), 400 is regular.
+   */
+  uint32_t weight_class;
+  /**
+   * usWidthClass: 1 (ultra-condensed) to 9 (ultra-expanded), 5 is normal.
+   */
+  uint32_t width_class;
+  /**
+   * fsSelection bits, e.g. 0x01 italic, 0x20 bold, 0x40 regular and
+   * 0x200 oblique.
+   */
+  uint32_t fs_selection;
+  /**
+   * fsType embedding permission bits: 0x02 restricted license, 0x04
+   * preview and print, 0x08 editable, 0x100 no subsetting and 0x200
+   * bitmap embedding only. 0 is installable, the most permissive.
+   */
+  uint32_t fs_type;
+  /**
+   * 1 if fsType allows embedding the outlines in a document.
+   */
+  uint32_t embeddable;
+  /**
+   * 1 if fsType allows embedding a subset rather than the whole font.
+   */
+  uint32_t subsettable;
+} HarfRustFontStyle;
+
+/**
+ * Values from the `post` table, as reported by `harfrust_font_post_info`.
+ */
+typedef struct HarfRustPostInfo {
+  /**
+   * Degrees counter-clockwise from vertical; negative for fonts leaning
+   * right, as PDF ItalicAngle.
+   */
+  float italic_angle;
+  /**
+   * Top of the underline in font units, negative below the baseline.
+   */
+  int32_t underline_position;
+  /**
+   * Underline thickness in font units.
+   */
+  int32_t underline_thickness;
+  /**
+   * 1 if the font is monospaced (PDF FixedPitch flag).
+   */
+  uint32_t is_fixed_pitch;
+} HarfRustPostInfo;
+
+/**
+ * Values from the `head` table, as reported by `harfrust_font_head_info`.
+ */
+typedef struct HarfRustHeadInfo {
+  /**
+   * Bounding box of all glyphs in font units (PDF FontBBox once scaled
+   * to 1000 units per em).
+   */
+  int32_t x_min;
+  int32_t y_min;
+  int32_t x_max;
+  int32_t y_max;
+  /**
+   * head flags bits, e.g. 0x01 baseline at y=0 and 0x02 left sidebearing
+   * at x=0.
+   */
+  uint32_t flags;
+  /**
+   * macStyle bits: 0x01 bold, 0x02 italic, 0x04 underline, 0x08 outline,
+   * 0x10 shadow, 0x20 condensed and 0x40 extended.
+   */
+  uint32_t mac_style;
+} HarfRustHeadInfo;
+
+/**
+ * Strikeout line from the OS/2 table, in font units.
+ */
+typedef struct HarfRustStrikeout {
+  /**
+   * Top of the strikeout line, above the baseline.
+   */
+  int32_t position;
+  /**
+   * Strikeout thickness.
+   */
+  int32_t thickness;
+} HarfRustStrikeout;
+
+/**
+ * Font-wide metrics a PDF text extractor checks font descriptors and width
+ * arrays against, in font units.
+ */
+typedef struct HarfRustPdfMetrics {
+  int32_t units_per_em;
+  /**
+   * Typographic ascent (positive, above the baseline).
+   */
+  int32_t ascent;
+  /**
+   * Typographic descent (negative, below the baseline).
+   */
+  int32_t descent;
+  /**
+   * Most common non-zero glyph advance, the natural CID font DW.
+   */
+  int32_t default_width;
+  /**
+   * Advance of the .notdef glyph, the natural MissingWidth.
+   */
+  int32_t missing_width;
+} HarfRustPdfMetrics;
+
+/**
+ * One glyph outline path command. Unused control points are zero.
+ */
+typedef struct HarfRustPathCommand {
+  /**
+   * The operator.
+   */
+  enum HarfRustPathVerb verb;
+  /**
+   * First control point (`CubicTo` only).
+   */
+  float x1;
+  float y1;
+  /**
+   * Second control point (`CubicTo` only).
+   */
+  float x2;
+  float y2;
+  /**
+   * End point.
+   */
+  float x;
+  float y;
+} HarfRustPathCommand;
+
+/**
+ * Vertical writing-mode metrics of a glyph in PDF glyph space (1000 units
+ * per em), as written to the W2 array of a vertical CID font.
+ */
+typedef struct HarfRustVerticalMetrics {
+  /**
+   * Vertical displacement; negative, since vertical text runs downwards.
+   */
+  float w1y;
+  /**
+   * Position vector from the horizontal origin to the vertical origin.
+   */
+  float vx;
+  float vy;
+} HarfRustVerticalMetrics;
+
+/**
+ * OpenType feature for shaping.
+ */
+typedef struct HarfRustFeature {
+  /**
+   * The feature tag (e.g. 'liga', 'kern').
+   */
+  uint32_t tag;
+  /**
+   * The value of the feature (0 = disabled, 1 = enabled, or other values).
+   */
+  uint32_t value;
+  /**
+   * The first cluster value the feature applies to. Cluster values are
+   * UTF-16 offsets for `harfrust_buffer_add_utf16` and UTF-8 byte offsets for
+   * `harfrust_buffer_add_str`; see `harfrust_feature_set_range`.
+   */
+  uint32_t start;
+  /**
+   * The cluster value one past the last one the feature applies to
+   * (u32::MAX for end).
+   */
+  uint32_t end;
+} HarfRustFeature;
+
+/**
+ * Advance a glyph is given after shaping, see
+ * `harfrust_font_set_advance_overrides`.
+ */
+typedef struct HarfRustAdvanceOverride {
+  uint32_t glyph_id;
+  /**
+   * Horizontal advance in font units.
+   */
+  int32_t advance;
+} HarfRustAdvanceOverride;
+
+/**
+ * One run of UTF-16 text to shape in a batch.
+ */
+typedef struct HarfRustRunDescriptor {
+  /**
+   * Pointer to the UTF-16 text of the run.
+   */
+  const uint16_t *text;
+  /**
+   * Length of the text in UTF-16 code units.
+   */
+  int32_t len;
+  /**
+   * Run direction (`Invalid` to guess from the text).
+   */
+  enum HarfRustDirection direction;
+  /**
+   * ISO 15924 script tag (0 to guess from the text).
+   */
+  uint32_t script;
+  /**
+   * Null-terminated BCP 47 language tag (null to use the default).
+   */
+  const char *language;
+  /**
+   * Features for this run (may be null).
+   */
+  const struct HarfRustFeature *features;
+  /**
+   * Number of entries in `features`.
+   */
+  uint32_t num_features;
+  /**
+   * Nonzero to normalize spaces as `harfrust_buffer_set_normalize_spaces`
+   * does.
+   */
+  int32_t normalize_spaces;
+} HarfRustRunDescriptor;
+
+/**
+ * Glyph information after shaping.
+ */
+typedef struct HarfRustGlyphInfo {
+  /**
+   * The glyph ID in the font.
+   */
+  uint32_t glyph_id;
+  /**
+   * The cluster index (position in original text).
+   */
+  uint32_t cluster;
+} HarfRustGlyphInfo;
+
+/**
+ * Glyph positioning information after shaping.
+ */
+typedef struct HarfRustGlyphPosition {
+  /**
+   * Horizontal advance after drawing this glyph.
+   */
+  int32_t x_advance;
+  /**
+   * Vertical advance after drawing this glyph.
+   */
+  int32_t y_advance;
+  /**
+   * Horizontal offset for drawing.
+   */
+  int32_t x_offset;
+  /**
+   * Vertical offset for drawing.
+   */
+  int32_t y_offset;
+} HarfRustGlyphPosition;
+
+/**
+ * Placement of a single line of text in a rectangle, in the rectangle's
+ * units (e.g. points).
+ */
+typedef struct HarfRustLineLayout {
+  /**
+   * Font size the line was shaped at.
+   */
+  float font_size;
+  /**
+   * Pen position of the first glyph, from the rectangle's lower-left corner.
+   */
+  float x;
+  /**
+   * Baseline, from the rectangle's lower-left corner.
+   */
+  float y;
+  /**
+   * Total advance of the line.
+   */
+  float width;
+} HarfRustLineLayout;
+
+/**
+ * A shaped run of a multi-run line, for `harfrust_layout_line`.
+ */
+typedef struct HarfRustLayoutRun {
+  /**
+   * The font the run was shaped with.
+   */
+  const struct HarfRustFont *font;
+  /**
+   * The shaping result of the run, with positions in font units.
+   */
+  const struct HarfRustGlyphBuffer *glyphs;
+  /**
+   * Font size of the run, in line units (e.g. points).
+   */
+  float font_size;
+  /**
+   * Baseline shift in line units, positive upwards, as the PDF text rise
+   * (Ts): raises superscript runs and lowers subscript runs.
+   */
+  float rise;
+} HarfRustLayoutRun;
+
+/**
+ * Where a run of a multi-run line goes, relative to the line's origin on
+ * its baseline, in line units.
+ */
+typedef struct HarfRustRunPlacement {
+  /**
+   * Pen position of the run's first glyph.
+   */
+  float x;
+  /**
+   * Baseline of the run, positive upwards (the run's rise).
+   */
+  float y;
+} HarfRustRunPlacement;
+
+/**
+ * Extent of a multi-run line in line units, with each run's ascent and
+ * descent moved by its rise.
+ */
+typedef struct HarfRustLineMetrics {
+  /**
+   * Total advance of the line.
+   */
+  float width;
+  /**
+   * Highest ascent above the line's baseline.
+   */
+  float ascent;
+  /**
+   * Lowest descent, negative below the line's baseline.
+   */
+  float descent;
+  /**
+   * Largest line gap of the line's fonts.
+   */
+  float line_gap;
+} HarfRustLineMetrics;
+
+/**
+ * Library-wide settings passed to `harfrust_init`. Zeroed fields take their
+ * defaults.
+ */
+typedef struct HarfRustConfig {
+  /**
+   * Size of the struct in bytes as the caller knows it, so fields added in
+   * later versions are left at their defaults for older callers.
+   */
+  uint32_t size;
+  /**
+   * Limits of the shaping result cache, as for `harfrust_cache_configure`;
+   * 0 disables the cache.
+   */
+  uint32_t result_cache_max_entries;
+  uint64_t result_cache_max_bytes;
+  /**
+   * Shape plans kept per font; 0 for the default of 32.
+   */
+  uint32_t plan_cache_capacity;
+  /**
+   * Threads `harfrust_shape_batch_parallel` uses when passed 0; 0 for one
+   * per available core. Larger values are clamped to the core count.
+   */
+  uint32_t num_threads;
+  /**
+   * Non-zero validates handles in release builds too (see the `checked`
+   * feature). Debug builds always do.
+   */
+  int32_t check_handles;
+  /**
+   * Least severe message passed to the log callback.
+   */
+  enum HarfRustLogLevel log_level;
+} HarfRustConfig;
+
+/**
+ * Allocates `size` bytes aligned to `align` (a power of two), returning
+ * null on failure.
+ */
+typedef void *(*HarfRustAllocFn)(uintptr_t size, uintptr_t align, void *user_data);
+
+/**
+ * Frees memory returned by the allocation callback, with the size and
+ * alignment it was requested with.
+ */
+typedef void (*HarfRustFreeFn)(void *ptr, uintptr_t size, uintptr_t align, void *user_data);
+
+/**
+ * Resizes an allocation to `new_size` bytes with the same alignment, keeping
+ * its contents, and returns null (leaving it untouched) on failure.
+ */
+typedef void *(*HarfRustReallocFn)(void *ptr,
+                                   uintptr_t size,
+                                   uintptr_t align,
+                                   uintptr_t new_size,
+                                   void *user_data);
+
+/**
+ * Callback receiving diagnostic messages as NUL-terminated UTF-8. The
+ * message is only valid for the duration of the call, which may happen on
+ * any thread.
+ */
+typedef void (*HarfRustLogCallback)(enum HarfRustLogLevel level,
+                                    const char *message,
+                                    void *user_data);
+
+/**
+ * Native memory and handles held by the library, as reported by
+ * `harfrust_memory_stats`. Byte counts cover the large allocations only.
+ */
+typedef struct HarfRustMemoryStats {
+  /**
+   * Font data copied into the library; data shared by several fonts is
+   * counted once. Caller-owned data (`harfrust_font_from_data_borrowed`)
+   * is not included.
+   */
+  uint64_t font_data_bytes;
+  /**
+   * Font files mapped into memory by `harfrust_font_from_file`.
+   */
+  uint64_t mapped_font_bytes;
+  /**
+   * Per-font glyph advance tables.
+   */
+  uint64_t advance_cache_bytes;
+  /**
+   * Shaping result cache (see `harfrust_cache_configure`).
+   */
+  uint64_t result_cache_bytes;
+  uint32_t result_cache_entries;
+  /**
+   * Shape plans cached across all fonts.
+   */
+  uint32_t shape_plans;
+  /**
+   * Shaping buffers kept for reuse across all threads (`arena` feature).
+   */
+  uint32_t pooled_buffers;
+  /**
+   * Live handles by type.
+   */
+  uint32_t fonts;
+  uint32_t buffers;
+  uint32_t buffer_states;
+  uint32_t glyph_buffers;
+  uint32_t color_bitmaps;
+  uint32_t subsetters;
+  uint32_t blobs;
+  uint32_t shapers;
+  uint32_t instances;
+} HarfRustMemoryStats;
+
+/**
+ * Text quality counts of one shaping result, as reported by
+ * `harfrust_glyph_buffer_get_summary`.
+ */
+typedef struct HarfRustShapeSummary {
+  uint32_t glyph_count;
+  /**
+   * Glyphs the font has no mapping for (glyph 0, .notdef).
+   */
+  uint32_t notdef_count;
+  /**
+   * Dotted circles (U+25CC) shown as the base of a mark that has none.
+   * Dotted circles in the source text are included.
+   */
+  uint32_t dotted_circle_count;
+  /**
+   * 1 if the result was produced without layout tables; see
+   * `harfrust_glyph_buffer_is_degraded`.
+   */
+  uint32_t degraded;
+} HarfRustShapeSummary;
+
+/**
+ * A shaped run placed on a line, for `harfrust_line_extract_text`.
+ */
+typedef struct HarfRustLineRun {
+  /**
+   * The shaping result of the run.
+   */
+  const struct HarfRustGlyphBuffer *glyphs;
+  /**
+   * The UTF-16 text the run was shaped from; clusters are offsets into it.
+   */
+  const uint16_t *text;
+  /**
+   * Length of the text in UTF-16 code units.
+   */
+  int32_t len;
+  /**
+   * Pen position of the run's first glyph on the line.
+   */
+  float x;
+  /**
+   * Line units per glyph position unit, e.g. font size / units per em.
+   */
+  float scale;
+} HarfRustLineRun;
+
+/**
+ * A character of a line in reading order, with the box it occupies.
+ */
+typedef struct HarfRustExtractedChar {
+  /**
+   * The Unicode codepoint.
+   */
+  uint32_t codepoint;
+  /**
+   * Index of the run the character comes from.
+   */
+  uint32_t run;
+  /**
+   * Offset of the character in its run's text, in UTF-16 code units.
+   */
+  uint32_t offset;
+  /**
+   * Left edge of the character on the line.
+   */
+  float x;
+  /**
+   * Width of the character; a ligature's width is split among its
+   * characters.
+   */
+  float width;
+} HarfRustExtractedChar;
+
+/**
+ * A range of UTF-16 code units in the source text.
+ */
+typedef struct HarfRustTextRange {
+  /**
+   * Offset of the first code unit.
+   */
+  uint32_t start;
+  /**
+   * Number of code units (0 if the glyph carries no text of its own).
+   */
+  uint32_t length;
+} HarfRustTextRange;
+
+/**
+ * Where a shaped glyph came from; see
+ * `harfrust_glyph_buffer_get_substitution_trace`.
+ */
+typedef struct HarfRustGlyphTrace {
+  enum HarfRustSubstitutionKind kind;
+  /**
+   * Tag of the feature the lookup belongs to, directly or through a
+   * contextual lookup, or 0.
+   */
+  uint32_t feature;
+  /**
+   * Index of the GSUB lookup, or -1.
+   */
+  int32_t lookup_index;
+  /**
+   * Position of the glyph in its alternate set for `Alternate`, or -1.
+   */
+  int32_t alternate_index;
+  /**
+   * UTF-16 range of the input characters: the glyph's whole cluster, so
+   * the components of a ligature.
+   */
+  uint32_t input_start;
+  uint32_t input_length;
+} HarfRustGlyphTrace;
+
+/**
+ * Total advance of a run of consecutive glyphs sharing a cluster.
+ */
+typedef struct HarfRustClusterAdvance {
+  /**
+   * The cluster value shared by the glyphs.
+   */
+  uint32_t cluster;
+  /**
+   * Index of the first glyph of the cluster in the glyph buffer.
+   */
+  uint32_t first_glyph;
+  /**
+   * Number of glyphs in the cluster.
+   */
+  uint32_t glyph_count;
+  /**
+   * Sum of the glyphs' horizontal advances.
+   */
+  int32_t x_advance;
+  /**
+   * Sum of the glyphs' vertical advances.
+   */
+  int32_t y_advance;
+} HarfRustClusterAdvance;
+
+/**
+ * Extra per-glyph transform produced by synthesized layout effects (small
+ * caps, superscripts, rotated glyphs). Applied around the glyph origin after
+ * positioning; advances and offsets already account for it.
+ */
+typedef struct HarfRustGlyphTransform {
+  /**
+   * Uniform scale factor for the glyph outline (1.0 = unscaled).
+   */
+  float scale;
+  /**
+   * Counter-clockwise rotation of the glyph outline in degrees, e.g. -90
+   * for glyphs set sideways by `harfrust_shape_vertical_mixed`.
+   */
+  float rotation;
+} HarfRustGlyphTransform;
+
+/**
+ * Replacement for one palette entry when rendering color glyphs, e.g. to
+ * adapt icons to a dark theme.
+ */
+typedef struct HarfRustColorOverride {
+  /**
+   * CPAL palette entry index, or 0xFFFF for the text foreground color
+   * (black unless overridden).
+   */
+  uint16_t index;
+  /**
+   * Color components, not premultiplied.
+   */
+  uint8_t red;
+  uint8_t green;
+  uint8_t blue;
+  uint8_t alpha;
+} HarfRustColorOverride;
+
+/**
+ * Layout of a rendered color glyph bitmap.
+ */
+typedef struct HarfRustBitmapInfo {
+  /**
+   * Width of the bitmap in pixels.
+   */
+  uint32_t width;
+  /**
+   * Height of the bitmap in pixels.
+   */
+  uint32_t height;
+  /**
+   * Distance from the pen position to the left edge of the bitmap, in pixels.
+   */
+  int32_t left;
+  /**
+   * Distance from the baseline up to the top edge of the bitmap, in pixels.
+   */
+  int32_t top;
+  /**
+   * Number of bytes per row (always `width * 4`).
+   */
+  uint32_t stride;
+} HarfRustBitmapInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a new empty buffer for text shaping.
+ */
+struct HarfRustBuffer *harfrust_buffer_new(void);
+
+/**
+ * Adds a UTF-8 string to the buffer.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `text` must be null or a
+ * NUL-terminated string.
+ */
+HarfRustStatus harfrust_buffer_add_str(struct HarfRustBuffer *buffer, const char *text);
+
+/**
+ * Adds `byte_len` bytes of UTF-8 text to the buffer. Unlike
+ * `harfrust_buffer_add_str` the text needs no NUL terminator, and embedded
+ * NULs are added like any other character. Clusters are byte offsets.
+ * Returns `InvalidUtf8` if the text is not valid UTF-8, in which case
+ * nothing is added.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `text` must be null or point
+ * to `byte_len` bytes.
+ */
+HarfRustStatus harfrust_buffer_add_utf8(struct HarfRustBuffer *buffer,
+                                        const uint8_t *text,
+                                        int32_t byte_len);
+
+/**
+ * Adds a UTF-16 string to the buffer.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `text` must be null or point
+ * to `len` UTF-16 code units.
+ */
+HarfRustStatus harfrust_buffer_add_utf16(struct HarfRustBuffer *buffer,
+                                         const uint16_t *text,
+                                         int32_t len);
+
+/**
+ * Same as `harfrust_buffer_add_utf16` with a 64-bit length. Returns
+ * `OutOfRange` if the text is too long for its offsets to fit the 32-bit
+ * cluster values.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `text` must be null or point
+ * to `len` UTF-16 code units.
+ */
+HarfRustStatus harfrust_buffer_add_utf16_64(struct HarfRustBuffer *buffer,
+                                            const uint16_t *text,
+                                            uint64_t len);
+
+/**
+ * Adds a UTF-16 string to the buffer after applying a case transform.
+ * Characters produced by the transform keep the cluster (UTF-16 offset) of
+ * the original character they came from, so shaped glyphs map back to the
+ * untransformed text, e.g. both glyphs of "SS" from "ß" share its cluster.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `text` must be null or point
+ * to `len` UTF-16 code units.
+ */
+HarfRustStatus harfrust_buffer_add_utf16_transformed(struct HarfRustBuffer *buffer,
+                                                     const uint16_t *text,
+                                                     int32_t len,
+                                                     enum HarfRustCaseTransform transform);
+
+/**
+ * Returns the number of characters currently in the buffer.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+int32_t harfrust_buffer_len(const struct HarfRustBuffer *buffer);
+
+/**
+ * Clears all content from the buffer, preparing it for reuse.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+void harfrust_buffer_clear(struct HarfRustBuffer *buffer);
+
+/**
+ * Saves the buffer's text, context and segment properties (direction,
+ * script, language, flags and cluster level). Since shaping consumes the buffer, a
+ * snapshot taken before a trial shape can be restored into a fresh buffer if
+ * the trial is rejected. Returns null if the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+struct HarfRustBufferState *harfrust_buffer_save_state(const struct HarfRustBuffer *buffer);
+
+/**
+ * Replaces the buffer's contents, context and segment properties with a
+ * saved state.
+ * The state is not consumed and can be restored any number of times.
+ * Returns `NullArgument` if the buffer or state is null.
+ *
+ * # Safety
+ *
+ * `buffer` and `state` must be null or live handles.
+ */
+HarfRustStatus harfrust_buffer_restore_state(struct HarfRustBuffer *buffer,
+                                             const struct HarfRustBufferState *state);
+
+/**
+ * Frees a state previously created by `harfrust_buffer_save_state`.
+ *
+ * # Safety
+ *
+ * `state` must be null or a live handle.
+ */
+void harfrust_buffer_state_free(struct HarfRustBufferState *state);
+
+/**
+ * Frees a buffer previously created by `harfrust_buffer_new`.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+void harfrust_buffer_free(struct HarfRustBuffer *buffer);
+
+/**
+ * Sets the text direction of the buffer.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+void harfrust_buffer_set_direction(struct HarfRustBuffer *buffer, enum HarfRustDirection direction);
+
+/**
+ * Gets the text direction of the buffer.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+enum HarfRustDirection harfrust_buffer_get_direction(const struct HarfRustBuffer *buffer);
+
+/**
+ * Sets the script of the buffer using an ISO 15924 tag (4 bytes as u32).
+ * Example: "Latn" = 0x4C61746E
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+void harfrust_buffer_set_script(struct HarfRustBuffer *buffer, uint32_t script_tag);
+
+/**
+ * Gets the script of the buffer as an ISO 15924 tag (4 bytes as u32).
+ * Returns 0 if no script is set.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+uint32_t harfrust_buffer_get_script(const struct HarfRustBuffer *buffer);
+
+/**
+ * Sets the language of the buffer from a BCP 47 language tag string.
+ * Example: "en", "en-US", "zh-Hans"
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `language` must be null or a
+ * NUL-terminated string.
+ */
+HarfRustStatus harfrust_buffer_set_language(struct HarfRustBuffer *buffer, const char *language);
+
+/**
+ * Sets how the buffer shapes soft hyphens (U+00AD), for text added before or
+ * after the call; see `HarfRustSoftHyphen`. Soft hyphens shown as a hyphen
+ * keep their own cluster, so the glyph still maps back to the soft hyphen.
+ * The setting survives `harfrust_buffer_clear`. Returns `NullArgument` if
+ * the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+HarfRustStatus harfrust_buffer_set_soft_hyphen(struct HarfRustBuffer *buffer,
+                                               enum HarfRustSoftHyphen mode);
+
+/**
+ * Returns how the buffer shapes soft hyphens, or `Hidden` if the buffer is
+ * null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+enum HarfRustSoftHyphen harfrust_buffer_get_soft_hyphen(const struct HarfRustBuffer *buffer);
+
+/**
+ * Enables (non-zero) or disables (0, the default) shaping no-break, fixed
+ * width and narrow spaces (U+00A0, U+2000 to U+200A, U+202F and U+205F) as
+ * an ordinary space, with its glyph and advance, when the font has no glyph
+ * for them, as is common in subset fonts embedded in PDFs. Otherwise such
+ * spaces are shaped with the space glyph at a width derived from the em, or
+ * as .notdef without layout tables. Applies to the shape call consuming the
+ * buffer, and to the copies `harfrust_shape_copy` and `harfrust_shape_into`
+ * shape; results report it in `harfrust_glyph_buffer_get_flags`. The
+ * setting survives `harfrust_buffer_clear`. Returns `NullArgument` if the
+ * buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+HarfRustStatus harfrust_buffer_set_normalize_spaces(struct HarfRustBuffer *buffer, int32_t enabled);
+
+/**
+ * Returns 1 if the buffer normalizes missing spaces, 0 if not, or a negative
+ * status if the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+int32_t harfrust_buffer_get_normalize_spaces(const struct HarfRustBuffer *buffer);
+
+/**
+ * Sets the UTF-16 text around the buffer's: `pre_context` comes before it
+ * and `post_context` after it. Context is not shaped but is seen by shaping
+ * decisions that look past the buffer's ends, such as Arabic joining, so a
+ * run split from a word (e.g. at a font change) keeps the forms it has in
+ * the word. Only the nearest 5 characters on each side are used. Null with
+ * a length of 0 sets no context; clearing the buffer removes it. Returns
+ * `NullArgument` if the buffer, or a text with a non-zero length, is null,
+ * or `InvalidArgument` if a length is negative.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle; `pre_context` must be null or
+ * point to `pre_len` UTF-16 code units; and `post_context` must be null or
+ * point to `post_len` UTF-16 code units.
+ */
+HarfRustStatus harfrust_buffer_set_context(struct HarfRustBuffer *buffer,
+                                           const uint16_t *pre_context,
+                                           int32_t pre_len,
+                                           const uint16_t *post_context,
+                                           int32_t post_len);
+
+/**
+ * Guesses and sets the segment properties (direction, script, language)
+ * based on the buffer contents.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+void harfrust_buffer_guess_segment_properties(struct HarfRustBuffer *buffer);
+
+/**
+ * Detects the base direction of a paragraph of UTF-16 text from its first
+ * strong character, skipping isolated (LRI/RLI/FSI...PDI) content.
+ * Returns `Invalid` if the text has no strong characters; passing that to
+ * `harfrust_buffer_set_direction` keeps automatic direction guessing at
+ * shape time.
+ *
+ * # Safety
+ *
+ * `text` must be null or point to `len` UTF-16 code units.
+ */
+enum HarfRustDirection harfrust_text_detect_base_direction(const uint16_t *text, int32_t len);
+
+/**
+ * Creates a font from raw font data (TTF/OTF bytes, or WOFF with the `woff`
+ * feature). The data is copied. Malformed fonts (e.g. embedded in PDFs with
+ * a bad sfnt version, truncated tables or a directory that overruns the
+ * data) are repaired as `harfrust_font_repair` does when they would
+ * otherwise fail to load or lack core tables. Fonts created from identical
+ * data share a single copy of it.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+struct HarfRustFont *harfrust_font_from_data(const uint8_t *data, int32_t len);
+
+/**
+ * Same as `harfrust_font_from_data` with a 64-bit length, for data of 2 GB
+ * or more. Returns null if the data cannot be addressed or copied on this
+ * platform, or is larger than the 4 GB a font file can describe.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+struct HarfRustFont *harfrust_font_from_data64(const uint8_t *data, uint64_t len);
+
+/**
+ * Creates a font from raw font data at a specific index (for font collections).
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+struct HarfRustFont *harfrust_font_from_data_index(const uint8_t *data,
+                                                   int32_t len,
+                                                   uint32_t index);
+
+/**
+ * Creates a font from raw font data at a specific index, choosing between
+ * lazy, eager and tolerant parsing. In eager mode the core tables are
+ * validated and caches are warmed before returning, and null is returned if
+ * validation fails. In tolerant mode recoverable corruption is repaired or
+ * skipped and reported as warnings through the log callback (see
+ * `harfrust_set_log_callback`).
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+struct HarfRustFont *harfrust_font_from_data_with_mode(const uint8_t *data,
+                                                       int32_t len,
+                                                       uint32_t index,
+                                                       enum HarfRustParseMode mode);
+
+/**
+ * Creates a font that reads `data` in place instead of copying it. The
+ * memory must stay valid and unmoved until `release_callback` is invoked
+ * with `user_data`, which happens when the font is freed, or before this
+ * function returns if creation fails. The callback may be null.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes that stay valid and unmoved
+ * until `release_callback` is invoked, and `release_callback` must be null
+ * or safe to call with `user_data` on any thread.
+ */
+struct HarfRustFont *harfrust_font_from_data_borrowed(const uint8_t *data,
+                                                      int32_t len,
+                                                      HarfRustReleaseCallback release_callback,
+                                                      void *user_data);
+
+/**
+ * Creates a font from a bare CFF font program, such as a PDF FontFile3
+ * stream (Type1C or CIDFontType0C), by wrapping it in a minimal OpenType
+ * font. Advances come from the charstrings at 1000 units per em. Glyphs are
+ * mapped to Unicode through "uniXXXX"/"uXXXX" and ASCII glyph names only;
+ * CID-keyed fonts have no Unicode mapping and must be addressed by glyph ID.
+ * The data is copied. Returns null if the data is not a readable CFF font.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+struct HarfRustFont *harfrust_font_from_cff(const uint8_t *data, int32_t len);
+
+/**
+ * Repairs common malformations of a single font file, as found in fonts
+ * embedded in PDFs: an unrecognized sfnt version, a table count larger than
+ * the directory, tables running past the end of the data, unsorted or
+ * duplicate table records, a bad `head` magic number and stale checksums.
+ * Returns the rebuilt font, or null if the data is null, a collection, or
+ * has no readable table directory. Free the result with `harfrust_blob_free`.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+struct HarfRustBlob *harfrust_font_repair(const uint8_t *data, int32_t len);
+
+/**
+ * Creates a font from face `face_index` of the font file at `path` (UTF-8),
+ * memory-mapping the file instead of reading it into memory. The file must
+ * not be modified while the font is alive. Returns null if the file cannot
+ * be opened or the face cannot be parsed.
+ *
+ * # Safety
+ *
+ * `path` must be null or a NUL-terminated string.
+ */
+struct HarfRustFont *harfrust_font_from_file(const char *path, uint32_t face_index);
+
+/**
+ * Returns the number of faces in font data: the face count of a TrueType
+ * collection (.ttc/.otc), 1 for a single font, or 0 if the data is null or
+ * not a font. Valid indices for `harfrust_font_from_data_index` are
+ * 0 to count - 1.
+ *
+ * # Safety
+ *
+ * `data` must be null or point to `len` bytes.
+ */
+int32_t harfrust_font_collection_count(const uint8_t *data, int32_t len);
+
+/**
+ * Returns the index of the face the font was created from (0 for fonts that
+ * are not part of a collection), or -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_get_index(const struct HarfRustFont *font);
+
+/**
+ * Returns a 64-bit FNV-1a hash of the font file data (after WOFF decoding;
+ * the whole file for a face of a collection), or 0 if the font is null.
+ * Fonts created from identical bytes share one copy of the data and report
+ * the same hash, which callers can use to deduplicate embedded fonts.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+uint64_t harfrust_font_data_hash(const struct HarfRustFont *font);
+
+/**
+ * Writes a key identifying a glyph as rendered from this font at the given
+ * variation settings and pixels-per-em to `out`. The key combines the font
+ * data hash, face index, glyph id, normalized axis coordinates and size, so
+ * it is stable across processes and identical for settings that resolve to
+ * the same instance (omitted axes and explicit defaults, out-of-range values
+ * and their clamped limits). Returns `NullArgument` if the font or `out` is
+ * null or `variations` is null with a non-zero count, `OutOfRange` if `ppem`
+ * is negative or not finite.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `variations` must be null or point
+ * to `num_variations` variations; and `out` must be null or point to a
+ * writable `HarfRustCacheKey`.
+ */
+HarfRustStatus harfrust_glyph_cache_key(const struct HarfRustFont *font,
+                                        uint32_t glyph_id,
+                                        const struct HarfRustVariation *variations,
+                                        uint32_t num_variations,
+                                        float ppem,
+                                        struct HarfRustCacheKey *out);
+
+/**
+ * Returns 1 if the font's GSUB, GPOS or GDEF table exists but cannot be
+ * read, so every shaping function shapes it as `harfrust_shape_fallback`
+ * does; 0 if not; -1 if the font is null. Unless the font was created in
+ * `HarfRustParseMode::Eager` or `Tolerant` mode, the tables are read on the
+ * first call to this or a shaping function.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_is_degraded(const struct HarfRustFont *font);
+
+/**
+ * Returns the font's units per em.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_units_per_em(const struct HarfRustFont *font);
+
+/**
+ * Returns the number of glyphs in the font (maxp numGlyphs), so valid glyph
+ * IDs are `0..count`. Returns -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_glyph_count(const struct HarfRustFont *font);
+
+/**
+ * Returns the horizontal advance of a glyph in font units.
+ * Results are cached per font, so repeated width-only queries are cheap.
+ * Returns -1 (`NullArgument`) if the font is null or -3 (`OutOfRange`) if
+ * the glyph ID is out of range.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_glyph_advance(const struct HarfRustFont *font, uint32_t glyph_id);
+
+/**
+ * Returns the horizontal advance of a glyph in font units at a variation
+ * instance from `harfrust_instance_new` (null for the default instance).
+ * Each instance's advances are cached separately. Returns the same errors as
+ * `harfrust_font_glyph_advance`.
+ *
+ * # Safety
+ *
+ * `font` and `instance` must be null or live handles.
+ */
+int32_t harfrust_font_glyph_advance_with_instance(const struct HarfRustFont *font,
+                                                  const struct HarfRustInstance *instance,
+                                                  uint32_t glyph_id);
+
+/**
+ * Copies the horizontal advance of every glyph (indexed by glyph ID) into `out`.
+ * At most `capacity` entries are written. Returns the total number of glyphs
+ * in the font, so a call with a null `out` can be used to size the array.
+ * Returns -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or have room
+ * for `capacity` entries.
+ */
+int32_t harfrust_font_all_advances(const struct HarfRustFont *font,
+                                   uint16_t *out,
+                                   uint32_t capacity);
+
+/**
+ * Writes the ink extents of the `count` glyphs in `glyphs` to `out`, which
+ * must have room for `count` entries, so that e.g. an atlas builder can
+ * measure many glyphs in one call. Edges are rounded outwards. Glyphs
+ * without an outline (such as spaces) and glyph IDs out of range get zero
+ * extents. Returns `NullArgument` if the font, `glyphs` or `out` is null
+ * (the arrays may be null if `count` is 0).
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `glyphs` must be null or point to
+ * `count` glyph IDs; and `out` must be null or have room for `count`
+ * entries.
+ */
+HarfRustStatus harfrust_font_glyph_extents_batch(const struct HarfRustFont *font,
+                                                 const uint32_t *glyphs,
+                                                 uint32_t count,
+                                                 struct HarfRustGlyphExtents *out);
+
+/**
+ * Looks up the glyph the font's best cmap subtable maps a Unicode codepoint
+ * to, without shaping, and writes it to `out_glyph` (0 if the codepoint is
+ * not mapped). Returns 1 if the codepoint is mapped, 0 if not, or -1 if the
+ * font or `out_glyph` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out_glyph` must be null or
+ * point to a writable `u32`.
+ */
+int32_t harfrust_font_nominal_glyph(const struct HarfRustFont *font,
+                                    uint32_t codepoint,
+                                    uint32_t *out_glyph);
+
+/**
+ * Copies all (codepoint, glyph) pairs of the font's best cmap subtable into
+ * `out`, ordered by codepoint. At most `capacity` entries are written.
+ * Returns the total number of mappings, so a call with a null `out` can be
+ * used to size the array. Returns -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or have room
+ * for `capacity` entries.
+ */
+int32_t harfrust_font_cmap_mappings(const struct HarfRustFont *font,
+                                    struct HarfRustCmapEntry *out,
+                                    uint32_t capacity);
+
+/**
+ * Checks whether the font renders a character sequence (e.g. an emoji ZWJ
+ * sequence) as a single glyph.
+ * The sequence is shaped and counts as supported when it forms one cluster
+ * with exactly one visible glyph and no .notdef glyphs; zero-advance glyphs
+ * such as hidden joiners are ignored.
+ * Returns 1 if supported, 0 if not, or a negative value on invalid arguments.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `text` must be null or point to
+ * `len` UTF-16 code units.
+ */
+int32_t harfrust_font_supports_sequence(const struct HarfRustFont *font,
+                                        const uint16_t *text,
+                                        int32_t len);
+
+/**
+ * Checks whether UTF-16 text fits within `max_width` font units.
+ * Sums the nominal (cmap + hmtx) advances without shaping and stops as soon
+ * as the limit is exceeded, so kerning and ligatures are not taken into
+ * account. The font's advance overrides, character spacing (once per
+ * character and the marks following it, as shaping adds it once per
+ * cluster) and horizontal scaling apply. Returns 1 if the text fits, 0 if not, or a
+ * negative value on invalid arguments.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `text` must be null or point to
+ * `len` UTF-16 code units.
+ */
+int32_t harfrust_text_fits(const struct HarfRustFont *font,
+                           const uint16_t *text,
+                           int32_t len,
+                           int32_t max_width);
+
+/**
+ * Eagerly does the parsing and cache filling that would otherwise happen on
+ * the first shape or metrics call: walks the cmap, resolves every glyph
+ * advance and shapes a short sample so the shaper's lookup data is built.
+ * Safe to call from a background thread while the font is otherwise unused.
+ * Returns `NullArgument` if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+HarfRustStatus harfrust_font_warmup(const struct HarfRustFont *font);
+
+/**
+ * Freezes a font: from now on no call changes how it shapes or measures, so
+ * the handle can be shared by any number of threads without further
+ * synchronization. Its lazily filled caches stay internally synchronized
+ * and do not change results. Functions that would change the font (such as
+ * `harfrust_font_set_feature_overrides`) fail with `Unsupported` on a frozen
+ * font, including through handles from `harfrust_font_retain`. Freezing is
+ * permanent, and freezing a frozen font does nothing.
+ * Returns `NullArgument` if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+HarfRustStatus harfrust_font_freeze(const struct HarfRustFont *font);
+
+/**
+ * Returns 1 if the font is frozen (see `harfrust_font_freeze`), 0 if not,
+ * or -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_is_frozen(const struct HarfRustFont *font);
+
+/**
+ * Writes the font's superscript or subscript size and offset (OS/2
+ * ySuperscript* and ySubscript* values, in font units) to `out`. Fonts without
+ * usable OS/2 values report conventional defaults.
+ * Returns `NullArgument` if the font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustScriptMetrics`.
+ */
+HarfRustStatus harfrust_font_get_script_metrics(const struct HarfRustFont *font,
+                                                enum HarfRustScriptPosition position,
+                                                struct HarfRustScriptMetrics *out);
+
+/**
+ * Writes the font's line layout metrics to `out` in one call. Ascent,
+ * descent and line gap follow the font's preferred vertical metrics (OS/2
+ * typographic when USE_TYPO_METRICS is set, else hhea); the x-height and cap
+ * height are those of `harfrust_font_get_x_height` and
+ * `harfrust_font_get_cap_height`. The baseline of a line lies `ascent` below
+ * its top, and lines are `ascent - descent + line_gap` apart. Returns
+ * `NullArgument` if the font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustFontMetrics`.
+ */
+HarfRustStatus harfrust_font_get_metrics(const struct HarfRustFont *font,
+                                         struct HarfRustFontMetrics *out);
+
+/**
+ * Writes the font's weight and width classes, fsSelection style bits and
+ * fsType embedding permissions to `out`. Fonts without an OS/2 table report
+ * regular weight, normal width, the italic and bold bits of the head table's
+ * macStyle, and no embedding restrictions. Returns `NullArgument` if the
+ * font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustFontStyle`.
+ */
+HarfRustStatus harfrust_font_get_style(const struct HarfRustFont *font,
+                                       struct HarfRustFontStyle *out);
+
+/**
+ * Writes the font's italic angle, underline position and thickness, and
+ * whether it is monospaced, from the `post` table, to `out`. Fonts without a
+ * `post` table report an upright proportional font with an underline 10% of
+ * the em below the baseline, 5% of the em thick. Returns `NullArgument` if
+ * the font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustPostInfo`.
+ */
+HarfRustStatus harfrust_font_post_info(const struct HarfRustFont *font,
+                                       struct HarfRustPostInfo *out);
+
+/**
+ * Writes the font's global bounding box, flags and macStyle from the head
+ * table to `out`. The bounding box is what a PDF FontDescriptor declares as
+ * /FontBBox and bounds every glyph, so it can be used to clip rendered runs
+ * conservatively. Fonts without a head table report all zeros.
+ * Returns `NullArgument` if the font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustHeadInfo`.
+ */
+HarfRustStatus harfrust_font_head_info(const struct HarfRustFont *font,
+                                       struct HarfRustHeadInfo *out);
+
+/**
+ * Writes the font's strikeout position and thickness (OS/2
+ * yStrikeoutPosition and yStrikeoutSize, in font units) to `out`, for
+ * drawing line-through decorations. Fonts without usable OS/2 values report
+ * a line 25% of the em above the baseline, 5% of the em thick. Superscript
+ * and subscript metrics are available from
+ * `harfrust_font_get_script_metrics`.
+ * Returns `NullArgument` if the font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustStrikeout`.
+ */
+HarfRustStatus harfrust_font_get_strikeout(const struct HarfRustFont *font,
+                                           struct HarfRustStrikeout *out);
+
+/**
+ * Writes the font's cap height in font units to `out_value`. Uses OS/2
+ * sCapHeight when present (version 2+), otherwise the top of the 'H' glyph,
+ * otherwise 70% of the em. `out_source` (optional) receives which was used.
+ * Returns `NullArgument` if the font or `out_value` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `out_value` must be null or point to
+ * a writable `i32`; and `out_source` must be null or point to a writable
+ * `HarfRustMetricSource`.
+ */
+HarfRustStatus harfrust_font_get_cap_height(const struct HarfRustFont *font,
+                                            int32_t *out_value,
+                                            enum HarfRustMetricSource *out_source);
+
+/**
+ * Writes the font's x-height in font units to `out_value`. Uses OS/2
+ * sxHeight when present (version 2+), otherwise the top of the 'x' glyph,
+ * otherwise 50% of the em. `out_source` (optional) receives which was used.
+ * Returns `NullArgument` if the font or `out_value` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `out_value` must be null or point to
+ * a writable `i32`; and `out_source` must be null or point to a writable
+ * `HarfRustMetricSource`.
+ */
+HarfRustStatus harfrust_font_get_x_height(const struct HarfRustFont *font,
+                                          int32_t *out_value,
+                                          enum HarfRustMetricSource *out_source);
+
+/**
+ * Writes an estimate of the dominant vertical stem width (PDF StemV) in font
+ * units to `out_value`, measured across the stem of 'l' or 'I'. Fonts
+ * without usable outlines get a heuristic based on the OS/2 weight class.
+ * `out_source` (optional) receives which method was used.
+ * Returns `NullArgument` if the font or `out_value` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `out_value` must be null or point to
+ * a writable `i32`; and `out_source` must be null or point to a writable
+ * `HarfRustMetricSource`.
+ */
+HarfRustStatus harfrust_font_estimate_stemv(const struct HarfRustFont *font,
+                                            int32_t *out_value,
+                                            enum HarfRustMetricSource *out_source);
+
+/**
+ * Writes the metrics PDF text extraction compares against (units per em,
+ * ascent, descent, default width and missing width) to `out`, so extraction
+ * heuristics and shaping use the same values. Ascent and descent follow the
+ * font's preferred vertical metrics (OS/2 typographic when USE_TYPO_METRICS
+ * is set, else hhea). Returns `NullArgument` if the font or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustPdfMetrics`.
+ */
+HarfRustStatus harfrust_font_get_pdf_metrics(const struct HarfRustFont *font,
+                                             struct HarfRustPdfMetrics *out);
+
+/**
+ * Writes the unhinted outline of a glyph as path commands, in a y-up glyph
+ * space of `units_per_em` units (1000 for PDF glyph space), or in font units
+ * if `units_per_em` is 0. Quadratic curves are converted to cubics, so the
+ * commands map directly onto PDF path operators. At most `capacity` commands
+ * are written. Returns the number of commands (0 for glyphs without an
+ * outline, such as spaces), so a call with a null `out` can be used to size
+ * the array. Returns -1 (`NullArgument`) if the font is null, -2
+ * (`InvalidArgument`) if `units_per_em` is negative, or -3 (`OutOfRange`) if
+ * the glyph ID is out of range.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or have room
+ * for `capacity` entries.
+ */
+int32_t harfrust_font_glyph_outline(const struct HarfRustFont *font,
+                                    uint32_t glyph_id,
+                                    float units_per_em,
+                                    struct HarfRustPathCommand *out,
+                                    uint32_t capacity);
+
+/**
+ * Writes the vertical metrics of `count` glyphs to `out`, in PDF glyph space
+ * and sign conventions, for the W2 array of a vertical (Identity-V) CID
+ * font. Advances come from `vmtx` and origins from `VORG` or `vmtx`; fonts
+ * without vertical tables get the advance of a full line (ascent minus
+ * descent) and an origin at the ascender, horizontally centered on the
+ * glyph. Returns the number of glyphs written, -1 if the font is null, or -2
+ * if `glyphs` or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `glyphs` must be null or point to
+ * `count` glyph IDs; and `out` must be null or have room for `count`
+ * entries.
+ */
+int32_t harfrust_font_vertical_metrics(const struct HarfRustFont *font,
+                                       const uint32_t *glyphs,
+                                       uint32_t count,
+                                       struct HarfRustVerticalMetrics *out);
+
+/**
+ * Adds a reference to a font, so that independent owners (e.g. a layout
+ * engine and a glyph cache) can share the handle and each call
+ * `harfrust_font_free` when done; the font and its data are freed by the
+ * last call. Returns `font`, or null if it is null or invalid.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+struct HarfRustFont *harfrust_font_retain(struct HarfRustFont *font);
+
+/**
+ * Releases a reference to a font created by `harfrust_font_from_data` (or
+ * added by `harfrust_font_retain`), freeing the font when it was the last.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+void harfrust_font_free(struct HarfRustFont *font);
+
+/**
+ * Sets `feature.start`/`feature.end` from a range of UTF-16 code units
+ * (.NET `string` indices) into `text`, converted to the cluster values of a
+ * buffer built with the given `unit`. `char_end` of -1 means the end of the
+ * text, which maps to u32::MAX so the feature also covers later additions;
+ * an explicit `char_end` of `len` stops the feature at the end of `text`.
+ * A range boundary inside a surrogate pair is widened to cover the whole pair.
+ * Returns `NullArgument` if the feature or text is null, `InvalidArgument`
+ * if `len` is negative, or `OutOfRange` if the range is outside the text.
+ *
+ * # Safety
+ *
+ * `feature` must be null or point to a writable `HarfRustFeature`, and
+ * `text` must be null or point to `len` UTF-16 code units.
+ */
+HarfRustStatus harfrust_feature_set_range(struct HarfRustFeature *feature,
+                                          const uint16_t *text,
+                                          int32_t len,
+                                          int32_t char_start,
+                                          int32_t char_end,
+                                          enum HarfRustClusterUnit unit);
+
+/**
+ * Builds feature ranges that turn digit/slash/digit spans (e.g. "1/2", or
+ * with U+2044 FRACTION SLASH) in UTF-16 `text` into fractions. Fonts with
+ * `frac` get one `frac` range per span; fonts with only `numr`/`dnom` get
+ * those over the numerator and denominator digits. Ranges are in cluster
+ * values for `unit`. At most `capacity` features are written. Returns the
+ * total number of features, so a call with a null `out` can be used to size
+ * the array. Returns -1 (`NullArgument`) if the font or text is null or -2
+ * (`InvalidArgument`) if `len` is negative.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `text` must be null or point to
+ * `len` UTF-16 code units; and `out` must be null or have room for
+ * `capacity` features.
+ */
+int32_t harfrust_font_fraction_features(const struct HarfRustFont *font,
+                                        const uint16_t *text,
+                                        int32_t len,
+                                        enum HarfRustClusterUnit unit,
+                                        struct HarfRustFeature *out,
+                                        uint32_t capacity);
+
+/**
+ * Writes the tags (4 bytes as u32) of the features the shaper applies by
+ * default to a run with the given ISO 15924 script and direction, limited to
+ * those the font actually implements for that script. Tags are in the order
+ * the shaper applies them; any of them can be turned off by passing it with
+ * value 0 to `harfrust_shape_with_features`. An `Invalid` direction uses the
+ * script's natural horizontal direction.
+ * At most `capacity` tags are written. Returns the total number of tags, or
+ * -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or have room
+ * for `capacity` entries.
+ */
+int32_t harfrust_font_default_features(const struct HarfRustFont *font,
+                                       uint32_t script_tag,
+                                       enum HarfRustDirection direction,
+                                       uint32_t *out,
+                                       uint32_t capacity);
+
+/**
+ * Sets features applied to every run shaped with this font, before the
+ * features passed to the shape call (which win where they overlap). Typical
+ * use is turning off a feature that a particular font implements badly, e.g.
+ * `calt` with value 0. Replaces any previous overrides; passing no features
+ * clears them. Shape calls already running are not affected.
+ * Returns `NullArgument` if the font is null, or if `features` is null with
+ * a non-zero count, and `Unsupported` if the font is frozen.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `features` must be null or point
+ * to `num_features` features.
+ */
+HarfRustStatus harfrust_font_set_feature_overrides(struct HarfRustFont *font,
+                                                   const struct HarfRustFeature *features,
+                                                   uint32_t num_features);
+
+/**
+ * Copies the font's feature overrides (see
+ * `harfrust_font_set_feature_overrides`) into `out`. At most `capacity`
+ * features are written. Returns the total number of overrides, so a call
+ * with a null `out` can be used to size the array, or -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or have room
+ * for `capacity` features.
+ */
+int32_t harfrust_font_get_feature_overrides(const struct HarfRustFont *font,
+                                            struct HarfRustFeature *out,
+                                            uint32_t capacity);
+
+/**
+ * Sets advances that replace the shaped horizontal advance of glyphs, e.g.
+ * to force the space to the width a PDF declares in its /Widths array so
+ * that layout matches what the PDF consumer will draw. Overrides apply to
+ * every result shaped with this font, after shaping and before any scaling
+ * the shape call does; offsets and vertical advances are left alone.
+ * Replaces any previous overrides (a later entry for the same glyph wins);
+ * passing none clears them. Shape calls already running are not affected.
+ * Returns `NullArgument` if the font is null, or if `overrides` is null with
+ * a non-zero count, and `Unsupported` if the font is frozen.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `overrides` must be null or
+ * point to `num_overrides` entries.
+ */
+HarfRustStatus harfrust_font_set_advance_overrides(struct HarfRustFont *font,
+                                                   const struct HarfRustAdvanceOverride *overrides,
+                                                   uint32_t num_overrides);
+
+/**
+ * Copies the font's advance overrides (see
+ * `harfrust_font_set_advance_overrides`) into `out`, in glyph ID order. At
+ * most `capacity` overrides are written. Returns the total number of
+ * overrides, so a call with a null `out` can be used to size the array, or
+ * -1 if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or have room
+ * for `capacity` entries.
+ */
+int32_t harfrust_font_get_advance_overrides(const struct HarfRustFont *font,
+                                            struct HarfRustAdvanceOverride *out,
+                                            uint32_t capacity);
+
+/**
+ * Sets a fixed extra advance, in font units, added after every cluster of
+ * every result shaped with this font, spaces included, like the PDF Tc
+ * text state parameter (a Tc of `t` at font size `s` is `t *
+ * units_per_em / s`). Unlike typographic tracking it does not depend on the
+ * size or skip any glyphs, so measured text matches what a PDF viewer
+ * draws. Applied after advance overrides and before horizontal scaling, as
+ * PDF scales Tc by Tz; vertical runs are left alone. Measurement and line
+ * fitting use the spaced advances. Negative values tighten text.
+ * Returns `NullArgument` if the font is null and `Unsupported` if the font
+ * is frozen.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+HarfRustStatus harfrust_font_set_character_spacing(struct HarfRustFont *font, int32_t spacing);
+
+/**
+ * Returns the font's character spacing in font units (see
+ * `harfrust_font_set_character_spacing`), 0 unless set or if the font is
+ * null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+int32_t harfrust_font_get_character_spacing(const struct HarfRustFont *font);
+
+/**
+ * Sets a horizontal scaling factor, like the PDF Tz text state parameter
+ * divided by 100, for condensed or expanded text. Every result shaped with
+ * this font has its advances and offsets multiplied by `scale` in x, after
+ * advance overrides and before any scaling the shape call does; vertical
+ * advances and offsets are left alone. Measurement and line fitting
+ * (`harfrust_text_fits`, `harfrust_shape_fit_size`,
+ * `harfrust_shape_field_line`) use the scaled advances. Font metrics such
+ * as `harfrust_font_glyph_advance` are not affected. Shape calls already
+ * running may or may not see the new factor.
+ * Returns `NullArgument` if the font is null, `OutOfRange` if `scale` is not
+ * a positive finite number, and `Unsupported` if the font is frozen.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+HarfRustStatus harfrust_font_set_horizontal_scale(struct HarfRustFont *font, float scale);
+
+/**
+ * Returns the font's horizontal scaling factor (see
+ * `harfrust_font_set_horizontal_scale`), 1.0 unless set, or 0.0 if the font
+ * is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+float harfrust_font_get_horizontal_scale(const struct HarfRustFont *font);
+
+/**
+ * Shapes text in a buffer using the given font. Fonts whose layout tables
+ * cannot be read are shaped as `harfrust_shape_fallback` does.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape(const struct HarfRustFont *font,
+                                           struct HarfRustBuffer *buffer);
+
+/**
+ * Shapes text in a buffer without layout tables: each character maps to
+ * its nominal glyph through `cmap` (.notdef if unmapped) and advances by its
+ * `hmtx` width, in visual order. This never fails for a valid font and
+ * buffer, even if GSUB, GPOS or GDEF are damaged. The result reports
+ * `harfrust_glyph_buffer_is_degraded`. Consumes the buffer.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_fallback(const struct HarfRustFont *font,
+                                                    struct HarfRustBuffer *buffer);
+
+/**
+ * Shapes text in a buffer using the given font and OpenType features.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles, and `features` must be
+ * null or point to `num_features` features.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_with_features(const struct HarfRustFont *font,
+                                                         struct HarfRustBuffer *buffer,
+                                                         const struct HarfRustFeature *features,
+                                                         uint32_t num_features);
+
+/**
+ * Shapes text in a buffer using the given font, features, and variable font settings.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles; `features` must be null
+ * or point to `num_features` features; and `variations` must be null or
+ * point to `num_variations` variations.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_full(const struct HarfRustFont *font,
+                                                struct HarfRustBuffer *buffer,
+                                                const struct HarfRustFeature *features,
+                                                uint32_t num_features,
+                                                const struct HarfRustVariation *variations,
+                                                uint32_t num_variations);
+
+/**
+ * Shapes a UTF-16 run in a single call, without a separate buffer handle.
+ * `direction` `Invalid`, `script_tag` 0 and a null `language` are guessed
+ * from the text. A nonzero `normalize_spaces` normalizes spaces as
+ * `harfrust_buffer_set_normalize_spaces` does. Returns null if the font or
+ * text is null or the language is invalid.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `text` must be null or point to
+ * `len` UTF-16 code units; `language` must be null or a NUL-terminated
+ * string; and `features` must be null or point to `num_features` features.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_run_utf16(const struct HarfRustFont *font,
+                                                     const uint16_t *text,
+                                                     int32_t len,
+                                                     enum HarfRustDirection direction,
+                                                     uint32_t script_tag,
+                                                     const char *language,
+                                                     const struct HarfRustFeature *features,
+                                                     uint32_t num_features,
+                                                     int32_t normalize_spaces);
+
+/**
+ * Writes a key identifying the result of shaping `run` with the given
+ * variation settings to `out`, without shaping it, so callers can look up
+ * their own cache of rendered runs first. The key combines the font data
+ * hash, face index, the text, its direction, script and language after
+ * guessing, the features in order, the font's advance overrides, character
+ * spacing and horizontal scale, whether spaces are normalized, the
+ * normalized axis coordinates and the library version: it is stable across processes and changes on upgrades,
+ * which may shape differently. Returns `NullArgument` if the font, `run` or
+ * `out` is null or `variations` is null with a non-zero count, or
+ * `InvalidArgument` if the run's text or language is invalid.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `run` must be null or point to a
+ * `HarfRustRunDescriptor`, and its pointers must meet the requirements
+ * `harfrust_shape_run_utf16` places on its arguments; `variations` must be
+ * null or point to `num_variations` variations; and `out` must be null or
+ * point to a writable `HarfRustCacheKey`.
+ */
+HarfRustStatus harfrust_run_key(const struct HarfRustFont *font,
+                                const struct HarfRustRunDescriptor *run,
+                                const struct HarfRustVariation *variations,
+                                uint32_t num_variations,
+                                struct HarfRustCacheKey *out);
+
+/**
+ * Shapes `num_runs` runs with one font in a single call, writing one glyph
+ * buffer handle per run to `out_buffers` (null for runs whose text or
+ * language is invalid). Runs of a degraded font are shaped with the
+ * fallback shaper, as `harfrust_shape` does. Each handle must be freed with
+ * `harfrust_glyph_buffer_free`. Returns the number of runs shaped,
+ * -1 if the font is null, or -2 if `runs` or `out_buffers` is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `runs` must be null or point to
+ * `num_runs` entries, each of which meets the requirements
+ * `harfrust_shape_run_utf16` places on its arguments.
+ */
+int32_t harfrust_shape_batch(const struct HarfRustFont *font,
+                             const struct HarfRustRunDescriptor *runs,
+                             uint32_t num_runs,
+                             struct HarfRustGlyphBuffer **out_buffers);
+
+/**
+ * Same as `harfrust_shape_batch`, but shapes the runs on up to
+ * `num_threads` threads (0 for the number set by `harfrust_init`, by default
+ * the number of available cores; never more than there are cores) and
+ * returns once all are complete. Runs are
+ * split into contiguous chunks; the calling thread shapes one of them. Where
+ * threads are unavailable (e.g. WebAssembly) every run is shaped on the
+ * calling thread.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `runs` must be null or point to
+ * `num_runs` entries, each of which meets the requirements
+ * `harfrust_shape_run_utf16` places on its arguments.
+ */
+int32_t harfrust_shape_batch_parallel(const struct HarfRustFont *font,
+                                      const struct HarfRustRunDescriptor *runs,
+                                      uint32_t num_runs,
+                                      struct HarfRustGlyphBuffer **out_buffers,
+                                      uint32_t num_threads);
+
+/**
+ * Shapes a copy of the buffer's text with the given font and optional
+ * OpenType features. Unlike `harfrust_shape`, the buffer is not consumed:
+ * it stays owned by the caller and can be cleared, reused or shaped again.
+ * Segment properties are guessed on the copy if no direction is set, and
+ * spaces are normalized in the copy if enabled on the buffer.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles, and `features` must be
+ * null or point to `num_features` features.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_copy(const struct HarfRustFont *font,
+                                                const struct HarfRustBuffer *buffer,
+                                                const struct HarfRustFeature *features,
+                                                uint32_t num_features);
+
+/**
+ * Shapes a copy of the buffer's text like `harfrust_shape_copy`, writing
+ * glyph infos and positions straight into caller arrays of `capacity`
+ * entries instead of allocating a glyph buffer. `written_out` receives the
+ * number of glyphs, and `flags_out`, if not null, the
+ * `HarfRustResultFlags` bits `harfrust_glyph_buffer_get_flags` would
+ * report. Returns `NullArgument` if the font, buffer or `infos_out`,
+ * `positions_out` or `written_out` is null, or `InsufficientCapacity` if the
+ * glyphs do not fit; nothing is written then and `written_out` receives the
+ * capacity needed. The buffer is not consumed, so it can be shaped again
+ * into larger arrays.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles; `features` must be null
+ * or point to `num_features` features; `infos_out` must be null or have room
+ * for `capacity` entries; `positions_out` must be null or have room for
+ * `capacity` entries; and `written_out` and `flags_out` must be null or
+ * point to a writable `u32` and `i32`.
+ */
+HarfRustStatus harfrust_shape_into(const struct HarfRustFont *font,
+                                   const struct HarfRustBuffer *buffer,
+                                   const struct HarfRustFeature *features,
+                                   uint32_t num_features,
+                                   struct HarfRustGlyphInfo *infos_out,
+                                   struct HarfRustGlyphPosition *positions_out,
+                                   uint32_t capacity,
+                                   uint32_t *written_out,
+                                   int32_t *flags_out);
+
+/**
+ * Shapes text as small caps. The font's `smcp` feature is applied to the
+ * characters it covers, whose glyphs keep a scale of 1.0. Lowercase letters
+ * it does not cover (all of them if the font has no `smcp`) are shaped as
+ * uppercase and their glyphs are reported with `scale` through
+ * `harfrust_glyph_buffer_get_transforms`; their advances and offsets are already
+ * scaled, so the renderer only needs to scale the outlines. Clusters still
+ * refer to the original text. Returns null on invalid arguments or if
+ * `scale` is not a positive finite number.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_small_caps(const struct HarfRustFont *font,
+                                                      struct HarfRustBuffer *buffer,
+                                                      float scale);
+
+/**
+ * Shapes text as superscript or subscript. The font's `sups` or `subs`
+ * feature is applied to the characters it covers. Glyphs of the others (all
+ * of them if the font has no such feature) are synthesized from the OS/2
+ * script metrics: advances and offsets are scaled by the script size, the
+ * script offset is added to each glyph's offset, and the scale is reported
+ * through `harfrust_glyph_buffer_get_transforms`.
+ * Returns null on invalid arguments.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_script_position(const struct HarfRustFont *font,
+                                                           struct HarfRustBuffer *buffer,
+                                                           enum HarfRustScriptPosition position);
+
+/**
+ * Shapes text for a vertical line with mixed orientation (CSS
+ * `text-orientation: mixed`): characters that are upright in vertical text
+ * (CJK, kana, Hangul and most symbols, per UAX #50) are shaped top to
+ * bottom, and runs of the others (e.g. Latin words) are shaped horizontally
+ * and set sideways, turned 90 degrees clockwise with their em box centered
+ * on the line. Sideways glyphs report a rotation of -90 through
+ * `harfrust_glyph_buffer_get_transforms`; their advances and offsets are
+ * already in the vertical line's coordinates. The buffer's script and
+ * language are used for both kinds of run, and its direction is ignored.
+ * Consumes the buffer. Returns null if the font or buffer is null.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_vertical_mixed(const struct HarfRustFont *font,
+                                                          struct HarfRustBuffer *buffer);
+
+/**
+ * Finds the largest font size in `[min_size, max_size]` at which the shaped
+ * UTF-16 text fits within `max_width` (and, if positive, `max_height`).
+ * Widths and heights use the same unit as the size (e.g. points); the height
+ * of the text is its ascent minus descent at that size.
+ * The shaped advance is binary-searched with the shaper's point size set, so
+ * size-dependent tracking is honored. The chosen size is written to
+ * `out_size`. Returns 0 on success, 1 if the text does not fit even at
+ * `min_size` (in which case `min_size` is written), or a negative value on
+ * invalid arguments.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `text` must be null or point to
+ * `len` UTF-16 code units; and `out_size` must be null or point to a
+ * writable `f32`.
+ */
+int32_t harfrust_shape_fit_size(const struct HarfRustFont *font,
+                                const uint16_t *text,
+                                int32_t len,
+                                float max_width,
+                                float max_height,
+                                float min_size,
+                                float max_size,
+                                float *out_size);
+
+/**
+ * Lays out one line of UTF-16 text in a `width` x `height` rectangle, as
+ * needed for PDF form field appearance streams. The line is aligned within
+ * `padding` of the left and right edges and centered vertically on its
+ * ascent and descent. A `font_size` of 0 selects the largest size, up to the
+ * padded height, at which the line fits inside the padding (but no smaller
+ * than 4); text set at a fixed size may overflow. Returns the shaped glyphs,
+ * whose positions are in font units, and writes the chosen size and
+ * placement to `out_layout`. Returns null on invalid arguments or if the
+ * padding leaves no room.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `text` must be null or point to
+ * `len` UTF-16 code units; and `out_layout` must be null or point to a
+ * writable `HarfRustLineLayout`.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_field_line(const struct HarfRustFont *font,
+                                                      const uint16_t *text,
+                                                      int32_t len,
+                                                      float width,
+                                                      float height,
+                                                      float padding,
+                                                      float font_size,
+                                                      enum HarfRustTextAlign align,
+                                                      struct HarfRustLineLayout *out_layout);
+
+/**
+ * Places the runs of a horizontal line side by side, in the order given
+ * (left to right), each on its own baseline shifted by its rise, so that
+ * superscript and subscript runs sit correctly among the others. The
+ * placement of each run is written to `out_placements` (optional, one entry
+ * per run), and the line's width and vertical extent to `out_metrics`: the
+ * ascent and descent of each run's font, scaled to its size and moved by
+ * its rise, so a raised run makes the line taller. A line without runs is
+ * empty. Returns `NullArgument` if `runs` is null with a non-zero count,
+ * `out_metrics` is null, or a run's font or glyphs is null, and
+ * `InvalidArgument` if a font size or rise is not finite.
+ *
+ * # Safety
+ *
+ * `runs` must be null or point to `num_runs` entries, and in each run `font`
+ * and `glyphs` must be null or live handles; `out_placements` must be null
+ * or have room for `num_runs` entries; and `out_metrics` must be null or
+ * point to a writable `HarfRustLineMetrics`.
+ */
+HarfRustStatus harfrust_layout_line(const struct HarfRustLayoutRun *runs,
+                                    uint32_t num_runs,
+                                    struct HarfRustRunPlacement *out_placements,
+                                    struct HarfRustLineMetrics *out_metrics);
+
+/**
+ * Creates a reusable shaper for `font` at the given variable font settings
+ * (none for the default instance). The variation instance is computed once
+ * here rather than on every shape call. The shaper holds a reference to the
+ * font (as `harfrust_font_retain`), so the caller may free the font first.
+ * Returns null if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `variations` must be null or
+ * point to `num_variations` variations.
+ */
+struct HarfRustShaper *harfrust_shaper_new(const struct HarfRustFont *font,
+                                           const struct HarfRustVariation *variations,
+                                           uint32_t num_variations);
+
+/**
+ * Shapes text in a buffer with the shaper's font and variation settings and
+ * the given OpenType features. Like `harfrust_shape`, the buffer is consumed.
+ *
+ * # Safety
+ *
+ * `shaper` and `buffer` must be null or live handles, and `features` must be
+ * null or point to `num_features` features.
+ */
+struct HarfRustGlyphBuffer *harfrust_shaper_shape(const struct HarfRustShaper *shaper,
+                                                  struct HarfRustBuffer *buffer,
+                                                  const struct HarfRustFeature *features,
+                                                  uint32_t num_features);
+
+/**
+ * Frees a shaper and releases its reference to the font, freeing the font
+ * if `harfrust_font_free` was already called on it.
+ *
+ * # Safety
+ *
+ * `shaper` must be null or a live handle.
+ */
+void harfrust_shaper_free(struct HarfRustShaper *shaper);
+
+/**
+ * Creates a variation instance of `font` at the given axis settings, to be
+ * passed to `harfrust_shape_with_instance` instead of recomputing it from
+ * the settings on every `harfrust_shape_full` call. The instance is only
+ * valid with the font it was created for, but does not borrow it. Returns
+ * null if the font is null.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `variations` must be null or
+ * point to `num_variations` variations.
+ */
+struct HarfRustInstance *harfrust_instance_new(const struct HarfRustFont *font,
+                                               const struct HarfRustVariation *variations,
+                                               uint32_t num_variations);
+
+/**
+ * Shapes text in a buffer using the given font, variation instance (null for
+ * the default instance) and OpenType features. The buffer is consumed.
+ *
+ * # Safety
+ *
+ * `font`, `instance` and `buffer` must be null or live handles; and
+ * `features` must be null or point to `num_features` features.
+ */
+struct HarfRustGlyphBuffer *harfrust_shape_with_instance(const struct HarfRustFont *font,
+                                                         const struct HarfRustInstance *instance,
+                                                         struct HarfRustBuffer *buffer,
+                                                         const struct HarfRustFeature *features,
+                                                         uint32_t num_features);
+
+/**
+ * Frees a variation instance.
+ *
+ * # Safety
+ *
+ * `instance` must be null or a live handle.
+ */
+void harfrust_instance_free(struct HarfRustInstance *instance);
+
+/**
+ * Writes the version of this library to the given (optional) pointers, so a
+ * host can check that the binary it loaded matches its bindings.
+ *
+ * # Safety
+ *
+ * `out_major` must be null or point to a writable `u32`; `out_minor` must be
+ * null or point to a writable `u32`; and `out_patch` must be null or point
+ * to a writable `u32`.
+ */
+void harfrust_version(uint32_t *out_major, uint32_t *out_minor, uint32_t *out_patch);
+
+/**
+ * Returns the version of the harfrust shaping engine the library was built
+ * with (e.g. "0.5.2") as a static NUL-terminated string, which must not be
+ * freed.
+ */
+const char *harfrust_engine_version_string(void);
+
+/**
+ * Writes the version of the Unicode Character Database the shaping engine
+ * was built from (e.g. 17.0.0) to the given (optional) pointers. Characters
+ * assigned in later versions shape with default properties.
+ *
+ * # Safety
+ *
+ * `out_major` must be null or point to a writable `u32`; `out_minor` must be
+ * null or point to a writable `u32`; and `out_micro` must be null or point
+ * to a writable `u32`.
+ */
+void harfrust_unicode_version(uint32_t *out_major, uint32_t *out_minor, uint32_t *out_micro);
+
+/**
+ * Copies the OpenType script tags (e.g. 'Arab', 'Deva') of the scripts with
+ * a dedicated shaper into `out`. Other scripts are shaped by applying the
+ * font's lookups without script-specific reordering or joining. At most
+ * `capacity` tags are written. Returns the total number of scripts, so a call
+ * with a null `out` can be used to size the array.
+ *
+ * # Safety
+ *
+ * `out` must be null or have room for `capacity` entries.
+ */
+int32_t harfrust_supported_scripts(uint32_t *out, uint32_t capacity);
+
+/**
+ * Writes to `out` how well the font supports the script with the given ISO
+ * 15924 tag (e.g. "Deva" = 0x44657661), for ranking fallback fonts: `Full`
+ * if it maps characters of the script and has GSUB lookups for it (or the
+ * script has no dedicated shaper), `CmapOnly` if it only maps characters,
+ * `Unsupported` if it maps none. Reading the `cmap` makes this too slow to
+ * call per run; cache the result per font. Returns `NullArgument` if the
+ * font or `out` is null, or `InvalidArgument` for an unknown script tag.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `out` must be null or point to a
+ * writable `HarfRustScriptSupport`.
+ */
+HarfRustStatus harfrust_font_script_support(const struct HarfRustFont *font,
+                                            uint32_t script_tag,
+                                            enum HarfRustScriptSupport *out);
+
+/**
+ * Returns the status of the last failure on the calling thread, or `Ok` if
+ * none was recorded. Failing calls record their status and a message;
+ * successful calls leave the record unchanged, so check it only after a
+ * call has failed.
+ */
+HarfRustStatus harfrust_last_error_code(void);
+
+/**
+ * Returns the message of the last failure on the calling thread as a
+ * NUL-terminated UTF-8 string, e.g. "font data cannot be parsed: ...", or
+ * null if none was recorded. Free it with `harfrust_string_free`.
+ */
+char *harfrust_last_error_message(void);
+
+/**
+ * Forgets the last failure on the calling thread.
+ */
+void harfrust_clear_last_error(void);
+
+/**
+ * Frees a string returned by this library.
+ *
+ * # Safety
+ *
+ * `string` must be null or a string returned by this library that has not
+ * been freed.
+ */
+void harfrust_string_free(char *string);
+
+/**
+ * Applies library-wide settings (see `HarfRustConfig`); a null `config`
+ * restores the defaults. May be called again to change them, and need not
+ * be called at all. Handles created before the call stay valid. Returns
+ * `InvalidArgument` if `config.size` is smaller than the `size` field.
+ *
+ * # Safety
+ *
+ * `config` must be null or point to a `HarfRustConfig`.
+ */
+HarfRustStatus harfrust_init(const struct HarfRustConfig *config);
+
+/**
+ * Restores the default settings and releases global state: the result
+ * cache is emptied and disabled, the log callback removed and the small-run
+ * fast path re-enabled. Live handles stay valid and must still be freed.
+ */
+void harfrust_shutdown(void);
+
+/**
+ * Routes all of the library's native allocations through host callbacks,
+ * e.g. to attribute leaks in a long-running service. Memory must be freed by
+ * the allocator that returned it, so this must be the first call into the
+ * library and cannot be undone; `realloc_fn` may be null, in which case
+ * resizing allocates, copies and frees. The callbacks may be called on any
+ * thread and must not call back into the library.
+ *
+ * Returns `NullArgument` if `alloc_fn` or `free_fn` is null, and
+ * `Unsupported` if the library was built without the `host-allocator`
+ * feature or has already allocated.
+ *
+ * # Safety
+ *
+ * `alloc_fn`, `free_fn` and `realloc_fn` must be null or behave like
+ * `malloc`, `free` and `realloc` when called with `user_data` from any
+ * thread.
+ */
+HarfRustStatus harfrust_set_allocator(HarfRustAllocFn alloc_fn,
+                                      HarfRustFreeFn free_fn,
+                                      HarfRustReallocFn realloc_fn,
+                                      void *user_data);
+
+/**
+ * Installs the callback that receives diagnostic messages, such as the
+ * warnings of `HarfRustParseMode::Tolerant`, replacing any previous one.
+ * A null callback removes it. `user_data` is passed back on every call.
+ *
+ * # Safety
+ *
+ * `callback` must be null or safe to call with `user_data` and a
+ * NUL-terminated message from any thread.
+ */
+void harfrust_set_log_callback(HarfRustLogCallback callback, void *user_data);
+
+/**
+ * Enables (non-zero, the default) or disables the small-run fast path:
+ * `harfrust_shape`, `harfrust_shape_with_features` and `harfrust_shape_full`
+ * (without variations) answer left-to-right Latin or Common runs of up to
+ * 8 printable ASCII characters directly from `cmap` and `hmtx` when none of
+ * their glyphs can start a GSUB or GPOS lookup and the font has no AAT
+ * tables or legacy `kern` kerning. Results are the same either way.
+ */
+void harfrust_set_small_run_fast_path(int32_t enabled);
+
+/**
+ * Enables the process-wide cache of shaping results, or changes its limits.
+ * `harfrust_shape`, `harfrust_shape_with_features` and `harfrust_shape_full`
+ * then answer repeated combinations of font, text, segment properties,
+ * features and variations from the cache. The least recently used entries
+ * are evicted beyond `max_entries` results or about `max_bytes` bytes of
+ * memory. A limit of 0 disables the cache and frees its entries (the
+ * default). Always returns `Ok`.
+ */
+HarfRustStatus harfrust_cache_configure(uint32_t max_entries, uint64_t max_bytes);
+
+/**
+ * Drops every cached shaping result, keeping the cache enabled.
+ */
+void harfrust_cache_clear(void);
+
+/**
+ * Writes the number of cached shaping results and the approximate memory
+ * they use to the given (optional) pointers.
+ *
+ * # Safety
+ *
+ * `out_entries` must be null or point to a writable `u32`, and `out_bytes`
+ * must be null or point to a writable `u64`.
+ */
+void harfrust_cache_stats(uint32_t *out_entries, uint64_t *out_bytes);
+
+/**
+ * Writes the native memory and live handle counts of the whole process to
+ * `out`, for monitoring memory the managed garbage collector cannot see.
+ * Handles retained with `harfrust_font_retain` count once. Returns
+ * `NullArgument` if `out` is null.
+ *
+ * # Safety
+ *
+ * `out` must be null or point to a writable `HarfRustMemoryStats`.
+ */
+HarfRustStatus harfrust_memory_stats(struct HarfRustMemoryStats *out);
+
+/**
+ * Returns 1 if the glyph buffer was produced by fallback shaping without
+ * layout tables (see `harfrust_shape_fallback`), 0 if not, or -1 if the
+ * buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+int32_t harfrust_glyph_buffer_is_degraded(const struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Returns the `HarfRustResultFlags` bits describing how a shaping result
+ * was produced, or a negative status if the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+int32_t harfrust_glyph_buffer_get_flags(const struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Writes the number of .notdef glyphs and inserted dotted circles in a
+ * shaping result to `out`, for logging text quality without reading the
+ * glyph arrays. `font` is the font the buffer was shaped with, which
+ * identifies its dotted circle glyph. Returns `NullArgument` if the font,
+ * buffer or `out` is null.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles, and `out` must be null
+ * or point to a writable `HarfRustShapeSummary`.
+ */
+HarfRustStatus harfrust_glyph_buffer_get_summary(const struct HarfRustFont *font,
+                                                 const struct HarfRustGlyphBuffer *buffer,
+                                                 struct HarfRustShapeSummary *out);
+
+/**
+ * Returns the number of glyphs in the glyph buffer.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+int32_t harfrust_glyph_buffer_len(const struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Returns a pointer to the glyph info array.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+const struct HarfRustGlyphInfo *harfrust_glyph_buffer_get_infos(const struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Returns a pointer to the glyph position array.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+const struct HarfRustGlyphPosition *harfrust_glyph_buffer_get_positions(const struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Recovers the reading order of a line for text extraction. `runs` are
+ * the horizontal runs of the line as placed on the page, left to right,
+ * possibly shaped by separate calls with different fonts and directions. The
+ * characters of their text are written to `out` in logical order, with
+ * their positions on the line; text that maps to no glyph is omitted.
+ * `direction` is the line's base direction, or `Invalid` to take it from
+ * the text at the line's ends. At most `capacity` entries are written.
+ * Returns the total number of characters, -1 (`NullArgument`) if `runs`,
+ * a run's glyphs or its text is null, or -2 (`InvalidArgument`) if a length
+ * is negative or `direction` is vertical.
+ *
+ * # Safety
+ *
+ * `runs` must be null or point to `num_runs` entries, and in each run
+ * `glyphs` must be null or a live handle and `text` must be null or point to
+ * `len` UTF-16 code units; and `out` must be null or have room for
+ * `capacity` entries.
+ */
+int32_t harfrust_line_extract_text(const struct HarfRustLineRun *runs,
+                                   uint32_t num_runs,
+                                   enum HarfRustDirection direction,
+                                   struct HarfRustExtractedChar *out,
+                                   uint32_t capacity);
+
+/**
+ * Writes, for each glyph, the range of the UTF-16 source text it represents.
+ * Clusters are expected to be UTF-16 offsets (as produced by
+ * `harfrust_buffer_add_utf16`). When several glyphs share a cluster, the first
+ * one in buffer order carries the cluster's whole text and the others get an
+ * empty range, so concatenating the ranges never duplicates text. Ranges are
+ * clamped to `len` and never split a surrogate pair.
+ * At most `capacity` entries are written. Returns the number of glyphs, -1
+ * (`NullArgument`) if the buffer or text is null or -2 (`InvalidArgument`) if
+ * `len` is negative.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle; `text` must be null or point to
+ * `len` UTF-16 code units; and `out` must be null or have room for
+ * `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_get_text_ranges(const struct HarfRustGlyphBuffer *buffer,
+                                              const uint16_t *text,
+                                              int32_t len,
+                                              struct HarfRustTextRange *out,
+                                              uint32_t capacity);
+
+/**
+ * Writes, for each glyph, which substitution produced it: the kind of GSUB
+ * lookup, its index and feature, the alternate chosen and the input text
+ * range. harfrust does not record the lookups it applies, so the trace is
+ * reconstructed from the font's GSUB table on request; shaping itself costs
+ * nothing extra. Glyphs that several lookups can produce are credited to the
+ * most likely one, so the trace is a debugging aid rather than exact.
+ * `text` is the UTF-16 text the buffer was shaped from, as for
+ * `harfrust_glyph_buffer_get_text_ranges`.
+ * At most `capacity` entries are written. Returns the number of glyphs, -1
+ * (`NullArgument`) if the font, buffer or text is null or -2
+ * (`InvalidArgument`) if `len` is negative.
+ *
+ * # Safety
+ *
+ * `font` and `buffer` must be null or live handles; `text` must be null or
+ * point to `len` UTF-16 code units; and `out` must be null or have room for
+ * `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_get_substitution_trace(const struct HarfRustFont *font,
+                                                     const struct HarfRustGlyphBuffer *buffer,
+                                                     const uint16_t *text,
+                                                     int32_t len,
+                                                     struct HarfRustGlyphTrace *out,
+                                                     uint32_t capacity);
+
+/**
+ * Writes the summed advance of each cluster, in glyph buffer order. A cluster
+ * entry covers a run of consecutive glyphs with the same cluster value.
+ * At most `capacity` entries are written. Returns the total number of
+ * clusters, so a call with a null `out` can be used to size the array.
+ * Returns -1 if the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `out` must be null or have
+ * room for `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_get_cluster_advances(const struct HarfRustGlyphBuffer *buffer,
+                                                   struct HarfRustClusterAdvance *out,
+                                                   uint32_t capacity);
+
+/**
+ * Writes the permutation between the visual and logical order of the
+ * buffer's clusters, so selection and text extraction can convert between
+ * them. Visual indices are those of `harfrust_glyph_buffer_get_cluster_advances`
+ * (glyph order, reversed for right-to-left runs); logical indices order the
+ * clusters by cluster value, i.e. by text position. `out_visual_to_logical[v]`
+ * receives the logical index of visual cluster `v` and
+ * `out_logical_to_visual[l]` the visual index of logical cluster `l`; either
+ * may be null. At most `capacity` entries are written to each. Returns the
+ * total number of clusters, so a call with null arrays can be used to size
+ * them. Returns -1 if the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle; `out_visual_to_logical` must be
+ * null or have room for `capacity` entries; and `out_logical_to_visual` must
+ * be null or have room for `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_get_cluster_order(const struct HarfRustGlyphBuffer *buffer,
+                                                uint32_t *out_visual_to_logical,
+                                                uint32_t *out_logical_to_visual,
+                                                uint32_t capacity);
+
+/**
+ * Copies the per-glyph transforms of a synthesized layout (such as
+ * `harfrust_shape_small_caps`) into `out`. This is an opt-in companion to
+ * the position array: glyphs without a transform report scale 1.0 and
+ * rotation 0. At most `capacity` entries are written. Returns the number of
+ * glyphs, or -1 if the buffer is null.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `out` must be null or have
+ * room for `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_get_transforms(const struct HarfRustGlyphBuffer *buffer,
+                                             struct HarfRustGlyphTransform *out,
+                                             uint32_t capacity);
+
+/**
+ * Copies only the scale factor of each glyph transform into `out`; see
+ * `harfrust_glyph_buffer_get_transforms`.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `out` must be null or have
+ * room for `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_get_scales(const struct HarfRustGlyphBuffer *buffer,
+                                         float *out,
+                                         uint32_t capacity);
+
+/**
+ * Writes the glyph IDs as the 2-byte big-endian character codes of an
+ * Identity-H (or Identity-V) encoded PDF string, in glyph buffer order, ready
+ * to be placed in a string operand of Tj or TJ. Only whole glyphs are
+ * written, at most `capacity / 2` of them. Returns the number of bytes the
+ * whole buffer needs, so a call with a null `out` can be used to size the
+ * array. Returns -1 if the buffer is null, or -3 if a glyph ID does not fit
+ * in two bytes.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle, and `out` must be null or have
+ * room for `capacity` bytes.
+ */
+int32_t harfrust_glyph_buffer_to_identity_h(const struct HarfRustGlyphBuffer *buffer,
+                                            uint8_t *out,
+                                            uint32_t capacity);
+
+/**
+ * Compares the shaped advances of a buffer with the widths a PDF font
+ * dictionary declares for its glyphs, so a viewer can decide whether to
+ * trust the embedded widths or the font. Character codes are glyph IDs, as
+ * written by `harfrust_glyph_buffer_to_identity_h`: `widths[i]` is the
+ * width of glyph `first_code + i` in PDF glyph space (1000 units per em),
+ * and glyphs outside the array have `missing_width` (the font descriptor's
+ * MissingWidth). The buffer must hold unscaled advances shaped with `font`.
+ * Each glyph's declared width minus its shaped advance, in PDF glyph space,
+ * is written to `out` in glyph buffer order: positive where the PDF draws
+ * the glyph wider than the font. At most `capacity` deltas are written.
+ * Returns the number of glyphs, so a call with a null `out` can be used to
+ * size the array. Returns -1 if the buffer or font is null, or if `widths`
+ * is null with a non-zero count.
+ *
+ * # Safety
+ *
+ * `buffer` and `font` must be null or live handles; `widths` must be null or
+ * point to `num_widths` entries; and `out` must be null or have room for
+ * `capacity` entries.
+ */
+int32_t harfrust_glyph_buffer_width_deltas(const struct HarfRustGlyphBuffer *buffer,
+                                           const struct HarfRustFont *font,
+                                           uint32_t first_code,
+                                           const float *widths,
+                                           uint32_t num_widths,
+                                           float missing_width,
+                                           float *out,
+                                           uint32_t capacity);
+
+/**
+ * Creates a new glyph buffer holding only the glyphs whose cluster lies in
+ * `[start_cluster, end_cluster)`, in their original order. Since whole
+ * clusters are selected, a ligature is never split. The source buffer is not
+ * modified. Returns null if the buffer is null or the range is empty.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+struct HarfRustGlyphBuffer *harfrust_glyph_buffer_slice(const struct HarfRustGlyphBuffer *buffer,
+                                                        uint32_t start_cluster,
+                                                        uint32_t end_cluster);
+
+/**
+ * Clears the glyph buffer and returns a new unicode buffer for reuse.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+struct HarfRustBuffer *harfrust_glyph_buffer_into_buffer(struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Frees a glyph buffer previously created by `harfrust_shape`.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or a live handle.
+ */
+void harfrust_glyph_buffer_free(struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Computes the glyph closure of a seed glyph set: the seeds plus .notdef,
+ * every glyph reachable from them through GSUB substitutions, and the
+ * components of composite glyphs. GSUB traversal can be limited to the given
+ * OpenType script tags and feature tags (4 bytes as u32); a null pointer
+ * selects all of them. Writes the closure sorted by glyph ID; at most
+ * `capacity` entries are written. Returns the closure size, so a call with a
+ * null `out` can be used to size the array. Returns -1 if the font is null,
+ * or -2 if `glyphs` is null with a non-zero count.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `glyphs` must be null or point to
+ * `count` glyph IDs; `scripts` must be null or point to `num_scripts`
+ * entries; `features` must be null or point to `num_features` entries; and
+ * `out` must be null or have room for `capacity` entries.
+ */
+int32_t harfrust_font_glyph_closure(const struct HarfRustFont *font,
+                                    const uint32_t *glyphs,
+                                    uint32_t count,
+                                    const uint32_t *scripts,
+                                    uint32_t num_scripts,
+                                    const uint32_t *features,
+                                    uint32_t num_features,
+                                    uint32_t *out,
+                                    uint32_t capacity);
+
+/**
+ * Builds a subset of a TrueType-outline (`glyf`) font that keeps the glyph
+ * closure of `glyphs` (see `harfrust_font_glyph_closure`). In `Compact` mode
+ * the kept glyphs are renumbered in glyph ID order, so the new ID of a glyph
+ * is its index in the closure, and tables indexed by glyph ID (cmap, layout
+ * and color tables) are dropped. In `RetainGids` mode glyph IDs and all other
+ * tables are unchanged. The family, unique, full and PostScript names are
+ * prefixed with a six-letter subset tag ("ABCDEF+"); the new PostScript name
+ * is written to `out_name` as a NUL-terminated string, truncated to
+ * `name_capacity` bytes (72 always suffice for valid names). `out_name` may
+ * be null. Returns null if the font is null, `glyphs` is null with a
+ * non-zero count, or the font has no `glyf` outlines.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle; `glyphs` must be null or point to
+ * `count` glyph IDs; and `out_name` must be null or have room for
+ * `name_capacity` bytes.
+ */
+struct HarfRustBlob *harfrust_font_subset(const struct HarfRustFont *font,
+                                          const uint32_t *glyphs,
+                                          uint32_t count,
+                                          enum HarfRustSubsetMode mode,
+                                          char *out_name,
+                                          uint32_t name_capacity);
+
+/**
+ * Creates an empty subsetter.
+ */
+struct HarfRustSubsetter *harfrust_subsetter_new(void);
+
+/**
+ * Adds glyph IDs to the subsetter's glyph set.
+ *
+ * # Safety
+ *
+ * `subsetter` must be null or a live handle, and `glyphs` must be null or
+ * point to `count` glyph IDs.
+ */
+HarfRustStatus harfrust_subsetter_add_glyphs(struct HarfRustSubsetter *subsetter,
+                                             const uint32_t *glyphs,
+                                             uint32_t count);
+
+/**
+ * Adds every glyph of a shaping result to the subsetter's glyph set. The
+ * glyph buffer is not consumed.
+ *
+ * # Safety
+ *
+ * `subsetter` and `buffer` must be null or live handles.
+ */
+HarfRustStatus harfrust_subsetter_add_glyph_buffer(struct HarfRustSubsetter *subsetter,
+                                                   const struct HarfRustGlyphBuffer *buffer);
+
+/**
+ * Returns the number of distinct glyphs added so far (before closure), or -1
+ * if the subsetter is null.
+ *
+ * # Safety
+ *
+ * `subsetter` must be null or a live handle.
+ */
+int32_t harfrust_subsetter_glyph_count(const struct HarfRustSubsetter *subsetter);
+
+/**
+ * Builds the subset of `font` for the accumulated glyph set; see
+ * `harfrust_font_subset` for `mode`, `out_name` and null returns. The
+ * subsetter is not consumed and can keep accumulating.
+ *
+ * # Safety
+ *
+ * `subsetter` and `font` must be null or live handles, and `out_name` must
+ * be null or have room for `name_capacity` bytes.
+ */
+struct HarfRustBlob *harfrust_subsetter_build(const struct HarfRustSubsetter *subsetter,
+                                              const struct HarfRustFont *font,
+                                              enum HarfRustSubsetMode mode,
+                                              char *out_name,
+                                              uint32_t name_capacity);
+
+/**
+ * Frees a subsetter created by `harfrust_subsetter_new`.
+ *
+ * # Safety
+ *
+ * `subsetter` must be null or a live handle.
+ */
+void harfrust_subsetter_free(struct HarfRustSubsetter *subsetter);
+
+/**
+ * Returns a pointer to the bytes of a blob.
+ *
+ * # Safety
+ *
+ * `blob` must be null or a live handle.
+ */
+const uint8_t *harfrust_blob_get_data(const struct HarfRustBlob *blob);
+
+/**
+ * Returns the length of a blob in bytes.
+ *
+ * # Safety
+ *
+ * `blob` must be null or a live handle.
+ */
+int32_t harfrust_blob_len(const struct HarfRustBlob *blob);
+
+/**
+ * Frees a blob returned by the library.
+ *
+ * # Safety
+ *
+ * `blob` must be null or a live handle.
+ */
+void harfrust_blob_free(struct HarfRustBlob *blob);
+
+/**
+ * Builds a static TrueType font from a variable font at the given axis
+ * values (e.g. wght=700), for consumers that don't support variable fonts.
+ * Outlines and advances are evaluated at that location; composite glyphs
+ * are flattened, hinting instructions dropped and variation tables removed.
+ * Axes not listed keep their default; a wght setting also becomes the OS/2
+ * weight class. Static fonts yield an equivalent static copy. Returns null if
+ * the font is null, `variations` is null with a non-zero count, or the font
+ * has no `glyf` outlines (CFF2 is not supported).
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `variations` must be null or
+ * point to `num_variations` variations.
+ */
+struct HarfRustBlob *harfrust_font_instantiate(const struct HarfRustFont *font,
+                                               const struct HarfRustVariation *variations,
+                                               uint32_t num_variations);
+
+/**
+ * Renders a color glyph (COLR/CPAL or embedded color bitmap) at the given
+ * pixels-per-em into a premultiplied RGBA bitmap.
+ * `palette_index` selects the CPAL palette; out-of-range values use palette 0.
+ * Returns null, recording `InvalidArgument`, if the glyph has no color
+ * representation, or `Unsupported` if its only one is an SVG document, which
+ * is not rendered.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle.
+ */
+struct HarfRustColorBitmap *harfrust_font_render_color_glyph(const struct HarfRustFont *font,
+                                                             uint32_t glyph_id,
+                                                             float ppem,
+                                                             uint32_t palette_index);
+
+/**
+ * Renders a color glyph as `harfrust_font_render_color_glyph` does, with
+ * `overrides_len` entries of the palette replaced by `overrides` (which may
+ * be null if the length is 0). An override with index 0xFFFF sets the text
+ * foreground color that COLR glyphs may paint with; overrides of entries the
+ * palette lacks are ignored. Embedded color bitmaps are unaffected.
+ *
+ * # Safety
+ *
+ * `font` must be null or a live handle, and `overrides` must be null or
+ * point to `overrides_len` entries.
+ */
+struct HarfRustColorBitmap *harfrust_font_render_color_glyph_with_overrides(const struct HarfRustFont *font,
+                                                                            uint32_t glyph_id,
+                                                                            float ppem,
+                                                                            uint32_t palette_index,
+                                                                            const struct HarfRustColorOverride *overrides,
+                                                                            uint32_t overrides_len);
+
+/**
+ * Writes the dimensions and placement of a color bitmap to `out`.
+ *
+ * # Safety
+ *
+ * `bitmap` must be null or a live handle, and `out` must be null or point to
+ * a writable `HarfRustBitmapInfo`.
+ */
+HarfRustStatus harfrust_color_bitmap_get_info(const struct HarfRustColorBitmap *bitmap,
+                                              struct HarfRustBitmapInfo *out);
+
+/**
+ * Returns a pointer to the premultiplied RGBA pixel data of a color bitmap.
+ *
+ * # Safety
+ *
+ * `bitmap` must be null or a live handle.
+ */
+const uint8_t *harfrust_color_bitmap_get_pixels(const struct HarfRustColorBitmap *bitmap);
+
+/**
+ * Frees a bitmap previously created by `harfrust_font_render_color_glyph`.
+ *
+ * # Safety
+ *
+ * `bitmap` must be null or a live handle.
+ */
+void harfrust_color_bitmap_free(struct HarfRustColorBitmap *bitmap);
+
+/**
+ * Allocates memory in the WASM linear memory.
+ * Used by the host to allocate space for passing data to WASM.
+ */
+int32_t harfrust_alloc(int32_t size);
+
+/**
+ * Frees memory allocated by harfrust_alloc.
+ *
+ * # Safety
+ *
+ * `ptr` must be 0 or an address returned by `harfrust_alloc` for the same
+ * `size` that has not been freed.
+ */
+void harfrust_dealloc(int32_t ptr, int32_t size);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* HARFRUST_FFI_H */
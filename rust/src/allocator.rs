@@ -0,0 +1,121 @@
+//! Native allocations routed through the host.
+//!
+//! With the `host-allocator` feature, the library's global allocator passes
+//! every allocation to the callbacks registered with `harfrust_set_allocator`,
+//! so a long-running host can account for native memory in its own leak
+//! diagnostics. Memory must be freed by the allocator that returned it, so the
+//! callbacks are only accepted before the library's first allocation; from
+//! then on, and without them, the system allocator is used.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use crate::{HarfRustAllocFn, HarfRustFreeFn, HarfRustReallocFn};
+
+/// No allocation has been made yet.
+const UNDECIDED: u8 = 0;
+const SYSTEM: u8 = 1;
+const HOST: u8 = 2;
+
+/// Which allocator serves the process, decided once by the first allocation
+/// or registration.
+static STATE: AtomicU8 = AtomicU8::new(UNDECIDED);
+
+struct Hooks {
+    alloc: unsafe extern "C" fn(usize, usize, *mut c_void) -> *mut c_void,
+    free: unsafe extern "C" fn(*mut c_void, usize, usize, *mut c_void),
+    realloc: HarfRustReallocFn,
+    user_data: usize,
+}
+
+/// Set before `STATE` becomes `HOST`; never changes afterwards.
+static HOOKS: OnceLock<Hooks> = OnceLock::new();
+
+#[global_allocator]
+static ALLOCATOR: HostAllocator = HostAllocator;
+
+/// Installs the host callbacks, or returns false if the library has already
+/// allocated (or callbacks were installed before).
+pub(crate) fn register(
+    alloc: HarfRustAllocFn,
+    free: HarfRustFreeFn,
+    realloc: HarfRustReallocFn,
+    user_data: *mut c_void,
+) -> bool {
+    let (Some(alloc), Some(free)) = (alloc, free) else {
+        return false;
+    };
+    if STATE.load(Ordering::Acquire) != UNDECIDED {
+        return false;
+    }
+    let hooks = Hooks {
+        alloc,
+        free,
+        realloc,
+        user_data: user_data as usize,
+    };
+    // Hooks are stored first so an allocation that sees `HOST` finds them; if
+    // an allocation wins the race instead, they are simply never used.
+    HOOKS.set(hooks).is_ok()
+        && STATE.compare_exchange(UNDECIDED, HOST, Ordering::AcqRel, Ordering::Acquire).is_ok()
+}
+
+/// The host callbacks, if they serve the process.
+fn hooks() -> Option<&'static Hooks> {
+    let state = match STATE.load(Ordering::Acquire) {
+        UNDECIDED => match STATE.compare_exchange(UNDECIDED, SYSTEM, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => SYSTEM,
+            Err(state) => state,
+        },
+        state => state,
+    };
+    if state == HOST {
+        HOOKS.get()
+    } else {
+        None
+    }
+}
+
+struct HostAllocator;
+
+unsafe impl GlobalAlloc for HostAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match hooks() {
+            Some(hooks) => unsafe {
+                (hooks.alloc)(layout.size(), layout.align(), hooks.user_data as *mut c_void).cast()
+            },
+            None => unsafe { System.alloc(layout) },
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match hooks() {
+            Some(hooks) => unsafe {
+                (hooks.free)(ptr.cast(), layout.size(), layout.align(), hooks.user_data as *mut c_void)
+            },
+            None => unsafe { System.dealloc(ptr, layout) },
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let Some(hooks) = hooks() else {
+            return unsafe { System.realloc(ptr, layout, new_size) };
+        };
+        if let Some(realloc) = hooks.realloc {
+            let user_data = hooks.user_data as *mut c_void;
+            return unsafe { realloc(ptr.cast(), layout.size(), layout.align(), new_size, user_data).cast() };
+        }
+        // Without a realloc callback, move the allocation
+        let new_layout = unsafe { Layout::from_size_align_unchecked(new_size, layout.align()) };
+        let new_ptr = unsafe { self.alloc(new_layout) };
+        if !new_ptr.is_null() {
+            unsafe {
+                std::ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+                self.dealloc(ptr, layout);
+            }
+        }
+        new_ptr
+    }
+}
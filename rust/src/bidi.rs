@@ -0,0 +1,66 @@
+//! Paragraph base direction detection.
+//!
+//! Implements rules P2/P3 of the Unicode Bidirectional Algorithm: the base
+//! direction is taken from the first strong character, skipping text inside
+//! isolates. Strong right-to-left characters are approximated as alphabetic
+//! characters in the right-to-left blocks, since harfrust does not expose
+//! Bidi_Class data.
+
+/// Strong direction of a character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Strong {
+    Left,
+    Right,
+}
+
+const LRM: char = '\u{200E}';
+const RLM: char = '\u{200F}';
+const ALM: char = '\u{061C}';
+const LRI: char = '\u{2066}';
+const RLI: char = '\u{2067}';
+const FSI: char = '\u{2068}';
+const PDI: char = '\u{2069}';
+
+/// Blocks whose letters have Bidi_Class R or AL.
+const RTL_RANGES: &[(u32, u32)] = &[
+    (0x0590, 0x08FF),   // Hebrew .. Arabic Extended-A
+    (0xFB1D, 0xFDFF),   // Hebrew and Arabic presentation forms
+    (0xFE70, 0xFEFF),   // Arabic Presentation Forms-B
+    (0x10800, 0x10FFF), // Cypriot .. Arabic Extended-C
+    (0x1E800, 0x1EFFF), // Mende Kikakui .. Arabic Mathematical Symbols
+];
+
+fn is_rtl_block(ch: char) -> bool {
+    let cp = ch as u32;
+    RTL_RANGES.iter().any(|&(start, end)| (start..=end).contains(&cp))
+}
+
+/// Returns the strong direction of `ch`, or `None` for weak and neutral characters.
+pub(crate) fn strong_direction(ch: char) -> Option<Strong> {
+    match ch {
+        LRM => Some(Strong::Left),
+        RLM | ALM => Some(Strong::Right),
+        _ if !ch.is_alphabetic() => None,
+        _ if is_rtl_block(ch) => Some(Strong::Right),
+        _ => Some(Strong::Left),
+    }
+}
+
+/// Returns the direction of the first strong character outside isolates, or
+/// `None` if the text has no strong characters.
+pub(crate) fn first_strong(text: impl IntoIterator<Item = char>) -> Option<Strong> {
+    let mut isolate_depth = 0u32;
+    for ch in text {
+        match ch {
+            LRI | RLI | FSI => isolate_depth += 1,
+            PDI => isolate_depth = isolate_depth.saturating_sub(1),
+            _ if isolate_depth > 0 => {}
+            _ => {
+                if let Some(strong) = strong_direction(ch) {
+                    return Some(strong);
+                }
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,241 @@
+//! Opt-in cache of shaping results.
+//!
+//! PDF generation shapes the same words and labels over and over; with the
+//! cache enabled, repeated (font, text, segment properties, features,
+//! variations) combinations are answered without shaping. Entries are
+//! evicted least recently used first once either limit is reached.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{HarfRustGlyphInfo, HarfRustGlyphPosition, HarfRustVariation};
+
+/// Everything that determines a shaping result.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ShapeKey {
+    font_id: u64,
+    text: Vec<(char, u32)>,
+    /// Pre- and post-context.
+    context: [String; 2],
+    direction: harfrust::Direction,
+    script: harfrust::Script,
+    language: Option<harfrust::Language>,
+    flags: u32,
+    cluster_level: harfrust::BufferClusterLevel,
+    /// (tag, value, start, end) per feature.
+    features: Vec<[u32; 4]>,
+    /// (tag, value bits) per variation setting.
+    variations: Vec<(u32, u32)>,
+}
+
+impl ShapeKey {
+    pub(crate) fn new(
+        font_id: u64,
+        text: &[(char, u32)],
+        context: &[String; 2],
+        buffer: &harfrust::UnicodeBuffer,
+        features: &[harfrust::Feature],
+        variations: &[HarfRustVariation],
+    ) -> Self {
+        Self {
+            font_id,
+            text: text.to_vec(),
+            context: context.clone(),
+            direction: buffer.direction(),
+            script: buffer.script(),
+            language: buffer.language(),
+            flags: buffer.flags().bits(),
+            cluster_level: buffer.cluster_level(),
+            features: features
+                .iter()
+                .map(|f| [u32::from_be_bytes(f.tag.to_be_bytes()), f.value, f.start, f.end])
+                .collect(),
+            variations: variations.iter().map(|v| (v.tag, v.value.to_bits())).collect(),
+        }
+    }
+
+    /// Approximate heap size, for the byte limit.
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.text.len() * std::mem::size_of::<(char, u32)>()
+            + self.context.iter().map(String::len).sum::<usize>()
+            + self.features.len() * std::mem::size_of::<[u32; 4]>()
+            + self.variations.len() * std::mem::size_of::<(u32, u32)>()
+    }
+}
+
+/// A cached shaping result.
+pub(crate) struct ShapedRun {
+    pub infos: Vec<HarfRustGlyphInfo>,
+    pub positions: Vec<HarfRustGlyphPosition>,
+}
+
+impl ShapedRun {
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.infos.len() * std::mem::size_of::<HarfRustGlyphInfo>()
+            + self.positions.len() * std::mem::size_of::<HarfRustGlyphPosition>()
+    }
+}
+
+struct Entry {
+    run: Arc<ShapedRun>,
+    last_used: u64,
+    size: usize,
+}
+
+struct ResultCache {
+    max_entries: usize,
+    max_bytes: usize,
+    bytes: usize,
+    clock: u64,
+    entries: HashMap<Arc<ShapeKey>, Entry>,
+    /// Keys by last use, oldest first.
+    recency: BTreeMap<u64, Arc<ShapeKey>>,
+}
+
+impl ResultCache {
+    fn new(max_entries: usize, max_bytes: usize) -> Self {
+        Self {
+            max_entries,
+            max_bytes,
+            bytes: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn get(&mut self, key: &ShapeKey) -> Option<Arc<ShapedRun>> {
+        let now = self.tick();
+        let (key, entry) = self.entries.get_key_value(key)?;
+        let key = key.clone();
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(now, key.clone());
+        let entry = self.entries.get_mut(&key)?;
+        entry.last_used = now;
+        Some(entry.run.clone())
+    }
+
+    fn insert(&mut self, key: ShapeKey, run: ShapedRun) {
+        let size = key.size() + run.size();
+        if size > self.max_bytes || self.entries.contains_key(&key) {
+            return;
+        }
+        while self.entries.len() >= self.max_entries || self.bytes + size > self.max_bytes {
+            if !self.evict_oldest() {
+                return;
+            }
+        }
+
+        let now = self.tick();
+        let key = Arc::new(key);
+        self.recency.insert(now, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                run: Arc::new(run),
+                last_used: now,
+                size,
+            },
+        );
+        self.bytes += size;
+    }
+
+    fn evict_oldest(&mut self) -> bool {
+        let Some((_, key)) = self.recency.pop_first() else {
+            return false;
+        };
+        if let Some(entry) = self.entries.remove(&key) {
+            self.bytes -= entry.size;
+        }
+        true
+    }
+
+    fn shrink_to_limits(&mut self) {
+        while self.entries.len() > self.max_entries || self.bytes > self.max_bytes {
+            if !self.evict_oldest() {
+                break;
+            }
+        }
+    }
+
+    fn remove_font(&mut self, font_id: u64) {
+        self.recency.retain(|_, key| key.font_id != font_id);
+        let mut freed = 0;
+        self.entries.retain(|key, entry| {
+            let keep = key.font_id != font_id;
+            if !keep {
+                freed += entry.size;
+            }
+            keep
+        });
+        self.bytes -= freed;
+    }
+}
+
+/// Set while the cache is enabled, so shaping skips the lock otherwise.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CACHE: Mutex<Option<ResultCache>> = Mutex::new(None);
+
+fn lock() -> MutexGuard<'static, Option<ResultCache>> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Sets the cache limits, evicting entries beyond them. A zero limit disables
+/// the cache and drops every entry.
+pub(crate) fn configure(max_entries: usize, max_bytes: usize) {
+    let mut cache = lock();
+    if max_entries == 0 || max_bytes == 0 {
+        *cache = None;
+        ENABLED.store(false, Ordering::Relaxed);
+        return;
+    }
+
+    let cache = cache.get_or_insert_with(|| ResultCache::new(max_entries, max_bytes));
+    cache.max_entries = max_entries;
+    cache.max_bytes = max_bytes;
+    cache.shrink_to_limits();
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Drops every entry, keeping the limits.
+pub(crate) fn clear() {
+    if let Some(cache) = lock().as_mut() {
+        *cache = ResultCache::new(cache.max_entries, cache.max_bytes);
+    }
+}
+
+/// Number of entries and approximate bytes held.
+pub(crate) fn usage() -> (usize, usize) {
+    lock().as_ref().map_or((0, 0), |cache| (cache.entries.len(), cache.bytes))
+}
+
+pub(crate) fn get(key: &ShapeKey) -> Option<Arc<ShapedRun>> {
+    lock().as_mut()?.get(key)
+}
+
+pub(crate) fn insert(key: ShapeKey, run: ShapedRun) {
+    if let Some(cache) = lock().as_mut() {
+        cache.insert(key, run);
+    }
+}
+
+/// Drops the entries of a font that is being freed.
+pub(crate) fn remove_font(font_id: u64) {
+    if enabled() {
+        if let Some(cache) = lock().as_mut() {
+            cache.remove_font(font_id);
+        }
+    }
+}
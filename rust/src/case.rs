@@ -0,0 +1,128 @@
+//! Cluster-preserving case transforms.
+//!
+//! Case mapping can change the number of characters (e.g. "ß" uppercases to
+//! "SS"). Every output character keeps the cluster of the source character it
+//! came from, so shaped glyphs still map back to the original text.
+
+use crate::HarfRustCaseTransform;
+
+const CAPITAL_SIGMA: char = '\u{03A3}';
+const SMALL_SIGMA: char = '\u{03C3}';
+const FINAL_SIGMA: char = '\u{03C2}';
+
+/// Applies `transform` to `(char, cluster)` pairs.
+pub(crate) fn transform_case(
+    text: &[(char, u32)],
+    transform: HarfRustCaseTransform,
+) -> Vec<(char, u32)> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut at_word_start = true;
+
+    for (i, &(ch, cluster)) in text.iter().enumerate() {
+        match transform {
+            HarfRustCaseTransform::None => out.push((ch, cluster)),
+            HarfRustCaseTransform::Uppercase => {
+                out.extend(ch.to_uppercase().map(|c| (c, cluster)));
+            }
+            HarfRustCaseTransform::Lowercase if ch == CAPITAL_SIGMA => {
+                out.push((lowercase_sigma(text, i), cluster));
+            }
+            HarfRustCaseTransform::Lowercase => {
+                out.extend(ch.to_lowercase().map(|c| (c, cluster)));
+            }
+            HarfRustCaseTransform::Capitalize if at_word_start && ch.is_alphabetic() => {
+                push_titlecase(&mut out, ch, cluster);
+            }
+            HarfRustCaseTransform::Capitalize => out.push((ch, cluster)),
+        }
+        at_word_start = !(ch.is_alphanumeric() || is_word_joiner(ch));
+    }
+
+    out
+}
+
+/// Appends the titlecase mapping of `ch`. It differs from the uppercase
+/// mapping only for the characters below (UnicodeData.txt and
+/// SpecialCasing.txt): digraphs, ligatures, Georgian letters and Greek
+/// letters with a iota subscript.
+fn push_titlecase(out: &mut Vec<(char, u32)>, ch: char, cluster: u32) {
+    let title = match ch {
+        '\u{01C4}'..='\u{01C6}' => "\u{01C5}",
+        '\u{01C7}'..='\u{01C9}' => "\u{01C8}",
+        '\u{01CA}'..='\u{01CC}' => "\u{01CB}",
+        '\u{01F1}'..='\u{01F3}' => "\u{01F2}",
+        '\u{00DF}' => "Ss",
+        '\u{FB00}' => "Ff",
+        '\u{FB01}' => "Fi",
+        '\u{FB02}' => "Fl",
+        '\u{FB03}' => "Ffi",
+        '\u{FB04}' => "Ffl",
+        '\u{FB05}' | '\u{FB06}' => "St",
+        '\u{0587}' => "\u{0535}\u{0582}",
+        '\u{FB13}' => "\u{0544}\u{0576}",
+        '\u{FB14}' => "\u{0544}\u{0565}",
+        '\u{FB15}' => "\u{0544}\u{056B}",
+        '\u{FB16}' => "\u{054E}\u{0576}",
+        '\u{FB17}' => "\u{0544}\u{056D}",
+        '\u{1FB2}' => "\u{1FBA}\u{0345}",
+        '\u{1FB4}' => "\u{0386}\u{0345}",
+        '\u{1FB7}' => "\u{0391}\u{0342}\u{0345}",
+        '\u{1FC2}' => "\u{1FCA}\u{0345}",
+        '\u{1FC4}' => "\u{0389}\u{0345}",
+        '\u{1FC7}' => "\u{0397}\u{0342}\u{0345}",
+        '\u{1FF2}' => "\u{1FFA}\u{0345}",
+        '\u{1FF4}' => "\u{038F}\u{0345}",
+        '\u{1FF7}' => "\u{03A9}\u{0342}\u{0345}",
+        _ => {
+            let single = match ch {
+                // Small letters with ypogegrammeni map to the prosgegrammeni forms
+                '\u{1F80}'..='\u{1F87}' | '\u{1F90}'..='\u{1F97}' | '\u{1FA0}'..='\u{1FA7}' => {
+                    char::from_u32(ch as u32 + 8)
+                }
+                '\u{1FB3}' => Some('\u{1FBC}'),
+                '\u{1FC3}' => Some('\u{1FCC}'),
+                '\u{1FF3}' => Some('\u{1FFC}'),
+                // Already titlecase (Greek) or without one distinct from the
+                // letter itself (Georgian Mkhedruli)
+                '\u{1F88}'..='\u{1F8F}'
+                | '\u{1F98}'..='\u{1F9F}'
+                | '\u{1FA8}'..='\u{1FAF}'
+                | '\u{1FBC}'
+                | '\u{1FCC}'
+                | '\u{1FFC}'
+                | '\u{10D0}'..='\u{10FA}'
+                | '\u{10FD}'..='\u{10FF}' => Some(ch),
+                _ => None,
+            };
+            match single {
+                Some(title) => out.push((title, cluster)),
+                None => out.extend(ch.to_uppercase().map(|c| (c, cluster))),
+            }
+            return;
+        }
+    };
+    out.extend(title.chars().map(|c| (c, cluster)));
+}
+
+/// Characters that do not end a word for capitalization purposes.
+fn is_word_joiner(ch: char) -> bool {
+    matches!(ch, '\'' | '\u{2019}' | '\u{00AD}')
+}
+
+/// Lowercases capital sigma at `index`, using final sigma at the end of a word.
+fn lowercase_sigma(text: &[(char, u32)], index: usize) -> char {
+    let preceded_by_letter = text[..index]
+        .iter()
+        .rev()
+        .take_while(|&&(c, _)| !c.is_whitespace())
+        .any(|&(c, _)| c.is_alphabetic());
+    let followed_by_letter = text[index + 1..]
+        .iter()
+        .take_while(|&&(c, _)| !c.is_whitespace())
+        .any(|&(c, _)| c.is_alphabetic());
+    if preceded_by_letter && !followed_by_letter {
+        FINAL_SIGMA
+    } else {
+        SMALL_SIGMA
+    }
+}
@@ -0,0 +1,330 @@
+//! Wrapping bare CFF font programs (PDF FontFile3/Type1C and CIDFontType0C)
+//! in a minimal OpenType container.
+//!
+//! The container adds the tables shaping and metrics need: `head`, `hhea`,
+//! `hmtx` (advances from the charstrings), `maxp`, `post` and, when glyph
+//! names allow it, a Unicode `cmap`.
+
+use std::collections::BTreeMap;
+
+use skrifa::raw::tables::cff::Cff;
+use skrifa::raw::tables::postscript::dict::{self, Entry};
+use skrifa::raw::tables::postscript::Index1;
+use skrifa::raw::types::Tag;
+use skrifa::raw::{FontData, FontRead};
+
+use crate::sfnt;
+
+/// sfnt version of fonts with CFF outlines.
+const OPEN_TYPE: u32 = u32::from_be_bytes(*b"OTTO");
+
+/// CFF programs in PDFs use a 1/1000 font matrix.
+const UNITS_PER_EM: u16 = 1000;
+
+/// Glyph names of printable ASCII characters (U+0020..U+007E) other than
+/// letters, which are named after themselves.
+const ASCII_NAMES: &[(&str, char)] = &[
+    ("space", ' '), ("exclam", '!'), ("quotedbl", '"'), ("numbersign", '#'), ("dollar", '$'),
+    ("percent", '%'), ("ampersand", '&'), ("quotesingle", '\''), ("parenleft", '('),
+    ("parenright", ')'), ("asterisk", '*'), ("plus", '+'), ("comma", ','), ("hyphen", '-'),
+    ("period", '.'), ("slash", '/'), ("zero", '0'), ("one", '1'), ("two", '2'), ("three", '3'),
+    ("four", '4'), ("five", '5'), ("six", '6'), ("seven", '7'), ("eight", '8'), ("nine", '9'),
+    ("colon", ':'), ("semicolon", ';'), ("less", '<'), ("equal", '='), ("greater", '>'),
+    ("question", '?'), ("at", '@'), ("bracketleft", '['), ("backslash", '\\'),
+    ("bracketright", ']'), ("asciicircum", '^'), ("underscore", '_'), ("grave", '`'),
+    ("braceleft", '{'), ("bar", '|'), ("braceright", '}'), ("asciitilde", '~'),
+];
+
+/// Default and nominal widths from a Private DICT.
+#[derive(Clone, Copy, Default)]
+struct Widths {
+    default: f64,
+    nominal: f64,
+}
+
+impl Widths {
+    fn read(cff: &Cff, font_dict: &[u8]) -> Self {
+        let mut widths = Widths::default();
+        let private = dict::entries(font_dict, None).find_map(|entry| match entry {
+            Ok(Entry::PrivateDictRange(range)) => Some(range),
+            _ => None,
+        });
+        let Some(data) = private.and_then(|range| cff.offset_data().as_bytes().get(range)) else {
+            return widths;
+        };
+        for entry in dict::entries(data, None).flatten() {
+            match entry {
+                Entry::DefaultWidthX(value) => widths.default = value.to_f64(),
+                Entry::NominalWidthX(value) => widths.nominal = value.to_f64(),
+                _ => {}
+            }
+        }
+        widths
+    }
+}
+
+/// Wraps a CFF font program in an OpenType font. Returns `None` if the data
+/// is not a readable CFF font.
+pub(crate) fn wrap_cff(data: &[u8]) -> Option<Vec<u8>> {
+    let cff = Cff::read(FontData::new(data)).ok()?;
+    let top_dict = cff.top_dicts().get(0).ok()?;
+    let offset_data = cff.offset_data();
+
+    let mut charstrings = None;
+    let mut bbox = [0i16; 4];
+    let mut fd_array = None;
+    let mut fd_select = None;
+    let (mut italic_angle, mut underline_position, mut underline_thickness) = (0.0, -100.0, 50.0);
+    let mut fixed_pitch = false;
+    for entry in dict::entries(top_dict, None).flatten() {
+        match entry {
+            Entry::CharstringsOffset(offset) => charstrings = Some(offset),
+            Entry::FontBbox(values) => bbox = values.map(|v| v.to_f64().round() as i16),
+            Entry::FdArrayOffset(offset) => fd_array = Some(offset),
+            Entry::FdSelectOffset(offset) => fd_select = Some(offset),
+            Entry::ItalicAngle(value) => italic_angle = value.to_f64(),
+            Entry::UnderlinePosition(value) => underline_position = value.to_f64(),
+            Entry::UnderlineThickness(value) => underline_thickness = value.to_f64(),
+            Entry::IsFixedPitch(value) => fixed_pitch = value,
+            _ => {}
+        }
+    }
+    let charstrings = Index1::read(offset_data.split_off(charstrings?)?).ok()?;
+    let num_glyphs = charstrings.count();
+
+    // CID-keyed fonts have a Private DICT per font DICT, chosen by FDSelect.
+    let font_widths: Vec<Widths> = match fd_array {
+        Some(offset) => {
+            let fds = Index1::read(offset_data.split_off(offset)?).ok()?;
+            (0..fds.count() as usize)
+                .map(|i| Widths::read(&cff, fds.get(i).unwrap_or_default()))
+                .collect()
+        }
+        None => vec![Widths::read(&cff, top_dict)],
+    };
+    let fd_select = fd_select.and_then(|offset| offset_data.as_bytes().get(offset..));
+
+    let mut hmtx = Vec::with_capacity(num_glyphs as usize * 4);
+    let mut max_advance = 0u16;
+    for gid in 0..num_glyphs {
+        let fd = fd_select.and_then(|data| font_index(data, gid)).unwrap_or(0);
+        let widths = font_widths.get(fd as usize).copied().unwrap_or_default();
+        let charstring = charstrings.get(gid as usize).unwrap_or_default();
+        let advance = match charstring_width(charstring) {
+            Some(width) => widths.nominal + width,
+            None => widths.default,
+        };
+        let advance = advance.round().clamp(0.0, u16::MAX as f64) as u16;
+        max_advance = max_advance.max(advance);
+        hmtx.extend_from_slice(&advance.to_be_bytes());
+        hmtx.extend_from_slice(&0i16.to_be_bytes());
+    }
+
+    let [x_min, y_min, x_max, y_max] = bbox;
+    let mut head = Vec::with_capacity(54);
+    head.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // version
+    head.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // fontRevision
+    head.extend_from_slice(&0u32.to_be_bytes()); // checksumAdjustment
+    head.extend_from_slice(&0x5F0F_3CF5u32.to_be_bytes()); // magicNumber
+    head.extend_from_slice(&0x0003u16.to_be_bytes()); // flags: baseline and lsb at 0
+    head.extend_from_slice(&UNITS_PER_EM.to_be_bytes());
+    head.extend_from_slice(&[0; 16]); // created, modified
+    for value in [x_min, y_min, x_max, y_max] {
+        head.extend_from_slice(&value.to_be_bytes());
+    }
+    head.extend_from_slice(&0u16.to_be_bytes()); // macStyle
+    head.extend_from_slice(&8u16.to_be_bytes()); // lowestRecPPEM
+    head.extend_from_slice(&2i16.to_be_bytes()); // fontDirectionHint
+    head.extend_from_slice(&[0; 4]); // indexToLocFormat, glyphDataFormat
+
+    let mut hhea = Vec::with_capacity(36);
+    hhea.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    for value in [y_max, y_min, 0] {
+        hhea.extend_from_slice(&value.to_be_bytes()); // ascender, descender, lineGap
+    }
+    hhea.extend_from_slice(&max_advance.to_be_bytes());
+    for value in [0, 0, x_max, 1, 0, 0, 0, 0, 0, 0, 0] {
+        // min LSB/RSB, xMaxExtent, caret slope rise/run/offset, reserved,
+        // metricDataFormat
+        hhea.extend_from_slice(&value.to_be_bytes());
+    }
+    hhea.extend_from_slice(&num_glyphs.to_be_bytes());
+
+    let mut maxp = 0x0000_5000u32.to_be_bytes().to_vec();
+    maxp.extend_from_slice(&num_glyphs.to_be_bytes());
+
+    let mut post = 0x0003_0000u32.to_be_bytes().to_vec();
+    post.extend_from_slice(&((italic_angle * 65536.0).round() as i32).to_be_bytes());
+    post.extend_from_slice(&(underline_position.round() as i16).to_be_bytes());
+    post.extend_from_slice(&(underline_thickness.round() as i16).to_be_bytes());
+    post.extend_from_slice(&(fixed_pitch as u32).to_be_bytes());
+    post.extend_from_slice(&[0; 16]);
+
+    let mut tables = vec![
+        (Tag::new(b"CFF "), data.to_vec()),
+        (Tag::new(b"head"), head),
+        (Tag::new(b"hhea"), hhea),
+        (Tag::new(b"hmtx"), hmtx),
+        (Tag::new(b"maxp"), maxp),
+        (Tag::new(b"post"), post),
+    ];
+    let mappings = unicode_mappings(&cff);
+    if !mappings.is_empty() {
+        tables.push((Tag::new(b"cmap"), encode_cmap(&mappings)));
+    }
+
+    Some(sfnt::build_sfnt(OPEN_TYPE, tables))
+}
+
+/// Looks up the font DICT of a glyph in FDSelect format 0 or 3.
+fn font_index(data: &[u8], gid: u16) -> Option<u8> {
+    match *data.first()? {
+        0 => data.get(1 + gid as usize).copied(),
+        3 => {
+            let num_ranges = u16::from_be_bytes([*data.get(1)?, *data.get(2)?]) as usize;
+            let ranges = data.get(3..3 + num_ranges * 3 + 2)?;
+            (0..num_ranges).find_map(|i| {
+                let range = &ranges[i * 3..];
+                let first = u16::from_be_bytes([range[0], range[1]]);
+                let next = u16::from_be_bytes([range[3], range[4]]);
+                (first..next).contains(&gid).then_some(range[2])
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the width operand of a Type 2 charstring (relative to
+/// nominalWidthX), or `None` if the glyph uses defaultWidthX. The width is
+/// the extra leading operand of the first stack-clearing operator.
+fn charstring_width(charstring: &[u8]) -> Option<f64> {
+    let mut operands: Vec<f64> = Vec::new();
+    let mut i = 0;
+    while let Some(&b0) = charstring.get(i) {
+        let expected_even = match b0 {
+            32..=246 => {
+                operands.push(b0 as f64 - 139.0);
+                i += 1;
+                continue;
+            }
+            247..=250 => {
+                let b1 = *charstring.get(i + 1)? as f64;
+                operands.push((b0 as f64 - 247.0) * 256.0 + b1 + 108.0);
+                i += 2;
+                continue;
+            }
+            251..=254 => {
+                let b1 = *charstring.get(i + 1)? as f64;
+                operands.push(-(b0 as f64 - 251.0) * 256.0 - b1 - 108.0);
+                i += 2;
+                continue;
+            }
+            28 => {
+                let bytes = charstring.get(i + 1..i + 3)?;
+                operands.push(i16::from_be_bytes([bytes[0], bytes[1]]) as f64);
+                i += 3;
+                continue;
+            }
+            255 => {
+                let bytes = charstring.get(i + 1..i + 5)?;
+                operands.push(i32::from_be_bytes(bytes.try_into().ok()?) as f64 / 65536.0);
+                i += 5;
+                continue;
+            }
+            // hstem, vstem, hstemhm, vstemhm, hintmask, cntrmask, rmoveto:
+            // an even number of operands.
+            1 | 3 | 18 | 23 | 19 | 20 | 21 => true,
+            // hmoveto, vmoveto: one operand.
+            4 | 22 => false,
+            // endchar: none, or four for an accented character.
+            14 => true,
+            // Subroutine calls may supply the width; anything else has no width.
+            _ => return None,
+        };
+        let has_width = if expected_even {
+            operands.len() % 2 == 1
+        } else {
+            operands.len() == 2
+        };
+        return has_width.then(|| operands[0]);
+    }
+    None
+}
+
+/// Maps Unicode code points to glyphs using the charset's glyph names:
+/// "uniXXXX", "uXXXX[XX]", letters and the ASCII punctuation and digit
+/// names. CID-keyed fonts have no glyph names and get no mappings.
+fn unicode_mappings(cff: &Cff) -> BTreeMap<u32, u16> {
+    let mut mappings = BTreeMap::new();
+    let Ok(Some(charset)) = cff.charset(0) else {
+        return mappings;
+    };
+    for (gid, sid) in charset.iter() {
+        let Some(name) = cff.string(sid) else {
+            continue;
+        };
+        let name: String = name.chars().collect();
+        let code_point = if let Some(hex) = name.strip_prefix("uni").filter(|h| h.len() == 4) {
+            u32::from_str_radix(hex, 16).ok()
+        } else if let Some(hex) = name.strip_prefix('u').filter(|h| (4..=6).contains(&h.len())) {
+            u32::from_str_radix(hex, 16).ok()
+        } else if name.len() == 1 && name.as_bytes()[0].is_ascii_alphabetic() {
+            Some(name.as_bytes()[0] as u32)
+        } else {
+            ASCII_NAMES.iter().find(|(n, _)| *n == name).map(|&(_, c)| c as u32)
+        };
+        if let Some(code_point) = code_point.filter(|&cp| cp <= 0xFFFF) {
+            mappings.entry(code_point).or_insert(gid.to_u32() as u16);
+        }
+    }
+    mappings
+}
+
+/// Encodes BMP mappings as a `cmap` with one Windows Unicode format 4
+/// subtable, one segment per run of consecutive code points and glyphs.
+fn encode_cmap(mappings: &BTreeMap<u32, u16>) -> Vec<u8> {
+    let mut segments: Vec<(u16, u16, u16)> = Vec::new();
+    for (&cp, &gid) in mappings {
+        let cp = cp as u16;
+        match segments.last_mut() {
+            Some((start, end, first_gid))
+                if *end as u32 + 1 == cp as u32
+                    && (*first_gid as u32 + (cp - *start) as u32) == gid as u32 =>
+            {
+                *end = cp;
+            }
+            _ => segments.push((cp, cp, gid)),
+        }
+    }
+    segments.push((0xFFFF, 0xFFFF, 0));
+
+    let seg_count = segments.len() as u16;
+    let entry_selector = seg_count.ilog2() as u16;
+    let search_range = 2 * (1u16 << entry_selector);
+    let mut subtable = Vec::new();
+    for value in [4, 16 + 8 * seg_count, 0, seg_count * 2, search_range, entry_selector] {
+        subtable.extend_from_slice(&value.to_be_bytes());
+    }
+    subtable.extend_from_slice(&(seg_count * 2 - search_range).to_be_bytes());
+    for &(_, end, _) in &segments {
+        subtable.extend_from_slice(&end.to_be_bytes());
+    }
+    subtable.extend_from_slice(&0u16.to_be_bytes()); // reservedPad
+    for &(start, _, _) in &segments {
+        subtable.extend_from_slice(&start.to_be_bytes());
+    }
+    for &(start, _, gid) in &segments {
+        // The final segment maps 0xFFFF to glyph 0 with a delta of 1.
+        let delta = if start == 0xFFFF { 1 } else { gid.wrapping_sub(start) };
+        subtable.extend_from_slice(&delta.to_be_bytes());
+    }
+    subtable.extend_from_slice(&vec![0; segments.len() * 2]); // idRangeOffsets
+
+    let mut cmap = Vec::with_capacity(12 + subtable.len());
+    for value in [0u16, 1, 3, 1] {
+        cmap.extend_from_slice(&value.to_be_bytes()); // version, numTables, platform, encoding
+    }
+    cmap.extend_from_slice(&12u32.to_be_bytes());
+    cmap.extend_from_slice(&subtable);
+    cmap
+}
@@ -0,0 +1,659 @@
+//! Color glyph rasterization.
+//!
+//! COLR (v0 and v1) glyphs are painted through a tiny-skia backed
+//! [`ColorPainter`]; glyphs without COLR data fall back to embedded color
+//! bitmaps (sbix/CBDT). Sweep gradients and radial gradients with a nonzero
+//! start radius, which tiny-skia lacks, are evaluated per pixel. SVG glyphs
+//! are not rendered; [`has_svg_glyph`] lets callers report them.
+
+use skrifa::bitmap::{BitmapData, Origin};
+use skrifa::color::{Brush, ColorPainter, ColorStop, CompositeMode, Extend};
+use skrifa::instance::{LocationRef, Size};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::outline::OutlineGlyphCollection;
+use skrifa::raw::types::BoundingBox;
+use skrifa::raw::TableProvider;
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+use tiny_skia::{
+    BlendMode, Color, FillRule, FilterQuality, GradientStop, IntSize, LinearGradient, Mask, Paint,
+    Path, PathBuilder, Pixmap, PixmapPaint, Point, RadialGradient, Rect, Shader, SpreadMode,
+    Transform,
+};
+
+/// Palette entry index that refers to the text foreground color.
+pub(crate) const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+/// A rendered color glyph.
+pub(crate) struct ColorBitmap {
+    pub width: u32,
+    pub height: u32,
+    /// Distance from the pen position to the left edge, in pixels.
+    pub left: i32,
+    /// Distance from the baseline up to the top edge, in pixels.
+    pub top: i32,
+    /// Premultiplied RGBA pixels, row-major without padding.
+    pub pixels: Vec<u8>,
+}
+
+/// Renders a color glyph at `ppem` using the given CPAL palette, with
+/// `overrides` replacing individual entries (or the foreground color, under
+/// [`FOREGROUND_PALETTE_INDEX`]). Returns `None` if the glyph has no COLR or
+/// color bitmap representation.
+pub(crate) fn render_color_glyph(
+    font: &FontRef,
+    glyph_id: GlyphId,
+    ppem: f32,
+    palette_index: u16,
+    overrides: &[(u16, Color)],
+) -> Option<ColorBitmap> {
+    if !ppem.is_finite() || ppem <= 0.0 {
+        return None;
+    }
+
+    render_colr(font, glyph_id, ppem, palette_index, overrides)
+        .or_else(|| render_bitmap(font, glyph_id, ppem))
+}
+
+fn render_colr(
+    font: &FontRef,
+    glyph_id: GlyphId,
+    ppem: f32,
+    palette_index: u16,
+    overrides: &[(u16, Color)],
+) -> Option<ColorBitmap> {
+    let color_glyph = font.color_glyphs().get(glyph_id)?;
+    let upem = font.head().ok()?.units_per_em();
+    if upem == 0 {
+        return None;
+    }
+    let scale = ppem / upem as f32;
+
+    let outlines = font.outline_glyphs();
+    let bounds = match color_glyph.bounding_box(LocationRef::default(), Size::unscaled()) {
+        Some(bounds) => bounds,
+        None => {
+            let mut painter = BoundsPainter::new(&outlines);
+            color_glyph.paint(LocationRef::default(), &mut painter).ok()?;
+            painter.bounds?
+        }
+    };
+
+    let left = (bounds.x_min * scale).floor();
+    let right = (bounds.x_max * scale).ceil();
+    let bottom = (bounds.y_min * scale).floor();
+    let top = (bounds.y_max * scale).ceil();
+    let width = (right - left) as u32;
+    let height = (top - bottom) as u32;
+
+    // Font units are y-up, pixels are y-down with the origin at the top-left corner.
+    let base = Transform::from_row(scale, 0.0, 0.0, -scale, -left, top);
+    let mut palette = palette_colors(font, palette_index);
+    let mut foreground = Color::BLACK;
+    for &(index, color) in overrides {
+        match palette.get_mut(index as usize) {
+            Some(entry) => *entry = color,
+            None if index == FOREGROUND_PALETTE_INDEX => foreground = color,
+            None => {}
+        }
+    }
+    let mut painter = RasterPainter::new(&outlines, &palette, foreground, width, height, base)?;
+    color_glyph.paint(LocationRef::default(), &mut painter).ok()?;
+
+    Some(ColorBitmap {
+        width,
+        height,
+        left: left as i32,
+        top: top as i32,
+        pixels: painter.finish().take(),
+    })
+}
+
+fn render_bitmap(font: &FontRef, glyph_id: GlyphId, ppem: f32) -> Option<ColorBitmap> {
+    let glyph = font.bitmap_strikes().glyph_for_size(Size::new(ppem), glyph_id)?;
+    let source = match glyph.data {
+        BitmapData::Png(data) => Pixmap::decode_png(data).ok()?,
+        BitmapData::Bgra(data) => {
+            let mut rgba = data.to_vec();
+            for pixel in rgba.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+            Pixmap::from_vec(rgba, IntSize::from_wh(glyph.width, glyph.height)?)?
+        }
+        BitmapData::Mask(_) => return None,
+    };
+
+    let upem = font.head().ok()?.units_per_em();
+    if upem == 0 || glyph.ppem_x <= 0.0 || glyph.ppem_y <= 0.0 {
+        return None;
+    }
+    let scale_x = ppem / glyph.ppem_x;
+    let scale_y = ppem / glyph.ppem_y;
+    let width = (source.width() as f32 * scale_x).ceil().max(1.0) as u32;
+    let height = (source.height() as f32 * scale_y).ceil().max(1.0) as u32;
+
+    let font_scale = ppem / upem as f32;
+    let left = glyph.bearing_x * font_scale + glyph.inner_bearing_x * scale_x;
+    let y = glyph.bearing_y * font_scale + glyph.inner_bearing_y * scale_y;
+    let top = match glyph.placement_origin {
+        Origin::TopLeft => y,
+        Origin::BottomLeft => y + height as f32,
+    };
+
+    let mut pixmap = Pixmap::new(width, height)?;
+    let paint = PixmapPaint {
+        quality: FilterQuality::Bilinear,
+        ..Default::default()
+    };
+    pixmap.draw_pixmap(
+        0,
+        0,
+        source.as_ref(),
+        &paint,
+        Transform::from_scale(scale_x, scale_y),
+        None,
+    );
+
+    Some(ColorBitmap {
+        width,
+        height,
+        left: left.round() as i32,
+        top: top.round() as i32,
+        pixels: pixmap.take(),
+    })
+}
+
+/// Returns true if the font has an SVG document for the glyph.
+pub(crate) fn has_svg_glyph(font: &FontRef, glyph_id: GlyphId) -> bool {
+    font.svg()
+        .ok()
+        .and_then(|svg| svg.glyph_data(glyph_id).ok().flatten())
+        .is_some()
+}
+
+/// Resolves the colors of a CPAL palette, falling back to the default palette
+/// when `palette_index` is out of range.
+fn palette_colors(font: &FontRef, palette_index: u16) -> Vec<Color> {
+    let palettes = font.color_palettes();
+    let palette = palettes.get(palette_index).or_else(|| palettes.get(0));
+    palette
+        .map(|palette| {
+            palette
+                .colors()
+                .iter()
+                .map(|c| Color::from_rgba8(c.red, c.green, c.blue, c.alpha))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Builds the outline of a glyph as a path in font units.
+pub(crate) fn glyph_path(outlines: &OutlineGlyphCollection, glyph_id: GlyphId) -> Option<Path> {
+    let glyph = outlines.get(glyph_id)?;
+    let mut pen = PathPen(PathBuilder::new());
+    glyph
+        .draw(DrawSettings::unhinted(Size::unscaled(), LocationRef::default()), &mut pen)
+        .ok()?;
+    pen.0.finish()
+}
+
+fn to_skia_transform(t: skrifa::color::Transform) -> Transform {
+    Transform::from_row(t.xx, t.yx, t.xy, t.yy, t.dx, t.dy)
+}
+
+struct PathPen(PathBuilder);
+
+impl OutlinePen for PathPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.0.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.0.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.0.quad_to(cx0, cy0, x, y);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.0.cubic_to(cx0, cy0, cx1, cy1, x, y);
+    }
+
+    fn close(&mut self) {
+        self.0.close();
+    }
+}
+
+/// Computes the ink bounds of a color glyph (in font units) from the glyph
+/// outlines it clips to. Used for COLRv0 glyphs and COLRv1 glyphs without a
+/// clip box.
+struct BoundsPainter<'a> {
+    outlines: &'a OutlineGlyphCollection<'a>,
+    transforms: Vec<Transform>,
+    bounds: Option<BoundingBox<f32>>,
+}
+
+impl<'a> BoundsPainter<'a> {
+    fn new(outlines: &'a OutlineGlyphCollection<'a>) -> Self {
+        Self {
+            outlines,
+            transforms: vec![Transform::identity()],
+            bounds: None,
+        }
+    }
+
+    fn current_transform(&self) -> Transform {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+}
+
+impl ColorPainter for BoundsPainter<'_> {
+    fn push_transform(&mut self, transform: skrifa::color::Transform) {
+        let current = self.current_transform();
+        self.transforms.push(current.pre_concat(to_skia_transform(transform)));
+    }
+
+    fn pop_transform(&mut self) {
+        if self.transforms.len() > 1 {
+            self.transforms.pop();
+        }
+    }
+
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        let Some(path) = glyph_path(self.outlines, glyph_id)
+            .and_then(|path| path.transform(self.current_transform()))
+        else {
+            return;
+        };
+
+        let rect = path.bounds();
+        self.bounds = Some(match self.bounds {
+            Some(b) => BoundingBox {
+                x_min: b.x_min.min(rect.left()),
+                y_min: b.y_min.min(rect.top()),
+                x_max: b.x_max.max(rect.right()),
+                y_max: b.y_max.max(rect.bottom()),
+            },
+            None => BoundingBox {
+                x_min: rect.left(),
+                y_min: rect.top(),
+                x_max: rect.right(),
+                y_max: rect.bottom(),
+            },
+        });
+    }
+
+    fn push_clip_box(&mut self, _clip_box: BoundingBox<f32>) {}
+
+    fn pop_clip(&mut self) {}
+
+    fn fill(&mut self, _brush: Brush<'_>) {}
+
+    fn push_layer(&mut self, _composite_mode: CompositeMode) {}
+}
+
+/// Paints a color glyph into a premultiplied RGBA pixmap.
+struct RasterPainter<'a> {
+    outlines: &'a OutlineGlyphCollection<'a>,
+    palette: &'a [Color],
+    foreground: Color,
+    width: u32,
+    height: u32,
+    transforms: Vec<Transform>,
+    clips: Vec<Option<Mask>>,
+    layers: Vec<Pixmap>,
+}
+
+impl<'a> RasterPainter<'a> {
+    fn new(
+        outlines: &'a OutlineGlyphCollection<'a>,
+        palette: &'a [Color],
+        foreground: Color,
+        width: u32,
+        height: u32,
+        base: Transform,
+    ) -> Option<Self> {
+        Some(Self {
+            outlines,
+            palette,
+            foreground,
+            width,
+            height,
+            transforms: vec![base],
+            clips: vec![None],
+            layers: vec![Pixmap::new(width, height)?],
+        })
+    }
+
+    fn finish(mut self) -> Pixmap {
+        self.layers.swap_remove(0)
+    }
+
+    fn current_transform(&self) -> Transform {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+
+    fn current_clip(&self) -> Option<&Mask> {
+        self.clips.last().and_then(|clip| clip.as_ref())
+    }
+
+    fn push_clip_path(&mut self, path: Option<Path>) {
+        let transform = self.current_transform();
+        let mask = match self.current_clip().cloned() {
+            Some(mut mask) => {
+                match &path {
+                    Some(path) => mask.intersect_path(path, FillRule::Winding, true, transform),
+                    None => mask.clear(),
+                }
+                Some(mask)
+            }
+            None => Mask::new(self.width, self.height).map(|mut mask| {
+                if let Some(path) = &path {
+                    mask.fill_path(path, FillRule::Winding, true, transform);
+                }
+                mask
+            }),
+        };
+        self.clips.push(mask);
+    }
+
+    fn color(&self, palette_index: u16, alpha: f32) -> Color {
+        let mut color = if palette_index == FOREGROUND_PALETTE_INDEX {
+            self.foreground
+        } else {
+            self.palette
+                .get(palette_index as usize)
+                .copied()
+                .unwrap_or(Color::BLACK)
+        };
+        color.apply_opacity(alpha);
+        color
+    }
+
+    fn gradient_stops(&self, stops: &[ColorStop]) -> Vec<GradientStop> {
+        stops
+            .iter()
+            .map(|stop| GradientStop::new(stop.offset, self.color(stop.palette_index, stop.alpha)))
+            .collect()
+    }
+
+    /// Solid fill used when a gradient is degenerate or unsupported.
+    fn first_stop_shader(&self, stops: &[ColorStop]) -> Shader<'static> {
+        let color = stops
+            .first()
+            .map(|stop| self.color(stop.palette_index, stop.alpha))
+            .unwrap_or(Color::TRANSPARENT);
+        Shader::SolidColor(color)
+    }
+
+    /// Color of the gradient at `offset` along its color line, interpolated
+    /// between unpremultiplied stop colors as tiny-skia does.
+    fn color_at(&self, stops: &[ColorStop], offset: f32) -> Color {
+        let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+            return Color::TRANSPARENT;
+        };
+        if offset <= first.offset {
+            return self.color(first.palette_index, first.alpha);
+        }
+        if offset >= last.offset {
+            return self.color(last.palette_index, last.alpha);
+        }
+
+        let next = stops.iter().position(|stop| stop.offset > offset).unwrap_or(stops.len() - 1);
+        let (a, b) = (&stops[next - 1], &stops[next]);
+        let t = (offset - a.offset) / (b.offset - a.offset);
+        let (from, to) = (self.color(a.palette_index, a.alpha), self.color(b.palette_index, b.alpha));
+        let lerp = |x: f32, y: f32| x + (y - x) * t;
+        Color::from_rgba(
+            lerp(from.red(), to.red()),
+            lerp(from.green(), to.green()),
+            lerp(from.blue(), to.blue()),
+            lerp(from.alpha(), to.alpha()),
+        )
+        .unwrap_or(Color::TRANSPARENT)
+    }
+
+    /// Paints a gradient tiny-skia has no shader for. `offset` maps a point
+    /// in the current (font unit) space to a position on the color line, or
+    /// `None` where the gradient is not drawn.
+    fn fill_per_pixel(&mut self, stops: &[ColorStop], extend: Extend, offset: impl Fn(f32, f32) -> Option<f32>) {
+        let Some(inverse) = self.current_transform().invert() else {
+            return;
+        };
+        let Some(mut gradient) = Pixmap::new(self.width, self.height) else {
+            return;
+        };
+
+        let width = self.width as usize;
+        for (i, pixel) in gradient.pixels_mut().iter_mut().enumerate() {
+            let mut point = Point::from_xy((i % width) as f32 + 0.5, (i / width) as f32 + 0.5);
+            inverse.map_points(std::slice::from_mut(&mut point));
+            if let Some(offset) = offset(point.x, point.y) {
+                let color = self.color_at(stops, extend_offset(offset, extend));
+                *pixel = color.premultiply().to_color_u8();
+            }
+        }
+
+        let mask = self.clips.last().and_then(|clip| clip.as_ref());
+        if let Some(layer) = self.layers.last_mut() {
+            layer.draw_pixmap(0, 0, gradient.as_ref(), &PixmapPaint::default(), Transform::identity(), mask);
+        }
+    }
+
+    fn shader(&self, brush: Brush<'_>) -> Shader<'static> {
+        let transform = self.current_transform();
+        match brush {
+            Brush::Solid { palette_index, alpha } => {
+                Shader::SolidColor(self.color(palette_index, alpha))
+            }
+            Brush::LinearGradient {
+                p0,
+                p1,
+                color_stops,
+                extend,
+            } => LinearGradient::new(
+                Point::from_xy(p0.x, p0.y),
+                Point::from_xy(p1.x, p1.y),
+                self.gradient_stops(color_stops),
+                spread_mode(extend),
+                transform,
+            )
+            .unwrap_or_else(|| self.first_stop_shader(color_stops)),
+            // tiny-skia only supports a zero start radius; other radial
+            // gradients are painted per pixel by `fill`.
+            Brush::RadialGradient {
+                c0,
+                c1,
+                r1,
+                color_stops,
+                extend,
+                ..
+            } => RadialGradient::new(
+                Point::from_xy(c0.x, c0.y),
+                Point::from_xy(c1.x, c1.y),
+                r1,
+                self.gradient_stops(color_stops),
+                spread_mode(extend),
+                transform,
+            )
+            .unwrap_or_else(|| self.first_stop_shader(color_stops)),
+            // Painted per pixel by `fill`.
+            Brush::SweepGradient { color_stops, .. } => self.first_stop_shader(color_stops),
+        }
+    }
+}
+
+impl ColorPainter for RasterPainter<'_> {
+    fn push_transform(&mut self, transform: skrifa::color::Transform) {
+        let current = self.current_transform();
+        self.transforms.push(current.pre_concat(to_skia_transform(transform)));
+    }
+
+    fn pop_transform(&mut self) {
+        if self.transforms.len() > 1 {
+            self.transforms.pop();
+        }
+    }
+
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId) {
+        let path = glyph_path(self.outlines, glyph_id);
+        self.push_clip_path(path);
+    }
+
+    fn push_clip_box(&mut self, clip_box: BoundingBox<f32>) {
+        let path = Rect::from_ltrb(clip_box.x_min, clip_box.y_min, clip_box.x_max, clip_box.y_max)
+            .map(PathBuilder::from_rect);
+        self.push_clip_path(path);
+    }
+
+    fn pop_clip(&mut self) {
+        if self.clips.len() > 1 {
+            self.clips.pop();
+        }
+    }
+
+    fn fill(&mut self, brush: Brush<'_>) {
+        match brush {
+            Brush::SweepGradient { c0, start_angle, end_angle, color_stops, extend } => {
+                // skrifa hands out clockwise angles with start <= end
+                let sweep = end_angle - start_angle;
+                return self.fill_per_pixel(color_stops, extend, |x, y| {
+                    let angle = (c0.y - y).atan2(x - c0.x).to_degrees().rem_euclid(360.0);
+                    Some(if sweep == 0.0 {
+                        if angle < start_angle { 0.0 } else { 1.0 }
+                    } else {
+                        (angle - start_angle) / sweep
+                    })
+                });
+            }
+            Brush::RadialGradient { c0, r0, c1, r1, color_stops, extend } if r0 != 0.0 => {
+                return self.fill_per_pixel(color_stops, extend, |x, y| {
+                    two_circle_offset((c0.x, c0.y, r0), (c1.x, c1.y, r1), x, y)
+                });
+            }
+            _ => {}
+        }
+
+        let paint = Paint {
+            shader: self.shader(brush),
+            anti_alias: true,
+            ..Default::default()
+        };
+        let Some(rect) = Rect::from_xywh(0.0, 0.0, self.width as f32, self.height as f32) else {
+            return;
+        };
+
+        let mask = self.clips.last().and_then(|clip| clip.as_ref());
+        if let Some(layer) = self.layers.last_mut() {
+            layer.fill_rect(rect, &paint, Transform::identity(), mask);
+        }
+    }
+
+    fn push_layer(&mut self, _composite_mode: CompositeMode) {
+        if let Some(layer) = Pixmap::new(self.width, self.height) {
+            self.layers.push(layer);
+        }
+    }
+
+    fn pop_layer_with_mode(&mut self, composite_mode: CompositeMode) {
+        if self.layers.len() < 2 {
+            return;
+        }
+
+        let Some(layer) = self.layers.pop() else {
+            return;
+        };
+        let paint = PixmapPaint {
+            blend_mode: blend_mode(composite_mode),
+            ..Default::default()
+        };
+        let mask = self.clips.last().and_then(|clip| clip.as_ref());
+        if let Some(target) = self.layers.last_mut() {
+            target.draw_pixmap(0, 0, layer.as_ref(), &paint, Transform::identity(), mask);
+        }
+    }
+}
+
+/// Position on the color line of a radial gradient between the circles
+/// `(x, y, r)` at offsets 0 and 1 for the point `(x, y)`: the largest offset
+/// whose interpolated circle passes through the point with a radius of at
+/// least 0, or `None` if there is none.
+fn two_circle_offset(start: (f32, f32, f32), end: (f32, f32, f32), x: f32, y: f32) -> Option<f32> {
+    let (cdx, cdy, dr) = (end.0 - start.0, end.1 - start.1, end.2 - start.2);
+    let (pdx, pdy) = (x - start.0, y - start.1);
+    // |p - c(t)| = r(t) as a t^2 - 2 b t + c = 0
+    let a = cdx * cdx + cdy * cdy - dr * dr;
+    let b = pdx * cdx + pdy * cdy + start.2 * dr;
+    let c = pdx * pdx + pdy * pdy - start.2 * start.2;
+    let radius_ok = |t: f32| start.2 + t * dr >= 0.0;
+
+    if a.abs() < 1e-6 {
+        if b == 0.0 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return radius_ok(t).then_some(t);
+    }
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    let (t0, t1) = ((b + root) / a, (b - root) / a);
+    let (larger, smaller) = if t0 >= t1 { (t0, t1) } else { (t1, t0) };
+    [larger, smaller].into_iter().find(|&t| radius_ok(t))
+}
+
+/// Maps an offset outside `[0, 1]` back onto the color line.
+fn extend_offset(offset: f32, extend: Extend) -> f32 {
+    match extend {
+        Extend::Repeat => offset - offset.floor(),
+        Extend::Reflect => {
+            let offset = offset.rem_euclid(2.0);
+            if offset > 1.0 { 2.0 - offset } else { offset }
+        }
+        _ => offset.clamp(0.0, 1.0),
+    }
+}
+
+fn spread_mode(extend: Extend) -> SpreadMode {
+    match extend {
+        Extend::Repeat => SpreadMode::Repeat,
+        Extend::Reflect => SpreadMode::Reflect,
+        _ => SpreadMode::Pad,
+    }
+}
+
+fn blend_mode(mode: CompositeMode) -> BlendMode {
+    match mode {
+        CompositeMode::Clear => BlendMode::Clear,
+        CompositeMode::Src => BlendMode::Source,
+        CompositeMode::Dest => BlendMode::Destination,
+        CompositeMode::SrcOver => BlendMode::SourceOver,
+        CompositeMode::DestOver => BlendMode::DestinationOver,
+        CompositeMode::SrcIn => BlendMode::SourceIn,
+        CompositeMode::DestIn => BlendMode::DestinationIn,
+        CompositeMode::SrcOut => BlendMode::SourceOut,
+        CompositeMode::DestOut => BlendMode::DestinationOut,
+        CompositeMode::SrcAtop => BlendMode::SourceAtop,
+        CompositeMode::DestAtop => BlendMode::DestinationAtop,
+        CompositeMode::Xor => BlendMode::Xor,
+        CompositeMode::Plus => BlendMode::Plus,
+        CompositeMode::Screen => BlendMode::Screen,
+        CompositeMode::Overlay => BlendMode::Overlay,
+        CompositeMode::Darken => BlendMode::Darken,
+        CompositeMode::Lighten => BlendMode::Lighten,
+        CompositeMode::ColorDodge => BlendMode::ColorDodge,
+        CompositeMode::ColorBurn => BlendMode::ColorBurn,
+        CompositeMode::HardLight => BlendMode::HardLight,
+        CompositeMode::SoftLight => BlendMode::SoftLight,
+        CompositeMode::Difference => BlendMode::Difference,
+        CompositeMode::Exclusion => BlendMode::Exclusion,
+        CompositeMode::Multiply => BlendMode::Multiply,
+        CompositeMode::HslHue => BlendMode::Hue,
+        CompositeMode::HslSaturation => BlendMode::Saturation,
+        CompositeMode::HslColor => BlendMode::Color,
+        CompositeMode::HslLuminosity => BlendMode::Luminosity,
+        _ => BlendMode::SourceOver,
+    }
+}
@@ -0,0 +1,127 @@
+//! Per-thread record of the last failure.
+//!
+//! Exported functions report failure through a null pointer or a
+//! `HarfRustStatus`; the failing call also records the status and a message
+//! here, so the managed wrapper can raise a descriptive exception. Successful
+//! calls leave the record alone, as `errno` does.
+
+use std::cell::RefCell;
+use std::panic::AssertUnwindSafe;
+
+use crate::{HarfRustDirection, HarfRustSoftHyphen, HarfRustStatus};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(HarfRustStatus, String)>> = const { RefCell::new(None) };
+}
+
+/// Records a failure with the given status.
+pub(crate) fn set(status: HarfRustStatus, message: impl Into<String>) {
+    let message = message.into();
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some((status, message)));
+}
+
+/// Records a failure and returns its status.
+pub(crate) fn fail(status: HarfRustStatus, message: impl Into<String>) -> HarfRustStatus {
+    set(status, message);
+    status
+}
+
+/// Records a failure and returns null, for functions returning a handle.
+pub(crate) fn fail_null<T>(status: HarfRustStatus, message: impl Into<String>) -> *mut T {
+    set(status, message);
+    std::ptr::null_mut()
+}
+
+pub(crate) fn clear() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// Status of the last failure, or `Ok` if none was recorded.
+pub(crate) fn status() -> HarfRustStatus {
+    LAST_ERROR.with(|slot| slot.borrow().as_ref().map_or(HarfRustStatus::Ok, |(status, _)| *status))
+}
+
+pub(crate) fn message() -> Option<String> {
+    LAST_ERROR.with(|slot| slot.borrow().as_ref().map(|(_, message)| message.clone()))
+}
+
+/// Value an exported function returns when its body panics.
+pub(crate) trait PanicValue {
+    fn panic_value() -> Self;
+}
+
+impl PanicValue for () {
+    fn panic_value() -> Self {}
+}
+
+impl PanicValue for HarfRustStatus {
+    fn panic_value() -> Self {
+        HarfRustStatus::Panic
+    }
+}
+
+impl PanicValue for HarfRustDirection {
+    fn panic_value() -> Self {
+        HarfRustDirection::Invalid
+    }
+}
+
+impl PanicValue for HarfRustSoftHyphen {
+    fn panic_value() -> Self {
+        HarfRustSoftHyphen::Hidden
+    }
+}
+
+impl PanicValue for i32 {
+    fn panic_value() -> Self {
+        HarfRustStatus::Panic as i32
+    }
+}
+
+impl PanicValue for u32 {
+    fn panic_value() -> Self {
+        0
+    }
+}
+
+impl PanicValue for u64 {
+    fn panic_value() -> Self {
+        0
+    }
+}
+
+impl PanicValue for f32 {
+    fn panic_value() -> Self {
+        0.0
+    }
+}
+
+impl<T> PanicValue for *mut T {
+    fn panic_value() -> Self {
+        std::ptr::null_mut()
+    }
+}
+
+impl<T> PanicValue for *const T {
+    fn panic_value() -> Self {
+        std::ptr::null()
+    }
+}
+
+/// Runs the body of an exported function, so that a panic (from a malformed
+/// font or an internal assertion) is recorded as a `Panic` failure instead
+/// of unwinding into the host, which would abort it.
+pub(crate) fn guard<T: PanicValue>(body: impl FnOnce() -> T) -> T {
+    match std::panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            set(HarfRustStatus::Panic, format!("internal panic: {message}"));
+            T::panic_value()
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Reading order of the text on a line, for text extraction.
+//!
+//! A line is given as runs in the order they sit on the page, left to right,
+//! each a shaping result with the UTF-16 text it was shaped from. Clusters
+//! map glyphs back to text, runs whose clusters decrease are right-to-left,
+//! and the runs are put back into logical order by reversing them by level,
+//! as rule L2 of the Unicode Bidirectional Algorithm does for display (the
+//! reversal is its own inverse).
+
+use crate::bidi::{self, Strong};
+use crate::{glyph_text_ranges, utf16_chars, HarfRustExtractedChar, HarfRustGlyphInfo, HarfRustGlyphPosition};
+
+/// One positioned run of a line.
+pub(crate) struct Run<'a> {
+    pub(crate) infos: &'a [HarfRustGlyphInfo],
+    pub(crate) positions: &'a [HarfRustGlyphPosition],
+    pub(crate) text: &'a [u16],
+    /// Pen position of the first glyph, in line units.
+    pub(crate) x: f32,
+    /// Line units per glyph position unit.
+    pub(crate) scale: f32,
+}
+
+impl Run<'_> {
+    fn is_rtl(&self) -> bool {
+        match (self.infos.first(), self.infos.last()) {
+            (Some(first), Some(last)) if first.cluster != last.cluster => first.cluster > last.cluster,
+            _ => self.strong() == Some(Strong::Right),
+        }
+    }
+
+    fn strong(&self) -> Option<Strong> {
+        bidi::first_strong(utf16_chars(self.text).map(|(ch, _)| ch))
+    }
+
+    /// Characters of the run in logical order.
+    fn chars(&self, run_index: u32, rtl: bool) -> Vec<HarfRustExtractedChar> {
+        let ranges = glyph_text_ranges(self.infos, self.text);
+
+        // (text start, text end, left, right) of each group of consecutive
+        // glyphs sharing a cluster
+        let mut clusters: Vec<(u32, u32, f32, f32)> = Vec::new();
+        let mut pen = self.x;
+        for (i, (info, range)) in self.infos.iter().zip(&ranges).enumerate() {
+            let advance = self.positions[i].x_advance as f32 * self.scale;
+            let same_cluster = i > 0 && self.infos[i - 1].cluster == info.cluster;
+            match clusters.last_mut() {
+                Some(cluster) if same_cluster => {
+                    if range.length > 0 {
+                        cluster.0 = cluster.0.min(range.start);
+                        cluster.1 = cluster.1.max(range.start + range.length);
+                    }
+                    cluster.3 += advance;
+                }
+                _ => clusters.push((range.start, range.start + range.length, pen, pen + advance)),
+            }
+            pen += advance;
+        }
+        clusters.retain(|&(start, end, _, _)| start < end);
+        clusters.sort_by_key(|&(start, _, _, _)| start);
+
+        let mut chars = Vec::new();
+        for (start, end, left, right) in clusters {
+            let text = &self.text[start as usize..end as usize];
+            let count = utf16_chars(text).count();
+            let width = (right - left) / count as f32;
+            for (i, (ch, offset)) in utf16_chars(text).enumerate() {
+                // Ligatures split their box evenly, in the run's direction
+                let x = if rtl { right - (i + 1) as f32 * width } else { left + i as f32 * width };
+                chars.push(HarfRustExtractedChar {
+                    codepoint: ch as u32,
+                    run: run_index,
+                    offset: start + offset,
+                    x,
+                    width,
+                });
+            }
+        }
+        chars
+    }
+}
+
+/// Base direction of a line whose runs are in visual order: right-to-left if
+/// its rightmost strong run is, and its leftmost one is not left-to-right.
+pub(crate) fn detect_base(runs: &[Run]) -> Strong {
+    let mut strong = runs.iter().filter_map(|run| run.strong());
+    match (strong.next(), strong.next_back()) {
+        (Some(Strong::Right), None) | (Some(Strong::Right), Some(Strong::Right)) => Strong::Right,
+        _ => Strong::Left,
+    }
+}
+
+/// Characters of the line in logical order.
+pub(crate) fn logical_chars(runs: &[Run], base: Strong) -> Vec<HarfRustExtractedChar> {
+    let base_level = u8::from(base == Strong::Right);
+    let rtl: Vec<bool> = runs.iter().map(Run::is_rtl).collect();
+    // Right-to-left runs are at level 1, others at the next even level
+    let levels: Vec<u8> = rtl.iter().map(|&rtl| if rtl { 1 } else { base_level * 2 }).collect();
+
+    let mut order: Vec<usize> = (0..runs.len()).collect();
+    let max_level = levels.iter().copied().max().unwrap_or(0);
+    for level in (1..=max_level).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+
+    order
+        .into_iter()
+        .flat_map(|index| runs[index].chars(index as u32, rtl[index]))
+        .collect()
+}
@@ -0,0 +1,59 @@
+//! Shaping without layout tables, for fonts whose GSUB, GPOS or GDEF cannot
+//! be read (common in damaged PDF-embedded fonts).
+//!
+//! Every character maps to its nominal glyph through `cmap` (or .notdef) and
+//! advances by its `hmtx` width, or its vertical advance in vertical runs. No
+//! table beyond those is consulted, so this always produces output.
+
+use harfrust::Direction;
+use skrifa::instance::{LocationRef, Size};
+use skrifa::{FontRef, MetadataProvider};
+
+use crate::vertical::VerticalMetrics;
+use crate::{HarfRustGlyphInfo, HarfRustGlyphPosition};
+
+/// Shapes `text` (characters with their clusters) in the given direction.
+pub(crate) fn shape(
+    font: &FontRef,
+    text: &[(char, u32)],
+    direction: Direction,
+) -> (Vec<HarfRustGlyphInfo>, Vec<HarfRustGlyphPosition>) {
+    let charmap = font.charmap();
+    let glyph_metrics = font.glyph_metrics(Size::unscaled(), LocationRef::default());
+    let vertical = matches!(direction, Direction::TopToBottom | Direction::BottomToTop)
+        .then(|| VerticalMetrics::new(font));
+
+    let mut infos = Vec::with_capacity(text.len());
+    let mut positions = Vec::with_capacity(text.len());
+    for &(ch, cluster) in text {
+        let glyph_id = charmap.map(ch).unwrap_or_default();
+        infos.push(HarfRustGlyphInfo {
+            glyph_id: glyph_id.to_u32(),
+            cluster,
+        });
+        positions.push(match &vertical {
+            // Vertical origins are subtracted as HarfBuzz does; y grows upwards.
+            Some(vertical) => {
+                let metrics = vertical.get(glyph_id);
+                HarfRustGlyphPosition {
+                    x_advance: 0,
+                    y_advance: -metrics.advance.round() as i32,
+                    x_offset: -metrics.origin_x.round() as i32,
+                    y_offset: -metrics.origin_y.round() as i32,
+                }
+            }
+            None => HarfRustGlyphPosition {
+                x_advance: glyph_metrics.advance_width(glyph_id).unwrap_or(0.0).round() as i32,
+                y_advance: 0,
+                x_offset: 0,
+                y_offset: 0,
+            },
+        });
+    }
+
+    if matches!(direction, Direction::RightToLeft | Direction::BottomToTop) {
+        infos.reverse();
+        positions.reverse();
+    }
+    (infos, positions)
+}
@@ -0,0 +1,238 @@
+//! Fast path for short runs that no layout lookup can affect.
+//!
+//! Numbers and short labels make up much of the text in PDFs. A run of at
+//! most [`MAX_LEN`] printable ASCII characters whose glyphs start no GSUB or
+//! GPOS lookup and are not GDEF marks shapes to its nominal glyphs with their
+//! `hmtx` advances, so the result is produced from a per-font table instead
+//! of running the shaping pipeline.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use skrifa::instance::{LocationRef, Size};
+use skrifa::raw::tables::gpos::{PairPos, PositionSubtables, SinglePos};
+use skrifa::raw::tables::gsub::{SingleSubst, SubstitutionSubtables};
+use skrifa::raw::tables::layout::{ChainedSequenceContext, CoverageTable, SequenceContext};
+use skrifa::raw::types::Tag;
+use skrifa::raw::{ReadError, TableProvider};
+use skrifa::{FontRef, MetadataProvider};
+
+use crate::{HarfRustGlyphInfo, HarfRustGlyphPosition};
+
+/// Longest run the fast path handles.
+pub(crate) const MAX_LEN: usize = 8;
+
+const FIRST: char = ' ';
+const LAST: char = '~';
+const COUNT: usize = LAST as usize - FIRST as usize + 1;
+
+/// GDEF glyph class of mark glyphs, whose advances GPOS zeroes.
+const MARK_CLASS: u16 = 3;
+
+/// Tables applied outside GSUB/GPOS lookups, which rule the fast path out.
+const BLOCKING_TABLES: [Tag; 4] = [
+    Tag::new(b"kerx"),
+    Tag::new(b"morx"),
+    Tag::new(b"mort"),
+    Tag::new(b"trak"),
+];
+
+const KERN: Tag = Tag::new(b"kern");
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Glyph and advance of each printable ASCII character that no lookup can
+/// affect.
+pub(crate) struct SimpleGlyphs([Option<(u32, i32)>; COUNT]);
+
+impl SimpleGlyphs {
+    pub(crate) fn new(font: &FontRef) -> Self {
+        let mut glyphs = [None; COUNT];
+        if BLOCKING_TABLES.iter().any(|&tag| font.data_for_tag(tag).is_some())
+            || (font.data_for_tag(KERN).is_some() && !has_gpos_kerning(font))
+        {
+            return Self(glyphs);
+        }
+        // Fonts whose lookups cannot all be read get no fast path.
+        let Ok(coverages) = lookup_coverages(font) else {
+            return Self(glyphs);
+        };
+        let class_def = font.gdef().ok().and_then(|gdef| gdef.glyph_class_def()?.ok());
+
+        let charmap = font.charmap();
+        let metrics = font.glyph_metrics(Size::unscaled(), LocationRef::default());
+        for (slot, ch) in glyphs.iter_mut().zip(FIRST..=LAST) {
+            let glyph_id = charmap.map(ch).unwrap_or_default();
+            let is_mark = class_def.as_ref().is_some_and(|c| c.get(glyph_id) == MARK_CLASS);
+            if is_mark || coverages.iter().any(|coverage| coverage.get(glyph_id).is_some()) {
+                continue;
+            }
+            let advance = metrics.advance_width(glyph_id).unwrap_or(0.0).round() as i32;
+            *slot = Some((glyph_id.to_u32(), advance));
+        }
+        Self(glyphs)
+    }
+
+    /// Shapes a left-to-right run, or returns `None` if it is too long or
+    /// has a character the fast path does not handle.
+    pub(crate) fn shape(
+        &self,
+        text: &[(char, u32)],
+    ) -> Option<(Vec<HarfRustGlyphInfo>, Vec<HarfRustGlyphPosition>)> {
+        if text.len() > MAX_LEN {
+            return None;
+        }
+
+        let mut infos = Vec::with_capacity(text.len());
+        let mut positions = Vec::with_capacity(text.len());
+        for &(ch, cluster) in text {
+            let index = (ch as usize).checked_sub(FIRST as usize)?;
+            let (glyph_id, x_advance) = (*self.0.get(index)?)?;
+            infos.push(HarfRustGlyphInfo { glyph_id, cluster });
+            positions.push(HarfRustGlyphPosition {
+                x_advance,
+                ..Default::default()
+            });
+        }
+        Some((infos, positions))
+    }
+}
+
+/// Whether GPOS has a `kern` feature, in which case shaping ignores the legacy
+/// `kern` table.
+fn has_gpos_kerning(font: &FontRef) -> bool {
+    font.gpos()
+        .and_then(|gpos| gpos.feature_list())
+        .is_ok_and(|list| list.feature_records().iter().any(|record| record.feature_tag() == KERN))
+}
+
+/// Coverage of the glyph each GSUB and GPOS subtable starts matching at:
+/// a glyph outside all of them cannot start a lookup.
+fn lookup_coverages<'a>(font: &FontRef<'a>) -> Result<Vec<CoverageTable<'a>>, ReadError> {
+    let mut coverages = Vec::new();
+
+    if let Ok(gsub) = font.gsub() {
+        for lookup in gsub.lookup_list()?.lookups().iter() {
+            match lookup?.subtables()? {
+                SubstitutionSubtables::Single(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(match subtable? {
+                            SingleSubst::Format1(table) => table.coverage()?,
+                            SingleSubst::Format2(table) => table.coverage()?,
+                        });
+                    }
+                }
+                SubstitutionSubtables::Multiple(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.coverage()?);
+                    }
+                }
+                SubstitutionSubtables::Alternate(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.coverage()?);
+                    }
+                }
+                SubstitutionSubtables::Ligature(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.coverage()?);
+                    }
+                }
+                SubstitutionSubtables::Contextual(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(context_coverage(&subtable?)?);
+                    }
+                }
+                SubstitutionSubtables::ChainContextual(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(chain_context_coverage(&subtable?)?);
+                    }
+                }
+                SubstitutionSubtables::Reverse(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.coverage()?);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Ok(gpos) = font.gpos() {
+        for lookup in gpos.lookup_list()?.lookups().iter() {
+            match lookup?.subtables()? {
+                PositionSubtables::Single(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(match subtable? {
+                            SinglePos::Format1(table) => table.coverage()?,
+                            SinglePos::Format2(table) => table.coverage()?,
+                        });
+                    }
+                }
+                PositionSubtables::Pair(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(match subtable? {
+                            PairPos::Format1(table) => table.coverage()?,
+                            PairPos::Format2(table) => table.coverage()?,
+                        });
+                    }
+                }
+                PositionSubtables::Cursive(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.coverage()?);
+                    }
+                }
+                // Mark attachment starts at the mark.
+                PositionSubtables::MarkToBase(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.mark_coverage()?);
+                    }
+                }
+                PositionSubtables::MarkToLig(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.mark_coverage()?);
+                    }
+                }
+                PositionSubtables::MarkToMark(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(subtable?.mark1_coverage()?);
+                    }
+                }
+                PositionSubtables::Contextual(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(context_coverage(&subtable?)?);
+                    }
+                }
+                PositionSubtables::ChainContextual(subtables) => {
+                    for subtable in subtables.iter() {
+                        coverages.push(chain_context_coverage(&subtable?)?);
+                    }
+                }
+            }
+        }
+    }
+    Ok(coverages)
+}
+
+fn context_coverage<'a>(context: &SequenceContext<'a>) -> Result<CoverageTable<'a>, ReadError> {
+    match context {
+        SequenceContext::Format1(table) => table.coverage(),
+        SequenceContext::Format2(table) => table.coverage(),
+        SequenceContext::Format3(table) => table.coverages().get(0),
+    }
+}
+
+fn chain_context_coverage<'a>(
+    context: &ChainedSequenceContext<'a>,
+) -> Result<CoverageTable<'a>, ReadError> {
+    match context {
+        ChainedSequenceContext::Format1(table) => table.coverage(),
+        ChainedSequenceContext::Format2(table) => table.coverage(),
+        ChainedSequenceContext::Format3(table) => table.input_coverages().get(0),
+    }
+}
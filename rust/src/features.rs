@@ -0,0 +1,189 @@
+//! Default OpenType feature sets.
+//!
+//! Mirrors the features harfrust's shapers turn on without being asked, so
+//! callers can see (and selectively disable) what a run will get.
+//!
+//! harfrust does not expose its shaper selection or feature lists, so the
+//! tables below are a snapshot of harfrust [`SNAPSHOT_VERSION`]
+//! (`hb/ot_shaper.rs`, the `collect_features` of each shaper and the script
+//! tags of `hb/ot_tag.rs`). A test fails when the resolved harfrust version
+//! differs, so they are re-checked on every upgrade. Script directions are
+//! not copied: they come from harfrust itself.
+
+use skrifa::raw::tables::layout::{FeatureList, ScriptList};
+use skrifa::raw::types::Tag;
+use skrifa::raw::{FontRef, TableProvider};
+
+type Tags = &'static [&'static [u8; 4]];
+
+/// harfrust version the tables below were taken from.
+#[cfg(test)]
+pub(crate) const SNAPSHOT_VERSION: &str = "0.5.2";
+
+/// Features every shaper enables, in application order.
+const COMMON: Tags = &[b"rvrn", b"ccmp", b"locl", b"mark", b"mkmk", b"abvm", b"blwm", b"rlig"];
+const HORIZONTAL: Tags = &[b"calt", b"clig", b"curs", b"dist", b"kern", b"liga", b"rclt"];
+const VERTICAL: Tags = &[b"vert"];
+const LEFT_TO_RIGHT: Tags = &[b"ltra", b"ltrm"];
+const RIGHT_TO_LEFT: Tags = &[b"rtla", b"rtlm"];
+
+const ARABIC: Tags = &[b"stch", b"isol", b"fina", b"fin2", b"fin3", b"medi", b"med2", b"init", b"mset"];
+const INDIC: Tags = &[
+    b"nukt", b"akhn", b"rphf", b"rkrf", b"pref", b"blwf", b"abvf", b"half", b"pstf", b"vatu",
+    b"cjct", b"init", b"pres", b"abvs", b"blws", b"psts", b"haln",
+];
+const KHMER: Tags = &[b"pref", b"blwf", b"abvf", b"pstf", b"cfar", b"pres", b"abvs", b"blws", b"psts"];
+const MYANMAR: Tags = &[b"rphf", b"pref", b"blwf", b"pstf", b"pres", b"abvs", b"blws", b"psts"];
+const HANGUL: Tags = &[b"ljmo", b"vjmo", b"tjmo"];
+const UNIVERSAL: Tags = &[
+    b"nukt", b"akhn", b"rphf", b"pref", b"rkrf", b"abvf", b"blwf", b"half", b"pstf", b"vatu",
+    b"cjct", b"isol", b"init", b"medi", b"fina", b"abvs", b"blws", b"haln", b"pres", b"psts",
+];
+
+/// ISO 15924 tags of the scripts harfrust shapes with the Universal Shaping
+/// Engine.
+const UNIVERSAL_SCRIPTS: Tags = &[
+    b"Tibt", b"Mong", b"Sinh", b"Buhd", b"Hano", b"Tglg", b"Tagb", b"Limb", b"Tale", b"Bugi",
+    b"Khar", b"Sylo", b"Tfng", b"Bali", b"Nkoo", b"Phag", b"Cham", b"Kali", b"Lepc", b"Rjng",
+    b"Saur", b"Sund", b"Egyp", b"Java", b"Kthi", b"Mtei", b"Lana", b"Tavt", b"Batk", b"Brah",
+    b"Mand", b"Cakm", b"Plrd", b"Shrd", b"Takr", b"Dupl", b"Gran", b"Khoj", b"Sind", b"Mahj",
+    b"Mani", b"Modi", b"Hmng", b"Phlp", b"Sidd", b"Tirh", b"Ahom", b"Mult", b"Adlm", b"Bhks",
+    b"Marc", b"Newa", b"Gonm", b"Soyo", b"Zanb", b"Dogr", b"Gong", b"Rohg", b"Maka", b"Medf",
+    b"Sogo", b"Sogd", b"Elym", b"Nand", b"Hmnp", b"Wcho", b"Chrs", b"Diak", b"Kits", b"Yezi",
+    b"Cpmn", b"Ougr", b"Tnsa", b"Toto", b"Vith", b"Kawi", b"Nagm", b"Gara", b"Gukh", b"Krai",
+    b"Onao", b"Sunu", b"Todr", b"Tutg", b"Berf", b"Sidt", b"Tayo", b"Tols",
+];
+
+/// ISO 15924 tags that differ from their OpenType script tag by more than case.
+const OT_SCRIPT_TAGS: &[(&[u8; 4], &[&[u8; 4]])] = &[
+    (b"Beng", &[b"bng2", b"beng"]),
+    (b"Deva", &[b"dev2", b"deva"]),
+    (b"Gujr", &[b"gjr2", b"gujr"]),
+    (b"Guru", &[b"gur2", b"guru"]),
+    (b"Knda", &[b"knd2", b"knda"]),
+    (b"Mlym", &[b"mlm2", b"mlym"]),
+    (b"Mymr", &[b"mym2", b"mymr"]),
+    (b"Orya", &[b"ory2", b"orya"]),
+    (b"Taml", &[b"tml2", b"taml"]),
+    (b"Telu", &[b"tel2", b"telu"]),
+    (b"Hira", &[b"kana"]),
+    (b"Laoo", &[b"lao "]),
+    (b"Nkoo", &[b"nko "]),
+    (b"Vaii", &[b"vai "]),
+    (b"Yiii", &[b"yi  "]),
+];
+
+/// Whether harfrust writes `script` (an ISO 15924 tag) right to left.
+pub(crate) fn is_rtl_script(script: Tag) -> bool {
+    let Some(script) = harfrust::Script::from_iso15924_tag(script) else {
+        return false;
+    };
+    // The direction a buffer guesses from its script is the script's own
+    let mut buffer = harfrust::UnicodeBuffer::new();
+    buffer.set_script(script);
+    buffer.guess_segment_properties();
+    buffer.direction() == harfrust::Direction::RightToLeft
+}
+
+/// Features specific to the complex shaper harfrust uses for `script`.
+fn shaper_features(script: Tag) -> Tags {
+    let bytes = script.to_be_bytes();
+    match &bytes {
+        b"Arab" | b"Syrc" => ARABIC,
+        b"Beng" | b"Deva" | b"Gujr" | b"Guru" | b"Knda" | b"Mlym" | b"Orya" | b"Taml" | b"Telu" => {
+            INDIC
+        }
+        b"Khmr" => KHMER,
+        b"Mymr" => MYANMAR,
+        b"Hang" => HANGUL,
+        _ if UNIVERSAL_SCRIPTS.contains(&&bytes) => UNIVERSAL,
+        // Thai, Lao and Hebrew have shapers that add no features
+        _ => &[],
+    }
+}
+
+/// Returns the features the shaper enables by default for a run with the
+/// given ISO 15924 script and direction, in application order.
+pub(crate) fn default_features(script: Tag, vertical: bool, rtl: bool) -> Vec<Tag> {
+    let direction: Tags = if rtl { RIGHT_TO_LEFT } else { LEFT_TO_RIGHT };
+    let layout: Tags = if vertical { VERTICAL } else { HORIZONTAL };
+
+    let mut tags: Vec<Tag> = Vec::new();
+    for tag in [COMMON, direction, shaper_features(script), layout].concat() {
+        let tag = Tag::new(tag);
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// OpenType script tags of an ISO 15924 tag, newest first.
+fn script_tags(script: Tag) -> Vec<Tag> {
+    let bytes = script.to_be_bytes();
+    match OT_SCRIPT_TAGS.iter().find(|(iso, _)| **iso == bytes) {
+        Some((_, ot)) => ot.iter().map(|tag| Tag::new(tag)).collect(),
+        None => vec![Tag::new(&bytes.map(|b| b.to_ascii_lowercase()))],
+    }
+}
+
+/// OpenType script tags to look up for an ISO 15924 tag, most specific first.
+fn ot_script_tags(script: Tag) -> Vec<Tag> {
+    let mut tags = script_tags(script);
+    tags.extend([Tag::new(b"DFLT"), Tag::new(b"dflt"), Tag::new(b"latn")]);
+    tags
+}
+
+/// Feature tags of the default language system of the best-matching script.
+fn lang_sys_features(script_list: ScriptList, feature_list: FeatureList, script: Tag) -> Vec<Tag> {
+    let records = script_list.script_records();
+    let Some(record) = ot_script_tags(script)
+        .into_iter()
+        .find_map(|tag| records.iter().find(|r| r.script_tag() == tag))
+    else {
+        return Vec::new();
+    };
+
+    let Some(Ok(lang_sys)) = record
+        .script(script_list.offset_data())
+        .ok()
+        .and_then(|script| script.default_lang_sys())
+    else {
+        return Vec::new();
+    };
+
+    let features = feature_list.feature_records();
+    let required = Some(lang_sys.required_feature_index()).filter(|&index| index != 0xFFFF);
+    required
+        .into_iter()
+        .chain(lang_sys.feature_indices().iter().map(|index| index.get()))
+        .filter_map(|index| features.get(index as usize).map(|r| r.feature_tag()))
+        .collect()
+}
+
+/// Returns every feature tag the font's GSUB and GPOS tables provide for
+/// `script` (an ISO 15924 tag).
+pub(crate) fn font_features(font: &FontRef, script: Tag) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    if let Ok(gsub) = font.gsub() {
+        if let (Ok(scripts), Ok(features)) = (gsub.script_list(), gsub.feature_list()) {
+            tags.extend(lang_sys_features(scripts, features, script));
+        }
+    }
+    if let Ok(gpos) = font.gpos() {
+        if let (Ok(scripts), Ok(features)) = (gpos.script_list(), gpos.feature_list()) {
+            tags.extend(lang_sys_features(scripts, features, script));
+        }
+    }
+    tags
+}
+
+/// Whether the font's GSUB table has a script record for `script` (an ISO
+/// 15924 tag) itself, rather than only the default script.
+pub(crate) fn gsub_has_script(font: &FontRef, script: Tag) -> bool {
+    let Ok(script_list) = font.gsub().and_then(|gsub| gsub.script_list()) else {
+        return false;
+    };
+    let tags = script_tags(script);
+    script_list.script_records().iter().any(|record| tags.contains(&record.script_tag()))
+}
@@ -0,0 +1,309 @@
+//! Type tags on opaque handles.
+//!
+//! Handles are untyped pointers on the managed side, so a buffer passed where
+//! a font is expected would otherwise be read as a font. Every wrapper struct
+//! starts with a [`TypeTag`] naming its type; with debug assertions, the
+//! `checked` feature or checks enabled through `harfrust_init`, exported
+//! functions compare it before using a handle and fail with `InvalidHandle`
+//! on a mismatch. Freeing a handle clears its tag,
+//! which also catches most uses after free.
+//!
+//! With the `handle-table` feature, handles are not addresses but slot numbers
+//! in a process-wide table, with a generation count per slot. A handle used
+//! after its object was freed, including a second free, then fails with
+//! `StaleHandle` instead of reaching freed memory.
+
+#[cfg(any(test, feature = "handle-table"))]
+use std::collections::HashMap;
+use std::marker::PhantomData;
+#[cfg(not(feature = "handle-table"))]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error;
+use crate::{
+    HarfRustBlob, HarfRustBuffer, HarfRustBufferState, HarfRustColorBitmap, HarfRustFont,
+    HarfRustGlyphBuffer, HarfRustInstance, HarfRustShaper, HarfRustStatus, HarfRustSubsetter,
+};
+
+/// Whether handle addresses are validated regardless of [`set_checked`].
+#[cfg(not(feature = "handle-table"))]
+const ALWAYS_CHECKED: bool = cfg!(any(debug_assertions, feature = "checked"));
+
+/// Whether handle addresses are validated before use. Table handles always
+/// are.
+#[cfg(not(feature = "handle-table"))]
+static CHECKED: AtomicBool = AtomicBool::new(ALWAYS_CHECKED);
+
+/// Enables or disables validation in builds that do not always validate.
+#[cfg_attr(feature = "handle-table", allow(unused_variables))]
+pub(crate) fn set_checked(checked: bool) {
+    #[cfg(not(feature = "handle-table"))]
+    CHECKED.store(ALWAYS_CHECKED || checked, Ordering::Relaxed);
+}
+
+/// An opaque type handed out as a handle. Implementors are `repr(C)` with a
+/// `TypeTag<Self>` as their first field.
+pub(crate) trait Handle {
+    const TAG: u32;
+    /// Name used in error messages, e.g. "glyph buffer".
+    const NAME: &'static str;
+}
+
+/// Tag stored at the start of a handle's memory.
+#[repr(transparent)]
+pub(crate) struct TypeTag<T: Handle>(u32, PhantomData<fn() -> T>);
+
+impl<T: Handle> TypeTag<T> {
+    pub(crate) const fn new() -> Self {
+        Self(T::TAG, PhantomData)
+    }
+}
+
+impl<T: Handle> Drop for TypeTag<T> {
+    fn drop(&mut self) {
+        // Volatile, as the store is dead once the handle is deallocated.
+        unsafe { std::ptr::write_volatile(&mut self.0, 0) };
+    }
+}
+
+impl Handle for HarfRustBuffer {
+    const TAG: u32 = u32::from_be_bytes(*b"HRbf");
+    const NAME: &'static str = "buffer";
+}
+
+impl Handle for HarfRustBufferState {
+    const TAG: u32 = u32::from_be_bytes(*b"HRbs");
+    const NAME: &'static str = "buffer state";
+}
+
+impl Handle for HarfRustFont {
+    const TAG: u32 = u32::from_be_bytes(*b"HRfn");
+    const NAME: &'static str = "font";
+}
+
+impl Handle for HarfRustGlyphBuffer {
+    const TAG: u32 = u32::from_be_bytes(*b"HRgb");
+    const NAME: &'static str = "glyph buffer";
+}
+
+impl Handle for HarfRustColorBitmap {
+    const TAG: u32 = u32::from_be_bytes(*b"HRcb");
+    const NAME: &'static str = "color bitmap";
+}
+
+impl Handle for HarfRustSubsetter {
+    const TAG: u32 = u32::from_be_bytes(*b"HRss");
+    const NAME: &'static str = "subsetter";
+}
+
+impl Handle for HarfRustBlob {
+    const TAG: u32 = u32::from_be_bytes(*b"HRbl");
+    const NAME: &'static str = "blob";
+}
+
+impl Handle for HarfRustShaper {
+    const TAG: u32 = u32::from_be_bytes(*b"HRsh");
+    const NAME: &'static str = "shaper";
+}
+
+impl Handle for HarfRustInstance {
+    const TAG: u32 = u32::from_be_bytes(*b"HRin");
+    const NAME: &'static str = "instance";
+}
+
+/// Tag and name of every handle type, for reporting a handle of the wrong type.
+const HANDLE_TYPES: [(u32, &str); 9] = [
+    (HarfRustBuffer::TAG, HarfRustBuffer::NAME),
+    (HarfRustBufferState::TAG, HarfRustBufferState::NAME),
+    (HarfRustFont::TAG, HarfRustFont::NAME),
+    (HarfRustGlyphBuffer::TAG, HarfRustGlyphBuffer::NAME),
+    (HarfRustColorBitmap::TAG, HarfRustColorBitmap::NAME),
+    (HarfRustSubsetter::TAG, HarfRustSubsetter::NAME),
+    (HarfRustBlob::TAG, HarfRustBlob::NAME),
+    (HarfRustShaper::TAG, HarfRustShaper::NAME),
+    (HarfRustInstance::TAG, HarfRustInstance::NAME),
+];
+
+/// Number of live handles of each type, in `HANDLE_TYPES` order.
+static LIVE: [AtomicUsize; HANDLE_TYPES.len()] = [const { AtomicUsize::new(0) }; HANDLE_TYPES.len()];
+
+fn live_counter<T: Handle>() -> &'static AtomicUsize {
+    let index = HANDLE_TYPES.iter().position(|(tag, _)| *tag == T::TAG);
+    &LIVE[index.expect("handle type is listed")]
+}
+
+/// Number of handles of type `T` handed out and not yet released.
+pub(crate) fn live_count<T: Handle>() -> usize {
+    live_counter::<T>().load(Ordering::Relaxed)
+}
+
+/// Hands a new object to the caller as a handle.
+pub(crate) fn into_handle<T: Handle>(object: Box<T>) -> *mut T {
+    #[cfg(feature = "handle-table")]
+    let handle = registry::insert(object);
+    #[cfg(not(feature = "handle-table"))]
+    let handle = Box::into_raw(object);
+    if !handle.is_null() {
+        live_counter::<T>().fetch_add(1, Ordering::Relaxed);
+    }
+    handle
+}
+
+/// Resolves a handle argument to its object before it is dereferenced,
+/// recording the failure: `NullArgument` if it is null, `InvalidHandle` if
+/// checking is enabled and it does not refer to a `T`, or `StaleHandle` if
+/// its object was freed (handle table only).
+pub(crate) fn resolve<T: Handle>(handle: *const T) -> Result<*mut T, HarfRustStatus> {
+    if handle.is_null() {
+        return Err(error::fail(HarfRustStatus::NullArgument, format!("{} is null", T::NAME)));
+    }
+    #[cfg(feature = "handle-table")]
+    let (tag, object) = registry::get::<T>(handle as usize)?;
+    #[cfg(not(feature = "handle-table"))]
+    let (tag, object) = {
+        if !CHECKED.load(Ordering::Relaxed) {
+            return Ok(handle.cast_mut());
+        }
+        // SAFETY: every handle type starts with its tag, and the caller
+        // vouches for the pointer being readable.
+        (unsafe { handle.cast::<u32>().read() }, handle.cast_mut())
+    };
+
+    if tag == T::TAG {
+        return Ok(object);
+    }
+    let message = match type_name(tag) {
+        Some(name) => format!("expected a {} handle but got a {name}", T::NAME),
+        None => format!("{} handle is invalid or freed", T::NAME),
+    };
+    Err(error::fail(HarfRustStatus::InvalidHandle, message))
+}
+
+/// Like [`resolve`], for a handle argument that may be null.
+pub(crate) fn resolve_optional<T: Handle>(handle: *const T) -> Result<Option<*mut T>, HarfRustStatus> {
+    if handle.is_null() {
+        return Ok(None);
+    }
+    resolve(handle).map(Some)
+}
+
+/// Takes back ownership of the object behind a resolved handle, which becomes
+/// invalid.
+///
+/// # Safety
+///
+/// `object` must come from [`resolve`] and not be used afterwards.
+pub(crate) unsafe fn release<T: Handle>(object: *mut T) -> Box<T> {
+    live_counter::<T>().fetch_sub(1, Ordering::Relaxed);
+    #[cfg(feature = "handle-table")]
+    registry::remove(object as usize);
+    unsafe { Box::from_raw(object) }
+}
+
+fn type_name(tag: u32) -> Option<&'static str> {
+    HANDLE_TYPES.iter().find(|(other, _)| *other == tag).map(|(_, name)| *name)
+}
+
+/// Slots of live objects. A handle is a slot number plus the slot's
+/// generation, which changes whenever the slot is freed, so a handle outlives
+/// its object without ever referring to another one.
+#[cfg(any(test, feature = "handle-table"))]
+pub(crate) struct HandleTable {
+    // (generation, type tag, object address); address 0 marks a free slot
+    slots: Vec<(usize, u32, usize)>,
+    free: Vec<usize>,
+    by_address: HashMap<usize, usize>,
+}
+
+#[cfg(any(test, feature = "handle-table"))]
+impl HandleTable {
+    /// Half the bits of a handle number the slot, the rest count generations.
+    const INDEX_BITS: u32 = usize::BITS / 2;
+    const INDEX_MASK: usize = (1 << Self::INDEX_BITS) - 1;
+    const MAX_GENERATION: usize = usize::MAX >> Self::INDEX_BITS;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            by_address: HashMap::new(),
+        }
+    }
+
+    /// Registers an object, returning its (non-zero) handle, or `None` if
+    /// every slot is in use.
+    pub(crate) fn insert(&mut self, tag: u32, address: usize) -> Option<usize> {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if self.slots.len() <= Self::INDEX_MASK => {
+                self.slots.push((1, 0, 0));
+                self.slots.len() - 1
+            }
+            None => return None,
+        };
+        let slot = &mut self.slots[index];
+        *slot = (slot.0, tag, address);
+        self.by_address.insert(address, index);
+        Some(slot.0 << Self::INDEX_BITS | index)
+    }
+
+    /// Type tag and object address of a handle, or `None` if the handle was
+    /// never issued or its object has been removed.
+    pub(crate) fn get(&self, handle: usize) -> Option<(u32, usize)> {
+        let (generation, tag, address) = *self.slots.get(handle & Self::INDEX_MASK)?;
+        (address != 0 && generation == handle >> Self::INDEX_BITS).then_some((tag, address))
+    }
+
+    /// Unregisters an object, invalidating its handle.
+    pub(crate) fn remove(&mut self, address: usize) {
+        if let Some(index) = self.by_address.remove(&address) {
+            let slot = &mut self.slots[index];
+            // Generations start at 1, so no handle is 0.
+            let generation = if slot.0 == Self::MAX_GENERATION { 1 } else { slot.0 + 1 };
+            *slot = (generation, 0, 0);
+            self.free.push(index);
+        }
+    }
+}
+
+#[cfg(feature = "handle-table")]
+mod registry {
+    use std::sync::{OnceLock, RwLock};
+
+    use super::{Handle, HandleTable};
+    use crate::{error, HarfRustStatus};
+
+    fn table() -> &'static RwLock<HandleTable> {
+        static TABLE: OnceLock<RwLock<HandleTable>> = OnceLock::new();
+        TABLE.get_or_init(|| RwLock::new(HandleTable::new()))
+    }
+
+    pub(super) fn insert<T: Handle>(object: Box<T>) -> *mut T {
+        let address = Box::into_raw(object);
+        let mut table = table().write().unwrap_or_else(|e| e.into_inner());
+        match table.insert(T::TAG, address as usize) {
+            Some(handle) => handle as *mut T,
+            None => {
+                drop(table);
+                drop(unsafe { Box::from_raw(address) });
+                error::fail_null(HarfRustStatus::OutOfRange, "handle table is full")
+            }
+        }
+    }
+
+    pub(super) fn get<T: Handle>(handle: usize) -> Result<(u32, *mut T), HarfRustStatus> {
+        let table = table().read().unwrap_or_else(|e| e.into_inner());
+        match table.get(handle) {
+            Some((tag, address)) => Ok((tag, address as *mut T)),
+            None => Err(error::fail(
+                HarfRustStatus::StaleHandle,
+                format!("{} handle refers to a freed object", T::NAME),
+            )),
+        }
+    }
+
+    pub(super) fn remove(address: usize) {
+        table().write().unwrap_or_else(|e| e.into_inner()).remove(address);
+    }
+}
@@ -0,0 +1,256 @@
+//! Static instances of variable TrueType fonts.
+//!
+//! Every glyph is drawn at the requested location and re-encoded as a simple
+//! `glyf` glyph (composites are flattened and hinting instructions dropped),
+//! with advances taken from the varied metrics. Variation tables are removed
+//! so viewers that don't understand variable fonts see an ordinary font.
+
+use skrifa::instance::{LocationRef, Size};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::raw::types::Tag;
+use skrifa::raw::TableProvider;
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+
+use crate::sfnt::{self, patch, TRUETYPE};
+
+/// Tables dropped from an instance: variation data, and device metrics that
+/// no longer match the outlines.
+const DROPPED_TABLES: &[&[u8; 4]] = &[
+    b"DSIG", b"HVAR", b"LTSH", b"MVAR", b"STAT", b"VDMX", b"VVAR", b"avar", b"cvar", b"fvar",
+    b"gvar", b"hdmx",
+];
+
+/// Simple glyph point flags.
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+
+/// A contour point in font units.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Point {
+    x: i16,
+    y: i16,
+    on_curve: bool,
+}
+
+/// Collects drawn outlines as TrueType contours. Cubic curves (not produced
+/// by `glyf` outlines) are approximated by a single quadratic.
+#[derive(Default)]
+struct ContourPen {
+    contours: Vec<Vec<Point>>,
+}
+
+impl ContourPen {
+    fn push(&mut self, x: f32, y: f32, on_curve: bool) {
+        let point = Point {
+            x: x.round() as i16,
+            y: y.round() as i16,
+            on_curve,
+        };
+        if let Some(contour) = self.contours.last_mut() {
+            contour.push(point);
+        }
+    }
+
+    fn last(&self) -> (f32, f32) {
+        let point = self.contours.last().and_then(|c| c.last());
+        point.map_or((0.0, 0.0), |p| (p.x as f32, p.y as f32))
+    }
+}
+
+impl OutlinePen for ContourPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.contours.push(Vec::new());
+        self.push(x, y, true);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(x, y, true);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        self.push(cx0, cy0, false);
+        self.push(x, y, true);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        let (x0, y0) = self.last();
+        let cx = (3.0 * (cx0 + cx1) - x0 - x) / 4.0;
+        let cy = (3.0 * (cy0 + cy1) - y0 - y) / 4.0;
+        self.quad_to(cx, cy, x, y);
+    }
+
+    fn close(&mut self) {
+        if let Some(contour) = self.contours.last_mut() {
+            if contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
+            }
+        }
+    }
+}
+
+/// Bounding box of a glyph as (xMin, yMin, xMax, yMax).
+type Bounds = (i16, i16, i16, i16);
+
+/// Encodes contours as a simple `glyf` glyph without instructions. Returns
+/// empty data and no bounds for glyphs without points.
+fn encode_simple_glyph(contours: &[Vec<Point>]) -> (Vec<u8>, Option<Bounds>) {
+    let contours: Vec<&Vec<Point>> = contours.iter().filter(|c| !c.is_empty()).collect();
+    let points: Vec<Point> = contours.iter().flat_map(|c| c.iter().copied()).collect();
+    let Some(first) = points.first() else {
+        return (Vec::new(), None);
+    };
+    let bounds = points.iter().fold((first.x, first.y, first.x, first.y), |b, p| {
+        (b.0.min(p.x), b.1.min(p.y), b.2.max(p.x), b.3.max(p.y))
+    });
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&(contours.len() as i16).to_be_bytes());
+    for value in [bounds.0, bounds.1, bounds.2, bounds.3] {
+        data.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut end = 0;
+    for contour in &contours {
+        end += contour.len();
+        data.extend_from_slice(&(end as u16 - 1).to_be_bytes());
+    }
+    data.extend_from_slice(&0u16.to_be_bytes());
+
+    let mut flags = Vec::with_capacity(points.len());
+    let (mut xs, mut ys) = (Vec::new(), Vec::new());
+    let mut previous = (0i32, 0i32);
+    for point in &points {
+        let (dx, dy) = (point.x as i32 - previous.0, point.y as i32 - previous.1);
+        previous = (point.x as i32, point.y as i32);
+        let mut flag = if point.on_curve { ON_CURVE_POINT } else { 0 };
+        flag |= encode_delta(dx, X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE, &mut xs);
+        flag |= encode_delta(dy, Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE, &mut ys);
+        flags.push(flag);
+    }
+    data.extend_from_slice(&flags);
+    data.extend_from_slice(&xs);
+    data.extend_from_slice(&ys);
+    (data, Some(bounds))
+}
+
+/// Appends a coordinate delta in its smallest encoding and returns its flags.
+fn encode_delta(delta: i32, short: u8, same_or_positive: u8, out: &mut Vec<u8>) -> u8 {
+    if delta == 0 {
+        same_or_positive
+    } else if delta.abs() < 256 {
+        out.push(delta.unsigned_abs() as u8);
+        if delta > 0 {
+            short | same_or_positive
+        } else {
+            short
+        }
+    } else {
+        out.extend_from_slice(&(delta as i16).to_be_bytes());
+        0
+    }
+}
+
+/// Builds a static TrueType font from `font` at `location`, setting the OS/2
+/// weight class to `weight` if given. Returns `None` for fonts without `glyf`
+/// outlines.
+pub(crate) fn instantiate(
+    font: &FontRef,
+    location: LocationRef,
+    weight: Option<f32>,
+) -> Option<Vec<u8>> {
+    font.glyf().ok()?;
+    let num_glyphs = font.maxp().ok()?.num_glyphs() as u32;
+    let outlines = font.outline_glyphs();
+    let metrics = font.glyph_metrics(Size::unscaled(), location);
+    let settings = || DrawSettings::unhinted(Size::unscaled(), location);
+
+    let mut glyf = Vec::new();
+    let mut loca = Vec::with_capacity((num_glyphs as usize + 1) * 4);
+    let mut hmtx = Vec::with_capacity(num_glyphs as usize * 4);
+    let mut font_bounds: Option<Bounds> = None;
+    let (mut max_points, mut max_contours, mut max_advance) = (0u16, 0u16, 0u16);
+
+    for gid in (0..num_glyphs).map(GlyphId::new) {
+        let mut pen = ContourPen::default();
+        let adjusted = outlines.get(gid).and_then(|glyph| glyph.draw(settings(), &mut pen).ok());
+        let advance = adjusted
+            .and_then(|m| m.advance_width)
+            .or_else(|| metrics.advance_width(gid))
+            .unwrap_or(0.0)
+            .round()
+            .clamp(0.0, u16::MAX as f32) as u16;
+
+        let (data, bounds) = encode_simple_glyph(&pen.contours);
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+        glyf.extend_from_slice(&data);
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+
+        let lsb = bounds.map_or(0, |b| b.0);
+        hmtx.extend_from_slice(&advance.to_be_bytes());
+        hmtx.extend_from_slice(&lsb.to_be_bytes());
+
+        max_advance = max_advance.max(advance);
+        max_points = max_points.max(pen.contours.iter().map(|c| c.len() as u16).sum());
+        max_contours = max_contours.max(pen.contours.len() as u16);
+        if let Some(b) = bounds {
+            font_bounds = Some(match font_bounds {
+                Some(f) => (f.0.min(b.0), f.1.min(b.1), f.2.max(b.2), f.3.max(b.3)),
+                None => b,
+            });
+        }
+    }
+    loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+    let mut tables: Vec<(Tag, Vec<u8>)> = Vec::new();
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        if DROPPED_TABLES.iter().any(|t| Tag::new(t) == tag) {
+            continue;
+        }
+        let Some(data) = font.data_for_tag(tag).map(|data| data.as_bytes()) else {
+            continue;
+        };
+
+        let data = match &tag.to_be_bytes() {
+            b"glyf" => std::mem::take(&mut glyf),
+            b"loca" => std::mem::take(&mut loca),
+            b"hmtx" => std::mem::take(&mut hmtx),
+            b"head" => {
+                let (x_min, y_min, x_max, y_max) = font_bounds.unwrap_or_default();
+                let bounds: Vec<u8> = [x_min, y_min, x_max, y_max]
+                    .into_iter()
+                    .flat_map(i16::to_be_bytes)
+                    .collect();
+                // Long loca offsets.
+                patch(data, &[(36, &bounds), (50, &1u16.to_be_bytes())])?
+            }
+            b"hhea" => patch(
+                data,
+                &[(10, &max_advance.to_be_bytes()), (34, &(num_glyphs as u16).to_be_bytes())],
+            )?,
+            // Composites are flattened, so no composite limits apply.
+            b"maxp" if data.len() >= 32 => patch(
+                data,
+                &[
+                    (6, &max_points.to_be_bytes()),
+                    (8, &max_contours.to_be_bytes()),
+                    (10, &[0; 4]),
+                    (28, &[0; 4]),
+                ],
+            )?,
+            b"OS/2" => match weight {
+                Some(weight) => {
+                    let weight_class = weight.round().clamp(1.0, 1000.0) as u16;
+                    patch(data, &[(4, &weight_class.to_be_bytes())])?
+                }
+                None => data.to_vec(),
+            },
+            _ => data.to_vec(),
+        };
+        tables.push((tag, data));
+    }
+
+    Some(sfnt::build_sfnt(TRUETYPE, tables))
+}
@@ -3,10 +3,65 @@
 //! This crate provides C-compatible functions that can be called from .NET
 //! via P/Invoke. Objects are exposed as opaque pointers to allow .NET to
 //! manage their lifecycle.
+//!
+//! A live handle is a pointer returned by one of this crate's constructors
+//! (such as `harfrust_font_from_data` or `harfrust_buffer_new`) that has not
+//! yet been passed to the matching `_free` function. Functions that take
+//! handles treat null as an error, and in builds with the `handle-table`
+//! feature also detect freed and foreign pointers; otherwise passing anything
+//! but null or a live handle is undefined behavior.
 
-use std::ffi::CStr;
+use std::ffi::{c_void, CStr};
 use std::os::raw::c_char;
+use std::collections::{BTreeMap, BTreeSet};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+
+use skrifa::instance::{LocationRef, NormalizedCoord, Size};
+use skrifa::metrics::GlyphMetrics;
+use skrifa::raw::types::Tag;
+use skrifa::raw::TableProvider;
+use skrifa::{GlyphId, MetadataProvider};
+
+use handle::TypeTag;
+
+#[cfg(feature = "host-allocator")]
+mod allocator;
+mod bidi;
+mod cache;
+mod case;
+mod cff;
+mod color;
+mod error;
+mod extract;
+mod fallback;
+mod fastpath;
+mod features;
+mod handle;
+mod instance;
+mod metrics;
+mod outline;
+mod repair;
+mod scratch;
+mod scripts;
+mod sfnt;
+mod stats;
+mod subset;
+mod trace;
+mod vertical;
+#[cfg(feature = "woff")]
+mod woff;
+
+/// Exported name of an FFI function: its Rust name behind the symbol prefix
+/// the library was built with (`HARFRUST_SYMBOL_PREFIX`, see build.rs). Every
+/// export uses it instead of `#[no_mangle]`.
+macro_rules! symbol {
+    ($name:literal) => {
+        concat!(env!("HARFRUST_SYMBOL_PREFIX"), $name)
+    };
+}
 
 // =============================================================================
 // FFI-safe structs (repr(C) for direct marshalling)
@@ -76,6 +131,140 @@ impl From<HarfRustDirection> for harfrust::Direction {
     }
 }
 
+/// Result of a fallible function. Functions that return a count, size or
+/// flag instead report failure with these negative values.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required handle or pointer is null.
+    NullArgument = -1,
+    /// An argument is invalid, e.g. a negative length.
+    InvalidArgument = -2,
+    /// A value is outside the range the function accepts.
+    OutOfRange = -3,
+    /// A language tag cannot be parsed.
+    InvalidLanguage = -4,
+    /// Font data cannot be decoded or parsed, or lacks a required table.
+    InvalidFont = -5,
+    /// A file cannot be opened or read.
+    Io = -6,
+    /// Text is not valid UTF-8.
+    InvalidUtf8 = -7,
+    /// An output array is too small; the size needed was reported.
+    InsufficientCapacity = -8,
+    /// An internal error was caught; the last-error message has details.
+    Panic = -9,
+    /// A handle points at an object of another type, or at a freed one.
+    /// Detected only in debug builds or with the `checked` feature.
+    InvalidHandle = -10,
+    /// A handle refers to an object that has been freed. Detected only with
+    /// the `handle-table` feature.
+    StaleHandle = -11,
+    /// The library was built without the feature the function needs, the
+    /// call came too late to take effect, the object it would change is
+    /// frozen (see `harfrust_font_freeze`), or the font data uses a format
+    /// the library cannot handle, such as SVG glyphs.
+    Unsupported = -12,
+}
+
+/// How much work font creation does up front.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustParseMode {
+    /// Parse only what is needed to create the font; tables are read on first use.
+    Lazy = 0,
+    /// Validate the core tables and warm all caches during creation, so
+    /// broken fonts fail immediately and the first shape has no extra cost.
+    Eager = 1,
+    /// Load fonts with recoverable corruption (bad checksums, overlapping or
+    /// truncated tables, unreadable optional tables), reporting each problem
+    /// through the log callback. Only unreadable required tables fail.
+    Tolerant = 2,
+}
+
+/// Severity of a message passed to the log callback.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustLogLevel {
+    Info = 0,
+    Warning = 1,
+    Error = 2,
+}
+
+/// Case transform applied to text as it is added to a buffer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustCaseTransform {
+    /// Text is added unchanged.
+    None = 0,
+    /// Full Unicode uppercase mapping.
+    Uppercase = 1,
+    /// Full Unicode lowercase mapping, including final sigma.
+    Lowercase = 2,
+    /// The first letter of each word is titlecased (CSS `capitalize`).
+    Capitalize = 3,
+}
+
+/// Where a derived font metric came from.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustMetricSource {
+    /// Read directly from the font tables.
+    Table = 0,
+    /// Measured from representative glyph outlines.
+    GlyphOutline = 1,
+    /// Estimated from the em size because nothing better was available.
+    Estimated = 2,
+}
+
+/// Superscript or subscript placement.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustScriptPosition {
+    /// Raised, reduced-size text (e.g. footnote markers).
+    Superscript = 0,
+    /// Lowered, reduced-size text.
+    Subscript = 1,
+}
+
+/// How well a font supports a script, ordered from worst to best.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HarfRustScriptSupport {
+    /// The font maps no character of the script.
+    Unsupported = 0,
+    /// The font maps characters of a script that needs a dedicated shaper,
+    /// but its GSUB has no lookups for it, so text shapes in logical order
+    /// without conjuncts, joining or reordering.
+    CmapOnly = 1,
+    /// The font maps characters of the script and has GSUB lookups for it,
+    /// or the script needs none.
+    Full = 2,
+}
+
+/// How a subset font numbers its glyphs.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustSubsetMode {
+    /// Kept glyphs are renumbered 0..n in original glyph ID order.
+    Compact = 0,
+    /// Glyphs keep their original IDs; unused glyphs are left empty, so
+    /// content that references glyph IDs stays valid.
+    RetainGids = 1,
+}
+
+/// Horizontal alignment of a line, matching the PDF form field quadding (Q)
+/// values.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustTextAlign {
+    Left = 0,
+    Center = 1,
+    Right = 2,
+}
+
 /// OpenType feature for shaping.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -84,12 +273,42 @@ pub struct HarfRustFeature {
     pub tag: u32,
     /// The value of the feature (0 = disabled, 1 = enabled, or other values).
     pub value: u32,
-    /// The start index in the buffer to apply this feature.
+    /// The first cluster value the feature applies to. Cluster values are
+    /// UTF-16 offsets for `harfrust_buffer_add_utf16` and UTF-8 byte offsets for
+    /// `harfrust_buffer_add_str`; see `harfrust_feature_set_range`.
     pub start: u32,
-    /// The end index in the buffer to apply this feature (u32::MAX for end).
+    /// The cluster value one past the last one the feature applies to
+    /// (u32::MAX for end).
     pub end: u32,
 }
 
+/// Unit of the cluster values in a buffer, determined by how text was added.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustClusterUnit {
+    /// UTF-16 code unit offsets (`harfrust_buffer_add_utf16`).
+    Utf16 = 0,
+    /// UTF-8 byte offsets (`harfrust_buffer_add_str`).
+    Utf8 = 1,
+}
+
+/// How a buffer shapes soft hyphens (U+00AD), which mark where a word may be
+/// hyphenated.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HarfRustSoftHyphen {
+    /// Invisible and without advance, wherever they are.
+    #[default]
+    Hidden = 0,
+    /// Shown as a hyphen (U+002D) wherever they are.
+    Visible = 1,
+    /// Shown as a hyphen only at the end of the text, where a line broke at
+    /// it, and invisible elsewhere: how word processors show them. A layout
+    /// engine shaping each line separately gets the hyphen on lines that end
+    /// with one.
+    LineEnd = 2,
+}
+
 /// Font variation settings.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
@@ -100,864 +319,12183 @@ pub struct HarfRustVariation {
     pub value: f32,
 }
 
-// =============================================================================
-// Opaque wrapper types
-// =============================================================================
+/// One run of UTF-16 text to shape in a batch.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HarfRustRunDescriptor {
+    /// Pointer to the UTF-16 text of the run.
+    pub text: *const u16,
+    /// Length of the text in UTF-16 code units.
+    pub len: i32,
+    /// Run direction (`Invalid` to guess from the text).
+    pub direction: HarfRustDirection,
+    /// ISO 15924 script tag (0 to guess from the text).
+    pub script: u32,
+    /// Null-terminated BCP 47 language tag (null to use the default).
+    pub language: *const c_char,
+    /// Features for this run (may be null).
+    pub features: *const HarfRustFeature,
+    /// Number of entries in `features`.
+    pub num_features: u32,
+    /// Nonzero to normalize spaces as `harfrust_buffer_set_normalize_spaces`
+    /// does.
+    pub normalize_spaces: i32,
+}
 
-/// Opaque wrapper around harfrust's UnicodeBuffer.
-pub struct HarfRustBuffer {
-    inner: harfrust::UnicodeBuffer,
+/// A shaped run placed on a line, for `harfrust_line_extract_text`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HarfRustLineRun {
+    /// The shaping result of the run.
+    pub glyphs: *const HarfRustGlyphBuffer,
+    /// The UTF-16 text the run was shaped from; clusters are offsets into it.
+    pub text: *const u16,
+    /// Length of the text in UTF-16 code units.
+    pub len: i32,
+    /// Pen position of the run's first glyph on the line.
+    pub x: f32,
+    /// Line units per glyph position unit, e.g. font size / units per em.
+    pub scale: f32,
 }
 
-/// Internal structure that holds font data and parsed structures.
-/// Uses a two-phase construction to ensure proper lifetimes.
-struct FontInner {
-    data: Pin<Box<[u8]>>,
+/// A character of a line in reading order, with the box it occupies.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HarfRustExtractedChar {
+    /// The Unicode codepoint.
+    pub codepoint: u32,
+    /// Index of the run the character comes from.
+    pub run: u32,
+    /// Offset of the character in its run's text, in UTF-16 code units.
+    pub offset: u32,
+    /// Left edge of the character on the line.
+    pub x: f32,
+    /// Width of the character; a ligature's width is split among its
+    /// characters.
+    pub width: f32,
 }
 
-impl FontInner {
-    fn new(data: Vec<u8>) -> Self {
-        Self {
-            data: Pin::new(data.into_boxed_slice()),
-        }
-    }
-    
-    fn data(&self) -> &[u8] {
-        &self.data
-    }
+/// A single (codepoint, glyph) mapping from the font's cmap.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HarfRustCmapEntry {
+    /// The Unicode codepoint.
+    pub codepoint: u32,
+    /// The glyph ID the codepoint maps to.
+    pub glyph_id: u32,
 }
 
-/// Opaque wrapper that owns font data and provides shaping capabilities.
-pub struct HarfRustFont {
-    font_ref: harfrust::FontRef<'static>,
-    shaper_data: harfrust::ShaperData,
-    _inner: FontInner,
+/// Total advance of a run of consecutive glyphs sharing a cluster.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustClusterAdvance {
+    /// The cluster value shared by the glyphs.
+    pub cluster: u32,
+    /// Index of the first glyph of the cluster in the glyph buffer.
+    pub first_glyph: u32,
+    /// Number of glyphs in the cluster.
+    pub glyph_count: u32,
+    /// Sum of the glyphs' horizontal advances.
+    pub x_advance: i32,
+    /// Sum of the glyphs' vertical advances.
+    pub y_advance: i32,
 }
 
-/// Opaque wrapper around harfrust's GlyphBuffer (shaping result).
-pub struct HarfRustGlyphBuffer {
-    inner: harfrust::GlyphBuffer,
-    // Cache for FFI-safe glyph data
-    infos_cache: Vec<HarfRustGlyphInfo>,
-    positions_cache: Vec<HarfRustGlyphPosition>,
+/// Extra per-glyph transform produced by synthesized layout effects (small
+/// caps, superscripts, rotated glyphs). Applied around the glyph origin after
+/// positioning; advances and offsets already account for it.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HarfRustGlyphTransform {
+    /// Uniform scale factor for the glyph outline (1.0 = unscaled).
+    pub scale: f32,
+    /// Counter-clockwise rotation of the glyph outline in degrees, e.g. -90
+    /// for glyphs set sideways by `harfrust_shape_vertical_mixed`.
+    pub rotation: f32,
 }
 
-fn wrap_glyph_buffer(glyph_buffer: harfrust::GlyphBuffer) -> *mut HarfRustGlyphBuffer {
-    let glyph_infos = glyph_buffer.glyph_infos();
-    let glyph_positions = glyph_buffer.glyph_positions();
+impl HarfRustGlyphTransform {
+    const IDENTITY: Self = Self {
+        scale: 1.0,
+        rotation: 0.0,
+    };
+}
 
-    let mut infos = Vec::with_capacity(glyph_infos.len());
-    for info in glyph_infos {
-        infos.push(HarfRustGlyphInfo {
-            glyph_id: info.glyph_id,
-            cluster: info.cluster,
-        });
-    }
+/// Superscript or subscript size and offset from the OS/2 table, in font units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustScriptMetrics {
+    /// Horizontal font size of the scripted glyphs.
+    pub x_size: i32,
+    /// Vertical font size of the scripted glyphs.
+    pub y_size: i32,
+    /// Horizontal offset of the scripted glyphs.
+    pub x_offset: i32,
+    /// Vertical offset of the scripted glyphs: upwards from the baseline for
+    /// superscripts, downwards for subscripts (as in OS/2).
+    pub y_offset: i32,
+}
 
-    let mut positions = Vec::with_capacity(glyph_positions.len());
-    for pos in glyph_positions {
-        positions.push(HarfRustGlyphPosition {
-            x_advance: pos.x_advance,
-            y_advance: pos.y_advance,
-            x_offset: pos.x_offset,
-            y_offset: pos.y_offset,
-        });
-    }
+/// A range of UTF-16 code units in the source text.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustTextRange {
+    /// Offset of the first code unit.
+    pub start: u32,
+    /// Number of code units (0 if the glyph carries no text of its own).
+    pub length: u32,
+}
 
-    let wrapper = HarfRustGlyphBuffer {
-        inner: glyph_buffer,
-        infos_cache: infos,
-        positions_cache: positions,
-    };
+/// The kind of GSUB substitution that produced a glyph.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustSubstitutionKind {
+    /// The nominal glyph of a character in its cluster.
+    None = 0,
+    Single = 1,
+    Multiple = 2,
+    Alternate = 3,
+    Ligature = 4,
+    ReverseChainSingle = 5,
+    /// Not the output of any GSUB lookup (e.g. a glyph from a shaper
+    /// fallback such as the dotted circle).
+    Unknown = 6,
+}
 
-    Box::into_raw(Box::new(wrapper))
+/// Where a shaped glyph came from; see
+/// `harfrust_glyph_buffer_get_substitution_trace`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HarfRustGlyphTrace {
+    pub kind: HarfRustSubstitutionKind,
+    /// Tag of the feature the lookup belongs to, directly or through a
+    /// contextual lookup, or 0.
+    pub feature: u32,
+    /// Index of the GSUB lookup, or -1.
+    pub lookup_index: i32,
+    /// Position of the glyph in its alternate set for `Alternate`, or -1.
+    pub alternate_index: i32,
+    /// UTF-16 range of the input characters: the glyph's whole cluster, so
+    /// the components of a ligature.
+    pub input_start: u32,
+    pub input_length: u32,
 }
 
-fn create_font(data_vec: Vec<u8>, index: Option<u32>) -> Option<HarfRustFont> {
-    let inner = FontInner::new(data_vec);
-    let data: &'static [u8] = unsafe { std::mem::transmute(inner.data()) };
+/// Font-wide metrics a PDF text extractor checks font descriptors and width
+/// arrays against, in font units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustPdfMetrics {
+    pub units_per_em: i32,
+    /// Typographic ascent (positive, above the baseline).
+    pub ascent: i32,
+    /// Typographic descent (negative, below the baseline).
+    pub descent: i32,
+    /// Most common non-zero glyph advance, the natural CID font DW.
+    pub default_width: i32,
+    /// Advance of the .notdef glyph, the natural MissingWidth.
+    pub missing_width: i32,
+}
 
-    let font_ref = match index {
-        Some(index) => harfrust::FontRef::from_index(data, index).ok()?,
-        None => harfrust::FontRef::new(data).ok()?,
-    };
-    let shaper_data = harfrust::ShaperData::new(&font_ref);
+/// Font-wide metrics for line layout, in font units, as reported by
+/// `harfrust_font_get_metrics`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustFontMetrics {
+    pub units_per_em: i32,
+    /// Typographic ascent (positive, above the baseline).
+    pub ascent: i32,
+    /// Typographic descent (negative, below the baseline).
+    pub descent: i32,
+    /// Extra space between lines, added to ascent minus descent.
+    pub line_gap: i32,
+    /// See `harfrust_font_get_x_height`.
+    pub x_height: i32,
+    /// See `harfrust_font_get_cap_height`.
+    pub cap_height: i32,
+}
 
-    Some(HarfRustFont {
-        font_ref,
-        shaper_data,
-        _inner: inner,
-    })
+/// Style and embedding permissions from the OS/2 table, as reported by
+/// `harfrust_font_get_style`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustFontStyle {
+    /// usWeightClass: 100 (thin) to 900 (black), 400 is regular.
+    pub weight_class: u32,
+    /// usWidthClass: 1 (ultra-condensed) to 9 (ultra-expanded), 5 is normal.
+    pub width_class: u32,
+    /// fsSelection bits, e.g. 0x01 italic, 0x20 bold, 0x40 regular and
+    /// 0x200 oblique.
+    pub fs_selection: u32,
+    /// fsType embedding permission bits: 0x02 restricted license, 0x04
+    /// preview and print, 0x08 editable, 0x100 no subsetting and 0x200
+    /// bitmap embedding only. 0 is installable, the most permissive.
+    pub fs_type: u32,
+    /// 1 if fsType allows embedding the outlines in a document.
+    pub embeddable: u32,
+    /// 1 if fsType allows embedding a subset rather than the whole font.
+    pub subsettable: u32,
 }
 
-// =============================================================================
-// Buffer functions
-// =============================================================================
+/// Values from the `post` table, as reported by `harfrust_font_post_info`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HarfRustPostInfo {
+    /// Degrees counter-clockwise from vertical; negative for fonts leaning
+    /// right, as PDF ItalicAngle.
+    pub italic_angle: f32,
+    /// Top of the underline in font units, negative below the baseline.
+    pub underline_position: i32,
+    /// Underline thickness in font units.
+    pub underline_thickness: i32,
+    /// 1 if the font is monospaced (PDF FixedPitch flag).
+    pub is_fixed_pitch: u32,
+}
 
-/// Creates a new empty buffer for text shaping.
-#[no_mangle]
-pub extern "C" fn harfrust_buffer_new() -> *mut HarfRustBuffer {
-    let buffer = HarfRustBuffer {
-        inner: harfrust::UnicodeBuffer::new(),
-    };
-    Box::into_raw(Box::new(buffer))
+/// Values from the `head` table, as reported by `harfrust_font_head_info`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustHeadInfo {
+    /// Bounding box of all glyphs in font units (PDF FontBBox once scaled
+    /// to 1000 units per em).
+    pub x_min: i32,
+    pub y_min: i32,
+    pub x_max: i32,
+    pub y_max: i32,
+    /// head flags bits, e.g. 0x01 baseline at y=0 and 0x02 left sidebearing
+    /// at x=0.
+    pub flags: u32,
+    /// macStyle bits: 0x01 bold, 0x02 italic, 0x04 underline, 0x08 outline,
+    /// 0x10 shadow, 0x20 condensed and 0x40 extended.
+    pub mac_style: u32,
 }
 
-/// Adds a UTF-8 string to the buffer.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_add_str(
-    buffer: *mut HarfRustBuffer,
-    text: *const c_char,
-) -> i32 {
-    if buffer.is_null() {
-        return -1;
-    }
-    if text.is_null() {
-        return -2;
-    }
+/// Strikeout line from the OS/2 table, in font units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustStrikeout {
+    /// Top of the strikeout line, above the baseline.
+    pub position: i32,
+    /// Strikeout thickness.
+    pub thickness: i32,
+}
 
-    let c_str = unsafe { CStr::from_ptr(text) };
-    let rust_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return -3,
-    };
+/// Advance a glyph is given after shaping, see
+/// `harfrust_font_set_advance_overrides`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HarfRustAdvanceOverride {
+    pub glyph_id: u32,
+    /// Horizontal advance in font units.
+    pub advance: i32,
+}
 
-    let buffer_ref = unsafe { &mut *buffer };
-    buffer_ref.inner.push_str(rust_str);
+/// Path construction operator, matching the PDF path operators m, l, c and h.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustPathVerb {
+    /// Begins a new subpath at (x, y).
+    MoveTo = 0,
+    /// Straight line to (x, y).
+    LineTo = 1,
+    /// Cubic Bézier curve to (x, y) with control points (x1, y1) and (x2, y2).
+    CubicTo = 2,
+    /// Closes the current subpath; (x, y) is the current point.
+    Close = 3,
+}
 
-    0
+/// One glyph outline path command. Unused control points are zero.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HarfRustPathCommand {
+    /// The operator.
+    pub verb: HarfRustPathVerb,
+    /// First control point (`CubicTo` only).
+    pub x1: f32,
+    pub y1: f32,
+    /// Second control point (`CubicTo` only).
+    pub x2: f32,
+    pub y2: f32,
+    /// End point.
+    pub x: f32,
+    pub y: f32,
 }
 
-/// Adds a UTF-16 string to the buffer.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_add_utf16(
-    buffer: *mut HarfRustBuffer,
-    text: *const u16,
-    len: i32,
-) -> i32 {
-    if buffer.is_null() {
-        return -1;
-    }
-    if text.is_null() || len < 0 {
-        return -2;
-    }
+/// Placement of a single line of text in a rectangle, in the rectangle's
+/// units (e.g. points).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HarfRustLineLayout {
+    /// Font size the line was shaped at.
+    pub font_size: f32,
+    /// Pen position of the first glyph, from the rectangle's lower-left corner.
+    pub x: f32,
+    /// Baseline, from the rectangle's lower-left corner.
+    pub y: f32,
+    /// Total advance of the line.
+    pub width: f32,
+}
 
-    let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
-    let buffer_ref = unsafe { &mut *buffer };
-    
-    let mut cluster = 0;
-    for c in std::char::decode_utf16(slice.iter().cloned()) {
-        let ch = match c {
-            Ok(ch) => ch,
-            Err(_) => std::char::REPLACEMENT_CHARACTER,
-        };
-        
-        let char_len = ch.len_utf16() as u32;
-        // harfrust::UnicodeBuffer should have `add` method taking (char, cluster)
-        buffer_ref.inner.add(ch, cluster);
-        cluster += char_len;
-    }
+/// A shaped run of a multi-run line, for `harfrust_layout_line`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HarfRustLayoutRun {
+    /// The font the run was shaped with.
+    pub font: *const HarfRustFont,
+    /// The shaping result of the run, with positions in font units.
+    pub glyphs: *const HarfRustGlyphBuffer,
+    /// Font size of the run, in line units (e.g. points).
+    pub font_size: f32,
+    /// Baseline shift in line units, positive upwards, as the PDF text rise
+    /// (Ts): raises superscript runs and lowers subscript runs.
+    pub rise: f32,
+}
 
-    0
+/// Where a run of a multi-run line goes, relative to the line's origin on
+/// its baseline, in line units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HarfRustRunPlacement {
+    /// Pen position of the run's first glyph.
+    pub x: f32,
+    /// Baseline of the run, positive upwards (the run's rise).
+    pub y: f32,
 }
 
-/// Returns the number of characters currently in the buffer.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_len(buffer: *const HarfRustBuffer) -> i32 {
-    if buffer.is_null() {
-        return -1;
-    }
+/// Extent of a multi-run line in line units, with each run's ascent and
+/// descent moved by its rise.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HarfRustLineMetrics {
+    /// Total advance of the line.
+    pub width: f32,
+    /// Highest ascent above the line's baseline.
+    pub ascent: f32,
+    /// Lowest descent, negative below the line's baseline.
+    pub descent: f32,
+    /// Largest line gap of the line's fonts.
+    pub line_gap: f32,
+}
 
-    let buffer_ref = unsafe { &*buffer };
-    buffer_ref.inner.len() as i32
+/// Vertical writing-mode metrics of a glyph in PDF glyph space (1000 units
+/// per em), as written to the W2 array of a vertical CID font.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HarfRustVerticalMetrics {
+    /// Vertical displacement; negative, since vertical text runs downwards.
+    pub w1y: f32,
+    /// Position vector from the horizontal origin to the vertical origin.
+    pub vx: f32,
+    pub vy: f32,
 }
 
-/// Clears all content from the buffer, preparing it for reuse.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_clear(buffer: *mut HarfRustBuffer) {
-    if buffer.is_null() {
-        return;
-    }
+/// Ink bounding box of a glyph in font units, laid out as HarfBuzz's
+/// `hb_glyph_extents_t`: y grows upwards, so `height` is negative.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustGlyphExtents {
+    /// Left edge, relative to the glyph origin.
+    pub x_bearing: i32,
+    /// Top edge, relative to the baseline.
+    pub y_bearing: i32,
+    pub width: i32,
+    /// Distance from the top edge to the bottom edge.
+    pub height: i32,
+}
 
-    let buffer_ref = unsafe { &mut *buffer };
-    buffer_ref.inner.clear();
+/// Layout of a rendered color glyph bitmap.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HarfRustBitmapInfo {
+    /// Width of the bitmap in pixels.
+    pub width: u32,
+    /// Height of the bitmap in pixels.
+    pub height: u32,
+    /// Distance from the pen position to the left edge of the bitmap, in pixels.
+    pub left: i32,
+    /// Distance from the baseline up to the top edge of the bitmap, in pixels.
+    pub top: i32,
+    /// Number of bytes per row (always `width * 4`).
+    pub stride: u32,
 }
 
-/// Frees a buffer previously created by `harfrust_buffer_new`.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_free(buffer: *mut HarfRustBuffer) {
-    if !buffer.is_null() {
-        unsafe { drop(Box::from_raw(buffer)) };
+/// Replacement for one palette entry when rendering color glyphs, e.g. to
+/// adapt icons to a dark theme.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HarfRustColorOverride {
+    /// CPAL palette entry index, or 0xFFFF for the text foreground color
+    /// (black unless overridden).
+    pub index: u16,
+    /// Color components, not premultiplied.
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+/// 128-bit cache key, split into two halves.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HarfRustCacheKey {
+    /// Upper 64 bits of the key.
+    pub high: u64,
+    /// Lower 64 bits of the key.
+    pub low: u64,
+}
+
+impl HarfRustCacheKey {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let hash = content_hash_128(bytes);
+        Self {
+            high: (hash >> 64) as u64,
+            low: hash as u64,
+        }
     }
 }
 
 // =============================================================================
-// Buffer configuration functions (Phase 2)
+// Opaque wrapper types
 // =============================================================================
 
-/// Sets the text direction of the buffer.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_set_direction(
-    buffer: *mut HarfRustBuffer,
-    direction: HarfRustDirection,
-) {
-    if buffer.is_null() {
-        return;
-    }
+// Each wrapper starts with a type tag (see `handle`). Its repr(C) is applied
+// through `cfg_attr` so that csbindgen, which looks for a plain `repr`
+// attribute, still generates opaque C# structs.
 
-    let buffer_ref = unsafe { &mut *buffer };
-    buffer_ref.inner.set_direction(direction.into());
+/// Characters of pre- and post-context that shaping looks at.
+const CONTEXT_LENGTH: usize = 5;
+
+/// Opaque wrapper around harfrust's UnicodeBuffer.
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustBuffer {
+    tag: TypeTag<Self>,
+    inner: harfrust::UnicodeBuffer,
+    // Characters and clusters added so far; UnicodeBuffer does not expose its
+    // contents, so they are mirrored here for snapshots.
+    text: Vec<(char, u32)>,
+    // Text before and after the buffer's (see `harfrust_buffer_set_context`)
+    context: [String; 2],
+    // Applied to `inner` as text is added; `text` keeps the soft hyphens
+    soft_hyphen: HarfRustSoftHyphen,
+    // Applied when shaping, as it depends on the font
+    normalize_spaces: bool,
 }
 
-/// Gets the text direction of the buffer.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_get_direction(
-    buffer: *const HarfRustBuffer,
-) -> HarfRustDirection {
-    if buffer.is_null() {
-        return HarfRustDirection::Invalid;
-    }
+const SOFT_HYPHEN: char = '\u{AD}';
 
-    let buffer_ref = unsafe { &*buffer };
-    buffer_ref.inner.direction().into()
-}
+/// No-break, fixed-width and narrow spaces, which subset fonts often lack.
+const NORMALIZED_SPACES: [char; 14] = [
+    '\u{A0}', '\u{2000}', '\u{2001}', '\u{2002}', '\u{2003}', '\u{2004}', '\u{2005}', '\u{2006}', '\u{2007}',
+    '\u{2008}', '\u{2009}', '\u{200A}', '\u{202F}', '\u{205F}',
+];
 
-/// Sets the script of the buffer using an ISO 15924 tag (4 bytes as u32).
-/// Example: "Latn" = 0x4C61746E
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_set_script(buffer: *mut HarfRustBuffer, script_tag: u32) {
-    if buffer.is_null() {
-        return;
+impl HarfRustBuffer {
+    fn new(inner: harfrust::UnicodeBuffer) -> Self {
+        Self {
+            tag: TypeTag::new(),
+            inner,
+            text: Vec::new(),
+            context: Default::default(),
+            soft_hyphen: HarfRustSoftHyphen::Hidden,
+            normalize_spaces: false,
+        }
     }
 
-    let buffer_ref = unsafe { &mut *buffer };
-    let tag = harfrust::Tag::new(&script_tag.to_be_bytes());
-    if let Some(script) = harfrust::Script::from_iso15924_tag(tag) {
-        buffer_ref.inner.set_script(script);
+    fn add(&mut self, ch: char, cluster: u32) {
+        let ends_with_soft_hyphen = self.text.last().is_some_and(|&(last, _)| last == SOFT_HYPHEN);
+        self.text.push((ch, cluster));
+        if ends_with_soft_hyphen && self.soft_hyphen == HarfRustSoftHyphen::LineEnd {
+            // The soft hyphen shown at the end is now inside the text
+            self.rebuild();
+            return;
+        }
+        self.inner.add(self.shaped_char(self.text.len() - 1), cluster);
+        // Adding text drops the post-context, which belongs after all of it
+        if !self.context[1].is_empty() {
+            self.inner.set_post_context(&self.context[1]);
+        }
     }
-}
 
-/// Gets the script of the buffer as an ISO 15924 tag (4 bytes as u32).
-/// Returns 0 if no script is set.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_get_script(buffer: *const HarfRustBuffer) -> u32 {
-    if buffer.is_null() {
-        return 0;
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.text.clear();
+        self.context = Default::default();
     }
 
-    let buffer_ref = unsafe { &*buffer };
-    let tag = buffer_ref.inner.script().tag();
-    u32::from_be_bytes(tag.into_bytes())
-}
+    /// The character shaped for the one at `index`, after the soft hyphen
+    /// policy.
+    fn shaped_char(&self, index: usize) -> char {
+        let ch = self.text[index].0;
+        let visible = match self.soft_hyphen {
+            HarfRustSoftHyphen::Hidden => false,
+            HarfRustSoftHyphen::Visible => true,
+            HarfRustSoftHyphen::LineEnd => index + 1 == self.text.len(),
+        };
+        if ch == SOFT_HYPHEN && visible {
+            '-'
+        } else {
+            ch
+        }
+    }
 
-/// Sets the language of the buffer from a BCP 47 language tag string.
-/// Example: "en", "en-US", "zh-Hans"
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_set_language(
-    buffer: *mut HarfRustBuffer,
-    language: *const c_char,
-) -> i32 {
-    if buffer.is_null() {
-        return -1;
+    /// The text as shaped, after the soft hyphen policy.
+    fn shaped_text(&self) -> Cow<'_, [(char, u32)]> {
+        let shown = (0..self.text.len()).any(|i| self.shaped_char(i) != self.text[i].0);
+        if !shown {
+            return Cow::Borrowed(&self.text);
+        }
+        (0..self.text.len()).map(|i| (self.shaped_char(i), self.text[i].1)).collect()
     }
-    if language.is_null() {
-        return -2;
+
+    /// Replaces the spaces in `NORMALIZED_SPACES` that the font has no glyph
+    /// for with U+0020, if enabled and the font has one. Returns whether any
+    /// were replaced.
+    fn normalize_spaces(&mut self, font: &HarfRustFont) -> bool {
+        if !self.normalize_spaces {
+            return false;
+        }
+        let mut replaced = false;
+        for (ch, _) in &mut self.text {
+            let normalized = font.normalize_space(*ch);
+            replaced |= normalized != *ch;
+            *ch = normalized;
+        }
+        if replaced {
+            self.rebuild();
+        }
+        replaced
     }
 
-    let c_str = unsafe { CStr::from_ptr(language) };
-    let lang_str = match c_str.to_str() {
-        Ok(s) => s,
-        Err(_) => return -3,
-    };
+    /// The text as shaped with `font`, without changing the buffer: the
+    /// shaped text with spaces normalized as `normalize_spaces` would. Also
+    /// returns whether any were replaced.
+    fn text_for(&self, font: &HarfRustFont) -> (Cow<'_, [(char, u32)]>, bool) {
+        let text = self.shaped_text();
+        if !self.normalize_spaces || text.iter().all(|&(ch, _)| font.normalize_space(ch) == ch) {
+            return (text, false);
+        }
+        (text.iter().map(|&(ch, cluster)| (font.normalize_space(ch), cluster)).collect(), true)
+    }
 
-    let buffer_ref = unsafe { &mut *buffer };
-    if let Ok(lang) = lang_str.parse::<harfrust::Language>() {
-        buffer_ref.inner.set_language(lang);
-        0
-    } else {
-        -4
+    /// Refills the unicode buffer from `text`, e.g. after the soft hyphen
+    /// policy changed.
+    fn rebuild(&mut self) {
+        let inner = self.to_unicode_buffer();
+        scratch::recycle(std::mem::replace(&mut self.inner, inner));
     }
-}
 
-/// Guesses and sets the segment properties (direction, script, language)
-/// based on the buffer contents.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_buffer_guess_segment_properties(buffer: *mut HarfRustBuffer) {
-    if buffer.is_null() {
-        return;
+    /// Builds an independent unicode buffer with the same text and properties.
+    fn to_unicode_buffer(&self) -> harfrust::UnicodeBuffer {
+        self.unicode_buffer_with(&self.shaped_text())
     }
 
-    let buffer_ref = unsafe { &mut *buffer };
-    buffer_ref.inner.guess_segment_properties();
+    /// Builds an independent unicode buffer with the same properties and
+    /// context holding `text`, e.g. the result of `text_for`.
+    fn unicode_buffer_with(&self, text: &[(char, u32)]) -> harfrust::UnicodeBuffer {
+        let mut out = empty_buffer_like(&self.inner);
+        out.reserve(text.len());
+        for &(ch, cluster) in text {
+            out.add(ch, cluster);
+        }
+        out.set_pre_context(&self.context[0]);
+        out.set_post_context(&self.context[1]);
+        out
+    }
 }
 
-// =============================================================================
-// Font functions
-// =============================================================================
-
-/// Creates a font from raw font data (TTF/OTF bytes).
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_font_from_data(data: *const u8, len: i32) -> *mut HarfRustFont {
-    if data.is_null() || len <= 0 {
-        return std::ptr::null_mut();
-    }
+/// Saved buffer contents and segment properties.
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustBufferState {
+    tag: TypeTag<Self>,
+    text: Vec<(char, u32)>,
+    direction: harfrust::Direction,
+    script: harfrust::Script,
+    language: Option<harfrust::Language>,
+    flags: harfrust::BufferFlags,
+    cluster_level: harfrust::BufferClusterLevel,
+    context: [String; 2],
+}
 
-    let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
-    let data_vec = slice.to_vec();
+/// Native memory and handles held by the library, as reported by
+/// `harfrust_memory_stats`. Byte counts cover the large allocations only.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustMemoryStats {
+    /// Font data copied into the library; data shared by several fonts is
+    /// counted once. Caller-owned data (`harfrust_font_from_data_borrowed`)
+    /// is not included.
+    pub font_data_bytes: u64,
+    /// Font files mapped into memory by `harfrust_font_from_file`.
+    pub mapped_font_bytes: u64,
+    /// Per-font glyph advance tables.
+    pub advance_cache_bytes: u64,
+    /// Shaping result cache (see `harfrust_cache_configure`).
+    pub result_cache_bytes: u64,
+    pub result_cache_entries: u32,
+    /// Shape plans cached across all fonts.
+    pub shape_plans: u32,
+    /// Shaping buffers kept for reuse across all threads (`arena` feature).
+    pub pooled_buffers: u32,
+    /// Live handles by type.
+    pub fonts: u32,
+    pub buffers: u32,
+    pub buffer_states: u32,
+    pub glyph_buffers: u32,
+    pub color_bitmaps: u32,
+    pub subsetters: u32,
+    pub blobs: u32,
+    pub shapers: u32,
+    pub instances: u32,
+}
 
-    match create_font(data_vec, None) {
-        Some(wrapper) => Box::into_raw(Box::new(wrapper)),
-        None => std::ptr::null_mut(),
-    }
+/// Text quality counts of one shaping result, as reported by
+/// `harfrust_glyph_buffer_get_summary`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HarfRustShapeSummary {
+    pub glyph_count: u32,
+    /// Glyphs the font has no mapping for (glyph 0, .notdef).
+    pub notdef_count: u32,
+    /// Dotted circles (U+25CC) shown as the base of a mark that has none.
+    /// Dotted circles in the source text are included.
+    pub dotted_circle_count: u32,
+    /// 1 if the result was produced without layout tables; see
+    /// `harfrust_glyph_buffer_is_degraded`.
+    pub degraded: u32,
 }
 
-/// Creates a font from raw font data at a specific index (for font collections).
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_font_from_data_index(
-    data: *const u8,
-    len: i32,
-    index: u32,
-) -> *mut HarfRustFont {
-    if data.is_null() || len <= 0 {
-        return std::ptr::null_mut();
-    }
+/// Bits returned by `harfrust_glyph_buffer_get_flags`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HarfRustResultFlags {
+    /// Produced by fallback shaping, without layout tables.
+    Degraded = 1,
+    /// Spaces the font has no glyph for were shaped as U+0020; see
+    /// `harfrust_buffer_set_normalize_spaces`.
+    SpacesNormalized = 2,
+}
 
-    let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
-    let data_vec = slice.to_vec();
+/// Library-wide settings passed to `harfrust_init`. Zeroed fields take their
+/// defaults.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HarfRustConfig {
+    /// Size of the struct in bytes as the caller knows it, so fields added in
+    /// later versions are left at their defaults for older callers.
+    pub size: u32,
+    /// Limits of the shaping result cache, as for `harfrust_cache_configure`;
+    /// 0 disables the cache.
+    pub result_cache_max_entries: u32,
+    pub result_cache_max_bytes: u64,
+    /// Shape plans kept per font; 0 for the default of 32.
+    pub plan_cache_capacity: u32,
+    /// Threads `harfrust_shape_batch_parallel` uses when passed 0; 0 for one
+    /// per available core. Larger values are clamped to the core count.
+    pub num_threads: u32,
+    /// Non-zero validates handles in release builds too (see the `checked`
+    /// feature). Debug builds always do.
+    pub check_handles: i32,
+    /// Least severe message passed to the log callback.
+    pub log_level: HarfRustLogLevel,
+}
 
-    match create_font(data_vec, Some(index)) {
-        Some(wrapper) => Box::into_raw(Box::new(wrapper)),
-        None => std::ptr::null_mut(),
+impl Default for HarfRustConfig {
+    fn default() -> Self {
+        Self {
+            size: std::mem::size_of::<Self>() as u32,
+            result_cache_max_entries: 0,
+            result_cache_max_bytes: 0,
+            plan_cache_capacity: 0,
+            num_threads: 0,
+            check_handles: 0,
+            log_level: HarfRustLogLevel::Info,
+        }
     }
 }
 
-/// Returns the font's units per em.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_font_units_per_em(font: *const HarfRustFont) -> i32 {
-    if font.is_null() {
-        return -1;
-    }
+/// Callback receiving diagnostic messages as NUL-terminated UTF-8. The
+/// message is only valid for the duration of the call, which may happen on
+/// any thread.
+pub type HarfRustLogCallback = Option<
+    unsafe extern "C" fn(level: HarfRustLogLevel, message: *const c_char, user_data: *mut c_void),
+>;
 
-    let font_wrapper = unsafe { &*font };
-    let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
-    shaper.units_per_em()
-}
+/// The installed log callback and its user data (as an address, so the pair
+/// can live in a static).
+static LOGGER: Mutex<Option<(HarfRustLogCallback, usize)>> = Mutex::new(None);
 
-/// Frees a font previously created by `harfrust_font_from_data`.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_font_free(font: *mut HarfRustFont) {
-    if !font.is_null() {
-        unsafe { drop(Box::from_raw(font)) };
+/// Least severe level passed to the log callback.
+static LOG_LEVEL: AtomicI32 = AtomicI32::new(HarfRustLogLevel::Info as i32);
+
+/// Passes a message to the log callback, if one is installed.
+fn log(level: HarfRustLogLevel, message: &str) {
+    if (level as i32) < LOG_LEVEL.load(Ordering::Relaxed) {
+        return;
+    }
+    let logger = *LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((Some(callback), user_data)) = logger {
+        let message = std::ffi::CString::new(message.replace('\0', "")).unwrap_or_default();
+        unsafe { callback(level, message.as_ptr(), user_data as *mut c_void) };
     }
 }
 
-// =============================================================================
-// Shape function
-// =============================================================================
-
-/// Shapes text in a buffer using the given font.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_shape(
-    font: *const HarfRustFont,
-    buffer: *mut HarfRustBuffer,
-) -> *mut HarfRustGlyphBuffer {
-    if font.is_null() || buffer.is_null() {
-        return std::ptr::null_mut();
-    }
+/// Allocates `size` bytes aligned to `align` (a power of two), returning
+/// null on failure.
+pub type HarfRustAllocFn =
+    Option<unsafe extern "C" fn(size: usize, align: usize, user_data: *mut c_void) -> *mut c_void>;
 
-    let font_wrapper = unsafe { &*font };
-    let mut buffer_box = unsafe { Box::from_raw(buffer) };
-    let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+/// Frees memory returned by the allocation callback, with the size and
+/// alignment it was requested with.
+pub type HarfRustFreeFn =
+    Option<unsafe extern "C" fn(ptr: *mut c_void, size: usize, align: usize, user_data: *mut c_void)>;
 
-    // Guess segment properties only if direction is not explicitly set
-    if buffer_box.inner.direction() == harfrust::Direction::Invalid {
-        buffer_box.inner.guess_segment_properties();
-    }
+/// Resizes an allocation to `new_size` bytes with the same alignment, keeping
+/// its contents, and returns null (leaving it untouched) on failure.
+pub type HarfRustReallocFn = Option<
+    unsafe extern "C" fn(
+        ptr: *mut c_void,
+        size: usize,
+        align: usize,
+        new_size: usize,
+        user_data: *mut c_void,
+    ) -> *mut c_void,
+>;
 
-    // Perform shaping
-    let glyph_buffer = shaper.shape(buffer_box.inner, &[]);
+/// Callback that releases caller-owned font data once the font is freed.
+pub type HarfRustReleaseCallback = Option<unsafe extern "C" fn(user_data: *mut c_void)>;
 
-    wrap_glyph_buffer(glyph_buffer)
+/// Internal structure that holds font data and parsed structures.
+/// Uses a two-phase construction to ensure proper lifetimes.
+enum FontInner {
+    Owned(Pin<Box<[u8]>>),
+    #[cfg(not(target_family = "wasm"))]
+    Mapped(memmap2::Mmap),
+    /// Caller-owned memory that must stay valid and unmoved until `release`
+    /// is invoked on drop.
+    Borrowed {
+        data: *const u8,
+        len: usize,
+        release: HarfRustReleaseCallback,
+        user_data: *mut c_void,
+    },
 }
 
-/// Shapes text in a buffer using the given font and OpenType features.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_shape_with_features(
-    font: *const HarfRustFont,
-    buffer: *mut HarfRustBuffer,
-    features: *const HarfRustFeature,
-    num_features: u32,
-) -> *mut HarfRustGlyphBuffer {
-    if font.is_null() || buffer.is_null() {
-        return std::ptr::null_mut();
+// SAFETY: borrowed data is only read, and the caller guarantees it stays valid
+// until the release callback runs.
+unsafe impl Send for FontInner {}
+unsafe impl Sync for FontInner {}
+
+impl FontInner {
+    fn new(data: Vec<u8>) -> Self {
+        stats::add(&stats::FONT_DATA_BYTES, data.len());
+        Self::Owned(Pin::new(data.into_boxed_slice()))
     }
 
-    let font_wrapper = unsafe { &*font };
-    let mut buffer_box = unsafe { Box::from_raw(buffer) };
-    let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+    /// Maps the file at `path` read-only, or reads it where mapping is not
+    /// available.
+    fn from_file(path: &str) -> std::io::Result<Self> {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let file = std::fs::File::open(path)?;
+            // SAFETY: the mapping is read-only; the file must not be truncated
+            // while the font is alive.
+            let map = unsafe { memmap2::Mmap::map(&file) }?;
+            stats::add(&stats::MAPPED_FONT_BYTES, map.len());
+            Ok(Self::Mapped(map))
+        }
+        #[cfg(target_family = "wasm")]
+        {
+            std::fs::read(path).map(Self::new)
+        }
+    }
 
-    // Guess segment properties only if direction is not explicitly set
-    if buffer_box.inner.direction() == harfrust::Direction::Invalid {
-        buffer_box.inner.guess_segment_properties();
+    fn data(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            #[cfg(not(target_family = "wasm"))]
+            Self::Mapped(map) => map,
+            Self::Borrowed { data, len, .. } => unsafe { std::slice::from_raw_parts(*data, *len) },
+        }
     }
+}
 
-    // Prepare features
-    let mut rust_features = Vec::with_capacity(num_features as usize);
-    if !features.is_null() && num_features > 0 {
-        let feature_slice = std::slice::from_raw_parts(features, num_features as usize);
-        for f in feature_slice {
-            rust_features.push(harfrust::Feature {
-                tag: harfrust::Tag::new(&f.tag.to_be_bytes()),
-                value: f.value,
-                start: f.start,
-                end: f.end,
-            });
+impl Drop for FontInner {
+    fn drop(&mut self) {
+        match *self {
+            Self::Owned(ref data) => stats::sub(&stats::FONT_DATA_BYTES, data.len()),
+            #[cfg(not(target_family = "wasm"))]
+            Self::Mapped(ref map) => stats::sub(&stats::MAPPED_FONT_BYTES, map.len()),
+            Self::Borrowed { release: Some(release), user_data, .. } => unsafe { release(user_data) },
+            Self::Borrowed { release: None, .. } => {}
         }
     }
+}
+
+/// Sentinel marking an advance cache slot that has not been computed yet.
+const ADVANCE_UNSET: i32 = i32::MIN;
 
-    // Perform shaping
-    let glyph_buffer = shaper.shape(buffer_box.inner, &rust_features);
+/// Number of non-default variation instances a font keeps advances for.
+const ADVANCE_CACHE_INSTANCES: usize = 8;
 
-    wrap_glyph_buffer(glyph_buffer)
+/// Advances of one instance by glyph ID, or `ADVANCE_UNSET`.
+type AdvanceTable = Arc<[AtomicI32]>;
+
+/// Lazily populated horizontal advance tables, one per variation instance.
+///
+/// Width-only queries hit the same glyphs over and over while laying out a
+/// document, so advances are resolved once (including HVAR/gvar deltas) and
+/// then served from a flat array indexed by glyph ID. Slots are atomics so the
+/// tables can be filled from shared references. Tables of instances other
+/// than the default one are keyed by their normalized coordinates, most
+/// recently used last.
+struct AdvanceCache {
+    num_glyphs: u32,
+    default: OnceLock<AdvanceTable>,
+    instances: Mutex<Vec<(Box<[NormalizedCoord]>, AdvanceTable)>>,
 }
 
-/// Shapes text in a buffer using the given font, features, and variable font settings.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_shape_full(
-    font: *const HarfRustFont,
-    buffer: *mut HarfRustBuffer,
-    features: *const HarfRustFeature,
-    num_features: u32,
-    variations: *const HarfRustVariation,
-    num_variations: u32,
-) -> *mut HarfRustGlyphBuffer {
-    if font.is_null() || buffer.is_null() {
-        return std::ptr::null_mut();
+impl AdvanceCache {
+    fn new(num_glyphs: u32) -> Self {
+        Self {
+            num_glyphs,
+            default: OnceLock::new(),
+            instances: Mutex::new(Vec::new()),
+        }
     }
 
-    let font_wrapper = unsafe { &*font };
-    let mut buffer_box = unsafe { Box::from_raw(buffer) };
-    
-    // Handle variable font instance
-    let instance_opt = if !variations.is_null() && num_variations > 0 {
-        let var_slice = std::slice::from_raw_parts(variations, num_variations as usize);
-        
-        let rust_variations: Vec<harfrust::Variation> = var_slice.iter().map(|v| {
-            let tag = harfrust::Tag::new(&v.tag.to_be_bytes());
-            (tag, v.value).into()
-        }).collect();
-        
-        Some(harfrust::ShaperInstance::from_variations(&font_wrapper.font_ref, rust_variations))
-    } else {
-        None
-    };
-
-    let mut builder = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref);
-    
-    if let Some(inst) = &instance_opt {
-        builder = builder.instance(Some(inst));
+    /// Size of one advance table.
+    fn bytes(&self) -> usize {
+        self.num_glyphs as usize * std::mem::size_of::<AtomicI32>()
     }
-    
-    let shaper = builder.build();
 
-    // Guess segment properties only if direction is not explicitly set
-    if buffer_box.inner.direction() == harfrust::Direction::Invalid {
-        buffer_box.inner.guess_segment_properties();
+    fn new_table(&self) -> AdvanceTable {
+        stats::add(&stats::ADVANCE_CACHE_BYTES, self.bytes());
+        (0..self.num_glyphs)
+            .map(|_| AtomicI32::new(ADVANCE_UNSET))
+            .collect()
     }
 
-    // Prepare features
-    let mut rust_features = Vec::with_capacity(num_features as usize);
-    if !features.is_null() && num_features > 0 {
-        let feature_slice = std::slice::from_raw_parts(features, num_features as usize);
-        for f in feature_slice {
-            rust_features.push(harfrust::Feature {
-                tag: harfrust::Tag::new(&f.tag.to_be_bytes()),
-                value: f.value,
-                start: f.start,
-                end: f.end,
-            });
+    /// Returns the advance table of the instance at `coords`, allocating it
+    /// on first use.
+    fn table(&self, coords: &[NormalizedCoord]) -> AdvanceTable {
+        if coords.iter().all(|&coord| coord == NormalizedCoord::ZERO) {
+            return self.default.get_or_init(|| self.new_table()).clone();
+        }
+
+        let mut instances = self.instances.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = instances.iter().position(|(key, _)| **key == *coords) {
+            let entry = instances.remove(index);
+            let table = entry.1.clone();
+            instances.push(entry);
+            return table;
+        }
+        if instances.len() >= ADVANCE_CACHE_INSTANCES {
+            instances.remove(0);
+            stats::sub(&stats::ADVANCE_CACHE_BYTES, self.bytes());
         }
+        let table = self.new_table();
+        instances.push((coords.into(), table.clone()));
+        table
     }
+}
 
-    // Perform shaping
-    let glyph_buffer = shaper.shape(buffer_box.inner, &rust_features);
+impl Drop for AdvanceCache {
+    fn drop(&mut self) {
+        let instances = self.instances.get_mut().unwrap_or_else(|e| e.into_inner());
+        let tables = instances.len() + self.default.get().is_some() as usize;
+        stats::sub(&stats::ADVANCE_CACHE_BYTES, tables * self.bytes());
+    }
+}
 
-    wrap_glyph_buffer(glyph_buffer)
+/// Glyph advances of one variation instance, backed by its table in the
+/// font's [`AdvanceCache`].
+struct Advances<'a> {
+    metrics: GlyphMetrics<'a>,
+    table: AdvanceTable,
 }
 
-// =============================================================================
-// Glyph buffer functions
-// =============================================================================
+impl Advances<'_> {
+    /// Returns the advance for `glyph_id`, computing it on a miss, or `None`
+    /// if the glyph ID is out of range.
+    fn get(&self, glyph_id: u32) -> Option<i32> {
+        let slot = self.table.get(glyph_id as usize)?;
+        let cached = slot.load(Ordering::Relaxed);
+        if cached != ADVANCE_UNSET {
+            return Some(cached);
+        }
 
-/// Returns the number of glyphs in the glyph buffer.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_glyph_buffer_len(buffer: *const HarfRustGlyphBuffer) -> i32 {
-    if buffer.is_null() {
-        return -1;
+        let advance = self
+            .metrics
+            .advance_width(GlyphId::new(glyph_id))
+            .map(|a| a.round() as i32)
+            .unwrap_or(0);
+        slot.store(advance, Ordering::Relaxed);
+        Some(advance)
     }
-
-    let buffer_ref = unsafe { &*buffer };
-    buffer_ref.inner.len() as i32
 }
 
-/// Returns a pointer to the glyph info array.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_glyph_buffer_get_infos(
-    buffer: *const HarfRustGlyphBuffer,
-) -> *const HarfRustGlyphInfo {
-    if buffer.is_null() {
-        return std::ptr::null();
-    }
+/// Default number of shape plans kept per font.
+const PLAN_CACHE_CAPACITY: usize = 32;
+
+/// Number of shape plans kept per font, as configured by `harfrust_init`.
+static PLAN_CACHE_LIMIT: AtomicUsize = AtomicUsize::new(PLAN_CACHE_CAPACITY);
 
-    let buffer_ref = unsafe { &*buffer };
-    buffer_ref.infos_cache.as_ptr()
+/// Shape plans for the segment properties, features and variation instances
+/// a font has been shaped with, most recently used last. Building a plan is
+/// far more expensive than shaping a short run with one.
+#[derive(Default)]
+struct PlanCache {
+    plans: Mutex<Vec<Arc<harfrust::ShapePlan>>>,
 }
 
-/// Returns a pointer to the glyph position array.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_glyph_buffer_get_positions(
-    buffer: *const HarfRustGlyphBuffer,
-) -> *const HarfRustGlyphPosition {
-    if buffer.is_null() {
-        return std::ptr::null();
-    }
+impl PlanCache {
+    /// Returns the cached plan for shaping `buffer` with `features` and
+    /// `instance`, building and caching one on a miss.
+    fn get(
+        &self,
+        shaper: &harfrust::Shaper,
+        instance: Option<&harfrust::ShaperInstance>,
+        buffer: &harfrust::UnicodeBuffer,
+        features: &[harfrust::Feature],
+    ) -> Arc<harfrust::ShapePlan> {
+        let (direction, script, language) =
+            (buffer.direction(), Some(buffer.script()), buffer.language());
+        let key = harfrust::ShapePlanKey::new(script, direction)
+            .language(language.as_ref())
+            .instance(instance)
+            .features(features);
 
-    let buffer_ref = unsafe { &*buffer };
-    buffer_ref.positions_cache.as_ptr()
-}
+        let mut plans = self.plans.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = plans.iter().position(|plan| key.matches(plan)) {
+            let plan = plans.remove(index);
+            plans.push(plan.clone());
+            return plan;
+        }
 
-/// Clears the glyph buffer and returns a new unicode buffer for reuse.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_glyph_buffer_into_buffer(
-    buffer: *mut HarfRustGlyphBuffer,
-) -> *mut HarfRustBuffer {
-    if buffer.is_null() {
-        return std::ptr::null_mut();
+        let plan = Arc::new(harfrust::ShapePlan::new(
+            shaper,
+            direction,
+            script,
+            language.as_ref(),
+            features,
+        ));
+        let limit = PLAN_CACHE_LIMIT.load(Ordering::Relaxed);
+        if plans.len() >= limit {
+            let excess = plans.len() + 1 - limit;
+            plans.drain(..excess);
+            stats::sub(&stats::SHAPE_PLANS, excess);
+        }
+        plans.push(plan.clone());
+        stats::add(&stats::SHAPE_PLANS, 1);
+        plan
     }
+}
 
-    let buffer_box = unsafe { Box::from_raw(buffer) };
-    let unicode_buffer = buffer_box.inner.clear();
+impl Drop for PlanCache {
+    fn drop(&mut self) {
+        let plans = self.plans.get_mut().unwrap_or_else(|e| e.into_inner());
+        stats::sub(&stats::SHAPE_PLANS, plans.len());
+    }
+}
 
-    let wrapper = HarfRustBuffer {
-        inner: unicode_buffer,
-    };
-    Box::into_raw(Box::new(wrapper))
+/// Opaque wrapper that owns font data and provides shaping capabilities.
+/// The `FontRef`, `ShaperData` and units per em are built once at creation
+/// and reused by every call; only the lightweight `Shaper` view is rebuilt.
+/// Shape plans are cached per combination of segment properties, features
+/// and variation instance.
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustFont {
+    tag: TypeTag<Self>,
+    font_ref: harfrust::FontRef<'static>,
+    shaper_data: harfrust::ShaperData,
+    units_per_em: i32,
+    face_index: u32,
+    // Unique for the life of the process, unlike the handle's address
+    id: u64,
+    // Handle references; see `harfrust_font_retain`
+    refs: AtomicUsize,
+    advance_cache: AdvanceCache,
+    plan_cache: PlanCache,
+    // Layout tables are present but unreadable; see `degraded()`
+    degraded: OnceLock<bool>,
+    simple_glyphs: OnceLock<fastpath::SimpleGlyphs>,
+    data_hash: OnceLock<u64>,
+    // Applied before the caller's features; see `with_overrides`
+    feature_overrides: RwLock<Vec<harfrust::Feature>>,
+    // Glyph ID to advance; see `adjust_positions`
+    advance_overrides: RwLock<BTreeMap<u32, i32>>,
+    // Extra advance after each cluster, in font units; see `adjust_positions`
+    character_spacing: AtomicI32,
+    // f32 bits of the horizontal scaling factor; see `adjust_positions`
+    horizontal_scale: AtomicU32,
+    // Set by `harfrust_font_freeze`; never cleared
+    frozen: AtomicBool,
+    // Declared last so it is dropped after everything borrowing the data.
+    // Shared between fonts created from identical bytes.
+    inner: Arc<FontInner>,
 }
 
-/// Frees a glyph buffer previously created by `harfrust_shape`.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_glyph_buffer_free(buffer: *mut HarfRustGlyphBuffer) {
-    if !buffer.is_null() {
-        unsafe { drop(Box::from_raw(buffer)) };
+// `harfrust_font_freeze` promises frozen fonts can be shared across threads
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<HarfRustFont>();
+};
+
+impl HarfRustFont {
+    /// Superscript or subscript metrics from OS/2. Fonts without an OS/2
+    /// table get the usual defaults: 65% size, raised 35% or lowered 7.5% of
+    /// the em.
+    fn script_metrics(&self, position: HarfRustScriptPosition) -> HarfRustScriptMetrics {
+        let os2 = self.font_ref.os2().ok();
+        let metrics = os2.map(|os2| match position {
+            HarfRustScriptPosition::Superscript => HarfRustScriptMetrics {
+                x_size: os2.y_superscript_x_size() as i32,
+                y_size: os2.y_superscript_y_size() as i32,
+                x_offset: os2.y_superscript_x_offset() as i32,
+                y_offset: os2.y_superscript_y_offset() as i32,
+            },
+            HarfRustScriptPosition::Subscript => HarfRustScriptMetrics {
+                x_size: os2.y_subscript_x_size() as i32,
+                y_size: os2.y_subscript_y_size() as i32,
+                x_offset: os2.y_subscript_x_offset() as i32,
+                y_offset: os2.y_subscript_y_offset() as i32,
+            },
+        });
+
+        match metrics {
+            Some(metrics) if metrics.y_size > 0 => metrics,
+            _ => {
+                let upem = self.units_per_em as f32;
+                let offset = match position {
+                    HarfRustScriptPosition::Superscript => 0.35,
+                    HarfRustScriptPosition::Subscript => 0.075,
+                };
+                HarfRustScriptMetrics {
+                    x_size: (upem * 0.65).round() as i32,
+                    y_size: (upem * 0.65).round() as i32,
+                    x_offset: 0,
+                    y_offset: (upem * offset).round() as i32,
+                }
+            }
+        }
     }
-}
 
-// =============================================================================
-// Tests
-// =============================================================================
-// =============================================================================
-// WASM Memory Allocation (required for Wasmtime host calls)
-// =============================================================================
+    /// Shapes `buffer`, which holds `text`, with `shaper`, which must have
+    /// been built from this font with `instance`. A degraded font shapes
+    /// `text` with `fallback::shape` instead, marking the result degraded.
+    fn shape(
+        &self,
+        shaper: &harfrust::Shaper,
+        instance: Option<&harfrust::ShaperInstance>,
+        text: impl IntoIterator<Item = (char, u32)>,
+        buffer: harfrust::UnicodeBuffer,
+        features: &[harfrust::Feature],
+    ) -> HarfRustGlyphBuffer {
+        if self.degraded() {
+            let text: Vec<(char, u32)> = text.into_iter().collect();
+            let result = shape_fallback(self, &text, buffer.direction());
+            scratch::recycle(buffer);
+            return result;
+        }
+        glyph_buffer_wrapper(self.shape_with_plan(shaper, instance, buffer, features))
+    }
 
-/// Allocates memory in the WASM linear memory.
-/// Used by the host to allocate space for passing data to WASM.
-#[no_mangle]
-pub extern "C" fn harfrust_alloc(size: i32) -> i32 {
-    if size <= 0 {
-        return 0;
+    /// Shapes `buffer` like `shape`, reusing a cached shape plan, without
+    /// checking whether the font is degraded. Callers check first.
+    fn shape_with_plan(
+        &self,
+        shaper: &harfrust::Shaper,
+        instance: Option<&harfrust::ShaperInstance>,
+        buffer: harfrust::UnicodeBuffer,
+        features: &[harfrust::Feature],
+    ) -> harfrust::GlyphBuffer {
+        let features = self.with_overrides(features);
+        let plan = self.plan_cache.get(shaper, instance, &buffer, &features);
+        shaper.shape_with_plan(&plan, buffer, &features)
     }
-    
-    let layout = match std::alloc::Layout::from_size_align(size as usize, 8) {
-        Ok(l) => l,
-        Err(_) => return 0,
-    };
-    
-    // SAFETY: We're allocating with a valid layout and returning the pointer as i32
-    let ptr = unsafe { std::alloc::alloc(layout) };
-    if ptr.is_null() {
-        0
-    } else {
-        ptr as i32
+
+    /// Whether the font's layout tables are present but unreadable, so it is
+    /// shaped with `fallback::shape`. Checked on first use.
+    fn degraded(&self) -> bool {
+        *self.degraded.get_or_init(|| layout_damaged(&self.damaged_tables()))
     }
-}
 
-/// Frees memory allocated by harfrust_alloc.
-#[no_mangle]
-pub unsafe extern "C" fn harfrust_dealloc(ptr: i32, size: i32) {
-    if ptr == 0 || size <= 0 {
-        return;
+    /// The font's feature overrides followed by `features`, which take
+    /// precedence where they overlap.
+    fn with_overrides<'a>(&self, features: &'a [harfrust::Feature]) -> Cow<'a, [harfrust::Feature]> {
+        let overrides = self.feature_overrides.read().unwrap_or_else(|e| e.into_inner());
+        if overrides.is_empty() {
+            return Cow::Borrowed(features);
+        }
+        Cow::Owned(overrides.iter().chain(features).copied().collect())
     }
-    
-    let layout = match std::alloc::Layout::from_size_align(size as usize, 8) {
-        Ok(l) => l,
-        Err(_) => return,
-    };
-    
-    // SAFETY: Layout matches the one used in harfrust_alloc
-    unsafe { std::alloc::dealloc(ptr as *mut u8, layout) };
-}
 
-// =============================================================================
-// Tests
-// =============================================================================
+    /// `ch`, or U+0020 if `ch` is one of `NORMALIZED_SPACES` the font has no
+    /// glyph for and the font has one for U+0020.
+    fn normalize_space(&self, ch: char) -> char {
+        if !NORMALIZED_SPACES.contains(&ch) {
+            return ch;
+        }
+        let charmap = self.font_ref.charmap();
+        if charmap.map(ch).is_none() && charmap.map(' ').is_some() {
+            ' '
+        } else {
+            ch
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::ffi::CString;
+    /// Horizontal scaling factor, see `harfrust_font_set_horizontal_scale`.
+    fn horizontal_scale(&self) -> f32 {
+        f32::from_bits(self.horizontal_scale.load(Ordering::Relaxed))
+    }
 
-    #[test]
-    fn test_buffer_lifecycle() {
-        unsafe {
-            let buffer = harfrust_buffer_new();
-            assert!(!buffer.is_null());
+    /// Applies the font's output adjustments to a shaping result: replaces
+    /// the horizontal advances of glyphs that have an advance override (see
+    /// `harfrust_font_set_advance_overrides`), adds the character spacing
+    /// after each cluster (see `harfrust_font_set_character_spacing`), then
+    /// scales advances and offsets horizontally (see
+    /// `harfrust_font_set_horizontal_scale`).
+    fn adjust_positions(&self, infos: &[HarfRustGlyphInfo], positions: &mut [HarfRustGlyphPosition]) {
+        let overrides = self.advance_overrides.read().unwrap_or_else(|e| e.into_inner());
+        if !overrides.is_empty() {
+            for (info, pos) in infos.iter().zip(positions.iter_mut()) {
+                // Vertical runs advance in y only
+                if pos.y_advance != 0 {
+                    continue;
+                }
+                if let Some(&advance) = overrides.get(&info.glyph_id) {
+                    pos.x_advance = advance;
+                }
+            }
+        }
 
-            let text = CString::new("Hello, world!").unwrap();
-            let result = harfrust_buffer_add_str(buffer, text.as_ptr());
-            assert_eq!(result, 0);
+        let spacing = self.character_spacing.load(Ordering::Relaxed);
+        if spacing != 0 {
+            for i in 0..infos.len().min(positions.len()) {
+                let cluster_end = infos.get(i + 1).is_none_or(|next| next.cluster != infos[i].cluster);
+                if cluster_end && positions[i].y_advance == 0 {
+                    positions[i].x_advance += spacing;
+                }
+            }
+        }
 
-            let len = harfrust_buffer_len(buffer);
-            assert_eq!(len, 13);
+        let scale = self.horizontal_scale();
+        if scale != 1.0 {
+            for pos in positions {
+                pos.x_advance = (pos.x_advance as f32 * scale).round() as i32;
+                pos.x_offset = (pos.x_offset as f32 * scale).round() as i32;
+            }
+        }
+    }
 
-            harfrust_buffer_clear(buffer);
-            let len = harfrust_buffer_len(buffer);
-            assert_eq!(len, 0);
+    /// Glyph metrics at the default location, in font units.
+    fn glyph_metrics(&self) -> GlyphMetrics<'_> {
+        self.font_ref.glyph_metrics(Size::unscaled(), LocationRef::default())
+    }
 
-            harfrust_buffer_free(buffer);
+    /// Cached glyph advances of the instance at the normalized coordinates
+    /// `coords`, which are empty for the default instance.
+    fn advances<'a>(&'a self, coords: &'a [NormalizedCoord]) -> Advances<'a> {
+        Advances {
+            metrics: self.font_ref.glyph_metrics(Size::unscaled(), LocationRef::new(coords)),
+            table: self.advance_cache.table(coords),
         }
     }
 
-    #[test]
-    fn test_null_safety() {
-        unsafe {
-            assert_eq!(
-                harfrust_buffer_add_str(std::ptr::null_mut(), std::ptr::null()),
-                -1
-            );
-            assert_eq!(harfrust_buffer_len(std::ptr::null()), -1);
-            harfrust_buffer_clear(std::ptr::null_mut());
-            harfrust_buffer_free(std::ptr::null_mut());
+    fn data_hash(&self) -> u64 {
+        *self.data_hash.get_or_init(|| content_hash(self.inner.data()))
+    }
 
-            assert!(harfrust_font_from_data(std::ptr::null(), 0).is_null());
-            assert_eq!(harfrust_font_units_per_em(std::ptr::null()), -1);
-            harfrust_font_free(std::ptr::null_mut());
+    /// Start of a cache key: the font data hash, face index and the
+    /// normalized coordinates `variations` resolve to.
+    fn key_prefix(&self, variations: &[HarfRustVariation]) -> Vec<u8> {
+        let settings = variations.iter().map(|v| (Tag::new(&v.tag.to_be_bytes()), v.value));
+        let location = self.font_ref.axes().location(settings);
 
-            assert!(harfrust_shape(std::ptr::null(), std::ptr::null_mut()).is_null());
-            assert_eq!(harfrust_glyph_buffer_len(std::ptr::null()), -1);
-            assert!(harfrust_glyph_buffer_get_infos(std::ptr::null()).is_null());
-            assert!(harfrust_glyph_buffer_get_positions(std::ptr::null()).is_null());
-            assert!(harfrust_glyph_buffer_into_buffer(std::ptr::null_mut()).is_null());
-            harfrust_glyph_buffer_free(std::ptr::null_mut());
+        let mut key = Vec::with_capacity(12 + location.coords().len() * 2);
+        key.extend_from_slice(&self.data_hash().to_le_bytes());
+        key.extend_from_slice(&self.face_index.to_le_bytes());
+        for coord in location.coords() {
+            key.extend_from_slice(&coord.to_bits().to_le_bytes());
         }
+        key
     }
-    
-    #[test]
-    fn test_shape_with_font() {
-        // Try to load a system font for testing
-        let font_paths = [
-            r"C:\Windows\Fonts\arial.ttf",
-            r"C:\Windows\Fonts\segoeui.ttf",
-            r"C:\Windows\Fonts\tahoma.ttf",
+
+    /// Checks that the tables needed for shaping and metrics parse. Required
+    /// tables must be present; optional ones only need to parse if they exist.
+    fn validate(&self) -> bool {
+        self.damaged_tables().is_empty()
+    }
+
+    /// Tags of the required tables that are missing or do not parse,
+    /// followed by those of the optional tables that exist but do not parse.
+    fn damaged_tables(&self) -> Vec<Tag> {
+        let font = &self.font_ref;
+        let required = [
+            (Tag::new(b"head"), font.head().is_ok()),
+            (Tag::new(b"maxp"), font.maxp().is_ok()),
+            (Tag::new(b"hhea"), font.hhea().is_ok()),
+            (Tag::new(b"hmtx"), font.hmtx().is_ok()),
         ];
-        
-        let font_data = font_paths.iter()
-            .find_map(|path| std::fs::read(path).ok())
-            .expect("No system font found for testing");
-        
-        // Test harfrust directly first
-        let font_ref = harfrust::FontRef::new(&font_data).expect("Font parse failed");
-        let shaper_data = harfrust::ShaperData::new(&font_ref);
-        let shaper = shaper_data.shaper(&font_ref).build();
-        
-        let mut buffer = harfrust::UnicodeBuffer::new();
-        buffer.push_str("Hello");
-        buffer.guess_segment_properties();  // Required to set direction!
-        
-        let result = shaper.shape(buffer, &[]);
-        assert_eq!(result.len(), 5);
+        let optional = [
+            (Tag::new(b"cmap"), font.cmap().is_ok()),
+            (Tag::new(b"GDEF"), font.gdef().is_ok()),
+            (Tag::new(b"GSUB"), font.gsub().is_ok()),
+            (Tag::new(b"GPOS"), font.gpos().is_ok()),
+        ];
+        let optional = optional.into_iter().filter(|(tag, _)| font.data_for_tag(*tag).is_some());
+
+        required.into_iter().chain(optional).filter(|(_, ok)| !ok).map(|(tag, _)| tag).collect()
     }
-    
-    #[test]
+
+    /// Resolves every glyph advance, walks the cmap and shapes a short sample
+    /// so the shaper's lookup data is built.
+    fn warm_up(&self) {
+        let advances = self.advances(&[]);
+        for glyph_id in 0..self.advance_cache.num_glyphs {
+            advances.get(glyph_id);
+        }
+
+        let charmap = self.font_ref.charmap();
+        let sample: String = charmap
+            .mappings()
+            .take(16)
+            .filter_map(|(cp, _)| char::from_u32(cp))
+            .collect();
+        let sample = if sample.is_empty() { " ".to_string() } else { sample };
+
+        let mut unicode_buffer = harfrust::UnicodeBuffer::new();
+        unicode_buffer.push_str(&sample);
+        unicode_buffer.guess_segment_properties();
+        let shaper = self.shaper_data.shaper(&self.font_ref).build();
+        let text = sample.char_indices().map(|(i, ch)| (ch, i as u32));
+        self.shape(&shaper, None, text, unicode_buffer, &[]);
+    }
+}
+
+/// Opaque wrapper around harfrust's GlyphBuffer (shaping result).
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustGlyphBuffer {
+    tag: TypeTag<Self>,
+    // None for buffers derived from another result (e.g. slices)
+    inner: Option<harfrust::GlyphBuffer>,
+    // Cache for FFI-safe glyph data
+    infos_cache: Vec<HarfRustGlyphInfo>,
+    positions_cache: Vec<HarfRustGlyphPosition>,
+    // Per-glyph transforms from synthesized layout; empty means all identity
+    transforms: Vec<HarfRustGlyphTransform>,
+    // Produced by fallback shaping, without layout tables
+    degraded: bool,
+    // See `HarfRustResultFlags::SpacesNormalized`
+    spaces_normalized: bool,
+}
+
+/// Opaque wrapper around a rendered color glyph (premultiplied RGBA).
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustColorBitmap {
+    tag: TypeTag<Self>,
+    inner: color::ColorBitmap,
+}
+
+/// Opaque glyph set accumulated across many shaping results (e.g. every page
+/// of a document) and subset once at the end.
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustSubsetter {
+    tag: TypeTag<Self>,
+    glyphs: BTreeSet<u32>,
+}
+
+/// Opaque byte array produced by the library (e.g. a subset font).
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustBlob {
+    tag: TypeTag<Self>,
+    data: Vec<u8>,
+}
+
+/// Opaque shaping configuration: a font plus a variation instance computed
+/// once and reused for every shape call. Holds a reference to the font.
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustShaper {
+    tag: TypeTag<Self>,
+    // The handle rather than the object, released by `harfrust_font_free`
+    font: *const HarfRustFont,
+    instance: Option<harfrust::ShaperInstance>,
+}
+
+/// Opaque variable font instance (normalized axis coordinates and derived
+/// variation data) for shaping many runs at the same settings.
+#[cfg_attr(all(), repr(C))]
+pub struct HarfRustInstance {
+    tag: TypeTag<Self>,
+    inner: harfrust::ShaperInstance,
+}
+
+/// Scales a glyph's advances and offsets by `scale`.
+fn scale_position(pos: &mut HarfRustGlyphPosition, scale: f32) {
+    pos.x_advance = (pos.x_advance as f32 * scale).round() as i32;
+    pos.y_advance = (pos.y_advance as f32 * scale).round() as i32;
+    pos.x_offset = (pos.x_offset as f32 * scale).round() as i32;
+    pos.y_offset = (pos.y_offset as f32 * scale).round() as i32;
+}
+
+/// Wraps a result shaped with `font`, applying its output adjustments.
+fn wrap_glyph_buffer(font: &HarfRustFont, mut wrapper: HarfRustGlyphBuffer) -> *mut HarfRustGlyphBuffer {
+    font.adjust_positions(&wrapper.infos_cache, &mut wrapper.positions_cache);
+    handle::into_handle(Box::new(wrapper))
+}
+
+/// Copies a shaping result into the FFI-safe wrapper.
+fn glyph_buffer_wrapper(glyph_buffer: harfrust::GlyphBuffer) -> HarfRustGlyphBuffer {
+    let glyph_infos = glyph_buffer.glyph_infos();
+    let glyph_positions = glyph_buffer.glyph_positions();
+
+    let mut infos = Vec::with_capacity(glyph_infos.len());
+    for info in glyph_infos {
+        infos.push(HarfRustGlyphInfo {
+            glyph_id: info.glyph_id,
+            cluster: info.cluster,
+        });
+    }
+
+    let mut positions = Vec::with_capacity(glyph_positions.len());
+    for pos in glyph_positions {
+        positions.push(HarfRustGlyphPosition {
+            x_advance: pos.x_advance,
+            y_advance: pos.y_advance,
+            x_offset: pos.x_offset,
+            y_offset: pos.y_offset,
+        });
+    }
+
+    HarfRustGlyphBuffer {
+        tag: TypeTag::new(),
+        inner: Some(glyph_buffer),
+        infos_cache: infos,
+        positions_cache: positions,
+        transforms: Vec::new(),
+        degraded: false,
+        spaces_normalized: false,
+    }
+}
+
+/// Converts FFI features to harfrust features. A null pointer yields none.
+unsafe fn convert_features(
+    features: *const HarfRustFeature,
+    num_features: u32,
+) -> Vec<harfrust::Feature> {
+    if features.is_null() || num_features == 0 {
+        return Vec::new();
+    }
+
+    let feature_slice = unsafe { std::slice::from_raw_parts(features, num_features as usize) };
+    feature_slice
+        .iter()
+        .map(|f| harfrust::Feature {
+            tag: harfrust::Tag::new(&f.tag.to_be_bytes()),
+            value: f.value,
+            start: f.start,
+            end: f.end,
+        })
+        .collect()
+}
+
+/// Computes the variation instance for `num_variations` axis settings, or
+/// `None` if there are none.
+unsafe fn shaper_instance(
+    font_ref: &harfrust::FontRef,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+) -> Option<harfrust::ShaperInstance> {
+    if variations.is_null() || num_variations == 0 {
+        return None;
+    }
+
+    let var_slice = unsafe { std::slice::from_raw_parts(variations, num_variations as usize) };
+    let rust_variations: Vec<harfrust::Variation> = var_slice
+        .iter()
+        .map(|v| (harfrust::Tag::new(&v.tag.to_be_bytes()), v.value).into())
+        .collect();
+    Some(harfrust::ShaperInstance::from_variations(font_ref, rust_variations))
+}
+
+/// Views a caller's variation array, treating null as empty.
+unsafe fn variation_slice<'a>(
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+) -> &'a [HarfRustVariation] {
+    if variations.is_null() || num_variations == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(variations, num_variations as usize) }
+    }
+}
+
+/// Shapes a buffer whose segment properties are already set with `shape`,
+/// answering from the shaped-result cache instead when it is enabled and
+/// holds the same text, properties, features and variations for this font.
+fn shape_cached(
+    font: &HarfRustFont,
+    mut buffer: Box<HarfRustBuffer>,
+    features: &[harfrust::Feature],
+    variations: &[HarfRustVariation],
+    shape: impl FnOnce(harfrust::UnicodeBuffer) -> harfrust::GlyphBuffer,
+) -> *mut HarfRustGlyphBuffer {
+    let spaces_normalized = buffer.normalize_spaces(font);
+    let mut result = shape_result(font, buffer, features, variations, shape);
+    font.adjust_positions(&result.infos_cache, &mut result.positions_cache);
+    result.spaces_normalized = spaces_normalized;
+    handle::into_handle(Box::new(result))
+}
+
+/// The result of `shape_cached`, before the buffer's preprocessing is
+/// recorded in it.
+fn shape_result(
+    font: &HarfRustFont,
+    buffer: Box<HarfRustBuffer>,
+    features: &[harfrust::Feature],
+    variations: &[HarfRustVariation],
+    shape: impl FnOnce(harfrust::UnicodeBuffer) -> harfrust::GlyphBuffer,
+) -> HarfRustGlyphBuffer {
+    if font.degraded() {
+        return shape_fallback(font, &buffer.shaped_text(), buffer.inner.direction());
+    }
+    if variations.is_empty() {
+        if let Some(result) = shape_small_run(font, &buffer) {
+            return result;
+        }
+    }
+    if !cache::enabled() {
+        return glyph_buffer_wrapper(shape(buffer.inner));
+    }
+
+    let features = font.with_overrides(features);
+    let key = cache::ShapeKey::new(font.id, &buffer.shaped_text(), &buffer.context, &buffer.inner, &features, variations);
+    if let Some(run) = cache::get(&key) {
+        let wrapper = HarfRustGlyphBuffer {
+            tag: TypeTag::new(),
+            inner: None,
+            infos_cache: run.infos.clone(),
+            positions_cache: run.positions.clone(),
+            transforms: Vec::new(),
+            degraded: false,
+            spaces_normalized: false,
+        };
+        return wrapper;
+    }
+
+    let result = glyph_buffer_wrapper(shape(buffer.inner));
+    let run = cache::ShapedRun {
+        infos: result.infos_cache.clone(),
+        positions: result.positions_cache.clone(),
+    };
+    cache::insert(key, run);
+    result
+}
+
+/// Shapes text with `fallback::shape`, marking the result degraded.
+fn shape_fallback(font: &HarfRustFont, text: &[(char, u32)], direction: harfrust::Direction) -> HarfRustGlyphBuffer {
+    let (infos, positions) = fallback::shape(&font.font_ref, text, direction);
+    HarfRustGlyphBuffer {
+        tag: TypeTag::new(),
+        inner: None,
+        infos_cache: infos,
+        positions_cache: positions,
+        transforms: Vec::new(),
+        degraded: true,
+        spaces_normalized: false,
+    }
+}
+
+/// Shapes a short left-to-right Latin or Common run with `fastpath`, if it
+/// is enabled and applies.
+fn shape_small_run(font: &HarfRustFont, buffer: &HarfRustBuffer) -> Option<HarfRustGlyphBuffer> {
+    let script = buffer.inner.script();
+    let simple_script = [harfrust::script::LATIN, harfrust::script::COMMON].contains(&script);
+    let eligible = fastpath::enabled()
+        && buffer.text.len() <= fastpath::MAX_LEN
+        && buffer.inner.direction() == harfrust::Direction::LeftToRight
+        && simple_script;
+    if !eligible {
+        return None;
+    }
+
+    let simple_glyphs =
+        font.simple_glyphs.get_or_init(|| fastpath::SimpleGlyphs::new(&font.font_ref));
+    let (infos, positions) = simple_glyphs.shape(&buffer.shaped_text())?;
+    Some(HarfRustGlyphBuffer {
+        tag: TypeTag::new(),
+        inner: None,
+        infos_cache: infos,
+        positions_cache: positions,
+        transforms: Vec::new(),
+        degraded: false,
+        spaces_normalized: false,
+    })
+}
+
+/// Builds a unicode buffer for a UTF-16 run with the given properties. A
+/// direction of `Invalid`, a script tag of 0 or a null language are guessed
+/// from the text. If `spaces` is set, the spaces that font has no glyph for
+/// are normalized (see `run_chars`); whether any were is returned with the
+/// buffer. Returns `None`, recording the failure, if the text or language is
+/// invalid.
+unsafe fn run_buffer(
+    text: *const u16,
+    len: i32,
+    direction: HarfRustDirection,
+    script_tag: u32,
+    language: *const c_char,
+    spaces: Option<&HarfRustFont>,
+) -> Option<(harfrust::UnicodeBuffer, bool)> {
+    if text.is_null() {
+        error::set(HarfRustStatus::NullArgument, "text is null");
+        return None;
+    }
+    if len < 0 {
+        error::set(HarfRustStatus::InvalidArgument, "len is negative");
+        return None;
+    }
+
+    let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+    let mut unicode_buffer = scratch::unicode_buffer();
+    let mut spaces_normalized = false;
+    match spaces {
+        Some(font) => {
+            for (ch, cluster) in utf16_chars(slice) {
+                let normalized = font.normalize_space(ch);
+                spaces_normalized |= normalized != ch;
+                unicode_buffer.add(normalized, cluster);
+            }
+        }
+        None => push_utf16(&mut unicode_buffer, slice),
+    }
+
+    unicode_buffer.set_direction(direction.into());
+    let script_tag = harfrust::Tag::new(&script_tag.to_be_bytes());
+    if let Some(script) = harfrust::Script::from_iso15924_tag(script_tag) {
+        unicode_buffer.set_script(script);
+    }
+    if !language.is_null() {
+        let lang_str = match unsafe { CStr::from_ptr(language) }.to_str() {
+            Ok(lang_str) => lang_str,
+            Err(e) => {
+                error::set(HarfRustStatus::InvalidUtf8, format!("language is not valid UTF-8: {e}"));
+                return None;
+            }
+        };
+        let Ok(language) = lang_str.parse::<harfrust::Language>() else {
+            error::set(HarfRustStatus::InvalidLanguage, format!("invalid language tag '{lang_str}'"));
+            return None;
+        };
+        unicode_buffer.set_language(language);
+    }
+    unicode_buffer.guess_segment_properties();
+
+    Some((unicode_buffer, spaces_normalized))
+}
+
+/// Decodes a UTF-16 run like `utf16_chars`, normalizing the spaces `font`
+/// has no glyph for as `harfrust_buffer_set_normalize_spaces` does if
+/// `normalize` is set.
+fn run_chars<'a>(font: &'a HarfRustFont, text: &'a [u16], normalize: bool) -> impl Iterator<Item = (char, u32)> + 'a {
+    let normalize = move |ch| if normalize { font.normalize_space(ch) } else { ch };
+    utf16_chars(text).map(move |(ch, cluster)| (normalize(ch), cluster))
+}
+
+/// Returns true if the font's GSUB table has a feature with the given tag.
+fn has_gsub_feature(font_ref: &harfrust::FontRef, tag: Tag) -> bool {
+    font_ref
+        .gsub()
+        .and_then(|gsub| gsub.feature_list())
+        .map(|list| list.feature_records().iter().any(|r| r.feature_tag() == tag))
+        .unwrap_or(false)
+}
+
+/// Whether a GSUB feature covers the nominal glyph of each character of
+/// `text`, and features applying it to just the covered characters. Fonts
+/// often implement a feature such as `smcp` for some characters only, and
+/// the others need synthesizing.
+fn covered_features(font: &HarfRustFont, text: &[(char, u32)], tag: Tag) -> (Vec<bool>, Vec<harfrust::Feature>) {
+    let coverage = font
+        .font_ref
+        .gsub()
+        .map(|gsub| trace::feature_coverage(&gsub, tag))
+        .unwrap_or_default();
+    let charmap = font.font_ref.charmap();
+    let covered: Vec<bool> = text
+        .iter()
+        .map(|&(ch, _)| charmap.map(ch).is_some_and(|glyph| coverage.contains(&glyph.to_u32())))
+        .collect();
+
+    // One feature per run of covered characters, with an exclusive cluster
+    // end as in `convert_features`
+    let feature = |start, last: u32| harfrust::Feature { tag, value: 1, start, end: last + 1 };
+    let mut features: Vec<harfrust::Feature> = Vec::new();
+    let mut run_start = None;
+    for (i, &is_covered) in covered.iter().enumerate() {
+        match (is_covered, run_start) {
+            (true, None) => run_start = Some(text[i].1),
+            (false, Some(start)) => {
+                features.push(feature(start, text[i - 1].1));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(start), Some(&(_, last))) = (run_start, text.last()) {
+        features.push(feature(start, last));
+    }
+    (covered, features)
+}
+
+/// Creates a unicode buffer with the same segment properties as `buffer`
+/// but no text.
+fn empty_buffer_like(buffer: &harfrust::UnicodeBuffer) -> harfrust::UnicodeBuffer {
+    let mut out = scratch::unicode_buffer();
+    out.set_direction(buffer.direction());
+    out.set_script(buffer.script());
+    if let Some(language) = buffer.language() {
+        out.set_language(language);
+    }
+    out.set_flags(buffer.flags());
+    out.set_cluster_level(buffer.cluster_level());
+    out
+}
+
+/// Decodes UTF-16 text into characters paired with their UTF-16 code unit
+/// offsets. Unpaired surrogates are replaced with U+FFFD.
+fn utf16_chars(text: &[u16]) -> impl Iterator<Item = (char, u32)> + '_ {
+    let mut cluster = 0;
+    std::char::decode_utf16(text.iter().cloned()).map(move |c| {
+        let ch = c.unwrap_or(std::char::REPLACEMENT_CHARACTER);
+        let offset = cluster;
+        cluster += ch.len_utf16() as u32;
+        (ch, offset)
+    })
+}
+
+/// Adds UTF-16 text to a unicode buffer, using UTF-16 code unit offsets as
+/// cluster values.
+fn push_utf16(buffer: &mut harfrust::UnicodeBuffer, text: &[u16]) {
+    for (ch, cluster) in utf16_chars(text) {
+        buffer.add(ch, cluster);
+    }
+}
+
+/// Shapes a buffer holding the UTF-16 `text` with the shaper's point size
+/// set, so size-dependent tracking is honored.
+fn shape_at_size(
+    font: &HarfRustFont,
+    text: &[u16],
+    buffer: harfrust::UnicodeBuffer,
+    size: f32,
+) -> HarfRustGlyphBuffer {
+    let shaper = font.shaper_data.shaper(&font.font_ref).point_size(Some(size)).build();
+    font.shape(&shaper, None, utf16_chars(text), buffer, &[])
+}
+
+/// Finds the largest size in `[min_size, max_size]` at which the UTF-16 text
+/// fits within `max_width` (and, if positive, `max_height`), by binary
+/// search. Returns `None` if it does not fit even at `min_size`.
+fn fit_size(
+    font: &HarfRustFont,
+    text: &[u16],
+    max_width: f32,
+    max_height: f32,
+    min_size: f32,
+    max_size: f32,
+) -> Option<f32> {
+    let metrics = font.font_ref.metrics(Size::unscaled(), LocationRef::default());
+    let upem = metrics.units_per_em.max(1) as f32;
+    let line_height = metrics.ascent - metrics.descent;
+
+    let mut buffer = Some(harfrust::UnicodeBuffer::new());
+    let mut fits = |size: f32| -> bool {
+        if max_height > 0.0 && line_height * size / upem > max_height {
+            return false;
+        }
+
+        let mut unicode_buffer = buffer.take().unwrap_or_default();
+        push_utf16(&mut unicode_buffer, text);
+        unicode_buffer.guess_segment_properties();
+
+        let mut result = shape_at_size(font, text, unicode_buffer, size);
+        font.adjust_positions(&result.infos_cache, &mut result.positions_cache);
+        let advance: i64 = result.positions_cache.iter().map(|pos| pos.x_advance as i64).sum();
+        buffer = result.inner.take().map(harfrust::GlyphBuffer::clear);
+
+        advance as f32 * size / upem <= max_width
+    };
+
+    if fits(max_size) {
+        return Some(max_size);
+    }
+    if !fits(min_size) {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (min_size, max_size);
+    for _ in 0..32 {
+        if hi - lo <= 0.01 {
+            break;
+        }
+        let mid = (lo + hi) / 2.0;
+        if fits(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    Some(lo)
+}
+
+/// Maps each glyph to the UTF-16 text of its cluster. A cluster spans up to the
+/// next larger cluster value, which works for both LTR and RTL buffer order.
+fn glyph_text_ranges(infos: &[HarfRustGlyphInfo], text: &[u16]) -> Vec<HarfRustTextRange> {
+    let text_len = text.len() as u32;
+    let mut clusters: Vec<u32> = infos.iter().map(|info| info.cluster).collect();
+    clusters.sort_unstable();
+    clusters.dedup();
+
+    let snap = |offset: u32| -> u32 {
+        let offset = offset.min(text_len);
+        let splits_pair = offset > 0
+            && offset < text_len
+            && (0xD800..0xDC00).contains(&text[offset as usize - 1])
+            && (0xDC00..0xE000).contains(&text[offset as usize]);
+        if splits_pair {
+            offset + 1
+        } else {
+            offset
+        }
+    };
+
+    let mut seen = vec![false; clusters.len()];
+    infos
+        .iter()
+        .map(|info| {
+            let index = clusters.binary_search(&info.cluster).unwrap_or(0);
+            let start = snap(info.cluster);
+            if std::mem::replace(&mut seen[index], true) {
+                return HarfRustTextRange { start, length: 0 };
+            }
+            let end = clusters.get(index + 1).map_or(text_len, |&next| snap(next));
+            HarfRustTextRange {
+                start,
+                length: end.saturating_sub(start),
+            }
+        })
+        .collect()
+}
+
+/// Converts a UTF-16 offset into `text` to the cluster value the same
+/// position gets in a buffer filled through the given add path.
+fn utf16_offset_to_cluster(text: &[u16], offset: usize, unit: HarfRustClusterUnit) -> u32 {
+    match unit {
+        HarfRustClusterUnit::Utf16 => offset as u32,
+        HarfRustClusterUnit::Utf8 => std::char::decode_utf16(text[..offset].iter().cloned())
+            .map(|c| c.unwrap_or(std::char::REPLACEMENT_CHARACTER).len_utf8() as u32)
+            .sum(),
+    }
+}
+
+/// Finds `digits (/ or U+2044) digits` spans in UTF-16 text, returning the
+/// start, slash and end offsets of each.
+fn fraction_spans(text: &[u16]) -> Vec<(usize, usize, usize)> {
+    let is_digit = |unit: u16| (b'0' as u16..=b'9' as u16).contains(&unit);
+    let is_slash = |unit: u16| unit == b'/' as u16 || unit == 0x2044;
+
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < text.len() {
+        if !is_digit(text[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < text.len() && is_digit(text[i]) {
+            i += 1;
+        }
+        if i + 1 < text.len() && is_slash(text[i]) && is_digit(text[i + 1]) {
+            let slash = i;
+            i += 1;
+            while i < text.len() && is_digit(text[i]) {
+                i += 1;
+            }
+            spans.push((start, slash, i));
+        }
+    }
+    spans
+}
+
+/// 64-bit FNV-1a hash of font data.
+fn content_hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// 128-bit FNV-1a hash, for keys that must not collide in practice.
+fn content_hash_128(data: &[u8]) -> u128 {
+    data.iter().fold(0x6c62_272e_07bb_0142_62b8_2175_6295_c58d, |hash, &byte| {
+        (hash ^ byte as u128).wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b)
+    })
+}
+
+/// Owned font data by content hash, so that fonts created from identical
+/// bytes (the same font embedded in several documents) share one copy.
+static SHARED_FONT_DATA: Mutex<Vec<(u64, Weak<FontInner>)>> = Mutex::new(Vec::new());
+
+/// Returns an owned copy of font data, decoding WOFF when the `woff` feature
+/// is enabled, and its content hash. Data identical to that of a live font is
+/// shared instead of copied.
+fn owned_font_data(data: &[u8]) -> Option<(Arc<FontInner>, u64)> {
+    #[cfg(feature = "woff")]
+    let decoded = if woff::is_woff(data) {
+        let Some(decoded) = woff::decode(data) else {
+            error::set(HarfRustStatus::InvalidFont, "WOFF data cannot be decoded");
+            return None;
+        };
+        Some(decoded)
+    } else {
+        None
+    };
+    #[cfg(feature = "woff")]
+    let data = decoded.as_deref().unwrap_or(data);
+
+    let hash = content_hash(data);
+    let mut shared = SHARED_FONT_DATA.lock().unwrap_or_else(|e| e.into_inner());
+    shared.retain(|(_, inner)| inner.strong_count() > 0);
+    let existing = shared
+        .iter()
+        .filter(|(h, _)| *h == hash)
+        .filter_map(|(_, inner)| inner.upgrade())
+        .find(|inner| inner.data() == data);
+    if let Some(inner) = existing {
+        return Some((inner, hash));
+    }
+
+    // Fail instead of aborting if a very large input cannot be copied
+    let mut owned = Vec::new();
+    if owned.try_reserve_exact(data.len()).is_err() {
+        error::set(HarfRustStatus::OutOfRange, format!("{} bytes of font data cannot be allocated", data.len()));
+        return None;
+    }
+    owned.extend_from_slice(data);
+    let inner = Arc::new(FontInner::new(owned));
+    shared.push((hash, Arc::downgrade(&inner)));
+    Some((inner, hash))
+}
+
+/// Creates a font from a copy of `data`. If the data does not parse or its
+/// core tables are damaged, a repaired copy is used instead when that parses
+/// cleanly.
+fn create_owned_font(data: &[u8], index: Option<u32>) -> Option<HarfRustFont> {
+    let (inner, hash) = owned_font_data(data)?;
+    create_repaired_font(inner, hash, index)
+}
+
+/// Second half of `create_owned_font`, for data that is already owned.
+fn create_repaired_font(
+    inner: Arc<FontInner>,
+    hash: u64,
+    index: Option<u32>,
+) -> Option<HarfRustFont> {
+    let font = create_font(inner.clone(), index);
+    if let Some(font) = &font {
+        let _ = font.data_hash.set(hash);
+    }
+    if font.as_ref().is_some_and(HarfRustFont::validate) || index.unwrap_or(0) != 0 {
+        return font;
+    }
+
+    let repaired = repair::repair(inner.data())
+        .and_then(|data| create_font(Arc::new(FontInner::new(data)), index))
+        .filter(HarfRustFont::validate);
+    repaired.or(font)
+}
+
+/// Tables whose damage makes a font unusable even in tolerant mode.
+const REQUIRED_TABLES: [Tag; 4] =
+    [Tag::new(b"head"), Tag::new(b"maxp"), Tag::new(b"hhea"), Tag::new(b"hmtx")];
+
+/// Creates a font in `HarfRustParseMode::Tolerant`: the directory is repaired
+/// and optional tables that do not parse are left out, with every problem
+/// reported through the log callback. Fails only if a required table is
+/// unreadable.
+fn create_tolerant_font(data: &[u8], index: u32) -> Option<HarfRustFont> {
+    let Some((inner, hash)) = owned_font_data(data) else {
+        log(HarfRustLogLevel::Error, "font data could not be decoded");
+        return None;
+    };
+    for problem in repair::diagnose(inner.data()) {
+        log(HarfRustLogLevel::Warning, &problem);
+    }
+
+    let Some(font) = create_repaired_font(inner, hash, Some(index)) else {
+        log(HarfRustLogLevel::Error, "font data could not be parsed");
+        return None;
+    };
+    let damaged = font.damaged_tables();
+    if let Some(tag) = damaged.iter().find(|tag| REQUIRED_TABLES.contains(tag)) {
+        let message = format!("required '{tag}' table is missing or unreadable");
+        log(HarfRustLogLevel::Error, &message);
+        error::set(HarfRustStatus::InvalidFont, message);
+        return None;
+    }
+    if damaged.is_empty() {
+        let _ = font.degraded.set(false);
+        return Some(font);
+    }
+
+    // Collections are not rebuilt; their damaged tables are kept.
+    let rebuilt = repair::rebuild(font.inner.data(), &damaged)
+        .and_then(|data| create_font(Arc::new(FontInner::new(data)), Some(index)))
+        .filter(HarfRustFont::validate);
+    let action = if rebuilt.is_some() { "dropped" } else { "kept" };
+    for tag in &damaged {
+        log(HarfRustLogLevel::Warning, &format!("unreadable '{tag}' table {action}"));
+    }
+    let Some(rebuilt) = rebuilt else {
+        let _ = font.degraded.set(layout_damaged(&damaged));
+        return Some(font);
+    };
+    let _ = rebuilt.degraded.set(false);
+    Some(rebuilt)
+}
+
+/// Tables whose damage switches shaping to `fallback::shape`.
+const LAYOUT_TABLES: [Tag; 3] = [Tag::new(b"GDEF"), Tag::new(b"GSUB"), Tag::new(b"GPOS")];
+
+/// Whether `damaged` (see `HarfRustFont::damaged_tables`) includes a layout
+/// table.
+fn layout_damaged(damaged: &[Tag]) -> bool {
+    damaged.iter().any(|tag| LAYOUT_TABLES.contains(tag))
+}
+
+static NEXT_FONT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn create_font(inner: Arc<FontInner>, index: Option<u32>) -> Option<HarfRustFont> {
+    let data: &'static [u8] = unsafe { std::mem::transmute(inner.data()) };
+
+    let font_ref = match index {
+        Some(index) => harfrust::FontRef::from_index(data, index),
+        None => harfrust::FontRef::new(data),
+    };
+    let font_ref = match font_ref {
+        Ok(font_ref) => font_ref,
+        Err(e) => {
+            error::set(HarfRustStatus::InvalidFont, format!("font data cannot be parsed: {e}"));
+            return None;
+        }
+    };
+    let shaper_data = harfrust::ShaperData::new(&font_ref);
+    let units_per_em = shaper_data.shaper(&font_ref).build().units_per_em();
+    let num_glyphs = font_ref.glyph_metrics(Size::unscaled(), LocationRef::default()).glyph_count();
+
+    let font = HarfRustFont {
+        tag: TypeTag::new(),
+        font_ref,
+        shaper_data,
+        units_per_em,
+        face_index: index.unwrap_or(0),
+        id: NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed),
+        refs: AtomicUsize::new(1),
+        advance_cache: AdvanceCache::new(num_glyphs),
+        plan_cache: PlanCache::default(),
+        degraded: OnceLock::new(),
+        simple_glyphs: OnceLock::new(),
+        data_hash: OnceLock::new(),
+        feature_overrides: RwLock::default(),
+        advance_overrides: RwLock::default(),
+        character_spacing: AtomicI32::new(0),
+        horizontal_scale: AtomicU32::new(1.0f32.to_bits()),
+        frozen: AtomicBool::new(false),
+        inner,
+    };
+    Some(font)
+}
+
+// =============================================================================
+// Buffer functions
+// =============================================================================
+
+/// Creates a new empty buffer for text shaping.
+#[export_name = symbol!("harfrust_buffer_new")]
+pub extern "C" fn harfrust_buffer_new() -> *mut HarfRustBuffer {
+    error::guard(|| {
+        let buffer = HarfRustBuffer::new(scratch::unicode_buffer());
+        handle::into_handle(Box::new(buffer))
+    })
+}
+
+/// Adds a UTF-8 string to the buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `text` must be null or a
+/// NUL-terminated string.
+#[export_name = symbol!("harfrust_buffer_add_str")]
+pub unsafe extern "C" fn harfrust_buffer_add_str(
+    buffer: *mut HarfRustBuffer,
+    text: *const c_char,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null");
+        }
+
+        let c_str = unsafe { CStr::from_ptr(text) };
+        let rust_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(e) => return error::fail(HarfRustStatus::InvalidUtf8, format!("text is not valid UTF-8: {e}")),
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        for (i, ch) in rust_str.char_indices() {
+            buffer_ref.add(ch, i as u32);
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Adds `byte_len` bytes of UTF-8 text to the buffer. Unlike
+/// `harfrust_buffer_add_str` the text needs no NUL terminator, and embedded
+/// NULs are added like any other character. Clusters are byte offsets.
+/// Returns `InvalidUtf8` if the text is not valid UTF-8, in which case
+/// nothing is added.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `text` must be null or point
+/// to `byte_len` bytes.
+#[export_name = symbol!("harfrust_buffer_add_utf8")]
+pub unsafe extern "C" fn harfrust_buffer_add_utf8(
+    buffer: *mut HarfRustBuffer,
+    text: *const u8,
+    byte_len: i32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null");
+        }
+        if byte_len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "byte_len is negative");
+        }
+
+        let bytes = unsafe { std::slice::from_raw_parts(text, byte_len as usize) };
+        let rust_str = match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => return error::fail(HarfRustStatus::InvalidUtf8, format!("text is not valid UTF-8: {e}")),
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        for (i, ch) in rust_str.char_indices() {
+            buffer_ref.add(ch, i as u32);
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Adds a UTF-16 string to the buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `text` must be null or point
+/// to `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_buffer_add_utf16")]
+pub unsafe extern "C" fn harfrust_buffer_add_utf16(
+    buffer: *mut HarfRustBuffer,
+    text: *const u16,
+    len: i32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        if let Err(status) = handle::resolve(buffer) {
+            return status;
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative");
+        }
+
+        unsafe { harfrust_buffer_add_utf16_64(buffer, text, len as u64) }
+    })
+}
+
+/// Same as `harfrust_buffer_add_utf16` with a 64-bit length. Returns
+/// `OutOfRange` if the text is too long for its offsets to fit the 32-bit
+/// cluster values.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `text` must be null or point
+/// to `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_buffer_add_utf16_64")]
+pub unsafe extern "C" fn harfrust_buffer_add_utf16_64(
+    buffer: *mut HarfRustBuffer,
+    text: *const u16,
+    len: u64,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null");
+        }
+        let Some(len) = u32::try_from(len).ok().and_then(|len| usize::try_from(len).ok()) else {
+            return error::fail(HarfRustStatus::OutOfRange, format!("text of {len} code units is too long"));
+        };
+
+        let slice = unsafe { std::slice::from_raw_parts(text, len) };
+        let buffer_ref = unsafe { &mut *buffer };
+        for (ch, cluster) in utf16_chars(slice) {
+            buffer_ref.add(ch, cluster);
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Adds a UTF-16 string to the buffer after applying a case transform.
+/// Characters produced by the transform keep the cluster (UTF-16 offset) of
+/// the original character they came from, so shaped glyphs map back to the
+/// untransformed text, e.g. both glyphs of "SS" from "ß" share its cluster.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `text` must be null or point
+/// to `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_buffer_add_utf16_transformed")]
+pub unsafe extern "C" fn harfrust_buffer_add_utf16_transformed(
+    buffer: *mut HarfRustBuffer,
+    text: *const u16,
+    len: i32,
+    transform: HarfRustCaseTransform,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null");
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative");
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let buffer_ref = unsafe { &mut *buffer };
+        let chars: Vec<(char, u32)> = utf16_chars(slice).collect();
+        for (ch, cluster) in case::transform_case(&chars, transform) {
+            buffer_ref.add(ch, cluster);
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns the number of characters currently in the buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_len")]
+pub unsafe extern "C" fn harfrust_buffer_len(buffer: *const HarfRustBuffer) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        buffer_ref.inner.len() as i32
+    })
+}
+
+/// Clears all content from the buffer, preparing it for reuse.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_clear")]
+pub unsafe extern "C" fn harfrust_buffer_clear(buffer: *mut HarfRustBuffer) {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return;
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        buffer_ref.clear();
+    })
+}
+
+/// Saves the buffer's text, context and segment properties (direction,
+/// script, language, flags and cluster level). Since shaping consumes the buffer, a
+/// snapshot taken before a trial shape can be restored into a fresh buffer if
+/// the trial is rejected. Returns null if the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_save_state")]
+pub unsafe extern "C" fn harfrust_buffer_save_state(
+    buffer: *const HarfRustBuffer,
+) -> *mut HarfRustBufferState {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        let state = HarfRustBufferState {
+            tag: TypeTag::new(),
+            text: buffer_ref.text.clone(),
+            direction: buffer_ref.inner.direction(),
+            script: buffer_ref.inner.script(),
+            language: buffer_ref.inner.language(),
+            flags: buffer_ref.inner.flags(),
+            cluster_level: buffer_ref.inner.cluster_level(),
+            context: buffer_ref.context.clone(),
+        };
+        handle::into_handle(Box::new(state))
+    })
+}
+
+/// Replaces the buffer's contents, context and segment properties with a
+/// saved state.
+/// The state is not consumed and can be restored any number of times.
+/// Returns `NullArgument` if the buffer or state is null.
+///
+/// # Safety
+///
+/// `buffer` and `state` must be null or live handles.
+#[export_name = symbol!("harfrust_buffer_restore_state")]
+pub unsafe extern "C" fn harfrust_buffer_restore_state(
+    buffer: *mut HarfRustBuffer,
+    state: *const HarfRustBufferState,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        let state = match handle::resolve(state) {
+            Ok(state) => state,
+            Err(status) => return status,
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        let state_ref = unsafe { &*state };
+
+        buffer_ref.clear();
+        buffer_ref.inner.reserve(state_ref.text.len());
+        for &(ch, cluster) in &state_ref.text {
+            buffer_ref.add(ch, cluster);
+        }
+        buffer_ref.inner.set_direction(state_ref.direction);
+        buffer_ref.inner.set_script(state_ref.script);
+        if let Some(language) = state_ref.language.clone() {
+            buffer_ref.inner.set_language(language);
+        }
+        buffer_ref.inner.set_flags(state_ref.flags);
+        buffer_ref.inner.set_cluster_level(state_ref.cluster_level);
+        buffer_ref.context = state_ref.context.clone();
+        buffer_ref.inner.set_pre_context(&buffer_ref.context[0]);
+        buffer_ref.inner.set_post_context(&buffer_ref.context[1]);
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Frees a state previously created by `harfrust_buffer_save_state`.
+///
+/// # Safety
+///
+/// `state` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_state_free")]
+pub unsafe extern "C" fn harfrust_buffer_state_free(state: *mut HarfRustBufferState) {
+    error::guard(|| {
+        if state.is_null() {
+            return;
+        }
+        if let Ok(state) = handle::resolve(state) {
+            unsafe { drop(handle::release(state)) };
+        }
+    })
+}
+
+/// Frees a buffer previously created by `harfrust_buffer_new`.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_free")]
+pub unsafe extern "C" fn harfrust_buffer_free(buffer: *mut HarfRustBuffer) {
+    error::guard(|| {
+        if buffer.is_null() {
+            return;
+        }
+        if let Ok(buffer) = handle::resolve(buffer) {
+            let buffer_box = unsafe { handle::release(buffer) };
+            scratch::recycle(buffer_box.inner);
+        }
+    })
+}
+
+// =============================================================================
+// Buffer configuration functions (Phase 2)
+// =============================================================================
+
+/// Sets the text direction of the buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_set_direction")]
+pub unsafe extern "C" fn harfrust_buffer_set_direction(
+    buffer: *mut HarfRustBuffer,
+    direction: HarfRustDirection,
+) {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return;
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        buffer_ref.inner.set_direction(direction.into());
+    })
+}
+
+/// Gets the text direction of the buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_get_direction")]
+pub unsafe extern "C" fn harfrust_buffer_get_direction(
+    buffer: *const HarfRustBuffer,
+) -> HarfRustDirection {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return HarfRustDirection::Invalid;
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        buffer_ref.inner.direction().into()
+    })
+}
+
+/// Sets the script of the buffer using an ISO 15924 tag (4 bytes as u32).
+/// Example: "Latn" = 0x4C61746E
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_set_script")]
+pub unsafe extern "C" fn harfrust_buffer_set_script(buffer: *mut HarfRustBuffer, script_tag: u32) {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return;
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        let tag = harfrust::Tag::new(&script_tag.to_be_bytes());
+        if let Some(script) = harfrust::Script::from_iso15924_tag(tag) {
+            buffer_ref.inner.set_script(script);
+        }
+    })
+}
+
+/// Gets the script of the buffer as an ISO 15924 tag (4 bytes as u32).
+/// Returns 0 if no script is set.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_get_script")]
+pub unsafe extern "C" fn harfrust_buffer_get_script(buffer: *const HarfRustBuffer) -> u32 {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return 0;
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        let tag = buffer_ref.inner.script().tag();
+        u32::from_be_bytes(tag.into_bytes())
+    })
+}
+
+/// Sets the language of the buffer from a BCP 47 language tag string.
+/// Example: "en", "en-US", "zh-Hans"
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `language` must be null or a
+/// NUL-terminated string.
+#[export_name = symbol!("harfrust_buffer_set_language")]
+pub unsafe extern "C" fn harfrust_buffer_set_language(
+    buffer: *mut HarfRustBuffer,
+    language: *const c_char,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if language.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "language is null");
+        }
+
+        let c_str = unsafe { CStr::from_ptr(language) };
+        let lang_str = match c_str.to_str() {
+            Ok(s) => s,
+            Err(e) => return error::fail(HarfRustStatus::InvalidUtf8, format!("language is not valid UTF-8: {e}")),
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        if let Ok(lang) = lang_str.parse::<harfrust::Language>() {
+            buffer_ref.inner.set_language(lang);
+            HarfRustStatus::Ok
+        } else {
+            error::fail(HarfRustStatus::InvalidLanguage, format!("invalid language tag '{lang_str}'"))
+        }
+    })
+}
+
+/// Sets how the buffer shapes soft hyphens (U+00AD), for text added before or
+/// after the call; see `HarfRustSoftHyphen`. Soft hyphens shown as a hyphen
+/// keep their own cluster, so the glyph still maps back to the soft hyphen.
+/// The setting survives `harfrust_buffer_clear`. Returns `NullArgument` if
+/// the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_set_soft_hyphen")]
+pub unsafe extern "C" fn harfrust_buffer_set_soft_hyphen(
+    buffer: *mut HarfRustBuffer,
+    mode: HarfRustSoftHyphen,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        if buffer_ref.soft_hyphen != mode {
+            buffer_ref.soft_hyphen = mode;
+            if buffer_ref.text.iter().any(|&(ch, _)| ch == SOFT_HYPHEN) {
+                buffer_ref.rebuild();
+            }
+        }
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns how the buffer shapes soft hyphens, or `Hidden` if the buffer is
+/// null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_get_soft_hyphen")]
+pub unsafe extern "C" fn harfrust_buffer_get_soft_hyphen(
+    buffer: *const HarfRustBuffer,
+) -> HarfRustSoftHyphen {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return HarfRustSoftHyphen::Hidden;
+        };
+
+        unsafe { &*buffer }.soft_hyphen
+    })
+}
+
+/// Enables (non-zero) or disables (0, the default) shaping no-break, fixed
+/// width and narrow spaces (U+00A0, U+2000 to U+200A, U+202F and U+205F) as
+/// an ordinary space, with its glyph and advance, when the font has no glyph
+/// for them, as is common in subset fonts embedded in PDFs. Otherwise such
+/// spaces are shaped with the space glyph at a width derived from the em, or
+/// as .notdef without layout tables. Applies to the shape call consuming the
+/// buffer, and to the copies `harfrust_shape_copy` and `harfrust_shape_into`
+/// shape; results report it in `harfrust_glyph_buffer_get_flags`. The
+/// setting survives `harfrust_buffer_clear`. Returns `NullArgument` if the
+/// buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_set_normalize_spaces")]
+pub unsafe extern "C" fn harfrust_buffer_set_normalize_spaces(
+    buffer: *mut HarfRustBuffer,
+    enabled: i32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+
+        unsafe { &mut *buffer }.normalize_spaces = enabled != 0;
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns 1 if the buffer normalizes missing spaces, 0 if not, or a negative
+/// status if the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_get_normalize_spaces")]
+pub unsafe extern "C" fn harfrust_buffer_get_normalize_spaces(buffer: *const HarfRustBuffer) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        unsafe { &*buffer }.normalize_spaces as i32
+    })
+}
+
+/// Sets the UTF-16 text around the buffer's: `pre_context` comes before it
+/// and `post_context` after it. Context is not shaped but is seen by shaping
+/// decisions that look past the buffer's ends, such as Arabic joining, so a
+/// run split from a word (e.g. at a font change) keeps the forms it has in
+/// the word. Only the nearest 5 characters on each side are used. Null with
+/// a length of 0 sets no context; clearing the buffer removes it. Returns
+/// `NullArgument` if the buffer, or a text with a non-zero length, is null,
+/// or `InvalidArgument` if a length is negative.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle; `pre_context` must be null or
+/// point to `pre_len` UTF-16 code units; and `post_context` must be null or
+/// point to `post_len` UTF-16 code units.
+#[export_name = symbol!("harfrust_buffer_set_context")]
+pub unsafe extern "C" fn harfrust_buffer_set_context(
+    buffer: *mut HarfRustBuffer,
+    pre_context: *const u16,
+    pre_len: i32,
+    post_context: *const u16,
+    post_len: i32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        let mut context: [String; 2] = Default::default();
+        for (i, (text, len)) in [(pre_context, pre_len), (post_context, post_len)].into_iter().enumerate() {
+            if len < 0 {
+                return error::fail(HarfRustStatus::InvalidArgument, "context length is negative");
+            }
+            if len == 0 {
+                continue;
+            }
+            if text.is_null() {
+                return error::fail(HarfRustStatus::NullArgument, "context is null");
+            }
+            let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+            let chars: Vec<char> = utf16_chars(slice).map(|(ch, _)| ch).collect();
+            // Pre-context ends at the buffer, post-context starts there
+            let kept = if i == 0 {
+                &chars[chars.len().saturating_sub(CONTEXT_LENGTH)..]
+            } else {
+                &chars[..chars.len().min(CONTEXT_LENGTH)]
+            };
+            context[i] = kept.iter().collect();
+        }
+
+        let buffer_ref = unsafe { &mut *buffer };
+        buffer_ref.inner.set_pre_context(&context[0]);
+        buffer_ref.inner.set_post_context(&context[1]);
+        buffer_ref.context = context;
+        HarfRustStatus::Ok
+    })
+}
+
+/// Guesses and sets the segment properties (direction, script, language)
+/// based on the buffer contents.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_buffer_guess_segment_properties")]
+pub unsafe extern "C" fn harfrust_buffer_guess_segment_properties(buffer: *mut HarfRustBuffer) {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return;
+        };
+
+        let buffer_ref = unsafe { &mut *buffer };
+        buffer_ref.inner.guess_segment_properties();
+    })
+}
+
+/// Detects the base direction of a paragraph of UTF-16 text from its first
+/// strong character, skipping isolated (LRI/RLI/FSI...PDI) content.
+/// Returns `Invalid` if the text has no strong characters; passing that to
+/// `harfrust_buffer_set_direction` keeps automatic direction guessing at
+/// shape time.
+///
+/// # Safety
+///
+/// `text` must be null or point to `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_text_detect_base_direction")]
+pub unsafe extern "C" fn harfrust_text_detect_base_direction(
+    text: *const u16,
+    len: i32,
+) -> HarfRustDirection {
+    error::guard(|| {
+        if text.is_null() || len <= 0 {
+            return HarfRustDirection::Invalid;
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        match bidi::first_strong(utf16_chars(slice).map(|(ch, _)| ch)) {
+            Some(bidi::Strong::Left) => HarfRustDirection::LeftToRight,
+            Some(bidi::Strong::Right) => HarfRustDirection::RightToLeft,
+            None => HarfRustDirection::Invalid,
+        }
+    })
+}
+
+// =============================================================================
+// Font functions
+// =============================================================================
+
+/// Creates a font from raw font data (TTF/OTF bytes, or WOFF with the `woff`
+/// feature). The data is copied. Malformed fonts (e.g. embedded in PDFs with
+/// a bad sfnt version, truncated tables or a directory that overruns the
+/// data) are repaired as `harfrust_font_repair` does when they would
+/// otherwise fail to load or lack core tables. Fonts created from identical
+/// data share a single copy of it.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_from_data")]
+pub unsafe extern "C" fn harfrust_font_from_data(data: *const u8, len: i32) -> *mut HarfRustFont {
+    error::guard(|| {
+        if len < 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "len is negative");
+        }
+
+        unsafe { harfrust_font_from_data64(data, len as u64) }
+    })
+}
+
+/// Same as `harfrust_font_from_data` with a 64-bit length, for data of 2 GB
+/// or more. Returns null if the data cannot be addressed or copied on this
+/// platform, or is larger than the 4 GB a font file can describe.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_from_data64")]
+pub unsafe extern "C" fn harfrust_font_from_data64(data: *const u8, len: u64) -> *mut HarfRustFont {
+    error::guard(|| {
+        if data.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "data is null");
+        }
+        if len == 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "data is empty");
+        }
+        let Some(len) = u32::try_from(len).ok().and_then(|len| usize::try_from(len).ok()) else {
+            return error::fail_null(HarfRustStatus::OutOfRange, format!("{len} bytes of font data cannot be addressed"));
+        };
+
+        let slice = unsafe { std::slice::from_raw_parts(data, len) };
+        match create_owned_font(slice, None) {
+            Some(wrapper) => handle::into_handle(Box::new(wrapper)),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Creates a font from raw font data at a specific index (for font collections).
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_from_data_index")]
+pub unsafe extern "C" fn harfrust_font_from_data_index(
+    data: *const u8,
+    len: i32,
+    index: u32,
+) -> *mut HarfRustFont {
+    error::guard(|| {
+        if data.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "data is null");
+        }
+        if len <= 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "data is empty");
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
+        match create_owned_font(slice, Some(index)) {
+            Some(wrapper) => handle::into_handle(Box::new(wrapper)),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Creates a font from raw font data at a specific index, choosing between
+/// lazy, eager and tolerant parsing. In eager mode the core tables are
+/// validated and caches are warmed before returning, and null is returned if
+/// validation fails. In tolerant mode recoverable corruption is repaired or
+/// skipped and reported as warnings through the log callback (see
+/// `harfrust_set_log_callback`).
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_from_data_with_mode")]
+pub unsafe extern "C" fn harfrust_font_from_data_with_mode(
+    data: *const u8,
+    len: i32,
+    index: u32,
+    mode: HarfRustParseMode,
+) -> *mut HarfRustFont {
+    error::guard(|| {
+        if data.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "data is null");
+        }
+        if len <= 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "data is empty");
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
+        let wrapper = match mode {
+            HarfRustParseMode::Tolerant => create_tolerant_font(slice, index),
+            _ => create_owned_font(slice, Some(index)),
+        };
+        let Some(wrapper) = wrapper else {
+            return std::ptr::null_mut();
+        };
+
+        if mode == HarfRustParseMode::Eager {
+            if let Some(tag) = wrapper.damaged_tables().first() {
+                return error::fail_null(HarfRustStatus::InvalidFont, format!("'{tag}' table is missing or unreadable"));
+            }
+            let _ = wrapper.degraded.set(false);
+            wrapper.warm_up();
+        }
+
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Creates a font that reads `data` in place instead of copying it. The
+/// memory must stay valid and unmoved until `release_callback` is invoked
+/// with `user_data`, which happens when the font is freed, or before this
+/// function returns if creation fails. The callback may be null.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes that stay valid and unmoved
+/// until `release_callback` is invoked, and `release_callback` must be null
+/// or safe to call with `user_data` on any thread.
+#[export_name = symbol!("harfrust_font_from_data_borrowed")]
+pub unsafe extern "C" fn harfrust_font_from_data_borrowed(
+    data: *const u8,
+    len: i32,
+    release_callback: HarfRustReleaseCallback,
+    user_data: *mut c_void,
+) -> *mut HarfRustFont {
+    error::guard(|| {
+        let inner = FontInner::Borrowed {
+            data,
+            len: len.max(0) as usize,
+            release: release_callback,
+            user_data,
+        };
+        if data.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "data is null");
+        }
+        if len <= 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "data is empty");
+        }
+
+        match create_font(Arc::new(inner), None) {
+            Some(wrapper) => handle::into_handle(Box::new(wrapper)),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Creates a font from a bare CFF font program, such as a PDF FontFile3
+/// stream (Type1C or CIDFontType0C), by wrapping it in a minimal OpenType
+/// font. Advances come from the charstrings at 1000 units per em. Glyphs are
+/// mapped to Unicode through "uniXXXX"/"uXXXX" and ASCII glyph names only;
+/// CID-keyed fonts have no Unicode mapping and must be addressed by glyph ID.
+/// The data is copied. Returns null if the data is not a readable CFF font.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_from_cff")]
+pub unsafe extern "C" fn harfrust_font_from_cff(data: *const u8, len: i32) -> *mut HarfRustFont {
+    error::guard(|| {
+        if data.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "data is null");
+        }
+        if len <= 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "data is empty");
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
+        let Some(font_data) = cff::wrap_cff(slice) else {
+            return error::fail_null(HarfRustStatus::InvalidFont, "data is not a readable CFF font");
+        };
+
+        match create_font(Arc::new(FontInner::new(font_data)), None) {
+            Some(wrapper) => handle::into_handle(Box::new(wrapper)),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Repairs common malformations of a single font file, as found in fonts
+/// embedded in PDFs: an unrecognized sfnt version, a table count larger than
+/// the directory, tables running past the end of the data, unsorted or
+/// duplicate table records, a bad `head` magic number and stale checksums.
+/// Returns the rebuilt font, or null if the data is null, a collection, or
+/// has no readable table directory. Free the result with `harfrust_blob_free`.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_repair")]
+pub unsafe extern "C" fn harfrust_font_repair(data: *const u8, len: i32) -> *mut HarfRustBlob {
+    error::guard(|| {
+        if data.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "data is null");
+        }
+        if len <= 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "data is empty");
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
+        match repair::repair(slice) {
+            Some(data) => handle::into_handle(Box::new(HarfRustBlob { tag: TypeTag::new(), data })),
+            None => error::fail_null(HarfRustStatus::InvalidFont, "font data has no readable table directory"),
+        }
+    })
+}
+
+/// Creates a font from face `face_index` of the font file at `path` (UTF-8),
+/// memory-mapping the file instead of reading it into memory. The file must
+/// not be modified while the font is alive. Returns null if the file cannot
+/// be opened or the face cannot be parsed.
+///
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string.
+#[export_name = symbol!("harfrust_font_from_file")]
+pub unsafe extern "C" fn harfrust_font_from_file(
+    path: *const c_char,
+    face_index: u32,
+) -> *mut HarfRustFont {
+    error::guard(|| {
+        if path.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "path is null");
+        }
+
+        let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+            return error::fail_null(HarfRustStatus::InvalidUtf8, "path is not valid UTF-8");
+        };
+        let inner = match FontInner::from_file(path) {
+            Ok(inner) => inner,
+            Err(e) => return error::fail_null(HarfRustStatus::Io, format!("cannot open '{path}': {e}")),
+        };
+
+        match create_font(Arc::new(inner), Some(face_index)) {
+            Some(wrapper) => handle::into_handle(Box::new(wrapper)),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Returns the number of faces in font data: the face count of a TrueType
+/// collection (.ttc/.otc), 1 for a single font, or 0 if the data is null or
+/// not a font. Valid indices for `harfrust_font_from_data_index` are
+/// 0 to count - 1.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` bytes.
+#[export_name = symbol!("harfrust_font_collection_count")]
+pub unsafe extern "C" fn harfrust_font_collection_count(data: *const u8, len: i32) -> i32 {
+    error::guard(|| {
+        if data.is_null() || len <= 0 {
+            return 0;
+        }
+
+        let slice = unsafe { std::slice::from_raw_parts(data, len as usize) };
+        match skrifa::raw::FileRef::new(slice) {
+            Ok(skrifa::raw::FileRef::Font(_)) => 1,
+            Ok(skrifa::raw::FileRef::Collection(collection)) => collection.len() as i32,
+            Err(_) => 0,
+        }
+    })
+}
+
+/// Returns the index of the face the font was created from (0 for fonts that
+/// are not part of a collection), or -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_get_index")]
+pub unsafe extern "C" fn harfrust_font_get_index(font: *const HarfRustFont) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper.face_index as i32
+    })
+}
+
+/// Returns a 64-bit FNV-1a hash of the font file data (after WOFF decoding;
+/// the whole file for a face of a collection), or 0 if the font is null.
+/// Fonts created from identical bytes share one copy of the data and report
+/// the same hash, which callers can use to deduplicate embedded fonts.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_data_hash")]
+pub unsafe extern "C" fn harfrust_font_data_hash(font: *const HarfRustFont) -> u64 {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return 0;
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper.data_hash()
+    })
+}
+
+/// Writes a key identifying a glyph as rendered from this font at the given
+/// variation settings and pixels-per-em to `out`. The key combines the font
+/// data hash, face index, glyph id, normalized axis coordinates and size, so
+/// it is stable across processes and identical for settings that resolve to
+/// the same instance (omitted axes and explicit defaults, out-of-range values
+/// and their clamped limits). Returns `NullArgument` if the font or `out` is
+/// null or `variations` is null with a non-zero count, `OutOfRange` if `ppem`
+/// is negative or not finite.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `variations` must be null or point
+/// to `num_variations` variations; and `out` must be null or point to a
+/// writable `HarfRustCacheKey`.
+#[export_name = symbol!("harfrust_glyph_cache_key")]
+pub unsafe extern "C" fn harfrust_glyph_cache_key(
+    font: *const HarfRustFont,
+    glyph_id: u32,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+    ppem: f32,
+    out: *mut HarfRustCacheKey,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() || (variations.is_null() && num_variations > 0) {
+            return error::fail(HarfRustStatus::NullArgument, "out or variations is null");
+        }
+        if !ppem.is_finite() || ppem < 0.0 {
+            return error::fail(HarfRustStatus::OutOfRange, format!("invalid ppem {ppem}"));
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let variations = unsafe { variation_slice(variations, num_variations) };
+        let mut key = font_wrapper.key_prefix(variations);
+        key.extend_from_slice(&glyph_id.to_le_bytes());
+        // Adding zero maps -0.0 to 0.0.
+        key.extend_from_slice(&(ppem + 0.0).to_bits().to_le_bytes());
+
+        unsafe { *out = HarfRustCacheKey::from_bytes(&key) };
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns 1 if the font's GSUB, GPOS or GDEF table exists but cannot be
+/// read, so every shaping function shapes it as `harfrust_shape_fallback`
+/// does; 0 if not; -1 if the font is null. Unless the font was created in
+/// `HarfRustParseMode::Eager` or `Tolerant` mode, the tables are read on the
+/// first call to this or a shaping function.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_is_degraded")]
+pub unsafe extern "C" fn harfrust_font_is_degraded(font: *const HarfRustFont) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper.degraded() as i32
+    })
+}
+
+/// Returns the font's units per em.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_units_per_em")]
+pub unsafe extern "C" fn harfrust_font_units_per_em(font: *const HarfRustFont) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper.units_per_em
+    })
+}
+
+/// Returns the number of glyphs in the font (maxp numGlyphs), so valid glyph
+/// IDs are `0..count`. Returns -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_glyph_count")]
+pub unsafe extern "C" fn harfrust_font_glyph_count(font: *const HarfRustFont) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper.advance_cache.num_glyphs as i32
+    })
+}
+
+/// Returns the horizontal advance of a glyph in font units.
+/// Results are cached per font, so repeated width-only queries are cheap.
+/// Returns -1 (`NullArgument`) if the font is null or -3 (`OutOfRange`) if
+/// the glyph ID is out of range.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_glyph_advance")]
+pub unsafe extern "C" fn harfrust_font_glyph_advance(font: *const HarfRustFont, glyph_id: u32) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper
+            .advances(&[])
+            .get(glyph_id)
+            .unwrap_or_else(|| {
+                error::fail(HarfRustStatus::OutOfRange, format!("glyph {glyph_id} is out of range")) as i32
+            })
+    })
+}
+
+/// Returns the horizontal advance of a glyph in font units at a variation
+/// instance from `harfrust_instance_new` (null for the default instance).
+/// Each instance's advances are cached separately. Returns the same errors as
+/// `harfrust_font_glyph_advance`.
+///
+/// # Safety
+///
+/// `font` and `instance` must be null or live handles.
+#[export_name = symbol!("harfrust_font_glyph_advance_with_instance")]
+pub unsafe extern "C" fn harfrust_font_glyph_advance_with_instance(
+    font: *const HarfRustFont,
+    instance: *const HarfRustInstance,
+    glyph_id: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        let instance = match handle::resolve_optional(instance) {
+            Ok(instance) => instance,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let coords = instance.map_or(&[][..], |instance| unsafe { (*instance).inner.coords() });
+        font_wrapper
+            .advances(coords)
+            .get(glyph_id)
+            .unwrap_or_else(|| {
+                error::fail(HarfRustStatus::OutOfRange, format!("glyph {glyph_id} is out of range")) as i32
+            })
+    })
+}
+
+/// Copies the horizontal advance of every glyph (indexed by glyph ID) into `out`.
+/// At most `capacity` entries are written. Returns the total number of glyphs
+/// in the font, so a call with a null `out` can be used to size the array.
+/// Returns -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or have room
+/// for `capacity` entries.
+#[export_name = symbol!("harfrust_font_all_advances")]
+pub unsafe extern "C" fn harfrust_font_all_advances(
+    font: *const HarfRustFont,
+    out: *mut u16,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let num_glyphs = font_wrapper.advance_cache.num_glyphs;
+        if out.is_null() || capacity == 0 {
+            return num_glyphs as i32;
+        }
+
+        let count = num_glyphs.min(capacity);
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count as usize) };
+        let advances = font_wrapper.advances(&[]);
+        for (glyph_id, slot) in out_slice.iter_mut().enumerate() {
+            let advance = advances.get(glyph_id as u32).unwrap_or(0);
+            *slot = advance.clamp(0, u16::MAX as i32) as u16;
+        }
+
+        num_glyphs as i32
+    })
+}
+
+/// Writes the ink extents of the `count` glyphs in `glyphs` to `out`, which
+/// must have room for `count` entries, so that e.g. an atlas builder can
+/// measure many glyphs in one call. Edges are rounded outwards. Glyphs
+/// without an outline (such as spaces) and glyph IDs out of range get zero
+/// extents. Returns `NullArgument` if the font, `glyphs` or `out` is null
+/// (the arrays may be null if `count` is 0).
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `glyphs` must be null or point to
+/// `count` glyph IDs; and `out` must be null or have room for `count`
+/// entries.
+#[export_name = symbol!("harfrust_font_glyph_extents_batch")]
+pub unsafe extern "C" fn harfrust_font_glyph_extents_batch(
+    font: *const HarfRustFont,
+    glyphs: *const u32,
+    count: u32,
+    out: *mut HarfRustGlyphExtents,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if count == 0 {
+            return HarfRustStatus::Ok;
+        }
+        if glyphs.is_null() || out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "glyphs or out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let glyphs = unsafe { std::slice::from_raw_parts(glyphs, count as usize) };
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count as usize) };
+        let metrics = font_wrapper.glyph_metrics();
+        for (&glyph_id, slot) in glyphs.iter().zip(out_slice) {
+            *slot = match metrics.bounds(GlyphId::new(glyph_id)) {
+                Some(bounds) => {
+                    let (x_min, y_min) = (bounds.x_min.floor() as i32, bounds.y_min.floor() as i32);
+                    let (x_max, y_max) = (bounds.x_max.ceil() as i32, bounds.y_max.ceil() as i32);
+                    HarfRustGlyphExtents {
+                        x_bearing: x_min,
+                        y_bearing: y_max,
+                        width: x_max - x_min,
+                        height: y_min - y_max,
+                    }
+                }
+                None => HarfRustGlyphExtents::default(),
+            };
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Looks up the glyph the font's best cmap subtable maps a Unicode codepoint
+/// to, without shaping, and writes it to `out_glyph` (0 if the codepoint is
+/// not mapped). Returns 1 if the codepoint is mapped, 0 if not, or -1 if the
+/// font or `out_glyph` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out_glyph` must be null or
+/// point to a writable `u32`.
+#[export_name = symbol!("harfrust_font_nominal_glyph")]
+pub unsafe extern "C" fn harfrust_font_nominal_glyph(
+    font: *const HarfRustFont,
+    codepoint: u32,
+    out_glyph: *mut u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if out_glyph.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out_glyph is null") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let glyph = font_wrapper.font_ref.charmap().map(codepoint);
+        unsafe { *out_glyph = glyph.map_or(0, |glyph| glyph.to_u32()) };
+        glyph.is_some() as i32
+    })
+}
+
+/// Copies all (codepoint, glyph) pairs of the font's best cmap subtable into
+/// `out`, ordered by codepoint. At most `capacity` entries are written.
+/// Returns the total number of mappings, so a call with a null `out` can be
+/// used to size the array. Returns -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or have room
+/// for `capacity` entries.
+#[export_name = symbol!("harfrust_font_cmap_mappings")]
+pub unsafe extern "C" fn harfrust_font_cmap_mappings(
+    font: *const HarfRustFont,
+    out: *mut HarfRustCmapEntry,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mappings = font_wrapper.font_ref.charmap().mappings();
+        if out.is_null() || capacity == 0 {
+            return mappings.count() as i32;
+        }
+
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, capacity as usize) };
+        let mut total = 0usize;
+        for (codepoint, glyph_id) in mappings {
+            if let Some(slot) = out_slice.get_mut(total) {
+                *slot = HarfRustCmapEntry {
+                    codepoint,
+                    glyph_id: glyph_id.to_u32(),
+                };
+            }
+            total += 1;
+        }
+
+        total as i32
+    })
+}
+
+/// Checks whether the font renders a character sequence (e.g. an emoji ZWJ
+/// sequence) as a single glyph.
+/// The sequence is shaped and counts as supported when it forms one cluster
+/// with exactly one visible glyph and no .notdef glyphs; zero-advance glyphs
+/// such as hidden joiners are ignored.
+/// Returns 1 if supported, 0 if not, or a negative value on invalid arguments.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `text` must be null or point to
+/// `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_font_supports_sequence")]
+pub unsafe extern "C" fn harfrust_font_supports_sequence(
+    font: *const HarfRustFont,
+    text: *const u16,
+    len: i32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null") as i32;
+        }
+        if len <= 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is not positive") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+
+        let mut buffer = harfrust::UnicodeBuffer::new();
+        push_utf16(&mut buffer, slice);
+        buffer.guess_segment_properties();
+
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+        let glyph_buffer = font_wrapper.shape(&shaper, None, utf16_chars(slice), buffer, &[]);
+        let infos = &glyph_buffer.infos_cache;
+        let positions = &glyph_buffer.positions_cache;
+
+        let first_cluster = infos.first().map(|info| info.cluster);
+        let single_cluster = infos.iter().all(|info| Some(info.cluster) == first_cluster);
+        let has_notdef = infos.iter().any(|info| info.glyph_id == 0);
+        let visible = positions
+            .iter()
+            .filter(|pos| pos.x_advance != 0 || pos.y_advance != 0)
+            .count();
+
+        (!infos.is_empty() && single_cluster && !has_notdef && visible == 1) as i32
+    })
+}
+
+/// Checks whether UTF-16 text fits within `max_width` font units.
+/// Sums the nominal (cmap + hmtx) advances without shaping and stops as soon
+/// as the limit is exceeded, so kerning and ligatures are not taken into
+/// account. The font's advance overrides, character spacing (once per
+/// character and the marks following it, as shaping adds it once per
+/// cluster) and horizontal scaling apply. Returns 1 if the text fits, 0 if not, or a
+/// negative value on invalid arguments.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `text` must be null or point to
+/// `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_text_fits")]
+pub unsafe extern "C" fn harfrust_text_fits(
+    font: *const HarfRustFont,
+    text: *const u16,
+    len: i32,
+    max_width: i32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative") as i32;
+        }
+        if text.is_null() && len > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "text is null") as i32;
+        }
+        if len == 0 {
+            return (max_width >= 0) as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let charmap = font_wrapper.font_ref.charmap();
+        let advances = font_wrapper.advances(&[]);
+        let overrides = font_wrapper.advance_overrides.read().unwrap_or_else(|e| e.into_inner());
+
+        let scale = font_wrapper.horizontal_scale() as f64;
+        let spacing = font_wrapper.character_spacing.load(Ordering::Relaxed) as i64;
+
+        let mut width: i64 = 0;
+        for (i, c) in std::char::decode_utf16(slice.iter().cloned()).enumerate() {
+            let ch = c.unwrap_or(std::char::REPLACEMENT_CHARACTER);
+            let glyph_id = charmap.map(ch).map(|g| g.to_u32()).unwrap_or(0);
+            let advance = overrides.get(&glyph_id).copied().or_else(|| advances.get(glyph_id));
+            width += advance.unwrap_or(0) as i64;
+            // Marks join the cluster of the character before them
+            if i == 0 || !scripts::is_mark(ch) {
+                width += spacing;
+            }
+            if width as f64 * scale > max_width as f64 {
+                return 0;
+            }
+        }
+
+        1
+    })
+}
+
+/// Eagerly does the parsing and cache filling that would otherwise happen on
+/// the first shape or metrics call: walks the cmap, resolves every glyph
+/// advance and shapes a short sample so the shaper's lookup data is built.
+/// Safe to call from a background thread while the font is otherwise unused.
+/// Returns `NullArgument` if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_warmup")]
+pub unsafe extern "C" fn harfrust_font_warmup(font: *const HarfRustFont) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        font_wrapper.warm_up();
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Freezes a font: from now on no call changes how it shapes or measures, so
+/// the handle can be shared by any number of threads without further
+/// synchronization. Its lazily filled caches stay internally synchronized
+/// and do not change results. Functions that would change the font (such as
+/// `harfrust_font_set_feature_overrides`) fail with `Unsupported` on a frozen
+/// font, including through handles from `harfrust_font_retain`. Freezing is
+/// permanent, and freezing a frozen font does nothing.
+/// Returns `NullArgument` if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_freeze")]
+pub unsafe extern "C" fn harfrust_font_freeze(font: *const HarfRustFont) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+
+        unsafe { &*font }.frozen.store(true, Ordering::Release);
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns 1 if the font is frozen (see `harfrust_font_freeze`), 0 if not,
+/// or -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_is_frozen")]
+pub unsafe extern "C" fn harfrust_font_is_frozen(font: *const HarfRustFont) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        unsafe { &*font }.frozen.load(Ordering::Acquire) as i32
+    })
+}
+
+/// Writes the font's superscript or subscript size and offset (OS/2
+/// ySuperscript* and ySubscript* values, in font units) to `out`. Fonts without
+/// usable OS/2 values report conventional defaults.
+/// Returns `NullArgument` if the font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustScriptMetrics`.
+#[export_name = symbol!("harfrust_font_get_script_metrics")]
+pub unsafe extern "C" fn harfrust_font_get_script_metrics(
+    font: *const HarfRustFont,
+    position: HarfRustScriptPosition,
+    out: *mut HarfRustScriptMetrics,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        unsafe { *out = font_wrapper.script_metrics(position) };
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's line layout metrics to `out` in one call. Ascent,
+/// descent and line gap follow the font's preferred vertical metrics (OS/2
+/// typographic when USE_TYPO_METRICS is set, else hhea); the x-height and cap
+/// height are those of `harfrust_font_get_x_height` and
+/// `harfrust_font_get_cap_height`. The baseline of a line lies `ascent` below
+/// its top, and lines are `ascent - descent + line_gap` apart. Returns
+/// `NullArgument` if the font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustFontMetrics`.
+#[export_name = symbol!("harfrust_font_get_metrics")]
+pub unsafe extern "C" fn harfrust_font_get_metrics(
+    font: *const HarfRustFont,
+    out: *mut HarfRustFontMetrics,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let font_metrics = font_wrapper.font_ref.metrics(Size::unscaled(), LocationRef::default());
+        unsafe {
+            *out = HarfRustFontMetrics {
+                units_per_em: font_wrapper.units_per_em,
+                ascent: font_metrics.ascent.round() as i32,
+                descent: font_metrics.descent.round() as i32,
+                line_gap: font_metrics.leading.round() as i32,
+                x_height: metrics::x_height(&font_wrapper.font_ref).0,
+                cap_height: metrics::cap_height(&font_wrapper.font_ref).0,
+            };
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's weight and width classes, fsSelection style bits and
+/// fsType embedding permissions to `out`. Fonts without an OS/2 table report
+/// regular weight, normal width, the italic and bold bits of the head table's
+/// macStyle, and no embedding restrictions. Returns `NullArgument` if the
+/// font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustFontStyle`.
+#[export_name = symbol!("harfrust_font_get_style")]
+pub unsafe extern "C" fn harfrust_font_get_style(
+    font: *const HarfRustFont,
+    out: *mut HarfRustFontStyle,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_ref = &unsafe { &*font }.font_ref;
+        let mut style = match font_ref.os2() {
+            Ok(os2) => HarfRustFontStyle {
+                weight_class: os2.us_weight_class() as u32,
+                width_class: os2.us_width_class() as u32,
+                fs_selection: os2.fs_selection().bits() as u32,
+                fs_type: os2.fs_type() as u32,
+                ..Default::default()
+            },
+            Err(_) => {
+                // macStyle bit 0 is bold and bit 1 italic
+                let mac_style = font_ref.head().map_or(0, |head| head.mac_style().bits());
+                let (bold, italic) = (mac_style & 1 != 0, mac_style & 2 != 0);
+                HarfRustFontStyle {
+                    weight_class: if bold { 700 } else { 400 },
+                    width_class: 5,
+                    fs_selection: if bold || italic { (bold as u32) << 5 | italic as u32 } else { 0x40 },
+                    ..Default::default()
+                }
+            }
+        };
+        // Bits 0-3 are the usage permissions; only a restricted license, or
+        // bitmaps only, forbids embedding outlines
+        style.embeddable = (style.fs_type & 0x0F != 0x02 && style.fs_type & 0x200 == 0) as u32;
+        style.subsettable = (style.fs_type & 0x100 == 0) as u32;
+        unsafe { *out = style };
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's italic angle, underline position and thickness, and
+/// whether it is monospaced, from the `post` table, to `out`. Fonts without a
+/// `post` table report an upright proportional font with an underline 10% of
+/// the em below the baseline, 5% of the em thick. Returns `NullArgument` if
+/// the font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustPostInfo`.
+#[export_name = symbol!("harfrust_font_post_info")]
+pub unsafe extern "C" fn harfrust_font_post_info(
+    font: *const HarfRustFont,
+    out: *mut HarfRustPostInfo,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let info = match font_wrapper.font_ref.post() {
+            Ok(post) => HarfRustPostInfo {
+                italic_angle: post.italic_angle().to_f32(),
+                underline_position: post.underline_position().to_i16() as i32,
+                underline_thickness: post.underline_thickness().to_i16() as i32,
+                is_fixed_pitch: (post.is_fixed_pitch() != 0) as u32,
+            },
+            Err(_) => {
+                let upem = font_wrapper.units_per_em as f32;
+                HarfRustPostInfo {
+                    italic_angle: 0.0,
+                    underline_position: -(upem * 0.1).round() as i32,
+                    underline_thickness: (upem * 0.05).round() as i32,
+                    is_fixed_pitch: 0,
+                }
+            }
+        };
+        unsafe { *out = info };
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's global bounding box, flags and macStyle from the head
+/// table to `out`. The bounding box is what a PDF FontDescriptor declares as
+/// /FontBBox and bounds every glyph, so it can be used to clip rendered runs
+/// conservatively. Fonts without a head table report all zeros.
+/// Returns `NullArgument` if the font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustHeadInfo`.
+#[export_name = symbol!("harfrust_font_head_info")]
+pub unsafe extern "C" fn harfrust_font_head_info(
+    font: *const HarfRustFont,
+    out: *mut HarfRustHeadInfo,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let info = font_wrapper.font_ref.head().map_or(HarfRustHeadInfo::default(), |head| HarfRustHeadInfo {
+            x_min: head.x_min() as i32,
+            y_min: head.y_min() as i32,
+            x_max: head.x_max() as i32,
+            y_max: head.y_max() as i32,
+            flags: head.flags().bits() as u32,
+            mac_style: head.mac_style().bits() as u32,
+        });
+        unsafe { *out = info };
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's strikeout position and thickness (OS/2
+/// yStrikeoutPosition and yStrikeoutSize, in font units) to `out`, for
+/// drawing line-through decorations. Fonts without usable OS/2 values report
+/// a line 25% of the em above the baseline, 5% of the em thick. Superscript
+/// and subscript metrics are available from
+/// `harfrust_font_get_script_metrics`.
+/// Returns `NullArgument` if the font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustStrikeout`.
+#[export_name = symbol!("harfrust_font_get_strikeout")]
+pub unsafe extern "C" fn harfrust_font_get_strikeout(
+    font: *const HarfRustFont,
+    out: *mut HarfRustStrikeout,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let strikeout = font_wrapper.font_ref.os2().ok().map(|os2| HarfRustStrikeout {
+            position: os2.y_strikeout_position() as i32,
+            thickness: os2.y_strikeout_size() as i32,
+        });
+        let strikeout = match strikeout {
+            Some(strikeout) if strikeout.thickness > 0 => strikeout,
+            _ => {
+                let upem = font_wrapper.units_per_em as f32;
+                HarfRustStrikeout {
+                    position: (upem * 0.25).round() as i32,
+                    thickness: (upem * 0.05).round() as i32,
+                }
+            }
+        };
+        unsafe { *out = strikeout };
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's cap height in font units to `out_value`. Uses OS/2
+/// sCapHeight when present (version 2+), otherwise the top of the 'H' glyph,
+/// otherwise 70% of the em. `out_source` (optional) receives which was used.
+/// Returns `NullArgument` if the font or `out_value` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `out_value` must be null or point to
+/// a writable `i32`; and `out_source` must be null or point to a writable
+/// `HarfRustMetricSource`.
+#[export_name = symbol!("harfrust_font_get_cap_height")]
+pub unsafe extern "C" fn harfrust_font_get_cap_height(
+    font: *const HarfRustFont,
+    out_value: *mut i32,
+    out_source: *mut HarfRustMetricSource,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out_value.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out_value is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let (value, source) = metrics::cap_height(&font_wrapper.font_ref);
+        unsafe { *out_value = value };
+        if !out_source.is_null() {
+            unsafe { *out_source = source };
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the font's x-height in font units to `out_value`. Uses OS/2
+/// sxHeight when present (version 2+), otherwise the top of the 'x' glyph,
+/// otherwise 50% of the em. `out_source` (optional) receives which was used.
+/// Returns `NullArgument` if the font or `out_value` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `out_value` must be null or point to
+/// a writable `i32`; and `out_source` must be null or point to a writable
+/// `HarfRustMetricSource`.
+#[export_name = symbol!("harfrust_font_get_x_height")]
+pub unsafe extern "C" fn harfrust_font_get_x_height(
+    font: *const HarfRustFont,
+    out_value: *mut i32,
+    out_source: *mut HarfRustMetricSource,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out_value.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out_value is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let (value, source) = metrics::x_height(&font_wrapper.font_ref);
+        unsafe { *out_value = value };
+        if !out_source.is_null() {
+            unsafe { *out_source = source };
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes an estimate of the dominant vertical stem width (PDF StemV) in font
+/// units to `out_value`, measured across the stem of 'l' or 'I'. Fonts
+/// without usable outlines get a heuristic based on the OS/2 weight class.
+/// `out_source` (optional) receives which method was used.
+/// Returns `NullArgument` if the font or `out_value` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `out_value` must be null or point to
+/// a writable `i32`; and `out_source` must be null or point to a writable
+/// `HarfRustMetricSource`.
+#[export_name = symbol!("harfrust_font_estimate_stemv")]
+pub unsafe extern "C" fn harfrust_font_estimate_stemv(
+    font: *const HarfRustFont,
+    out_value: *mut i32,
+    out_source: *mut HarfRustMetricSource,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out_value.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out_value is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let (value, source) = metrics::stem_v(&font_wrapper.font_ref);
+        unsafe { *out_value = value };
+        if !out_source.is_null() {
+            unsafe { *out_source = source };
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the metrics PDF text extraction compares against (units per em,
+/// ascent, descent, default width and missing width) to `out`, so extraction
+/// heuristics and shaping use the same values. Ascent and descent follow the
+/// font's preferred vertical metrics (OS/2 typographic when USE_TYPO_METRICS
+/// is set, else hhea). Returns `NullArgument` if the font or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustPdfMetrics`.
+#[export_name = symbol!("harfrust_font_get_pdf_metrics")]
+pub unsafe extern "C" fn harfrust_font_get_pdf_metrics(
+    font: *const HarfRustFont,
+    out: *mut HarfRustPdfMetrics,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let font_metrics = font_wrapper.font_ref.metrics(Size::unscaled(), LocationRef::default());
+        let advances = font_wrapper.advances(&[]);
+
+        // Most common non-zero advance; ties go to the narrower width.
+        let mut counts: BTreeMap<i32, u32> = BTreeMap::new();
+        for glyph_id in 0..font_wrapper.advance_cache.num_glyphs {
+            match advances.get(glyph_id) {
+                Some(advance) if advance > 0 => *counts.entry(advance).or_default() += 1,
+                _ => {}
+            }
+        }
+        let default_width = counts
+            .iter()
+            .max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+            .map_or(0, |(&advance, _)| advance);
+
+        unsafe {
+            *out = HarfRustPdfMetrics {
+                units_per_em: font_wrapper.units_per_em,
+                ascent: font_metrics.ascent.round() as i32,
+                descent: font_metrics.descent.round() as i32,
+                default_width,
+                missing_width: advances.get(0).unwrap_or(0),
+            }
+        };
+        HarfRustStatus::Ok
+    })
+}
+
+/// Writes the unhinted outline of a glyph as path commands, in a y-up glyph
+/// space of `units_per_em` units (1000 for PDF glyph space), or in font units
+/// if `units_per_em` is 0. Quadratic curves are converted to cubics, so the
+/// commands map directly onto PDF path operators. At most `capacity` commands
+/// are written. Returns the number of commands (0 for glyphs without an
+/// outline, such as spaces), so a call with a null `out` can be used to size
+/// the array. Returns -1 (`NullArgument`) if the font is null, -2
+/// (`InvalidArgument`) if `units_per_em` is negative, or -3 (`OutOfRange`) if
+/// the glyph ID is out of range.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or have room
+/// for `capacity` entries.
+#[export_name = symbol!("harfrust_font_glyph_outline")]
+pub unsafe extern "C" fn harfrust_font_glyph_outline(
+    font: *const HarfRustFont,
+    glyph_id: u32,
+    units_per_em: f32,
+    out: *mut HarfRustPathCommand,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        let font_wrapper = unsafe { &*font };
+        if glyph_id >= font_wrapper.advance_cache.num_glyphs {
+            return error::fail(HarfRustStatus::OutOfRange, format!("glyph {glyph_id} is out of range")) as i32;
+        }
+        if units_per_em.is_nan() || units_per_em < 0.0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "units_per_em is negative or NaN") as i32;
+        }
+
+        let scale = if units_per_em == 0.0 {
+            1.0
+        } else {
+            units_per_em / font_wrapper.units_per_em as f32
+        };
+        let commands = outline::glyph_commands(&font_wrapper.font_ref, GlyphId::new(glyph_id), scale)
+            .unwrap_or_default();
+
+        if !out.is_null() && capacity > 0 {
+            let written = commands.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, written) };
+            out_slice.copy_from_slice(&commands[..written]);
+        }
+
+        commands.len() as i32
+    })
+}
+
+/// Writes the vertical metrics of `count` glyphs to `out`, in PDF glyph space
+/// and sign conventions, for the W2 array of a vertical (Identity-V) CID
+/// font. Advances come from `vmtx` and origins from `VORG` or `vmtx`; fonts
+/// without vertical tables get the advance of a full line (ascent minus
+/// descent) and an origin at the ascender, horizontally centered on the
+/// glyph. Returns the number of glyphs written, -1 if the font is null, or -2
+/// if `glyphs` or `out` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `glyphs` must be null or point to
+/// `count` glyph IDs; and `out` must be null or have room for `count`
+/// entries.
+#[export_name = symbol!("harfrust_font_vertical_metrics")]
+pub unsafe extern "C" fn harfrust_font_vertical_metrics(
+    font: *const HarfRustFont,
+    glyphs: *const u32,
+    count: u32,
+    out: *mut HarfRustVerticalMetrics,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if count == 0 {
+            return 0;
+        }
+        if glyphs.is_null() || out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "glyphs or out is null") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let glyphs = unsafe { std::slice::from_raw_parts(glyphs, count as usize) };
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count as usize) };
+        let vertical = vertical::VerticalMetrics::new(&font_wrapper.font_ref);
+        let scale = 1000.0 / font_wrapper.units_per_em.max(1) as f32;
+
+        for (&glyph_id, slot) in glyphs.iter().zip(out_slice) {
+            let metrics = vertical.get(GlyphId::new(glyph_id));
+            *slot = HarfRustVerticalMetrics {
+                w1y: -metrics.advance * scale,
+                vx: metrics.origin_x * scale,
+                vy: metrics.origin_y * scale,
+            };
+        }
+
+        count as i32
+    })
+}
+
+/// Adds a reference to a font, so that independent owners (e.g. a layout
+/// engine and a glyph cache) can share the handle and each call
+/// `harfrust_font_free` when done; the font and its data are freed by the
+/// last call. Returns `font`, or null if it is null or invalid.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_retain")]
+pub unsafe extern "C" fn harfrust_font_retain(font: *mut HarfRustFont) -> *mut HarfRustFont {
+    error::guard(|| {
+        let Ok(font_object) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+
+        unsafe { &*font_object }.refs.fetch_add(1, Ordering::Relaxed);
+        font
+    })
+}
+
+/// Releases a reference to a font created by `harfrust_font_from_data` (or
+/// added by `harfrust_font_retain`), freeing the font when it was the last.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_free")]
+pub unsafe extern "C" fn harfrust_font_free(font: *mut HarfRustFont) {
+    error::guard(|| {
+        if font.is_null() {
+            return;
+        }
+        if let Ok(font) = handle::resolve(font) {
+            // Release/acquire as in `Arc`, so other owners' uses of the font
+            // happen before it is dropped.
+            if unsafe { &*font }.refs.fetch_sub(1, Ordering::Release) != 1 {
+                return;
+            }
+            std::sync::atomic::fence(Ordering::Acquire);
+            let font = unsafe { handle::release(font) };
+            cache::remove_font(font.id);
+            drop(font);
+        }
+    })
+}
+
+// =============================================================================
+// Feature functions
+// =============================================================================
+
+/// Sets `feature.start`/`feature.end` from a range of UTF-16 code units
+/// (.NET `string` indices) into `text`, converted to the cluster values of a
+/// buffer built with the given `unit`. `char_end` of -1 means the end of the
+/// text, which maps to u32::MAX so the feature also covers later additions;
+/// an explicit `char_end` of `len` stops the feature at the end of `text`.
+/// A range boundary inside a surrogate pair is widened to cover the whole pair.
+/// Returns `NullArgument` if the feature or text is null, `InvalidArgument`
+/// if `len` is negative, or `OutOfRange` if the range is outside the text.
+///
+/// # Safety
+///
+/// `feature` must be null or point to a writable `HarfRustFeature`, and
+/// `text` must be null or point to `len` UTF-16 code units.
+#[export_name = symbol!("harfrust_feature_set_range")]
+pub unsafe extern "C" fn harfrust_feature_set_range(
+    feature: *mut HarfRustFeature,
+    text: *const u16,
+    len: i32,
+    char_start: i32,
+    char_end: i32,
+    unit: HarfRustClusterUnit,
+) -> HarfRustStatus {
+    error::guard(|| {
+        if feature.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "feature is null");
+        }
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null");
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative");
+        }
+        let open_ended = char_end == -1;
+        let char_end = if open_ended { len } else { char_end };
+        if char_start < 0 || char_start > char_end || char_end > len {
+            return error::fail(HarfRustStatus::OutOfRange, format!("character range {char_start}..{char_end} is outside the text"));
+        }
+
+        let text = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let feature_ref = unsafe { &mut *feature };
+        let is_low_surrogate = |i: usize| (0xDC00..0xE000).contains(&text[i]);
+        let is_high_surrogate = |i: usize| (0xD800..0xDC00).contains(&text[i]);
+
+        let mut start = char_start as usize;
+        if start > 0 && start < text.len() && is_low_surrogate(start) && is_high_surrogate(start - 1) {
+            start -= 1;
+        }
+        let mut end = char_end as usize;
+        if end > 0 && end < text.len() && is_low_surrogate(end) && is_high_surrogate(end - 1) {
+            end += 1;
+        }
+
+        feature_ref.start = utf16_offset_to_cluster(text, start, unit);
+        feature_ref.end = if open_ended {
+            u32::MAX
+        } else {
+            utf16_offset_to_cluster(text, end, unit)
+        };
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Builds feature ranges that turn digit/slash/digit spans (e.g. "1/2", or
+/// with U+2044 FRACTION SLASH) in UTF-16 `text` into fractions. Fonts with
+/// `frac` get one `frac` range per span; fonts with only `numr`/`dnom` get
+/// those over the numerator and denominator digits. Ranges are in cluster
+/// values for `unit`. At most `capacity` features are written. Returns the
+/// total number of features, so a call with a null `out` can be used to size
+/// the array. Returns -1 (`NullArgument`) if the font or text is null or -2
+/// (`InvalidArgument`) if `len` is negative.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `text` must be null or point to
+/// `len` UTF-16 code units; and `out` must be null or have room for
+/// `capacity` features.
+#[export_name = symbol!("harfrust_font_fraction_features")]
+pub unsafe extern "C" fn harfrust_font_fraction_features(
+    font: *const HarfRustFont,
+    text: *const u16,
+    len: i32,
+    unit: HarfRustClusterUnit,
+    out: *mut HarfRustFeature,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null") as i32;
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let text = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let use_numr_dnom = !has_gsub_feature(&font_wrapper.font_ref, Tag::new(b"frac"))
+            && has_gsub_feature(&font_wrapper.font_ref, Tag::new(b"numr"))
+            && has_gsub_feature(&font_wrapper.font_ref, Tag::new(b"dnom"));
+
+        let feature = |tag: &[u8; 4], start: usize, end: usize| HarfRustFeature {
+            tag: u32::from_be_bytes(*tag),
+            value: 1,
+            start: utf16_offset_to_cluster(text, start, unit),
+            end: utf16_offset_to_cluster(text, end, unit),
+        };
+
+        let mut features = Vec::new();
+        for (numerator, slash, denominator_end) in fraction_spans(text) {
+            if use_numr_dnom {
+                features.push(feature(b"numr", numerator, slash));
+                features.push(feature(b"dnom", slash + 1, denominator_end));
+            } else {
+                features.push(feature(b"frac", numerator, denominator_end));
+            }
+        }
+
+        if !out.is_null() && capacity > 0 {
+            let count = features.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            out_slice.copy_from_slice(&features[..count]);
+        }
+
+        features.len() as i32
+    })
+}
+
+/// Writes the tags (4 bytes as u32) of the features the shaper applies by
+/// default to a run with the given ISO 15924 script and direction, limited to
+/// those the font actually implements for that script. Tags are in the order
+/// the shaper applies them; any of them can be turned off by passing it with
+/// value 0 to `harfrust_shape_with_features`. An `Invalid` direction uses the
+/// script's natural horizontal direction.
+/// At most `capacity` tags are written. Returns the total number of tags, or
+/// -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or have room
+/// for `capacity` entries.
+#[export_name = symbol!("harfrust_font_default_features")]
+pub unsafe extern "C" fn harfrust_font_default_features(
+    font: *const HarfRustFont,
+    script_tag: u32,
+    direction: HarfRustDirection,
+    out: *mut u32,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let script = Tag::new(&script_tag.to_be_bytes());
+        let vertical = matches!(
+            direction,
+            HarfRustDirection::TopToBottom | HarfRustDirection::BottomToTop
+        );
+        let rtl = match direction {
+            HarfRustDirection::Invalid => features::is_rtl_script(script),
+            _ => direction == HarfRustDirection::RightToLeft,
+        };
+
+        let available = features::font_features(&font_wrapper.font_ref, script);
+        let tags: Vec<u32> = features::default_features(script, vertical, rtl)
+            .into_iter()
+            .filter(|tag| available.contains(tag))
+            .map(|tag| u32::from_be_bytes(tag.to_be_bytes()))
+            .collect();
+
+        if !out.is_null() && capacity > 0 {
+            let count = tags.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            out_slice.copy_from_slice(&tags[..count]);
+        }
+
+        tags.len() as i32
+    })
+}
+
+/// Sets features applied to every run shaped with this font, before the
+/// features passed to the shape call (which win where they overlap). Typical
+/// use is turning off a feature that a particular font implements badly, e.g.
+/// `calt` with value 0. Replaces any previous overrides; passing no features
+/// clears them. Shape calls already running are not affected.
+/// Returns `NullArgument` if the font is null, or if `features` is null with
+/// a non-zero count, and `Unsupported` if the font is frozen.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `features` must be null or point
+/// to `num_features` features.
+#[export_name = symbol!("harfrust_font_set_feature_overrides")]
+pub unsafe extern "C" fn harfrust_font_set_feature_overrides(
+    font: *mut HarfRustFont,
+    features: *const HarfRustFeature,
+    num_features: u32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if features.is_null() && num_features > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "features is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        if font_wrapper.frozen.load(Ordering::Acquire) {
+            return error::fail(HarfRustStatus::Unsupported, "font is frozen");
+        }
+        let overrides = unsafe { convert_features(features, num_features) };
+        *font_wrapper.feature_overrides.write().unwrap_or_else(|e| e.into_inner()) = overrides;
+        HarfRustStatus::Ok
+    })
+}
+
+/// Copies the font's feature overrides (see
+/// `harfrust_font_set_feature_overrides`) into `out`. At most `capacity`
+/// features are written. Returns the total number of overrides, so a call
+/// with a null `out` can be used to size the array, or -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or have room
+/// for `capacity` features.
+#[export_name = symbol!("harfrust_font_get_feature_overrides")]
+pub unsafe extern "C" fn harfrust_font_get_feature_overrides(
+    font: *const HarfRustFont,
+    out: *mut HarfRustFeature,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let overrides = font_wrapper.feature_overrides.read().unwrap_or_else(|e| e.into_inner());
+        if !out.is_null() && capacity > 0 {
+            let count = overrides.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            for (slot, feature) in out_slice.iter_mut().zip(overrides.iter()) {
+                *slot = HarfRustFeature {
+                    tag: u32::from_be_bytes(feature.tag.to_be_bytes()),
+                    value: feature.value,
+                    start: feature.start,
+                    end: feature.end,
+                };
+            }
+        }
+
+        overrides.len() as i32
+    })
+}
+
+/// Sets advances that replace the shaped horizontal advance of glyphs, e.g.
+/// to force the space to the width a PDF declares in its /Widths array so
+/// that layout matches what the PDF consumer will draw. Overrides apply to
+/// every result shaped with this font, after shaping and before any scaling
+/// the shape call does; offsets and vertical advances are left alone.
+/// Replaces any previous overrides (a later entry for the same glyph wins);
+/// passing none clears them. Shape calls already running are not affected.
+/// Returns `NullArgument` if the font is null, or if `overrides` is null with
+/// a non-zero count, and `Unsupported` if the font is frozen.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `overrides` must be null or
+/// point to `num_overrides` entries.
+#[export_name = symbol!("harfrust_font_set_advance_overrides")]
+pub unsafe extern "C" fn harfrust_font_set_advance_overrides(
+    font: *mut HarfRustFont,
+    overrides: *const HarfRustAdvanceOverride,
+    num_overrides: u32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if overrides.is_null() && num_overrides > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "overrides is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        if font_wrapper.frozen.load(Ordering::Acquire) {
+            return error::fail(HarfRustStatus::Unsupported, "font is frozen");
+        }
+        let entries = if num_overrides == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(overrides, num_overrides as usize) }
+        };
+        let map = entries.iter().map(|entry| (entry.glyph_id, entry.advance)).collect();
+        *font_wrapper.advance_overrides.write().unwrap_or_else(|e| e.into_inner()) = map;
+        HarfRustStatus::Ok
+    })
+}
+
+/// Copies the font's advance overrides (see
+/// `harfrust_font_set_advance_overrides`) into `out`, in glyph ID order. At
+/// most `capacity` overrides are written. Returns the total number of
+/// overrides, so a call with a null `out` can be used to size the array, or
+/// -1 if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or have room
+/// for `capacity` entries.
+#[export_name = symbol!("harfrust_font_get_advance_overrides")]
+pub unsafe extern "C" fn harfrust_font_get_advance_overrides(
+    font: *const HarfRustFont,
+    out: *mut HarfRustAdvanceOverride,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let overrides = font_wrapper.advance_overrides.read().unwrap_or_else(|e| e.into_inner());
+        if !out.is_null() && capacity > 0 {
+            let count = overrides.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            for (slot, (&glyph_id, &advance)) in out_slice.iter_mut().zip(overrides.iter()) {
+                *slot = HarfRustAdvanceOverride { glyph_id, advance };
+            }
+        }
+
+        overrides.len() as i32
+    })
+}
+
+/// Sets a fixed extra advance, in font units, added after every cluster of
+/// every result shaped with this font, spaces included, like the PDF Tc
+/// text state parameter (a Tc of `t` at font size `s` is `t *
+/// units_per_em / s`). Unlike typographic tracking it does not depend on the
+/// size or skip any glyphs, so measured text matches what a PDF viewer
+/// draws. Applied after advance overrides and before horizontal scaling, as
+/// PDF scales Tc by Tz; vertical runs are left alone. Measurement and line
+/// fitting use the spaced advances. Negative values tighten text.
+/// Returns `NullArgument` if the font is null and `Unsupported` if the font
+/// is frozen.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_set_character_spacing")]
+pub unsafe extern "C" fn harfrust_font_set_character_spacing(font: *mut HarfRustFont, spacing: i32) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+
+        let font_wrapper = unsafe { &*font };
+        if font_wrapper.frozen.load(Ordering::Acquire) {
+            return error::fail(HarfRustStatus::Unsupported, "font is frozen");
+        }
+        font_wrapper.character_spacing.store(spacing, Ordering::Relaxed);
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns the font's character spacing in font units (see
+/// `harfrust_font_set_character_spacing`), 0 unless set or if the font is
+/// null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_get_character_spacing")]
+pub unsafe extern "C" fn harfrust_font_get_character_spacing(font: *const HarfRustFont) -> i32 {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return 0;
+        };
+
+        unsafe { &*font }.character_spacing.load(Ordering::Relaxed)
+    })
+}
+
+/// Sets a horizontal scaling factor, like the PDF Tz text state parameter
+/// divided by 100, for condensed or expanded text. Every result shaped with
+/// this font has its advances and offsets multiplied by `scale` in x, after
+/// advance overrides and before any scaling the shape call does; vertical
+/// advances and offsets are left alone. Measurement and line fitting
+/// (`harfrust_text_fits`, `harfrust_shape_fit_size`,
+/// `harfrust_shape_field_line`) use the scaled advances. Font metrics such
+/// as `harfrust_font_glyph_advance` are not affected. Shape calls already
+/// running may or may not see the new factor.
+/// Returns `NullArgument` if the font is null, `OutOfRange` if `scale` is not
+/// a positive finite number, and `Unsupported` if the font is frozen.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_set_horizontal_scale")]
+pub unsafe extern "C" fn harfrust_font_set_horizontal_scale(font: *mut HarfRustFont, scale: f32) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if !(scale > 0.0 && scale.is_finite()) {
+            return error::fail(HarfRustStatus::OutOfRange, "scale must be positive and finite");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        if font_wrapper.frozen.load(Ordering::Acquire) {
+            return error::fail(HarfRustStatus::Unsupported, "font is frozen");
+        }
+        font_wrapper.horizontal_scale.store(scale.to_bits(), Ordering::Relaxed);
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns the font's horizontal scaling factor (see
+/// `harfrust_font_set_horizontal_scale`), 1.0 unless set, or 0.0 if the font
+/// is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_get_horizontal_scale")]
+pub unsafe extern "C" fn harfrust_font_get_horizontal_scale(font: *const HarfRustFont) -> f32 {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return 0.0;
+        };
+
+        unsafe { &*font }.horizontal_scale()
+    })
+}
+
+// =============================================================================
+// Shape function
+// =============================================================================
+
+/// Shapes text in a buffer using the given font. Fonts whose layout tables
+/// cannot be read are shaped as `harfrust_shape_fallback` does.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles.
+#[export_name = symbol!("harfrust_shape")]
+pub unsafe extern "C" fn harfrust_shape(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        // Perform shaping
+        shape_cached(font_wrapper, buffer_box, &[], &[], |unicode_buffer| {
+            font_wrapper.shape_with_plan(&shaper, None, unicode_buffer, &[])
+        })
+    })
+}
+
+/// Shapes text in a buffer without layout tables: each character maps to
+/// its nominal glyph through `cmap` (.notdef if unmapped) and advances by its
+/// `hmtx` width, in visual order. This never fails for a valid font and
+/// buffer, even if GSUB, GPOS or GDEF are damaged. The result reports
+/// `harfrust_glyph_buffer_is_degraded`. Consumes the buffer.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles.
+#[export_name = symbol!("harfrust_shape_fallback")]
+pub unsafe extern "C" fn harfrust_shape_fallback(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        let spaces_normalized = buffer_box.normalize_spaces(font_wrapper);
+        let mut result = shape_fallback(font_wrapper, &buffer_box.shaped_text(), buffer_box.inner.direction());
+        font_wrapper.adjust_positions(&result.infos_cache, &mut result.positions_cache);
+        result.spaces_normalized = spaces_normalized;
+        handle::into_handle(Box::new(result))
+    })
+}
+
+/// Shapes text in a buffer using the given font and OpenType features.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles, and `features` must be
+/// null or point to `num_features` features.
+#[export_name = symbol!("harfrust_shape_with_features")]
+pub unsafe extern "C" fn harfrust_shape_with_features(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+    features: *const HarfRustFeature,
+    num_features: u32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        // Prepare features
+        let rust_features = unsafe { convert_features(features, num_features) };
+
+        // Perform shaping
+        shape_cached(font_wrapper, buffer_box, &rust_features, &[], |unicode_buffer| {
+            font_wrapper.shape_with_plan(&shaper, None, unicode_buffer, &rust_features)
+        })
+    })
+}
+
+/// Shapes text in a buffer using the given font, features, and variable font settings.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles; `features` must be null
+/// or point to `num_features` features; and `variations` must be null or
+/// point to `num_variations` variations.
+#[export_name = symbol!("harfrust_shape_full")]
+pub unsafe extern "C" fn harfrust_shape_full(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+    features: *const HarfRustFeature,
+    num_features: u32,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        // Prepare features
+        let rust_features = unsafe { convert_features(features, num_features) };
+        let settings = unsafe { variation_slice(variations, num_variations) };
+
+        // Perform shaping; the variable font instance is only built on a cache miss
+        shape_cached(font_wrapper, buffer_box, &rust_features, settings, |unicode_buffer| {
+            let instance_opt =
+                unsafe { shaper_instance(&font_wrapper.font_ref, variations, num_variations) };
+            let shaper = font_wrapper
+                .shaper_data
+                .shaper(&font_wrapper.font_ref)
+                .instance(instance_opt.as_ref())
+                .build();
+            font_wrapper.shape_with_plan(&shaper, instance_opt.as_ref(), unicode_buffer, &rust_features)
+        })
+    })
+}
+
+/// Shapes a UTF-16 run in a single call, without a separate buffer handle.
+/// `direction` `Invalid`, `script_tag` 0 and a null `language` are guessed
+/// from the text. A nonzero `normalize_spaces` normalizes spaces as
+/// `harfrust_buffer_set_normalize_spaces` does. Returns null if the font or
+/// text is null or the language is invalid.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `text` must be null or point to
+/// `len` UTF-16 code units; `language` must be null or a NUL-terminated
+/// string; and `features` must be null or point to `num_features` features.
+#[export_name = symbol!("harfrust_shape_run_utf16")]
+pub unsafe extern "C" fn harfrust_shape_run_utf16(
+    font: *const HarfRustFont,
+    text: *const u16,
+    len: i32,
+    direction: HarfRustDirection,
+    script_tag: u32,
+    language: *const c_char,
+    features: *const HarfRustFeature,
+    num_features: u32,
+    normalize_spaces: i32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let normalize = normalize_spaces != 0;
+        let spaces = normalize.then_some(font_wrapper);
+        let (unicode_buffer, spaces_normalized) =
+            match unsafe { run_buffer(text, len, direction, script_tag, language, spaces) } {
+                Some(run) => run,
+                None => return std::ptr::null_mut(),
+            };
+
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+        let rust_features = unsafe { convert_features(features, num_features) };
+        let text = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let chars = run_chars(font_wrapper, text, normalize);
+        let mut glyph_buffer = font_wrapper.shape(&shaper, None, chars, unicode_buffer, &rust_features);
+        glyph_buffer.spaces_normalized = spaces_normalized;
+
+        wrap_glyph_buffer(font_wrapper, glyph_buffer)
+    })
+}
+
+/// Writes a key identifying the result of shaping `run` with the given
+/// variation settings to `out`, without shaping it, so callers can look up
+/// their own cache of rendered runs first. The key combines the font data
+/// hash, face index, the text, its direction, script and language after
+/// guessing, the features in order, the font's advance overrides, character
+/// spacing and horizontal scale, whether spaces are normalized, the
+/// normalized axis coordinates and the library version: it is stable across processes and changes on upgrades,
+/// which may shape differently. Returns `NullArgument` if the font, `run` or
+/// `out` is null or `variations` is null with a non-zero count, or
+/// `InvalidArgument` if the run's text or language is invalid.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `run` must be null or point to a
+/// `HarfRustRunDescriptor`, and its pointers must meet the requirements
+/// `harfrust_shape_run_utf16` places on its arguments; `variations` must be
+/// null or point to `num_variations` variations; and `out` must be null or
+/// point to a writable `HarfRustCacheKey`.
+#[export_name = symbol!("harfrust_run_key")]
+pub unsafe extern "C" fn harfrust_run_key(
+    font: *const HarfRustFont,
+    run: *const HarfRustRunDescriptor,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+    out: *mut HarfRustCacheKey,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if run.is_null() || out.is_null() || (variations.is_null() && num_variations > 0) {
+            return error::fail(HarfRustStatus::NullArgument, "run, out or variations is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let run = unsafe { &*run };
+        let Some((unicode_buffer, _)) =
+            (unsafe { run_buffer(run.text, run.len, run.direction, run.script, run.language, None) })
+        else {
+            let message = "run text is null or its language is invalid";
+            return error::fail(HarfRustStatus::InvalidArgument, message);
+        };
+
+        let variations = unsafe { variation_slice(variations, num_variations) };
+        let mut key = font_wrapper.key_prefix(variations);
+        key.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+        key.push(0);
+        key.push(HarfRustDirection::from(unicode_buffer.direction()) as u8);
+        key.extend_from_slice(&unicode_buffer.script().tag().to_be_bytes());
+        let language = unicode_buffer.language();
+        key.extend_from_slice(language.as_ref().map_or("", |l| l.as_str()).as_bytes());
+        key.push(0);
+        scratch::recycle(unicode_buffer);
+
+        let features = unsafe { convert_features(run.features, run.num_features) };
+        let features = font_wrapper.with_overrides(&features);
+        key.extend_from_slice(&(features.len() as u32).to_le_bytes());
+        for feature in features.iter() {
+            key.extend_from_slice(&feature.tag.to_be_bytes());
+            for value in [feature.value, feature.start, feature.end] {
+                key.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        let overrides = font_wrapper.advance_overrides.read().unwrap_or_else(|e| e.into_inner());
+        key.extend_from_slice(&(overrides.len() as u32).to_le_bytes());
+        for (glyph_id, advance) in overrides.iter() {
+            key.extend_from_slice(&glyph_id.to_le_bytes());
+            key.extend_from_slice(&advance.to_le_bytes());
+        }
+        drop(overrides);
+        key.extend_from_slice(&font_wrapper.character_spacing.load(Ordering::Relaxed).to_le_bytes());
+        key.extend_from_slice(&font_wrapper.horizontal_scale().to_bits().to_le_bytes());
+        key.push((run.normalize_spaces != 0) as u8);
+        // The text goes last, so its length needs no prefix.
+        let text = unsafe { std::slice::from_raw_parts(run.text, run.len as usize) };
+        for unit in text {
+            key.extend_from_slice(&unit.to_le_bytes());
+        }
+
+        unsafe { *out = HarfRustCacheKey::from_bytes(&key) };
+        HarfRustStatus::Ok
+    })
+}
+
+/// Shapes `num_runs` runs with one font in a single call, writing one glyph
+/// buffer handle per run to `out_buffers` (null for runs whose text or
+/// language is invalid). Runs of a degraded font are shaped with the
+/// fallback shaper, as `harfrust_shape` does. Each handle must be freed with
+/// `harfrust_glyph_buffer_free`. Returns the number of runs shaped,
+/// -1 if the font is null, or -2 if `runs` or `out_buffers` is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `runs` must be null or point to
+/// `num_runs` entries, each of which meets the requirements
+/// `harfrust_shape_run_utf16` places on its arguments.
+#[export_name = symbol!("harfrust_shape_batch")]
+pub unsafe extern "C" fn harfrust_shape_batch(
+    font: *const HarfRustFont,
+    runs: *const HarfRustRunDescriptor,
+    num_runs: u32,
+    out_buffers: *mut *mut HarfRustGlyphBuffer,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if runs.is_null() || out_buffers.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "runs or out_buffers is null") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let runs = unsafe { std::slice::from_raw_parts(runs, num_runs as usize) };
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out_buffers, num_runs as usize) };
+        unsafe { shape_batch(font_wrapper, runs, out_slice) }
+    })
+}
+
+/// Shapes the runs of `harfrust_shape_batch` one after another, returning how
+/// many were shaped.
+unsafe fn shape_batch(
+    font_wrapper: &HarfRustFont,
+    runs: &[HarfRustRunDescriptor],
+    out_slice: &mut [*mut HarfRustGlyphBuffer],
+) -> i32 {
+    let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+
+    let mut shaped = 0;
+    for (run, out) in runs.iter().zip(out_slice) {
+        let normalize = run.normalize_spaces != 0;
+        let unicode_buffer = unsafe {
+            let spaces = normalize.then_some(font_wrapper);
+            run_buffer(run.text, run.len, run.direction, run.script, run.language, spaces)
+        };
+        *out = match unicode_buffer {
+            Some((unicode_buffer, spaces_normalized)) => {
+                let rust_features = unsafe { convert_features(run.features, run.num_features) };
+                shaped += 1;
+                let text = unsafe { std::slice::from_raw_parts(run.text, run.len as usize) };
+                let chars = run_chars(font_wrapper, text, normalize);
+                let mut glyph_buffer =
+                    font_wrapper.shape(&shaper, None, chars, unicode_buffer, &rust_features);
+                glyph_buffer.spaces_normalized = spaces_normalized;
+                wrap_glyph_buffer(font_wrapper, glyph_buffer)
+            }
+            None => std::ptr::null_mut(),
+        };
+    }
+
+    shaped
+}
+
+/// Threads `harfrust_shape_batch_parallel` uses when passed 0; 0 for one per
+/// available core.
+static DEFAULT_THREADS: AtomicU32 = AtomicU32::new(0);
+
+/// Threads to shape a parallel batch on for a requested count: 0 selects
+/// `DEFAULT_THREADS`, and the result never exceeds the available cores.
+fn batch_threads(requested: u32) -> usize {
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let requested = match requested {
+        0 => DEFAULT_THREADS.load(Ordering::Relaxed),
+        n => n,
+    };
+    match requested {
+        0 => available,
+        n => (n as usize).min(available),
+    }
+}
+
+/// A run of `harfrust_shape_batch_parallel` converted to owned inputs.
+type ShapeJob = Option<(harfrust::UnicodeBuffer, Vec<harfrust::Feature>)>;
+
+/// Same as `harfrust_shape_batch`, but shapes the runs on up to
+/// `num_threads` threads (0 for the number set by `harfrust_init`, by default
+/// the number of available cores; never more than there are cores) and
+/// returns once all are complete. Runs are
+/// split into contiguous chunks; the calling thread shapes one of them. Where
+/// threads are unavailable (e.g. WebAssembly) every run is shaped on the
+/// calling thread.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `runs` must be null or point to
+/// `num_runs` entries, each of which meets the requirements
+/// `harfrust_shape_run_utf16` places on its arguments.
+#[export_name = symbol!("harfrust_shape_batch_parallel")]
+pub unsafe extern "C" fn harfrust_shape_batch_parallel(
+    font: *const HarfRustFont,
+    runs: *const HarfRustRunDescriptor,
+    num_runs: u32,
+    out_buffers: *mut *mut HarfRustGlyphBuffer,
+    num_threads: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if runs.is_null() || out_buffers.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "runs or out_buffers is null") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let runs = unsafe { std::slice::from_raw_parts(runs, num_runs as usize) };
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out_buffers, num_runs as usize) };
+        if font_wrapper.degraded() {
+            // Fallback shaping is cheap enough not to need threads
+            return unsafe { shape_batch(font_wrapper, runs, out_slice) };
+        }
+
+        // Run descriptors point into caller memory, so the inputs are converted
+        // here and only owned buffers cross threads.
+        let (mut jobs, spaces_normalized): (Vec<ShapeJob>, Vec<bool>) = runs
+            .iter()
+            .map(|run| {
+                let spaces = (run.normalize_spaces != 0).then_some(font_wrapper);
+                let unicode_buffer = unsafe {
+                    run_buffer(run.text, run.len, run.direction, run.script, run.language, spaces)
+                };
+                let Some((unicode_buffer, spaces_normalized)) = unicode_buffer else {
+                    return (None, false);
+                };
+                let rust_features = unsafe { convert_features(run.features, run.num_features) };
+                (Some((unicode_buffer, rust_features)), spaces_normalized)
+            })
+            .unzip();
+        let shaped = jobs.iter().filter(|job| job.is_some()).count() as i32;
+        let mut results: Vec<Option<harfrust::GlyphBuffer>> = Vec::new();
+        results.resize_with(jobs.len(), || None);
+
+        // Shapes the jobs still pending, leaving each result in its slot
+        let shape_jobs = |jobs: &mut [ShapeJob], results: &mut [Option<harfrust::GlyphBuffer>]| {
+            let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+            for (job, result) in jobs.iter_mut().zip(results) {
+                if let Some((unicode_buffer, rust_features)) = job.take() {
+                    *result = Some(font_wrapper.shape_with_plan(&shaper, None, unicode_buffer, &rust_features));
+                }
+            }
+        };
+
+        let chunk_size = jobs.len().div_ceil(batch_threads(num_threads)).max(1);
+        std::thread::scope(|scope| {
+            let mut chunks = jobs.chunks_mut(chunk_size).zip(results.chunks_mut(chunk_size));
+            let first = chunks.next();
+            for (jobs, results) in chunks {
+                // Chunks whose thread cannot be started stay pending
+                let _ = std::thread::Builder::new().spawn_scoped(scope, || shape_jobs(jobs, results));
+            }
+            if let Some((jobs, results)) = first {
+                shape_jobs(jobs, results);
+            }
+        });
+        shape_jobs(&mut jobs, &mut results);
+
+        for ((result, spaces_normalized), out) in results.into_iter().zip(spaces_normalized).zip(out_slice) {
+            *out = result.map_or(std::ptr::null_mut(), |result| {
+                let mut glyph_buffer = glyph_buffer_wrapper(result);
+                glyph_buffer.spaces_normalized = spaces_normalized;
+                wrap_glyph_buffer(font_wrapper, glyph_buffer)
+            });
+        }
+        shaped
+    })
+}
+
+/// Shapes a copy of the buffer's text with the given font and optional
+/// OpenType features. Unlike `harfrust_shape`, the buffer is not consumed:
+/// it stays owned by the caller and can be cleared, reused or shaped again.
+/// Segment properties are guessed on the copy if no direction is set, and
+/// spaces are normalized in the copy if enabled on the buffer.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles, and `features` must be
+/// null or point to `num_features` features.
+#[export_name = symbol!("harfrust_shape_copy")]
+pub unsafe extern "C" fn harfrust_shape_copy(
+    font: *const HarfRustFont,
+    buffer: *const HarfRustBuffer,
+    features: *const HarfRustFeature,
+    num_features: u32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let buffer_ref = unsafe { &*buffer };
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+
+        let (text, spaces_normalized) = buffer_ref.text_for(font_wrapper);
+        let mut unicode_buffer = buffer_ref.unicode_buffer_with(&text);
+        if unicode_buffer.direction() == harfrust::Direction::Invalid {
+            unicode_buffer.guess_segment_properties();
+        }
+
+        let rust_features = unsafe { convert_features(features, num_features) };
+        let mut glyph_buffer =
+            font_wrapper.shape(&shaper, None, text.iter().copied(), unicode_buffer, &rust_features);
+        glyph_buffer.spaces_normalized = spaces_normalized;
+
+        wrap_glyph_buffer(font_wrapper, glyph_buffer)
+    })
+}
+
+/// Shapes a copy of the buffer's text like `harfrust_shape_copy`, writing
+/// glyph infos and positions straight into caller arrays of `capacity`
+/// entries instead of allocating a glyph buffer. `written_out` receives the
+/// number of glyphs, and `flags_out`, if not null, the
+/// `HarfRustResultFlags` bits `harfrust_glyph_buffer_get_flags` would
+/// report. Returns `NullArgument` if the font, buffer or `infos_out`,
+/// `positions_out` or `written_out` is null, or `InsufficientCapacity` if the
+/// glyphs do not fit; nothing is written then and `written_out` receives the
+/// capacity needed. The buffer is not consumed, so it can be shaped again
+/// into larger arrays.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles; `features` must be null
+/// or point to `num_features` features; `infos_out` must be null or have room
+/// for `capacity` entries; `positions_out` must be null or have room for
+/// `capacity` entries; and `written_out` and `flags_out` must be null or
+/// point to a writable `u32` and `i32`.
+#[export_name = symbol!("harfrust_shape_into")]
+pub unsafe extern "C" fn harfrust_shape_into(
+    font: *const HarfRustFont,
+    buffer: *const HarfRustBuffer,
+    features: *const HarfRustFeature,
+    num_features: u32,
+    infos_out: *mut HarfRustGlyphInfo,
+    positions_out: *mut HarfRustGlyphPosition,
+    capacity: u32,
+    written_out: *mut u32,
+    flags_out: *mut i32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if infos_out.is_null() || positions_out.is_null() || written_out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "output pointer is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let buffer_ref = unsafe { &*buffer };
+        let (text, spaces_normalized) = buffer_ref.text_for(font_wrapper);
+        let mut unicode_buffer = buffer_ref.unicode_buffer_with(&text);
+        if unicode_buffer.direction() == harfrust::Direction::Invalid {
+            unicode_buffer.guess_segment_properties();
+        }
+        let mut flags = 0;
+        if spaces_normalized {
+            flags |= HarfRustResultFlags::SpacesNormalized as i32;
+        }
+
+        let infos_out = unsafe { std::slice::from_raw_parts_mut(infos_out, capacity as usize) };
+        let positions_out =
+            unsafe { std::slice::from_raw_parts_mut(positions_out, capacity as usize) };
+        let fits = |count: usize| {
+            unsafe { *written_out = count as u32 };
+            if count > capacity as usize {
+                error::set(HarfRustStatus::InsufficientCapacity, format!("{count} glyphs do not fit in a capacity of {capacity}"));
+                return false;
+            }
+            true
+        };
+
+        if font_wrapper.degraded() {
+            let direction = unicode_buffer.direction();
+            let (infos, positions) = fallback::shape(&font_wrapper.font_ref, &text, direction);
+            if !fits(infos.len()) {
+                return HarfRustStatus::InsufficientCapacity;
+            }
+            infos_out[..infos.len()].copy_from_slice(&infos);
+            positions_out[..positions.len()].copy_from_slice(&positions);
+            font_wrapper.adjust_positions(&infos, &mut positions_out[..positions.len()]);
+            if !flags_out.is_null() {
+                unsafe { *flags_out = flags | HarfRustResultFlags::Degraded as i32 };
+            }
+            return HarfRustStatus::Ok;
+        }
+
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+        let rust_features = unsafe { convert_features(features, num_features) };
+        let glyph_buffer = font_wrapper.shape_with_plan(&shaper, None, unicode_buffer, &rust_features);
+        if !fits(glyph_buffer.len()) {
+            scratch::recycle_glyphs(glyph_buffer);
+            return HarfRustStatus::InsufficientCapacity;
+        }
+
+        for (info, out) in glyph_buffer.glyph_infos().iter().zip(infos_out.iter_mut()) {
+            *out = HarfRustGlyphInfo {
+                glyph_id: info.glyph_id,
+                cluster: info.cluster,
+            };
+        }
+        for (pos, out) in glyph_buffer.glyph_positions().iter().zip(positions_out.iter_mut()) {
+            *out = HarfRustGlyphPosition {
+                x_advance: pos.x_advance,
+                y_advance: pos.y_advance,
+                x_offset: pos.x_offset,
+                y_offset: pos.y_offset,
+            };
+        }
+        let count = glyph_buffer.len();
+        font_wrapper.adjust_positions(&infos_out[..count], &mut positions_out[..count]);
+        scratch::recycle_glyphs(glyph_buffer);
+        if !flags_out.is_null() {
+            unsafe { *flags_out = flags };
+        }
+        HarfRustStatus::Ok
+    })
+}
+
+/// Shapes text as small caps. The font's `smcp` feature is applied to the
+/// characters it covers, whose glyphs keep a scale of 1.0. Lowercase letters
+/// it does not cover (all of them if the font has no `smcp`) are shaped as
+/// uppercase and their glyphs are reported with `scale` through
+/// `harfrust_glyph_buffer_get_transforms`; their advances and offsets are already
+/// scaled, so the renderer only needs to scale the outlines. Clusters still
+/// refer to the original text. Returns null on invalid arguments or if
+/// `scale` is not a positive finite number.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles.
+#[export_name = symbol!("harfrust_shape_small_caps")]
+pub unsafe extern "C" fn harfrust_shape_small_caps(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+    scale: f32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+        if !(scale > 0.0 && scale.is_finite()) {
+            return error::fail_null(HarfRustStatus::OutOfRange, "scale must be positive and finite");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        let text = buffer_box.shaped_text();
+        let (covered, features) = covered_features(font_wrapper, &text, Tag::new(b"smcp"));
+        let mut unicode_buffer = empty_buffer_like(&buffer_box.inner);
+        let mut cased_text = Vec::with_capacity(text.len());
+        let mut small_clusters = Vec::new();
+        for (&(ch, cluster), covered) in text.iter().zip(covered) {
+            if ch.is_lowercase() && !covered {
+                small_clusters.push(cluster);
+                cased_text.extend(ch.to_uppercase().map(|upper| (upper, cluster)));
+            } else {
+                cased_text.push((ch, cluster));
+            }
+        }
+        small_clusters.sort_unstable();
+        for &(ch, cluster) in &cased_text {
+            unicode_buffer.add(ch, cluster);
+        }
+
+        let mut wrapper = font_wrapper.shape(&shaper, None, cased_text, unicode_buffer, &features);
+        font_wrapper.adjust_positions(&wrapper.infos_cache, &mut wrapper.positions_cache);
+        wrapper.transforms = vec![HarfRustGlyphTransform::IDENTITY; wrapper.infos_cache.len()];
+        let glyphs = wrapper.infos_cache.iter().zip(&mut wrapper.positions_cache);
+        for ((info, pos), transform) in glyphs.zip(&mut wrapper.transforms) {
+            if small_clusters.binary_search(&info.cluster).is_ok() {
+                transform.scale = scale;
+                scale_position(pos, scale);
+            }
+        }
+
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Shapes text as superscript or subscript. The font's `sups` or `subs`
+/// feature is applied to the characters it covers. Glyphs of the others (all
+/// of them if the font has no such feature) are synthesized from the OS/2
+/// script metrics: advances and offsets are scaled by the script size, the
+/// script offset is added to each glyph's offset, and the scale is reported
+/// through `harfrust_glyph_buffer_get_transforms`.
+/// Returns null on invalid arguments.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles.
+#[export_name = symbol!("harfrust_shape_script_position")]
+pub unsafe extern "C" fn harfrust_shape_script_position(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+    position: HarfRustScriptPosition,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        let tag = match position {
+            HarfRustScriptPosition::Superscript => Tag::new(b"sups"),
+            HarfRustScriptPosition::Subscript => Tag::new(b"subs"),
+        };
+        let unicode_buffer = std::mem::take(&mut buffer_box.inner);
+        let text = buffer_box.shaped_text();
+        let (covered, features) = covered_features(font_wrapper, &text, tag);
+        let mut synthesized: Vec<u32> =
+            text.iter().zip(covered).filter(|(_, covered)| !covered).map(|(&(_, cluster), _)| cluster).collect();
+        synthesized.sort_unstable();
+        let glyph_buffer = font_wrapper.shape(&shaper, None, text.iter().copied(), unicode_buffer, &features);
+        if synthesized.is_empty() {
+            return wrap_glyph_buffer(font_wrapper, glyph_buffer);
+        }
+
+        let metrics = font_wrapper.script_metrics(position);
+        let scale = metrics.y_size as f32 / font_wrapper.units_per_em as f32;
+        let y_shift = match position {
+            HarfRustScriptPosition::Superscript => metrics.y_offset,
+            HarfRustScriptPosition::Subscript => -metrics.y_offset,
+        };
+
+        let mut wrapper = glyph_buffer;
+        font_wrapper.adjust_positions(&wrapper.infos_cache, &mut wrapper.positions_cache);
+        wrapper.transforms = vec![HarfRustGlyphTransform::IDENTITY; wrapper.infos_cache.len()];
+        let glyphs = wrapper.infos_cache.iter().zip(&mut wrapper.positions_cache);
+        for ((info, pos), transform) in glyphs.zip(&mut wrapper.transforms) {
+            if synthesized.binary_search(&info.cluster).is_ok() {
+                scale_position(pos, scale);
+                pos.x_offset += metrics.x_offset;
+                pos.y_offset += y_shift;
+                transform.scale = scale;
+            }
+        }
+
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Shapes text for a vertical line with mixed orientation (CSS
+/// `text-orientation: mixed`): characters that are upright in vertical text
+/// (CJK, kana, Hangul and most symbols, per UAX #50) are shaped top to
+/// bottom, and runs of the others (e.g. Latin words) are shaped horizontally
+/// and set sideways, turned 90 degrees clockwise with their em box centered
+/// on the line. Sideways glyphs report a rotation of -90 through
+/// `harfrust_glyph_buffer_get_transforms`; their advances and offsets are
+/// already in the vertical line's coordinates. The buffer's script and
+/// language are used for both kinds of run, and its direction is ignored.
+/// Consumes the buffer. Returns null if the font or buffer is null.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles.
+#[export_name = symbol!("harfrust_shape_vertical_mixed")]
+pub unsafe extern "C" fn harfrust_shape_vertical_mixed(
+    font: *const HarfRustFont,
+    buffer: *mut HarfRustBuffer,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper.shaper_data.shaper(&font_wrapper.font_ref).build();
+        buffer_box.inner.set_direction(harfrust::Direction::TopToBottom);
+        buffer_box.inner.guess_segment_properties();
+
+        // Split the text into runs of one orientation
+        let text = buffer_box.shaped_text();
+        let mut runs: Vec<(bool, Vec<(char, u32)>)> = Vec::new();
+        for &(ch, cluster) in text.iter() {
+            let upright = match runs.last() {
+                Some(&(upright, _)) if vertical::follows_base(ch) => upright,
+                _ => vertical::is_upright(ch),
+            };
+            match runs.last_mut() {
+                Some((run_upright, run)) if *run_upright == upright => run.push((ch, cluster)),
+                _ => runs.push((upright, vec![(ch, cluster)])),
+            }
+        }
+
+        // Sideways glyphs turn about their origin, which moves left so that
+        // the em box is centered on the line
+        let metrics = font_wrapper.font_ref.metrics(Size::unscaled(), LocationRef::default());
+        let center = ((metrics.ascent + metrics.descent) / 2.0).round() as i32;
+
+        let mut wrapper = HarfRustGlyphBuffer {
+            tag: TypeTag::new(),
+            inner: None,
+            infos_cache: Vec::with_capacity(text.len()),
+            positions_cache: Vec::with_capacity(text.len()),
+            transforms: Vec::with_capacity(text.len()),
+            degraded: false,
+            spaces_normalized: false,
+        };
+        for (upright, run) in runs {
+            let mut unicode_buffer = empty_buffer_like(&buffer_box.inner);
+            if !upright {
+                unicode_buffer.set_direction(harfrust::Direction::LeftToRight);
+            }
+            for &(ch, cluster) in &run {
+                unicode_buffer.add(ch, cluster);
+            }
+            let shaped = font_wrapper.shape(&shaper, None, run, unicode_buffer, &[]);
+            wrapper.degraded |= shaped.degraded;
+            let mut transform = HarfRustGlyphTransform::IDENTITY;
+            let mut positions = shaped.positions_cache;
+            if !upright {
+                transform.rotation = -90.0;
+                for pos in &mut positions {
+                    *pos = HarfRustGlyphPosition {
+                        x_advance: 0,
+                        y_advance: -pos.x_advance,
+                        x_offset: pos.y_offset - center,
+                        y_offset: -pos.x_offset,
+                    };
+                }
+            }
+            wrapper.transforms.extend(std::iter::repeat_n(transform, shaped.infos_cache.len()));
+            wrapper.infos_cache.extend(shaped.infos_cache);
+            wrapper.positions_cache.extend(positions);
+        }
+        font_wrapper.adjust_positions(&wrapper.infos_cache, &mut wrapper.positions_cache);
+
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Finds the largest font size in `[min_size, max_size]` at which the shaped
+/// UTF-16 text fits within `max_width` (and, if positive, `max_height`).
+/// Widths and heights use the same unit as the size (e.g. points); the height
+/// of the text is its ascent minus descent at that size.
+/// The shaped advance is binary-searched with the shaper's point size set, so
+/// size-dependent tracking is honored. The chosen size is written to
+/// `out_size`. Returns 0 on success, 1 if the text does not fit even at
+/// `min_size` (in which case `min_size` is written), or a negative value on
+/// invalid arguments.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `text` must be null or point to
+/// `len` UTF-16 code units; and `out_size` must be null or point to a
+/// writable `f32`.
+#[export_name = symbol!("harfrust_shape_fit_size")]
+pub unsafe extern "C" fn harfrust_shape_fit_size(
+    font: *const HarfRustFont,
+    text: *const u16,
+    len: i32,
+    max_width: f32,
+    max_height: f32,
+    min_size: f32,
+    max_size: f32,
+    out_size: *mut f32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if text.is_null() || out_size.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text or out_size is null") as i32;
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative") as i32;
+        }
+        if !(min_size > 0.0 && min_size <= max_size && max_size.is_finite()) {
+            return error::fail(HarfRustStatus::OutOfRange, "min_size must be positive and at most max_size") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        match fit_size(font_wrapper, slice, max_width, max_height, min_size, max_size) {
+            Some(size) => {
+                unsafe { *out_size = size };
+                0
+            }
+            None => {
+                unsafe { *out_size = min_size };
+                1
+            }
+        }
+    })
+}
+
+/// Lays out one line of UTF-16 text in a `width` x `height` rectangle, as
+/// needed for PDF form field appearance streams. The line is aligned within
+/// `padding` of the left and right edges and centered vertically on its
+/// ascent and descent. A `font_size` of 0 selects the largest size, up to the
+/// padded height, at which the line fits inside the padding (but no smaller
+/// than 4); text set at a fixed size may overflow. Returns the shaped glyphs,
+/// whose positions are in font units, and writes the chosen size and
+/// placement to `out_layout`. Returns null on invalid arguments or if the
+/// padding leaves no room.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `text` must be null or point to
+/// `len` UTF-16 code units; and `out_layout` must be null or point to a
+/// writable `HarfRustLineLayout`.
+#[export_name = symbol!("harfrust_shape_field_line")]
+pub unsafe extern "C" fn harfrust_shape_field_line(
+    font: *const HarfRustFont,
+    text: *const u16,
+    len: i32,
+    width: f32,
+    height: f32,
+    padding: f32,
+    font_size: f32,
+    align: HarfRustTextAlign,
+    out_layout: *mut HarfRustLineLayout,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        if text.is_null() || out_layout.is_null() {
+            return error::fail_null(HarfRustStatus::NullArgument, "text or out_layout is null");
+        }
+        if len < 0 {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "len is negative");
+        }
+        let (inner_width, inner_height) = (width - 2.0 * padding, height - 2.0 * padding);
+        let valid_rect =
+            inner_width > 0.0 && inner_height > 0.0 && width.is_finite() && height.is_finite();
+        if !valid_rect || font_size.is_nan() || font_size < 0.0 || font_size.is_infinite() {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "field rectangle or font size is invalid");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let slice = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let size = if font_size > 0.0 {
+            font_size
+        } else {
+            let min_size = inner_height.min(4.0);
+            fit_size(font_wrapper, slice, inner_width, inner_height, min_size, inner_height)
+                .unwrap_or(min_size)
+        };
+
+        let mut unicode_buffer = harfrust::UnicodeBuffer::new();
+        push_utf16(&mut unicode_buffer, slice);
+        unicode_buffer.guess_segment_properties();
+        let mut wrapper = shape_at_size(font_wrapper, slice, unicode_buffer, size);
+        font_wrapper.adjust_positions(&wrapper.infos_cache, &mut wrapper.positions_cache);
+
+        let metrics = font_wrapper.font_ref.metrics(Size::unscaled(), LocationRef::default());
+        let scale = size / metrics.units_per_em.max(1) as f32;
+        let advance: i64 = wrapper.positions_cache.iter().map(|pos| pos.x_advance as i64).sum();
+        let line_width = advance as f32 * scale;
+        let x = match align {
+            HarfRustTextAlign::Left => padding,
+            HarfRustTextAlign::Center => (width - line_width) / 2.0,
+            HarfRustTextAlign::Right => width - padding - line_width,
+        };
+        let y = (height - (metrics.ascent - metrics.descent) * scale) / 2.0 - metrics.descent * scale;
+
+        unsafe {
+            *out_layout = HarfRustLineLayout {
+                font_size: size,
+                x,
+                y,
+                width: line_width,
+            }
+        };
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Places the runs of a horizontal line side by side, in the order given
+/// (left to right), each on its own baseline shifted by its rise, so that
+/// superscript and subscript runs sit correctly among the others. The
+/// placement of each run is written to `out_placements` (optional, one entry
+/// per run), and the line's width and vertical extent to `out_metrics`: the
+/// ascent and descent of each run's font, scaled to its size and moved by
+/// its rise, so a raised run makes the line taller. A line without runs is
+/// empty. Returns `NullArgument` if `runs` is null with a non-zero count,
+/// `out_metrics` is null, or a run's font or glyphs is null, and
+/// `InvalidArgument` if a font size or rise is not finite.
+///
+/// # Safety
+///
+/// `runs` must be null or point to `num_runs` entries, and in each run `font`
+/// and `glyphs` must be null or live handles; `out_placements` must be null
+/// or have room for `num_runs` entries; and `out_metrics` must be null or
+/// point to a writable `HarfRustLineMetrics`.
+#[export_name = symbol!("harfrust_layout_line")]
+pub unsafe extern "C" fn harfrust_layout_line(
+    runs: *const HarfRustLayoutRun,
+    num_runs: u32,
+    out_placements: *mut HarfRustRunPlacement,
+    out_metrics: *mut HarfRustLineMetrics,
+) -> HarfRustStatus {
+    error::guard(|| {
+        if runs.is_null() && num_runs > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "runs is null");
+        }
+        if out_metrics.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out_metrics is null");
+        }
+
+        let runs = if num_runs == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(runs, num_runs as usize) }
+        };
+        let mut placements = Vec::with_capacity(runs.len());
+        let mut line = HarfRustLineMetrics::default();
+        let mut pen = 0.0;
+        for (i, run) in runs.iter().enumerate() {
+            let font = match handle::resolve(run.font) {
+                Ok(font) => unsafe { &*font },
+                Err(status) => return status,
+            };
+            let glyphs = match handle::resolve(run.glyphs) {
+                Ok(glyphs) => unsafe { &*glyphs },
+                Err(status) => return status,
+            };
+            if !(run.font_size.is_finite() && run.rise.is_finite()) {
+                return error::fail(HarfRustStatus::InvalidArgument, format!("size or rise of run {i} is not finite"));
+            }
+
+            let metrics = font.font_ref.metrics(Size::unscaled(), LocationRef::default());
+            let scale = run.font_size / metrics.units_per_em.max(1) as f32;
+            let ascent = metrics.ascent * scale + run.rise;
+            let descent = metrics.descent * scale + run.rise;
+            if i == 0 {
+                (line.ascent, line.descent) = (ascent, descent);
+            } else {
+                line.ascent = line.ascent.max(ascent);
+                line.descent = line.descent.min(descent);
+            }
+            line.line_gap = line.line_gap.max(metrics.leading * scale);
+
+            placements.push(HarfRustRunPlacement { x: pen, y: run.rise });
+            let advance: i64 = glyphs.positions_cache.iter().map(|pos| pos.x_advance as i64).sum();
+            pen += advance as f32 * scale;
+        }
+        line.width = pen;
+
+        if !out_placements.is_null() && !placements.is_empty() {
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out_placements, placements.len()) };
+            out_slice.copy_from_slice(&placements);
+        }
+        unsafe { *out_metrics = line };
+        HarfRustStatus::Ok
+    })
+}
+
+// =============================================================================
+// Shaper functions
+// =============================================================================
+
+/// Creates a reusable shaper for `font` at the given variable font settings
+/// (none for the default instance). The variation instance is computed once
+/// here rather than on every shape call. The shaper holds a reference to the
+/// font (as `harfrust_font_retain`), so the caller may free the font first.
+/// Returns null if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `variations` must be null or
+/// point to `num_variations` variations.
+#[export_name = symbol!("harfrust_shaper_new")]
+pub unsafe extern "C" fn harfrust_shaper_new(
+    font: *const HarfRustFont,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+) -> *mut HarfRustShaper {
+    error::guard(|| {
+        let Ok(font_object) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font_object };
+        let instance = unsafe { shaper_instance(&font_wrapper.font_ref, variations, num_variations) };
+        font_wrapper.refs.fetch_add(1, Ordering::Relaxed);
+        handle::into_handle(Box::new(HarfRustShaper { tag: TypeTag::new(), font, instance }))
+    })
+}
+
+/// Shapes text in a buffer with the shaper's font and variation settings and
+/// the given OpenType features. Like `harfrust_shape`, the buffer is consumed.
+///
+/// # Safety
+///
+/// `shaper` and `buffer` must be null or live handles, and `features` must be
+/// null or point to `num_features` features.
+#[export_name = symbol!("harfrust_shaper_shape")]
+pub unsafe extern "C" fn harfrust_shaper_shape(
+    shaper: *const HarfRustShaper,
+    buffer: *mut HarfRustBuffer,
+    features: *const HarfRustFeature,
+    num_features: u32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(shaper) = handle::resolve(shaper) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let shaper_wrapper = unsafe { &*shaper };
+        let Ok(font) = handle::resolve(shaper_wrapper.font) else {
+            return std::ptr::null_mut();
+        };
+        let font_wrapper = unsafe { &*font };
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper
+            .shaper_data
+            .shaper(&font_wrapper.font_ref)
+            .instance(shaper_wrapper.instance.as_ref())
+            .build();
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        let spaces_normalized = buffer_box.normalize_spaces(font_wrapper);
+        let rust_features = unsafe { convert_features(features, num_features) };
+        let instance = shaper_wrapper.instance.as_ref();
+        let unicode_buffer = std::mem::take(&mut buffer_box.inner);
+        let text = buffer_box.shaped_text();
+        let mut result = font_wrapper.shape(&shaper, instance, text.iter().copied(), unicode_buffer, &rust_features);
+        font_wrapper.adjust_positions(&result.infos_cache, &mut result.positions_cache);
+        result.spaces_normalized = spaces_normalized;
+        handle::into_handle(Box::new(result))
+    })
+}
+
+/// Frees a shaper and releases its reference to the font, freeing the font
+/// if `harfrust_font_free` was already called on it.
+///
+/// # Safety
+///
+/// `shaper` must be null or a live handle.
+#[export_name = symbol!("harfrust_shaper_free")]
+pub unsafe extern "C" fn harfrust_shaper_free(shaper: *mut HarfRustShaper) {
+    error::guard(|| {
+        if shaper.is_null() {
+            return;
+        }
+        if let Ok(shaper) = handle::resolve(shaper) {
+            let shaper = unsafe { handle::release(shaper) };
+            unsafe { harfrust_font_free(shaper.font.cast_mut()) };
+        }
+    })
+}
+
+/// Creates a variation instance of `font` at the given axis settings, to be
+/// passed to `harfrust_shape_with_instance` instead of recomputing it from
+/// the settings on every `harfrust_shape_full` call. The instance is only
+/// valid with the font it was created for, but does not borrow it. Returns
+/// null if the font is null.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `variations` must be null or
+/// point to `num_variations` variations.
+#[export_name = symbol!("harfrust_instance_new")]
+pub unsafe extern "C" fn harfrust_instance_new(
+    font: *const HarfRustFont,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+) -> *mut HarfRustInstance {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_ref = unsafe { &(*font).font_ref };
+        let inner = unsafe { shaper_instance(font_ref, variations, num_variations) }
+            .unwrap_or_else(|| harfrust::ShaperInstance::from_coords(font_ref, std::iter::empty()));
+        handle::into_handle(Box::new(HarfRustInstance { tag: TypeTag::new(), inner }))
+    })
+}
+
+/// Shapes text in a buffer using the given font, variation instance (null for
+/// the default instance) and OpenType features. The buffer is consumed.
+///
+/// # Safety
+///
+/// `font`, `instance` and `buffer` must be null or live handles; and
+/// `features` must be null or point to `num_features` features.
+#[export_name = symbol!("harfrust_shape_with_instance")]
+pub unsafe extern "C" fn harfrust_shape_with_instance(
+    font: *const HarfRustFont,
+    instance: *const HarfRustInstance,
+    buffer: *mut HarfRustBuffer,
+    features: *const HarfRustFeature,
+    num_features: u32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let Ok(instance) = handle::resolve_optional(instance) else {
+            return std::ptr::null_mut();
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let instance = instance.map(|instance| unsafe { &(*instance).inner });
+        let mut buffer_box = unsafe { handle::release(buffer) };
+        let shaper = font_wrapper
+            .shaper_data
+            .shaper(&font_wrapper.font_ref)
+            .instance(instance)
+            .build();
+
+        // Guess segment properties only if direction is not explicitly set
+        if buffer_box.inner.direction() == harfrust::Direction::Invalid {
+            buffer_box.inner.guess_segment_properties();
+        }
+
+        let spaces_normalized = buffer_box.normalize_spaces(font_wrapper);
+        let rust_features = unsafe { convert_features(features, num_features) };
+        let unicode_buffer = std::mem::take(&mut buffer_box.inner);
+        let text = buffer_box.shaped_text();
+        let mut result = font_wrapper.shape(&shaper, instance, text.iter().copied(), unicode_buffer, &rust_features);
+        font_wrapper.adjust_positions(&result.infos_cache, &mut result.positions_cache);
+        result.spaces_normalized = spaces_normalized;
+        handle::into_handle(Box::new(result))
+    })
+}
+
+/// Frees a variation instance.
+///
+/// # Safety
+///
+/// `instance` must be null or a live handle.
+#[export_name = symbol!("harfrust_instance_free")]
+pub unsafe extern "C" fn harfrust_instance_free(instance: *mut HarfRustInstance) {
+    error::guard(|| {
+        if instance.is_null() {
+            return;
+        }
+        if let Ok(instance) = handle::resolve(instance) {
+            unsafe { drop(handle::release(instance)) };
+        }
+    })
+}
+
+// =============================================================================
+// Version functions
+// =============================================================================
+
+/// Writes the version of this library to the given (optional) pointers, so a
+/// host can check that the binary it loaded matches its bindings.
+///
+/// # Safety
+///
+/// `out_major` must be null or point to a writable `u32`; `out_minor` must be
+/// null or point to a writable `u32`; and `out_patch` must be null or point
+/// to a writable `u32`.
+#[export_name = symbol!("harfrust_version")]
+pub unsafe extern "C" fn harfrust_version(out_major: *mut u32, out_minor: *mut u32, out_patch: *mut u32) {
+    error::guard(|| {
+        let major = env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap_or(0);
+        let minor = env!("CARGO_PKG_VERSION_MINOR").parse().unwrap_or(0);
+        let patch = env!("CARGO_PKG_VERSION_PATCH").parse().unwrap_or(0);
+        for (out, value) in [(out_major, major), (out_minor, minor), (out_patch, patch)] {
+            if !out.is_null() {
+                unsafe { *out = value };
+            }
+        }
+    })
+}
+
+/// Returns the version of the harfrust shaping engine the library was built
+/// with (e.g. "0.5.2") as a static NUL-terminated string, which must not be
+/// freed.
+#[export_name = symbol!("harfrust_engine_version_string")]
+pub extern "C" fn harfrust_engine_version_string() -> *const c_char {
+    error::guard(|| concat!(env!("HARFRUST_ENGINE_VERSION"), "\0").as_ptr().cast())
+}
+
+// =============================================================================
+// Unicode functions
+// =============================================================================
+
+/// Writes the version of the Unicode Character Database the shaping engine
+/// was built from (e.g. 17.0.0) to the given (optional) pointers. Characters
+/// assigned in later versions shape with default properties.
+///
+/// # Safety
+///
+/// `out_major` must be null or point to a writable `u32`; `out_minor` must be
+/// null or point to a writable `u32`; and `out_micro` must be null or point
+/// to a writable `u32`.
+#[export_name = symbol!("harfrust_unicode_version")]
+pub unsafe extern "C" fn harfrust_unicode_version(
+    out_major: *mut u32,
+    out_minor: *mut u32,
+    out_micro: *mut u32,
+) {
+    error::guard(|| {
+        let (major, minor, micro) = scripts::UNICODE_VERSION;
+        for (out, value) in [(out_major, major), (out_minor, minor), (out_micro, micro)] {
+            if !out.is_null() {
+                unsafe { *out = value };
+            }
+        }
+    })
+}
+
+/// Copies the OpenType script tags (e.g. 'Arab', 'Deva') of the scripts with
+/// a dedicated shaper into `out`. Other scripts are shaped by applying the
+/// font's lookups without script-specific reordering or joining. At most
+/// `capacity` tags are written. Returns the total number of scripts, so a call
+/// with a null `out` can be used to size the array.
+///
+/// # Safety
+///
+/// `out` must be null or have room for `capacity` entries.
+#[export_name = symbol!("harfrust_supported_scripts")]
+pub unsafe extern "C" fn harfrust_supported_scripts(out: *mut u32, capacity: u32) -> i32 {
+    error::guard(|| {
+        let scripts = scripts::COMPLEX_SCRIPTS;
+        if !out.is_null() && capacity > 0 {
+            let count = scripts.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            for (slot, script) in out_slice.iter_mut().zip(scripts) {
+                *slot = u32::from_be_bytes(script.tag().into_bytes());
+            }
+        }
+        scripts.len() as i32
+    })
+}
+
+/// Writes to `out` how well the font supports the script with the given ISO
+/// 15924 tag (e.g. "Deva" = 0x44657661), for ranking fallback fonts: `Full`
+/// if it maps characters of the script and has GSUB lookups for it (or the
+/// script has no dedicated shaper), `CmapOnly` if it only maps characters,
+/// `Unsupported` if it maps none. Reading the `cmap` makes this too slow to
+/// call per run; cache the result per font. Returns `NullArgument` if the
+/// font or `out` is null, or `InvalidArgument` for an unknown script tag.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `out` must be null or point to a
+/// writable `HarfRustScriptSupport`.
+#[export_name = symbol!("harfrust_font_script_support")]
+pub unsafe extern "C" fn harfrust_font_script_support(
+    font: *const HarfRustFont,
+    script_tag: u32,
+    out: *mut HarfRustScriptSupport,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+        let tag = Tag::new(&script_tag.to_be_bytes());
+        let Some(script) = harfrust::Script::from_iso15924_tag(tag)
+            .filter(|&script| script != harfrust::script::UNKNOWN)
+        else {
+            return error::fail(HarfRustStatus::InvalidArgument, format!("invalid script tag '{tag}'"));
+        };
+
+        let font_ref = &unsafe { &*font }.font_ref;
+        let support = if !scripts::cmap_covers(font_ref, script) {
+            HarfRustScriptSupport::Unsupported
+        } else if !scripts::COMPLEX_SCRIPTS.contains(&script) || features::gsub_has_script(font_ref, script.tag()) {
+            HarfRustScriptSupport::Full
+        } else {
+            HarfRustScriptSupport::CmapOnly
+        };
+        unsafe { *out = support };
+
+        HarfRustStatus::Ok
+    })
+}
+
+// =============================================================================
+// Error functions
+// =============================================================================
+
+/// Returns the status of the last failure on the calling thread, or `Ok` if
+/// none was recorded. Failing calls record their status and a message;
+/// successful calls leave the record unchanged, so check it only after a
+/// call has failed.
+#[export_name = symbol!("harfrust_last_error_code")]
+pub extern "C" fn harfrust_last_error_code() -> HarfRustStatus {
+    error::guard(|| {
+        error::status()
+    })
+}
+
+/// Returns the message of the last failure on the calling thread as a
+/// NUL-terminated UTF-8 string, e.g. "font data cannot be parsed: ...", or
+/// null if none was recorded. Free it with `harfrust_string_free`.
+#[export_name = symbol!("harfrust_last_error_message")]
+pub extern "C" fn harfrust_last_error_message() -> *mut c_char {
+    error::guard(|| {
+        match error::message() {
+            Some(message) => {
+                let message = std::ffi::CString::new(message.replace('\0', "")).unwrap_or_default();
+                message.into_raw()
+            }
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Forgets the last failure on the calling thread.
+#[export_name = symbol!("harfrust_clear_last_error")]
+pub extern "C" fn harfrust_clear_last_error() {
+    error::guard(|| {
+        error::clear();
+    })
+}
+
+/// Frees a string returned by this library.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by this library that has not
+/// been freed.
+#[export_name = symbol!("harfrust_string_free")]
+pub unsafe extern "C" fn harfrust_string_free(string: *mut c_char) {
+    error::guard(|| {
+        if !string.is_null() {
+            unsafe { drop(std::ffi::CString::from_raw(string)) };
+        }
+    })
+}
+
+// =============================================================================
+// Library functions
+// =============================================================================
+
+/// Applies library-wide settings (see `HarfRustConfig`); a null `config`
+/// restores the defaults. May be called again to change them, and need not
+/// be called at all. Handles created before the call stay valid. Returns
+/// `InvalidArgument` if `config.size` is smaller than the `size` field.
+///
+/// # Safety
+///
+/// `config` must be null or point to a `HarfRustConfig`.
+#[export_name = symbol!("harfrust_init")]
+pub unsafe extern "C" fn harfrust_init(config: *const HarfRustConfig) -> HarfRustStatus {
+    error::guard(|| {
+        let mut settings = HarfRustConfig::default();
+        if !config.is_null() {
+            let size = unsafe { (*config).size } as usize;
+            if size < std::mem::size_of::<u32>() {
+                return error::fail(HarfRustStatus::InvalidArgument, "config size is too small");
+            }
+            // Fields past the caller's size keep their defaults
+            let len = size.min(std::mem::size_of::<HarfRustConfig>());
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    config.cast::<u8>(),
+                    (&mut settings as *mut HarfRustConfig).cast::<u8>(),
+                    len,
+                );
+            }
+        }
+
+        cache::configure(settings.result_cache_max_entries as usize, settings.result_cache_max_bytes as usize);
+        let plan_cache_capacity = match settings.plan_cache_capacity {
+            0 => PLAN_CACHE_CAPACITY,
+            n => n as usize,
+        };
+        PLAN_CACHE_LIMIT.store(plan_cache_capacity, Ordering::Relaxed);
+        DEFAULT_THREADS.store(settings.num_threads, Ordering::Relaxed);
+        handle::set_checked(settings.check_handles != 0);
+        LOG_LEVEL.store(settings.log_level as i32, Ordering::Relaxed);
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Restores the default settings and releases global state: the result
+/// cache is emptied and disabled, the log callback removed and the small-run
+/// fast path re-enabled. Live handles stay valid and must still be freed.
+#[export_name = symbol!("harfrust_shutdown")]
+pub extern "C" fn harfrust_shutdown() {
+    error::guard(|| {
+        unsafe { harfrust_init(std::ptr::null()) };
+        *LOGGER.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        fastpath::set_enabled(true);
+    })
+}
+
+/// Routes all of the library's native allocations through host callbacks,
+/// e.g. to attribute leaks in a long-running service. Memory must be freed by
+/// the allocator that returned it, so this must be the first call into the
+/// library and cannot be undone; `realloc_fn` may be null, in which case
+/// resizing allocates, copies and frees. The callbacks may be called on any
+/// thread and must not call back into the library.
+///
+/// Returns `NullArgument` if `alloc_fn` or `free_fn` is null, and
+/// `Unsupported` if the library was built without the `host-allocator`
+/// feature or has already allocated.
+///
+/// # Safety
+///
+/// `alloc_fn`, `free_fn` and `realloc_fn` must be null or behave like
+/// `malloc`, `free` and `realloc` when called with `user_data` from any
+/// thread.
+#[export_name = symbol!("harfrust_set_allocator")]
+pub unsafe extern "C" fn harfrust_set_allocator(
+    alloc_fn: HarfRustAllocFn,
+    free_fn: HarfRustFreeFn,
+    realloc_fn: HarfRustReallocFn,
+    user_data: *mut c_void,
+) -> HarfRustStatus {
+    error::guard(|| {
+        if alloc_fn.is_none() || free_fn.is_none() {
+            return error::fail(HarfRustStatus::NullArgument, "allocation callbacks are null");
+        }
+        #[cfg(feature = "host-allocator")]
+        if allocator::register(alloc_fn, free_fn, realloc_fn, user_data) {
+            return HarfRustStatus::Ok;
+        }
+        #[cfg(feature = "host-allocator")]
+        let message = "the library has already allocated memory";
+        #[cfg(not(feature = "host-allocator"))]
+        let message = {
+            let _ = (realloc_fn, user_data);
+            "the library was built without the host-allocator feature"
+        };
+        error::fail(HarfRustStatus::Unsupported, message)
+    })
+}
+
+// =============================================================================
+// Logging functions
+// =============================================================================
+
+/// Installs the callback that receives diagnostic messages, such as the
+/// warnings of `HarfRustParseMode::Tolerant`, replacing any previous one.
+/// A null callback removes it. `user_data` is passed back on every call.
+///
+/// # Safety
+///
+/// `callback` must be null or safe to call with `user_data` and a
+/// NUL-terminated message from any thread.
+#[export_name = symbol!("harfrust_set_log_callback")]
+pub unsafe extern "C" fn harfrust_set_log_callback(
+    callback: HarfRustLogCallback,
+    user_data: *mut c_void,
+) {
+    error::guard(|| {
+        let mut logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+        *logger = callback.map(|_| (callback, user_data as usize));
+    })
+}
+
+// =============================================================================
+// Result cache functions
+// =============================================================================
+
+/// Enables (non-zero, the default) or disables the small-run fast path:
+/// `harfrust_shape`, `harfrust_shape_with_features` and `harfrust_shape_full`
+/// (without variations) answer left-to-right Latin or Common runs of up to
+/// 8 printable ASCII characters directly from `cmap` and `hmtx` when none of
+/// their glyphs can start a GSUB or GPOS lookup and the font has no AAT
+/// tables or legacy `kern` kerning. Results are the same either way.
+#[export_name = symbol!("harfrust_set_small_run_fast_path")]
+pub extern "C" fn harfrust_set_small_run_fast_path(enabled: i32) {
+    error::guard(|| {
+        fastpath::set_enabled(enabled != 0);
+    })
+}
+
+/// Enables the process-wide cache of shaping results, or changes its limits.
+/// `harfrust_shape`, `harfrust_shape_with_features` and `harfrust_shape_full`
+/// then answer repeated combinations of font, text, segment properties,
+/// features and variations from the cache. The least recently used entries
+/// are evicted beyond `max_entries` results or about `max_bytes` bytes of
+/// memory. A limit of 0 disables the cache and frees its entries (the
+/// default). Always returns `Ok`.
+#[export_name = symbol!("harfrust_cache_configure")]
+pub extern "C" fn harfrust_cache_configure(max_entries: u32, max_bytes: u64) -> HarfRustStatus {
+    error::guard(|| {
+        let max_bytes = usize::try_from(max_bytes).unwrap_or(usize::MAX);
+        cache::configure(max_entries as usize, max_bytes);
+        HarfRustStatus::Ok
+    })
+}
+
+/// Drops every cached shaping result, keeping the cache enabled.
+#[export_name = symbol!("harfrust_cache_clear")]
+pub extern "C" fn harfrust_cache_clear() {
+    error::guard(|| {
+        cache::clear();
+    })
+}
+
+/// Writes the number of cached shaping results and the approximate memory
+/// they use to the given (optional) pointers.
+///
+/// # Safety
+///
+/// `out_entries` must be null or point to a writable `u32`, and `out_bytes`
+/// must be null or point to a writable `u64`.
+#[export_name = symbol!("harfrust_cache_stats")]
+pub unsafe extern "C" fn harfrust_cache_stats(out_entries: *mut u32, out_bytes: *mut u64) {
+    error::guard(|| {
+        let (entries, bytes) = cache::usage();
+        if !out_entries.is_null() {
+            unsafe { *out_entries = entries as u32 };
+        }
+        if !out_bytes.is_null() {
+            unsafe { *out_bytes = bytes as u64 };
+        }
+    })
+}
+
+/// Writes the native memory and live handle counts of the whole process to
+/// `out`, for monitoring memory the managed garbage collector cannot see.
+/// Handles retained with `harfrust_font_retain` count once. Returns
+/// `NullArgument` if `out` is null.
+///
+/// # Safety
+///
+/// `out` must be null or point to a writable `HarfRustMemoryStats`.
+#[export_name = symbol!("harfrust_memory_stats")]
+pub unsafe extern "C" fn harfrust_memory_stats(out: *mut HarfRustMemoryStats) -> HarfRustStatus {
+    error::guard(|| {
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let (result_cache_entries, result_cache_bytes) = cache::usage();
+        unsafe {
+            *out = HarfRustMemoryStats {
+                font_data_bytes: stats::get(&stats::FONT_DATA_BYTES) as u64,
+                mapped_font_bytes: stats::get(&stats::MAPPED_FONT_BYTES) as u64,
+                advance_cache_bytes: stats::get(&stats::ADVANCE_CACHE_BYTES) as u64,
+                result_cache_bytes: result_cache_bytes as u64,
+                result_cache_entries: result_cache_entries as u32,
+                shape_plans: stats::get(&stats::SHAPE_PLANS) as u32,
+                pooled_buffers: stats::get(&stats::POOLED_BUFFERS) as u32,
+                fonts: handle::live_count::<HarfRustFont>() as u32,
+                buffers: handle::live_count::<HarfRustBuffer>() as u32,
+                buffer_states: handle::live_count::<HarfRustBufferState>() as u32,
+                glyph_buffers: handle::live_count::<HarfRustGlyphBuffer>() as u32,
+                color_bitmaps: handle::live_count::<HarfRustColorBitmap>() as u32,
+                subsetters: handle::live_count::<HarfRustSubsetter>() as u32,
+                blobs: handle::live_count::<HarfRustBlob>() as u32,
+                shapers: handle::live_count::<HarfRustShaper>() as u32,
+                instances: handle::live_count::<HarfRustInstance>() as u32,
+            }
+        };
+
+        HarfRustStatus::Ok
+    })
+}
+
+// =============================================================================
+// Glyph buffer functions
+// =============================================================================
+
+/// Returns 1 if the glyph buffer was produced by fallback shaping without
+/// layout tables (see `harfrust_shape_fallback`), 0 if not, or -1 if the
+/// buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_is_degraded")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_is_degraded(
+    buffer: *const HarfRustGlyphBuffer,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        buffer_ref.degraded as i32
+    })
+}
+
+/// Returns the `HarfRustResultFlags` bits describing how a shaping result
+/// was produced, or a negative status if the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_get_flags")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_flags(
+    buffer: *const HarfRustGlyphBuffer,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        let mut flags = 0;
+        if buffer_ref.degraded {
+            flags |= HarfRustResultFlags::Degraded as i32;
+        }
+        if buffer_ref.spaces_normalized {
+            flags |= HarfRustResultFlags::SpacesNormalized as i32;
+        }
+        flags
+    })
+}
+
+/// Writes the number of .notdef glyphs and inserted dotted circles in a
+/// shaping result to `out`, for logging text quality without reading the
+/// glyph arrays. `font` is the font the buffer was shaped with, which
+/// identifies its dotted circle glyph. Returns `NullArgument` if the font,
+/// buffer or `out` is null.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles, and `out` must be null
+/// or point to a writable `HarfRustShapeSummary`.
+#[export_name = symbol!("harfrust_glyph_buffer_get_summary")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_summary(
+    font: *const HarfRustFont,
+    buffer: *const HarfRustGlyphBuffer,
+    out: *mut HarfRustShapeSummary,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status,
+        };
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let buffer_ref = unsafe { &*buffer };
+        // Fonts without a dotted circle get none inserted
+        let dotted_circle = unsafe { &*font }
+            .font_ref
+            .charmap()
+            .map('\u{25CC}')
+            .map(|glyph| glyph.to_u32())
+            .filter(|&glyph| glyph != 0);
+        let count = |glyph: Option<u32>| {
+            buffer_ref.infos_cache.iter().filter(|info| Some(info.glyph_id) == glyph).count() as u32
+        };
+        unsafe {
+            *out = HarfRustShapeSummary {
+                glyph_count: buffer_ref.infos_cache.len() as u32,
+                notdef_count: count(Some(0)),
+                dotted_circle_count: count(dotted_circle),
+                degraded: buffer_ref.degraded as u32,
+            };
+        }
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns the number of glyphs in the glyph buffer.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_len")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_len(buffer: *const HarfRustGlyphBuffer) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        buffer_ref.infos_cache.len() as i32
+    })
+}
+
+/// Returns a pointer to the glyph info array.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_get_infos")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_infos(
+    buffer: *const HarfRustGlyphBuffer,
+) -> *const HarfRustGlyphInfo {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null();
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        buffer_ref.infos_cache.as_ptr()
+    })
+}
+
+/// Returns a pointer to the glyph position array.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_get_positions")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_positions(
+    buffer: *const HarfRustGlyphBuffer,
+) -> *const HarfRustGlyphPosition {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null();
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        buffer_ref.positions_cache.as_ptr()
+    })
+}
+
+/// Recovers the reading order of a line for text extraction. `runs` are
+/// the horizontal runs of the line as placed on the page, left to right,
+/// possibly shaped by separate calls with different fonts and directions. The
+/// characters of their text are written to `out` in logical order, with
+/// their positions on the line; text that maps to no glyph is omitted.
+/// `direction` is the line's base direction, or `Invalid` to take it from
+/// the text at the line's ends. At most `capacity` entries are written.
+/// Returns the total number of characters, -1 (`NullArgument`) if `runs`,
+/// a run's glyphs or its text is null, or -2 (`InvalidArgument`) if a length
+/// is negative or `direction` is vertical.
+///
+/// # Safety
+///
+/// `runs` must be null or point to `num_runs` entries, and in each run
+/// `glyphs` must be null or a live handle and `text` must be null or point to
+/// `len` UTF-16 code units; and `out` must be null or have room for
+/// `capacity` entries.
+#[export_name = symbol!("harfrust_line_extract_text")]
+pub unsafe extern "C" fn harfrust_line_extract_text(
+    runs: *const HarfRustLineRun,
+    num_runs: u32,
+    direction: HarfRustDirection,
+    out: *mut HarfRustExtractedChar,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        if runs.is_null() && num_runs > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "runs is null") as i32;
+        }
+        let base = match direction {
+            HarfRustDirection::Invalid => None,
+            HarfRustDirection::LeftToRight => Some(bidi::Strong::Left),
+            HarfRustDirection::RightToLeft => Some(bidi::Strong::Right),
+            _ => {
+                return error::fail(HarfRustStatus::InvalidArgument, "line direction is vertical") as i32;
+            }
+        };
+
+        let descriptors = if num_runs == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(runs, num_runs as usize) }
+        };
+        let mut line = Vec::with_capacity(descriptors.len());
+        for (i, run) in descriptors.iter().enumerate() {
+            let glyphs = match handle::resolve(run.glyphs) {
+                Ok(glyphs) => unsafe { &*glyphs },
+                Err(status) => return status as i32,
+            };
+            if run.text.is_null() {
+                return error::fail(HarfRustStatus::NullArgument, format!("text of run {i} is null")) as i32;
+            }
+            if run.len < 0 {
+                return error::fail(HarfRustStatus::InvalidArgument, format!("length of run {i} is negative"))
+                    as i32;
+            }
+            line.push(extract::Run {
+                infos: &glyphs.infos_cache,
+                positions: &glyphs.positions_cache,
+                text: unsafe { std::slice::from_raw_parts(run.text, run.len as usize) },
+                x: run.x,
+                scale: run.scale,
+            });
+        }
+
+        let base = base.unwrap_or_else(|| extract::detect_base(&line));
+        let chars = extract::logical_chars(&line, base);
+        if !out.is_null() {
+            let count = chars.len().min(capacity as usize);
+            unsafe { std::ptr::copy_nonoverlapping(chars.as_ptr(), out, count) };
+        }
+        chars.len() as i32
+    })
+}
+
+/// Writes, for each glyph, the range of the UTF-16 source text it represents.
+/// Clusters are expected to be UTF-16 offsets (as produced by
+/// `harfrust_buffer_add_utf16`). When several glyphs share a cluster, the first
+/// one in buffer order carries the cluster's whole text and the others get an
+/// empty range, so concatenating the ranges never duplicates text. Ranges are
+/// clamped to `len` and never split a surrogate pair.
+/// At most `capacity` entries are written. Returns the number of glyphs, -1
+/// (`NullArgument`) if the buffer or text is null or -2 (`InvalidArgument`) if
+/// `len` is negative.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle; `text` must be null or point to
+/// `len` UTF-16 code units; and `out` must be null or have room for
+/// `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_get_text_ranges")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_text_ranges(
+    buffer: *const HarfRustGlyphBuffer,
+    text: *const u16,
+    len: i32,
+    out: *mut HarfRustTextRange,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null") as i32;
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative") as i32;
+        }
+
+        let buffer_ref = unsafe { &*buffer };
+        let num_glyphs = buffer_ref.infos_cache.len();
+        if out.is_null() || capacity == 0 {
+            return num_glyphs as i32;
+        }
+
+        let text = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let ranges = glyph_text_ranges(&buffer_ref.infos_cache, text);
+        let count = num_glyphs.min(capacity as usize);
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+        out_slice.copy_from_slice(&ranges[..count]);
+
+        num_glyphs as i32
+    })
+}
+
+/// Writes, for each glyph, which substitution produced it: the kind of GSUB
+/// lookup, its index and feature, the alternate chosen and the input text
+/// range. harfrust does not record the lookups it applies, so the trace is
+/// reconstructed from the font's GSUB table on request; shaping itself costs
+/// nothing extra. Glyphs that several lookups can produce are credited to the
+/// most likely one, so the trace is a debugging aid rather than exact.
+/// `text` is the UTF-16 text the buffer was shaped from, as for
+/// `harfrust_glyph_buffer_get_text_ranges`.
+/// At most `capacity` entries are written. Returns the number of glyphs, -1
+/// (`NullArgument`) if the font, buffer or text is null or -2
+/// (`InvalidArgument`) if `len` is negative.
+///
+/// # Safety
+///
+/// `font` and `buffer` must be null or live handles; `text` must be null or
+/// point to `len` UTF-16 code units; and `out` must be null or have room for
+/// `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_get_substitution_trace")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_substitution_trace(
+    font: *const HarfRustFont,
+    buffer: *const HarfRustGlyphBuffer,
+    text: *const u16,
+    len: i32,
+    out: *mut HarfRustGlyphTrace,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+        if text.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "text is null") as i32;
+        }
+        if len < 0 {
+            return error::fail(HarfRustStatus::InvalidArgument, "len is negative") as i32;
+        }
+
+        let buffer_ref = unsafe { &*buffer };
+        let num_glyphs = buffer_ref.infos_cache.len();
+        if out.is_null() || capacity == 0 {
+            return num_glyphs as i32;
+        }
+
+        let text = unsafe { std::slice::from_raw_parts(text, len as usize) };
+        let traces = trace::trace(unsafe { &(*font).font_ref }, &buffer_ref.infos_cache, text);
+        let count = num_glyphs.min(capacity as usize);
+        unsafe { std::ptr::copy_nonoverlapping(traces.as_ptr(), out, count) };
+
+        num_glyphs as i32
+    })
+}
+
+/// Writes the summed advance of each cluster, in glyph buffer order. A cluster
+/// entry covers a run of consecutive glyphs with the same cluster value.
+/// At most `capacity` entries are written. Returns the total number of
+/// clusters, so a call with a null `out` can be used to size the array.
+/// Returns -1 if the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `out` must be null or have
+/// room for `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_get_cluster_advances")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_cluster_advances(
+    buffer: *const HarfRustGlyphBuffer,
+    out: *mut HarfRustClusterAdvance,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let clusters = cluster_advances(unsafe { &*buffer });
+        if !out.is_null() && capacity > 0 {
+            let count = clusters.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            out_slice.copy_from_slice(&clusters[..count]);
+        }
+
+        clusters.len() as i32
+    })
+}
+
+/// Clusters of a glyph buffer in glyph (visual) order; see
+/// `harfrust_glyph_buffer_get_cluster_advances`.
+fn cluster_advances(buffer: &HarfRustGlyphBuffer) -> Vec<HarfRustClusterAdvance> {
+    let glyphs = buffer.infos_cache.iter().zip(&buffer.positions_cache);
+
+    let mut clusters: Vec<HarfRustClusterAdvance> = Vec::new();
+    for (index, (info, pos)) in glyphs.enumerate() {
+        match clusters.last_mut() {
+            Some(last) if last.cluster == info.cluster => {
+                last.glyph_count += 1;
+                last.x_advance += pos.x_advance;
+                last.y_advance += pos.y_advance;
+            }
+            _ => clusters.push(HarfRustClusterAdvance {
+                cluster: info.cluster,
+                first_glyph: index as u32,
+                glyph_count: 1,
+                x_advance: pos.x_advance,
+                y_advance: pos.y_advance,
+            }),
+        }
+    }
+    clusters
+}
+
+/// Writes the permutation between the visual and logical order of the
+/// buffer's clusters, so selection and text extraction can convert between
+/// them. Visual indices are those of `harfrust_glyph_buffer_get_cluster_advances`
+/// (glyph order, reversed for right-to-left runs); logical indices order the
+/// clusters by cluster value, i.e. by text position. `out_visual_to_logical[v]`
+/// receives the logical index of visual cluster `v` and
+/// `out_logical_to_visual[l]` the visual index of logical cluster `l`; either
+/// may be null. At most `capacity` entries are written to each. Returns the
+/// total number of clusters, so a call with null arrays can be used to size
+/// them. Returns -1 if the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle; `out_visual_to_logical` must be
+/// null or have room for `capacity` entries; and `out_logical_to_visual` must
+/// be null or have room for `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_get_cluster_order")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_cluster_order(
+    buffer: *const HarfRustGlyphBuffer,
+    out_visual_to_logical: *mut u32,
+    out_logical_to_visual: *mut u32,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let clusters = cluster_advances(unsafe { &*buffer });
+        // Stable, so clusters split by reordering keep their visual order
+        let mut logical_to_visual: Vec<u32> = (0..clusters.len() as u32).collect();
+        logical_to_visual.sort_by_key(|&visual| clusters[visual as usize].cluster);
+
+        let count = clusters.len().min(capacity as usize);
+        if !out_logical_to_visual.is_null() && count > 0 {
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out_logical_to_visual, count) };
+            out_slice.copy_from_slice(&logical_to_visual[..count]);
+        }
+        if !out_visual_to_logical.is_null() && count > 0 {
+            let mut visual_to_logical = vec![0; clusters.len()];
+            for (logical, &visual) in logical_to_visual.iter().enumerate() {
+                visual_to_logical[visual as usize] = logical as u32;
+            }
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out_visual_to_logical, count) };
+            out_slice.copy_from_slice(&visual_to_logical[..count]);
+        }
+
+        clusters.len() as i32
+    })
+}
+
+/// Copies the per-glyph transforms of a synthesized layout (such as
+/// `harfrust_shape_small_caps`) into `out`. This is an opt-in companion to
+/// the position array: glyphs without a transform report scale 1.0 and
+/// rotation 0. At most `capacity` entries are written. Returns the number of
+/// glyphs, or -1 if the buffer is null.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `out` must be null or have
+/// room for `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_get_transforms")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_transforms(
+    buffer: *const HarfRustGlyphBuffer,
+    out: *mut HarfRustGlyphTransform,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        let num_glyphs = buffer_ref.infos_cache.len();
+        if !out.is_null() && capacity > 0 {
+            let count = num_glyphs.min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            for (i, slot) in out_slice.iter_mut().enumerate() {
+                *slot = buffer_ref
+                    .transforms
+                    .get(i)
+                    .copied()
+                    .unwrap_or(HarfRustGlyphTransform::IDENTITY);
+            }
+        }
+
+        num_glyphs as i32
+    })
+}
+
+/// Copies only the scale factor of each glyph transform into `out`; see
+/// `harfrust_glyph_buffer_get_transforms`.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `out` must be null or have
+/// room for `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_get_scales")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_get_scales(
+    buffer: *const HarfRustGlyphBuffer,
+    out: *mut f32,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        let num_glyphs = buffer_ref.infos_cache.len();
+        if !out.is_null() && capacity > 0 {
+            let count = num_glyphs.min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            for (i, slot) in out_slice.iter_mut().enumerate() {
+                *slot = buffer_ref.transforms.get(i).map_or(1.0, |t| t.scale);
+            }
+        }
+
+        num_glyphs as i32
+    })
+}
+
+/// Writes the glyph IDs as the 2-byte big-endian character codes of an
+/// Identity-H (or Identity-V) encoded PDF string, in glyph buffer order, ready
+/// to be placed in a string operand of Tj or TJ. Only whole glyphs are
+/// written, at most `capacity / 2` of them. Returns the number of bytes the
+/// whole buffer needs, so a call with a null `out` can be used to size the
+/// array. Returns -1 if the buffer is null, or -3 if a glyph ID does not fit
+/// in two bytes.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `out` must be null or have
+/// room for `capacity` bytes.
+#[export_name = symbol!("harfrust_glyph_buffer_to_identity_h")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_to_identity_h(
+    buffer: *const HarfRustGlyphBuffer,
+    out: *mut u8,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+
+        let buffer_ref = unsafe { &*buffer };
+        let infos = &buffer_ref.infos_cache;
+        if infos.iter().any(|info| info.glyph_id > u16::MAX as u32) {
+            return error::fail(HarfRustStatus::OutOfRange, "glyph IDs above 65535 cannot be encoded as Identity-H") as i32;
+        }
+        let num_bytes = infos.len() * 2;
+        if out.is_null() || capacity < 2 {
+            return num_bytes as i32;
+        }
+
+        let count = infos.len().min(capacity as usize / 2);
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count * 2) };
+        for (info, code) in infos.iter().zip(out_slice.chunks_exact_mut(2)) {
+            code.copy_from_slice(&(info.glyph_id as u16).to_be_bytes());
+        }
+
+        num_bytes as i32
+    })
+}
+
+/// Compares the shaped advances of a buffer with the widths a PDF font
+/// dictionary declares for its glyphs, so a viewer can decide whether to
+/// trust the embedded widths or the font. Character codes are glyph IDs, as
+/// written by `harfrust_glyph_buffer_to_identity_h`: `widths[i]` is the
+/// width of glyph `first_code + i` in PDF glyph space (1000 units per em),
+/// and glyphs outside the array have `missing_width` (the font descriptor's
+/// MissingWidth). The buffer must hold unscaled advances shaped with `font`.
+/// Each glyph's declared width minus its shaped advance, in PDF glyph space,
+/// is written to `out` in glyph buffer order: positive where the PDF draws
+/// the glyph wider than the font. At most `capacity` deltas are written.
+/// Returns the number of glyphs, so a call with a null `out` can be used to
+/// size the array. Returns -1 if the buffer or font is null, or if `widths`
+/// is null with a non-zero count.
+///
+/// # Safety
+///
+/// `buffer` and `font` must be null or live handles; `widths` must be null or
+/// point to `num_widths` entries; and `out` must be null or have room for
+/// `capacity` entries.
+#[export_name = symbol!("harfrust_glyph_buffer_width_deltas")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_width_deltas(
+    buffer: *const HarfRustGlyphBuffer,
+    font: *const HarfRustFont,
+    first_code: u32,
+    widths: *const f32,
+    num_widths: u32,
+    missing_width: f32,
+    out: *mut f32,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status as i32,
+        };
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if widths.is_null() && num_widths > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "widths is null") as i32;
+        }
+
+        let buffer_ref = unsafe { &*buffer };
+        let widths = if num_widths == 0 {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(widths, num_widths as usize) }
+        };
+        let scale = 1000.0 / unsafe { &*font }.units_per_em.max(1) as f32;
+        let num_glyphs = buffer_ref.infos_cache.len();
+        if !out.is_null() && capacity > 0 {
+            let count = num_glyphs.min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, count) };
+            let glyphs = buffer_ref.infos_cache.iter().zip(&buffer_ref.positions_cache);
+            for (slot, (info, pos)) in out_slice.iter_mut().zip(glyphs) {
+                let declared = info
+                    .glyph_id
+                    .checked_sub(first_code)
+                    .and_then(|index| widths.get(index as usize))
+                    .copied()
+                    .unwrap_or(missing_width);
+                *slot = declared - pos.x_advance as f32 * scale;
+            }
+        }
+
+        num_glyphs as i32
+    })
+}
+
+/// Creates a new glyph buffer holding only the glyphs whose cluster lies in
+/// `[start_cluster, end_cluster)`, in their original order. Since whole
+/// clusters are selected, a ligature is never split. The source buffer is not
+/// modified. Returns null if the buffer is null or the range is empty.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_slice")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_slice(
+    buffer: *const HarfRustGlyphBuffer,
+    start_cluster: u32,
+    end_cluster: u32,
+) -> *mut HarfRustGlyphBuffer {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+        if start_cluster >= end_cluster {
+            return error::fail_null(HarfRustStatus::InvalidArgument, "cluster range is empty");
+        }
+
+        let buffer_ref = unsafe { &*buffer };
+        let selected: Vec<usize> = (0..buffer_ref.infos_cache.len())
+            .filter(|&i| (start_cluster..end_cluster).contains(&buffer_ref.infos_cache[i].cluster))
+            .collect();
+
+        let wrapper = HarfRustGlyphBuffer {
+            tag: TypeTag::new(),
+            inner: None,
+            infos_cache: selected.iter().map(|&i| buffer_ref.infos_cache[i]).collect(),
+            positions_cache: selected.iter().map(|&i| buffer_ref.positions_cache[i]).collect(),
+            transforms: if buffer_ref.transforms.is_empty() {
+                Vec::new()
+            } else {
+                selected.iter().map(|&i| buffer_ref.transforms[i]).collect()
+            },
+            degraded: buffer_ref.degraded,
+            spaces_normalized: buffer_ref.spaces_normalized,
+        };
+
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Clears the glyph buffer and returns a new unicode buffer for reuse.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_into_buffer")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_into_buffer(
+    buffer: *mut HarfRustGlyphBuffer,
+) -> *mut HarfRustBuffer {
+    error::guard(|| {
+        let Ok(buffer) = handle::resolve(buffer) else {
+            return std::ptr::null_mut();
+        };
+
+        let buffer_box = unsafe { handle::release(buffer) };
+        let unicode_buffer = match buffer_box.inner {
+            Some(glyph_buffer) => glyph_buffer.clear(),
+            None => scratch::unicode_buffer(),
+        };
+
+        let wrapper = HarfRustBuffer::new(unicode_buffer);
+        handle::into_handle(Box::new(wrapper))
+    })
+}
+
+/// Frees a glyph buffer previously created by `harfrust_shape`.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[export_name = symbol!("harfrust_glyph_buffer_free")]
+pub unsafe extern "C" fn harfrust_glyph_buffer_free(buffer: *mut HarfRustGlyphBuffer) {
+    error::guard(|| {
+        if buffer.is_null() {
+            return;
+        }
+        if let Ok(buffer) = handle::resolve(buffer) {
+            let buffer_box = unsafe { handle::release(buffer) };
+            if let Some(glyph_buffer) = buffer_box.inner {
+                scratch::recycle_glyphs(glyph_buffer);
+            }
+        }
+    })
+}
+
+// =============================================================================
+// Subsetting functions
+// =============================================================================
+
+/// Computes the glyph closure of a seed glyph set: the seeds plus .notdef,
+/// every glyph reachable from them through GSUB substitutions, and the
+/// components of composite glyphs. GSUB traversal can be limited to the given
+/// OpenType script tags and feature tags (4 bytes as u32); a null pointer
+/// selects all of them. Writes the closure sorted by glyph ID; at most
+/// `capacity` entries are written. Returns the closure size, so a call with a
+/// null `out` can be used to size the array. Returns -1 if the font is null,
+/// or -2 if `glyphs` is null with a non-zero count.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `glyphs` must be null or point to
+/// `count` glyph IDs; `scripts` must be null or point to `num_scripts`
+/// entries; `features` must be null or point to `num_features` entries; and
+/// `out` must be null or have room for `capacity` entries.
+#[export_name = symbol!("harfrust_font_glyph_closure")]
+pub unsafe extern "C" fn harfrust_font_glyph_closure(
+    font: *const HarfRustFont,
+    glyphs: *const u32,
+    count: u32,
+    scripts: *const u32,
+    num_scripts: u32,
+    features: *const u32,
+    num_features: u32,
+    out: *mut u32,
+    capacity: u32,
+) -> i32 {
+    error::guard(|| {
+        let font = match handle::resolve(font) {
+            Ok(font) => font,
+            Err(status) => return status as i32,
+        };
+        if glyphs.is_null() && count > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "glyphs is null") as i32;
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let seeds: &[u32] = if count == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(glyphs, count as usize) }
+        };
+        let to_tags = |tags: *const u32, len: u32| -> Option<Vec<Tag>> {
+            if tags.is_null() {
+                return None;
+            }
+            let slice = unsafe { std::slice::from_raw_parts(tags, len as usize) };
+            Some(slice.iter().map(|tag| Tag::new(&tag.to_be_bytes())).collect())
+        };
+        let scripts = to_tags(scripts, num_scripts);
+        let features = to_tags(features, num_features);
+
+        let closure = subset::glyph_closure(
+            &font_wrapper.font_ref,
+            seeds,
+            scripts.as_deref(),
+            features.as_deref(),
+        );
+
+        if !out.is_null() && capacity > 0 {
+            let written = closure.len().min(capacity as usize);
+            let out_slice = unsafe { std::slice::from_raw_parts_mut(out, written) };
+            out_slice.copy_from_slice(&closure[..written]);
+        }
+
+        closure.len() as i32
+    })
+}
+
+/// Subsets `font` to the closure of `seeds` and writes the tagged PostScript
+/// name to `out_name` (see `harfrust_font_subset`).
+unsafe fn build_subset(
+    font: &HarfRustFont,
+    seeds: &[u32],
+    mode: HarfRustSubsetMode,
+    out_name: *mut c_char,
+    name_capacity: u32,
+) -> *mut HarfRustBlob {
+    let retain_gids = mode == HarfRustSubsetMode::RetainGids;
+    let Some(subset) = subset::subset_font(&font.font_ref, seeds, retain_gids) else {
+        return error::fail_null(HarfRustStatus::InvalidFont, "font cannot be subset");
+    };
+
+    if !out_name.is_null() && name_capacity > 0 {
+        let name = subset.postscript_name.as_bytes();
+        let written = name.len().min(name_capacity as usize - 1);
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out_name as *mut u8, written + 1) };
+        out_slice[..written].copy_from_slice(&name[..written]);
+        out_slice[written] = 0;
+    }
+
+    handle::into_handle(Box::new(HarfRustBlob { tag: TypeTag::new(), data: subset.data }))
+}
+
+/// Builds a subset of a TrueType-outline (`glyf`) font that keeps the glyph
+/// closure of `glyphs` (see `harfrust_font_glyph_closure`). In `Compact` mode
+/// the kept glyphs are renumbered in glyph ID order, so the new ID of a glyph
+/// is its index in the closure, and tables indexed by glyph ID (cmap, layout
+/// and color tables) are dropped. In `RetainGids` mode glyph IDs and all other
+/// tables are unchanged. The family, unique, full and PostScript names are
+/// prefixed with a six-letter subset tag ("ABCDEF+"); the new PostScript name
+/// is written to `out_name` as a NUL-terminated string, truncated to
+/// `name_capacity` bytes (72 always suffice for valid names). `out_name` may
+/// be null. Returns null if the font is null, `glyphs` is null with a
+/// non-zero count, or the font has no `glyf` outlines.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle; `glyphs` must be null or point to
+/// `count` glyph IDs; and `out_name` must be null or have room for
+/// `name_capacity` bytes.
+#[export_name = symbol!("harfrust_font_subset")]
+pub unsafe extern "C" fn harfrust_font_subset(
+    font: *const HarfRustFont,
+    glyphs: *const u32,
+    count: u32,
+    mode: HarfRustSubsetMode,
+    out_name: *mut c_char,
+    name_capacity: u32,
+) -> *mut HarfRustBlob {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        if glyphs.is_null() && count > 0 {
+            return error::fail_null(HarfRustStatus::NullArgument, "glyphs is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let seeds: &[u32] = if count == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(glyphs, count as usize) }
+        };
+        unsafe { build_subset(font_wrapper, seeds, mode, out_name, name_capacity) }
+    })
+}
+
+/// Creates an empty subsetter.
+#[export_name = symbol!("harfrust_subsetter_new")]
+pub extern "C" fn harfrust_subsetter_new() -> *mut HarfRustSubsetter {
+    error::guard(|| {
+        handle::into_handle(Box::new(HarfRustSubsetter { tag: TypeTag::new(), glyphs: BTreeSet::new() }))
+    })
+}
+
+/// Adds glyph IDs to the subsetter's glyph set.
+///
+/// # Safety
+///
+/// `subsetter` must be null or a live handle, and `glyphs` must be null or
+/// point to `count` glyph IDs.
+#[export_name = symbol!("harfrust_subsetter_add_glyphs")]
+pub unsafe extern "C" fn harfrust_subsetter_add_glyphs(
+    subsetter: *mut HarfRustSubsetter,
+    glyphs: *const u32,
+    count: u32,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let subsetter = match handle::resolve(subsetter) {
+            Ok(subsetter) => subsetter,
+            Err(status) => return status,
+        };
+        if glyphs.is_null() && count > 0 {
+            return error::fail(HarfRustStatus::NullArgument, "glyphs is null");
+        }
+        if count == 0 {
+            return HarfRustStatus::Ok;
+        }
+
+        let subsetter_ref = unsafe { &mut *subsetter };
+        let glyphs = unsafe { std::slice::from_raw_parts(glyphs, count as usize) };
+        subsetter_ref.glyphs.extend(glyphs);
+        HarfRustStatus::Ok
+    })
+}
+
+/// Adds every glyph of a shaping result to the subsetter's glyph set. The
+/// glyph buffer is not consumed.
+///
+/// # Safety
+///
+/// `subsetter` and `buffer` must be null or live handles.
+#[export_name = symbol!("harfrust_subsetter_add_glyph_buffer")]
+pub unsafe extern "C" fn harfrust_subsetter_add_glyph_buffer(
+    subsetter: *mut HarfRustSubsetter,
+    buffer: *const HarfRustGlyphBuffer,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let subsetter = match handle::resolve(subsetter) {
+            Ok(subsetter) => subsetter,
+            Err(status) => return status,
+        };
+        let buffer = match handle::resolve(buffer) {
+            Ok(buffer) => buffer,
+            Err(status) => return status,
+        };
+
+        let subsetter_ref = unsafe { &mut *subsetter };
+        let buffer_ref = unsafe { &*buffer };
+        subsetter_ref.glyphs.extend(buffer_ref.infos_cache.iter().map(|info| info.glyph_id));
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns the number of distinct glyphs added so far (before closure), or -1
+/// if the subsetter is null.
+///
+/// # Safety
+///
+/// `subsetter` must be null or a live handle.
+#[export_name = symbol!("harfrust_subsetter_glyph_count")]
+pub unsafe extern "C" fn harfrust_subsetter_glyph_count(subsetter: *const HarfRustSubsetter) -> i32 {
+    error::guard(|| {
+        let subsetter = match handle::resolve(subsetter) {
+            Ok(subsetter) => subsetter,
+            Err(status) => return status as i32,
+        };
+
+        let subsetter_ref = unsafe { &*subsetter };
+        subsetter_ref.glyphs.len() as i32
+    })
+}
+
+/// Builds the subset of `font` for the accumulated glyph set; see
+/// `harfrust_font_subset` for `mode`, `out_name` and null returns. The
+/// subsetter is not consumed and can keep accumulating.
+///
+/// # Safety
+///
+/// `subsetter` and `font` must be null or live handles, and `out_name` must
+/// be null or have room for `name_capacity` bytes.
+#[export_name = symbol!("harfrust_subsetter_build")]
+pub unsafe extern "C" fn harfrust_subsetter_build(
+    subsetter: *const HarfRustSubsetter,
+    font: *const HarfRustFont,
+    mode: HarfRustSubsetMode,
+    out_name: *mut c_char,
+    name_capacity: u32,
+) -> *mut HarfRustBlob {
+    error::guard(|| {
+        let Ok(subsetter) = handle::resolve(subsetter) else {
+            return std::ptr::null_mut();
+        };
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+
+        let subsetter_ref = unsafe { &*subsetter };
+        let font_wrapper = unsafe { &*font };
+        let seeds: Vec<u32> = subsetter_ref.glyphs.iter().copied().collect();
+        unsafe { build_subset(font_wrapper, &seeds, mode, out_name, name_capacity) }
+    })
+}
+
+/// Frees a subsetter created by `harfrust_subsetter_new`.
+///
+/// # Safety
+///
+/// `subsetter` must be null or a live handle.
+#[export_name = symbol!("harfrust_subsetter_free")]
+pub unsafe extern "C" fn harfrust_subsetter_free(subsetter: *mut HarfRustSubsetter) {
+    error::guard(|| {
+        if subsetter.is_null() {
+            return;
+        }
+        if let Ok(subsetter) = handle::resolve(subsetter) {
+            unsafe { drop(handle::release(subsetter)) };
+        }
+    })
+}
+
+/// Returns a pointer to the bytes of a blob.
+///
+/// # Safety
+///
+/// `blob` must be null or a live handle.
+#[export_name = symbol!("harfrust_blob_get_data")]
+pub unsafe extern "C" fn harfrust_blob_get_data(blob: *const HarfRustBlob) -> *const u8 {
+    error::guard(|| {
+        let Ok(blob) = handle::resolve(blob) else {
+            return std::ptr::null();
+        };
+
+        let blob_ref = unsafe { &*blob };
+        blob_ref.data.as_ptr()
+    })
+}
+
+/// Returns the length of a blob in bytes.
+///
+/// # Safety
+///
+/// `blob` must be null or a live handle.
+#[export_name = symbol!("harfrust_blob_len")]
+pub unsafe extern "C" fn harfrust_blob_len(blob: *const HarfRustBlob) -> i32 {
+    error::guard(|| {
+        let Ok(blob) = handle::resolve(blob) else {
+            return 0;
+        };
+
+        let blob_ref = unsafe { &*blob };
+        blob_ref.data.len() as i32
+    })
+}
+
+/// Frees a blob returned by the library.
+///
+/// # Safety
+///
+/// `blob` must be null or a live handle.
+#[export_name = symbol!("harfrust_blob_free")]
+pub unsafe extern "C" fn harfrust_blob_free(blob: *mut HarfRustBlob) {
+    error::guard(|| {
+        if blob.is_null() {
+            return;
+        }
+        if let Ok(blob) = handle::resolve(blob) {
+            unsafe { drop(handle::release(blob)) };
+        }
+    })
+}
+
+// =============================================================================
+// Instancing functions
+// =============================================================================
+
+/// Builds a static TrueType font from a variable font at the given axis
+/// values (e.g. wght=700), for consumers that don't support variable fonts.
+/// Outlines and advances are evaluated at that location; composite glyphs
+/// are flattened, hinting instructions dropped and variation tables removed.
+/// Axes not listed keep their default; a wght setting also becomes the OS/2
+/// weight class. Static fonts yield an equivalent static copy. Returns null if
+/// the font is null, `variations` is null with a non-zero count, or the font
+/// has no `glyf` outlines (CFF2 is not supported).
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `variations` must be null or
+/// point to `num_variations` variations.
+#[export_name = symbol!("harfrust_font_instantiate")]
+pub unsafe extern "C" fn harfrust_font_instantiate(
+    font: *const HarfRustFont,
+    variations: *const HarfRustVariation,
+    num_variations: u32,
+) -> *mut HarfRustBlob {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        if variations.is_null() && num_variations > 0 {
+            return error::fail_null(HarfRustStatus::NullArgument, "variations is null");
+        }
+
+        let font_wrapper = unsafe { &*font };
+        let variations: &[HarfRustVariation] = if num_variations == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(variations, num_variations as usize) }
+        };
+        let settings = variations.iter().map(|v| (Tag::new(&v.tag.to_be_bytes()), v.value));
+        let location = font_wrapper.font_ref.axes().location(settings);
+        let weight = variations
+            .iter()
+            .rev()
+            .find(|v| v.tag == u32::from_be_bytes(*b"wght"))
+            .map(|v| v.value);
+
+        match instance::instantiate(&font_wrapper.font_ref, LocationRef::from(&location), weight) {
+            Some(data) => handle::into_handle(Box::new(HarfRustBlob { tag: TypeTag::new(), data })),
+            None => error::fail_null(HarfRustStatus::InvalidFont, "font cannot be instantiated"),
+        }
+    })
+}
+
+// =============================================================================
+// Color glyph functions
+// =============================================================================
+
+/// Renders a color glyph (COLR/CPAL or embedded color bitmap) at the given
+/// pixels-per-em into a premultiplied RGBA bitmap.
+/// `palette_index` selects the CPAL palette; out-of-range values use palette 0.
+/// Returns null, recording `InvalidArgument`, if the glyph has no color
+/// representation, or `Unsupported` if its only one is an SVG document, which
+/// is not rendered.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle.
+#[export_name = symbol!("harfrust_font_render_color_glyph")]
+pub unsafe extern "C" fn harfrust_font_render_color_glyph(
+    font: *const HarfRustFont,
+    glyph_id: u32,
+    ppem: f32,
+    palette_index: u32,
+) -> *mut HarfRustColorBitmap {
+    unsafe { harfrust_font_render_color_glyph_with_overrides(font, glyph_id, ppem, palette_index, std::ptr::null(), 0) }
+}
+
+/// Renders a color glyph as `harfrust_font_render_color_glyph` does, with
+/// `overrides_len` entries of the palette replaced by `overrides` (which may
+/// be null if the length is 0). An override with index 0xFFFF sets the text
+/// foreground color that COLR glyphs may paint with; overrides of entries the
+/// palette lacks are ignored. Embedded color bitmaps are unaffected.
+///
+/// # Safety
+///
+/// `font` must be null or a live handle, and `overrides` must be null or
+/// point to `overrides_len` entries.
+#[export_name = symbol!("harfrust_font_render_color_glyph_with_overrides")]
+pub unsafe extern "C" fn harfrust_font_render_color_glyph_with_overrides(
+    font: *const HarfRustFont,
+    glyph_id: u32,
+    ppem: f32,
+    palette_index: u32,
+    overrides: *const HarfRustColorOverride,
+    overrides_len: u32,
+) -> *mut HarfRustColorBitmap {
+    error::guard(|| {
+        let Ok(font) = handle::resolve(font) else {
+            return std::ptr::null_mut();
+        };
+        if overrides.is_null() && overrides_len > 0 {
+            return error::fail_null(HarfRustStatus::NullArgument, "overrides is null");
+        }
+
+        let overrides: Vec<(u16, tiny_skia::Color)> = if overrides_len == 0 {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(overrides, overrides_len as usize) }
+                .iter()
+                .map(|o| (o.index, tiny_skia::Color::from_rgba8(o.red, o.green, o.blue, o.alpha)))
+                .collect()
+        };
+
+        let font_wrapper = unsafe { &*font };
+        let palette_index = u16::try_from(palette_index).unwrap_or(0);
+        let glyph_id = GlyphId::new(glyph_id);
+        match color::render_color_glyph(&font_wrapper.font_ref, glyph_id, ppem, palette_index, &overrides) {
+            Some(bitmap) => handle::into_handle(Box::new(HarfRustColorBitmap { tag: TypeTag::new(), inner: bitmap })),
+            None if !(ppem > 0.0 && ppem.is_finite()) => {
+                error::fail_null(HarfRustStatus::OutOfRange, format!("invalid ppem {ppem}"))
+            }
+            None if color::has_svg_glyph(&font_wrapper.font_ref, glyph_id) => error::fail_null(
+                HarfRustStatus::Unsupported,
+                format!("glyph {} is an SVG glyph, which cannot be rendered", glyph_id.to_u32()),
+            ),
+            None => error::fail_null(
+                HarfRustStatus::InvalidArgument,
+                format!("glyph {} has no color representation", glyph_id.to_u32()),
+            ),
+        }
+    })
+}
+
+/// Writes the dimensions and placement of a color bitmap to `out`.
+///
+/// # Safety
+///
+/// `bitmap` must be null or a live handle, and `out` must be null or point to
+/// a writable `HarfRustBitmapInfo`.
+#[export_name = symbol!("harfrust_color_bitmap_get_info")]
+pub unsafe extern "C" fn harfrust_color_bitmap_get_info(
+    bitmap: *const HarfRustColorBitmap,
+    out: *mut HarfRustBitmapInfo,
+) -> HarfRustStatus {
+    error::guard(|| {
+        let bitmap = match handle::resolve(bitmap) {
+            Ok(bitmap) => bitmap,
+            Err(status) => return status,
+        };
+        if out.is_null() {
+            return error::fail(HarfRustStatus::NullArgument, "out is null");
+        }
+
+        let bitmap_ref = unsafe { &(*bitmap).inner };
+        unsafe {
+            *out = HarfRustBitmapInfo {
+                width: bitmap_ref.width,
+                height: bitmap_ref.height,
+                left: bitmap_ref.left,
+                top: bitmap_ref.top,
+                stride: bitmap_ref.width * 4,
+            };
+        }
+
+        HarfRustStatus::Ok
+    })
+}
+
+/// Returns a pointer to the premultiplied RGBA pixel data of a color bitmap.
+///
+/// # Safety
+///
+/// `bitmap` must be null or a live handle.
+#[export_name = symbol!("harfrust_color_bitmap_get_pixels")]
+pub unsafe extern "C" fn harfrust_color_bitmap_get_pixels(bitmap: *const HarfRustColorBitmap) -> *const u8 {
+    error::guard(|| {
+        let Ok(bitmap) = handle::resolve(bitmap) else {
+            return std::ptr::null();
+        };
+
+        let bitmap_ref = unsafe { &*bitmap };
+        bitmap_ref.inner.pixels.as_ptr()
+    })
+}
+
+/// Frees a bitmap previously created by `harfrust_font_render_color_glyph`.
+///
+/// # Safety
+///
+/// `bitmap` must be null or a live handle.
+#[export_name = symbol!("harfrust_color_bitmap_free")]
+pub unsafe extern "C" fn harfrust_color_bitmap_free(bitmap: *mut HarfRustColorBitmap) {
+    error::guard(|| {
+        if bitmap.is_null() {
+            return;
+        }
+        if let Ok(bitmap) = handle::resolve(bitmap) {
+            unsafe { drop(handle::release(bitmap)) };
+        }
+    })
+}
+
+// =============================================================================
+// WASM Memory Allocation (required for Wasmtime host calls)
+// =============================================================================
+
+/// Allocates memory in the WASM linear memory.
+/// Used by the host to allocate space for passing data to WASM.
+#[export_name = symbol!("harfrust_alloc")]
+pub extern "C" fn harfrust_alloc(size: i32) -> i32 {
+    error::guard(|| {
+        if size <= 0 {
+            return 0;
+        }
+    
+        let layout = match std::alloc::Layout::from_size_align(size as usize, 8) {
+            Ok(l) => l,
+            Err(_) => return 0,
+        };
+    
+        // SAFETY: We're allocating with a valid layout and returning the pointer as i32
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        if ptr.is_null() {
+            0
+        } else {
+            ptr as i32
+        }
+    })
+}
+
+/// Frees memory allocated by harfrust_alloc.
+///
+/// # Safety
+///
+/// `ptr` must be 0 or an address returned by `harfrust_alloc` for the same
+/// `size` that has not been freed.
+#[export_name = symbol!("harfrust_dealloc")]
+pub unsafe extern "C" fn harfrust_dealloc(ptr: i32, size: i32) {
+    error::guard(|| {
+        if ptr == 0 || size <= 0 {
+            return;
+        }
+    
+        let layout = match std::alloc::Layout::from_size_align(size as usize, 8) {
+            Ok(l) => l,
+            Err(_) => return,
+        };
+    
+        // SAFETY: Layout matches the one used in harfrust_alloc
+        unsafe { std::alloc::dealloc(ptr as *mut u8, layout) };
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    /// Held by tests that change library-wide settings, which would otherwise
+    /// race with each other.
+    static GLOBAL_SETTINGS: Mutex<()> = Mutex::new(());
+
+    /// The object behind a live handle. Handles are addresses only without
+    /// the `handle-table` feature, so tests never dereference them directly.
+    unsafe fn object<'a, T: handle::Handle>(handle: *const T) -> &'a mut T {
+        unsafe { &mut *handle::resolve(handle).expect("handle is live") }
+    }
+
+    /// `harfrust_run_key` of a run of UTF-16 text with guessed properties.
+    unsafe fn run_key(font: *const HarfRustFont, text: &[u16]) -> HarfRustCacheKey {
+        let run = HarfRustRunDescriptor {
+            text: text.as_ptr(),
+            len: text.len() as i32,
+            direction: HarfRustDirection::Invalid,
+            script: 0,
+            language: std::ptr::null(),
+            features: std::ptr::null(),
+            num_features: 0,
+            normalize_spaces: 0,
+        };
+        let mut key = HarfRustCacheKey::default();
+        assert_eq!(unsafe { harfrust_run_key(font, &run, std::ptr::null(), 0, &mut key) }, HarfRustStatus::Ok);
+        key
+    }
+
+    /// Message of the last failure on this thread.
+    fn last_error_message() -> String {
+        unsafe {
+            let message = harfrust_last_error_message();
+            assert!(!message.is_null());
+            let text = CStr::from_ptr(message).to_str().unwrap().to_string();
+            harfrust_string_free(message);
+            text
+        }
+    }
+
+    /// Builds a COLRv0 font from the test font: the glyph for 'A' is painted
+    /// with palette entry 0, which is red in palette 0 and blue in palette 1,
+    /// under the glyph for '.' in the foreground color.
+    fn test_color_font_data() -> (Vec<u8>, u32) {
+        use write_fonts::tables::{colr, cpal};
+        use write_fonts::types::GlyphId16;
+
+        let base = test_font_data();
+        let font_ref = harfrust::FontRef::new(&base).unwrap();
+        let glyph = font_ref.charmap().map('A').unwrap().to_u32();
+        let glyph16 = GlyphId16::new(glyph as u16);
+        let period = GlyphId16::new(font_ref.charmap().map('.').unwrap().to_u32() as u16);
+
+        let colr = colr::Colr::new(
+            1,
+            Some(vec![colr::BaseGlyph::new(glyph16, 0, 2)]),
+            Some(vec![colr::Layer::new(glyph16, 0), colr::Layer::new(period, 0xFFFF)]),
+            2,
+        );
+        let cpal = cpal::Cpal::new(
+            1,
+            2,
+            2,
+            Some(vec![
+                cpal::ColorRecord::new(0, 0, 255, 255),
+                cpal::ColorRecord::new(255, 0, 0, 255),
+            ]),
+            vec![0, 1],
+        );
+
+        let data = write_fonts::FontBuilder::new()
+            .add_table(&colr)
+            .unwrap()
+            .add_table(&cpal)
+            .unwrap()
+            .copy_missing_tables(font_ref)
+            .build();
+        (data, glyph)
+    }
+
+    /// Test font whose GSUB covers only part of the text: `smcp` maps 'a' to
+    /// 'Q' and `sups` maps '1' to U+00B9, leaving 'b' and '2' uncovered.
+    fn test_partial_gsub_font_data() -> Vec<u8> {
+        use write_fonts::tables::gsub::{Gsub, SingleSubst, SubstitutionLookup, SubstitutionLookupList};
+        use write_fonts::tables::layout::{
+            Feature, FeatureList, FeatureRecord, LangSys, Lookup, LookupFlag, Script, ScriptList, ScriptRecord,
+        };
+        use write_fonts::types::GlyphId16;
+
+        let base = test_font_data();
+        let font_ref = harfrust::FontRef::new(&base).unwrap();
+        let glyph = |ch| GlyphId16::new(font_ref.charmap().map(ch).unwrap().to_u32() as u16);
+        let single = |from: char, to: char| {
+            let subst = SingleSubst::format_2(vec![glyph(from)].into(), vec![glyph(to)]);
+            SubstitutionLookup::Single(Lookup::new(LookupFlag::empty(), vec![subst]))
+        };
+
+        let lang_sys = || Some(LangSys::new(vec![0, 1]));
+        let gsub = Gsub::new(
+            ScriptList::new(vec![
+                ScriptRecord::new(Tag::new(b"DFLT"), Script::new(lang_sys(), vec![])),
+                ScriptRecord::new(Tag::new(b"latn"), Script::new(lang_sys(), vec![])),
+            ]),
+            FeatureList::new(vec![
+                FeatureRecord::new(Tag::new(b"smcp"), Feature::new(None, vec![0])),
+                FeatureRecord::new(Tag::new(b"sups"), Feature::new(None, vec![1])),
+            ]),
+            SubstitutionLookupList::new(vec![single('a', 'Q'), single('1', '\u{b9}')]),
+        );
+
+        write_fonts::FontBuilder::new()
+            .add_table(&gsub)
+            .unwrap()
+            .copy_missing_tables(font_ref)
+            .build()
+    }
+
+    /// Builds a COLRv1 font from the test font whose glyph for 'A' is `paint`,
+    /// clipped to a 1000 unit square around the origin. Palette entry 0 is
+    /// red and entry 1 blue.
+    fn test_colr_v1_font_data(paint: write_fonts::tables::colr::Paint) -> (Vec<u8>, u32) {
+        use write_fonts::tables::{colr, cpal};
+        use write_fonts::types::{FWord, GlyphId16};
+
+        let base = test_font_data();
+        let font_ref = harfrust::FontRef::new(&base).unwrap();
+        let glyph = font_ref.charmap().map('A').unwrap().to_u32();
+        let glyph16 = GlyphId16::new(glyph as u16);
+
+        let clip_box = colr::ClipBoxFormat1::new(FWord::new(-500), FWord::new(-500), FWord::new(500), FWord::new(500));
+        let colr = colr::Colr {
+            base_glyph_list: colr::BaseGlyphList::new(1, vec![colr::BaseGlyphPaint::new(glyph16, paint)]).into(),
+            clip_list: colr::ClipList::new(1, 1, vec![colr::Clip::new(glyph16, glyph16, clip_box.into())]).into(),
+            ..colr::Colr::new(0, None, None, 0)
+        };
+        let cpal = cpal::Cpal::new(
+            2,
+            1,
+            2,
+            Some(vec![
+                cpal::ColorRecord::new(0, 0, 255, 255),
+                cpal::ColorRecord::new(255, 0, 0, 255),
+            ]),
+            vec![0],
+        );
+
+        let data = write_fonts::FontBuilder::new()
+            .add_table(&colr)
+            .unwrap()
+            .add_table(&cpal)
+            .unwrap()
+            .copy_missing_tables(font_ref)
+            .build();
+        (data, glyph)
+    }
+
+    /// A red to blue color line with the given extend mode.
+    fn test_color_line(extend: write_fonts::tables::colr::Extend) -> write_fonts::tables::colr::ColorLine {
+        use write_fonts::tables::colr::{ColorLine, ColorStop};
+        use write_fonts::types::F2Dot14;
+
+        let stop = |offset, palette_index| ColorStop::new(F2Dot14::from_f32(offset), palette_index, F2Dot14::from_f32(1.0));
+        ColorLine::new(extend, 2, vec![stop(0.0, 0), stop(1.0, 1)])
+    }
+
+    /// Renders `glyph` at a tenth of the test font's em (100 pixels for the
+    /// clip box of `test_colr_v1_font_data`) and returns its RGBA pixels.
+    unsafe fn render_pixels(font: *const HarfRustFont, glyph: u32) -> (HarfRustBitmapInfo, Vec<u8>) {
+        unsafe {
+            let ppem = harfrust_font_units_per_em(font) as f32 / 10.0;
+            let bitmap = harfrust_font_render_color_glyph(font, glyph, ppem, 0);
+            assert!(!bitmap.is_null());
+            let mut info = HarfRustBitmapInfo::default();
+            assert_eq!(harfrust_color_bitmap_get_info(bitmap, &mut info), HarfRustStatus::Ok);
+            let len = (info.stride * info.height) as usize;
+            let pixels = std::slice::from_raw_parts(harfrust_color_bitmap_get_pixels(bitmap), len).to_vec();
+            harfrust_color_bitmap_free(bitmap);
+            (info, pixels)
+        }
+    }
+
+    /// Two-face TrueType collection whose faces both point at the test font's
+    /// tables.
+    fn test_collection_data() -> Vec<u8> {
+        let font = test_font_data();
+        let num_tables = u16::from_be_bytes([font[4], font[5]]) as usize;
+        let directory_len = 12 + 16 * num_tables;
+        let base = (12 + 4 * 2 + directory_len * 2) as u32;
+
+        let mut data = b"ttcf".to_vec();
+        data.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        for face in 0..2 {
+            let offset = 20 + face * directory_len as u32;
+            data.extend_from_slice(&offset.to_be_bytes());
+        }
+        for _ in 0..2 {
+            let mut directory = font[..directory_len].to_vec();
+            for record in directory[12..].chunks_mut(16) {
+                let offset = u32::from_be_bytes(record[8..12].try_into().unwrap()) + base;
+                record[8..12].copy_from_slice(&offset.to_be_bytes());
+            }
+            data.extend_from_slice(&directory);
+        }
+        data.extend_from_slice(&font);
+        data
+    }
+
+    /// Test font with a wght axis (400-700) under which 'l' moves right by 50
+    /// units and widens its advance by 100 at 700. Returns the data and the
+    /// glyph ID of 'l'.
+    fn test_variable_font_data() -> (Vec<u8>, u32) {
+        use skrifa::raw::tables::glyf::Glyph;
+        use write_fonts::tables::fvar::{AxisInstanceArrays, Fvar, VariationAxisRecord};
+        use write_fonts::tables::gvar::{GlyphDelta, GlyphDeltas, GlyphVariations, Gvar, Tent};
+        use write_fonts::types::{F2Dot14, Fixed, NameId};
+
+        let base = test_font_data();
+        let font_ref = harfrust::FontRef::new(&base).unwrap();
+        let glyph = font_ref.charmap().map('l').unwrap();
+        let glyf = font_ref.glyf().unwrap();
+        let loca = font_ref.loca(None).unwrap();
+        let Ok(Some(Glyph::Simple(simple))) = loca.get_glyf(glyph, &glyf) else {
+            panic!("'l' is not a simple glyph");
+        };
+
+        // Outline points, then phantom points (origin, advance, top, bottom)
+        let mut deltas = vec![GlyphDelta::required(50, 0); simple.num_points()];
+        deltas.extend([(0, 0), (100, 0), (0, 0), (0, 0)].map(|(x, y)| GlyphDelta::required(x, y)));
+        let num_glyphs = font_ref.maxp().unwrap().num_glyphs() as u32;
+        let variations = (0..num_glyphs)
+            .map(|gid| {
+                let gid = GlyphId::new(gid);
+                let tents = vec![Tent::new(F2Dot14::ONE, None)];
+                match gid == glyph {
+                    true => GlyphVariations::new(gid, vec![GlyphDeltas::new(tents, deltas.clone())]),
+                    false => GlyphVariations::new(gid, vec![]),
+                }
+            })
+            .collect();
+        let gvar = Gvar::new(variations, 1).unwrap();
+
+        let (default, max) = (Fixed::from_f64(400.0), Fixed::from_f64(700.0));
+        let axis = VariationAxisRecord::new(Tag::new(b"wght"), default, default, max, 0, NameId::new(256));
+        let fvar = Fvar::new(AxisInstanceArrays::new(vec![axis], vec![]));
+
+        let data = write_fonts::FontBuilder::new()
+            .add_table(&fvar)
+            .unwrap()
+            .add_table(&gvar)
+            .unwrap()
+            .copy_missing_tables(font_ref)
+            .build();
+        (data, glyph.to_u32())
+    }
+
+    /// Minimal bare CFF font: .notdef (width 0 from nominal 500), 'A' (a
+    /// 500x700 box, width 600) and 'space' (default width 250).
+    fn test_cff_data() -> Vec<u8> {
+        let num = |v: i32| {
+            let mut bytes = vec![28];
+            bytes.extend_from_slice(&(v as i16).to_be_bytes());
+            bytes
+        };
+        let dict_int = |v: usize| {
+            let mut bytes = vec![29];
+            bytes.extend_from_slice(&(v as i32).to_be_bytes());
+            bytes
+        };
+        let index = |items: &[Vec<u8>]| {
+            let mut bytes = (items.len() as u16).to_be_bytes().to_vec();
+            bytes.push(1);
+            let mut offset = 1;
+            bytes.push(offset);
+            for item in items {
+                offset += item.len() as u8;
+                bytes.push(offset);
+            }
+            bytes.extend(items.concat());
+            bytes
+        };
+
+        let (rmoveto, rlineto, endchar) = (21, 5, 14);
+        let charstrings = index(&[
+            [num(0), vec![endchar]].concat(),
+            [
+                num(100), num(50), num(0), vec![rmoveto],
+                num(500), num(0), vec![rlineto], num(0), num(700), vec![rlineto],
+                num(-500), num(0), vec![rlineto], vec![endchar],
+            ]
+            .concat(),
+            vec![endchar],
+        ]);
+        // defaultWidthX 250, nominalWidthX 500
+        let private = [num(250), vec![20], num(500), vec![21]].concat();
+        // Format 0: SIDs of "A" and "space"
+        let charset = [vec![0], 34u16.to_be_bytes().to_vec(), 1u16.to_be_bytes().to_vec()].concat();
+
+        let header = [1, 0, 4, 1];
+        let names = index(&[b"Test".to_vec()]);
+        let top_dict_len = 5 + 1 + 5 + 1 + 5 + 5 + 1;
+        let charset_offset = header.len() + names.len() + 2 + 1 + 2 + top_dict_len + 2 + 2;
+        let charstrings_offset = charset_offset + charset.len();
+        let private_offset = charstrings_offset + charstrings.len();
+        let top_dict = [
+            dict_int(charset_offset), vec![15],
+            dict_int(charstrings_offset), vec![17],
+            dict_int(private.len()), dict_int(private_offset), vec![18],
+        ]
+        .concat();
+        assert_eq!(top_dict.len(), top_dict_len);
+
+        let empty_index = vec![0, 0];
+        // String and Global Subr INDEXes are empty
+        let strings_and_subrs = [empty_index.clone(), empty_index].concat();
+        [header.to_vec(), names, index(&[top_dict]), strings_and_subrs, charset, charstrings, private].concat()
+    }
+
+    /// Loads a system font for tests that need real font data.
+    fn test_font_data() -> Vec<u8> {
+        let font_paths = [
+            r"C:\Windows\Fonts\arial.ttf",
+            r"C:\Windows\Fonts\segoeui.ttf",
+            "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+            "/Library/Fonts/Arial.ttf",
+        ];
+
+        font_paths.iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .expect("No system font found for testing")
+    }
+
+    #[test]
+    fn test_buffer_lifecycle() {
+        unsafe {
+            let buffer = harfrust_buffer_new();
+            assert!(!buffer.is_null());
+
+            let text = CString::new("Hello, world!").unwrap();
+            let result = harfrust_buffer_add_str(buffer, text.as_ptr());
+            assert_eq!(result, HarfRustStatus::Ok);
+
+            let len = harfrust_buffer_len(buffer);
+            assert_eq!(len, 13);
+
+            harfrust_buffer_clear(buffer);
+            let len = harfrust_buffer_len(buffer);
+            assert_eq!(len, 0);
+
+            harfrust_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn test_null_safety() {
+        unsafe {
+            assert_eq!(
+                harfrust_buffer_add_str(std::ptr::null_mut(), std::ptr::null()),
+                HarfRustStatus::NullArgument
+            );
+            assert_eq!(harfrust_buffer_len(std::ptr::null()), -1);
+            harfrust_buffer_clear(std::ptr::null_mut());
+            harfrust_buffer_free(std::ptr::null_mut());
+
+            assert!(harfrust_font_from_data(std::ptr::null(), 0).is_null());
+            assert_eq!(harfrust_font_units_per_em(std::ptr::null()), -1);
+            harfrust_font_free(std::ptr::null_mut());
+
+            assert!(harfrust_shape(std::ptr::null(), std::ptr::null_mut()).is_null());
+            assert_eq!(harfrust_glyph_buffer_len(std::ptr::null()), -1);
+            assert!(harfrust_glyph_buffer_get_infos(std::ptr::null()).is_null());
+            assert!(harfrust_glyph_buffer_get_positions(std::ptr::null()).is_null());
+            assert!(harfrust_glyph_buffer_into_buffer(std::ptr::null_mut()).is_null());
+            harfrust_glyph_buffer_free(std::ptr::null_mut());
+        }
+    }
+    
+    #[test]
+    fn test_shape_with_font() {
+        // Try to load a system font for testing
+        let font_paths = [
+            r"C:\Windows\Fonts\arial.ttf",
+            r"C:\Windows\Fonts\segoeui.ttf",
+            r"C:\Windows\Fonts\tahoma.ttf",
+        ];
+        
+        let font_data = font_paths.iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .expect("No system font found for testing");
+        
+        // Test harfrust directly first
+        let font_ref = harfrust::FontRef::new(&font_data).expect("Font parse failed");
+        let shaper_data = harfrust::ShaperData::new(&font_ref);
+        let shaper = shaper_data.shaper(&font_ref).build();
+        
+        let mut buffer = harfrust::UnicodeBuffer::new();
+        buffer.push_str("Hello");
+        buffer.guess_segment_properties();  // Required to set direction!
+        
+        let result = shaper.shape(buffer, &[]);
+        assert_eq!(result.len(), 5);
+    }
+    
+    #[test]
     fn test_buffer_direction() {
         unsafe {
-            let buffer = harfrust_buffer_new();
-            
-            // Default should be Invalid
-            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::Invalid);
-            
-            // Set and get direction
-            harfrust_buffer_set_direction(buffer, HarfRustDirection::RightToLeft);
-            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::RightToLeft);
-            
-            harfrust_buffer_set_direction(buffer, HarfRustDirection::LeftToRight);
-            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::LeftToRight);
-            
-            harfrust_buffer_free(buffer);
+            let buffer = harfrust_buffer_new();
+            
+            // Default should be Invalid
+            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::Invalid);
+            
+            // Set and get direction
+            harfrust_buffer_set_direction(buffer, HarfRustDirection::RightToLeft);
+            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::RightToLeft);
+            
+            harfrust_buffer_set_direction(buffer, HarfRustDirection::LeftToRight);
+            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::LeftToRight);
+            
+            harfrust_buffer_free(buffer);
+        }
+    }
+    
+    #[test]
+    fn test_buffer_script() {
+        unsafe {
+            let buffer = harfrust_buffer_new();
+            
+            // Set Latin script (0x4C61746E = "Latn")
+            let latn_tag = u32::from_be_bytes(*b"Latn");
+            harfrust_buffer_set_script(buffer, latn_tag);
+            assert_eq!(harfrust_buffer_get_script(buffer), latn_tag);
+            
+            // Set Arabic script (0x41726162 = "Arab")
+            let arab_tag = u32::from_be_bytes(*b"Arab");
+            harfrust_buffer_set_script(buffer, arab_tag);
+            assert_eq!(harfrust_buffer_get_script(buffer), arab_tag);
+            
+            harfrust_buffer_free(buffer);
+        }
+    }
+    
+    #[test]
+    fn test_buffer_language() {
+        unsafe {
+            let buffer = harfrust_buffer_new();
+            
+            // Set English language
+            let lang = CString::new("en").unwrap();
+            let result = harfrust_buffer_set_language(buffer, lang.as_ptr());
+            assert_eq!(result, HarfRustStatus::Ok);
+            
+            // Set more specific language tag
+            let lang = CString::new("en-US").unwrap();
+            let result = harfrust_buffer_set_language(buffer, lang.as_ptr());
+            assert_eq!(result, HarfRustStatus::Ok);
+            
+            harfrust_buffer_free(buffer);
+        }
+    }
+    
+    #[test]
+    fn test_explicit_direction_not_overridden() {
+        // Try to load a system font for testing
+        let font_paths = [
+            r"C:\Windows\Fonts\arial.ttf",
+            r"C:\Windows\Fonts\segoeui.ttf",
+            r"C:\Windows\Fonts\tahoma.ttf",
+        ];
+        
+        let font_data = font_paths.iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .expect("No system font found for testing");
+        
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            
+            // Set RTL direction explicitly
+            harfrust_buffer_set_direction(buffer, HarfRustDirection::RightToLeft);
+            
+            let text = CString::new("Hello").unwrap();
+            harfrust_buffer_add_str(buffer, text.as_ptr());
+            
+            // Shape should work (direction is set, won't call guess)
+            let glyph_buffer = harfrust_shape(font, buffer);
+            assert!(!glyph_buffer.is_null());
+            assert_eq!(harfrust_glyph_buffer_len(glyph_buffer), 5);
+            
+            harfrust_glyph_buffer_free(glyph_buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_with_features() {
+        // Try to load a system font for testing
+        let font_paths = [
+            r"C:\Windows\Fonts\calibri.ttf",
+            r"C:\Windows\Fonts\arial.ttf",
+        ];
+        
+        let font_data = font_paths.iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .expect("No system font found for testing");
+            
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            
+            let text = CString::new("fi").unwrap();
+            harfrust_buffer_add_str(buffer, text.as_ptr());
+            
+            // Disable ligatures: liga = 0
+            let features = [
+                HarfRustFeature {
+                    tag: u32::from_be_bytes(*b"liga"),
+                    value: 0,
+                    start: 0,
+                    end: u32::MAX,
+                }
+            ];
+            
+            let glyph_buffer = harfrust_shape_with_features(
+                font,
+                buffer,
+                features.as_ptr(),
+                features.len() as u32
+            );
+            
+            assert!(!glyph_buffer.is_null());
+            
+            // Just verify it produced something
+            let len = harfrust_glyph_buffer_len(glyph_buffer);
+            assert!(len > 0);
+            
+            harfrust_glyph_buffer_free(glyph_buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_with_variations() {
+        // Try to load a system font for testing
+        let font_paths = [
+            r"C:\Windows\Fonts\calibri.ttf",
+            r"C:\Windows\Fonts\arial.ttf",
+        ];
+        
+        let font_data = font_paths.iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .expect("No system font found for testing");
+            
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            
+            let text = CString::new("Hello").unwrap();
+            harfrust_buffer_add_str(buffer, text.as_ptr());
+            
+            // Set variation: wght = 700.0 (Bold)
+            let variations = [
+                HarfRustVariation {
+                    tag: u32::from_be_bytes(*b"wght"),
+                    value: 700.0,
+                }
+            ];
+            
+            let glyph_buffer = harfrust_shape_full(
+                font,
+                buffer,
+                std::ptr::null(), // No features
+                0,
+                variations.as_ptr(),
+                variations.len() as u32
+            );
+            
+            assert!(!glyph_buffer.is_null());
+            
+            // Just verify it produced something
+            let len = harfrust_glyph_buffer_len(glyph_buffer);
+            assert!(len > 0);
+            
+            harfrust_glyph_buffer_free(glyph_buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_advance_cache() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let font_ref = &object(font).font_ref;
+            let expected = font_ref
+                .glyph_metrics(Size::unscaled(), LocationRef::default())
+                .advance_width(GlyphId::new(1))
+                .unwrap()
+                .round() as i32;
+
+            // First call fills the cache, second call is served from it
+            assert_eq!(harfrust_font_glyph_advance(font, 1), expected);
+            assert_eq!(harfrust_font_glyph_advance(font, 1), expected);
+
+            assert_eq!(harfrust_font_glyph_advance(font, u32::MAX), HarfRustStatus::OutOfRange as i32);
+            assert_eq!(harfrust_font_glyph_advance(std::ptr::null(), 1), -1);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_advance_instances() {
+        let (font_data, glyph) = test_variable_font_data();
+        let variation = |value| [HarfRustVariation { tag: u32::from_be_bytes(*b"wght"), value }];
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let default = harfrust_font_glyph_advance(font, glyph);
+            let regular = harfrust_instance_new(font, variation(400.0).as_ptr(), 1);
+            let bold = harfrust_instance_new(font, variation(700.0).as_ptr(), 1);
+
+            // Each instance gets its own table; the regular instance shares
+            // the default one
+            assert_eq!(harfrust_font_glyph_advance_with_instance(font, std::ptr::null(), glyph), default);
+            assert_eq!(harfrust_font_glyph_advance_with_instance(font, regular, glyph), default);
+            assert_eq!(harfrust_font_glyph_advance_with_instance(font, bold, glyph), default + 100);
+            assert_eq!(harfrust_font_glyph_advance_with_instance(font, bold, glyph), default + 100);
+            assert_eq!(harfrust_font_glyph_advance(font, glyph), default);
+            assert_eq!(object(font).advance_cache.instances.lock().unwrap().len(), 1);
+
+            // Least recently used instances are dropped beyond the limit
+            for i in 0..=ADVANCE_CACHE_INSTANCES {
+                let instance = harfrust_instance_new(font, variation(450.0 + i as f32).as_ptr(), 1);
+                assert!(harfrust_font_glyph_advance_with_instance(font, instance, glyph) > default);
+                harfrust_instance_free(instance);
+            }
+            assert_eq!(object(font).advance_cache.instances.lock().unwrap().len(), ADVANCE_CACHE_INSTANCES);
+            assert_eq!(harfrust_font_glyph_advance_with_instance(font, bold, glyph), default + 100);
+
+            let status = harfrust_font_glyph_advance_with_instance(font, bold, u32::MAX);
+            assert_eq!(status, HarfRustStatus::OutOfRange as i32);
+
+            harfrust_instance_free(regular);
+            harfrust_instance_free(bold);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_all_advances() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let count = harfrust_font_all_advances(font, std::ptr::null_mut(), 0);
+            assert!(count > 0);
+
+            let mut advances = vec![0u16; count as usize];
+            assert_eq!(harfrust_font_all_advances(font, advances.as_mut_ptr(), count as u32), count);
+            for glyph_id in [1u32, 2, (count - 1) as u32] {
+                assert_eq!(advances[glyph_id as usize] as i32, harfrust_font_glyph_advance(font, glyph_id));
+            }
+
+            // A short output array is filled up to its capacity
+            let mut short = [0u16; 2];
+            assert_eq!(harfrust_font_all_advances(font, short.as_mut_ptr(), 2), count);
+            assert_eq!(short, [advances[0], advances[1]]);
+
+            assert_eq!(harfrust_font_all_advances(std::ptr::null(), short.as_mut_ptr(), 2), -1);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_cmap_mappings() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let count = harfrust_font_cmap_mappings(font, std::ptr::null_mut(), 0);
+            assert!(count > 0);
+
+            let mut entries = vec![HarfRustCmapEntry::default(); count as usize];
+            assert_eq!(harfrust_font_cmap_mappings(font, entries.as_mut_ptr(), count as u32), count);
+
+            let expected = object(font).font_ref.charmap().map('A').unwrap().to_u32();
+            let entry = entries.iter().find(|e| e.codepoint == 'A' as u32).unwrap();
+            assert_eq!(entry.glyph_id, expected);
+            assert!(entries.windows(2).all(|w| w[0].codepoint < w[1].codepoint));
+
+            assert_eq!(harfrust_font_cmap_mappings(std::ptr::null(), entries.as_mut_ptr(), 1), -1);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_render_color_glyph() {
+        let (font_data, glyph) = test_color_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            for (palette, expected) in [(0u32, [255u8, 0, 0, 255]), (1, [0, 0, 255, 255])] {
+                let bitmap = harfrust_font_render_color_glyph(font, glyph, 64.0, palette);
+                assert!(!bitmap.is_null());
+
+                let mut info = HarfRustBitmapInfo::default();
+                assert_eq!(harfrust_color_bitmap_get_info(bitmap, &mut info), HarfRustStatus::Ok);
+                assert!(info.width > 0 && info.height > 0);
+                assert!(info.top > 0);
+                assert_eq!(info.stride, info.width * 4);
+
+                let pixels = std::slice::from_raw_parts(
+                    harfrust_color_bitmap_get_pixels(bitmap),
+                    (info.stride * info.height) as usize,
+                );
+                assert!(pixels.chunks_exact(4).any(|p| p == expected));
+
+                harfrust_color_bitmap_free(bitmap);
+            }
+
+            // Glyphs without color data are not rendered
+            assert!(harfrust_font_render_color_glyph(font, glyph + 1, 64.0, 0).is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::InvalidArgument);
+            assert!(harfrust_font_render_color_glyph(font, glyph, 0.0, 0).is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::OutOfRange);
+            assert!(harfrust_font_render_color_glyph(std::ptr::null(), glyph, 64.0, 0).is_null());
+            assert_eq!(harfrust_color_bitmap_get_info(std::ptr::null(), std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert!(harfrust_color_bitmap_get_pixels(std::ptr::null()).is_null());
+            harfrust_color_bitmap_free(std::ptr::null_mut());
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_render_gradients() {
+        use write_fonts::tables::colr::{Extend, Paint};
+        use write_fonts::types::{F2Dot14, FWord, UfWord};
+
+        let pixel = |info: &HarfRustBitmapInfo, pixels: &[u8], x: u32, y: u32| {
+            let at = (y * info.stride + x * 4) as usize;
+            [pixels[at], pixels[at + 1], pixels[at + 2], pixels[at + 3]]
+        };
+
+        unsafe {
+            // A full counterclockwise sweep from red to blue, starting at the
+            // positive x axis: 90 degrees is mostly red, 270 mostly blue
+            let sweep = Paint::sweep_gradient(
+                test_color_line(Extend::Pad),
+                FWord::new(0),
+                FWord::new(0),
+                F2Dot14::from_f32(-1.0),
+                F2Dot14::from_f32(1.0),
+            );
+            let (font_data, glyph) = test_colr_v1_font_data(sweep);
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let (info, pixels) = render_pixels(font, glyph);
+            assert_eq!((info.width, info.height), (100, 100));
+            let [red, _, blue, alpha] = pixel(&info, &pixels, 50, 20);
+            assert!(red > 2 * blue && alpha == 255);
+            let [red, _, blue, _] = pixel(&info, &pixels, 20, 50);
+            assert!(red.abs_diff(blue) < 16);
+            let [red, _, blue, _] = pixel(&info, &pixels, 50, 80);
+            assert!(blue > 2 * red);
+            harfrust_font_free(font);
+
+            // Concentric circles of radius 200 and 400: the start circle is
+            // padded with the first stop, the color line runs between them
+            let radial = Paint::radial_gradient(
+                test_color_line(Extend::Pad),
+                FWord::new(0),
+                FWord::new(0),
+                UfWord::new(200),
+                FWord::new(0),
+                FWord::new(0),
+                UfWord::new(400),
+            );
+            let (font_data, glyph) = test_colr_v1_font_data(radial);
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let (info, pixels) = render_pixels(font, glyph);
+            assert_eq!(pixel(&info, &pixels, 59, 50), [255, 0, 0, 255]);
+            let [red, _, blue, _] = pixel(&info, &pixels, 79, 50);
+            assert!(red.abs_diff(blue) < 16);
+            assert_eq!(pixel(&info, &pixels, 99, 50), [0, 0, 255, 255]);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_render_svg_glyph() {
+        let base = test_font_data();
+        let font_ref = harfrust::FontRef::new(&base).unwrap();
+        let glyph = font_ref.charmap().map('A').unwrap().to_u32();
+
+        // SVG table with one document for 'A'
+        let document = br#"<svg xmlns="http://www.w3.org/2000/svg"/>"#;
+        let mut svg = Vec::new();
+        svg.extend_from_slice(&0u16.to_be_bytes());
+        svg.extend_from_slice(&10u32.to_be_bytes());
+        svg.extend_from_slice(&0u32.to_be_bytes());
+        svg.extend_from_slice(&1u16.to_be_bytes());
+        svg.extend_from_slice(&(glyph as u16).to_be_bytes());
+        svg.extend_from_slice(&(glyph as u16).to_be_bytes());
+        svg.extend_from_slice(&14u32.to_be_bytes());
+        svg.extend_from_slice(&(document.len() as u32).to_be_bytes());
+        svg.extend_from_slice(document);
+        let font_data = write_fonts::FontBuilder::new()
+            .add_raw(Tag::new(b"SVG "), svg)
+            .copy_missing_tables(font_ref)
+            .build();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(harfrust_font_render_color_glyph(font, glyph, 64.0, 0).is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::Unsupported);
+            assert_eq!(last_error_message(), format!("glyph {glyph} is an SVG glyph, which cannot be rendered"));
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_supports_sequence() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let single: Vec<u16> = "A".encode_utf16().collect();
+            assert_eq!(harfrust_font_supports_sequence(font, single.as_ptr(), single.len() as i32), 1);
+
+            let pair: Vec<u16> = "AB".encode_utf16().collect();
+            assert_eq!(harfrust_font_supports_sequence(font, pair.as_ptr(), pair.len() as i32), 0);
+
+            // Family emoji: the test fonts have no glyph for the ZWJ sequence
+            let family: Vec<u16> = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}".encode_utf16().collect();
+            assert_eq!(harfrust_font_supports_sequence(font, family.as_ptr(), family.len() as i32), 0);
+
+            assert_eq!(harfrust_font_supports_sequence(std::ptr::null(), single.as_ptr(), 1), -1);
+            assert_eq!(harfrust_font_supports_sequence(font, std::ptr::null(), 1), HarfRustStatus::NullArgument as i32);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_text_fits() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let text: Vec<u16> = "Hello".encode_utf16().collect();
+            let charmap = object(font).font_ref.charmap();
+            let width: i32 = "Hello"
+                .chars()
+                .map(|c| harfrust_font_glyph_advance(font, charmap.map(c).unwrap().to_u32()))
+                .sum();
+
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), text.len() as i32, width), 1);
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), text.len() as i32, width - 1), 0);
+            assert_eq!(harfrust_text_fits(font, std::ptr::null(), 0, 0), 1);
+
+            assert_eq!(harfrust_text_fits(std::ptr::null(), text.as_ptr(), 1, 0), -1);
+            assert_eq!(harfrust_text_fits(font, std::ptr::null(), 1, 0), HarfRustStatus::NullArgument as i32);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_fit_size() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let text: Vec<u16> = "Hello".encode_utf16().collect();
+            let (ptr, len) = (text.as_ptr(), text.len() as i32);
+            let upem = harfrust_font_units_per_em(font) as f32;
+            let charmap = object(font).font_ref.charmap();
+            let advance: i32 = "Hello"
+                .chars()
+                .map(|c| harfrust_font_glyph_advance(font, charmap.map(c).unwrap().to_u32()))
+                .sum();
+
+            // Width limit that is met exactly at 12pt
+            let max_width = advance as f32 * 12.0 / upem;
+            let mut size = 0.0f32;
+            let status = harfrust_shape_fit_size(font, ptr, len, max_width, 0.0, 1.0, 100.0, &mut size);
+            assert_eq!(status, 0);
+            assert!((size - 12.0).abs() < 0.05, "size was {size}");
+
+            // Everything fits: the maximum size is chosen
+            assert_eq!(harfrust_shape_fit_size(font, ptr, len, 1.0e6, 0.0, 1.0, 100.0, &mut size), 0);
+            assert_eq!(size, 100.0);
+
+            // Nothing fits: the minimum size is reported
+            assert_eq!(harfrust_shape_fit_size(font, ptr, len, 0.1, 0.0, 4.0, 100.0, &mut size), 1);
+            assert_eq!(size, 4.0);
+
+            assert_eq!(harfrust_shape_fit_size(std::ptr::null(), ptr, len, 1.0, 0.0, 1.0, 2.0, &mut size), -1);
+            assert_eq!(harfrust_shape_fit_size(font, ptr, len, 1.0, 0.0, 2.0, 1.0, &mut size), -3);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_warmup() {
+        let font_data = test_font_data();
+
+        unsafe {
+            assert_eq!(harfrust_font_warmup(std::ptr::null()), HarfRustStatus::NullArgument);
+
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+            assert_eq!(harfrust_font_warmup(font), HarfRustStatus::Ok);
+
+            // Every advance is resolved after warm-up
+            let slots = object(font).advance_cache.default.get().expect("advances populated");
+            assert!(slots.iter().all(|slot| slot.load(Ordering::Relaxed) != ADVANCE_UNSET));
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_parse_mode() {
+        let font_data = test_font_data();
+        let (ptr, len) = (font_data.as_ptr(), font_data.len() as i32);
+
+        unsafe {
+            let lazy = harfrust_font_from_data_with_mode(ptr, len, 0, HarfRustParseMode::Lazy);
+            assert!(!lazy.is_null());
+            assert!(object(lazy).advance_cache.default.get().is_none());
+            assert!(object(lazy).degraded.get().is_none());
+            harfrust_font_free(lazy);
+
+            let eager = harfrust_font_from_data_with_mode(ptr, len, 0, HarfRustParseMode::Eager);
+            assert!(!eager.is_null());
+            assert!(object(eager).advance_cache.default.get().is_some());
+            assert_eq!(object(eager).degraded.get(), Some(&false));
+            harfrust_font_free(eager);
+
+            // Drop hmtx from the table directory: lazy creation still succeeds,
+            // eager validation rejects the font.
+            let mut broken = font_data.clone();
+            let num_tables = u16::from_be_bytes([broken[4], broken[5]]) as usize;
+            for i in 0..num_tables {
+                let record = 12 + i * 16;
+                if &broken[record..record + 4] == b"hmtx" {
+                    broken[record..record + 4].copy_from_slice(b"xxxx");
+                }
+            }
+            let (ptr, len) = (broken.as_ptr(), broken.len() as i32);
+            let lazy = harfrust_font_from_data_with_mode(ptr, len, 0, HarfRustParseMode::Lazy);
+            assert!(!lazy.is_null());
+            harfrust_font_free(lazy);
+            let eager = harfrust_font_from_data_with_mode(ptr, len, 0, HarfRustParseMode::Eager);
+            assert!(eager.is_null());
+        }
+    }
+
+    #[test]
+    fn test_glyph_text_ranges() {
+        let range = |start, length| HarfRustTextRange { start, length };
+        let info = |cluster| HarfRustGlyphInfo { glyph_id: 1, cluster };
+        let text: Vec<u16> = "abc\u{1F600}".encode_utf16().collect();
+
+        // Ligature: one glyph for "ab", then "c", then the emoji (surrogate pair)
+        let ranges = glyph_text_ranges(&[info(0), info(2), info(3)], &text);
+        assert_eq!(ranges, vec![range(0, 2), range(2, 1), range(3, 2)]);
+
+        // Decomposition: two glyphs for "a", only the first carries the text
+        let ranges = glyph_text_ranges(&[info(0), info(0), info(1)], &text[..2]);
+        assert_eq!(ranges, vec![range(0, 1), range(0, 0), range(1, 1)]);
+
+        // RTL buffer order
+        let ranges = glyph_text_ranges(&[info(3), info(2), info(0)], &text);
+        assert_eq!(ranges, vec![range(3, 2), range(2, 1), range(0, 2)]);
+
+        let font_data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "Hi!".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let glyphs = harfrust_shape(font, buffer);
+
+            let (ptr, len) = (text.as_ptr(), text.len() as i32);
+            let count =
+                harfrust_glyph_buffer_get_text_ranges(glyphs, ptr, len, std::ptr::null_mut(), 0);
+            assert_eq!(count, harfrust_glyph_buffer_len(glyphs));
+
+            let mut out = vec![HarfRustTextRange::default(); count as usize];
+            harfrust_glyph_buffer_get_text_ranges(glyphs, ptr, len, out.as_mut_ptr(), count as u32);
+            assert_eq!(out.iter().map(|r| r.length).sum::<u32>(), text.len() as u32);
+
+            let null_out = std::ptr::null_mut();
+            assert_eq!(harfrust_glyph_buffer_get_text_ranges(std::ptr::null(), ptr, len, null_out, 0), -1);
+            assert_eq!(harfrust_glyph_buffer_get_text_ranges(glyphs, std::ptr::null(), len, null_out, 0), HarfRustStatus::NullArgument as i32);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_feature_set_range() {
+        use HarfRustClusterUnit::{Utf16, Utf8};
+
+        let text: Vec<u16> = "a\u{e9}\u{1F600}b".encode_utf16().collect();
+        let (ptr, len) = (text.as_ptr(), text.len() as i32);
+        let mut feature = HarfRustFeature { tag: 0, value: 1, start: 0, end: u32::MAX };
+
+        unsafe {
+            // UTF-16 clusters map 1:1, except that surrogate pairs are not split
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 1, 3, Utf16), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (1, 4));
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 3, 4, Utf16), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (2, 4));
+
+            // UTF-8 clusters are byte offsets: a=1, e-acute=2, emoji=4
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 1, 4, Utf8), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (1, 7));
+
+            // Open end
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 4, -1, Utf8), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (7, u32::MAX));
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 0, -1, Utf16), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (0, u32::MAX));
+
+            // An explicit end at the text's length is converted, not left open
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 4, len, Utf8), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (7, 8));
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 0, len, Utf16), HarfRustStatus::Ok);
+            assert_eq!((feature.start, feature.end), (0, 5));
+
+            assert_eq!(harfrust_feature_set_range(std::ptr::null_mut(), ptr, len, 0, 1, Utf16), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 2, 1, Utf16), HarfRustStatus::OutOfRange);
+            assert_eq!(harfrust_feature_set_range(&mut feature, ptr, len, 0, 9, Utf16), HarfRustStatus::OutOfRange);
+        }
+    }
+
+    #[test]
+    fn test_buffer_save_restore_state() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "Hello".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            harfrust_buffer_set_direction(buffer, HarfRustDirection::RightToLeft);
+            harfrust_buffer_set_script(buffer, u32::from_be_bytes(*b"Latn"));
+
+            let state = harfrust_buffer_save_state(buffer);
+            assert!(!state.is_null());
+
+            // Trial shape consumes the buffer
+            let trial = harfrust_shape(font, buffer);
+            let expected: Vec<HarfRustGlyphInfo> = {
+                let len = harfrust_glyph_buffer_len(trial) as usize;
+                std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(trial), len).to_vec()
+            };
+
+            // Restoring into the recycled buffer reproduces the same input
+            let buffer = harfrust_glyph_buffer_into_buffer(trial);
+            assert_eq!(harfrust_buffer_len(buffer), 0);
+            assert_eq!(harfrust_buffer_restore_state(buffer, state), HarfRustStatus::Ok);
+            assert_eq!(harfrust_buffer_len(buffer), 5);
+            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::RightToLeft);
+            assert_eq!(harfrust_buffer_get_script(buffer), u32::from_be_bytes(*b"Latn"));
+
+            let glyphs = harfrust_shape(font, buffer);
+            let len = harfrust_glyph_buffer_len(glyphs) as usize;
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), len);
+            assert_eq!(infos.len(), expected.len());
+            for (a, b) in infos.iter().zip(&expected) {
+                assert_eq!((a.glyph_id, a.cluster), (b.glyph_id, b.cluster));
+            }
+
+            assert!(harfrust_buffer_save_state(std::ptr::null()).is_null());
+            assert_eq!(harfrust_buffer_restore_state(std::ptr::null_mut(), state), HarfRustStatus::NullArgument);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_buffer_state_free(state);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_buffer_save_restore_context() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let beh: Vec<u16> = "\u{628}".encode_utf16().collect();
+            let first_glyph = |glyphs: *mut HarfRustGlyphBuffer| {
+                let glyph = (*harfrust_glyph_buffer_get_infos(glyphs)).glyph_id;
+                harfrust_glyph_buffer_free(glyphs);
+                glyph
+            };
+
+            let isolated = {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_utf16(buffer, beh.as_ptr(), beh.len() as i32);
+                harfrust_buffer_guess_segment_properties(buffer);
+                first_glyph(harfrust_shape(font, buffer))
+            };
+
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_utf16(buffer, beh.as_ptr(), beh.len() as i32);
+            harfrust_buffer_set_context(buffer, beh.as_ptr(), beh.len() as i32, beh.as_ptr(), beh.len() as i32);
+            harfrust_buffer_guess_segment_properties(buffer);
+            let state = harfrust_buffer_save_state(buffer);
+
+            let trial = harfrust_shape(font, buffer);
+            let medial = (*harfrust_glyph_buffer_get_infos(trial)).glyph_id;
+            let recycled = harfrust_glyph_buffer_into_buffer(trial);
+            assert_ne!(medial, isolated);
+
+            // The restored buffer is joined to its context again
+            assert_eq!(harfrust_buffer_restore_state(recycled, state), HarfRustStatus::Ok);
+            assert_eq!(first_glyph(harfrust_shape(font, recycled)), medial);
+
+            harfrust_buffer_state_free(state);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_cluster_advances() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "Hello".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let glyphs = harfrust_shape(font, buffer);
+
+            let null_out = std::ptr::null_mut();
+            let count = harfrust_glyph_buffer_get_cluster_advances(glyphs, null_out, 0);
+            assert_eq!(count, 5);
+
+            let mut out = vec![HarfRustClusterAdvance::default(); count as usize];
+            harfrust_glyph_buffer_get_cluster_advances(glyphs, out.as_mut_ptr(), count as u32);
+
+            let len = harfrust_glyph_buffer_len(glyphs) as usize;
+            let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(glyphs), len);
+            let total: i32 = positions.iter().map(|p| p.x_advance).sum();
+            assert_eq!(out.iter().map(|c| c.x_advance).sum::<i32>(), total);
+            assert_eq!(out.iter().map(|c| c.glyph_count).sum::<u32>(), len as u32);
+            assert_eq!(out.iter().map(|c| c.cluster).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+            assert_eq!(harfrust_glyph_buffer_get_cluster_advances(std::ptr::null(), null_out, 0), -1);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_detect_base_direction() {
+        let detect = |text: &str| {
+            let utf16: Vec<u16> = text.encode_utf16().collect();
+            unsafe { harfrust_text_detect_base_direction(utf16.as_ptr(), utf16.len() as i32) }
+        };
+
+        assert_eq!(detect("Hello"), HarfRustDirection::LeftToRight);
+        assert_eq!(detect("\u{5E9}\u{5DC}\u{5D5}\u{5DD}"), HarfRustDirection::RightToLeft);
+        assert_eq!(detect("123 \u{627}\u{644}"), HarfRustDirection::RightToLeft);
+        assert_eq!(detect("\u{200F}abc"), HarfRustDirection::RightToLeft);
+        // Isolated content is skipped
+        assert_eq!(detect("\u{2067}abc\u{2069}\u{5D0}"), HarfRustDirection::RightToLeft);
+        assert_eq!(detect("\u{2067}\u{5D0}\u{2069}abc"), HarfRustDirection::LeftToRight);
+        assert_eq!(detect("123 !?"), HarfRustDirection::Invalid);
+        assert_eq!(detect(""), HarfRustDirection::Invalid);
+    }
+
+    #[test]
+    fn test_case_transform() {
+        use HarfRustCaseTransform as Case;
+
+        let transform = |text: &str, mode| -> (String, Vec<u32>) {
+            let chars: Vec<(char, u32)> = text.char_indices().map(|(i, c)| (c, i as u32)).collect();
+            let out = case::transform_case(&chars, mode);
+            (out.iter().map(|&(c, _)| c).collect(), out.iter().map(|&(_, i)| i).collect())
+        };
+
+        assert_eq!(transform("stra\u{df}e", Case::Uppercase).0, "STRASSE");
+        assert_eq!(transform("stra\u{df}e", Case::Uppercase).1, vec![0, 1, 2, 3, 4, 4, 6]);
+        let greek = transform("\u{39f}\u{394}\u{39f}\u{3a3} \u{3a3}\u{391}", Case::Lowercase);
+        assert_eq!(greek.0, "\u{3bf}\u{3b4}\u{3bf}\u{3c2} \u{3c3}\u{3b1}");
+        assert_eq!(transform("hello wORLD o'neil", Case::Capitalize).0, "Hello WORLD O'neil");
+        // Titlecase, not uppercase: digraphs, ligatures, Georgian and iota subscripts
+        assert_eq!(transform("\u{1C6}emal \u{1C9}ubljana", Case::Capitalize).0, "\u{1C5}emal \u{1C8}ubljana");
+        assert_eq!(transform("\u{FB01}sh \u{DF}x", Case::Capitalize), ("Fish Ssx".into(), vec![0, 0, 3, 4, 5, 6, 6, 8]));
+        assert_eq!(transform("\u{10D0}\u{10D1}", Case::Capitalize).0, "\u{10D0}\u{10D1}");
+        assert_eq!(transform("\u{1FB3}\u{1F80}", Case::Capitalize).0, "\u{1FBC}\u{1F80}");
+        assert_eq!(transform("abc", Case::None).0, "abc");
+
+        unsafe {
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "\u{df}x".encode_utf16().collect();
+            let status = harfrust_buffer_add_utf16_transformed(buffer, text.as_ptr(), 2, Case::Uppercase);
+            assert_eq!(status, HarfRustStatus::Ok);
+            assert_eq!(harfrust_buffer_len(buffer), 3);
+            assert_eq!(object(buffer).text, vec![('S', 0), ('S', 0), ('X', 1)]);
+            harfrust_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn test_shape_small_caps() {
+        let font_data = test_partial_gsub_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+            let nominal = |ch: char| object(font).font_ref.charmap().map(ch).unwrap().to_u32();
+
+            let buffer = harfrust_buffer_new();
+            let utf16: Vec<u16> = "Aab".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, utf16.as_ptr(), utf16.len() as i32);
+            let glyphs = harfrust_shape_small_caps(font, buffer, 0.75);
+            assert!(!glyphs.is_null());
+
+            let mut scales = [0.0f32; 3];
+            assert_eq!(harfrust_glyph_buffer_get_scales(glyphs, scales.as_mut_ptr(), 3), 3);
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), 3);
+            let mut transforms = [HarfRustGlyphTransform::IDENTITY; 3];
+            harfrust_glyph_buffer_get_transforms(glyphs, transforms.as_mut_ptr(), 3);
+            assert_eq!(transforms.map(|t| t.scale), scales);
+            assert!(transforms.iter().all(|t| t.rotation == 0.0));
+
+            // 'a' takes the font's small cap; smcp does not cover 'b', so it is
+            // shaped as a scaled-down 'B' that keeps its own cluster
+            assert_eq!(scales, [1.0, 1.0, 0.75]);
+            assert_eq!(infos[1].glyph_id, nominal('Q'));
+            assert_eq!(infos[2].glyph_id, nominal('B'));
+            assert_eq!(infos[2].cluster, 2);
+
+            let buffer = harfrust_buffer_new();
+            assert!(harfrust_shape_small_caps(font, buffer, 0.0).is_null());
+            harfrust_buffer_free(buffer);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_default_glyph_transforms() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "Hi".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let glyphs = harfrust_shape(font, buffer);
+
+            // Plain shaping results report identity transforms
+            let mut transforms = [HarfRustGlyphTransform { scale: 0.0, rotation: 9.0 }; 2];
+            assert_eq!(harfrust_glyph_buffer_get_transforms(glyphs, transforms.as_mut_ptr(), 2), 2);
+            assert_eq!(transforms, [HarfRustGlyphTransform::IDENTITY; 2]);
+            assert_eq!(harfrust_glyph_buffer_get_transforms(std::ptr::null(), std::ptr::null_mut(), 0), -1);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_vertical_mixed() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let shape = |text: &str, direction| {
+                let buffer = harfrust_buffer_new();
+                let text: Vec<u16> = text.encode_utf16().collect();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                harfrust_buffer_set_direction(buffer, direction);
+                buffer
+            };
+
+            // Latin is set sideways, the sun symbol upright
+            let glyphs = harfrust_shape_vertical_mixed(font, shape("ab\u{2600}", HarfRustDirection::LeftToRight));
+            assert!(!glyphs.is_null());
+            let mut transforms = [HarfRustGlyphTransform::IDENTITY; 3];
+            assert_eq!(harfrust_glyph_buffer_get_transforms(glyphs, transforms.as_mut_ptr(), 3), 3);
+            assert_eq!(transforms.map(|t| t.rotation), [-90.0, -90.0, 0.0]);
+            assert_eq!(transforms.map(|t| t.scale), [1.0; 3]);
+
+            let infos = &object(glyphs).infos_cache;
+            let positions = &object(glyphs).positions_cache;
+            assert_eq!(infos.iter().map(|i| i.cluster).collect::<Vec<_>>(), [0, 1, 2]);
+            let metrics = object(font).font_ref.metrics(Size::unscaled(), LocationRef::default());
+            let center = ((metrics.ascent + metrics.descent) / 2.0).round() as i32;
+            for (info, pos) in infos.iter().zip(positions).take(2) {
+                // The horizontal advance runs down the line
+                assert_eq!((pos.x_advance, pos.y_advance), (0, -harfrust_font_glyph_advance(font, info.glyph_id)));
+                assert_eq!(pos.x_offset, -center);
+            }
+
+            // Upright glyphs are positioned as in a top-to-bottom run
+            let upright = harfrust_shape(font, shape("\u{2600}", HarfRustDirection::TopToBottom));
+            let expected = object(upright).positions_cache[0];
+            assert_eq!((positions[2].x_advance, positions[2].y_advance), (expected.x_advance, expected.y_advance));
+            assert_eq!((positions[2].x_offset, positions[2].y_offset), (expected.x_offset, expected.y_offset));
+            harfrust_glyph_buffer_free(upright);
+            harfrust_glyph_buffer_free(glyphs);
+
+            // Combining marks keep the orientation of their base
+            let glyphs = harfrust_shape_vertical_mixed(font, shape("e\u{301}", HarfRustDirection::Invalid));
+            let mut transforms = [HarfRustGlyphTransform::IDENTITY; 2];
+            let count = harfrust_glyph_buffer_get_transforms(glyphs, transforms.as_mut_ptr(), 2) as usize;
+            assert!(transforms[..count].iter().all(|t| t.rotation == -90.0));
+            harfrust_glyph_buffer_free(glyphs);
+
+            assert!(harfrust_shape_vertical_mixed(std::ptr::null(), std::ptr::null_mut()).is_null());
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_script_position() {
+        use HarfRustScriptPosition::{Subscript, Superscript};
+
+        let font_data = test_partial_gsub_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let mut sup = HarfRustScriptMetrics::default();
+            let mut sub = HarfRustScriptMetrics::default();
+            assert_eq!(harfrust_font_get_script_metrics(font, Superscript, &mut sup), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_get_script_metrics(font, Subscript, &mut sub), HarfRustStatus::Ok);
+            assert!(sup.y_size > 0 && sup.y_offset > 0);
+            assert!(sub.y_size > 0);
+
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "12".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let glyphs = harfrust_shape_script_position(font, buffer, Superscript);
+            assert!(!glyphs.is_null());
+
+            // sups covers '1' only: it gets the font's superior glyph, while
+            // '2' is synthesized from the OS/2 metrics
+            let upem = harfrust_font_units_per_em(font) as f32;
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), 2);
+            let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(glyphs), 2);
+            let mut transforms = [HarfRustGlyphTransform::IDENTITY; 2];
+            harfrust_glyph_buffer_get_transforms(glyphs, transforms.as_mut_ptr(), 2);
+            let superior = object(font).font_ref.charmap().map('\u{b9}').unwrap().to_u32();
+            assert_eq!(infos[0].glyph_id, superior);
+            assert_eq!(positions[0].y_offset, 0);
+            assert_eq!(transforms[0], HarfRustGlyphTransform::IDENTITY);
+            assert_eq!(positions[1].y_offset, sup.y_offset);
+            assert_eq!(transforms[1].scale, sup.y_size as f32 / upem);
+
+            let mut out = HarfRustScriptMetrics::default();
+            let null_font = std::ptr::null();
+            assert_eq!(harfrust_font_get_script_metrics(null_font, Subscript, &mut out), HarfRustStatus::NullArgument);
+            let null_out = std::ptr::null_mut();
+            assert_eq!(harfrust_font_get_script_metrics(font, Subscript, null_out), HarfRustStatus::NullArgument);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_fraction_features() {
+        use HarfRustClusterUnit::{Utf16, Utf8};
+
+        let spans = |text: &str| fraction_spans(&text.encode_utf16().collect::<Vec<_>>());
+        assert_eq!(spans("1/2"), vec![(0, 1, 3)]);
+        assert_eq!(spans("take 355\u{2044}113 or 22/7."), vec![(5, 8, 12), (16, 18, 20)]);
+        assert_eq!(spans("a/b 1/ /2 12"), vec![]);
+
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let text: Vec<u16> = "\u{e9} 1/2".encode_utf16().collect();
+            let (ptr, len) = (text.as_ptr(), text.len() as i32);
+
+            let null_out = std::ptr::null_mut();
+            let count = harfrust_font_fraction_features(font, ptr, len, Utf8, null_out, 0);
+            assert!(count >= 1);
+
+            let empty = HarfRustFeature { tag: 0, value: 0, start: 0, end: 0 };
+            let mut features = vec![empty; count as usize];
+            let out = features.as_mut_ptr();
+            harfrust_font_fraction_features(font, ptr, len, Utf8, out, count as u32);
+            // UTF-8 clusters: the e-acute takes two bytes
+            assert_eq!(features[0].start, 3);
+            assert_eq!(features.last().unwrap().end, 6);
+
+            let null_font = std::ptr::null();
+            assert_eq!(harfrust_font_fraction_features(null_font, ptr, len, Utf16, null_out, 0), -1);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_feature_tables_snapshot() {
+        // The shaper tables in features.rs are copied from harfrust; re-check
+        // them against its sources and bump the snapshot version on upgrade
+        assert_eq!(env!("HARFRUST_ENGINE_VERSION"), features::SNAPSHOT_VERSION);
+
+        let rtl = |script: &[u8; 4]| features::is_rtl_script(Tag::new(script));
+        for script in [b"Arab", b"Hebr", b"Rohg", b"Yezi", b"Ougr", b"Adlm", b"Nkoo"] {
+            assert!(rtl(script), "{}", String::from_utf8_lossy(script));
+        }
+        assert!(!rtl(b"Latn") && !rtl(b"Hani") && !rtl(b"Runr") && !rtl(b"Zzzz"));
+    }
+
+    #[test]
+    fn test_default_features() {
+        let tags = |script: &[u8; 4], vertical, rtl| -> Vec<[u8; 4]> {
+            features::default_features(Tag::new(script), vertical, rtl)
+                .into_iter()
+                .map(|tag| tag.to_be_bytes())
+                .collect()
+        };
+
+        let latin = tags(b"Latn", false, false);
+        assert!(latin.contains(b"liga") && latin.contains(b"kern") && latin.contains(b"ltra"));
+        assert!(!latin.contains(b"init"));
+
+        let arabic = tags(b"Arab", false, true);
+        assert!(arabic.contains(b"init") && arabic.contains(b"medi") && arabic.contains(b"fina"));
+        assert!(arabic.contains(b"rtla") && !arabic.contains(b"ltra"));
+
+        let devanagari = tags(b"Deva", false, false);
+        assert!(devanagari.contains(b"abvs") && devanagari.contains(b"blws"));
+
+        let vertical = tags(b"Hani", true, false);
+        assert!(vertical.contains(b"vert") && !vertical.contains(b"kern"));
+
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let latn = u32::from_be_bytes(*b"Latn");
+            let ltr = HarfRustDirection::LeftToRight;
+
+            let count = harfrust_font_default_features(font, latn, ltr, std::ptr::null_mut(), 0);
+            assert!(count > 0);
+            let mut out = vec![0u32; count as usize];
+            harfrust_font_default_features(font, latn, ltr, out.as_mut_ptr(), count as u32);
+            assert!(out.contains(&u32::from_be_bytes(*b"kern")));
+
+            let null_font = std::ptr::null();
+            assert_eq!(harfrust_font_default_features(null_font, latn, ltr, std::ptr::null_mut(), 0), -1);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_buffer_slice() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "Hello world".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let glyphs = harfrust_shape(font, buffer);
+
+            let slice = harfrust_glyph_buffer_slice(glyphs, 6, 11);
+            assert!(!slice.is_null());
+            assert_eq!(harfrust_glyph_buffer_len(slice), 5);
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(slice), 5);
+            assert_eq!(infos.iter().map(|i| i.cluster).collect::<Vec<_>>(), vec![6, 7, 8, 9, 10]);
+
+            let source_positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(glyphs), 11);
+            let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(slice), 5);
+            assert_eq!(positions[0].x_advance, source_positions[6].x_advance);
+
+            // A sliced buffer can still be recycled
+            let recycled = harfrust_glyph_buffer_into_buffer(slice);
+            assert!(!recycled.is_null());
+            assert_eq!(harfrust_buffer_len(recycled), 0);
+            harfrust_buffer_free(recycled);
+
+            assert!(harfrust_glyph_buffer_slice(glyphs, 5, 5).is_null());
+            assert!(harfrust_glyph_buffer_slice(std::ptr::null(), 0, 1).is_null());
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_copy() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "Hello".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+
+            // The buffer survives shaping and can be shaped again
+            let first = harfrust_shape_copy(font, buffer, std::ptr::null(), 0);
+            assert!(!first.is_null());
+            assert_eq!(harfrust_buffer_len(buffer), 5);
+            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::Invalid);
+
+            let kern_off = HarfRustFeature {
+                tag: u32::from_be_bytes(*b"kern"),
+                value: 0,
+                start: 0,
+                end: u32::MAX,
+            };
+            let second = harfrust_shape_copy(font, buffer, &kern_off, 1);
+            assert_eq!(harfrust_glyph_buffer_len(second), 5);
+
+            let reference = harfrust_shape(font, buffer);
+            let first_infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(first), 5);
+            let ref_infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(reference), 5);
+            for (a, b) in first_infos.iter().zip(ref_infos) {
+                assert_eq!((a.glyph_id, a.cluster), (b.glyph_id, b.cluster));
+            }
+
+            assert!(harfrust_shape_copy(font, std::ptr::null(), std::ptr::null(), 0).is_null());
+
+            harfrust_glyph_buffer_free(first);
+            harfrust_glyph_buffer_free(second);
+            harfrust_glyph_buffer_free(reference);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_cap_and_x_height() {
+        let font_data = test_font_data();
+
+        // Same font with the OS/2 table hidden from the table directory
+        let mut no_os2 = font_data.clone();
+        let num_tables = u16::from_be_bytes([no_os2[4], no_os2[5]]) as usize;
+        for i in 0..num_tables {
+            let record = 12 + i * 16;
+            if &no_os2[record..record + 4] == b"OS/2" {
+                no_os2[record..record + 4].copy_from_slice(b"xxxx");
+            }
+        }
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let fallback = harfrust_font_from_data(no_os2.as_ptr(), no_os2.len() as i32);
+            assert!(!font.is_null() && !fallback.is_null());
+
+            let mut value = 0;
+            let mut source = HarfRustMetricSource::Estimated;
+            assert_eq!(harfrust_font_get_cap_height(fallback, &mut value, &mut source), HarfRustStatus::Ok);
+            assert_eq!(source, HarfRustMetricSource::GlyphOutline);
+            let measured_cap = value;
+            assert_eq!(harfrust_font_get_x_height(fallback, &mut value, &mut source), HarfRustStatus::Ok);
+            assert_eq!(source, HarfRustMetricSource::GlyphOutline);
+            let measured_x = value;
+            assert!(measured_x > 0 && measured_x < measured_cap);
+
+            // Table values, when present, agree closely with the outlines
+            assert_eq!(harfrust_font_get_cap_height(font, &mut value, &mut source), HarfRustStatus::Ok);
+            if source == HarfRustMetricSource::Table {
+                assert!((value - measured_cap).abs() <= 20, "{value} vs {measured_cap}");
+            }
+            assert_eq!(harfrust_font_get_x_height(font, &mut value, std::ptr::null_mut()), HarfRustStatus::Ok);
+
+            assert_eq!(harfrust_font_get_cap_height(std::ptr::null(), &mut value, &mut source), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_get_x_height(font, std::ptr::null_mut(), &mut source), HarfRustStatus::NullArgument);
+
+            harfrust_font_free(font);
+            harfrust_font_free(fallback);
+        }
+    }
+
+    #[test]
+    fn test_shape_run_utf16() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let text: Vec<u16> = "Hello".encode_utf16().collect();
+            let (ptr, len) = (text.as_ptr(), text.len() as i32);
+            let no_features = std::ptr::null();
+
+            let guessed = HarfRustDirection::Invalid;
+            let run = harfrust_shape_run_utf16(font, ptr, len, guessed, 0, std::ptr::null(), no_features, 0, 0);
+            assert!(!run.is_null());
+            assert_eq!(harfrust_glyph_buffer_len(run), 5);
+
+            // Same result as the multi-call path
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_utf16(buffer, ptr, len);
+            let reference = harfrust_shape(font, buffer);
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(run), 5);
+            let ref_infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(reference), 5);
+            for (a, b) in infos.iter().zip(ref_infos) {
+                assert_eq!((a.glyph_id, a.cluster), (b.glyph_id, b.cluster));
+            }
+
+            // Explicit properties are honored
+            let latn = u32::from_be_bytes(*b"Latn");
+            let rtl = HarfRustDirection::RightToLeft;
+            let lang = c"en".as_ptr();
+            let reversed = harfrust_shape_run_utf16(font, ptr, len, rtl, latn, lang, no_features, 0, 0);
+            let rev_infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(reversed), 5);
+            assert_eq!(rev_infos[0].cluster, 4);
+
+            let bad_lang = c"".as_ptr();
+            assert!(harfrust_shape_run_utf16(font, ptr, len, rtl, latn, bad_lang, no_features, 0, 0).is_null());
+            assert!(harfrust_shape_run_utf16(font, std::ptr::null(), 1, rtl, 0, lang, no_features, 0, 0).is_null());
+
+            harfrust_glyph_buffer_free(run);
+            harfrust_glyph_buffer_free(reference);
+            harfrust_glyph_buffer_free(reversed);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_estimate_stemv() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let upem = harfrust_font_units_per_em(font);
+
+            let mut value = 0;
+            let mut source = HarfRustMetricSource::Table;
+            assert_eq!(harfrust_font_estimate_stemv(font, &mut value, &mut source), HarfRustStatus::Ok);
+            assert_eq!(source, HarfRustMetricSource::GlyphOutline);
+            // Text faces have stems of a few percent of the em
+            assert!(value > upem / 50 && value < upem / 5, "stem {value} for upem {upem}");
+
+            assert_eq!(harfrust_font_estimate_stemv(std::ptr::null(), &mut value, &mut source), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_estimate_stemv(font, std::ptr::null_mut(), &mut source), HarfRustStatus::NullArgument);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_batch() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let texts: Vec<Vec<u16>> = ["Hello", "world", "!"]
+                .iter()
+                .map(|t| t.encode_utf16().collect())
+                .collect();
+            let mut runs: Vec<HarfRustRunDescriptor> = texts
+                .iter()
+                .map(|text| HarfRustRunDescriptor {
+                    text: text.as_ptr(),
+                    len: text.len() as i32,
+                    direction: HarfRustDirection::Invalid,
+                    script: 0,
+                    language: std::ptr::null(),
+                    features: std::ptr::null(),
+                    num_features: 0,
+                    normalize_spaces: 0,
+                })
+                .collect();
+            // An invalid run yields a null handle without failing the batch
+            runs[2].text = std::ptr::null();
+
+            let mut out = vec![std::ptr::null_mut(); runs.len()];
+            assert_eq!(harfrust_shape_batch(font, runs.as_ptr(), 3, out.as_mut_ptr()), 2);
+            assert_eq!(harfrust_glyph_buffer_len(out[0]), 5);
+            assert_eq!(harfrust_glyph_buffer_len(out[1]), 5);
+            assert!(out[2].is_null());
+
+            assert_eq!(harfrust_shape_batch(std::ptr::null(), runs.as_ptr(), 3, out.as_mut_ptr()), -1);
+            assert_eq!(harfrust_shape_batch(font, std::ptr::null(), 3, out.as_mut_ptr()), HarfRustStatus::NullArgument as i32);
+
+            for buffer in out {
+                harfrust_glyph_buffer_free(buffer);
+            }
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_closure() {
+        use skrifa::raw::tables::glyf::Glyph;
+
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let charmap = object(font).font_ref.charmap();
+            let seeds: Vec<u32> = "fi\u{e9}".chars().map(|c| charmap.map(c).unwrap().to_u32()).collect();
+            let closure_of = |font, seeds: *const u32, out: *mut u32, capacity| {
+                let none = std::ptr::null();
+                harfrust_font_glyph_closure(font, seeds, 3, none, 0, none, 0, out, capacity)
+            };
+
+            let count = closure_of(font, seeds.as_ptr(), std::ptr::null_mut(), 0);
+            assert!(count >= 4);
+            let mut closure = vec![0u32; count as usize];
+            closure_of(font, seeds.as_ptr(), closure.as_mut_ptr(), count as u32);
+
+            assert_eq!(closure[0], 0);
+            assert!(closure.windows(2).all(|w| w[0] < w[1]));
+            assert!(seeds.iter().all(|g| closure.contains(g)));
+
+            // Whatever shaping produces from the seeds is in the closure
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "fifi\u{e9}".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let glyphs = harfrust_shape(font, buffer);
+            let len = harfrust_glyph_buffer_len(glyphs) as usize;
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), len);
+            assert!(infos.iter().all(|info| closure.contains(&info.glyph_id)));
+
+            // Composite components are included
+            let loca = object(font).font_ref.loca(None).unwrap();
+            let glyf = object(font).font_ref.glyf().unwrap();
+            for &gid in &closure {
+                if let Ok(Some(Glyph::Composite(composite))) = loca.get_glyf(GlyphId::new(gid), &glyf) {
+                    assert!(composite.components().all(|c| closure.contains(&c.glyph.to_u32())));
+                }
+            }
+
+            assert_eq!(closure_of(std::ptr::null(), seeds.as_ptr(), std::ptr::null_mut(), 0), -1);
+            assert_eq!(closure_of(font, std::ptr::null(), std::ptr::null_mut(), 0), HarfRustStatus::NullArgument as i32);
+
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_subset() {
+        use skrifa::raw::tables::glyf::Glyph;
+        use skrifa::FontRef;
+
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let original = &object(font).font_ref;
+            let charmap = original.charmap();
+            let seeds: Vec<u32> = ['A', '\u{e9}'].map(|c| charmap.map(c).unwrap().to_u32()).into();
+            let closure = subset::glyph_closure(original, &seeds, None, None);
+            let glyph = |font: &FontRef, gid: u32| {
+                let (loca, glyf) = (font.loca(None).unwrap(), font.glyf().unwrap());
+                let glyph = loca.get_glyf(GlyphId::new(gid), &glyf).unwrap();
+                glyph.map(|g| (g.x_min(), g.y_min(), g.x_max(), g.y_max()))
+            };
+            let components = |font: &FontRef, gid: u32| {
+                let (loca, glyf) = (font.loca(None).unwrap(), font.glyf().unwrap());
+                match loca.get_glyf(GlyphId::new(gid), &glyf) {
+                    Ok(Some(Glyph::Composite(composite))) => {
+                        Some(composite.components().map(|c| c.glyph.to_u32()).collect::<Vec<_>>())
+                    }
+                    _ => None,
+                }
+            };
+            let subset_of = |mode| {
+                let no_name = std::ptr::null_mut();
+                let blob = harfrust_font_subset(font, seeds.as_ptr(), 2, mode, no_name, 0);
+                assert!(!blob.is_null());
+                let len = harfrust_blob_len(blob) as usize;
+                let bytes = std::slice::from_raw_parts(harfrust_blob_get_data(blob), len).to_vec();
+                harfrust_blob_free(blob);
+                bytes
+            };
+
+            // Retained IDs: same glyph count, used glyphs intact, others empty
+            let data = subset_of(HarfRustSubsetMode::RetainGids);
+            assert!(data.len() < font_data.len());
+            let subset = FontRef::new(&data).unwrap();
+            assert_eq!(subset.maxp().unwrap().num_glyphs(), original.maxp().unwrap().num_glyphs());
+            assert_eq!(subset.charmap().map('A').unwrap().to_u32(), seeds[0]);
+            for &gid in &seeds {
+                assert_eq!(glyph(&subset, gid), glyph(original, gid));
+            }
+            assert!(glyph(&subset, charmap.map('Z').unwrap().to_u32()).is_none());
+
+            // Compact: closure renumbered in order, composites point at new IDs
+            let data = subset_of(HarfRustSubsetMode::Compact);
+            let subset = FontRef::new(&data).unwrap();
+            assert_eq!(subset.maxp().unwrap().num_glyphs() as usize, closure.len());
+            assert!(subset.cmap().is_err());
+            let hmtx = subset.hmtx().unwrap();
+            let mut composites = 0;
+            for (new, &old) in closure.iter().enumerate() {
+                let new = new as u32;
+                assert_eq!(glyph(&subset, new), glyph(original, old));
+                let original_advance = original.hmtx().unwrap().advance(GlyphId::new(old));
+                assert_eq!(hmtx.advance(GlyphId::new(new)), original_advance);
+                if let Some(new_components) = components(&subset, new) {
+                    let remapped: Vec<u32> =
+                        new_components.iter().map(|&c| closure[c as usize]).collect();
+                    assert_eq!(Some(remapped), components(original, old));
+                    composites += 1;
+                }
+            }
+            assert!(composites > 0);
+
+            let mode = HarfRustSubsetMode::Compact;
+            let no_name = std::ptr::null_mut();
+            assert!(harfrust_font_subset(std::ptr::null(), seeds.as_ptr(), 2, mode, no_name, 0).is_null());
+            assert!(harfrust_font_subset(font, std::ptr::null(), 2, mode, no_name, 0).is_null());
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_from_data_borrowed() {
+        unsafe extern "C" fn release(user_data: *mut c_void) {
+            unsafe { (*(user_data as *const AtomicI32)).fetch_add(1, Ordering::SeqCst) };
+        }
+
+        let font_data = test_font_data();
+        let released = AtomicI32::new(0);
+        let user_data = &released as *const AtomicI32 as *mut c_void;
+
+        unsafe {
+            let font = harfrust_font_from_data_borrowed(
+                font_data.as_ptr(),
+                font_data.len() as i32,
+                Some(release),
+                user_data,
+            );
+            assert!(!font.is_null());
+            assert_eq!(harfrust_font_units_per_em(font), 2048);
+
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"Hello".as_ptr());
+            let glyphs = harfrust_shape(font, buffer);
+            assert_eq!(harfrust_glyph_buffer_len(glyphs), 5);
+            harfrust_glyph_buffer_free(glyphs);
+
+            assert_eq!(released.load(Ordering::SeqCst), 0);
+            harfrust_font_free(font);
+            assert_eq!(released.load(Ordering::SeqCst), 1);
+
+            // Failed creation releases immediately
+            let garbage = [0u8; 16];
+            let font = harfrust_font_from_data_borrowed(garbage.as_ptr(), 16, Some(release), user_data);
+            assert!(font.is_null());
+            assert_eq!(released.load(Ordering::SeqCst), 2);
+
+            let font = harfrust_font_from_data_borrowed(font_data.as_ptr(), 0, None, std::ptr::null_mut());
+            assert!(font.is_null());
+        }
+    }
+
+    #[test]
+    fn test_font_from_file() {
+        let font_data = test_font_data();
+        let path = std::env::temp_dir().join(format!("harfrust-ffi-test-{}.ttf", std::process::id()));
+        std::fs::write(&path, &font_data).unwrap();
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let font = harfrust_font_from_file(c_path.as_ptr(), 0);
+            assert!(!font.is_null());
+            let from_data = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert_eq!(harfrust_font_units_per_em(font), harfrust_font_units_per_em(from_data));
+            assert_eq!(harfrust_font_glyph_advance(font, 1), harfrust_font_glyph_advance(from_data, 1));
+            harfrust_font_free(from_data);
+            harfrust_font_free(font);
+
+            assert!(harfrust_font_from_file(c_path.as_ptr(), 1).is_null());
+            assert!(harfrust_font_from_file(c"/nonexistent/font.ttf".as_ptr(), 0).is_null());
+            assert!(harfrust_font_from_file(std::ptr::null(), 0).is_null());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_subset_names() {
+        use skrifa::string::StringId;
+        use skrifa::FontRef;
+
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let mode = HarfRustSubsetMode::Compact;
+            let subset_name = |glyphs: &[u32], capacity: usize| {
+                let mut name = vec![0 as c_char; capacity];
+                let (count, out) = (glyphs.len() as u32, name.as_mut_ptr());
+                let blob = harfrust_font_subset(font, glyphs.as_ptr(), count, mode, out, capacity as u32);
+                let len = harfrust_blob_len(blob) as usize;
+                let data = std::slice::from_raw_parts(harfrust_blob_get_data(blob), len).to_vec();
+                harfrust_blob_free(blob);
+                (CStr::from_ptr(name.as_ptr()).to_str().unwrap().to_string(), data)
+            };
+
+            let (name, data) = subset_name(&[36, 37], 72);
+            let (tag, base) = name.split_once('+').unwrap();
+            assert_eq!(tag.len(), 6);
+            assert!(tag.bytes().all(|b| b.is_ascii_uppercase()));
+            let string = |font: &FontRef, id| font.localized_strings(id).english_or_first().unwrap().to_string();
+            assert_eq!(base, string(&object(font).font_ref, StringId::POSTSCRIPT_NAME));
+
+            // Name records carry the tag; the tag is stable per glyph set
+            let subset = FontRef::new(&data).unwrap();
+            assert_eq!(string(&subset, StringId::POSTSCRIPT_NAME), name);
+            assert!(string(&subset, StringId::FAMILY_NAME).starts_with(&format!("{tag}+")));
+            assert!(string(&subset, StringId::FULL_NAME).starts_with(&format!("{tag}+")));
+            assert_eq!(subset_name(&[36, 37], 72).0, name);
+            assert_ne!(subset_name(&[36, 38], 72).0, name);
+
+            // Truncated to the capacity, always terminated
+            assert_eq!(subset_name(&[36, 37], 8).0, name[..7]);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_subsetter() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let subsetter = harfrust_subsetter_new();
+            assert_eq!(harfrust_subsetter_glyph_count(subsetter), 0);
+
+            // Two "pages" of shaped text, with overlapping glyphs
+            let mut page_glyphs = Vec::new();
+            for page in ["Hello", "World"] {
+                let buffer = harfrust_buffer_new();
+                let text = CString::new(page).unwrap();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                let glyphs = harfrust_shape(font, buffer);
+                assert_eq!(harfrust_subsetter_add_glyph_buffer(subsetter, glyphs), HarfRustStatus::Ok);
+                let len = harfrust_glyph_buffer_len(glyphs) as usize;
+                let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), len);
+                page_glyphs.extend(infos.iter().map(|info| info.glyph_id));
+                harfrust_glyph_buffer_free(glyphs);
+            }
+            page_glyphs.sort();
+            page_glyphs.dedup();
+            assert_eq!(harfrust_subsetter_glyph_count(subsetter), page_glyphs.len() as i32);
+
+            let extra = [3u32, page_glyphs[0]];
+            assert_eq!(harfrust_subsetter_add_glyphs(subsetter, extra.as_ptr(), 2), HarfRustStatus::Ok);
+            assert_eq!(harfrust_subsetter_glyph_count(subsetter), page_glyphs.len() as i32 + 1);
+            page_glyphs.push(3);
+
+            // Same result as a one-shot subset of the accumulated glyphs
+            let mode = HarfRustSubsetMode::RetainGids;
+            let no_name = std::ptr::null_mut();
+            let blob = harfrust_subsetter_build(subsetter, font, mode, no_name, 0);
+            let count = page_glyphs.len() as u32;
+            let expected = harfrust_font_subset(font, page_glyphs.as_ptr(), count, mode, no_name, 0);
+            assert_eq!(harfrust_blob_len(blob), harfrust_blob_len(expected));
+            harfrust_blob_free(blob);
+            harfrust_blob_free(expected);
+
+            assert_eq!(harfrust_subsetter_add_glyphs(std::ptr::null_mut(), extra.as_ptr(), 2), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_subsetter_add_glyphs(subsetter, std::ptr::null(), 2), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_subsetter_glyph_count(std::ptr::null()), -1);
+            assert!(harfrust_subsetter_build(subsetter, std::ptr::null(), mode, no_name, 0).is_null());
+
+            harfrust_subsetter_free(subsetter);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[cfg(feature = "woff")]
+    #[test]
+    fn test_font_from_woff() {
+        let font_data = test_font_data();
+        let font_ref = skrifa::FontRef::new(&font_data).unwrap();
+        let records = font_ref.table_directory.table_records();
+
+        // Encode a WOFF file; tables that don't shrink are stored uncompressed
+        let mut directory = Vec::new();
+        let mut tables = Vec::new();
+        let mut offset = 44 + records.len() * 20;
+        for record in records {
+            let table = font_ref.data_for_tag(record.tag()).unwrap().as_bytes();
+            let mut compressed = miniz_oxide::deflate::compress_to_vec_zlib(table, 6);
+            if compressed.len() >= table.len() {
+                compressed = table.to_vec();
+            }
+            directory.extend_from_slice(&record.tag().to_be_bytes());
+            directory.extend_from_slice(&(offset as u32).to_be_bytes());
+            directory.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+            directory.extend_from_slice(&(table.len() as u32).to_be_bytes());
+            directory.extend_from_slice(&record.checksum().to_be_bytes());
+            offset += compressed.len().next_multiple_of(4);
+            tables.extend_from_slice(&compressed);
+            tables.resize(tables.len().next_multiple_of(4), 0);
+        }
+        let mut woff = b"wOFF".to_vec();
+        woff.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        woff.extend_from_slice(&(offset as u32).to_be_bytes());
+        woff.extend_from_slice(&(records.len() as u16).to_be_bytes());
+        woff.resize(44, 0);
+        woff.extend_from_slice(&directory);
+        woff.extend_from_slice(&tables);
+
+        unsafe {
+            let font = harfrust_font_from_data(woff.as_ptr(), woff.len() as i32);
+            assert!(!font.is_null());
+            let expected = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert_eq!(harfrust_font_units_per_em(font), harfrust_font_units_per_em(expected));
+            for glyph_id in [0, 36, 100] {
+                let advance = harfrust_font_glyph_advance(expected, glyph_id);
+                assert_eq!(harfrust_font_glyph_advance(font, glyph_id), advance);
+            }
+            harfrust_font_free(expected);
+            harfrust_font_free(font);
+
+            // Corrupt compressed data is rejected
+            let last = woff.len() - 8;
+            woff[last..].fill(0xFF);
+            assert!(harfrust_font_from_data(woff.as_ptr(), woff.len() as i32).is_null());
+        }
+    }
+
+    #[test]
+    fn test_font_instantiate() {
+        use skrifa::raw::tables::glyf::Glyph;
+        use skrifa::FontRef;
+
+        let (font_data, l) = test_variable_font_data();
+        let bounds = |font: &FontRef, gid: u32| {
+            let (loca, glyf) = (font.loca(None).unwrap(), font.glyf().unwrap());
+            let glyph = loca.get_glyf(GlyphId::new(gid), &glyf).unwrap();
+            glyph.map(|g| (g.x_min(), g.y_min(), g.x_max(), g.y_max()))
+        };
+        let advance = |font: &FontRef, gid: u32| {
+            font.hmtx().unwrap().advance(GlyphId::new(gid)).unwrap()
+        };
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let original = &object(font).font_ref;
+            let instantiate = |wght: f32| {
+                let tag = u32::from_be_bytes(*b"wght");
+                let variations = [HarfRustVariation { tag, value: wght }];
+                let blob = harfrust_font_instantiate(font, variations.as_ptr(), 1);
+                assert!(!blob.is_null());
+                let len = harfrust_blob_len(blob) as usize;
+                let data = std::slice::from_raw_parts(harfrust_blob_get_data(blob), len).to_vec();
+                harfrust_blob_free(blob);
+                data
+            };
+
+            // Deltas applied at the full and half-way locations
+            let (x_min, y_min, x_max, y_max) = bounds(original, l).unwrap();
+            for (wght, shift) in [(700.0, 50), (550.0, 25)] {
+                let data = instantiate(wght);
+                let instance = FontRef::new(&data).unwrap();
+                assert!(instance.fvar().is_err() && instance.gvar().is_err());
+                assert_eq!(bounds(&instance, l), Some((x_min + shift, y_min, x_max + shift, y_max)));
+                assert_eq!(advance(&instance, l), advance(original, l) + 2 * shift as u16);
+                assert_eq!(instance.os2().unwrap().us_weight_class(), wght as u16);
+            }
+
+            // Other glyphs keep their shape; composites are flattened
+            let data = instantiate(700.0);
+            let instance = FontRef::new(&data).unwrap();
+            let e_acute = original.charmap().map('\u{e9}').unwrap().to_u32();
+            for gid in [original.charmap().map('A').unwrap().to_u32(), e_acute] {
+                assert_eq!(bounds(&instance, gid), bounds(original, gid));
+                assert_eq!(advance(&instance, gid), advance(original, gid));
+            }
+            let glyf = instance.glyf().unwrap();
+            let glyph = instance.loca(None).unwrap().get_glyf(GlyphId::new(e_acute), &glyf);
+            assert!(matches!(glyph, Ok(Some(Glyph::Simple(_)))));
+
+            assert!(harfrust_font_instantiate(std::ptr::null(), std::ptr::null(), 0).is_null());
+            assert!(harfrust_font_instantiate(font, std::ptr::null(), 1).is_null());
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_from_cff() {
+        let cff = test_cff_data();
+
+        unsafe {
+            let font = harfrust_font_from_cff(cff.as_ptr(), cff.len() as i32);
+            assert!(!font.is_null());
+            assert_eq!(harfrust_font_units_per_em(font), 1000);
+            assert_eq!(harfrust_font_glyph_advance(font, 0), 500);
+            assert_eq!(harfrust_font_glyph_advance(font, 1), 600);
+            assert_eq!(harfrust_font_glyph_advance(font, 2), 250);
+
+            // Glyph names give the Unicode mapping; outlines come from the CFF
+            let bounds = metrics::char_bounds(&object(font).font_ref, 'A').unwrap();
+            let edges = (bounds.left(), bounds.top(), bounds.right(), bounds.bottom());
+            assert_eq!(edges, (50.0, 0.0, 550.0, 700.0));
+
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"A A".as_ptr());
+            let glyphs = harfrust_shape(font, buffer);
+            let len = harfrust_glyph_buffer_len(glyphs) as usize;
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), len);
+            let positions = harfrust_glyph_buffer_get_positions(glyphs);
+            let positions = std::slice::from_raw_parts(positions, len);
+            assert_eq!(infos.iter().map(|i| i.glyph_id).collect::<Vec<_>>(), [1, 2, 1]);
+            assert_eq!(positions.iter().map(|p| p.x_advance).collect::<Vec<_>>(), [600, 250, 600]);
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_font_free(font);
+
+            let font_data = test_font_data();
+            assert!(harfrust_font_from_cff(font_data.as_ptr(), font_data.len() as i32).is_null());
+            assert!(harfrust_font_from_cff(std::ptr::null(), 0).is_null());
+        }
+    }
+
+    #[test]
+    fn test_glyph_outline() {
+        use HarfRustPathVerb as Verb;
+
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let upem = harfrust_font_units_per_em(font) as f32;
+            let charmap = object(font).font_ref.charmap();
+            let outline = |ch: char, units_per_em: f32| {
+                let gid = charmap.map(ch).unwrap().to_u32();
+                let count = harfrust_font_glyph_outline(font, gid, units_per_em, std::ptr::null_mut(), 0);
+                let empty = HarfRustPathCommand {
+                    verb: Verb::Close, x1: 0.0, y1: 0.0, x2: 0.0, y2: 0.0, x: 0.0, y: 0.0,
+                };
+                let mut commands = vec![empty; count as usize];
+                let out = commands.as_mut_ptr();
+                harfrust_font_glyph_outline(font, gid, units_per_em, out, count as u32);
+                commands
+            };
+
+            // 'o' has two closed contours of cubics (converted from quadratics)
+            let font_units = outline('o', 0.0);
+            assert_eq!(font_units[0].verb, Verb::MoveTo);
+            assert_eq!(font_units.iter().filter(|c| c.verb == Verb::MoveTo).count(), 2);
+            assert_eq!(font_units.iter().filter(|c| c.verb == Verb::Close).count(), 2);
+            assert!(font_units.iter().any(|c| c.verb == Verb::CubicTo));
+
+            // PDF glyph space is the same path scaled to 1000 units per em
+            let glyph_space = outline('o', 1000.0);
+            assert_eq!(glyph_space.len(), font_units.len());
+            let scale = 1000.0 / upem;
+            for (scaled, unscaled) in glyph_space.iter().zip(&font_units) {
+                assert_eq!(scaled.verb, unscaled.verb);
+                assert!((scaled.x - unscaled.x * scale).abs() < 1e-3);
+                assert!((scaled.y2 - unscaled.y2 * scale).abs() < 1e-3);
+            }
+            let max_y = glyph_space.iter().map(|c| c.y).fold(f32::MIN, f32::max);
+            assert!(max_y > 400.0 && max_y < 700.0);
+
+            assert_eq!(outline(' ', 1000.0).len(), 0);
+            let none = std::ptr::null_mut();
+            assert_eq!(harfrust_font_glyph_outline(std::ptr::null(), 1, 1000.0, none, 0), -1);
+            assert_eq!(harfrust_font_glyph_outline(font, u32::MAX, 1000.0, none, 0), HarfRustStatus::OutOfRange as i32);
+            assert_eq!(harfrust_font_glyph_outline(font, 1, -1.0, none, 0), HarfRustStatus::InvalidArgument as i32);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_collection_index() {
+        let font_data = test_font_data();
+        let collection = test_collection_data();
+
+        unsafe {
+            assert_eq!(harfrust_font_collection_count(font_data.as_ptr(), font_data.len() as i32), 1);
+            assert_eq!(harfrust_font_collection_count(collection.as_ptr(), collection.len() as i32), 2);
+            assert_eq!(harfrust_font_collection_count(b"junk".as_ptr(), 4), 0);
+            assert_eq!(harfrust_font_collection_count(std::ptr::null(), 0), 0);
+
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert_eq!(harfrust_font_get_index(font), 0);
+
+            let face = harfrust_font_from_data_index(collection.as_ptr(), collection.len() as i32, 1);
+            assert!(!face.is_null());
+            assert_eq!(harfrust_font_get_index(face), 1);
+            assert_eq!(harfrust_font_glyph_advance(face, 36), harfrust_font_glyph_advance(font, 36));
+            harfrust_font_free(face);
+            harfrust_font_free(font);
+
+            assert!(harfrust_font_from_data_index(collection.as_ptr(), collection.len() as i32, 2).is_null());
+            assert_eq!(harfrust_font_get_index(std::ptr::null()), -1);
+        }
+    }
+
+    #[test]
+    fn test_shaper() {
+        let (font_data, l) = test_variable_font_data();
+        let tag = u32::from_be_bytes(*b"wght");
+        let variations = [HarfRustVariation { tag, value: 700.0 }];
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let shaper = harfrust_shaper_new(font, variations.as_ptr(), 1);
+            let default_shaper = harfrust_shaper_new(font, std::ptr::null(), 0);
+            assert!(!shaper.is_null() && !default_shaper.is_null());
+
+            let shape = |shaper: *const HarfRustShaper| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, c"ll".as_ptr());
+                let result = harfrust_shaper_shape(shaper, buffer, std::ptr::null(), 0);
+                assert_eq!(harfrust_glyph_buffer_len(result), 2);
+                assert_eq!((*harfrust_glyph_buffer_get_infos(result)).glyph_id, l);
+                let advance = (*harfrust_glyph_buffer_get_positions(result)).x_advance;
+                harfrust_glyph_buffer_free(result);
+                advance
+            };
+
+            // The same shaper serves repeated calls
+            let bold = shape(shaper);
+            assert_eq!(shape(shaper), bold);
+            assert_eq!(bold, shape(default_shaper) + 100);
+
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"ll".as_ptr());
+            let full = harfrust_shape_full(font, buffer, std::ptr::null(), 0, variations.as_ptr(), 1);
+            assert_eq!((*harfrust_glyph_buffer_get_positions(full)).x_advance, bold);
+            harfrust_glyph_buffer_free(full);
+
+            assert!(harfrust_shaper_new(std::ptr::null(), std::ptr::null(), 0).is_null());
+            let null_buffer = std::ptr::null_mut();
+            assert!(harfrust_shaper_shape(shaper, null_buffer, std::ptr::null(), 0).is_null());
+
+            // The shapers keep the font alive
+            harfrust_font_free(font);
+            assert_eq!(shape(shaper), bold);
+            harfrust_shaper_free(shaper);
+            assert_eq!(shape(default_shaper), bold - 100);
+            harfrust_shaper_free(default_shaper);
+        }
+    }
+
+    #[test]
+    fn test_shape_field_line() {
+        let font_data = test_font_data();
+        let text: Vec<u16> = "Hello".encode_utf16().collect();
+        let (ptr, len) = (text.as_ptr(), text.len() as i32);
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let metrics = object(font).font_ref.metrics(Size::unscaled(), LocationRef::default());
+            let upem = metrics.units_per_em as f32;
+            let advance: i32 = {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_utf16(buffer, ptr, len);
+                let result = harfrust_shape(font, buffer);
+                let n = harfrust_glyph_buffer_len(result) as usize;
+                let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(result), n);
+                let sum = positions.iter().map(|p| p.x_advance).sum();
+                harfrust_glyph_buffer_free(result);
+                sum
+            };
+
+            // Auto size: the padded width is met exactly at 12pt
+            let width = advance as f32 * 12.0 / upem + 4.0;
+            let mut layout = HarfRustLineLayout::default();
+            let lay_out = |font_size: f32, align: HarfRustTextAlign, layout: &mut HarfRustLineLayout| {
+                let result = harfrust_shape_field_line(font, ptr, len, width, 100.0, 2.0, font_size, align, layout);
+                assert!(!result.is_null());
+                assert_eq!(harfrust_glyph_buffer_len(result), 5);
+                harfrust_glyph_buffer_free(result);
+            };
+            lay_out(0.0, HarfRustTextAlign::Left, &mut layout);
+            assert!((layout.font_size - 12.0).abs() < 0.05, "size was {}", layout.font_size);
+            assert_eq!(layout.x, 2.0);
+            assert!((layout.width - (width - 4.0)).abs() < 0.05);
+
+            // Line box centered vertically
+            let scale = layout.font_size / upem;
+            let (top, bottom) = (layout.y + metrics.ascent * scale, layout.y + metrics.descent * scale);
+            assert!((top + bottom - 100.0).abs() < 1e-3);
+
+            // Fixed size with right and center alignment
+            lay_out(6.0, HarfRustTextAlign::Right, &mut layout);
+            assert_eq!(layout.font_size, 6.0);
+            assert!((layout.x + layout.width - (width - 2.0)).abs() < 1e-3);
+            lay_out(6.0, HarfRustTextAlign::Center, &mut layout);
+            assert!((2.0 * layout.x + layout.width - width).abs() < 1e-3);
+
+            // Short rectangles limit the size by height
+            let result = harfrust_shape_field_line(font, ptr, len, 1000.0, 14.0, 2.0, 0.0, HarfRustTextAlign::Left, &mut layout);
+            let line_height = (metrics.ascent - metrics.descent) * layout.font_size / upem;
+            assert!(line_height <= 10.0 && line_height > 9.9);
+            harfrust_glyph_buffer_free(result);
+
+            let align = HarfRustTextAlign::Left;
+            assert!(harfrust_shape_field_line(std::ptr::null(), ptr, len, width, 20.0, 2.0, 0.0, align, &mut layout).is_null());
+            assert!(harfrust_shape_field_line(font, ptr, len, 4.0, 20.0, 2.0, 0.0, align, &mut layout).is_null());
+            assert!(harfrust_shape_field_line(font, ptr, len, width, 20.0, 2.0, -1.0, align, &mut layout).is_null());
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_pdf_metrics() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let mut metrics = HarfRustPdfMetrics::default();
+            assert_eq!(harfrust_font_get_pdf_metrics(font, &mut metrics), HarfRustStatus::Ok);
+
+            assert_eq!(metrics.units_per_em, harfrust_font_units_per_em(font));
+            assert!(metrics.ascent > 0 && metrics.descent < 0);
+            assert_eq!(metrics.missing_width, harfrust_font_glyph_advance(font, 0));
+
+            let mut advances = vec![0u16; harfrust_font_all_advances(font, std::ptr::null_mut(), 0) as usize];
+            harfrust_font_all_advances(font, advances.as_mut_ptr(), advances.len() as u32);
+            let count = |width: i32| advances.iter().filter(|&&a| a as i32 == width).count();
+            assert!(metrics.default_width > 0);
+            assert!(advances.iter().all(|&a| a == 0 || count(a as i32) <= count(metrics.default_width)));
+
+            assert_eq!(harfrust_font_get_pdf_metrics(font, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_get_pdf_metrics(std::ptr::null(), &mut metrics), HarfRustStatus::NullArgument);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_with_instance() {
+        let (font_data, _) = test_variable_font_data();
+        let tag = u32::from_be_bytes(*b"wght");
+        let variations = [HarfRustVariation { tag, value: 700.0 }];
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let bold = harfrust_instance_new(font, variations.as_ptr(), 1);
+            let regular = harfrust_instance_new(font, std::ptr::null(), 0);
+            assert!(!bold.is_null() && !regular.is_null());
+
+            let advance = |result: *mut HarfRustGlyphBuffer| {
+                let advance = (*harfrust_glyph_buffer_get_positions(result)).x_advance;
+                harfrust_glyph_buffer_free(result);
+                advance
+            };
+            let buffer = || {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, c"l".as_ptr());
+                buffer
+            };
+            let shape = |instance: *const HarfRustInstance| {
+                advance(harfrust_shape_with_instance(font, instance, buffer(), std::ptr::null(), 0))
+            };
+
+            let full = harfrust_shape_full(font, buffer(), std::ptr::null(), 0, variations.as_ptr(), 1);
+            assert_eq!(shape(bold), advance(full));
+            assert_eq!(shape(bold), shape(bold));
+            assert_eq!(shape(regular), advance(harfrust_shape(font, buffer())));
+            assert_eq!(shape(std::ptr::null()), shape(regular));
+            assert_eq!(shape(bold), shape(regular) + 100);
+
+            assert!(harfrust_instance_new(std::ptr::null(), variations.as_ptr(), 1).is_null());
+
+            harfrust_instance_free(bold);
+            harfrust_instance_free(regular);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_plan_cache() {
+        let _settings = GLOBAL_SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let plan_count = || object(font).plan_cache.plans.lock().unwrap().len();
+            let shape = |text: &CStr, features: &[HarfRustFeature]| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                let result = harfrust_shape_with_features(font, buffer, features.as_ptr(), features.len() as u32);
+                let len = harfrust_glyph_buffer_len(result) as usize;
+                let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(result), len).to_vec();
+                let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(result), len);
+                let advances: Vec<i32> = positions.iter().map(|p| p.x_advance).collect();
+                harfrust_glyph_buffer_free(result);
+                (infos.iter().map(|i| i.glyph_id).collect::<Vec<_>>(), advances)
+            };
+
+            // Same segment properties and features share one plan
+            let first = shape(c"office AVATAR", &[]);
+            assert_eq!(plan_count(), 1);
+            assert_eq!(shape(c"office AVATAR", &[]), first);
+            shape(c"other words", &[]);
+            assert_eq!(plan_count(), 1);
+
+            // Cached plans give the same result as shaping without the cache
+            let mut unicode_buffer = harfrust::UnicodeBuffer::new();
+            unicode_buffer.push_str("office AVATAR");
+            unicode_buffer.guess_segment_properties();
+            let uncached = object(font).shaper_data.shaper(&object(font).font_ref).build().shape(unicode_buffer, &[]);
+            let glyphs: Vec<u32> = uncached.glyph_infos().iter().map(|i| i.glyph_id).collect();
+            let advances: Vec<i32> = uncached.glyph_positions().iter().map(|p| p.x_advance).collect();
+            assert_eq!(first, (glyphs, advances));
+
+            // Features and scripts key separate plans
+            let kern_off = HarfRustFeature { tag: u32::from_be_bytes(*b"kern"), value: 0, start: 0, end: u32::MAX };
+            let unkerned = shape(c"office AVATAR", &[kern_off]);
+            assert_eq!(plan_count(), 2);
+            assert_ne!(unkerned.1, first.1);
+            shape(c"\u{5e9}\u{5dc}\u{5d5}\u{5dd}", &[]);
+            assert_eq!(plan_count(), 3);
+
+            // The cache is bounded
+            for value in 0..2 * PLAN_CACHE_CAPACITY as u32 {
+                let feature = HarfRustFeature { tag: u32::from_be_bytes(*b"ss01"), value, start: 0, end: u32::MAX };
+                shape(c"a", &[feature]);
+            }
+            assert_eq!(plan_count(), PLAN_CACHE_CAPACITY);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_vertical_metrics() {
+        use skrifa::FontRef;
+        use write_fonts::tables::hmtx::LongMetric;
+        use write_fonts::tables::vhea::Vhea;
+        use write_fonts::tables::vmtx::Vmtx;
+        use write_fonts::types::{FWord, UfWord};
+
+        let font_data = test_font_data();
+        let font_ref = FontRef::new(&font_data).unwrap();
+        let num_glyphs = font_ref.maxp().unwrap().num_glyphs();
+        let glyph = font_ref.charmap().map('A').unwrap();
+        let y_max = font_ref.glyph_metrics(Size::unscaled(), LocationRef::default()).bounds(glyph).unwrap().y_max;
+        let h_advance = font_ref.hmtx().unwrap().advance(glyph).unwrap() as f32;
+
+        // Every glyph advances a full em and sits 100 units below the top
+        let (upem, zero) = (font_ref.head().unwrap().units_per_em(), FWord::new(0));
+        let vhea = Vhea::new(zero, zero, zero, UfWord::new(upem), zero, zero, zero, 0, 1, 0, 1);
+        let vmtx = Vmtx::new(vec![LongMetric::new(upem, 100)], vec![100; num_glyphs as usize - 1]);
+        let build = |default_origin_y: Option<i16>| {
+            let mut builder = write_fonts::FontBuilder::new();
+            builder.add_table(&vhea).unwrap().add_table(&vmtx).unwrap();
+            if let Some(origin_y) = default_origin_y {
+                // VORG 1.0 without per-glyph entries
+                let vorg = [&[0, 1, 0, 0], &origin_y.to_be_bytes()[..], &[0, 0]].concat();
+                builder.add_raw(Tag::new(b"VORG"), vorg);
+            }
+            builder.copy_missing_tables(font_ref.clone()).build()
+        };
+
+        let vertical_metrics = |data: &[u8]| unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let mut out = HarfRustVerticalMetrics::default();
+            assert_eq!(harfrust_font_vertical_metrics(font, &glyph.to_u32(), 1, &mut out), 1);
+            harfrust_font_free(font);
+            out
+        };
+        let scale = 1000.0 / upem as f32;
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+
+        let from_vmtx = vertical_metrics(&build(None));
+        assert!(close(from_vmtx.w1y, -1000.0));
+        assert!(close(from_vmtx.vx, h_advance / 2.0 * scale));
+        assert!(close(from_vmtx.vy, (100.0 + y_max) * scale));
+
+        let from_vorg = vertical_metrics(&build(Some(1800)));
+        assert!(close(from_vorg.w1y, -1000.0));
+        assert!(close(from_vorg.vy, 1800.0 * scale));
+
+        // Without vertical tables: a line-height advance, origin at the ascender
+        let metrics = font_ref.metrics(Size::unscaled(), LocationRef::default());
+        let fallback = vertical_metrics(&font_data);
+        assert!(close(fallback.w1y, -(metrics.ascent - metrics.descent) * scale));
+        assert!(close(fallback.vy, metrics.ascent * scale));
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let mut out = HarfRustVerticalMetrics::default();
+            assert_eq!(harfrust_font_vertical_metrics(std::ptr::null(), &1, 1, &mut out), -1);
+            assert_eq!(harfrust_font_vertical_metrics(font, std::ptr::null(), 1, &mut out), HarfRustStatus::NullArgument as i32);
+            assert_eq!(harfrust_font_vertical_metrics(font, std::ptr::null(), 0, std::ptr::null_mut()), 0);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_result_cache() {
+        let _settings = GLOBAL_SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            // Returns the glyph IDs and whether the result came from the cache
+            let shape = |text: &CStr, wght: Option<f32>| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                let tag = u32::from_be_bytes(*b"wght");
+                let variations: Vec<HarfRustVariation> =
+                    wght.into_iter().map(|value| HarfRustVariation { tag, value }).collect();
+                let result = harfrust_shape_full(
+                    font,
+                    buffer,
+                    std::ptr::null(),
+                    0,
+                    variations.as_ptr(),
+                    variations.len() as u32,
+                );
+                let len = harfrust_glyph_buffer_len(result) as usize;
+                let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(result), len);
+                let glyphs: Vec<u32> = infos.iter().map(|info| info.glyph_id).collect();
+                let hit = object(result).inner.is_none();
+                harfrust_glyph_buffer_free(result);
+                (glyphs, hit)
+            };
+
+            let (glyphs, hit) = shape(c"cached", None);
+            assert!(!hit);
+            assert_eq!(shape(c"cached", None), (glyphs.clone(), false));
+
+            // Repeated runs hit once the cache is enabled
+            assert_eq!(harfrust_cache_configure(10_000, 1 << 24), HarfRustStatus::Ok);
+            assert_eq!(shape(c"cached", None), (glyphs.clone(), false));
+            assert_eq!(shape(c"cached", None), (glyphs.clone(), true));
+            assert!(!shape(c"cached", Some(700.0)).1);
+            assert!(shape(c"cached", Some(700.0)).1);
+
+            let (mut entries, mut bytes) = (0u32, 0u64);
+            harfrust_cache_stats(&mut entries, &mut bytes);
+            assert!(entries >= 2 && bytes > 0);
+
+            // Least recently used entries are evicted beyond the limit
+            harfrust_cache_configure(1, 1 << 24);
+            shape(c"first", None);
+            shape(c"second", None);
+            assert!(!shape(c"first", None).1);
+            harfrust_cache_stats(&mut entries, std::ptr::null_mut());
+            assert!(entries <= 1);
+
+            // Disabling drops everything
+            harfrust_cache_configure(0, 0);
+            harfrust_cache_stats(&mut entries, &mut bytes);
+            assert_eq!((entries, bytes), (0, 0));
+            assert!(!shape(c"first", None).1);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_buffer_to_identity_h() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"Hi!".as_ptr());
+            let result = harfrust_shape(font, buffer);
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(result), 3);
+            let expected: Vec<u8> = infos.iter().flat_map(|info| (info.glyph_id as u16).to_be_bytes()).collect();
+
+            assert_eq!(harfrust_glyph_buffer_to_identity_h(result, std::ptr::null_mut(), 0), 6);
+            let mut bytes = [0xFFu8; 6];
+            assert_eq!(harfrust_glyph_buffer_to_identity_h(result, bytes.as_mut_ptr(), 6), 6);
+            assert_eq!(bytes.to_vec(), expected);
+
+            // Only whole glyphs fit in a short array
+            let mut short = [0xFFu8; 5];
+            assert_eq!(harfrust_glyph_buffer_to_identity_h(result, short.as_mut_ptr(), 5), 6);
+            assert_eq!(short[..4], expected[..4]);
+            assert_eq!(short[4], 0xFF);
+
+            (&mut object(result).infos_cache)[0].glyph_id = 0x10000;
+            assert_eq!(harfrust_glyph_buffer_to_identity_h(result, bytes.as_mut_ptr(), 6), -3);
+            assert_eq!(harfrust_glyph_buffer_to_identity_h(std::ptr::null(), bytes.as_mut_ptr(), 6), -1);
+
+            harfrust_glyph_buffer_free(result);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_repair() {
+        use skrifa::FontRef;
+
+        let font_data = test_font_data();
+        let font_ref = FontRef::new(&font_data).unwrap();
+        let records = font_ref.table_directory.table_records();
+        let head_index = records.iter().position(|r| r.tag() == Tag::new(b"head")).unwrap();
+
+        // Unknown sfnt version, a head length past the end of the data and
+        // a wrong checksum
+        let mut damaged = font_data.clone();
+        damaged[..4].copy_from_slice(&0xDEAD_BEEFu32.to_be_bytes());
+        let head_record = 12 + head_index * 16;
+        damaged[head_record + 4..head_record + 8].copy_from_slice(&[0; 4]);
+        damaged[head_record + 12..head_record + 16].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(FontRef::new(&damaged).is_err());
+
+        unsafe {
+            let blob = harfrust_font_repair(damaged.as_ptr(), damaged.len() as i32);
+            assert!(!blob.is_null());
+            let repaired = std::slice::from_raw_parts(harfrust_blob_get_data(blob), harfrust_blob_len(blob) as usize);
+            let repaired_ref = FontRef::new(repaired).unwrap();
+            assert_eq!(repaired_ref.table_directory.sfnt_version(), 0x0001_0000);
+            assert!(repaired_ref.head().is_ok());
+            for record in repaired_ref.table_directory.table_records() {
+                let table = repaired_ref.table_data(record.tag()).unwrap();
+                let sum = table.as_bytes().chunks(4).fold(0u32, |sum, chunk| {
+                    let mut word = [0u8; 4];
+                    word[..chunk.len()].copy_from_slice(chunk);
+                    sum.wrapping_add(u32::from_be_bytes(word))
+                });
+                if record.tag() != Tag::new(b"head") {
+                    assert_eq!(record.checksum(), sum, "{}", record.tag());
+                }
+            }
+            harfrust_blob_free(blob);
+
+            // Loading repairs the data automatically
+            let font = harfrust_font_from_data(damaged.as_ptr(), damaged.len() as i32);
+            assert!(!font.is_null());
+            assert_eq!(harfrust_font_units_per_em(font), 2048);
+            let original = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert_eq!(harfrust_font_glyph_advance(font, 36), harfrust_font_glyph_advance(original, 36));
+            harfrust_font_free(original);
+            harfrust_font_free(font);
+
+            assert!(harfrust_font_repair(b"junk".as_ptr(), 4).is_null());
+            assert!(harfrust_font_repair(b"ttcf\0\x01\0\0\0\0\0\x01".as_ptr(), 12).is_null());
+            assert!(harfrust_font_repair(std::ptr::null(), 0).is_null());
+        }
+    }
+
+    #[test]
+    fn test_font_data_dedup() {
+        let font_data = test_font_data();
+        let copy = font_data.clone();
+        let (other_data, _) = test_variable_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let same = harfrust_font_from_data(copy.as_ptr(), copy.len() as i32);
+            let other = harfrust_font_from_data(other_data.as_ptr(), other_data.len() as i32);
+            assert!(Arc::ptr_eq(&object(font).inner, &object(same).inner));
+            assert!(!Arc::ptr_eq(&object(font).inner, &object(other).inner));
+
+            let hash = harfrust_font_data_hash(font);
+            assert_ne!(hash, 0);
+            assert_eq!(harfrust_font_data_hash(same), hash);
+            assert_ne!(harfrust_font_data_hash(other), hash);
+            assert_eq!(hash, content_hash(&font_data));
+
+            // Borrowed fonts hash their data on demand
+            let borrowed =
+                harfrust_font_from_data_borrowed(font_data.as_ptr(), font_data.len() as i32, None, std::ptr::null_mut());
+            assert_eq!(harfrust_font_data_hash(borrowed), hash);
+            assert_eq!(harfrust_font_data_hash(std::ptr::null()), 0);
+
+            harfrust_font_free(font);
+            // The shared data outlives the first font
+            assert_eq!(harfrust_font_units_per_em(same), 2048);
+            assert!(harfrust_font_glyph_advance(same, 36) > 0);
+            harfrust_font_free(same);
+            harfrust_font_free(other);
+            harfrust_font_free(borrowed);
+        }
+    }
+
+    #[test]
+    fn test_shape_batch_parallel() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let texts: Vec<Vec<u16>> = (0..37)
+                .map(|i| format!("Run {i} of the batch").encode_utf16().collect())
+                .collect();
+            let mut runs: Vec<HarfRustRunDescriptor> = texts
+                .iter()
+                .map(|text| HarfRustRunDescriptor {
+                    text: text.as_ptr(),
+                    len: text.len() as i32,
+                    direction: HarfRustDirection::Invalid,
+                    script: 0,
+                    language: std::ptr::null(),
+                    features: std::ptr::null(),
+                    num_features: 0,
+                    normalize_spaces: 0,
+                })
+                .collect();
+            runs[20].text = std::ptr::null();
+
+            let mut expected = vec![std::ptr::null_mut(); runs.len()];
+            assert_eq!(harfrust_shape_batch(font, runs.as_ptr(), 37, expected.as_mut_ptr()), 36);
+
+            // Results match the sequential batch, in order, for any thread count
+            for num_threads in [0, 1, 4, 64] {
+                let mut out = vec![std::ptr::null_mut(); runs.len()];
+                assert_eq!(harfrust_shape_batch_parallel(font, runs.as_ptr(), 37, out.as_mut_ptr(), num_threads), 36);
+                for (buffer, expected) in out.iter().zip(&expected) {
+                    if expected.is_null() {
+                        assert!(buffer.is_null());
+                        continue;
+                    }
+                    let glyphs = |b: *mut HarfRustGlyphBuffer| -> Vec<(u32, u32, i32)> {
+                        object(b).infos_cache.iter().zip(&object(b).positions_cache)
+                            .map(|(i, p)| (i.glyph_id, i.cluster, p.x_advance))
+                            .collect()
+                    };
+                    assert_eq!(glyphs(*buffer), glyphs(*expected));
+                }
+                for buffer in out {
+                    harfrust_glyph_buffer_free(buffer);
+                }
+            }
+
+            // Thread counts are clamped to the available cores
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+            assert_eq!(batch_threads(u32::MAX), cores);
+            assert_eq!(batch_threads(1), 1);
+
+            let mut out = vec![std::ptr::null_mut(); runs.len()];
+            assert_eq!(harfrust_shape_batch_parallel(font, runs.as_ptr(), 0, out.as_mut_ptr(), 0), 0);
+            assert_eq!(harfrust_shape_batch_parallel(std::ptr::null(), runs.as_ptr(), 37, out.as_mut_ptr(), 0), -1);
+            assert_eq!(harfrust_shape_batch_parallel(font, runs.as_ptr(), 37, std::ptr::null_mut(), 0), HarfRustStatus::NullArgument as i32);
+
+            for buffer in expected {
+                harfrust_glyph_buffer_free(buffer);
+            }
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_tolerant_mode() {
+        let _settings = GLOBAL_SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+        unsafe extern "C" fn collect(level: HarfRustLogLevel, message: *const c_char, user_data: *mut c_void) {
+            let messages = unsafe { &*(user_data as *const Mutex<Vec<(HarfRustLogLevel, String)>>) };
+            let message = unsafe { CStr::from_ptr(message) }.to_string_lossy().into_owned();
+            messages.lock().unwrap().push((level, message));
+        }
+
+        let font_data = test_font_data();
+        let font_ref = skrifa::FontRef::new(&font_data).unwrap();
+        let records = font_ref.table_directory.table_records();
+        let gpos_index = records.iter().position(|r| r.tag() == Tag::new(b"GPOS")).unwrap();
+
+        // Truncate GPOS to two bytes so it no longer parses, which also
+        // invalidates its checksum
+        let mut damaged = font_data.clone();
+        let gpos_record = 12 + gpos_index * 16;
+        damaged[gpos_record + 12..gpos_record + 16].copy_from_slice(&2u32.to_be_bytes());
+        let (ptr, len) = (damaged.as_ptr(), damaged.len() as i32);
+
+        let messages: Mutex<Vec<(HarfRustLogLevel, String)>> = Mutex::new(Vec::new());
+        unsafe {
+            let eager = harfrust_font_from_data_with_mode(ptr, len, 0, HarfRustParseMode::Eager);
+            assert!(eager.is_null());
+
+            harfrust_set_log_callback(Some(collect), &messages as *const _ as *mut c_void);
+            let font = harfrust_font_from_data_with_mode(ptr, len, 0, HarfRustParseMode::Tolerant);
+            harfrust_set_log_callback(None, std::ptr::null_mut());
+            assert!(!font.is_null());
+            assert!(object(font).validate());
+            assert!(object(font).font_ref.data_for_tag(Tag::new(b"GPOS")).is_none());
+            assert!(object(font).font_ref.data_for_tag(Tag::new(b"GSUB")).is_some());
+            harfrust_font_free(font);
+
+            let messages = messages.lock().unwrap();
+            let warnings: Vec<&str> = messages
+                .iter()
+                .map(|(level, message)| {
+                    assert_eq!(*level, HarfRustLogLevel::Warning);
+                    message.as_str()
+                })
+                .collect();
+            assert!(warnings.contains(&"'GPOS' table checksum mismatch"), "{warnings:?}");
+            assert!(warnings.contains(&"unreadable 'GPOS' table dropped"), "{warnings:?}");
+
+            // Undamaged fonts load without warnings
+            let clean = harfrust_font_from_data_with_mode(font_data.as_ptr(), font_data.len() as i32, 0, HarfRustParseMode::Tolerant);
+            assert!(!clean.is_null());
+            harfrust_font_free(clean);
+        }
+        assert!(repair::diagnose(&font_data).is_empty());
+    }
+
+    #[test]
+    fn test_shape_fallback() {
+        let font_data = test_font_data();
+        let font_ref = skrifa::FontRef::new(&font_data).unwrap();
+        let records = font_ref.table_directory.table_records();
+        let gsub_index = records.iter().position(|r| r.tag() == Tag::new(b"GSUB")).unwrap();
+
+        // A GSUB too short to parse
+        let mut damaged = font_data.clone();
+        let gsub_record = 12 + gsub_index * 16;
+        damaged[gsub_record + 12..gsub_record + 16].copy_from_slice(&2u32.to_be_bytes());
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let broken = harfrust_font_from_data(damaged.as_ptr(), damaged.len() as i32);
+            assert_eq!(harfrust_font_is_degraded(font), 0);
+            assert_eq!(harfrust_font_is_degraded(broken), 1);
+            assert_eq!(harfrust_font_is_degraded(std::ptr::null()), -1);
+
+            let text = CString::new("AVa").unwrap();
+            let shape = |font, fallback: bool, direction| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                harfrust_buffer_set_direction(buffer, direction);
+                if fallback { harfrust_shape_fallback(font, buffer) } else { harfrust_shape(font, buffer) }
+            };
+
+            // Each character gets its nominal glyph and hmtx advance
+            let expected = shape(font, true, HarfRustDirection::LeftToRight);
+            assert_eq!(harfrust_glyph_buffer_is_degraded(expected), 1);
+            assert_eq!(harfrust_glyph_buffer_len(expected), 3);
+            let expected_ref = object(expected);
+            for (i, ch) in "AVa".chars().enumerate() {
+                let info = expected_ref.infos_cache[i];
+                let glyph = object(font).font_ref.charmap().map(ch).unwrap().to_u32();
+                assert_eq!(info.glyph_id, glyph);
+                assert_eq!(info.cluster, i as u32);
+                assert_eq!(expected_ref.positions_cache[i].x_advance, harfrust_font_glyph_advance(font, glyph));
+            }
+
+            // Damaged fonts switch to fallback shaping automatically
+            let result = shape(broken, false, HarfRustDirection::LeftToRight);
+            assert_eq!(harfrust_glyph_buffer_is_degraded(result), 1);
+            let result_ref = object(result);
+            for i in 0..3 {
+                assert_eq!(result_ref.infos_cache[i].glyph_id, expected_ref.infos_cache[i].glyph_id);
+                assert_eq!(result_ref.positions_cache[i].x_advance, expected_ref.positions_cache[i].x_advance);
+            }
+            harfrust_glyph_buffer_free(result);
+
+            let result = shape(font, false, HarfRustDirection::LeftToRight);
+            assert_eq!(harfrust_glyph_buffer_is_degraded(result), 0);
+            harfrust_glyph_buffer_free(result);
+
+            // So does every other shaping function
+            let glyphs = |b: &HarfRustGlyphBuffer| b.infos_cache.iter().map(|i| i.glyph_id).collect::<Vec<_>>();
+            let new_buffer = || {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                buffer
+            };
+            let source = new_buffer();
+            let shaper = harfrust_shaper_new(broken, std::ptr::null(), 0);
+            let instance = harfrust_instance_new(broken, std::ptr::null(), 0);
+            let utf16: Vec<u16> = "AVa".encode_utf16().collect();
+            let results = [
+                harfrust_shape_copy(broken, source, std::ptr::null(), 0),
+                harfrust_shape_run_utf16(broken, utf16.as_ptr(), 3, HarfRustDirection::LeftToRight, 0, std::ptr::null(), std::ptr::null(), 0, 0),
+                harfrust_shaper_shape(shaper, new_buffer(), std::ptr::null(), 0),
+                harfrust_shape_with_instance(broken, instance, new_buffer(), std::ptr::null(), 0),
+                harfrust_shape_small_caps(broken, new_buffer(), 0.8),
+                harfrust_shape_script_position(broken, new_buffer(), HarfRustScriptPosition::Superscript),
+            ];
+            for result in results {
+                assert_eq!(harfrust_glyph_buffer_is_degraded(result), 1);
+                harfrust_glyph_buffer_free(result);
+            }
+            let result = harfrust_shape_copy(broken, source, std::ptr::null(), 0);
+            assert_eq!(glyphs(object(result)), glyphs(expected_ref));
+            harfrust_glyph_buffer_free(result);
+
+            let mut infos = [HarfRustGlyphInfo::default(); 3];
+            let mut positions = [HarfRustGlyphPosition::default(); 3];
+            let (mut written, mut flags) = (0, 0);
+            let status = harfrust_shape_into(broken, source, std::ptr::null(), 0, infos.as_mut_ptr(), positions.as_mut_ptr(), 3, &mut written, &mut flags);
+            assert_eq!(status, HarfRustStatus::Ok);
+            assert_eq!(flags, HarfRustResultFlags::Degraded as i32);
+            assert_eq!(infos.map(|i| i.glyph_id).to_vec(), glyphs(expected_ref));
+            harfrust_instance_free(instance);
+            harfrust_shaper_free(shaper);
+            harfrust_buffer_free(source);
+
+            // Batches fall back too, in parallel or not
+            let utf16: Vec<u16> = "AVa".encode_utf16().collect();
+            let run = HarfRustRunDescriptor {
+                text: utf16.as_ptr(),
+                len: 3,
+                direction: HarfRustDirection::LeftToRight,
+                script: 0,
+                language: std::ptr::null(),
+                features: std::ptr::null(),
+                num_features: 0,
+                normalize_spaces: 0,
+            };
+            let runs = [run; 2];
+            for parallel in [false, true] {
+                let mut out = [std::ptr::null_mut(); 2];
+                let shaped = if parallel {
+                    harfrust_shape_batch_parallel(broken, runs.as_ptr(), 2, out.as_mut_ptr(), 2)
+                } else {
+                    harfrust_shape_batch(broken, runs.as_ptr(), 2, out.as_mut_ptr())
+                };
+                assert_eq!(shaped, 2);
+                for result in out {
+                    assert_eq!(harfrust_glyph_buffer_is_degraded(result), 1);
+                    assert_eq!(glyphs(object(result)), glyphs(expected_ref));
+                    harfrust_glyph_buffer_free(result);
+                }
+            }
+
+            // Right-to-left output is in visual order
+            let result = shape(font, true, HarfRustDirection::RightToLeft);
+            let clusters: Vec<u32> = object(result).infos_cache.iter().map(|info| info.cluster).collect();
+            assert_eq!(clusters, [2, 1, 0]);
+            harfrust_glyph_buffer_free(result);
+
+            assert!(harfrust_shape_fallback(font, std::ptr::null_mut()).is_null());
+            assert_eq!(harfrust_glyph_buffer_is_degraded(std::ptr::null()), -1);
+            harfrust_glyph_buffer_free(expected);
+            harfrust_font_free(broken);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_shape_into() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            let text = CString::new("Hello, world").unwrap();
+            harfrust_buffer_add_str(buffer, text.as_ptr());
+
+            let mut infos = vec![HarfRustGlyphInfo::default(); 4];
+            let mut positions = vec![HarfRustGlyphPosition::default(); 4];
+            let mut written = 0;
+            let status = harfrust_shape_into(font, buffer, std::ptr::null(), 0, infos.as_mut_ptr(), positions.as_mut_ptr(), 4, &mut written, std::ptr::null_mut());
+            assert_eq!(status, HarfRustStatus::InsufficientCapacity);
+            assert_eq!(written, 12);
+            assert_eq!(infos[0].glyph_id, 0);
+
+            // The buffer is not consumed, so it can be shaped again
+            let mut infos = vec![HarfRustGlyphInfo::default(); written as usize];
+            let mut positions = vec![HarfRustGlyphPosition::default(); written as usize];
+            let mut flags = -1;
+            let status = harfrust_shape_into(font, buffer, std::ptr::null(), 0, infos.as_mut_ptr(), positions.as_mut_ptr(), written, &mut written, &mut flags);
+            assert_eq!(status, HarfRustStatus::Ok);
+            assert_eq!(written, 12);
+            assert_eq!(flags, 0);
+
+            let expected = harfrust_shape_copy(font, buffer, std::ptr::null(), 0);
+            let expected_ref = object(expected);
+            for i in 0..12 {
+                assert_eq!(infos[i].glyph_id, expected_ref.infos_cache[i].glyph_id);
+                assert_eq!(infos[i].cluster, expected_ref.infos_cache[i].cluster);
+                assert_eq!(positions[i].x_advance, expected_ref.positions_cache[i].x_advance);
+            }
+            harfrust_glyph_buffer_free(expected);
+
+            let null_infos = std::ptr::null_mut();
+            assert_eq!(harfrust_shape_into(font, buffer, std::ptr::null(), 0, null_infos, positions.as_mut_ptr(), 12, &mut written, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_shape_into(std::ptr::null(), buffer, std::ptr::null(), 0, infos.as_mut_ptr(), positions.as_mut_ptr(), 12, &mut written, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+
+            harfrust_buffer_free(buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_64_bit_lengths() {
+        let font_data = test_font_data();
+        let text: Vec<u16> = "Hello".encode_utf16().collect();
+
+        unsafe {
+            let font = harfrust_font_from_data64(font_data.as_ptr(), font_data.len() as u64);
+            assert!(!font.is_null());
+            assert_eq!(harfrust_font_units_per_em(font), 2048);
+            // Lengths no font file can have are rejected before reading the data
+            assert!(harfrust_font_from_data64(font_data.as_ptr(), u32::MAX as u64 + 1).is_null());
+            assert!(harfrust_font_from_data64(font_data.as_ptr(), 0).is_null());
+
+            let buffer = harfrust_buffer_new();
+            assert_eq!(harfrust_buffer_add_utf16_64(buffer, text.as_ptr(), text.len() as u64), HarfRustStatus::Ok);
+            assert_eq!(harfrust_buffer_len(buffer), 5);
+            assert_eq!(harfrust_buffer_add_utf16_64(buffer, text.as_ptr(), u64::MAX), HarfRustStatus::OutOfRange);
+            assert_eq!(harfrust_buffer_add_utf16_64(buffer, std::ptr::null(), 0), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_buffer_add_utf16_64(std::ptr::null_mut(), text.as_ptr(), 5), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_buffer_add_utf16(buffer, text.as_ptr(), -1), HarfRustStatus::InvalidArgument);
+            assert_eq!(harfrust_buffer_len(buffer), 5);
+
+            let result = harfrust_shape(font, buffer);
+            assert_eq!(harfrust_glyph_buffer_len(result), 5);
+            harfrust_glyph_buffer_free(result);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_buffer_reuse() {
+        let font_data = test_font_data();
+        let text: Vec<u16> = "office".encode_utf16().collect();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let shape = |configure: &dyn Fn(*mut HarfRustBuffer)| {
+                let buffer = harfrust_buffer_new();
+                configure(buffer);
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                let result = harfrust_shape(font, buffer);
+                let glyphs: Vec<u32> = object(result).infos_cache.iter().map(|info| info.glyph_id).collect();
+                harfrust_glyph_buffer_free(result);
+                glyphs
+            };
+
+            // Buffers released with non-default properties come back clean
+            let expected = shape(&|_| {});
+            let rtl = shape(&|buffer| {
+                harfrust_buffer_set_direction(buffer, HarfRustDirection::RightToLeft);
+                harfrust_buffer_set_script(buffer, u32::from_be_bytes(*b"Arab"));
+            });
+            assert_ne!(rtl, expected);
+            for _ in 0..8 {
+                assert_eq!(shape(&|_| {}), expected);
+            }
+
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_set_direction(buffer, HarfRustDirection::TopToBottom);
+            harfrust_buffer_free(buffer);
+            let buffer = harfrust_buffer_new();
+            assert_eq!(harfrust_buffer_get_direction(buffer), HarfRustDirection::Invalid);
+            assert_eq!(harfrust_buffer_len(buffer), 0);
+            harfrust_buffer_free(buffer);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_buffer_add_utf8() {
+        unsafe {
+            let buffer = harfrust_buffer_new();
+            // Not NUL-terminated, with an embedded NUL
+            let text = "a\0é€!";
+            let bytes = text.as_bytes();
+            assert_eq!(harfrust_buffer_add_utf8(buffer, bytes.as_ptr(), 7), HarfRustStatus::Ok);
+            let added: Vec<(char, u32)> = object(buffer).text.clone();
+            assert_eq!(added, [('a', 0), ('\0', 1), ('é', 2), ('€', 4)]);
+
+            // Invalid UTF-8, here a sequence cut short, adds nothing
+            assert_eq!(harfrust_buffer_add_utf8(buffer, bytes.as_ptr(), 6), HarfRustStatus::InvalidUtf8);
+            assert_eq!(harfrust_buffer_len(buffer), 4);
+            assert_eq!(harfrust_buffer_add_utf8(buffer, std::ptr::null(), 0), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_buffer_add_utf8(buffer, bytes.as_ptr(), -1), HarfRustStatus::InvalidArgument);
+            assert_eq!(harfrust_buffer_add_utf8(std::ptr::null_mut(), bytes.as_ptr(), 1), HarfRustStatus::NullArgument);
+            harfrust_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn test_small_run_fast_path() {
+        let font_data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let simple_glyphs = fastpath::SimpleGlyphs::new(&object(font).font_ref);
+            let chars = |s: &str| s.chars().enumerate().map(|(i, c)| (c, i as u32)).collect::<Vec<_>>();
+
+            // Kerned pairs, marks and long runs go through the full pipeline
+            assert!(simple_glyphs.shape(&chars("12.50")).is_some());
+            assert!(simple_glyphs.shape(&chars("AV")).is_none());
+            assert!(simple_glyphs.shape(&chars("e\u{301}")).is_none());
+            assert!(simple_glyphs.shape(&chars("123456789")).is_none());
+
+            let shape = |text: &str| {
+                let text = CString::new(text).unwrap();
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                harfrust_shape(font, buffer)
+            };
+            for text in ["12.50", "Page 3", "AV", "(a+b)"] {
+                fastpath::set_enabled(false);
+                let expected = shape(text);
+                fastpath::set_enabled(true);
+                let result = shape(text);
+
+                let (expected_ref, result_ref) = (object(expected), object(result));
+                assert_eq!(result_ref.infos_cache.len(), expected_ref.infos_cache.len());
+                for (a, b) in result_ref.infos_cache.iter().zip(&expected_ref.infos_cache) {
+                    assert_eq!((a.glyph_id, a.cluster), (b.glyph_id, b.cluster), "{text}");
+                }
+                for (a, b) in result_ref.positions_cache.iter().zip(&expected_ref.positions_cache) {
+                    let a = (a.x_advance, a.y_advance, a.x_offset, a.y_offset);
+                    let b = (b.x_advance, b.y_advance, b.x_offset, b.y_offset);
+                    assert_eq!(a, b, "{text}");
+                }
+                harfrust_glyph_buffer_free(expected);
+                harfrust_glyph_buffer_free(result);
+            }
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_cache_key() {
+        let (font_data, l) = test_variable_font_data();
+        let wght = |value| [HarfRustVariation { tag: u32::from_be_bytes(*b"wght"), value }];
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let copy = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let key = |font, glyph, variations: &[HarfRustVariation], ppem| {
+                let mut out = HarfRustCacheKey::default();
+                let count = variations.len() as u32;
+                assert_eq!(harfrust_glyph_cache_key(font, glyph, variations.as_ptr(), count, ppem, &mut out), HarfRustStatus::Ok);
+                out
+            };
+
+            // Equal for every way of naming the same instance, in any font with the same data
+            let default = key(font, l, &[], 12.0);
+            assert_eq!(key(font, l, &wght(400.0), 12.0), default);
+            assert_eq!(key(font, l, &wght(100.0), 12.0), default);
+            assert_eq!(key(copy, l, &[], 12.0), default);
+            assert_eq!(key(font, l, &wght(900.0), 12.0), key(font, l, &wght(700.0), 12.0));
+
+            assert_ne!(key(font, l, &wght(700.0), 12.0), default);
+            assert_ne!(key(font, l + 1, &[], 12.0), default);
+            assert_ne!(key(font, l, &[], 12.5), default);
+
+            let mut out = HarfRustCacheKey::default();
+            assert_eq!(harfrust_glyph_cache_key(std::ptr::null(), l, std::ptr::null(), 0, 12.0, &mut out), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_glyph_cache_key(font, l, std::ptr::null(), 0, 12.0, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_glyph_cache_key(font, l, std::ptr::null(), 1, 12.0, &mut out), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_glyph_cache_key(font, l, std::ptr::null(), 0, f32::NAN, &mut out), HarfRustStatus::OutOfRange);
+
+            harfrust_font_free(font);
+            harfrust_font_free(copy);
+        }
+    }
+
+    #[test]
+    fn test_last_error() {
+        let last_message = || unsafe {
+            let message = harfrust_last_error_message();
+            assert!(!message.is_null());
+            let text = CStr::from_ptr(message).to_str().unwrap().to_string();
+            harfrust_string_free(message);
+            text
+        };
+
+        unsafe {
+            harfrust_clear_last_error();
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::Ok);
+            assert!(harfrust_last_error_message().is_null());
+
+            let garbage = [0u8; 64];
+            assert!(harfrust_font_from_data(garbage.as_ptr(), garbage.len() as i32).is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::InvalidFont);
+            assert!(last_message().starts_with("font data cannot be parsed"));
+
+            let buffer = harfrust_buffer_new();
+            assert_eq!(harfrust_buffer_set_language(buffer, c"".as_ptr()), HarfRustStatus::InvalidLanguage);
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::InvalidLanguage);
+            assert_eq!(last_message(), "invalid language tag ''");
+
+            let invalid = [b'a', 0xFF];
+            assert_eq!(harfrust_buffer_add_utf8(buffer, invalid.as_ptr(), 2), HarfRustStatus::InvalidUtf8);
+            assert!(last_message().starts_with("text is not valid UTF-8"));
+
+            // Successful calls keep the record; errors are per thread
+            assert_eq!(harfrust_buffer_add_str(buffer, c"ok".as_ptr()), HarfRustStatus::Ok);
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::InvalidUtf8);
+            std::thread::spawn(|| assert_eq!(harfrust_last_error_code(), HarfRustStatus::Ok)).join().unwrap();
+
+            assert!(harfrust_font_from_file(c"/nonexistent/font.ttf".as_ptr(), 0).is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::Io);
+            assert!(last_message().starts_with("cannot open '/nonexistent/font.ttf'"));
+
+            // Argument checks record their failure too
+            let font_data = test_font_data();
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let text: Vec<u16> = "ab".encode_utf16().collect();
+            let (ptr, len) = (text.as_ptr(), text.len() as i32);
+            let out = std::ptr::null_mut();
+            let status = harfrust_font_get_script_metrics(font, HarfRustScriptPosition::Superscript, out);
+            assert_eq!(status, HarfRustStatus::NullArgument);
+            assert_eq!(last_message(), "out is null");
+            assert_eq!(harfrust_text_fits(font, ptr, -1, 1000), HarfRustStatus::InvalidArgument as i32);
+            assert_eq!(last_message(), "len is negative");
+            assert_eq!(harfrust_font_supports_sequence(font, std::ptr::null(), len), HarfRustStatus::NullArgument as i32);
+            assert_eq!(last_message(), "text is null");
+            let glyph_count = harfrust_font_glyph_count(font) as u32;
+            let outline = harfrust_font_glyph_outline(font, glyph_count, 0.0, std::ptr::null_mut(), 0);
+            assert_eq!(outline, HarfRustStatus::OutOfRange as i32);
+            assert_eq!(last_message(), format!("glyph {glyph_count} is out of range"));
+            let mut feature = HarfRustFeature { tag: 0, value: 1, start: 0, end: 0 };
+            let status = harfrust_feature_set_range(&mut feature, ptr, len, 1, 3, HarfRustClusterUnit::Utf16);
+            assert_eq!(status, HarfRustStatus::OutOfRange);
+            assert_eq!(last_message(), "character range 1..3 is outside the text");
+            harfrust_font_free(font);
+
+            harfrust_clear_last_error();
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::Ok);
+            harfrust_string_free(std::ptr::null_mut());
+            harfrust_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn test_run_key() {
+        let (font_data, _) = test_variable_font_data();
+        let text: Vec<u16> = "Hello".encode_utf16().collect();
+        let other_text: Vec<u16> = "Hellp".encode_utf16().collect();
+        let liga = [HarfRustFeature { tag: u32::from_be_bytes(*b"liga"), value: 0, start: 0, end: u32::MAX }];
+        let bold = [HarfRustVariation { tag: u32::from_be_bytes(*b"wght"), value: 700.0 }];
+        let run = |text: &[u16]| HarfRustRunDescriptor {
+            text: text.as_ptr(),
+            len: text.len() as i32,
+            direction: HarfRustDirection::Invalid,
+            script: 0,
+            language: std::ptr::null(),
+            features: std::ptr::null(),
+            num_features: 0,
+            normalize_spaces: 0,
+        };
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let copy = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let key = |font, run: HarfRustRunDescriptor, variations: &[HarfRustVariation]| {
+                let mut out = HarfRustCacheKey::default();
+                let count = variations.len() as u32;
+                assert_eq!(harfrust_run_key(font, &run, variations.as_ptr(), count, &mut out), HarfRustStatus::Ok);
+                out
+            };
+
+            // Guessed properties key like the same properties given explicitly
+            let guessed = key(font, run(&text), &[]);
+            let explicit = HarfRustRunDescriptor {
+                direction: HarfRustDirection::LeftToRight,
+                script: u32::from_be_bytes(*b"Latn"),
+                ..run(&text)
+            };
+            assert_eq!(key(font, explicit, &[]), guessed);
+            assert_eq!(key(copy, run(&text), &[]), guessed);
+
+            assert_ne!(key(font, run(&other_text), &[]), guessed);
+            assert_ne!(key(font, run(&text[..4]), &[]), guessed);
+            assert_ne!(key(font, run(&text), &bold), guessed);
+            let rtl = HarfRustRunDescriptor { direction: HarfRustDirection::RightToLeft, ..run(&text) };
+            assert_ne!(key(font, rtl, &[]), guessed);
+            let german = HarfRustRunDescriptor { language: c"de".as_ptr(), ..run(&text) };
+            assert_ne!(key(font, german, &[]), guessed);
+            let no_ligatures = HarfRustRunDescriptor { features: liga.as_ptr(), num_features: 1, ..run(&text) };
+            assert_ne!(key(font, no_ligatures, &[]), guessed);
+
+            let mut out = HarfRustCacheKey::default();
+            assert_eq!(harfrust_run_key(std::ptr::null(), &run(&text), std::ptr::null(), 0, &mut out), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_run_key(font, std::ptr::null(), std::ptr::null(), 0, &mut out), HarfRustStatus::NullArgument);
+            let invalid = HarfRustRunDescriptor { len: -1, ..run(&text) };
+            assert_eq!(harfrust_run_key(font, &invalid, std::ptr::null(), 0, &mut out), HarfRustStatus::InvalidArgument);
+
+            harfrust_font_free(font);
+            harfrust_font_free(copy);
+        }
+    }
+
+    #[test]
+    fn test_panic_guard() {
+        unsafe {
+            harfrust_clear_last_error();
+            let font = error::guard(|| -> *mut HarfRustFont { panic!("boom") });
+            assert!(font.is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::Panic);
+            let message = harfrust_last_error_message();
+            assert!(CStr::from_ptr(message).to_str().unwrap().starts_with("internal panic: boom"));
+            harfrust_string_free(message);
+
+            let count = error::guard(|| -> i32 { panic!("{}", 42) });
+            assert_eq!(count, HarfRustStatus::Panic as i32);
+            assert_eq!(error::guard(|| 7), 7);
+            harfrust_clear_last_error();
+        }
+    }
+
+    #[test]
+    fn test_unicode_version_and_scripts() {
+        unsafe {
+            let (mut major, mut minor) = (0u32, u32::MAX);
+            harfrust_unicode_version(&mut major, &mut minor, std::ptr::null_mut());
+            assert!(major >= 15);
+            assert_eq!(minor, 0);
+
+            let total = harfrust_supported_scripts(std::ptr::null_mut(), 0);
+            assert!(total > 100);
+            let mut tags = vec![0u32; total as usize];
+            assert_eq!(harfrust_supported_scripts(tags.as_mut_ptr(), total as u32), total);
+            for tag in [b"Arab", b"Deva", b"Hang", b"Khmr", b"Tibt"] {
+                assert!(tags.contains(&u32::from_be_bytes(*tag)));
+            }
+            assert!(!tags.contains(&u32::from_be_bytes(*b"Latn")));
+
+            let mut first = [0u32; 2];
+            assert_eq!(harfrust_supported_scripts(first.as_mut_ptr(), 2), total);
+            assert_eq!(first, [tags[0], tags[1]]);
+        }
+    }
+
+    #[test]
+    fn test_handle_type_tags() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"abc".as_ptr());
+
+            // A buffer passed as a font is rejected instead of read as one
+            let wrong = buffer as *const HarfRustFont;
+            assert_eq!(harfrust_font_units_per_em(wrong), HarfRustStatus::InvalidHandle as i32);
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::InvalidHandle);
+            let message = harfrust_last_error_message();
+            assert_eq!(CStr::from_ptr(message).to_str().unwrap(), "expected a font handle but got a buffer");
+            harfrust_string_free(message);
+
+            let swapped = harfrust_shape(buffer as *const HarfRustFont, font as *mut HarfRustBuffer);
+            assert!(swapped.is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::InvalidHandle);
+
+            // Freeing through the wrong function is refused too
+            harfrust_font_free(buffer as *mut HarfRustFont);
+            assert_eq!(harfrust_buffer_len(buffer), 3);
+
+            let result = harfrust_shape(font, buffer);
+            assert!(!result.is_null());
+            harfrust_glyph_buffer_free(result);
+            harfrust_font_free(font);
+            harfrust_clear_last_error();
+        }
+    }
+
+    #[test]
+    fn test_feature_overrides() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let liga = |value| HarfRustFeature { tag: u32::from_be_bytes(*b"liga"), value, start: 0, end: u32::MAX };
+            let shape = |features: &[HarfRustFeature]| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, c"fi".as_ptr());
+                let result = harfrust_shape_with_features(font, buffer, features.as_ptr(), features.len() as u32);
+                let len = harfrust_glyph_buffer_len(result);
+                harfrust_glyph_buffer_free(result);
+                len
+            };
+            assert_eq!(shape(&[]), 1);
+
+            let overrides = [liga(0)];
+            assert_eq!(harfrust_font_set_feature_overrides(font, overrides.as_ptr(), 1), HarfRustStatus::Ok);
+            assert_eq!(shape(&[]), 2);
+            // Features passed to the call take precedence
+            assert_eq!(shape(&[liga(1)]), 1);
+
+            let mut out = [liga(7); 2];
+            assert_eq!(harfrust_font_get_feature_overrides(font, out.as_mut_ptr(), 2), 1);
+            assert_eq!((out[0].tag, out[0].value), (overrides[0].tag, 0));
+
+            assert_eq!(harfrust_font_set_feature_overrides(font, std::ptr::null(), 0), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_get_feature_overrides(font, std::ptr::null_mut(), 0), 0);
+            assert_eq!(shape(&[]), 1);
+            assert_eq!(
+                harfrust_font_set_feature_overrides(font, std::ptr::null(), 1),
+                HarfRustStatus::NullArgument
+            );
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_handle_table() {
+        let mut table = handle::HandleTable::new();
+        let font = table.insert(1, 0x1000).unwrap();
+        let buffer = table.insert(2, 0x2000).unwrap();
+        assert!(font != 0 && buffer != font);
+        assert_eq!(table.get(font), Some((1, 0x1000)));
+        assert_eq!(table.get(buffer), Some((2, 0x2000)));
+
+        // A freed slot is reused under a new generation, so the old handle
+        // stays stale rather than reaching the new object
+        table.remove(0x1000);
+        assert_eq!(table.get(font), None);
+        let reused = table.insert(1, 0x3000).unwrap();
+        assert_ne!(reused, font);
+        assert_eq!(table.get(font), None);
+        assert_eq!(table.get(reused), Some((1, 0x3000)));
+
+        // Removing twice is harmless; unknown handles resolve to nothing
+        table.remove(0x1000);
+        assert_eq!(table.get(reused), Some((1, 0x3000)));
+        assert_eq!(table.get(12345), None);
+    }
+
+    #[test]
+    fn test_font_script_support() {
+        let font_data = test_font_data();
+
+        // Same font with GSUB hidden from the table directory (renamed in
+        // place so the directory stays sorted)
+        let mut no_gsub = font_data.clone();
+        let num_tables = u16::from_be_bytes([no_gsub[4], no_gsub[5]]) as usize;
+        for i in 0..num_tables {
+            let record = 12 + i * 16;
+            if &no_gsub[record..record + 4] == b"GSUB" {
+                no_gsub[record..record + 4].copy_from_slice(b"GSUX");
+            }
+        }
+
+        let tag = |tag: &[u8; 4]| u32::from_be_bytes(*tag);
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let cmap_only = harfrust_font_from_data(no_gsub.as_ptr(), no_gsub.len() as i32);
+            assert!(!font.is_null() && !cmap_only.is_null());
+
+            let support = |font, script| {
+                let mut out = HarfRustScriptSupport::Unsupported;
+                assert_eq!(harfrust_font_script_support(font, script, &mut out), HarfRustStatus::Ok);
+                out
+            };
+            assert_eq!(support(font, tag(b"Latn")), HarfRustScriptSupport::Full);
+            assert_eq!(support(font, tag(b"arab")), HarfRustScriptSupport::Full);
+            assert_eq!(support(font, tag(b"Deva")), HarfRustScriptSupport::Unsupported);
+
+            // Latin needs no lookups; Arabic without them cannot join
+            assert_eq!(support(cmap_only, tag(b"Latn")), HarfRustScriptSupport::Full);
+            assert_eq!(support(cmap_only, tag(b"Arab")), HarfRustScriptSupport::CmapOnly);
+
+            let mut out = HarfRustScriptSupport::Unsupported;
+            assert_eq!(harfrust_font_script_support(font, tag(b"12ab"), &mut out), HarfRustStatus::InvalidArgument);
+            assert_eq!(harfrust_font_script_support(font, tag(b"Latn"), std::ptr::null_mut()), HarfRustStatus::NullArgument);
+
+            harfrust_font_free(font);
+            harfrust_font_free(cmap_only);
+        }
+    }
+
+    #[test]
+    fn test_render_color_glyph_with_overrides() {
+        let (font_data, glyph) = test_color_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let render = |overrides: &[HarfRustColorOverride]| {
+                let bitmap = harfrust_font_render_color_glyph_with_overrides(
+                    font,
+                    glyph,
+                    64.0,
+                    1,
+                    overrides.as_ptr(),
+                    overrides.len() as u32,
+                );
+                assert!(!bitmap.is_null());
+                let mut info = HarfRustBitmapInfo::default();
+                assert_eq!(harfrust_color_bitmap_get_info(bitmap, &mut info), HarfRustStatus::Ok);
+                let pixels = std::slice::from_raw_parts(
+                    harfrust_color_bitmap_get_pixels(bitmap),
+                    (info.stride * info.height) as usize,
+                )
+                .to_vec();
+                harfrust_color_bitmap_free(bitmap);
+                pixels
+            };
+            let has = |pixels: &[u8], color: [u8; 4]| pixels.chunks_exact(4).any(|p| p == color);
+
+            // The foreground defaults to black
+            let pixels = render(&[]);
+            assert!(has(&pixels, [0, 0, 255, 255]) && has(&pixels, [0, 0, 0, 255]));
+
+            // Dark theme: green instead of palette 1's blue, white foreground;
+            // entries the palette lacks are ignored
+            let color = |index, red, green, blue| HarfRustColorOverride { index, red, green, blue, alpha: 255 };
+            let pixels = render(&[color(0, 0, 255, 0), color(0xFFFF, 255, 255, 255), color(7, 255, 0, 0)]);
+            assert!(has(&pixels, [0, 255, 0, 255]) && has(&pixels, [255, 255, 255, 255]));
+            assert!(!has(&pixels, [0, 0, 255, 255]) && !has(&pixels, [0, 0, 0, 255]));
+
+            assert!(harfrust_font_render_color_glyph_with_overrides(font, glyph, 64.0, 0, std::ptr::null(), 1).is_null());
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::NullArgument);
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_retain() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+            let upem = harfrust_font_units_per_em(font);
+
+            // Each owner releases its own reference from its own thread
+            assert_eq!(harfrust_font_retain(font), font);
+            assert_eq!(harfrust_font_retain(font), font);
+            let address = font as usize;
+            std::thread::spawn(move || harfrust_font_free(address as *mut HarfRustFont)).join().unwrap();
+            harfrust_font_free(font);
+            assert_eq!(harfrust_font_units_per_em(font), upem);
+
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"fi".as_ptr());
+            harfrust_buffer_guess_segment_properties(buffer);
+            let result = harfrust_shape(font, buffer);
+            assert!(!result.is_null());
+            harfrust_glyph_buffer_free(result);
+            harfrust_font_free(font);
+
+            assert!(harfrust_font_retain(std::ptr::null_mut()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_init_and_shutdown() {
+        unsafe extern "C" fn count(_level: HarfRustLogLevel, _message: *const c_char, user_data: *mut c_void) {
+            unsafe { &*(user_data as *const AtomicUsize) }.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let _settings = GLOBAL_SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+        let messages = AtomicUsize::new(0);
+        unsafe {
+            let config = HarfRustConfig {
+                result_cache_max_entries: 100,
+                result_cache_max_bytes: 1 << 20,
+                plan_cache_capacity: 2,
+                num_threads: 3,
+                check_handles: 1,
+                log_level: HarfRustLogLevel::Error,
+                ..Default::default()
+            };
+            assert_eq!(harfrust_init(&config), HarfRustStatus::Ok);
+            assert!(cache::enabled());
+            assert_eq!(PLAN_CACHE_LIMIT.load(Ordering::Relaxed), 2);
+            assert_eq!(DEFAULT_THREADS.load(Ordering::Relaxed), 3);
+
+            // Messages below the configured level are dropped
+            harfrust_set_log_callback(Some(count), &messages as *const _ as *mut c_void);
+            log(HarfRustLogLevel::Warning, "dropped");
+            log(HarfRustLogLevel::Error, "passed");
+            assert_eq!(messages.load(Ordering::Relaxed), 1);
+
+            // A caller built against a smaller struct leaves the rest default
+            let old = HarfRustConfig { size: 8, ..config };
+            assert_eq!(harfrust_init(&old), HarfRustStatus::Ok);
+            assert!(!cache::enabled());
+            assert_eq!(PLAN_CACHE_LIMIT.load(Ordering::Relaxed), PLAN_CACHE_CAPACITY);
+            let too_small = HarfRustConfig { size: 0, ..config };
+            assert_eq!(harfrust_init(&too_small), HarfRustStatus::InvalidArgument);
+
+            assert_eq!(harfrust_init(&config), HarfRustStatus::Ok);
+            harfrust_shutdown();
+            assert!(!cache::enabled());
+            assert_eq!(PLAN_CACHE_LIMIT.load(Ordering::Relaxed), PLAN_CACHE_CAPACITY);
+            assert_eq!(DEFAULT_THREADS.load(Ordering::Relaxed), 0);
+            assert!(LOGGER.lock().unwrap().is_none());
+            log(HarfRustLogLevel::Error, "no callback");
+            assert_eq!(messages.load(Ordering::Relaxed), 1);
+        }
+    }
+
+    #[test]
+    fn test_font_glyph_extents_batch() {
+        let font_data = test_font_data();
+        let font_ref = skrifa::FontRef::new(&font_data).unwrap();
+        let charmap = font_ref.charmap();
+        let glyph = |ch| charmap.map(ch).unwrap().to_u32();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let glyphs = [glyph('A'), glyph('g'), glyph(' '), 0xFFFF];
+            let mut out = [HarfRustGlyphExtents { x_bearing: 1, ..Default::default() }; 4];
+            assert_eq!(
+                harfrust_font_glyph_extents_batch(font, glyphs.as_ptr(), 4, out.as_mut_ptr()),
+                HarfRustStatus::Ok
+            );
+
+            // Matches the glyf bounding box
+            let bounds = font_ref.glyph_metrics(Size::unscaled(), LocationRef::default()).bounds(GlyphId::new(glyphs[0])).unwrap();
+            assert_eq!(out[0].x_bearing, bounds.x_min as i32);
+            assert_eq!(out[0].y_bearing, bounds.y_max as i32);
+            assert_eq!(out[0].width, (bounds.x_max - bounds.x_min) as i32);
+            assert_eq!(out[0].height, (bounds.y_min - bounds.y_max) as i32);
+
+            // 'g' descends below the baseline; spaces and unknown glyphs are empty
+            assert!(out[1].y_bearing + out[1].height < 0);
+            assert_eq!(out[2], HarfRustGlyphExtents::default());
+            assert_eq!(out[3], HarfRustGlyphExtents::default());
+
+            assert_eq!(harfrust_font_glyph_extents_batch(font, std::ptr::null(), 0, std::ptr::null_mut()), HarfRustStatus::Ok);
+            assert_eq!(
+                harfrust_font_glyph_extents_batch(font, glyphs.as_ptr(), 4, std::ptr::null_mut()),
+                HarfRustStatus::NullArgument
+            );
+
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_cluster_order() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+
+            let orders = |text: &std::ffi::CStr| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                harfrust_buffer_guess_segment_properties(buffer);
+                let result = harfrust_shape(font, buffer);
+                assert!(!result.is_null());
+
+                let count = harfrust_glyph_buffer_get_cluster_order(result, std::ptr::null_mut(), std::ptr::null_mut(), 0);
+                let mut visual_to_logical = vec![u32::MAX; count as usize];
+                let mut logical_to_visual = vec![u32::MAX; count as usize];
+                assert_eq!(
+                    harfrust_glyph_buffer_get_cluster_order(
+                        result,
+                        visual_to_logical.as_mut_ptr(),
+                        logical_to_visual.as_mut_ptr(),
+                        count as u32
+                    ),
+                    count
+                );
+
+                // Visual indices match the cluster advance entries
+                let mut advances = vec![HarfRustClusterAdvance::default(); count as usize];
+                assert_eq!(harfrust_glyph_buffer_get_cluster_advances(result, advances.as_mut_ptr(), count as u32), count);
+                for (logical, &visual) in logical_to_visual.iter().enumerate() {
+                    assert_eq!(visual_to_logical[visual as usize], logical as u32);
+                }
+                let clusters: Vec<u32> = logical_to_visual.iter().map(|&v| advances[v as usize].cluster).collect();
+                assert!(clusters.is_sorted());
+
+                harfrust_glyph_buffer_free(result);
+                visual_to_logical
+            };
+
+            // Left to right, with the "fi" ligature as one cluster
+            assert_eq!(orders(c"fig"), [0, 1]);
+            // Right to left runs come out in reverse
+            assert_eq!(orders(c"\u{5d0}\u{5d1}\u{5d2}"), [2, 1, 0]);
+
+            assert_eq!(
+                harfrust_glyph_buffer_get_cluster_order(std::ptr::null(), std::ptr::null_mut(), std::ptr::null_mut(), 0),
+                HarfRustStatus::NullArgument as i32
+            );
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_memory_stats() {
+        // Trailing bytes make the data unique, so it is not shared with fonts
+        // of other tests
+        let mut font_data = test_font_data();
+        font_data.extend_from_slice(b"test_memory_stats");
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert!(!font.is_null());
+            harfrust_font_glyph_advance(font, 1);
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"stats".as_ptr());
+            harfrust_buffer_guess_segment_properties(buffer);
+            let result = harfrust_shape(font, buffer);
+
+            // Other tests run concurrently, so only lower bounds hold
+            let mut stats = HarfRustMemoryStats::default();
+            assert_eq!(harfrust_memory_stats(&mut stats), HarfRustStatus::Ok);
+            assert!(stats.font_data_bytes >= font_data.len() as u64);
+            assert!(stats.advance_cache_bytes >= 4 * object(font).advance_cache.num_glyphs as u64);
+            assert!(stats.shape_plans >= 1);
+            assert!(stats.fonts >= 1 && stats.glyph_buffers >= 1);
+
+            harfrust_glyph_buffer_free(result);
+            harfrust_font_free(font);
+            assert_eq!(harfrust_memory_stats(std::ptr::null_mut()), HarfRustStatus::NullArgument);
+        }
+    }
+
+    #[test]
+    fn test_set_allocator() {
+        unsafe extern "C" fn alloc(size: usize, align: usize, _: *mut c_void) -> *mut c_void {
+            unsafe { std::alloc::alloc(std::alloc::Layout::from_size_align_unchecked(size, align)).cast() }
+        }
+        unsafe extern "C" fn free(ptr: *mut c_void, size: usize, align: usize, _: *mut c_void) {
+            unsafe { std::alloc::dealloc(ptr.cast(), std::alloc::Layout::from_size_align_unchecked(size, align)) }
+        }
+
+        unsafe {
+            assert_eq!(
+                harfrust_set_allocator(None, Some(free), None, std::ptr::null_mut()),
+                HarfRustStatus::NullArgument
+            );
+            // The test harness has allocated long before, so the callbacks are
+            // refused with or without the feature
+            assert_eq!(
+                harfrust_set_allocator(Some(alloc), Some(free), None, std::ptr::null_mut()),
+                HarfRustStatus::Unsupported
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_extract_text() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let shape = |text: &[u16]| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                harfrust_buffer_guess_segment_properties(buffer);
+                let result = harfrust_shape(font, buffer);
+                harfrust_buffer_free(buffer);
+                result
+            };
+            let extract = |runs: &[HarfRustLineRun], direction| {
+                let count = harfrust_line_extract_text(runs.as_ptr(), runs.len() as u32, direction, std::ptr::null_mut(), 0);
+                let mut chars = vec![HarfRustExtractedChar::default(); count as usize];
+                assert_eq!(
+                    harfrust_line_extract_text(runs.as_ptr(), runs.len() as u32, direction, chars.as_mut_ptr(), count as u32),
+                    count
+                );
+                chars
+            };
+            let text = |chars: &[HarfRustExtractedChar]| {
+                chars.iter().map(|c| char::from_u32(c.codepoint).unwrap()).collect::<String>()
+            };
+
+            let latin: Vec<u16> = "fig ".encode_utf16().collect();
+            let hebrew: Vec<u16> = "\u{5d0}\u{5d1}".encode_utf16().collect();
+            let latin_glyphs = shape(&latin);
+            let hebrew_glyphs = shape(&hebrew);
+            let run = |glyphs, text: &[u16], x| HarfRustLineRun {
+                glyphs,
+                text: text.as_ptr(),
+                len: text.len() as i32,
+                x,
+                scale: 0.01,
+            };
+
+            // Latin then Hebrew in a left-to-right line
+            let runs = [run(latin_glyphs, &latin, 0.0), run(hebrew_glyphs, &hebrew, 50.0)];
+            let chars = extract(&runs, HarfRustDirection::Invalid);
+            assert_eq!(text(&chars), "fig \u{5d0}\u{5d1}");
+            // The "fi" ligature's box is split between its characters
+            assert_eq!(chars[0].x, 0.0);
+            assert!(chars[1].x > 0.0 && chars[1].x + chars[1].width <= chars[2].x + 0.001);
+            // Hebrew reads from the right
+            assert_eq!((chars[4].run, chars[4].offset), (1, 0));
+            assert!(chars[4].x > chars[5].x && chars[5].x >= 50.0);
+
+            // A right-to-left line reads from its rightmost run
+            assert_eq!(text(&extract(&runs, HarfRustDirection::RightToLeft)), "\u{5d0}\u{5d1}fig ");
+            let runs = [run(hebrew_glyphs, &hebrew, 0.0), run(latin_glyphs, &latin, 50.0)];
+            assert_eq!(text(&extract(&runs, HarfRustDirection::RightToLeft)), "fig \u{5d0}\u{5d1}");
+            assert_eq!(text(&extract(&runs, HarfRustDirection::LeftToRight)), "\u{5d0}\u{5d1}fig ");
+
+            let runs = [run(std::ptr::null(), &latin, 0.0)];
+            assert_eq!(
+                harfrust_line_extract_text(runs.as_ptr(), 1, HarfRustDirection::Invalid, std::ptr::null_mut(), 0),
+                HarfRustStatus::NullArgument as i32
+            );
+            assert_eq!(
+                harfrust_line_extract_text(runs.as_ptr(), 1, HarfRustDirection::TopToBottom, std::ptr::null_mut(), 0),
+                HarfRustStatus::InvalidArgument as i32
+            );
+
+            harfrust_glyph_buffer_free(latin_glyphs);
+            harfrust_glyph_buffer_free(hebrew_glyphs);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_buffer_context() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let beh: Vec<u16> = "\u{628}".encode_utf16().collect();
+            let zwj: Vec<u16> = "\u{200d}".encode_utf16().collect();
+            let shape = |pre: &[u16], post: &[u16], clear: bool| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_utf16(buffer, beh.as_ptr(), beh.len() as i32);
+                assert_eq!(
+                    harfrust_buffer_set_context(buffer, pre.as_ptr(), pre.len() as i32, post.as_ptr(), post.len() as i32),
+                    HarfRustStatus::Ok
+                );
+                if clear {
+                    harfrust_buffer_clear(buffer);
+                    harfrust_buffer_add_utf16(buffer, beh.as_ptr(), beh.len() as i32);
+                }
+                harfrust_buffer_guess_segment_properties(buffer);
+                let result = harfrust_shape(font, buffer);
+                let glyph = (*harfrust_glyph_buffer_get_infos(result)).glyph_id;
+                harfrust_glyph_buffer_free(result);
+                glyph
+            };
+
+            // A beh between two others takes its medial form
+            let isolated = shape(&[], &[], false);
+            let medial = shape(&beh, &beh, false);
+            assert_ne!(medial, isolated);
+            assert_ne!(shape(&beh, &[], false), isolated);
+            // A joiner stands in for the neighbouring letters
+            assert_eq!(shape(&zwj, &zwj, false), medial);
+            assert_eq!(shape(&beh, &beh, true), isolated);
+
+            let buffer = harfrust_buffer_new();
+            assert_eq!(
+                harfrust_buffer_set_context(buffer, beh.as_ptr(), -1, std::ptr::null(), 0),
+                HarfRustStatus::InvalidArgument
+            );
+            assert_eq!(
+                harfrust_buffer_set_context(buffer, std::ptr::null(), 1, std::ptr::null(), 0),
+                HarfRustStatus::NullArgument
+            );
+            harfrust_buffer_free(buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_version() {
+        let (mut major, mut minor, mut patch) = (u32::MAX, u32::MAX, u32::MAX);
+        unsafe { harfrust_version(&mut major, &mut minor, &mut patch) };
+        assert_eq!(format!("{major}.{minor}.{patch}"), env!("CARGO_PKG_VERSION"));
+        unsafe { harfrust_version(std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut()) };
+
+        let engine = unsafe { CStr::from_ptr(harfrust_engine_version_string()) }.to_str().unwrap();
+        let parts: Vec<&str> = engine.split('.').collect();
+        assert!(parts.len() == 3 && parts.iter().all(|part| part.parse::<u32>().is_ok()), "{engine}");
+    }
+
+    #[test]
+    fn test_substitution_trace() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let trace = |text: &[u16]| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                harfrust_buffer_guess_segment_properties(buffer);
+                let glyphs = harfrust_shape(font, buffer);
+                harfrust_buffer_free(buffer);
+                let len = text.len() as i32;
+                let count = harfrust_glyph_buffer_get_substitution_trace(font, glyphs, text.as_ptr(), len, std::ptr::null_mut(), 0);
+                let mut out = Vec::with_capacity(count as usize);
+                assert_eq!(
+                    harfrust_glyph_buffer_get_substitution_trace(font, glyphs, text.as_ptr(), len, out.as_mut_ptr(), count as u32),
+                    count
+                );
+                out.set_len(count as usize);
+                harfrust_glyph_buffer_free(glyphs);
+                out
+            };
+            let tag = |s: &[u8; 4]| u32::from_be_bytes(*s);
+
+            // "fi" ligature plus a nominal space
+            let latin: Vec<u16> = "fi ".encode_utf16().collect();
+            let traces = trace(&latin);
+            assert_eq!(traces.len(), 2);
+            assert_eq!(traces[0].kind, HarfRustSubstitutionKind::Ligature);
+            assert_eq!(traces[0].feature, tag(b"liga"));
+            assert!(traces[0].lookup_index >= 0);
+            assert_eq!((traces[0].input_start, traces[0].input_length), (0, 2));
+            assert_eq!(traces[1].kind, HarfRustSubstitutionKind::None);
+            assert_eq!((traces[1].lookup_index, traces[1].alternate_index), (-1, -1));
+            assert_eq!((traces[1].input_start, traces[1].input_length), (2, 1));
+
+            // Arabic joining forms come from single substitutions
+            let arabic: Vec<u16> = "\u{628}\u{628}".encode_utf16().collect();
+            let traces = trace(&arabic);
+            assert_eq!(traces.len(), 2);
+            let mut features: Vec<u32> = traces.iter().map(|t| t.feature).collect();
+            features.sort_unstable();
+            assert_eq!(features, [tag(b"fina"), tag(b"init")]);
+            assert!(traces.iter().all(|t| t.kind == HarfRustSubstitutionKind::Single && t.input_length == 1));
+
+            let null_out = std::ptr::null_mut();
+            let buffer = harfrust_buffer_new();
+            let glyphs = harfrust_shape(font, buffer);
+            assert_eq!(harfrust_glyph_buffer_get_substitution_trace(std::ptr::null(), glyphs, latin.as_ptr(), 3, null_out, 0), -1);
+            assert_eq!(harfrust_glyph_buffer_get_substitution_trace(font, glyphs, std::ptr::null(), 3, null_out, 0), -1);
+            assert_eq!(harfrust_glyph_buffer_get_substitution_trace(font, glyphs, latin.as_ptr(), -1, null_out, 0), -2);
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_buffer_free(buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_glyph_buffer_summary() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let summary = |text: &str| {
+                let buffer = harfrust_buffer_new();
+                let text: Vec<u16> = text.encode_utf16().collect();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                harfrust_buffer_guess_segment_properties(buffer);
+                let glyphs = harfrust_shape(font, buffer);
+                let mut summary = HarfRustShapeSummary::default();
+                assert_eq!(harfrust_glyph_buffer_get_summary(font, glyphs, &mut summary), HarfRustStatus::Ok);
+                harfrust_glyph_buffer_free(glyphs);
+                harfrust_buffer_free(buffer);
+                summary
+            };
+
+            let clean = summary("Hello");
+            assert_eq!((clean.glyph_count, clean.notdef_count, clean.dotted_circle_count), (5, 0, 0));
+            assert_eq!(clean.degraded, 0);
+            // A CJK ideograph DejaVu Sans lacks
+            assert_eq!(summary("a\u{4e00}b").notdef_count, 1);
+            // A Khmer vowel sign with no base gets a dotted circle; the font
+            // has no Khmer, so the sign itself is .notdef
+            let broken = summary("\u{17b6}");
+            assert_eq!((broken.glyph_count, broken.notdef_count, broken.dotted_circle_count), (2, 1, 1));
+
+            let mut out = HarfRustShapeSummary::default();
+            assert_eq!(harfrust_glyph_buffer_get_summary(font, std::ptr::null(), &mut out), HarfRustStatus::NullArgument);
+            let buffer = harfrust_buffer_new();
+            let glyphs = harfrust_shape(font, buffer);
+            assert_eq!(harfrust_glyph_buffer_get_summary(font, glyphs, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            harfrust_glyph_buffer_free(glyphs);
+            harfrust_buffer_free(buffer);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_freeze() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let overrides = [HarfRustFeature { tag: u32::from_be_bytes(*b"liga"), value: 0, start: 0, end: u32::MAX }];
+            assert_eq!(harfrust_font_is_frozen(font), 0);
+            assert_eq!(harfrust_font_set_feature_overrides(font, overrides.as_ptr(), 1), HarfRustStatus::Ok);
+
+            let shared = harfrust_font_retain(font);
+            assert_eq!(harfrust_font_freeze(font), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_freeze(font), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_is_frozen(shared), 1);
+            // Frozen through every handle, and the overrides set before stay
+            assert_eq!(harfrust_font_set_feature_overrides(shared, std::ptr::null(), 0), HarfRustStatus::Unsupported);
+            assert_eq!(harfrust_font_get_feature_overrides(font, std::ptr::null_mut(), 0), 1);
+
+            // Shaping from many threads at once
+            let address = font as usize;
+            let glyph_counts: Vec<i32> = (0..4)
+                .map(|_| {
+                    std::thread::spawn(move || {
+                        let text: Vec<u16> = "fi".encode_utf16().collect();
+                        let buffer = harfrust_buffer_new();
+                        harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                        harfrust_buffer_guess_segment_properties(buffer);
+                        let glyphs = harfrust_shape(address as *const HarfRustFont, buffer);
+                        let len = harfrust_glyph_buffer_len(glyphs);
+                        harfrust_glyph_buffer_free(glyphs);
+                        harfrust_buffer_free(buffer);
+                        len
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect();
+            // liga is off, so "fi" stays two glyphs
+            assert_eq!(glyph_counts, [2; 4]);
+
+            assert_eq!(harfrust_font_freeze(std::ptr::null()), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_is_frozen(std::ptr::null()), -1);
+            harfrust_font_free(shared);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_soft_hyphen() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            // (glyph, advance) of the glyph for the soft hyphen
+            let shape = |text: &str, mode: HarfRustSoftHyphen| {
+                let buffer = harfrust_buffer_new();
+                assert_eq!(harfrust_buffer_set_soft_hyphen(buffer, mode), HarfRustStatus::Ok);
+                let text: Vec<u16> = text.encode_utf16().collect();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                harfrust_buffer_guess_segment_properties(buffer);
+                let glyphs = harfrust_shape(font, buffer);
+                let len = harfrust_glyph_buffer_len(glyphs) as usize;
+                let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), len);
+                let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(glyphs), len);
+                let index = infos.iter().position(|info| info.cluster == 2).unwrap();
+                let glyph = (infos[index].glyph_id, positions[index].x_advance);
+                harfrust_glyph_buffer_free(glyphs);
+                glyph
+            };
+
+            let hyphen = shape("co-op", HarfRustSoftHyphen::Hidden).0;
+            assert_eq!(shape("co\u{AD}op", HarfRustSoftHyphen::Hidden).1, 0);
+            let (glyph, advance) = shape("co\u{AD}op", HarfRustSoftHyphen::Visible);
+            assert_eq!(glyph, hyphen);
+            assert!(advance > 0);
+            assert_eq!(shape("co\u{AD}", HarfRustSoftHyphen::LineEnd).0, hyphen);
+            assert_eq!(shape("co\u{AD}op", HarfRustSoftHyphen::LineEnd).1, 0);
+
+            // Changing the policy applies to text already added
+            let buffer = harfrust_buffer_new();
+            let text: Vec<u16> = "co\u{AD}op".encode_utf16().collect();
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            assert_eq!(harfrust_buffer_set_soft_hyphen(buffer, HarfRustSoftHyphen::Visible), HarfRustStatus::Ok);
+            assert_eq!(harfrust_buffer_get_soft_hyphen(buffer), HarfRustSoftHyphen::Visible);
+            harfrust_buffer_guess_segment_properties(buffer);
+            let glyphs = harfrust_shape(font, buffer);
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), 5);
+            assert_eq!((infos[2].glyph_id, infos[2].cluster), (hyphen, 2));
+            harfrust_glyph_buffer_free(glyphs);
+
+            let status = harfrust_buffer_set_soft_hyphen(std::ptr::null_mut(), HarfRustSoftHyphen::Visible);
+            assert_eq!(status, HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_buffer_get_soft_hyphen(std::ptr::null()), HarfRustSoftHyphen::Hidden);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_normalize_spaces() {
+        let data = test_cff_data();
+
+        unsafe {
+            // The font has 'A' and 'space' only
+            let font = harfrust_font_from_cff(data.as_ptr(), data.len() as i32);
+            let shape = |text: &str, normalize: i32, fallback: bool| {
+                let buffer = harfrust_buffer_new();
+                assert_eq!(harfrust_buffer_set_normalize_spaces(buffer, normalize), HarfRustStatus::Ok);
+                assert_eq!(harfrust_buffer_get_normalize_spaces(buffer), normalize);
+                let text: Vec<u16> = text.encode_utf16().collect();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                let glyphs = if fallback { harfrust_shape_fallback(font, buffer) } else { harfrust_shape(font, buffer) };
+                let len = harfrust_glyph_buffer_len(glyphs) as usize;
+                let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(glyphs), len);
+                let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(glyphs), len);
+                let space = (infos[1].glyph_id, positions[1].x_advance);
+                let flags = harfrust_glyph_buffer_get_flags(glyphs);
+                harfrust_glyph_buffer_free(glyphs);
+                (space, flags)
+            };
+
+            let space = shape("A A", 0, false).0;
+            let normalized = HarfRustResultFlags::SpacesNormalized as i32;
+            assert_eq!(shape("A A", 1, false), (space, 0));
+            assert_eq!(shape("A\u{A0}A", 1, false), (space, normalized));
+            assert_eq!(shape("A\u{2009}A", 1, false), (space, normalized));
+            // Without the option, the shaper's own fallback sizes thin spaces
+            // from the em, and fallback shaping finds no glyph
+            let (thin, flags) = shape("A\u{2009}A", 0, false);
+            assert_eq!((thin.0, flags), (space.0, 0));
+            assert_ne!(thin.1, space.1);
+            assert_eq!(shape("A\u{A0}A", 0, true).0 .0, 0);
+            let degraded = HarfRustResultFlags::Degraded as i32;
+            assert_eq!(shape("A\u{A0}A", 1, true), (space, normalized | degraded));
+
+            // Copies are normalized without changing the buffer
+            let text: Vec<u16> = "A\u{A0}A".encode_utf16().collect();
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_set_normalize_spaces(buffer, 1);
+            harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+            let copy = harfrust_shape_copy(font, buffer, std::ptr::null(), 0);
+            assert_eq!(harfrust_glyph_buffer_get_flags(copy), normalized);
+            assert_eq!(object(copy).infos_cache[1].glyph_id, space.0);
+            harfrust_glyph_buffer_free(copy);
+            assert_eq!(object(buffer).text[1].0, '\u{A0}');
+            let mut infos = [HarfRustGlyphInfo::default(); 3];
+            let mut positions = [HarfRustGlyphPosition::default(); 3];
+            let (mut written, mut flags) = (0, 0);
+            let status = harfrust_shape_into(font, buffer, std::ptr::null(), 0, infos.as_mut_ptr(), positions.as_mut_ptr(), 3, &mut written, &mut flags);
+            assert_eq!(status, HarfRustStatus::Ok);
+            assert_eq!((infos[1].glyph_id, positions[1].x_advance), space);
+            assert_eq!(flags, normalized);
+            harfrust_buffer_free(buffer);
+
+            // Runs opt in with a flag
+            for normalize in [0, 1] {
+                let glyphs = harfrust_shape_run_utf16(font, text.as_ptr(), 3, HarfRustDirection::Invalid, 0, std::ptr::null(), std::ptr::null(), 0, normalize);
+                assert_eq!(harfrust_glyph_buffer_get_flags(glyphs), normalize * normalized);
+                harfrust_glyph_buffer_free(glyphs);
+            }
+            let run = HarfRustRunDescriptor {
+                text: text.as_ptr(),
+                len: 3,
+                direction: HarfRustDirection::Invalid,
+                script: 0,
+                language: std::ptr::null(),
+                features: std::ptr::null(),
+                num_features: 0,
+                normalize_spaces: 1,
+            };
+            let runs = [run, HarfRustRunDescriptor { normalize_spaces: 0, ..run }];
+            for parallel in [false, true] {
+                let mut out = [std::ptr::null_mut(); 2];
+                if parallel {
+                    harfrust_shape_batch_parallel(font, runs.as_ptr(), 2, out.as_mut_ptr(), 2);
+                } else {
+                    harfrust_shape_batch(font, runs.as_ptr(), 2, out.as_mut_ptr());
+                }
+                assert_eq!(out.map(|glyphs| harfrust_glyph_buffer_get_flags(glyphs)), [normalized, 0]);
+                assert_eq!(object(out[0]).infos_cache[1].glyph_id, space.0);
+                out.into_iter().for_each(|glyphs| harfrust_glyph_buffer_free(glyphs));
+            }
+            let mut keys = [HarfRustCacheKey::default(); 2];
+            for (run, key) in runs.iter().zip(&mut keys) {
+                assert_eq!(harfrust_run_key(font, run, std::ptr::null(), 0, key), HarfRustStatus::Ok);
+            }
+            assert_ne!(keys[0], keys[1]);
+
+            assert_eq!(harfrust_buffer_set_normalize_spaces(std::ptr::null_mut(), 1), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_buffer_get_normalize_spaces(std::ptr::null()), HarfRustStatus::NullArgument as i32);
+            assert_eq!(harfrust_glyph_buffer_get_flags(std::ptr::null()), HarfRustStatus::NullArgument as i32);
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_metrics() {
+        let font_data = test_font_data();
+
+        unsafe {
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let mut metrics = HarfRustFontMetrics::default();
+            assert_eq!(harfrust_font_get_metrics(font, &mut metrics), HarfRustStatus::Ok);
+
+            let font_ref = &object(font).font_ref;
+            let hhea = font_ref.hhea().unwrap();
+            assert_eq!(metrics.units_per_em, harfrust_font_units_per_em(font));
+            assert!(metrics.ascent > 0 && metrics.descent < 0 && metrics.line_gap >= 0);
+            if !font_ref.os2().unwrap().fs_selection().contains(skrifa::raw::tables::os2::SelectionFlags::USE_TYPO_METRICS) {
+                assert_eq!(metrics.ascent, hhea.ascender().to_i16() as i32);
+                assert_eq!(metrics.descent, hhea.descender().to_i16() as i32);
+            }
+            let (mut x_height, mut cap_height) = (0, 0);
+            harfrust_font_get_x_height(font, &mut x_height, std::ptr::null_mut());
+            harfrust_font_get_cap_height(font, &mut cap_height, std::ptr::null_mut());
+            assert_eq!((metrics.x_height, metrics.cap_height), (x_height, cap_height));
+            assert!(0 < x_height && x_height < cap_height && cap_height < metrics.ascent);
+
+            assert_eq!(harfrust_font_get_metrics(std::ptr::null(), &mut metrics), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_get_metrics(font, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(last_error_message(), "out is null");
+            harfrust_font_free(font);
+        }
+    }
+
+    #[test]
+    fn test_font_style() {
+        let font_data = test_font_data();
+        let style = |data: &[u8]| unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let mut style = HarfRustFontStyle::default();
+            assert_eq!(harfrust_font_get_style(font, &mut style), HarfRustStatus::Ok);
+            harfrust_font_free(font);
+            style
+        };
+
+        let regular = style(&font_data);
+        let os2 = skrifa::FontRef::new(&font_data).unwrap().os2().unwrap();
+        assert_eq!((regular.weight_class, regular.width_class), (400, 5));
+        assert_eq!(regular.fs_selection, os2.fs_selection().bits() as u32);
+        assert_eq!(regular.fs_selection & 0x21, 0);
+        assert_eq!(regular.fs_type, os2.fs_type() as u32);
+
+        // Restricted license and no subsetting
+        let mut restricted = font_data.clone();
+        let os2_offset = os2.offset_data().as_bytes().as_ptr() as usize - font_data.as_ptr() as usize;
+        restricted[os2_offset + 8..os2_offset + 10].copy_from_slice(&0x0102u16.to_be_bytes());
+        let restricted = style(&restricted);
+        assert_eq!(restricted.fs_type, 0x0102);
+        assert_eq!((restricted.embeddable, restricted.subsettable), (0, 0));
+        let mut editable = font_data.clone();
+        editable[os2_offset + 8..os2_offset + 10].copy_from_slice(&0x0008u16.to_be_bytes());
+        let editable = style(&editable);
+        assert_eq!((editable.embeddable, editable.subsettable), (1, 1));
+
+        unsafe {
+            let mut out = HarfRustFontStyle::default();
+            assert_eq!(harfrust_font_get_style(std::ptr::null(), &mut out), HarfRustStatus::NullArgument);
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            assert_eq!(harfrust_font_get_style(font, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(last_error_message(), "out is null");
+            harfrust_font_free(font);
         }
     }
-    
+
     #[test]
-    fn test_buffer_script() {
+    fn test_font_post_info() {
+        let font_data = test_font_data();
+
         unsafe {
-            let buffer = harfrust_buffer_new();
-            
-            // Set Latin script (0x4C61746E = "Latn")
-            let latn_tag = u32::from_be_bytes(*b"Latn");
-            harfrust_buffer_set_script(buffer, latn_tag);
-            assert_eq!(harfrust_buffer_get_script(buffer), latn_tag);
-            
-            // Set Arabic script (0x41726162 = "Arab")
-            let arab_tag = u32::from_be_bytes(*b"Arab");
-            harfrust_buffer_set_script(buffer, arab_tag);
-            assert_eq!(harfrust_buffer_get_script(buffer), arab_tag);
-            
-            harfrust_buffer_free(buffer);
+            let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
+            let mut info = HarfRustPostInfo::default();
+            assert_eq!(harfrust_font_post_info(font, &mut info), HarfRustStatus::Ok);
+
+            let post = skrifa::FontRef::new(&font_data).unwrap().post().unwrap();
+            assert_eq!(info.italic_angle, post.italic_angle().to_f32());
+            assert_eq!(info.underline_position, post.underline_position().to_i16() as i32);
+            assert_eq!(info.underline_thickness, post.underline_thickness().to_i16() as i32);
+            // An upright proportional font with the underline below the baseline
+            assert_eq!((info.italic_angle, info.is_fixed_pitch), (0.0, 0));
+            assert!(info.underline_position < 0 && info.underline_thickness > 0);
+
+            assert_eq!(harfrust_font_post_info(std::ptr::null(), &mut info), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_post_info(font, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(last_error_message(), "out is null");
+            harfrust_font_free(font);
         }
     }
-    
+
+
     #[test]
-    fn test_buffer_language() {
+    fn test_advance_overrides() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let shape = || {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, c"a b".as_ptr());
+                let result = harfrust_shape(font, buffer);
+                let len = harfrust_glyph_buffer_len(result);
+                let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(result), len as usize).to_vec();
+                let positions = harfrust_glyph_buffer_get_positions(result);
+                let advances: Vec<i32> =
+                    std::slice::from_raw_parts(positions, len as usize).iter().map(|pos| pos.x_advance).collect();
+                harfrust_glyph_buffer_free(result);
+                (infos, advances)
+            };
+            let (infos, advances) = shape();
+            let space = infos[1].glyph_id;
+            let text: Vec<u16> = "a b".encode_utf16().collect();
+            let plain_key = run_key(font, &text);
+
+            let overrides = [HarfRustAdvanceOverride { glyph_id: space, advance: 1000 }];
+            assert_eq!(harfrust_font_set_advance_overrides(font, overrides.as_ptr(), 1), HarfRustStatus::Ok);
+            assert_eq!(shape().1, vec![advances[0], 1000, advances[2]]);
+            // Measuring and run keys see the overrides too
+            let width = advances[0] + 1000 + advances[2];
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 3, width), 1);
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 3, width - 1), 0);
+            assert_ne!(run_key(font, &text), plain_key);
+
+            let mut out = [HarfRustAdvanceOverride { glyph_id: 0, advance: 0 }; 2];
+            assert_eq!(harfrust_font_get_advance_overrides(font, out.as_mut_ptr(), 2), 1);
+            assert_eq!(out[0], overrides[0]);
+
+            assert_eq!(harfrust_font_set_advance_overrides(font, std::ptr::null(), 0), HarfRustStatus::Ok);
+            assert_eq!(shape().1, advances);
+            assert_eq!(run_key(font, &text), plain_key);
+            assert_eq!(
+                harfrust_font_set_advance_overrides(font, std::ptr::null(), 1),
+                HarfRustStatus::NullArgument
+            );
+            assert_eq!(harfrust_font_freeze(font), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_set_advance_overrides(font, overrides.as_ptr(), 1), HarfRustStatus::Unsupported);
+            harfrust_font_free(font);
+        }
+    }
+
+
+    #[test]
+    fn test_width_deltas() {
+        let data = test_font_data();
         unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
             let buffer = harfrust_buffer_new();
-            
-            // Set English language
-            let lang = CString::new("en").unwrap();
-            let result = harfrust_buffer_set_language(buffer, lang.as_ptr());
-            assert_eq!(result, 0);
-            
-            // Set more specific language tag
-            let lang = CString::new("en-US").unwrap();
-            let result = harfrust_buffer_set_language(buffer, lang.as_ptr());
-            assert_eq!(result, 0);
-            
-            harfrust_buffer_free(buffer);
+            harfrust_buffer_add_str(buffer, c"a b".as_ptr());
+            let result = harfrust_shape(font, buffer);
+            let infos = std::slice::from_raw_parts(harfrust_glyph_buffer_get_infos(result), 3);
+            let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(result), 3);
+            let scale = 1000.0 / object(font).units_per_em as f32;
+
+            // Declare 'a' as the font has it and the space 50 units wider;
+            // 'b' falls outside the array and gets the missing width
+            let (a, space) = (infos[0].glyph_id, infos[1].glyph_id);
+            assert!(space < a && infos[2].glyph_id > a);
+            let mut widths = vec![0.0; (a - space + 1) as usize];
+            widths[0] = positions[1].x_advance as f32 * scale + 50.0;
+            widths[(a - space) as usize] = positions[0].x_advance as f32 * scale;
+            let mut out = [f32::NAN; 3];
+            let count = harfrust_glyph_buffer_width_deltas(
+                result, font, space, widths.as_ptr(), widths.len() as u32, 500.0, out.as_mut_ptr(), 3,
+            );
+            assert_eq!(count, 3);
+            assert!(out[0].abs() < 1e-3 && (out[1] - 50.0).abs() < 1e-3);
+            assert!((out[2] - (500.0 - positions[2].x_advance as f32 * scale)).abs() < 1e-3);
+
+            assert_eq!(
+                harfrust_glyph_buffer_width_deltas(result, font, 0, std::ptr::null(), 0, 0.0, std::ptr::null_mut(), 0),
+                3
+            );
+            assert_eq!(
+                harfrust_glyph_buffer_width_deltas(result, font, 0, std::ptr::null(), 1, 0.0, out.as_mut_ptr(), 3),
+                -1
+            );
+            assert_eq!(
+                harfrust_glyph_buffer_width_deltas(result, std::ptr::null(), 0, widths.as_ptr(), 1, 0.0, out.as_mut_ptr(), 3),
+                -1
+            );
+            harfrust_glyph_buffer_free(result);
+            harfrust_font_free(font);
         }
     }
-    
+
+
     #[test]
-    fn test_explicit_direction_not_overridden() {
-        // Try to load a system font for testing
-        let font_paths = [
-            r"C:\Windows\Fonts\arial.ttf",
-            r"C:\Windows\Fonts\segoeui.ttf",
-            r"C:\Windows\Fonts\tahoma.ttf",
-        ];
-        
-        let font_data = font_paths.iter()
-            .find_map(|path| std::fs::read(path).ok())
-            .expect("No system font found for testing");
-        
+    fn test_font_strikeout() {
+        let font_data = test_font_data();
+
         unsafe {
             let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
-            let buffer = harfrust_buffer_new();
-            
-            // Set RTL direction explicitly
-            harfrust_buffer_set_direction(buffer, HarfRustDirection::RightToLeft);
-            
-            let text = CString::new("Hello").unwrap();
-            harfrust_buffer_add_str(buffer, text.as_ptr());
-            
-            // Shape should work (direction is set, won't call guess)
-            let glyph_buffer = harfrust_shape(font, buffer);
-            assert!(!glyph_buffer.is_null());
-            assert_eq!(harfrust_glyph_buffer_len(glyph_buffer), 5);
-            
-            harfrust_glyph_buffer_free(glyph_buffer);
+            let mut strikeout = HarfRustStrikeout::default();
+            assert_eq!(harfrust_font_get_strikeout(font, &mut strikeout), HarfRustStatus::Ok);
+            let os2 = skrifa::FontRef::new(&font_data).unwrap().os2().unwrap();
+            assert_eq!(strikeout.position, os2.y_strikeout_position() as i32);
+            assert_eq!(strikeout.thickness, os2.y_strikeout_size() as i32);
+            assert!(strikeout.position > 0 && strikeout.thickness > 0);
+
+            assert_eq!(harfrust_font_get_strikeout(std::ptr::null(), &mut strikeout), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_get_strikeout(font, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(last_error_message(), "out is null");
             harfrust_font_free(font);
         }
     }
 
+
     #[test]
-    fn test_shape_with_features() {
-        // Try to load a system font for testing
-        let font_paths = [
-            r"C:\Windows\Fonts\calibri.ttf",
-            r"C:\Windows\Fonts\arial.ttf",
-        ];
-        
-        let font_data = font_paths.iter()
-            .find_map(|path| std::fs::read(path).ok())
-            .expect("No system font found for testing");
-            
+    fn test_font_head_info() {
+        let font_data = test_font_data();
+
         unsafe {
             let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
-            let buffer = harfrust_buffer_new();
-            
-            let text = CString::new("fi").unwrap();
-            harfrust_buffer_add_str(buffer, text.as_ptr());
-            
-            // Disable ligatures: liga = 0
-            let features = [
-                HarfRustFeature {
-                    tag: u32::from_be_bytes(*b"liga"),
-                    value: 0,
-                    start: 0,
-                    end: u32::MAX,
-                }
-            ];
-            
-            let glyph_buffer = harfrust_shape_with_features(
-                font,
-                buffer,
-                features.as_ptr(),
-                features.len() as u32
+            let mut info = HarfRustHeadInfo::default();
+            assert_eq!(harfrust_font_head_info(font, &mut info), HarfRustStatus::Ok);
+
+            let head = skrifa::FontRef::new(&font_data).unwrap().head().unwrap();
+            assert_eq!(
+                (info.x_min, info.y_min, info.x_max, info.y_max),
+                (head.x_min() as i32, head.y_min() as i32, head.x_max() as i32, head.y_max() as i32)
             );
-            
-            assert!(!glyph_buffer.is_null());
-            
-            // Just verify it produced something
-            let len = harfrust_glyph_buffer_len(glyph_buffer);
-            assert!(len > 0);
-            
-            harfrust_glyph_buffer_free(glyph_buffer);
+            assert!(info.x_min < 0 && info.y_min < 0 && info.x_max > 0 && info.y_max > 0);
+            // DejaVu Sans is upright and regular, with its baseline at y=0
+            assert_eq!(info.mac_style, 0);
+            assert_eq!(info.flags & 1, 1);
+
+            assert_eq!(harfrust_font_head_info(std::ptr::null(), &mut info), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_head_info(font, std::ptr::null_mut()), HarfRustStatus::NullArgument);
+            assert_eq!(last_error_message(), "out is null");
+            harfrust_font_free(font);
+        }
+    }
+
+
+    #[test]
+    fn test_horizontal_scale() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let text: Vec<u16> = "a b".encode_utf16().collect();
+            let advances = || {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, c"a b".as_ptr());
+                let result = harfrust_shape(font, buffer);
+                let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(result), 3);
+                let advances: Vec<i32> = positions.iter().map(|pos| pos.x_advance).collect();
+                harfrust_glyph_buffer_free(result);
+                advances
+            };
+            let unscaled = advances();
+            let width: i32 = unscaled.iter().sum();
+            let unscaled_key = run_key(font, &text);
+            assert_eq!(harfrust_font_get_horizontal_scale(font), 1.0);
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 3, width * 3 / 4), 0);
+
+            assert_eq!(harfrust_font_set_horizontal_scale(font, 0.5), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_get_horizontal_scale(font), 0.5);
+            let expected: Vec<i32> = unscaled.iter().map(|&advance| (advance as f32 * 0.5).round() as i32).collect();
+            assert_eq!(advances(), expected);
+            // Measurement and run keys see the condensed text
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 3, width * 3 / 4), 1);
+            assert_ne!(run_key(font, &text), unscaled_key);
+            let mut layout = HarfRustLineLayout::default();
+            let result = harfrust_shape_field_line(
+                font, text.as_ptr(), 3, 200.0, 20.0, 2.0, 10.0, HarfRustTextAlign::Left, &mut layout,
+            );
+            let scaled: i32 = expected.iter().sum();
+            assert!((layout.width - scaled as f32 * 10.0 / object(font).units_per_em as f32).abs() < 1e-3);
+            harfrust_glyph_buffer_free(result);
+
+            for invalid in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+                assert_eq!(harfrust_font_set_horizontal_scale(font, invalid), HarfRustStatus::OutOfRange);
+            }
+            assert_eq!(harfrust_font_set_horizontal_scale(font, 1.0), HarfRustStatus::Ok);
+            assert_eq!(advances(), unscaled);
+            assert_eq!(run_key(font, &text), unscaled_key);
+            assert_eq!(harfrust_font_get_horizontal_scale(std::ptr::null()), 0.0);
+            assert_eq!(harfrust_font_freeze(font), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_set_horizontal_scale(font, 0.5), HarfRustStatus::Unsupported);
             harfrust_font_free(font);
         }
     }
 
+
     #[test]
-    fn test_shape_with_variations() {
-        // Try to load a system font for testing
-        let font_paths = [
-            r"C:\Windows\Fonts\calibri.ttf",
-            r"C:\Windows\Fonts\arial.ttf",
-        ];
-        
-        let font_data = font_paths.iter()
-            .find_map(|path| std::fs::read(path).ok())
-            .expect("No system font found for testing");
-            
+    fn test_font_glyph_count() {
+        let font_data = test_font_data();
+        let cff_data = test_cff_data();
         unsafe {
             let font = harfrust_font_from_data(font_data.as_ptr(), font_data.len() as i32);
-            let buffer = harfrust_buffer_new();
-            
-            let text = CString::new("Hello").unwrap();
-            harfrust_buffer_add_str(buffer, text.as_ptr());
-            
-            // Set variation: wght = 700.0 (Bold)
-            let variations = [
-                HarfRustVariation {
-                    tag: u32::from_be_bytes(*b"wght"),
-                    value: 700.0,
-                }
+            let maxp = skrifa::FontRef::new(&font_data).unwrap().maxp().unwrap();
+            assert_eq!(harfrust_font_glyph_count(font), maxp.num_glyphs() as i32);
+            harfrust_font_free(font);
+
+            // .notdef, 'A' and the space
+            let font = harfrust_font_from_cff(cff_data.as_ptr(), cff_data.len() as i32);
+            assert_eq!(harfrust_font_glyph_count(font), 3);
+            harfrust_font_free(font);
+
+            assert_eq!(harfrust_font_glyph_count(std::ptr::null()), -1);
+        }
+    }
+
+
+    #[test]
+    fn test_layout_line() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let shape = |text: &std::ffi::CStr| {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_str(buffer, text.as_ptr());
+                harfrust_shape(font, buffer)
+            };
+            let (base, sup) = (shape(c"x"), shape(c"2"));
+            let mut metrics = HarfRustFontMetrics::default();
+            harfrust_font_get_metrics(font, &mut metrics);
+            let scale = 10.0 / metrics.units_per_em as f32;
+            let base_width = (&object(base).positions_cache)[0].x_advance as f32 * scale;
+
+            let runs = [
+                HarfRustLayoutRun { font, glyphs: base, font_size: 10.0, rise: 0.0 },
+                HarfRustLayoutRun { font, glyphs: sup, font_size: 6.0, rise: 4.0 },
             ];
-            
-            let glyph_buffer = harfrust_shape_full(
-                font,
-                buffer,
-                std::ptr::null(), // No features
-                0,
-                variations.as_ptr(),
-                variations.len() as u32
+            let mut placements = [HarfRustRunPlacement::default(); 2];
+            let mut line = HarfRustLineMetrics::default();
+            assert_eq!(harfrust_layout_line(runs.as_ptr(), 2, placements.as_mut_ptr(), &mut line), HarfRustStatus::Ok);
+            assert_eq!(placements[0], HarfRustRunPlacement { x: 0.0, y: 0.0 });
+            assert!((placements[1].x - base_width).abs() < 1e-4 && placements[1].y == 4.0);
+            // The raised run reaches above the base run, which keeps the descent
+            let sup_ascent = metrics.ascent as f32 * 6.0 / metrics.units_per_em as f32 + 4.0;
+            assert!(sup_ascent > metrics.ascent as f32 * scale);
+            assert!((line.ascent - sup_ascent).abs() < 0.01);
+            assert!((line.descent - metrics.descent as f32 * scale).abs() < 0.01);
+            let sup_width = (&object(sup).positions_cache)[0].x_advance as f32 * 6.0 / metrics.units_per_em as f32;
+            assert!((line.width - base_width - sup_width).abs() < 1e-3);
+
+            assert_eq!(harfrust_layout_line(std::ptr::null(), 0, std::ptr::null_mut(), &mut line), HarfRustStatus::Ok);
+            assert_eq!(line, HarfRustLineMetrics::default());
+            let bad = [HarfRustLayoutRun { rise: f32::NAN, ..runs[0] }];
+            assert_eq!(
+                harfrust_layout_line(bad.as_ptr(), 1, std::ptr::null_mut(), &mut line),
+                HarfRustStatus::InvalidArgument
             );
-            
-            assert!(!glyph_buffer.is_null());
-            
-            // Just verify it produced something
-            let len = harfrust_glyph_buffer_len(glyph_buffer);
-            assert!(len > 0);
-            
-            harfrust_glyph_buffer_free(glyph_buffer);
+            assert_eq!(
+                harfrust_layout_line(runs.as_ptr(), 2, std::ptr::null_mut(), std::ptr::null_mut()),
+                HarfRustStatus::NullArgument
+            );
+            harfrust_glyph_buffer_free(base);
+            harfrust_glyph_buffer_free(sup);
+            harfrust_font_free(font);
+        }
+    }
+
+
+    #[test]
+    fn test_character_spacing() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let text: Vec<u16> = "a fi".encode_utf16().collect();
+            let shape = || {
+                let buffer = harfrust_buffer_new();
+                harfrust_buffer_add_utf16(buffer, text.as_ptr(), text.len() as i32);
+                let result = harfrust_shape(font, buffer);
+                let len = harfrust_glyph_buffer_len(result) as usize;
+                let positions = std::slice::from_raw_parts(harfrust_glyph_buffer_get_positions(result), len);
+                let advances: Vec<i32> = positions.iter().map(|pos| pos.x_advance).collect();
+                harfrust_glyph_buffer_free(result);
+                advances
+            };
+            let plain = shape();
+            // The "fi" ligature is one cluster and gets the spacing once
+            assert_eq!(plain.len(), 3);
+            let width: i32 = plain.iter().sum();
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 4, width + 200), 1);
+
+            assert_eq!(harfrust_font_set_character_spacing(font, 100), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_get_character_spacing(font), 100);
+            assert_eq!(shape(), plain.iter().map(|advance| advance + 100).collect::<Vec<_>>());
+            // Nominal measurement spaces each of the four characters
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 4, width + 200), 0);
+            assert_eq!(harfrust_text_fits(font, text.as_ptr(), 4, width + 400), 1);
+
+            // A combining mark joins the cluster before it, so the spacing counts once
+            let marked: Vec<u16> = "e\u{301}".encode_utf16().collect();
+            let fits = |max_width: i32| harfrust_text_fits(font, marked.as_ptr(), 2, max_width) == 1;
+            let narrowest = (0..4096).find(|&max_width| fits(max_width)).unwrap();
+            assert_eq!(harfrust_font_set_character_spacing(font, 200), HarfRustStatus::Ok);
+            assert!(!fits(narrowest + 99));
+            assert!(fits(narrowest + 100));
+            assert_eq!(harfrust_font_set_character_spacing(font, 100), HarfRustStatus::Ok);
+
+            // The spacing is part of the run key
+            let spaced = run_key(font, &text);
+            assert_eq!(harfrust_font_set_character_spacing(font, 0), HarfRustStatus::Ok);
+            let unspaced = run_key(font, &text);
+            assert_ne!(spaced, unspaced);
+            assert_eq!(harfrust_font_set_character_spacing(font, 100), HarfRustStatus::Ok);
+            assert_eq!(run_key(font, &text), spaced);
+
+            // Horizontal scaling applies to the spacing too
+            assert_eq!(harfrust_font_set_horizontal_scale(font, 0.5), HarfRustStatus::Ok);
+            let expected: Vec<i32> = plain.iter().map(|&advance| ((advance + 100) as f32 * 0.5).round() as i32).collect();
+            assert_eq!(shape(), expected);
+
+            assert_eq!(harfrust_font_get_character_spacing(std::ptr::null()), 0);
+            assert_eq!(harfrust_font_set_character_spacing(std::ptr::null_mut(), 1), HarfRustStatus::NullArgument);
+            assert_eq!(harfrust_font_freeze(font), HarfRustStatus::Ok);
+            assert_eq!(harfrust_font_set_character_spacing(font, 0), HarfRustStatus::Unsupported);
+            harfrust_font_free(font);
+        }
+    }
+
+
+    #[test]
+    fn test_nominal_glyph() {
+        let data = test_font_data();
+        unsafe {
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            let mut glyph = u32::MAX;
+            assert_eq!(harfrust_font_nominal_glyph(font, 'A' as u32, &mut glyph), 1);
+            assert_eq!(glyph, object(font).font_ref.charmap().map('A').unwrap().to_u32());
+            assert_ne!(glyph, 0);
+
+            // A private use codepoint, and one past the end of Unicode
+            for codepoint in [0xF0000, 0x110000] {
+                assert_eq!(harfrust_font_nominal_glyph(font, codepoint, &mut glyph), 0);
+                assert_eq!(glyph, 0);
+            }
+            assert_eq!(harfrust_font_nominal_glyph(font, 'A' as u32, std::ptr::null_mut()), -1);
+            assert_eq!(harfrust_font_nominal_glyph(std::ptr::null(), 'A' as u32, &mut glyph), -1);
+            harfrust_font_free(font);
+        }
+    }
+
+
+    #[test]
+    #[cfg(feature = "handle-table")]
+    fn test_stale_handles() {
+        let data = test_font_data();
+        unsafe {
+            let buffer = harfrust_buffer_new();
+            harfrust_buffer_add_str(buffer, c"stale".as_ptr());
+            harfrust_buffer_free(buffer);
+            harfrust_clear_last_error();
+            assert_eq!(harfrust_buffer_len(buffer), HarfRustStatus::StaleHandle as i32);
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::StaleHandle);
+
+            // A second free is caught rather than freeing whatever took the slot
+            harfrust_clear_last_error();
+            harfrust_buffer_free(buffer);
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::StaleHandle);
+
+            let font = harfrust_font_from_data(data.as_ptr(), data.len() as i32);
+            harfrust_font_free(font);
+            assert_eq!(harfrust_font_units_per_em(font), HarfRustStatus::StaleHandle as i32);
+            harfrust_clear_last_error();
             harfrust_font_free(font);
+            assert_eq!(harfrust_last_error_code(), HarfRustStatus::StaleHandle);
         }
     }
 }
@@ -0,0 +1,158 @@
+//! Font-wide metrics for PDF font descriptors.
+//!
+//! Values come from the font tables when present and are otherwise derived
+//! from representative glyph outlines, so descriptors can always be filled in.
+
+use skrifa::raw::TableProvider;
+use skrifa::{FontRef, MetadataProvider};
+use tiny_skia::{Path, PathSegment, Point, Rect};
+
+use crate::HarfRustMetricSource;
+
+/// Tight outline bounds of the glyph `ch` maps to, in font units.
+pub(crate) fn char_bounds(font: &FontRef, ch: char) -> Option<Rect> {
+    let glyph_id = font.charmap().map(ch)?;
+    crate::color::glyph_path(&font.outline_glyphs(), glyph_id)?.compute_tight_bounds()
+}
+
+/// Height measured from the tops of the given glyphs (the tallest wins).
+fn measured_height(font: &FontRef, chars: &[char]) -> Option<i32> {
+    chars
+        .iter()
+        .filter_map(|&ch| char_bounds(font, ch))
+        .map(|bounds| bounds.bottom().round() as i32)
+        .filter(|&top| top > 0)
+        .max()
+}
+
+fn resolve(
+    table_value: Option<i16>,
+    font: &FontRef,
+    chars: &[char],
+    em_fraction: f32,
+) -> (i32, HarfRustMetricSource) {
+    if let Some(value) = table_value.filter(|&v| v > 0) {
+        return (value as i32, HarfRustMetricSource::Table);
+    }
+    if let Some(value) = measured_height(font, chars) {
+        return (value, HarfRustMetricSource::GlyphOutline);
+    }
+    let upem = font.head().map_or(1000, |head| head.units_per_em()) as f32;
+    ((upem * em_fraction).round() as i32, HarfRustMetricSource::Estimated)
+}
+
+/// Cap height from OS/2 sCapHeight, else the height of 'H'.
+pub(crate) fn cap_height(font: &FontRef) -> (i32, HarfRustMetricSource) {
+    let table_value = font.os2().ok().and_then(|os2| os2.s_cap_height());
+    resolve(table_value, font, &['H', 'I'], 0.7)
+}
+
+/// x-height from OS/2 sxHeight, else the height of 'x'.
+pub(crate) fn x_height(font: &FontRef) -> (i32, HarfRustMetricSource) {
+    let table_value = font.os2().ok().and_then(|os2| os2.sx_height());
+    resolve(table_value, font, &['x', 'z'], 0.5)
+}
+
+/// Dominant vertical stem width in font units, measured as the width of the
+/// filled span crossing the middle of 'l' or 'I'. Falls back to a
+/// weight-class heuristic for fonts without usable outlines.
+pub(crate) fn stem_v(font: &FontRef) -> (i32, HarfRustMetricSource) {
+    let outlines = font.outline_glyphs();
+    for ch in ['l', 'I'] {
+        let Some(glyph_id) = font.charmap().map(ch) else {
+            continue;
+        };
+        let Some(path) = crate::color::glyph_path(&outlines, glyph_id) else {
+            continue;
+        };
+        let Some(bounds) = path.compute_tight_bounds() else {
+            continue;
+        };
+
+        let mut crossings = scanline_crossings(&path, (bounds.top() + bounds.bottom()) / 2.0);
+        crossings.sort_by(f32::total_cmp);
+        if let [left, right, ..] = crossings[..] {
+            let width = (right - left).round() as i32;
+            if width > 0 {
+                return (width, HarfRustMetricSource::GlyphOutline);
+            }
+        }
+    }
+
+    // Empirical fit: about 40 units per 1000 em for regular, 120 for bold.
+    let weight = font.os2().map_or(400, |os2| os2.us_weight_class()) as f32;
+    let upem = font.head().map_or(1000, |head| head.units_per_em()) as f32;
+    let normalized = ((weight - 50.0) / 900.0).clamp(0.0, 1.0);
+    let stem = 10.0 + 220.0 * normalized * normalized;
+    ((stem * upem / 1000.0).round() as i32, HarfRustMetricSource::Estimated)
+}
+
+/// X coordinates where the outline crosses the horizontal line at `y`.
+/// Curves are flattened into short line segments.
+fn scanline_crossings(path: &Path, y: f32) -> Vec<f32> {
+    const STEPS: usize = 16;
+
+    let mut crossings = Vec::new();
+    let mut add_line = |p0: Point, p1: Point| {
+        if (p0.y <= y) != (p1.y <= y) {
+            let t = (y - p0.y) / (p1.y - p0.y);
+            crossings.push(p0.x + t * (p1.x - p0.x));
+        }
+    };
+
+    let mut start = Point::zero();
+    let mut current = Point::zero();
+    for segment in path.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                if current != start {
+                    add_line(current, start);
+                }
+                start = p;
+                current = p;
+            }
+            PathSegment::LineTo(p) => {
+                add_line(current, p);
+                current = p;
+            }
+            PathSegment::QuadTo(c, p) => {
+                let mut prev = current;
+                for i in 1..=STEPS {
+                    let t = i as f32 / STEPS as f32;
+                    let mt = 1.0 - t;
+                    let next = Point::from_xy(
+                        mt * mt * current.x + 2.0 * mt * t * c.x + t * t * p.x,
+                        mt * mt * current.y + 2.0 * mt * t * c.y + t * t * p.y,
+                    );
+                    add_line(prev, next);
+                    prev = next;
+                }
+                current = p;
+            }
+            PathSegment::CubicTo(c1, c2, p) => {
+                let mut prev = current;
+                for i in 1..=STEPS {
+                    let t = i as f32 / STEPS as f32;
+                    let mt = 1.0 - t;
+                    let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+                    let next = Point::from_xy(
+                        a * current.x + b * c1.x + c * c2.x + d * p.x,
+                        a * current.y + b * c1.y + c * c2.y + d * p.y,
+                    );
+                    add_line(prev, next);
+                    prev = next;
+                }
+                current = p;
+            }
+            PathSegment::Close => {
+                add_line(current, start);
+                current = start;
+            }
+        }
+    }
+    if current != start {
+        add_line(current, start);
+    }
+
+    crossings
+}
@@ -0,0 +1,77 @@
+//! Glyph outlines as PDF-style path commands.
+
+use skrifa::instance::{LocationRef, Size};
+use skrifa::outline::{DrawSettings, OutlinePen};
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+
+use crate::{HarfRustPathCommand, HarfRustPathVerb};
+
+/// Collects path commands scaled by a factor, elevating quadratic curves to
+/// cubics since PDF paths have no quadratic operator.
+struct CommandPen {
+    scale: f32,
+    commands: Vec<HarfRustPathCommand>,
+    current: (f32, f32),
+}
+
+impl CommandPen {
+    fn push(&mut self, verb: HarfRustPathVerb, points: [(f32, f32); 3]) {
+        let s = self.scale;
+        let [(x1, y1), (x2, y2), (x, y)] = points;
+        self.commands.push(HarfRustPathCommand {
+            verb,
+            x1: x1 * s,
+            y1: y1 * s,
+            x2: x2 * s,
+            y2: y2 * s,
+            x: x * s,
+            y: y * s,
+        });
+        self.current = (x, y);
+    }
+}
+
+impl OutlinePen for CommandPen {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.push(HarfRustPathVerb::MoveTo, [(0.0, 0.0), (0.0, 0.0), (x, y)]);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.push(HarfRustPathVerb::LineTo, [(0.0, 0.0), (0.0, 0.0), (x, y)]);
+    }
+
+    fn quad_to(&mut self, cx0: f32, cy0: f32, x: f32, y: f32) {
+        let (x0, y0) = self.current;
+        let c1 = (x0 + 2.0 / 3.0 * (cx0 - x0), y0 + 2.0 / 3.0 * (cy0 - y0));
+        let c2 = (x + 2.0 / 3.0 * (cx0 - x), y + 2.0 / 3.0 * (cy0 - y));
+        self.push(HarfRustPathVerb::CubicTo, [c1, c2, (x, y)]);
+    }
+
+    fn curve_to(&mut self, cx0: f32, cy0: f32, cx1: f32, cy1: f32, x: f32, y: f32) {
+        self.push(HarfRustPathVerb::CubicTo, [(cx0, cy0), (cx1, cy1), (x, y)]);
+    }
+
+    fn close(&mut self) {
+        let current = self.current;
+        self.push(HarfRustPathVerb::Close, [(0.0, 0.0), (0.0, 0.0), current]);
+    }
+}
+
+/// Returns the unhinted outline of `glyph_id` at the default location,
+/// with coordinates multiplied by `scale`, or `None` if the font has no
+/// outline for the glyph.
+pub(crate) fn glyph_commands(
+    font: &FontRef,
+    glyph_id: GlyphId,
+    scale: f32,
+) -> Option<Vec<HarfRustPathCommand>> {
+    let glyph = font.outline_glyphs().get(glyph_id)?;
+    let mut pen = CommandPen {
+        scale,
+        commands: Vec::new(),
+        current: (0.0, 0.0),
+    };
+    let settings = DrawSettings::unhinted(Size::unscaled(), LocationRef::default());
+    glyph.draw(settings, &mut pen).ok()?;
+    Some(pen.commands)
+}
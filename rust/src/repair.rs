@@ -0,0 +1,134 @@
+//! Repair of malformed font files, such as fonts extracted from PDFs.
+//!
+//! Embedded fonts often carry damage that strict parsers reject or that hides
+//! tables: an unrecognized sfnt version, a table count larger than the
+//! directory, tables running past the end of the data (truncated padding),
+//! unsorted or duplicate table records, and stale checksums. The directory is
+//! read tolerantly and the font rebuilt from whatever table data is present.
+
+use skrifa::raw::types::Tag;
+
+use crate::sfnt::{self, TRUETYPE};
+
+/// sfnt versions accepted by parsers.
+const CFF: u32 = u32::from_be_bytes(*b"OTTO");
+const APPLE_TRUETYPE: u32 = u32::from_be_bytes(*b"true");
+
+const HEAD_MAGIC_NUMBER: u32 = 0x5F0F_3CF5;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+/// Rebuilds a single (non-collection) font from its table directory, keeping
+/// the first record of each tag and clamping tables to the available data.
+/// Returns `None` for collections or if no table survives.
+pub(crate) fn repair(data: &[u8]) -> Option<Vec<u8>> {
+    rebuild(data, &[])
+}
+
+/// Same as [`repair`], leaving out the tables in `drop`.
+pub(crate) fn rebuild(data: &[u8], drop: &[Tag]) -> Option<Vec<u8>> {
+    let version = read_u32(data, 0)?;
+    if version == u32::from_be_bytes(*b"ttcf") {
+        return None;
+    }
+    let num_tables = read_u16(data, 4)? as usize;
+    // Trust only the records that fit in the data.
+    let num_tables = num_tables.min(data.len().saturating_sub(12) / 16);
+
+    let mut tables: Vec<(Tag, Vec<u8>)> = Vec::with_capacity(num_tables);
+    for record in data.get(12..12 + num_tables * 16)?.chunks_exact(16) {
+        let tag = Tag::new(record[..4].try_into().ok()?);
+        let offset = read_u32(record, 8)? as usize;
+        let length = read_u32(record, 12)? as usize;
+        let skip = drop.contains(&tag) || tables.iter().any(|(t, _)| *t == tag);
+        if skip || offset >= data.len() || length == 0 {
+            continue;
+        }
+        let end = offset.saturating_add(length).min(data.len());
+        tables.push((tag, data[offset..end].to_vec()));
+    }
+    if tables.is_empty() {
+        return None;
+    }
+
+    for (tag, table) in &mut tables {
+        if *tag == Tag::new(b"head") && table.len() >= 16 {
+            table[12..16].copy_from_slice(&HEAD_MAGIC_NUMBER.to_be_bytes());
+        }
+    }
+
+    let version = match version {
+        TRUETYPE | CFF | APPLE_TRUETYPE => version,
+        _ if tables.iter().any(|(tag, _)| *tag == Tag::new(b"CFF ")) => CFF,
+        _ => TRUETYPE,
+    };
+    Some(sfnt::build_sfnt(version, tables))
+}
+
+/// Lists the recoverable problems [`repair`] fixes in a single font's table
+/// directory, one message each. Collections are not inspected.
+pub(crate) fn diagnose(data: &[u8]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let Some(version) = read_u32(data, 0) else {
+        return problems;
+    };
+    if version == u32::from_be_bytes(*b"ttcf") {
+        return problems;
+    }
+    if !matches!(version, TRUETYPE | CFF | APPLE_TRUETYPE) {
+        problems.push(format!("unrecognized sfnt version 0x{version:08X}"));
+    }
+
+    let declared = read_u16(data, 4).unwrap_or(0) as usize;
+    let num_tables = declared.min(data.len().saturating_sub(12) / 16);
+    if num_tables < declared {
+        problems.push(format!("{declared} tables declared but only {num_tables} records fit"));
+    }
+
+    let mut seen: Vec<(Tag, usize, usize)> = Vec::with_capacity(num_tables);
+    let records = data.get(12..12 + num_tables * 16).unwrap_or_default();
+    for record in records.chunks_exact(16) {
+        let tag = Tag::new(record[..4].try_into().unwrap_or(&[0; 4]));
+        let stored_checksum = read_u32(record, 4).unwrap_or(0);
+        let offset = read_u32(record, 8).unwrap_or(0) as usize;
+        let length = read_u32(record, 12).unwrap_or(0) as usize;
+        let end = offset.saturating_add(length);
+
+        if seen.iter().any(|(t, _, _)| *t == tag) {
+            problems.push(format!("duplicate '{tag}' table record"));
+            continue;
+        }
+        if offset >= data.len() {
+            problems.push(format!("'{tag}' table starts past the end of the data"));
+            continue;
+        }
+        if end > data.len() {
+            problems.push(format!("'{tag}' table is truncated"));
+        }
+        if let Some((other, _, _)) = seen.iter().find(|(_, start, stop)| offset < *stop && *start < end)
+        {
+            problems.push(format!("'{tag}' table overlaps '{other}'"));
+        }
+
+        let table = &data[offset..end.min(data.len())];
+        let mut table_checksum = sfnt::checksum(table);
+        if tag == Tag::new(b"head") {
+            // The checksum of head is computed with checkSumAdjustment zeroed.
+            table_checksum = table_checksum.wrapping_sub(read_u32(table, 8).unwrap_or(0));
+            if read_u32(table, 12).is_some_and(|magic| magic != HEAD_MAGIC_NUMBER) {
+                problems.push("bad 'head' magic number".to_string());
+            }
+        }
+        if table_checksum != stored_checksum {
+            problems.push(format!("'{tag}' table checksum mismatch"));
+        }
+        seen.push((tag, offset, end));
+    }
+    problems
+}
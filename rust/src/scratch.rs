@@ -0,0 +1,80 @@
+//! Per-thread reuse of shaping buffers (the `arena` feature).
+//!
+//! harfrust keeps the working storage of a shape call (glyph infos,
+//! positions and output arrays) inside the buffer it shapes, and returns it
+//! with the result. With the feature enabled, buffers released when a call's
+//! input or result is freed are reset and kept for the next call on the same
+//! thread, so steady shaping loops stop going through the global allocator,
+//! which contends across threads in multi-threaded renderers. Without the
+//! feature buffers are allocated and dropped as usual.
+
+use harfrust::{GlyphBuffer, UnicodeBuffer};
+
+#[cfg(feature = "arena")]
+mod pool {
+    use std::cell::RefCell;
+
+    use harfrust::{BufferFlags, UnicodeBuffer};
+
+    use crate::stats;
+
+    /// Buffers kept per thread; more are dropped.
+    const POOL_SIZE: usize = 4;
+
+    /// A thread's buffers, counted in `stats::POOLED_BUFFERS` until the
+    /// thread exits.
+    struct Pool(Vec<UnicodeBuffer>);
+
+    impl Drop for Pool {
+        fn drop(&mut self) {
+            stats::sub(&stats::POOLED_BUFFERS, self.0.len());
+        }
+    }
+
+    thread_local! {
+        static POOL: RefCell<Pool> = const { RefCell::new(Pool(Vec::new())) };
+    }
+
+    pub(super) fn take() -> Option<UnicodeBuffer> {
+        let buffer = POOL.with_borrow_mut(|pool| pool.0.pop())?;
+        stats::sub(&stats::POOLED_BUFFERS, 1);
+        Some(buffer)
+    }
+
+    pub(super) fn put(mut buffer: UnicodeBuffer) {
+        POOL.with_borrow_mut(|pool| {
+            if pool.0.len() < POOL_SIZE {
+                // Clearing resets everything but the flags.
+                buffer.clear();
+                buffer.set_flags(BufferFlags::empty());
+                pool.0.push(buffer);
+                stats::add(&stats::POOLED_BUFFERS, 1);
+            }
+        });
+    }
+}
+
+/// Returns an empty buffer with default properties.
+pub(crate) fn unicode_buffer() -> UnicodeBuffer {
+    #[cfg(feature = "arena")]
+    if let Some(buffer) = pool::take() {
+        return buffer;
+    }
+    UnicodeBuffer::new()
+}
+
+/// Releases a buffer whose contents are no longer needed.
+pub(crate) fn recycle(buffer: UnicodeBuffer) {
+    #[cfg(feature = "arena")]
+    pool::put(buffer);
+    #[cfg(not(feature = "arena"))]
+    drop(buffer);
+}
+
+/// Releases a shaping result whose glyphs are no longer needed.
+pub(crate) fn recycle_glyphs(buffer: GlyphBuffer) {
+    #[cfg(feature = "arena")]
+    pool::put(buffer.clear());
+    #[cfg(not(feature = "arena"))]
+    drop(buffer);
+}
@@ -0,0 +1,68 @@
+//! SFNT (TrueType/OpenType) font file serialization.
+
+use skrifa::raw::types::Tag;
+
+/// sfnt version of fonts with TrueType outlines.
+pub(crate) const TRUETYPE: u32 = 0x0001_0000;
+
+/// Returns a copy of `data` with the given byte ranges overwritten.
+pub(crate) fn patch(data: &[u8], edits: &[(usize, &[u8])]) -> Option<Vec<u8>> {
+    let mut data = data.to_vec();
+    for &(offset, bytes) in edits {
+        data.get_mut(offset..offset + bytes.len())?.copy_from_slice(bytes);
+    }
+    Some(data)
+}
+
+/// Sum of big-endian u32 words, zero-padding the tail.
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Serializes tables into a font file with the given sfnt version and sets
+/// `head`'s checkSumAdjustment.
+pub(crate) fn build_sfnt(sfnt_version: u32, mut tables: Vec<(Tag, Vec<u8>)>) -> Vec<u8> {
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    let num_tables = tables.len() as u16;
+    let entry_selector = num_tables.max(1).ilog2() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&sfnt_version.to_be_bytes());
+    out.extend_from_slice(&num_tables.to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&(num_tables * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in &mut tables {
+        if *tag == Tag::new(b"head") {
+            // checkSumAdjustment is computed with the field zeroed.
+            if let Some(adjustment) = data.get_mut(8..12) {
+                adjustment.fill(0);
+                head_offset = Some(offset);
+            }
+        }
+        out.extend_from_slice(&tag.to_be_bytes());
+        out.extend_from_slice(&checksum(data).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        out.extend_from_slice(data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+        out[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    out
+}
@@ -0,0 +1,31 @@
+//! Process-wide counters of native memory, reported by
+//! `harfrust_memory_stats`.
+//!
+//! Each counter is updated where its allocation is made and released, so
+//! reading them costs nothing beyond the loads. Sizes cover the large
+//! allocations only (font data and per-glyph tables), not small bookkeeping.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Font data copied into owned memory.
+pub(crate) static FONT_DATA_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// Font files mapped into memory.
+pub(crate) static MAPPED_FONT_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// Per-font glyph advance tables.
+pub(crate) static ADVANCE_CACHE_BYTES: AtomicUsize = AtomicUsize::new(0);
+/// Shape plans cached across all fonts.
+pub(crate) static SHAPE_PLANS: AtomicUsize = AtomicUsize::new(0);
+/// Buffers kept for reuse across all threads (the `arena` feature).
+pub(crate) static POOLED_BUFFERS: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn add(counter: &AtomicUsize, amount: usize) {
+    counter.fetch_add(amount, Ordering::Relaxed);
+}
+
+pub(crate) fn sub(counter: &AtomicUsize, amount: usize) {
+    counter.fetch_sub(amount, Ordering::Relaxed);
+}
+
+pub(crate) fn get(counter: &AtomicUsize) -> usize {
+    counter.load(Ordering::Relaxed)
+}
@@ -0,0 +1,292 @@
+//! Font subsetting: glyph closure and TrueType-outline subset fonts.
+
+use std::collections::BTreeSet;
+
+use skrifa::raw::collections::IntSet;
+use skrifa::raw::tables::glyf::Glyph;
+use skrifa::raw::types::Tag;
+use skrifa::raw::TableProvider;
+use skrifa::string::StringId;
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+
+use crate::sfnt::{self, patch, TRUETYPE};
+
+/// Expands `seeds` to every glyph a subset must keep for them to render and
+/// shape the same: .notdef, glyphs reachable through GSUB lookups of the
+/// selected scripts/features (`None` selects all), and composite glyph
+/// components. Returns the closure sorted by glyph ID.
+pub(crate) fn glyph_closure(
+    font: &FontRef,
+    seeds: &[u32],
+    scripts: Option<&[Tag]>,
+    features: Option<&[Tag]>,
+) -> Vec<u32> {
+    let num_glyphs = font.maxp().map_or(u16::MAX as u32, |maxp| maxp.num_glyphs() as u32);
+    let mut glyphs: IntSet<GlyphId> = seeds
+        .iter()
+        .copied()
+        .chain([0])
+        .filter(|&gid| gid < num_glyphs)
+        .map(GlyphId::new)
+        .collect();
+
+    if let Ok(gsub) = font.gsub() {
+        let to_set = |tags: Option<&[Tag]>| match tags {
+            Some(tags) => tags.iter().copied().collect(),
+            None => IntSet::<Tag>::all(),
+        };
+        let feature_indices =
+            gsub.collect_features(&to_set(scripts), &IntSet::all(), &to_set(features));
+        if let Ok(mut lookups) = feature_indices.and_then(|f| gsub.collect_lookups(&f)) {
+            // Errors in malformed lookups leave the closure as far as it got.
+            let _ = gsub.closure_lookups(&glyphs, &mut lookups);
+            let _ = gsub.closure_glyphs(&lookups, &mut glyphs);
+        }
+    }
+
+    let mut closure: BTreeSet<u32> = glyphs.iter().map(|gid| gid.to_u32()).collect();
+    add_composite_components(font, &mut closure);
+    closure.into_iter().collect()
+}
+
+/// Adds the components of composite `glyf` glyphs, recursively.
+fn add_composite_components(font: &FontRef, glyphs: &mut BTreeSet<u32>) {
+    let (Ok(loca), Ok(glyf)) = (font.loca(None), font.glyf()) else {
+        return;
+    };
+
+    let mut pending: Vec<u32> = glyphs.iter().copied().collect();
+    while let Some(gid) = pending.pop() {
+        if let Ok(Some(Glyph::Composite(composite))) = loca.get_glyf(GlyphId::new(gid), &glyf) {
+            for component in composite.components() {
+                let component_id = component.glyph.to_u32();
+                if glyphs.insert(component_id) {
+                    pending.push(component_id);
+                }
+            }
+        }
+    }
+}
+
+/// Tables kept by a compact subset. Everything else either indexes glyphs by
+/// their original IDs (cmap, layout, color and bitmap tables) or is not needed
+/// to render the embedded glyphs.
+const COMPACT_TABLES: &[&[u8; 4]] = &[
+    b"OS/2", b"cvt ", b"fpgm", b"gasp", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp",
+    b"name", b"post", b"prep",
+];
+
+/// Name IDs that get the subset tag: family, unique ID, full and PostScript name.
+const TAGGED_NAME_IDS: &[u16] = &[1, 3, 4, 6];
+
+/// Composite glyph component flags.
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+/// A subset font file.
+pub(crate) struct Subset {
+    pub data: Vec<u8>,
+    /// PostScript name with the subset tag, e.g. "ABCDEF+DejaVuSans".
+    pub postscript_name: String,
+}
+
+/// Builds a subset of a TrueType-outline font containing the closure of
+/// `seeds`. With `retain_gids` every glyph keeps its ID and glyphs outside the
+/// closure are emptied; all other tables are copied unchanged. Otherwise the
+/// closure is renumbered compactly in glyph ID order, and tables that index
+/// glyphs by ID are dropped. In both modes the family, unique, full and
+/// PostScript names get a six-letter subset tag prefix derived from the glyph
+/// set. Returns `None` for fonts without `glyf` outlines.
+pub(crate) fn subset_font(font: &FontRef, seeds: &[u32], retain_gids: bool) -> Option<Subset> {
+    let loca = font.loca(None).ok()?;
+    let glyf = font.data_for_tag(Tag::new(b"glyf"))?;
+    let num_glyphs = font.maxp().ok()?.num_glyphs() as u32;
+    let closure = glyph_closure(font, seeds, None, None);
+    let postscript_name = postscript_name(font);
+    let prefix = format!("{}+", subset_tag(&postscript_name, &closure));
+
+    let glyph_data = |gid: u32| -> &[u8] {
+        let start = loca.get_raw(gid as usize).unwrap_or(0) as usize;
+        let end = loca.get_raw(gid as usize + 1).unwrap_or(0) as usize;
+        glyf.as_bytes().get(start..end).unwrap_or(&[])
+    };
+
+    // Old glyph ID for each new glyph ID; `None` for emptied glyphs.
+    let order: Vec<Option<u32>> = if retain_gids {
+        (0..num_glyphs).map(|gid| closure.binary_search(&gid).ok().map(|_| gid)).collect()
+    } else {
+        closure.iter().map(|&gid| Some(gid)).collect()
+    };
+
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((order.len() + 1) * 4);
+    for old in &order {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if let Some(old) = *old {
+            let start = new_glyf.len();
+            new_glyf.extend_from_slice(glyph_data(old));
+            if !retain_gids {
+                remap_components(&mut new_glyf[start..], &closure);
+            }
+            new_glyf.resize(new_glyf.len().next_multiple_of(4), 0);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    let mut tables: Vec<(Tag, Vec<u8>)> = Vec::new();
+    for record in font.table_directory.table_records() {
+        let tag = record.tag();
+        let keep = if retain_gids {
+            tag != Tag::new(b"DSIG")
+        } else {
+            COMPACT_TABLES.iter().any(|t| Tag::new(t) == tag)
+        };
+        if !keep {
+            continue;
+        }
+        let Some(data) = font.data_for_tag(tag).map(|data| data.as_bytes()) else {
+            continue;
+        };
+
+        let data = match &tag.to_be_bytes() {
+            b"glyf" => std::mem::take(&mut new_glyf),
+            b"name" => prefix_names(font, &prefix).unwrap_or_else(|| data.to_vec()),
+            b"loca" => std::mem::take(&mut new_loca),
+            // Switch to long loca offsets.
+            b"head" => patch(data, &[(50, &1u16.to_be_bytes())])?,
+            b"maxp" if !retain_gids => patch(data, &[(4, &(order.len() as u16).to_be_bytes())])?,
+            b"hhea" if !retain_gids => patch(data, &[(34, &(order.len() as u16).to_be_bytes())])?,
+            b"hmtx" if !retain_gids => compact_hmtx(font, &closure)?,
+            // Version 3.0: no glyph names, which are indexed by glyph ID.
+            b"post" if !retain_gids => {
+                patch(data.get(..32)?, &[(0, &0x0003_0000u32.to_be_bytes())])?
+            }
+            _ => data.to_vec(),
+        };
+        tables.push((tag, data));
+    }
+
+    Some(Subset {
+        data: sfnt::build_sfnt(TRUETYPE, tables),
+        postscript_name: prefix + &postscript_name,
+    })
+}
+
+/// The font's PostScript name, falling back to the family name without
+/// spaces.
+fn postscript_name(font: &FontRef) -> String {
+    let name = |id| font.localized_strings(id).english_or_first().map(|s| s.to_string());
+    name(StringId::POSTSCRIPT_NAME)
+        .or_else(|| name(StringId::FAMILY_NAME).map(|family| family.replace(' ', "")))
+        .unwrap_or_default()
+}
+
+/// Six uppercase letters derived from the font name and glyph set (FNV-1a),
+/// so the same subset of the same font always gets the same tag.
+fn subset_tag(postscript_name: &str, glyphs: &[u32]) -> String {
+    let bytes = postscript_name.bytes().chain(glyphs.iter().flat_map(|gid| gid.to_le_bytes()));
+    let mut hash = bytes.fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    (0..6)
+        .map(|_| {
+            let letter = (b'A' + (hash % 26) as u8) as char;
+            hash /= 26;
+            letter
+        })
+        .collect()
+}
+
+/// Rebuilds the `name` table with `prefix` prepended to the tagged names.
+fn prefix_names(font: &FontRef, prefix: &str) -> Option<Vec<u8>> {
+    let name = font.name().ok()?;
+    let strings = name.string_data().as_bytes();
+    let string = |offset: usize, len: usize| strings.get(offset..offset + len);
+    let utf16_prefix: Vec<u8> = prefix.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+    let records = name.name_record();
+    let lang_tags = name.lang_tag_record().unwrap_or_default();
+    let mut storage = Vec::new();
+    let mut header = Vec::new();
+    header.extend_from_slice(&name.version().to_be_bytes());
+    header.extend_from_slice(&(records.len() as u16).to_be_bytes());
+    let lang_tag_size = name.lang_tag_count().map_or(0, |count| 2 + count as usize * 4);
+    let storage_offset = 6 + records.len() * 12 + lang_tag_size;
+    header.extend_from_slice(&(storage_offset as u16).to_be_bytes());
+
+    for record in records {
+        let offset = record.string_offset().to_u32() as usize;
+        let mut bytes = string(offset, record.length() as usize)?.to_vec();
+        if TAGGED_NAME_IDS.contains(&record.name_id().to_u16()) {
+            // Macintosh names are single-byte; the ASCII prefix is the same in
+            // every Mac encoding.
+            let prefix = if record.platform_id() == 1 { prefix.as_bytes() } else { &utf16_prefix };
+            bytes.splice(0..0, prefix.iter().copied());
+        }
+        let ids = [record.platform_id(), record.encoding_id(), record.language_id()];
+        for value in ids.into_iter().chain([record.name_id().to_u16()]) {
+            header.extend_from_slice(&value.to_be_bytes());
+        }
+        header.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        header.extend_from_slice(&(storage.len() as u16).to_be_bytes());
+        storage.extend_from_slice(&bytes);
+    }
+
+    if let Some(count) = name.lang_tag_count() {
+        header.extend_from_slice(&count.to_be_bytes());
+        for tag in lang_tags {
+            let bytes = string(tag.lang_tag_offset().to_u32() as usize, tag.length() as usize)?;
+            header.extend_from_slice(&tag.length().to_be_bytes());
+            header.extend_from_slice(&(storage.len() as u16).to_be_bytes());
+            storage.extend_from_slice(bytes);
+        }
+    }
+
+    header.extend_from_slice(&storage);
+    Some(header)
+}
+
+/// Long horizontal metrics for the renumbered glyphs.
+fn compact_hmtx(font: &FontRef, closure: &[u32]) -> Option<Vec<u8>> {
+    let hmtx = font.hmtx().ok()?;
+    let mut data = Vec::with_capacity(closure.len() * 4);
+    for &gid in closure {
+        let gid = GlyphId::new(gid);
+        data.extend_from_slice(&hmtx.advance(gid).unwrap_or(0).to_be_bytes());
+        data.extend_from_slice(&hmtx.side_bearing(gid).unwrap_or(0).to_be_bytes());
+    }
+    Some(data)
+}
+
+/// Rewrites the component glyph IDs of a composite glyph to their index in
+/// `closure`. Simple glyphs are left untouched.
+fn remap_components(glyph: &mut [u8], closure: &[u32]) {
+    if glyph.len() < 10 || i16::from_be_bytes([glyph[0], glyph[1]]) >= 0 {
+        return;
+    }
+
+    let mut offset = 10;
+    while offset + 4 <= glyph.len() {
+        let flags = u16::from_be_bytes([glyph[offset], glyph[offset + 1]]);
+        let old = u16::from_be_bytes([glyph[offset + 2], glyph[offset + 3]]) as u32;
+        let new = closure.binary_search(&old).unwrap_or(0) as u16;
+        glyph[offset + 2..offset + 4].copy_from_slice(&new.to_be_bytes());
+
+        offset += 4 + if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        offset += if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+}
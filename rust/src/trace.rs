@@ -0,0 +1,345 @@
+//! Where each shaped glyph came from, for debugging unexpected substitutions.
+//!
+//! harfrust does not report the lookups it applies, so the trace is
+//! reconstructed after shaping. A glyph that is not the nominal glyph of a
+//! character in its cluster is matched against the outputs of every GSUB
+//! substitution subtable. Subtables that take one of the cluster's nominal
+//! glyphs as input are preferred, then lookups that belong to a feature, then
+//! the last lookup in lookup order, as it is applied last. A lookup reached
+//! only from a contextual lookup is credited to that lookup's feature.
+
+use std::collections::{BTreeSet, HashMap};
+
+use skrifa::raw::tables::gsub::{Gsub, SingleSubst, SubstitutionSubtables};
+use skrifa::raw::tables::layout::{ChainedSequenceContext, CoverageTable, SequenceContext, SequenceLookupRecord};
+use skrifa::raw::types::Tag;
+use skrifa::raw::{ReadError, TableProvider};
+use skrifa::{FontRef, MetadataProvider};
+
+use crate::{glyph_text_ranges, utf16_chars, HarfRustGlyphInfo, HarfRustGlyphTrace, HarfRustSubstitutionKind, HarfRustTextRange};
+
+/// A subtable that can output a glyph.
+#[derive(Clone, Copy)]
+struct Producer {
+    lookup_index: u16,
+    kind: HarfRustSubstitutionKind,
+    alternate_index: Option<u16>,
+    /// The glyph the subtable starts matching at.
+    input: u32,
+}
+
+/// Traces each glyph of a shaping result whose clusters are UTF-16 offsets
+/// into `text`.
+pub(crate) fn trace(font: &FontRef, infos: &[HarfRustGlyphInfo], text: &[u16]) -> Vec<HarfRustGlyphTrace> {
+    let charmap = font.charmap();
+    // Every glyph of a cluster traces back to the cluster's whole text
+    let mut ranges: HashMap<u32, HarfRustTextRange> = HashMap::new();
+    for (info, range) in infos.iter().zip(glyph_text_ranges(infos, text)) {
+        ranges.entry(info.cluster).or_insert(range);
+    }
+    let sources: Vec<(HarfRustTextRange, Vec<u32>)> = infos
+        .iter()
+        .map(|info| {
+            let range = ranges[&info.cluster];
+            let chars = &text[range.start as usize..(range.start + range.length) as usize];
+            let nominal = utf16_chars(chars).map(|(ch, _)| charmap.map(ch).unwrap_or_default().to_u32()).collect();
+            (range, nominal)
+        })
+        .collect();
+
+    let wanted: Vec<u32> = infos
+        .iter()
+        .zip(&sources)
+        .filter(|(info, (_, nominal))| !nominal.contains(&info.glyph_id))
+        .map(|(info, _)| info.glyph_id)
+        .collect();
+    let gsub = font.gsub().ok();
+    let producers = gsub.as_ref().map(|gsub| producers(gsub, &wanted).unwrap_or_default()).unwrap_or_default();
+    let features = gsub.as_ref().map(lookup_features).unwrap_or_default();
+    let feature_of = |lookup: u16| features.get(lookup as usize).copied().unwrap_or(0);
+
+    infos
+        .iter()
+        .zip(sources)
+        .map(|(info, (range, nominal))| {
+            let mut trace = HarfRustGlyphTrace {
+                kind: HarfRustSubstitutionKind::None,
+                feature: 0,
+                lookup_index: -1,
+                alternate_index: -1,
+                input_start: range.start,
+                input_length: range.length,
+            };
+            if nominal.contains(&info.glyph_id) {
+                return trace;
+            }
+            let best = producers.get(&info.glyph_id).and_then(|candidates| {
+                candidates.iter().max_by_key(|p| {
+                    (nominal.contains(&p.input), feature_of(p.lookup_index) != 0, p.lookup_index)
+                })
+            });
+            match best {
+                Some(producer) => {
+                    trace.kind = producer.kind;
+                    trace.feature = feature_of(producer.lookup_index);
+                    trace.lookup_index = producer.lookup_index as i32;
+                    trace.alternate_index = producer.alternate_index.map_or(-1, i32::from);
+                }
+                None => trace.kind = HarfRustSubstitutionKind::Unknown,
+            }
+            trace
+        })
+        .collect()
+}
+
+/// Subtables outputting each of the `wanted` glyphs.
+fn producers(gsub: &Gsub, wanted: &[u32]) -> Result<HashMap<u32, Vec<Producer>>, ReadError> {
+    let mut found: HashMap<u32, Vec<Producer>> = HashMap::new();
+    if wanted.is_empty() {
+        return Ok(found);
+    }
+    let mut record = |output: u32, producer: Producer| {
+        if wanted.contains(&output) {
+            found.entry(output).or_default().push(producer);
+        }
+    };
+
+    for (lookup_index, lookup) in gsub.lookup_list()?.lookups().iter().enumerate() {
+        let lookup_index = lookup_index as u16;
+        let producer = |kind, input, alternate_index| Producer {
+            lookup_index,
+            kind,
+            alternate_index,
+            input,
+        };
+        match lookup?.subtables()? {
+            SubstitutionSubtables::Single(subtables) => {
+                for subtable in subtables.iter() {
+                    match subtable? {
+                        SingleSubst::Format1(table) => {
+                            let delta = table.delta_glyph_id() as i32;
+                            for glyph in table.coverage()?.iter() {
+                                let output = (glyph.to_u32() as i32 + delta).rem_euclid(0x10000) as u32;
+                                record(output, producer(HarfRustSubstitutionKind::Single, glyph.to_u32(), None));
+                            }
+                        }
+                        SingleSubst::Format2(table) => {
+                            let outputs = table.substitute_glyph_ids();
+                            for (glyph, output) in table.coverage()?.iter().zip(outputs) {
+                                record(output.get().to_u32(), producer(HarfRustSubstitutionKind::Single, glyph.to_u32(), None));
+                            }
+                        }
+                    }
+                }
+            }
+            SubstitutionSubtables::Multiple(subtables) => {
+                for subtable in subtables.iter() {
+                    let subtable = subtable?;
+                    for (glyph, sequence) in subtable.coverage()?.iter().zip(subtable.sequences().iter()) {
+                        for output in sequence?.substitute_glyph_ids() {
+                            record(output.get().to_u32(), producer(HarfRustSubstitutionKind::Multiple, glyph.to_u32(), None));
+                        }
+                    }
+                }
+            }
+            SubstitutionSubtables::Alternate(subtables) => {
+                for subtable in subtables.iter() {
+                    let subtable = subtable?;
+                    for (glyph, set) in subtable.coverage()?.iter().zip(subtable.alternate_sets().iter()) {
+                        for (index, output) in set?.alternate_glyph_ids().iter().enumerate() {
+                            let kind = HarfRustSubstitutionKind::Alternate;
+                            record(output.get().to_u32(), producer(kind, glyph.to_u32(), Some(index as u16)));
+                        }
+                    }
+                }
+            }
+            SubstitutionSubtables::Ligature(subtables) => {
+                for subtable in subtables.iter() {
+                    let subtable = subtable?;
+                    for (glyph, set) in subtable.coverage()?.iter().zip(subtable.ligature_sets().iter()) {
+                        for ligature in set?.ligatures().iter() {
+                            let output = ligature?.ligature_glyph().to_u32();
+                            record(output, producer(HarfRustSubstitutionKind::Ligature, glyph.to_u32(), None));
+                        }
+                    }
+                }
+            }
+            SubstitutionSubtables::Reverse(subtables) => {
+                for subtable in subtables.iter() {
+                    let subtable = subtable?;
+                    let outputs = subtable.substitute_glyph_ids();
+                    for (glyph, output) in subtable.coverage()?.iter().zip(outputs) {
+                        let kind = HarfRustSubstitutionKind::ReverseChainSingle;
+                        record(output.get().to_u32(), producer(kind, glyph.to_u32(), None));
+                    }
+                }
+            }
+            // Contextual lookups substitute through the lookups they nest
+            SubstitutionSubtables::Contextual(_) | SubstitutionSubtables::ChainContextual(_) => {}
+        }
+    }
+    Ok(found)
+}
+
+/// Tag of the first feature using each lookup, directly or through a
+/// contextual lookup, or 0.
+fn lookup_features(gsub: &Gsub) -> Vec<u32> {
+    let (Ok(feature_list), Ok(lookup_list)) = (gsub.feature_list(), gsub.lookup_list()) else {
+        return Vec::new();
+    };
+    let mut features = vec![0u32; lookup_list.lookup_count() as usize];
+    for record in feature_list.feature_records() {
+        let Ok(feature) = record.feature(feature_list.offset_data()) else {
+            continue;
+        };
+        for index in feature.lookup_list_indices() {
+            if let Some(slot) = features.get_mut(index.get() as usize) {
+                if *slot == 0 {
+                    *slot = u32::from_be_bytes(record.feature_tag().to_be_bytes());
+                }
+            }
+        }
+    }
+
+    let nested: Vec<Vec<u16>> = lookup_list
+        .lookups()
+        .iter()
+        .map(|lookup| lookup.ok().and_then(|lookup| nested_lookups(&lookup.subtables().ok()?).ok()).unwrap_or_default())
+        .collect();
+    // Each pass credits one more level of nesting
+    loop {
+        let mut changed = false;
+        for (index, nested) in nested.iter().enumerate() {
+            let feature = features[index];
+            if feature == 0 {
+                continue;
+            }
+            for &inner in nested {
+                if let Some(slot) = features.get_mut(inner as usize).filter(|slot| **slot == 0) {
+                    *slot = feature;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return features;
+        }
+    }
+}
+
+/// Glyphs the lookups of a GSUB feature can substitute: the coverage of
+/// each lookup's subtables, following contextual lookups into the lookups
+/// they apply. A glyph outside the set is never changed by the feature.
+pub(crate) fn feature_coverage(gsub: &Gsub, feature: Tag) -> BTreeSet<u32> {
+    let mut glyphs = BTreeSet::new();
+    let (Ok(feature_list), Ok(lookup_list)) = (gsub.feature_list(), gsub.lookup_list()) else {
+        return glyphs;
+    };
+    let mut pending: Vec<u16> = Vec::new();
+    for record in feature_list.feature_records().iter().filter(|record| record.feature_tag() == feature) {
+        if let Ok(table) = record.feature(feature_list.offset_data()) {
+            pending.extend(table.lookup_list_indices().iter().map(|index| index.get()));
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    while let Some(index) = pending.pop() {
+        if !seen.insert(index) {
+            continue;
+        }
+        let Ok(subtables) = lookup_list.lookups().get(index as usize).and_then(|lookup| lookup.subtables()) else {
+            continue;
+        };
+        let _ = subtable_coverage(&subtables, &mut glyphs);
+        pending.extend(nested_lookups(&subtables).unwrap_or_default());
+    }
+    glyphs
+}
+
+/// Adds the glyphs covered by non-contextual subtables to `glyphs`.
+fn subtable_coverage(subtables: &SubstitutionSubtables, glyphs: &mut BTreeSet<u32>) -> Result<(), ReadError> {
+    let mut add = |coverage: CoverageTable| glyphs.extend(coverage.iter().map(|glyph| glyph.to_u32()));
+    match subtables {
+        SubstitutionSubtables::Single(subtables) => {
+            for subtable in subtables.iter() {
+                match subtable? {
+                    SingleSubst::Format1(table) => add(table.coverage()?),
+                    SingleSubst::Format2(table) => add(table.coverage()?),
+                }
+            }
+        }
+        SubstitutionSubtables::Multiple(subtables) => {
+            for subtable in subtables.iter() {
+                add(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Alternate(subtables) => {
+            for subtable in subtables.iter() {
+                add(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Ligature(subtables) => {
+            for subtable in subtables.iter() {
+                add(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Reverse(subtables) => {
+            for subtable in subtables.iter() {
+                add(subtable?.coverage()?);
+            }
+        }
+        SubstitutionSubtables::Contextual(_) | SubstitutionSubtables::ChainContextual(_) => {}
+    }
+    Ok(())
+}
+
+/// Lookups a contextual lookup applies.
+fn nested_lookups(subtables: &SubstitutionSubtables) -> Result<Vec<u16>, ReadError> {
+    let mut records: Vec<SequenceLookupRecord> = Vec::new();
+    match subtables {
+        SubstitutionSubtables::Contextual(subtables) => {
+            for subtable in subtables.iter() {
+                match subtable? {
+                    SequenceContext::Format1(table) => {
+                        for set in table.seq_rule_sets().iter().flatten() {
+                            for rule in set?.seq_rules().iter() {
+                                records.extend_from_slice(rule?.seq_lookup_records());
+                            }
+                        }
+                    }
+                    SequenceContext::Format2(table) => {
+                        for set in table.class_seq_rule_sets().iter().flatten() {
+                            for rule in set?.class_seq_rules().iter() {
+                                records.extend_from_slice(rule?.seq_lookup_records());
+                            }
+                        }
+                    }
+                    SequenceContext::Format3(table) => records.extend_from_slice(table.seq_lookup_records()),
+                }
+            }
+        }
+        SubstitutionSubtables::ChainContextual(subtables) => {
+            for subtable in subtables.iter() {
+                match subtable? {
+                    ChainedSequenceContext::Format1(table) => {
+                        for set in table.chained_seq_rule_sets().iter().flatten() {
+                            for rule in set?.chained_seq_rules().iter() {
+                                records.extend_from_slice(rule?.seq_lookup_records());
+                            }
+                        }
+                    }
+                    ChainedSequenceContext::Format2(table) => {
+                        for set in table.chained_class_seq_rule_sets().iter().flatten() {
+                            for rule in set?.chained_class_seq_rules().iter() {
+                                records.extend_from_slice(rule?.seq_lookup_records());
+                            }
+                        }
+                    }
+                    ChainedSequenceContext::Format3(table) => records.extend_from_slice(table.seq_lookup_records()),
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(records.iter().map(|record| record.lookup_list_index()).collect())
+}
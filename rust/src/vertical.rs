@@ -0,0 +1,126 @@
+//! Vertical writing-mode metrics for vertical CID fonts (PDF W2 entries),
+//! and the orientation of characters in vertical lines.
+//!
+//! Values come from `vmtx` and `VORG` when present, with the fallbacks
+//! HarfBuzz uses for fonts without vertical tables: an advance of the line
+//! height and an origin at the ascender, centered horizontally.
+
+use skrifa::instance::{LocationRef, Size};
+use skrifa::metrics::GlyphMetrics;
+use skrifa::raw::tables::{vmtx::Vmtx, vorg::Vorg};
+use skrifa::raw::TableProvider;
+use skrifa::{FontRef, GlyphId, MetadataProvider};
+
+/// Vertical metrics of one glyph in font units.
+pub(crate) struct GlyphVerticalMetrics {
+    /// Vertical advance, positive downwards.
+    pub advance: f32,
+    /// Vertical origin relative to the horizontal origin.
+    pub origin_x: f32,
+    pub origin_y: f32,
+}
+
+/// Vertical metrics source for a font at its default location.
+pub(crate) struct VerticalMetrics<'a> {
+    vmtx: Option<Vmtx<'a>>,
+    vorg: Option<Vorg<'a>>,
+    glyph_metrics: GlyphMetrics<'a>,
+    ascent: f32,
+    descent: f32,
+}
+
+impl<'a> VerticalMetrics<'a> {
+    pub(crate) fn new(font: &FontRef<'a>) -> Self {
+        let metrics = font.metrics(Size::unscaled(), LocationRef::default());
+        Self {
+            vmtx: font.vmtx().ok(),
+            vorg: font.vorg().ok(),
+            glyph_metrics: font.glyph_metrics(Size::unscaled(), LocationRef::default()),
+            ascent: metrics.ascent,
+            descent: metrics.descent,
+        }
+    }
+
+    pub(crate) fn get(&self, glyph_id: GlyphId) -> GlyphVerticalMetrics {
+        let vmtx = self.vmtx.as_ref();
+        let advance = vmtx
+            .and_then(|vmtx| vmtx.advance(glyph_id))
+            .map_or(self.ascent - self.descent, f32::from);
+
+        // The top side bearing is measured from the glyph's top edge.
+        let top_side_bearing = || {
+            let side_bearing = vmtx?.side_bearing(glyph_id)? as f32;
+            let y_max = self.glyph_metrics.bounds(glyph_id).map_or(0.0, |b| b.y_max);
+            Some(side_bearing + y_max)
+        };
+        let origin_y = match &self.vorg {
+            Some(vorg) => vorg.vertical_origin_y(glyph_id) as f32,
+            None => top_side_bearing().unwrap_or(self.ascent),
+        };
+
+        let h_advance = self.glyph_metrics.advance_width(glyph_id).unwrap_or(0.0);
+        GlyphVerticalMetrics {
+            advance,
+            origin_x: h_advance / 2.0,
+            origin_y,
+        }
+    }
+}
+
+/// Ranges of characters set upright in vertical text: those with the UAX #50
+/// Vertical_Orientation U, Tu or Tr. Tu and Tr characters are transformed by
+/// the font's `vert` feature rather than rotated. Everything else is set
+/// sideways.
+const UPRIGHT: &[(u32, u32)] = &[
+    (0x00A7, 0x00A7), (0x00A9, 0x00A9), (0x00AE, 0x00AE), (0x00B1, 0x00B1),
+    (0x00BC, 0x00BE), (0x00D7, 0x00D7), (0x00F7, 0x00F7), (0x02EA, 0x02EB),
+    (0x1100, 0x11FF), (0x1401, 0x167F), (0x18B0, 0x18FF), (0x2016, 0x2016),
+    (0x2020, 0x2021), (0x2030, 0x2031), (0x203B, 0x203C), (0x2042, 0x2042),
+    (0x2047, 0x2049), (0x2051, 0x2051), (0x2065, 0x2065), (0x20DD, 0x20E0),
+    (0x20E2, 0x20E4), (0x2100, 0x2101), (0x2103, 0x2109), (0x210F, 0x210F),
+    (0x2113, 0x2114), (0x2116, 0x2117), (0x211E, 0x2123), (0x2125, 0x2125),
+    (0x2127, 0x2127), (0x2129, 0x2129), (0x212E, 0x212E), (0x2135, 0x213F),
+    (0x2145, 0x214A), (0x214C, 0x214D), (0x214F, 0x2189), (0x218C, 0x218F),
+    (0x221E, 0x221E), (0x2234, 0x2235), (0x2300, 0x2307), (0x230C, 0x231F),
+    (0x2324, 0x2328), (0x232B, 0x232B), (0x237D, 0x239A), (0x23BE, 0x23CD),
+    (0x23CF, 0x23CF), (0x23D1, 0x23DB), (0x23E2, 0x2422), (0x2424, 0x24FF),
+    (0x25A0, 0x2619), (0x2620, 0x2767), (0x2776, 0x2793), (0x2B12, 0x2B2F),
+    (0x2B50, 0x2B59), (0x2BB8, 0x2BFF), (0x2E50, 0x2E51), (0x2E80, 0xA4CF),
+    (0xA960, 0xA97F), (0xAC00, 0xD7FF), (0xE000, 0xFAFF), (0xFE10, 0xFE1F),
+    (0xFE30, 0xFE6F), (0xFF00, 0xFFEF), (0x13000, 0x1345F), (0x14400, 0x1467F),
+    (0x16FE0, 0x18DFF), (0x1AFF0, 0x1B2FF), (0x1D300, 0x1D35F), (0x1F000, 0x1FAFF),
+    (0x20000, 0x3FFFD), (0xF0000, 0x10FFFD),
+];
+
+/// Whether `ch` stays upright in a vertical line; see [`UPRIGHT`].
+pub(crate) fn is_upright(ch: char) -> bool {
+    let code = ch as u32;
+    UPRIGHT
+        .binary_search_by(|&(start, end)| {
+            if end < code {
+                std::cmp::Ordering::Less
+            } else if start > code {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Whether `ch` takes the orientation of the character before it: combining
+/// marks, joiners and variation selectors.
+pub(crate) fn follows_base(ch: char) -> bool {
+    matches!(
+        ch as u32,
+        0x0300..=0x036F
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200C..=0x200D
+            | 0x20D0..=0x20FF
+            | 0x3099..=0x309A
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0xE0100..=0xE01EF
+    )
+}
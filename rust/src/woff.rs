@@ -0,0 +1,57 @@
+//! WOFF 1.0 decoding.
+//!
+//! WOFF wraps the tables of an sfnt font, each optionally zlib-compressed.
+//! Decoding rebuilds the original font file. WOFF2 (Brotli and table
+//! transforms) is not supported.
+
+use skrifa::raw::types::Tag;
+
+use crate::sfnt;
+
+const SIGNATURE: &[u8; 4] = b"wOFF";
+const HEADER_SIZE: usize = 44;
+const TABLE_ENTRY_SIZE: usize = 20;
+
+pub(crate) fn is_woff(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+/// Decodes a WOFF file into the sfnt font it wraps. Returns `None` if the
+/// data is malformed or a table fails to decompress.
+pub(crate) fn decode(data: &[u8]) -> Option<Vec<u8>> {
+    if !is_woff(data) || data.len() < HEADER_SIZE {
+        return None;
+    }
+    let flavor = read_u32(data, 4)?;
+    let num_tables = read_u16(data, 12)? as usize;
+
+    let mut tables = Vec::with_capacity(num_tables);
+    for index in 0..num_tables {
+        let entry = HEADER_SIZE + index * TABLE_ENTRY_SIZE;
+        let tag = Tag::from_u32(read_u32(data, entry)?);
+        let offset = read_u32(data, entry + 4)? as usize;
+        let comp_length = read_u32(data, entry + 8)? as usize;
+        let orig_length = read_u32(data, entry + 12)? as usize;
+        let stored = data.get(offset..offset.checked_add(comp_length)?)?;
+
+        let table = if comp_length < orig_length {
+            miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(stored, orig_length).ok()?
+        } else {
+            stored.to_vec()
+        };
+        if table.len() != orig_length {
+            return None;
+        }
+        tables.push((tag, table));
+    }
+
+    Some(sfnt::build_sfnt(flavor, tables))
+}